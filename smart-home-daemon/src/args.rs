@@ -0,0 +1,123 @@
+//! Аргументы командной строки демона
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Период опроса устройств по умолчанию
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+pub const USAGE: &str = "Использование: smart-home-daemon --config <house.json> \
+[--pid-file <path>] [--state-file <path>] [--interval <секунды>] [--http <адрес:порт>]";
+
+/// Параметры запуска демона
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaemonArgs {
+    /// Файл конфигурации дома
+    pub config: PathBuf,
+    /// Файл с PID процесса
+    pub pid_file: Option<PathBuf>,
+    /// Файл с последним состоянием дома
+    pub state_file: Option<PathBuf>,
+    /// Период опроса устройств
+    pub interval: Duration,
+    /// Адрес HTTP API (без него API выключен)
+    pub http: Option<SocketAddr>,
+}
+
+impl DaemonArgs {
+    /// Разбирает аргументы (без имени программы)
+    pub fn parse<I>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut config = None;
+        let mut pid_file = None;
+        let mut state_file = None;
+        let mut interval = DEFAULT_INTERVAL;
+        let mut http = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Не указано значение для {}", arg))
+            };
+            match arg.as_str() {
+                "--config" => config = Some(PathBuf::from(value()?)),
+                "--pid-file" => pid_file = Some(PathBuf::from(value()?)),
+                "--state-file" => state_file = Some(PathBuf::from(value()?)),
+                "--interval" => {
+                    let raw = value()?;
+                    let secs: u64 = raw
+                        .parse()
+                        .ok()
+                        .filter(|secs| *secs > 0)
+                        .ok_or_else(|| format!("Некорректный интервал: '{}'", raw))?;
+                    interval = Duration::from_secs(secs);
+                }
+                "--http" => {
+                    let raw = value()?;
+                    let addr = raw
+                        .parse()
+                        .map_err(|_| format!("Некорректный адрес HTTP API: '{}'", raw))?;
+                    http = Some(addr);
+                }
+                other => return Err(format!("Неизвестный аргумент: '{}'", other)),
+            }
+        }
+
+        Ok(Self {
+            config: config.ok_or_else(|| "Не указан --config".to_string())?,
+            pid_file,
+            state_file,
+            interval,
+            http,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<DaemonArgs, String> {
+        DaemonArgs::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn full_and_defaults() {
+        let args = parse(&[
+            "--config",
+            "house.json",
+            "--pid-file",
+            "/run/smart-home.pid",
+            "--state-file",
+            "state.json",
+            "--interval",
+            "5",
+            "--http",
+            "127.0.0.1:8080",
+        ])
+        .unwrap();
+        assert_eq!(args.config, PathBuf::from("house.json"));
+        assert_eq!(args.pid_file, Some(PathBuf::from("/run/smart-home.pid")));
+        assert_eq!(args.state_file, Some(PathBuf::from("state.json")));
+        assert_eq!(args.interval, Duration::from_secs(5));
+        assert_eq!(args.http, Some("127.0.0.1:8080".parse().unwrap()));
+
+        let args = parse(&["--config", "house.json"]).unwrap();
+        assert_eq!(args.pid_file, None);
+        assert_eq!(args.interval, DEFAULT_INTERVAL);
+        assert_eq!(args.http, None);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["--config"]).is_err());
+        assert!(parse(&["--config", "a.json", "--interval", "0"]).is_err());
+        assert!(parse(&["--config", "a.json", "--verbose"]).is_err());
+        assert!(parse(&["--config", "a.json", "--http", "localhost"]).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! HTTP API демона
+//!
+//! `GET /status` отдает снимок состояния (как в файле состояния),
+//! `POST /command` с телом `{"turn_on": "hall/lamp"}` выполняет действие
+//! правила. Соединения обслуживаются отдельными задачами, но сами запросы
+//! передаются в основной цикл (`ApiCall`): дом принадлежит циклу, и
+//! команды не пересекаются с опросом и перезагрузкой.
+
+use serde::Serialize;
+use smart_home_lib::rules::RuleAction;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+
+/// Максимальный размер строки запроса или заголовка
+const MAX_LINE: usize = 8 * 1024;
+/// Максимальный размер тела запроса
+const MAX_BODY: usize = 64 * 1024;
+
+/// Запрос к дому
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiRequest {
+    /// Состояние демона
+    Status,
+    /// Команда устройству
+    Command(RuleAction),
+}
+
+/// Запрос и канал для ответа основного цикла
+#[derive(Debug)]
+pub struct ApiCall {
+    pub request: ApiRequest,
+    pub reply: oneshot::Sender<ApiResponse>,
+}
+
+/// Ответ: код статуса и JSON тело
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl ApiResponse {
+    /// Успешный ответ
+    pub fn ok(body: &impl Serialize) -> Self {
+        match serde_json::to_string_pretty(body) {
+            Ok(body) => Self { status: 200, body },
+            Err(e) => Self::error(500, e),
+        }
+    }
+
+    /// Ответ с ошибкой: `{"error": "..."}`
+    pub fn error(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message.to_string() }).to_string(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Принимает соединения, пока работает основной цикл
+pub async fn serve(listener: TcpListener, calls: mpsc::Sender<ApiCall>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(handle(stream, calls.clone()));
+            }
+            Err(e) => eprintln!("⚠️ HTTP API: {}", e),
+        }
+    }
+}
+
+/// Обслуживает одно соединение: один запрос и ответ
+async fn handle(stream: TcpStream, calls: mpsc::Sender<ApiCall>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let response = match read_request(&mut reader).await {
+        Ok(request) => dispatch(request, &calls).await,
+        Err(response) => response,
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.body.len()
+    );
+    let _ = writer.write_all(head.as_bytes()).await;
+    let _ = writer.write_all(response.body.as_bytes()).await;
+    let _ = writer.shutdown().await;
+}
+
+async fn dispatch(request: ApiRequest, calls: &mpsc::Sender<ApiCall>) -> ApiResponse {
+    let (reply, response) = oneshot::channel();
+    if calls.send(ApiCall { request, reply }).await.is_err() {
+        return ApiResponse::error(503, "демон останавливается");
+    }
+    response
+        .await
+        .unwrap_or_else(|_| ApiResponse::error(503, "демон останавливается"))
+}
+
+/// Читает строку запроса, заголовки и тело
+async fn read_request<R>(reader: &mut R) -> Result<ApiRequest, ApiResponse>
+where
+    R: AsyncBufReadExt + Unpin,
+{
+    let request_line = read_line(reader).await?;
+    let mut content_length = 0;
+    loop {
+        let line = read_line(reader).await?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value
+                .trim()
+                .parse()
+                .map_err(|_| ApiResponse::error(400, "некорректный Content-Length"))?;
+        }
+    }
+    if content_length > MAX_BODY {
+        return Err(ApiResponse::error(413, "слишком большое тело запроса"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .await
+        .map_err(|e| ApiResponse::error(400, e))?;
+    parse_request(&request_line, &body)
+}
+
+async fn read_line<R>(reader: &mut R) -> Result<String, ApiResponse>
+where
+    R: AsyncBufReadExt + Unpin,
+{
+    let mut line = Vec::new();
+    reader
+        .take(MAX_LINE as u64)
+        .read_until(b'\n', &mut line)
+        .await
+        .map_err(|e| ApiResponse::error(400, e))?;
+    if !line.ends_with(b"\n") {
+        return Err(ApiResponse::error(400, "незавершенный заголовок"));
+    }
+    String::from_utf8(line)
+        .map(|line| line.trim_end().to_string())
+        .map_err(|_| ApiResponse::error(400, "заголовок не в UTF-8"))
+}
+
+/// Разбирает запрос по строке запроса и телу
+fn parse_request(request_line: &str, body: &[u8]) -> Result<ApiRequest, ApiResponse> {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(ApiResponse::error(400, "некорректная строка запроса"));
+    };
+
+    match (method, path) {
+        ("GET", "/status") => Ok(ApiRequest::Status),
+        ("POST", "/command") => serde_json::from_slice(body)
+            .map(ApiRequest::Command)
+            .map_err(|e| ApiResponse::error(400, e)),
+        (_, "/status" | "/command") => Err(ApiResponse::error(405, method)),
+        _ => Err(ApiResponse::error(404, path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_request("GET /status HTTP/1.1", b""),
+            Ok(ApiRequest::Status)
+        );
+        assert_eq!(
+            parse_request("POST /command HTTP/1.1", br#"{"turn_off": "hall/lamp"}"#),
+            Ok(ApiRequest::Command(RuleAction::TurnOff(
+                "hall/lamp".parse().unwrap()
+            )))
+        );
+
+        let status = |line: &str, body: &[u8]| parse_request(line, body).unwrap_err().status;
+        assert_eq!(status("POST /command HTTP/1.1", b"{}"), 400);
+        assert_eq!(status("GET /command HTTP/1.1", b""), 405);
+        assert_eq!(status("GET /devices HTTP/1.1", b""), 404);
+        assert_eq!(status("", b""), 400);
+    }
+
+    async fn request(addr: std::net::SocketAddr, raw: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(raw.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn serves_calls() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (calls, mut received) = mpsc::channel(4);
+        tokio::spawn(serve(listener, calls));
+        // Основной цикл: отвечает командой, которую получил
+        tokio::spawn(async move {
+            while let Some(call) = received.recv().await {
+                let ApiCall { request, reply } = call;
+                let _ = reply.send(ApiResponse::ok(&format!("{:?}", request)));
+            }
+        });
+
+        let body = r#"{"turn_on": "hall/lamp"}"#;
+        let response = request(
+            addr,
+            &format!(
+                "POST /command HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("TurnOn"), "{}", response);
+
+        let response = request(addr, "GET /nope HTTP/1.1\r\n\r\n").await;
+        assert!(
+            response.starts_with("HTTP/1.1 404 Not Found\r\n"),
+            "{}",
+            response
+        );
+        assert!(response.ends_with(r#"{"error":"/nope"}"#), "{}", response);
+    }
+}
@@ -0,0 +1,506 @@
+//! Демон умного дома
+//!
+//! Загружает конфигурацию дома, запускает контроллеры и периодически
+//! опрашивает устройства, выполняя расписание и правила из конфигурации и
+//! поддерживая желаемые состояния розеток. С `--http` поднимает HTTP API
+//! (см. `http`). SIGHUP перечитывает конфигурацию, SIGTERM и Ctrl+C
+//! завершают работу с удалением PID файла.
+
+mod args;
+mod http;
+mod state;
+
+use args::{DaemonArgs, USAGE};
+use http::{ApiCall, ApiRequest, ApiResponse};
+use smart_home_lib::config::HouseConfig;
+use smart_home_lib::controllers::DeviceController;
+use smart_home_lib::energy::BudgetManager;
+use smart_home_lib::house::{SmartHouse, SmartHouseError};
+use smart_home_lib::protocol::now_ms;
+use smart_home_lib::reconciler::{DesiredState, Reconciler};
+use smart_home_lib::rules::{RuleAction, RuleError, RuleSet, Schedule};
+use smart_home_lib::units::Watts;
+use state::{BudgetSnapshot, DaemonState, PidFile};
+use std::process::ExitCode;
+use tokio::net::TcpListener;
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::mpsc;
+use tokio::time::{MissedTickBehavior, interval};
+
+/// Загруженный дом и связанные с ним службы
+struct Runtime {
+    house: SmartHouse,
+    budget: Option<BudgetManager>,
+    reconciler: Option<Reconciler>,
+    rules: RuleSet,
+    schedule: Schedule,
+}
+
+impl Runtime {
+    /// Собирает дом по конфигурации; контроллеры еще не запущены
+    fn new(config: &HouseConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            house: config.build()?,
+            budget: config.budget_manager(),
+            reconciler: config.reconciler(),
+            rules: config.rule_set(),
+            schedule: config.schedule(),
+        })
+    }
+
+    /// Заменяет дом домом из новой конфигурации
+    ///
+    /// Новый дом собирается без запуска контроллеров, затем старые
+    /// контроллеры останавливаются, освобождая UDP порты, и запускаются
+    /// новые. Недоступные розетки, как и при старте, только предупреждение;
+    /// если термометру не удалось занять порт, перезагрузка отменяется и
+    /// прежний дом запускается снова.
+    async fn reload(&mut self, config: &HouseConfig) -> Result<(), Box<dyn std::error::Error>> {
+        let mut next = Self::new(config)?;
+
+        if let Err(e) = self.house.stop_controllers().await {
+            eprintln!("⚠️ {}", e);
+        }
+        let (fatal, unreachable): (Vec<_>, Vec<_>) = next
+            .house
+            .start_all()
+            .await
+            .into_iter()
+            .partition(|failure| {
+                matches!(
+                    next.house.controller(&failure.room, &failure.key),
+                    Ok(DeviceController::Therm(_))
+                )
+            });
+        if !fatal.is_empty() {
+            let _ = next.house.stop_controllers().await;
+            drop(next);
+            if let Err(e) = self.house.start_controllers().await {
+                eprintln!("⚠️ {}", e);
+            }
+            return Err(SmartHouseError::Controllers(fatal).into());
+        }
+        if !unreachable.is_empty() {
+            eprintln!("⚠️ {}", SmartHouseError::Controllers(unreachable));
+        }
+
+        *self = next;
+        Ok(())
+    }
+
+    /// Опрашивает устройства, применяет энергобюджет, выполняет расписание
+    /// и правила и исправляет расхождения
+    async fn poll(&mut self) -> (Option<BudgetSnapshot>, Vec<String>) {
+        let (snapshot, mut errors) = self.enforce_budget().await;
+        errors.extend(self.run_schedule().await);
+        errors.extend(self.run_rules().await);
+        errors.extend(self.reconcile().await);
+        (snapshot, errors)
+    }
+
+    /// Выполняет команду HTTP API
+    ///
+    /// Если сверка следит за устройством, желаемое состояние меняется
+    /// вместе с командой, иначе следующий опрос вернул бы прежнее.
+    async fn command(&mut self, action: &RuleAction) -> Result<(), RuleError> {
+        action.execute(&mut self.house).await?;
+        let path = action.path();
+        if let Some(reconciler) = &mut self.reconciler
+            && reconciler.desired(path.room(), path.key()).is_some()
+        {
+            let state = DesiredState::from_active(matches!(action, RuleAction::TurnOn(_)));
+            reconciler.set_desired(path.room(), path.key(), state);
+        }
+        Ok(())
+    }
+
+    async fn run_schedule(&mut self) -> Vec<String> {
+        let report = self.schedule.run(&mut self.house).await;
+        for name in &report.fired {
+            println!("⏰ Выполнено расписание {}", name);
+        }
+        report
+            .errors
+            .into_iter()
+            .map(|(name, e)| format!("расписание {}: {}", name, e))
+            .collect()
+    }
+
+    async fn run_rules(&mut self) -> Vec<String> {
+        let report = self.rules.run(&mut self.house).await;
+        for name in &report.fired {
+            println!("📜 Сработало правило {}", name);
+        }
+        report
+            .errors
+            .into_iter()
+            .map(|(name, e)| format!("правило {}: {}", name, e))
+            .collect()
+    }
+
+    async fn enforce_budget(&mut self) -> (Option<BudgetSnapshot>, Vec<String>) {
+        let Some(budget) = &self.budget else {
+            // Без бюджета только обновляем состояние розеток (опрос не зависит от лимита)
+            let errors = BudgetManager::new(Watts::new(f64::INFINITY))
+                .refresh(&mut self.house)
+                .await;
+            return (None, format_errors(errors));
+        };
+
+        let report = budget.enforce(&mut self.house).await;
+        for consumer in &report.shed {
+            println!(
+                "⚡ Отключена нагрузка {}/{} ({})",
+                consumer.room, consumer.key, consumer.power
+            );
+            // Сверка не должна включать обратно отключенную бюджетом нагрузку
+            if let Some(reconciler) = &mut self.reconciler {
+                reconciler.set_desired(&consumer.room, &consumer.key, DesiredState::Off);
+            }
+        }
+        (Some((&report.usage).into()), format_errors(report.errors))
+    }
+
+    async fn reconcile(&mut self) -> Vec<String> {
+        let Some(reconciler) = &self.reconciler else {
+            return Vec::new();
+        };
+
+        let report = reconciler.reconcile(&mut self.house).await;
+        for drift in report.drifts.iter().filter(|drift| drift.corrected) {
+            println!(
+                "🔁 Восстановлено состояние {}/{}: {}",
+                drift.room, drift.key, drift.desired
+            );
+        }
+        let mut errors: Vec<String> = report.errors.iter().map(ToString::to_string).collect();
+        errors.extend(
+            report
+                .missing
+                .into_iter()
+                .map(|(room, key)| format!("{}/{}: розетка не найдена", room, key)),
+        );
+        errors
+    }
+}
+
+fn format_errors<E: std::fmt::Display>(errors: Vec<(String, String, E)>) -> Vec<String> {
+    errors
+        .into_iter()
+        .map(|(room, key, e)| format!("{}/{}: {}", room, key, e))
+        .collect()
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match DaemonArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("❌ {}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: DaemonArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = HouseConfig::load(&args.config)?;
+    let _pid_file = args.pid_file.as_deref().map(PidFile::create).transpose()?;
+
+    let mut runtime = Runtime::new(&config)?;
+    // Недоступные при запуске устройства опрашиваются дальше
+    if let Err(e) = runtime.house.start_controllers().await {
+        eprintln!("⚠️ {}", e);
+    }
+    println!(
+        "🏠 Демон запущен (PID {}): комнат {}, конфигурация {}",
+        std::process::id(),
+        runtime.house.rooms_count(),
+        args.config.display()
+    );
+
+    // Отправитель живет до конца цикла, поэтому без HTTP API канал просто пуст
+    let (api_calls, mut api_requests) = mpsc::channel::<ApiCall>(16);
+    if let Some(addr) = args.http {
+        let listener = TcpListener::bind(addr).await?;
+        println!("🌐 HTTP API: http://{}", listener.local_addr()?);
+        tokio::spawn(http::serve(listener, api_calls.clone()));
+    }
+
+    let started_ms = now_ms();
+    let mut reloads = 0;
+    let mut last_budget = None;
+    let mut last_errors = Vec::new();
+    let mut ticker = interval(args.interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut sighup = signal(SignalKind::hangup())?;
+    let mut sigterm = signal(SignalKind::terminate())?;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let (budget, errors) = runtime.poll().await;
+                for error in &errors {
+                    eprintln!("⚠️ {}", error);
+                }
+                if let Some(path) = &args.state_file {
+                    let state = DaemonState::capture(
+                        &runtime.house,
+                        started_ms,
+                        reloads,
+                        budget.clone(),
+                        errors.clone(),
+                    );
+                    if let Err(e) = state.write(path) {
+                        eprintln!("⚠️ Не удалось записать состояние {}: {}", path.display(), e);
+                    }
+                }
+                last_budget = budget;
+                last_errors = errors;
+            }
+            Some(ApiCall { request, reply }) = api_requests.recv() => {
+                let response = match request {
+                    ApiRequest::Status => ApiResponse::ok(&DaemonState::capture(
+                        &runtime.house,
+                        started_ms,
+                        reloads,
+                        last_budget.clone(),
+                        last_errors.clone(),
+                    )),
+                    ApiRequest::Command(action) => match runtime.command(&action).await {
+                        Ok(()) => {
+                            println!("🌐 Выполнена команда: {}", action);
+                            ApiResponse::ok(&serde_json::json!({ "ok": true }))
+                        }
+                        Err(e) => {
+                            let status = match e {
+                                RuleError::UnknownDevice(_) => 404,
+                                RuleError::Command { .. } => 502,
+                                _ => 400,
+                            };
+                            ApiResponse::error(status, e)
+                        }
+                    },
+                };
+                let _ = reply.send(response);
+            }
+            _ = sighup.recv() => {
+                // Некорректная конфигурация не останавливает работающий дом
+                let reloaded = match HouseConfig::load(&args.config) {
+                    Ok(config) => runtime.reload(&config).await,
+                    Err(e) => Err(e.into()),
+                };
+                match reloaded {
+                    Ok(()) => {
+                        reloads += 1;
+                        ticker.reset_immediately();
+                        println!("🔄 Конфигурация перечитана: комнат {}", runtime.house.rooms_count());
+                    }
+                    Err(e) => eprintln!("⚠️ Перезагрузка отменена: {}", e),
+                }
+            }
+            _ = sigterm.recv() => break,
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    println!("⏹️ Демон остановлен");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smart_home_lib::clock::MockClock;
+    use smart_home_lib::config::GroupMemberConfig;
+    use smart_home_lib::devices::Device;
+    use smart_home_lib::rules::{Rule, ScheduleEntry};
+    use std::net::{TcpListener, UdpSocket};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn free_addr() -> String {
+        UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string()
+    }
+
+    fn is_bound(addr: &str) -> bool {
+        UdpSocket::bind(addr).is_err()
+    }
+
+    fn config(therm: &str, socket: &str) -> HouseConfig {
+        HouseConfig::from_json(&format!(
+            r#"{{"rooms": {{"hall": {{"devices": {{
+                "therm": {{"kind": "therm", "initial_temp": 21.0, "address": "{}"}},
+                "lamp": {{"kind": "socket", "power_rating": 60.0, "address": "{}",
+                          "controller": {{"timeout_ms": 200}}}}
+            }}}}}}}}"#,
+            therm, socket
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reload_with_unreachable_socket() {
+        // Адрес, который никто не слушает
+        let socket = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let therm = free_addr();
+        let config = config(&therm, &socket);
+
+        let mut runtime = Runtime::new(&config).unwrap();
+        assert!(!is_bound(&therm));
+        assert!(runtime.house.start_controllers().await.is_err());
+
+        // Термометр на том же порту занимает его заново после остановки старого
+        runtime.reload(&config).await.unwrap();
+        assert!(is_bound(&therm));
+    }
+
+    #[tokio::test]
+    async fn reload_rejected_on_therm_bind_error() {
+        let socket = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let therm = free_addr();
+        let mut runtime = Runtime::new(&config(&therm, &socket)).unwrap();
+        let _ = runtime.house.start_controllers().await;
+
+        let busy = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let error = runtime
+            .reload(&config(&busy.local_addr().unwrap().to_string(), &socket))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("hall"));
+
+        // Прежний дом снова запущен
+        assert!(is_bound(&therm));
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let config = HouseConfig::from_json(include_str!("../house.example.json")).unwrap();
+        assert_eq!(config.rooms.len(), 2);
+        assert!(config.budget_manager().is_some());
+        assert_eq!(config.schedule().entries().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_reload_keeps_running_house() {
+        let config = HouseConfig::from_json(
+            r#"{"rooms": {"hall": {"devices": {"therm": {
+                "kind": "therm", "initial_temp": 20.0, "address": "127.0.0.1:0"
+            }}}}}"#,
+        )
+        .unwrap();
+        let mut runtime = Runtime::new(&config).unwrap();
+
+        // Конфигурация с ошибкой: дом не пересобирается
+        let mut invalid = config.clone();
+        invalid.rooms.get_mut("hall").unwrap().devices.clear();
+        invalid.groups.insert(
+            "all".to_string(),
+            vec![GroupMemberConfig {
+                room: "hall".to_string(),
+                device: "therm".to_string(),
+            }],
+        );
+        assert!(runtime.reload(&invalid).await.is_err());
+        assert!(runtime.house.controller("hall", "therm").is_ok());
+
+        runtime.reload(&HouseConfig::default()).await.unwrap();
+        assert_eq!(runtime.house.rooms_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn poll_runs_config_rules() {
+        let mut runtime = Runtime::new(&HouseConfig::default()).unwrap();
+        runtime.house = SmartHouse::builder()
+            .room("hall", |r| r.therm("therm", 15.0).socket("heater", 1500.0))
+            .build();
+        runtime.rules = RuleSet::new().with_rule(
+            Rule::new("heat", "hall/therm.temperature < 18".parse().unwrap())
+                .with_action(RuleAction::TurnOn("hall/heater".parse().unwrap())),
+        );
+
+        let (_, errors) = runtime.poll().await;
+        assert!(errors.is_empty(), "{:?}", errors);
+        let Ok(Device::Socket(heater)) = runtime.house.device("hall", "heater") else {
+            panic!("Expected heater socket");
+        };
+        assert!(heater.is_active());
+    }
+
+    fn is_active(runtime: &Runtime, key: &str) -> bool {
+        matches!(runtime.house.device("hall", key), Ok(Device::Socket(socket)) if socket.is_active())
+    }
+
+    #[tokio::test]
+    async fn poll_runs_schedule() {
+        let mut runtime = Runtime::new(&HouseConfig::default()).unwrap();
+        runtime.house = SmartHouse::builder()
+            .room("hall", |r| r.socket("lamp", 60.0))
+            .build();
+        // 18:59 UTC
+        let clock = Arc::new(MockClock::starting_at(((18 * 60) + 59) * 60_000));
+        runtime.schedule = Schedule::new()
+            .with_entry(
+                ScheduleEntry::new("evening", "19:00".parse().unwrap())
+                    .with_action(RuleAction::TurnOn("hall/lamp".parse().unwrap())),
+            )
+            .with_clock(clock.clone());
+
+        runtime.poll().await;
+        assert!(!is_active(&runtime, "lamp"));
+        clock.advance(Duration::from_secs(60));
+        let (_, errors) = runtime.poll().await;
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(is_active(&runtime, "lamp"));
+    }
+
+    #[tokio::test]
+    async fn command_updates_desired_state() {
+        let mut runtime = Runtime::new(&HouseConfig::default()).unwrap();
+        runtime.house = SmartHouse::builder()
+            .room("hall", |r| r.socket("lamp", 60.0).socket("tv", 100.0))
+            .build();
+        let mut reconciler = Reconciler::new();
+        reconciler.set_desired("hall", "tv", DesiredState::Off);
+        runtime.reconciler = Some(reconciler);
+
+        runtime
+            .command(&RuleAction::TurnOn("hall/tv".parse().unwrap()))
+            .await
+            .unwrap();
+        runtime
+            .command(&RuleAction::TurnOn("hall/lamp".parse().unwrap()))
+            .await
+            .unwrap();
+        assert!(is_active(&runtime, "tv") && is_active(&runtime, "lamp"));
+        let reconciler = runtime.reconciler.as_ref().unwrap();
+        assert_eq!(reconciler.desired("hall", "tv"), Some(DesiredState::On));
+        // Устройства без желаемого состояния сверка не отслеживает и дальше
+        assert_eq!(reconciler.desired("hall", "lamp"), None);
+
+        let error = runtime
+            .command(&RuleAction::TurnOff("hall/fan".parse().unwrap()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, RuleError::UnknownDevice(_)));
+    }
+}
@@ -0,0 +1,161 @@
+//! PID файл и файл состояния демона
+
+use serde::Serialize;
+use smart_home_lib::energy::BudgetUsage;
+use smart_home_lib::house::SmartHouse;
+use smart_home_lib::protocol::now_ms;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// PID файл; удаляется при Drop
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Записывает PID текущего процесса
+    pub fn create(path: &Path) -> io::Result<Self> {
+        if let Ok(existing) = std::fs::read_to_string(path)
+            && is_process_alive(existing.trim())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Демон уже запущен (PID {})", existing.trim()),
+            ));
+        }
+
+        write_atomically(path, &format!("{}\n", std::process::id()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Проверяет процесс по PID (через /proc, где он доступен)
+fn is_process_alive(pid: &str) -> bool {
+    match pid.parse::<u32>() {
+        Ok(pid) if pid != std::process::id() => Path::new(&format!("/proc/{}", pid)).exists(),
+        _ => false,
+    }
+}
+
+/// Энергобюджет в файле состояния
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetSnapshot {
+    pub total_watts: f64,
+    pub limit_watts: f64,
+    pub exceeded: bool,
+}
+
+impl From<&BudgetUsage> for BudgetSnapshot {
+    fn from(usage: &BudgetUsage) -> Self {
+        Self {
+            total_watts: usage.total.value(),
+            limit_watts: usage.limit.value(),
+            exceeded: usage.is_exceeded(),
+        }
+    }
+}
+
+/// Снимок состояния демона
+#[derive(Debug, Serialize)]
+pub struct DaemonState {
+    pub pid: u32,
+    pub started_ms: u64,
+    pub updated_ms: u64,
+    /// Число перезагрузок конфигурации (SIGHUP)
+    pub reloads: u32,
+    pub rooms: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetSnapshot>,
+    /// Ошибки последнего опроса
+    pub errors: Vec<String>,
+    /// Текстовый отчет дома
+    pub report: Vec<String>,
+}
+
+impl DaemonState {
+    pub fn capture(
+        house: &SmartHouse,
+        started_ms: u64,
+        reloads: u32,
+        budget: Option<BudgetSnapshot>,
+        errors: Vec<String>,
+    ) -> Self {
+        Self {
+            pid: std::process::id(),
+            started_ms,
+            updated_ms: now_ms(),
+            reloads,
+            rooms: house.rooms_count(),
+            budget,
+            errors,
+            report: house.report_lines(),
+        }
+    }
+
+    /// Записывает состояние в файл (через временный файл и rename)
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        write_atomically(path, &json)
+    }
+}
+
+/// Читатели файла никогда не видят частично записанное содержимое
+fn write_atomically(path: &Path, content: &str) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("smart-home-daemon-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn pid_file_lifecycle() {
+        let path = temp_path("pid");
+        {
+            let _pid = PidFile::create(&path).unwrap();
+            let content = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(content.trim(), std::process::id().to_string());
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn stale_pid_file_replaced() {
+        let path = temp_path("stale-pid");
+        std::fs::write(&path, "not-a-pid").unwrap();
+        let pid = PidFile::create(&path).unwrap();
+        drop(pid);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn state_file() {
+        let path = temp_path("state");
+        let state = DaemonState::capture(&SmartHouse::default(), 1, 2, None, vec!["e".into()]);
+        state.write(&path).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["reloads"], 2);
+        assert_eq!(json["rooms"], 0);
+        assert_eq!(json["errors"][0], "e");
+        assert!(json.get("budget").is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
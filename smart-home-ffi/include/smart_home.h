@@ -0,0 +1,58 @@
+/*
+ * C ABI библиотеки умного дома (smart-home-ffi)
+ *
+ * Функции возвращают код SMART_HOME_* или NULL при ошибке; текст последней
+ * ошибки потока возвращает smart_home_last_error().
+ */
+
+#ifndef SMART_HOME_H
+#define SMART_HOME_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define SMART_HOME_OK 0
+#define SMART_HOME_ERR_ARGUMENT 1
+#define SMART_HOME_ERR_HOUSE 2
+#define SMART_HOME_ERR_INTERNAL 3
+
+#define SMART_HOME_CMD_TURN_ON 0
+#define SMART_HOME_CMD_TURN_OFF 1
+#define SMART_HOME_CMD_READ_TEMPERATURE 2
+#define SMART_HOME_CMD_READ_POWER 3
+
+/* Дом вместе с фоновыми задачами контроллеров */
+typedef struct SmartHomeHouse SmartHomeHouse;
+
+/* Создает дом из JSON файла конфигурации и запускает контроллеры;
+   NULL при ошибке конфигурации */
+SmartHomeHouse *smart_home_house_load(const char *config_path);
+
+/* Останавливает контроллеры и освобождает дом; NULL игнорируется */
+void smart_home_house_free(SmartHomeHouse *house);
+
+/* Выполняет команду SMART_HOME_CMD_* на устройстве "room/key";
+ * температура или мощность записывается в out_value (может быть NULL) */
+int32_t smart_home_house_execute(SmartHomeHouse *house, const char *path,
+                                 int32_t command, double *out_value);
+
+/* Отчет о доме в JSON по последним известным данным */
+char *smart_home_house_report(const SmartHomeHouse *house);
+
+/* Опрашивает контроллеры (не дольше timeout_ms каждый), отчет в JSON */
+char *smart_home_house_poll(SmartHomeHouse *house, uint64_t timeout_ms);
+
+/* Освобождает строку отчета; NULL игнорируется */
+void smart_home_string_free(char *s);
+
+/* Текст последней ошибки потока или NULL; действителен до следующего вызова */
+const char *smart_home_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SMART_HOME_H */
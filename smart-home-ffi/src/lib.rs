@@ -0,0 +1,416 @@
+//! C ABI для встраивания умного дома в C/C++ хабы
+//!
+//! Дом создается из файла конфигурации (`HouseConfig`) и живет в
+//! непрозрачном указателе `SmartHomeHouse` вместе с собственным рантаймом
+//! tokio: фоновые задачи контроллеров работают между вызовами из C.
+//! Заголовок для C лежит в `include/smart_home.h`.
+//!
+//! Функции не паникуют через границу ABI: ошибка возвращается кодом или
+//! `NULL`, текст последней ошибки потока доступен через
+//! `smart_home_last_error`.
+
+use smart_home_lib::audit::CommandOrigin;
+use smart_home_lib::config::HouseConfig;
+use smart_home_lib::controllers::{DeviceCommand, DeviceOutput};
+use smart_home_lib::house::SmartHouse;
+use smart_home_lib::path::DevicePath;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// Команда выполнена
+pub const SMART_HOME_OK: i32 = 0;
+/// Некорректный аргумент: `NULL`, не UTF-8, неизвестная команда или путь
+pub const SMART_HOME_ERR_ARGUMENT: i32 = 1;
+/// Ошибка дома: устройство не найдено или команда не удалась
+pub const SMART_HOME_ERR_HOUSE: i32 = 2;
+/// Внутренняя ошибка библиотеки
+pub const SMART_HOME_ERR_INTERNAL: i32 = 3;
+
+/// Включить розетку
+pub const SMART_HOME_CMD_TURN_ON: i32 = 0;
+/// Выключить розетку
+pub const SMART_HOME_CMD_TURN_OFF: i32 = 1;
+/// Прочитать температуру, °C
+pub const SMART_HOME_CMD_READ_TEMPERATURE: i32 = 2;
+/// Прочитать мощность, Вт
+pub const SMART_HOME_CMD_READ_POWER: i32 = 3;
+
+/// Источник команд в журнале дома
+const ORIGIN: &str = "ffi";
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Дом вместе с рантаймом, на котором работают его контроллеры
+pub struct SmartHomeHouse {
+    runtime: Runtime,
+    house: SmartHouse,
+}
+
+impl SmartHomeHouse {
+    fn load(path: &str) -> Result<Self, String> {
+        let runtime = Runtime::new().map_err(|e| e.to_string())?;
+        let config = HouseConfig::load(path).map_err(|e| e.to_string())?;
+        let mut house = {
+            let _guard = runtime.enter();
+            config.build().map_err(|e| e.to_string())?
+        };
+        // Как и в демоне: недоступные при загрузке устройства опрашиваются дальше
+        if let Err(e) = runtime.block_on(house.start_controllers()) {
+            eprintln!("⚠️ {}", e);
+        }
+        Ok(Self { runtime, house })
+    }
+
+    fn execute(
+        &mut self,
+        path: &DevicePath,
+        command: DeviceCommand,
+    ) -> Result<DeviceOutput, String> {
+        let origin = CommandOrigin::User(ORIGIN.to_string());
+        self.runtime
+            .block_on(self.house.execute(path, command, origin))
+            .map_err(|e| e.to_string())
+    }
+
+    fn report_json(&self) -> String {
+        serde_json::to_string(&self.house.snapshot()).unwrap_or_default()
+    }
+
+    fn poll_json(&mut self, timeout: Duration) -> String {
+        self.runtime.block_on(self.house.live_report_lines(timeout));
+        self.report_json()
+    }
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Выполняет `f`, превращая панику во внутреннюю ошибку
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    clear_last_error();
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        set_last_error("внутренняя ошибка библиотеки");
+        fallback
+    })
+}
+
+/// Читает строку C; `None` (с текстом ошибки) для `NULL` и не UTF-8
+///
+/// # Safety
+///
+/// `ptr` — `NULL` или указатель на строку, завершенную нулем.
+unsafe fn read_str<'a>(ptr: *const c_char, name: &str) -> Option<&'a str> {
+    if ptr.is_null() {
+        set_last_error(format!("{}: NULL", name));
+        return None;
+    }
+    // SAFETY: вызывающий гарантирует строку, завершенную нулем
+    match unsafe { CStr::from_ptr(ptr) }.to_str() {
+        Ok(s) => Some(s),
+        Err(_) => {
+            set_last_error(format!("{}: строка не в UTF-8", name));
+            None
+        }
+    }
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+fn command_from(code: i32) -> Option<DeviceCommand> {
+    match code {
+        SMART_HOME_CMD_TURN_ON => Some(DeviceCommand::TurnOn),
+        SMART_HOME_CMD_TURN_OFF => Some(DeviceCommand::TurnOff),
+        SMART_HOME_CMD_READ_TEMPERATURE => Some(DeviceCommand::ReadTemperature),
+        SMART_HOME_CMD_READ_POWER => Some(DeviceCommand::ReadPower),
+        _ => None,
+    }
+}
+
+/// Создает дом из JSON файла конфигурации и запускает контроллеры
+///
+/// Контроллеры, которые не удалось запустить (недоступная розетка, занятый
+/// порт термометра), только выводят предупреждение. Возвращает `NULL` при
+/// ошибке конфигурации. Дом освобождается `smart_home_house_free`.
+///
+/// # Safety
+///
+/// `config_path` — `NULL` или строка, завершенная нулем.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn smart_home_house_load(config_path: *const c_char) -> *mut SmartHomeHouse {
+    guard(ptr::null_mut(), || {
+        // SAFETY: контракт функции
+        let Some(path) = (unsafe { read_str(config_path, "config_path") }) else {
+            return ptr::null_mut();
+        };
+        match SmartHomeHouse::load(path) {
+            Ok(house) => Box::into_raw(Box::new(house)),
+            Err(e) => {
+                set_last_error(e);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Останавливает контроллеры и освобождает дом; `NULL` игнорируется
+///
+/// # Safety
+///
+/// `house` — `NULL` или указатель из `smart_home_house_load`, который еще
+/// не освобожден.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn smart_home_house_free(house: *mut SmartHomeHouse) {
+    if house.is_null() {
+        return;
+    }
+    guard((), || {
+        // SAFETY: указатель получен из Box::into_raw и освобождается один раз
+        let mut house = unsafe { Box::from_raw(house) };
+        let SmartHomeHouse { runtime, house } = &mut *house;
+        runtime.block_on(house.stop_all());
+    })
+}
+
+/// Выполняет команду `SMART_HOME_CMD_*` на устройстве `room/key`
+///
+/// Прочитанное значение (температура или мощность) записывается в
+/// `out_value`, если он не `NULL`. Команда попадает в журнал дома с
+/// источником `ffi`.
+///
+/// # Safety
+///
+/// `house` — живой указатель из `smart_home_house_load`, `path` — строка,
+/// завершенная нулем, `out_value` — `NULL` или указатель на `double`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn smart_home_house_execute(
+    house: *mut SmartHomeHouse,
+    path: *const c_char,
+    command: i32,
+    out_value: *mut f64,
+) -> i32 {
+    guard(SMART_HOME_ERR_INTERNAL, || {
+        // SAFETY: контракт функции
+        let Some(house) = (unsafe { house.as_mut() }) else {
+            set_last_error("house: NULL");
+            return SMART_HOME_ERR_ARGUMENT;
+        };
+        // SAFETY: контракт функции
+        let Some(path) = (unsafe { read_str(path, "path") }) else {
+            return SMART_HOME_ERR_ARGUMENT;
+        };
+        let path: DevicePath = match path.parse() {
+            Ok(path) => path,
+            Err(e) => {
+                set_last_error(format!("path: {}", e));
+                return SMART_HOME_ERR_ARGUMENT;
+            }
+        };
+        let Some(command) = command_from(command) else {
+            set_last_error(format!("неизвестная команда {}", command));
+            return SMART_HOME_ERR_ARGUMENT;
+        };
+
+        match house.execute(&path, command) {
+            Ok(output) => {
+                let value = match output {
+                    DeviceOutput::Done => None,
+                    DeviceOutput::Temperature(t) => Some(t.value()),
+                    DeviceOutput::Power(p) => Some(p.value()),
+                };
+                if let (Some(value), false) = (value, out_value.is_null()) {
+                    // SAFETY: контракт функции
+                    unsafe { *out_value = value };
+                }
+                SMART_HOME_OK
+            }
+            Err(e) => {
+                set_last_error(e);
+                SMART_HOME_ERR_HOUSE
+            }
+        }
+    })
+}
+
+/// Структурированный отчет о доме (JSON) по последним известным данным
+///
+/// Строка освобождается `smart_home_string_free`; `NULL` при ошибке.
+///
+/// # Safety
+///
+/// `house` — `NULL` или живой указатель из `smart_home_house_load`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn smart_home_house_report(house: *const SmartHomeHouse) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        // SAFETY: контракт функции
+        let Some(house) = (unsafe { house.as_ref() }) else {
+            set_last_error("house: NULL");
+            return ptr::null_mut();
+        };
+        into_c_string(house.report_json())
+    })
+}
+
+/// Опрашивает контроллеры (не дольше `timeout_ms` каждый) и возвращает
+/// отчет о доме в JSON
+///
+/// Строка освобождается `smart_home_string_free`; `NULL` при ошибке.
+///
+/// # Safety
+///
+/// `house` — `NULL` или живой указатель из `smart_home_house_load`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn smart_home_house_poll(
+    house: *mut SmartHomeHouse,
+    timeout_ms: u64,
+) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        // SAFETY: контракт функции
+        let Some(house) = (unsafe { house.as_mut() }) else {
+            set_last_error("house: NULL");
+            return ptr::null_mut();
+        };
+        into_c_string(house.poll_json(Duration::from_millis(timeout_ms)))
+    })
+}
+
+/// Освобождает строку, полученную от библиотеки; `NULL` игнорируется
+///
+/// # Safety
+///
+/// `s` — `NULL` или строка из `smart_home_house_report`/`smart_home_house_poll`,
+/// которая еще не освобождена.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn smart_home_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: строка получена из CString::into_raw и освобождается один раз
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Текст последней ошибки в текущем потоке или `NULL`
+///
+/// Строка принадлежит библиотеке и действительна до следующего вызова
+/// функций библиотеки в этом потоке.
+#[unsafe(no_mangle)]
+pub extern "C" fn smart_home_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const CONFIG: &str = r#"{
+        "rooms": {
+            "kitchen": {
+                "devices": {
+                    "kettle": { "kind": "socket", "power_rating": 2000.0, "address": "127.0.0.1:1" },
+                    "therm": { "kind": "therm", "initial_temp": 21.5, "address": "127.0.0.1:0" }
+                }
+            }
+        }
+    }"#;
+
+    fn config_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "smart-home-ffi-{}-{}.json",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, CONFIG).unwrap();
+        path
+    }
+
+    fn last_error() -> String {
+        let ptr = smart_home_last_error();
+        assert!(!ptr.is_null());
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    #[test]
+    fn load_reports_errors() {
+        unsafe {
+            assert!(smart_home_house_load(ptr::null()).is_null());
+            assert!(last_error().contains("config_path"));
+
+            let missing = c("/nonexistent/house.json");
+            assert!(smart_home_house_load(missing.as_ptr()).is_null());
+            assert!(last_error().contains("/nonexistent/house.json"));
+        }
+    }
+
+    #[test]
+    fn report_and_commands() {
+        let path = config_file("report");
+        let path_c = c(path.to_str().unwrap());
+
+        unsafe {
+            let house = smart_home_house_load(path_c.as_ptr());
+            assert!(!house.is_null());
+            assert!(smart_home_last_error().is_null());
+
+            let report = smart_home_house_report(house);
+            let json = CStr::from_ptr(report).to_str().unwrap().to_string();
+            smart_home_string_free(report);
+            let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(value["rooms"][0]["room"], "kitchen");
+
+            // Некорректный путь, неизвестная команда и устройство
+            let mut out = 0.0;
+            let invalid = c("kettle");
+            let status =
+                smart_home_house_execute(house, invalid.as_ptr(), SMART_HOME_CMD_TURN_ON, &mut out);
+            assert_eq!(status, SMART_HOME_ERR_ARGUMENT);
+
+            let kettle = c("kitchen/kettle");
+            let status = smart_home_house_execute(house, kettle.as_ptr(), 42, &mut out);
+            assert_eq!(status, SMART_HOME_ERR_ARGUMENT);
+
+            let missing = c("kitchen/missing");
+            let status =
+                smart_home_house_execute(house, missing.as_ptr(), SMART_HOME_CMD_TURN_ON, &mut out);
+            assert_eq!(status, SMART_HOME_ERR_HOUSE);
+            assert!(last_error().contains("missing"));
+
+            // Розетка недоступна: ошибка с путем устройства
+            let status =
+                smart_home_house_execute(house, kettle.as_ptr(), SMART_HOME_CMD_TURN_ON, &mut out);
+            assert_eq!(status, SMART_HOME_ERR_HOUSE);
+            assert!(last_error().contains("kitchen/kettle"));
+
+            // Команда, которую устройство не поддерживает
+            let therm = c("kitchen/therm");
+            let status =
+                smart_home_house_execute(house, therm.as_ptr(), SMART_HOME_CMD_TURN_ON, &mut out);
+            assert_eq!(status, SMART_HOME_ERR_HOUSE);
+
+            let report = smart_home_house_poll(house, 10);
+            assert!(!report.is_null());
+            smart_home_string_free(report);
+
+            smart_home_house_free(house);
+        }
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,149 @@
+//! Бенчмарки протоколов: сериализация, кадры и прием сообщений
+//!
+//! Запуск: `cargo bench -p smart-home-lib --bench protocol`. После замеров
+//! печатаются счетчики `protocol_stats` - сколько времени ушло на JSON и
+//! сколько байт скопировано между буферами.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use smart_home_lib::protocol::socket_protocol::{
+    SocketCommand, SocketData, SocketResponse, receive_message, receive_message_into,
+    receive_response, send_message, send_message_chunked, send_response,
+};
+use smart_home_lib::protocol::{ThermData, protocol_stats, reset_protocol_stats};
+use std::hint::black_box;
+use tokio::runtime::Runtime;
+
+fn response() -> SocketResponse {
+    SocketResponse::Ok(SocketData {
+        active: true,
+        power: 1234.5,
+        energy_wh: 42.0,
+        device_id: Some("kitchen-kettle".to_string()),
+        outlets: Vec::new(),
+    })
+}
+
+/// Закодированные кадры сообщения: целым кадром или частями
+fn encoded(message: &str, chunk_size: usize) -> Vec<u8> {
+    let runtime = Runtime::new().unwrap();
+    let mut frames = Vec::new();
+    runtime
+        .block_on(send_message_chunked(&mut frames, message, chunk_size))
+        .unwrap();
+    frames
+}
+
+fn json(c: &mut Criterion) {
+    let response = response();
+    let json = serde_json::to_string(&response).unwrap();
+
+    c.bench_function("serialize_response", |b| {
+        b.iter(|| serde_json::to_string(black_box(&response)).unwrap())
+    });
+    c.bench_function("deserialize_response", |b| {
+        b.iter(|| serde_json::from_str::<SocketResponse>(black_box(&json)).unwrap())
+    });
+    // Телеметрия термометра: разбор из буфера пакета и с копией строк
+    let packet = br#"{"temperature":21.5,"device_id":"kitchen_001"}"#;
+    c.bench_function("parse_therm_data/borrowed", |b| {
+        b.iter(|| ThermData::parse(black_box(packet)).unwrap())
+    });
+    c.bench_function("parse_therm_data/owned", |b| {
+        b.iter(|| ThermData::parse(black_box(packet)).unwrap().into_owned())
+    });
+    c.bench_function("serialize_command", |b| {
+        b.iter(|| {
+            serde_json::to_string(black_box(&SocketCommand::SetLoad { percent: 50 })).unwrap()
+        })
+    });
+}
+
+fn frames(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let response = response();
+    let mut encoded_response = Vec::new();
+    runtime
+        .block_on(send_response(&mut encoded_response, &response))
+        .unwrap();
+
+    c.bench_function("send_response", |b| {
+        let mut out = Vec::with_capacity(1024);
+        b.iter(|| {
+            out.clear();
+            runtime
+                .block_on(send_response(&mut out, &response))
+                .unwrap();
+        })
+    });
+    c.bench_function("receive_response", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(receive_response(&mut encoded_response.as_slice()))
+                .unwrap()
+        })
+    });
+}
+
+fn receive(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("receive_message");
+
+    for size in [64, 4 * 1024, 64 * 1024] {
+        let message = "x".repeat(size);
+        // Целый кадр и сообщение из частей по 1 КиБ
+        for (name, chunk_size) in [("frame", usize::MAX), ("chunked", 1024)] {
+            let frames = encoded(&message, chunk_size);
+            group.throughput(Throughput::Bytes(size as u64));
+
+            group.bench_with_input(
+                BenchmarkId::new(format!("{}/alloc", name), size),
+                &frames,
+                |b, frames| {
+                    b.iter(|| {
+                        runtime
+                            .block_on(receive_message(&mut frames.as_slice()))
+                            .unwrap()
+                    })
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("{}/reuse", name), size),
+                &frames,
+                |b, frames| {
+                    let mut buf = Vec::new();
+                    b.iter(|| {
+                        runtime
+                            .block_on(receive_message_into(&mut frames.as_slice(), &mut buf))
+                            .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn send(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let message = "x".repeat(4 * 1024);
+    let mut out = Vec::with_capacity(8 * 1024);
+
+    c.bench_function("send_message/4096", |b| {
+        b.iter(|| {
+            out.clear();
+            runtime.block_on(send_message(&mut out, &message)).unwrap();
+        })
+    });
+}
+
+fn stats(c: &mut Criterion) {
+    reset_protocol_stats();
+    json(c);
+    frames(c);
+    receive(c);
+    send(c);
+    println!("protocol stats: {}", protocol_stats());
+}
+
+criterion_group!(benches, stats);
+criterion_main!(benches);
@@ -81,11 +81,11 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // Демонстрация удаления устройства
     println!("\n=== Удаление устройства ===");
-    if let Some(room) = house.room_mut("гостиная") {
-        if let Some(removed) = room.remove_device("кондиционер") {
-            println!("Устройство удалено: {}", removed);
-            println!("Оставшиеся устройства: {:?}", room.devices_keys());
-        }
+    if let Some(room) = house.room_mut("гостиная")
+        && let Some(removed) = room.remove_device("кондиционер")
+    {
+        println!("Устройство удалено: {}", removed);
+        println!("Оставшиеся устройства: {:?}", room.devices_keys());
     }
 
     // Демонстрация удаления комнаты
@@ -2,71 +2,19 @@
 //!
 //! Демонстрирует управление реальными устройствами через TCP (розетки) и UDP (термометры).
 //!
-//! Для тестирования запустите эмуляторы в отдельных терминалах:
+//! Эмуляторы устройств поднимает парк эмуляторов (`Fleet`) на свободных
+//! портах внутри процесса:
 //!
-//! **Термометры (UDP):**
-//! ```bash
-//! cargo run --example therm_emulator kitchen_therm_001 127.0.0.1:4001 22.5 normal
-//! cargo run --example therm_emulator ac_therm_001 127.0.0.1:4002 24.0 normal
-//! ```
-//!
-//! **Розетки (TCP):**
-//! ```bash
-//! cargo run --example socket_emulator kettle_001 127.0.0.1:3001 2000.0
-//! cargo run --example socket_emulator tv_001 127.0.0.1:3002 150.0
-//! ```
-//!
-//! Затем запустите этот пример:
 //! ```bash
 //! cargo run --example controllers_usage
 //! ```
 
+use smart_home_lib::emulators::{Fleet, FleetSpec};
 use smart_home_lib::prelude::*;
 use std::error::Error;
-use std::net::SocketAddr;
 use std::time::Duration;
 use tokio::time::sleep;
 
-/// Создает кухню с сетевыми контроллерами
-fn create_kitchen() -> Room {
-    let mut room = Room::new();
-
-    // TCP контроллер для чайника
-    let kettle_addr: SocketAddr = "127.0.0.1:3001".parse().unwrap();
-    let kettle_controller = SocketController::new(
-        kettle_addr,
-        2000.0, // 2кВт чайник
-        Duration::from_secs(3),
-    );
-    room.add_controller("чайник", DeviceController::Socket(kettle_controller));
-
-    // UDP контроллер для термометра
-    let kitchen_therm = ThermController::new(22.5, "127.0.0.1:4001", Duration::from_secs(5));
-    room.add_controller("термометр", DeviceController::Therm(kitchen_therm));
-
-    room
-}
-
-/// Создает гостиную с сетевыми контроллерами
-fn create_living_room() -> Room {
-    let mut room = Room::new();
-
-    // TCP контроллер для телевизора
-    let tv_addr: SocketAddr = "127.0.0.1:3002".parse().unwrap();
-    let tv_controller = SocketController::new(
-        tv_addr,
-        150.0, // 150Вт телевизор
-        Duration::from_secs(3),
-    );
-    room.add_controller("телевизор", DeviceController::Socket(tv_controller));
-
-    // UDP контроллер для кондиционера
-    let ac_therm = ThermController::new(24.0, "127.0.0.1:4002", Duration::from_secs(10));
-    room.add_controller("кондиционер", DeviceController::Therm(ac_therm));
-
-    room
-}
-
 /// Демонстрирует управление розетками через TCP
 async fn demo_socket_controllers(house: &mut SmartHouse) -> Result<(), Box<dyn Error>> {
     println!("🔌 === Управление розетками через TCP ===");
@@ -122,18 +70,13 @@ async fn demo_socket_controllers(house: &mut SmartHouse) -> Result<(), Box<dyn E
 }
 
 /// Демонстрирует мониторинг термометров через UDP
-async fn demo_therm_controllers(house: &mut SmartHouse) -> Result<(), Box<dyn Error>> {
+async fn demo_therm_controllers(house: &SmartHouse) -> Result<(), Box<dyn Error>> {
     println!("\n🌡️ === Мониторинг термометров через UDP ===");
 
-    // Запускаем термометр на кухне
+    // Термометры уже слушают UDP: их запустил `SmartHouse::start_controllers`
     println!("\n🍳 Мониторинг температуры на кухне:");
-    if let Ok(DeviceController::Therm(kitchen_therm)) = house.controller_mut("кухня", "термометр")
+    if let Ok(DeviceController::Therm(kitchen_therm)) = house.controller("кухня", "термометр")
     {
-        println!("📡 Подключение к термометру кухни...");
-
-        kitchen_therm.start();
-        println!("🌡️ Термометр кухни запущен");
-
         // Собираем несколько показаний
         for i in 1..=3 {
             sleep(Duration::from_secs(2)).await;
@@ -143,27 +86,19 @@ async fn demo_therm_controllers(house: &mut SmartHouse) -> Result<(), Box<dyn Er
                 Err(e) => println!("❌ Ошибка показания #{}: {}", i, e),
             }
         }
-
-        kitchen_therm.stop();
-        println!("🛑 Термометр кухни остановлен");
     }
 
-    // Запускаем термометр кондиционера
     println!("\n❄️ Мониторинг кондиционера:");
-    if let Ok(DeviceController::Therm(ac_therm)) = house.controller_mut("гостиная", "кондиционер")
+    if let Ok(DeviceController::Therm(ac_therm)) = house.controller("гостиная", "кондиционер")
     {
-        println!("📡 Подключение к термометру кондиционера...");
-
-        ac_therm.start();
-        println!("🌡️ Термометр кондиционера запущен");
-
         sleep(Duration::from_secs(3)).await;
 
         match ac_therm.temperature() {
-            Ok(temp) => {
-                println!("📊 Температура кондиционера: {}", temp);
+            Ok(reading) => {
+                println!("📊 Температура кондиционера: {}", reading);
 
                 // Имитируем корректировку температуры
+                let temp = reading.value;
                 if temp.value() > 25.0 {
                     println!("🔥 Температура высокая, кондиционер работает интенсивнее");
                 } else if temp.value() < 20.0 {
@@ -174,9 +109,6 @@ async fn demo_therm_controllers(house: &mut SmartHouse) -> Result<(), Box<dyn Er
             }
             Err(e) => println!("❌ Ошибка получения температуры: {}", e),
         }
-
-        ac_therm.stop();
-        println!("🛑 Термометр кондиционера остановлен");
     }
 
     Ok(())
@@ -207,7 +139,7 @@ async fn demo_connection_errors(house: &mut SmartHouse) {
     // Создаем контроллер с неверным адресом
     let mut temp_room = Room::new();
     let broken_socket = SocketController::new(
-        "127.0.0.1:9999".parse().unwrap(), // Несуществующий порт
+        DeviceAddress::host("localhost", 9999), // Несуществующий порт (имя разрешается при подключении)
         1000.0,
         Duration::from_secs(1), // Короткий таймаут
     );
@@ -234,41 +166,32 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("🏡 Умный дом: сетевые контроллеры");
     println!("═══════════════════════════════════");
 
-    // Создаем дом только с сетевыми контроллерами
-    let mut house = house![
-        ("кухня", create_kitchen()),
-        ("гостиная", create_living_room()),
-    ];
+    // Поднимаем эмуляторы и дом с контроллерами, подключенными к ним;
+    // контроллеры уже запущены (`SmartHouse::start_controllers`)
+    let spec = FleetSpec::new()
+        .socket("кухня", "чайник", 2000.0)
+        .socket("гостиная", "телевизор", 150.0)
+        .therm("кухня", "термометр", 22.5, EmulationScenario::Normal)
+        .therm("гостиная", "кондиционер", 24.0, EmulationScenario::Normal)
+        .with_update_interval(Duration::from_millis(500));
+    let mut fleet = Fleet::start(spec).await?;
 
     println!("🏗️ Создан умный дом с сетевыми контроллерами:");
     println!("   🍳 Кухня: чайник (TCP) + термометр (UDP)");
     println!("   🛋️ Гостиная: телевизор (TCP) + кондиционер (UDP)");
 
-    // Правильные команды запуска эмуляторов
-    println!("\n💡 Запустите эмуляторы в отдельных терминалах:");
-    println!("📋 Термометры (UDP):");
-    println!(
-        "   🌡️ cargo run --example therm_emulator kitchen_therm_001 127.0.0.1:4001 22.5 normal"
-    );
-    println!("   🌡️ cargo run --example therm_emulator ac_therm_001 127.0.0.1:4002 24.0 normal");
-    println!("📋 Розетки (TCP):");
-    println!("   🔌 cargo run --example socket_emulator kettle_001 127.0.0.1:3001 2000.0");
-    println!("   🔌 cargo run --example socket_emulator tv_001 127.0.0.1:3002 150.0");
-
-    // Небольшая пауза для подготовки
-    println!("\n⏳ Начинаем через 5 секунд (время для запуска эмуляторов)...");
-    sleep(Duration::from_secs(5)).await;
-
     // Демонстрации
-    demo_socket_controllers(&mut house).await?;
-    demo_therm_controllers(&mut house).await?;
-    demo_generic_controllers(&house).await;
-    demo_connection_errors(&mut house).await;
+    demo_socket_controllers(fleet.house_mut()).await?;
+    demo_therm_controllers(fleet.house()).await?;
+    demo_generic_controllers(fleet.house()).await;
+    demo_connection_errors(fleet.house_mut()).await;
 
     // Итоговый отчет
     println!("\n📋 === Итоговый отчет контроллеров ===");
-    println!("{}", house.report());
+    println!("{}", fleet.house().report());
 
+    // Контроллеры останавливаются раньше эмуляторов
+    fleet.shutdown().await;
     println!("\n✅ Демонстрация завершена!");
     Ok(())
 }
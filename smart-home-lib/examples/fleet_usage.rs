@@ -0,0 +1,68 @@
+//! Пример работы с контроллерами без ручного запуска эмуляторов
+//!
+//! Парк эмуляторов поднимает розетки и термометры на свободных портах
+//! внутри одного процесса и собирает дом с подключенными контроллерами.
+//!
+//! ```bash
+//! cargo run --example fleet_usage
+//! ```
+
+use smart_home_lib::emulators::{Fleet, FleetSpec};
+use smart_home_lib::prelude::*;
+use std::error::Error;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    println!("🏭 Запуск парка эмуляторов...");
+
+    let spec = FleetSpec::new()
+        .socket("кухня", "чайник", 2000.0)
+        .socket("гостиная", "телевизор", 150.0)
+        .therm("кухня", "термометр", 22.5, EmulationScenario::Normal)
+        .therm(
+            "гостиная",
+            "кондиционер",
+            24.0,
+            EmulationScenario::Fluctuate,
+        )
+        .with_update_interval(Duration::from_millis(500));
+
+    let mut fleet = Fleet::start(spec).await?;
+
+    for (room, key) in [("кухня", "чайник"), ("гостиная", "телевизор")]
+    {
+        if let Some(addr) = fleet.socket_addr(room, key) {
+            println!("🔌 {}/{} слушает TCP {}", room, key, addr);
+        }
+    }
+
+    if let Ok(DeviceController::Socket(kettle)) =
+        fleet.house_mut().controller_mut("кухня", "чайник")
+    {
+        kettle.turn_on().await?;
+        println!("⚡ Мощность чайника: {}", kettle.power().await?);
+    }
+
+    println!("\n⏳ Следим за показаниями термометров...");
+    {
+        // Пустая пауза дольше 2 секунд завершает наблюдение
+        let reports = fleet.house().watch_reports(Duration::from_millis(500));
+        let mut reports = Box::pin(reports.timeout(Duration::from_secs(2)));
+        let mut count = 0;
+        while let Some(Ok(report)) = reports.next().await {
+            count += 1;
+            println!("\n📋 === Отчет #{} ===", count);
+            println!("{}", report);
+            if count == 3 {
+                break;
+            }
+        }
+    }
+
+    fleet.shutdown().await;
+    println!("\n✅ Парк эмуляторов остановлен");
+
+    Ok(())
+}
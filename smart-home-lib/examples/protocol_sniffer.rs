@@ -0,0 +1,47 @@
+//! Анализатор трафика между контроллером и розеткой
+//!
+//! Контроллер подключается к адресу анализатора вместо адреса розетки,
+//! а все сообщения в обе стороны печатаются с отметками времени.
+
+use smart_home_lib::protocol::Sniffer;
+use std::env;
+use tokio::signal;
+use tokio::sync::broadcast::error::RecvError;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Порядок: 1-адрес прослушивания, 2-адрес розетки
+    let args: Vec<String> = env::args().collect();
+    let (listen_addr, target) = match args.as_slice() {
+        [_, listen, target, ..] => (listen.clone(), target.clone()),
+        _ => {
+            println!(
+                "📝 Использование: {} <listen_address> <device_address>",
+                args[0]
+            );
+            println!("🔧 Используем значения по умолчанию");
+            ("127.0.0.1:3031".to_string(), "127.0.0.1:3030".to_string())
+        }
+    };
+
+    let mut sniffer = Sniffer::new(target.parse()?);
+    let mut frames = sniffer.subscribe();
+    let addr = sniffer.start(&listen_addr).await?;
+    println!("🔍 Анализатор слушает {} и пересылает на {}", addr, target);
+    println!("⏹️  Нажмите Ctrl+C для остановки\n");
+
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(frame) => println!("{}", frame),
+                Err(RecvError::Lagged(skipped)) => println!("⚠️ Пропущено сообщений: {}", skipped),
+                Err(RecvError::Closed) => break,
+            },
+            _ = signal::ctrl_c() => break,
+        }
+    }
+
+    sniffer.stop();
+    println!("\n🛑 Анализатор остановлен");
+    Ok(())
+}
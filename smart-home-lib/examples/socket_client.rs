@@ -36,11 +36,22 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Тестируем команды
     let test_commands = vec![
+        ("Проверка соединения", SocketCommand::Ping),
+        ("Описание устройства", SocketCommand::Describe),
         ("Запрос текущего состояния", SocketCommand::Power),
-        ("Включение розетки", SocketCommand::TurnOn),
+        ("Включение розетки", SocketCommand::TurnOn { outlet: None }),
         ("Запрос состояния после включения", SocketCommand::Power),
-        ("Выключение розетки", SocketCommand::TurnOff),
+        (
+            "Снижение нагрузки диммера до 50%",
+            SocketCommand::SetLoad { percent: 50 },
+        ),
+        (
+            "Выключение розетки",
+            SocketCommand::TurnOff { outlet: None },
+        ),
         ("Запрос состояния после выключения", SocketCommand::Power),
+        ("Запрос накопленной энергии", SocketCommand::Energy),
+        ("Состояние обновления прошивки", SocketCommand::UpdateStatus),
     ];
 
     for (description, command) in test_commands {
@@ -82,12 +93,28 @@ fn format_response(response: &smart_home_lib::protocol::socket_protocol::SocketR
             let device_id = data.device_id.as_deref().unwrap_or("unknown");
 
             format!(
-                "✅ Успех | Устройство: {} | Статус: {} | Мощность: {:.1}W",
-                device_id, status, data.power
+                "✅ Успех | Устройство: {} | Статус: {} | Мощность: {:.1}W | Энергия: {:.3}Wh",
+                device_id, status, data.power, data.energy_wh
             )
         }
-        SocketResponse::Error { message } => {
-            format!("❌ Ошибка: {}", message)
+        SocketResponse::Error { code, message } => {
+            format!("❌ Ошибка ({}): {}", code, message)
         }
+        SocketResponse::Tripped(data) => {
+            let device_id = data.device_id.as_deref().unwrap_or("unknown");
+            format!(
+                "⚠️ Сработала защита от перегрузки | Устройство: {}",
+                device_id
+            )
+        }
+        SocketResponse::Pong => "🏓 Pong".to_string(),
+        SocketResponse::Update(progress) => format!("🔄 {}", progress),
+        SocketResponse::Descriptor(descriptor) => format!(
+            "📋 {} | Прошивка: {} | Мощность: {:.0}W | Команды: {}",
+            descriptor.device_type,
+            descriptor.firmware,
+            descriptor.power_rating,
+            descriptor.supported_commands.join(", ")
+        ),
     }
 }
@@ -27,6 +27,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Запускаем неблокирующий TCP сервер
     emulator.start().await?;
 
+    // Административный HTTP интерфейс (состояние и неисправности на лету)
+    let _admin = match env::var("EMULATOR_ADMIN_ADDR") {
+        Ok(addr) => Some(emulator.serve_admin(&addr)?),
+        Err(_) => None,
+    };
+
     let actual_addr = emulator.local_addr()?;
     println!("✅ Async эмулятор запущен на TCP адресе: {}", actual_addr);
     println!("🌐 Принимает множественные соединения одновременно");
@@ -24,8 +24,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Запускаем автоматическое обновление
-    kitchen_therm.start();
-    living_therm.start();
+    kitchen_therm.start()?;
+    living_therm.start()?;
 
     println!("✅ Термометры подключены и слушают UDP пакеты");
     println!("💡 Запустите эмуляторы в отдельных терминалах:");
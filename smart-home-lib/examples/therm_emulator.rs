@@ -28,6 +28,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Включаем сетевую отправку
     emulator.connect_to(&target_addr)?;
 
+    // Административный HTTP интерфейс (смена сценария и неисправности на лету)
+    let _admin = match env::var("EMULATOR_ADMIN_ADDR") {
+        Ok(addr) => Some(emulator.serve_admin(&addr)?),
+        Err(_) => None,
+    };
+
     // Запускаем эмулятор в фоне
     emulator.start();
 
@@ -1,23 +1,32 @@
 //! Простой UDP listener для проверки эмулятора
 
-use std::net::UdpSocket;
+use smart_home_lib::controllers::SensorHub;
+use std::thread;
+use std::time::Duration;
 
-fn main() -> std::io::Result<()> {
+fn main() {
     println!("🎧 UDP Listener запущен на 127.0.0.1:8080");
     println!("Ожидание пакетов от эмулятора...\n");
 
-    let socket = UdpSocket::bind("127.0.0.1:8080")?;
-    let mut buf = [0; 1024];
+    // Датчики не зарегистрированы - все пакеты попадают в общий обработчик
+    let mut hub = SensorHub::new("127.0.0.1:8080").with_fallback(|data| {
+        println!(
+            "📦 Получен пакет от {}: {:.1}°C",
+            data.device_id.as_deref().unwrap_or("неизвестного датчика"),
+            data.temperature
+        );
+    });
+    hub.start();
 
     loop {
-        match socket.recv_from(&mut buf) {
-            Ok((size, src)) => {
-                let data = String::from_utf8_lossy(&buf[..size]);
-                println!("📦 Получен пакет от {}: {}", src, data);
-            }
-            Err(e) => {
-                eprintln!("❌ Ошибка получения: {}", e);
-            }
+        thread::sleep(Duration::from_secs(10));
+        let stats = hub.stats();
+        println!(
+            "📊 Принято: {}, не разобрано: {}",
+            stats.received, stats.malformed
+        );
+        if let Some(error) = hub.health().last_error {
+            eprintln!("❌ Ошибка получения: {}", error);
         }
     }
 }
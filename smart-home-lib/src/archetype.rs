@@ -0,0 +1,293 @@
+//! Типовые комнаты с набором устройств по умолчанию
+//!
+//! `RoomArchetype` описывает, что обычно есть в кухне, спальне или ванной:
+//! ключи устройств, их параметры и метаданные (приоритет для энергобюджета,
+//! теги). Ключи одинаковы во всех типовых комнатах (`temp` - термометр,
+//! `water_valve` - клапан), поэтому группы и правила можно писать один раз.
+//! Типовую комнату можно дополнить обычными методами `RoomBuilder`.
+
+use crate::builder::{RoomBuilder, SmartHouseBuilder};
+#[cfg(feature = "net")]
+use crate::controllers::{DeviceController, SocketController, ThermController};
+use crate::metadata::DeviceMetadata;
+use crate::room::Room;
+use crate::room_kind::RoomKind;
+use std::fmt;
+#[cfg(feature = "net")]
+use std::net::SocketAddr;
+#[cfg(feature = "net")]
+use std::time::Duration;
+
+/// Тип комнаты
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoomArchetype {
+    /// Чайник, холодильник, термометр, датчик протечки под мойкой и клапан
+    Kitchen,
+    /// Свет, обогреватель, термометр и жалюзи (с фичей `blinds`)
+    Bedroom,
+    /// Полотенцесушитель, термометр, датчик протечки и клапан
+    Bathroom,
+}
+
+/// Устройство типовой комнаты
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchetypeDevice {
+    /// Розетка с номинальной мощностью, Вт
+    Socket(f64),
+    /// Термометр с начальной температурой
+    Therm(f64),
+    /// Жалюзи в положении, %
+    #[cfg(feature = "blinds")]
+    Blinds(f64),
+    /// Датчик протечки
+    #[cfg(feature = "leak")]
+    Leak,
+    /// Водяной кран
+    #[cfg(feature = "valve")]
+    Valve,
+}
+
+/// Элемент типовой комнаты: ключ, устройство, приоритет и теги
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArchetypeItem {
+    pub key: &'static str,
+    pub device: ArchetypeDevice,
+    pub priority: u8,
+    pub tags: &'static [&'static str],
+}
+
+impl ArchetypeItem {
+    const fn new(
+        key: &'static str,
+        device: ArchetypeDevice,
+        priority: u8,
+        tags: &'static [&'static str],
+    ) -> Self {
+        Self {
+            key,
+            device,
+            priority,
+            tags,
+        }
+    }
+
+    /// Метаданные элемента
+    pub fn metadata(&self) -> DeviceMetadata {
+        self.tags.iter().fold(
+            DeviceMetadata::new().with_priority(self.priority),
+            |m, tag| m.with_tag(tag),
+        )
+    }
+}
+
+const KITCHEN: &[ArchetypeItem] = &[
+    ArchetypeItem::new("kettle", ArchetypeDevice::Socket(2000.0), 30, &["heavy"]),
+    ArchetypeItem::new(
+        "fridge",
+        ArchetypeDevice::Socket(150.0),
+        100,
+        &["essential"],
+    ),
+    ArchetypeItem::new("temp", ArchetypeDevice::Therm(21.0), 50, &[]),
+    #[cfg(feature = "leak")]
+    ArchetypeItem::new("leak_sink", ArchetypeDevice::Leak, 100, &["water"]),
+    #[cfg(feature = "valve")]
+    ArchetypeItem::new("water_valve", ArchetypeDevice::Valve, 100, &["water"]),
+];
+
+const BEDROOM: &[ArchetypeItem] = &[
+    ArchetypeItem::new("lamp", ArchetypeDevice::Socket(60.0), 60, &["light"]),
+    ArchetypeItem::new(
+        "heater",
+        ArchetypeDevice::Socket(1500.0),
+        40,
+        &["heater", "heavy"],
+    ),
+    ArchetypeItem::new("temp", ArchetypeDevice::Therm(20.0), 50, &[]),
+    #[cfg(feature = "blinds")]
+    ArchetypeItem::new("blinds", ArchetypeDevice::Blinds(0.0), 50, &["light"]),
+];
+
+const BATHROOM: &[ArchetypeItem] = &[
+    ArchetypeItem::new(
+        "towel_dryer",
+        ArchetypeDevice::Socket(300.0),
+        40,
+        &["heater"],
+    ),
+    ArchetypeItem::new("temp", ArchetypeDevice::Therm(24.0), 50, &[]),
+    #[cfg(feature = "leak")]
+    ArchetypeItem::new("leak_floor", ArchetypeDevice::Leak, 100, &["water"]),
+    #[cfg(feature = "valve")]
+    ArchetypeItem::new("water_valve", ArchetypeDevice::Valve, 100, &["water"]),
+];
+
+impl RoomArchetype {
+    /// Все типы комнат
+    pub const ALL: [RoomArchetype; 3] = [Self::Kitchen, Self::Bedroom, Self::Bathroom];
+
+    /// Ключ комнаты по умолчанию
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Kitchen => "kitchen",
+            Self::Bedroom => "bedroom",
+            Self::Bathroom => "bathroom",
+        }
+    }
+
+    /// Элементы комнаты в порядке добавления
+    pub fn items(&self) -> &'static [ArchetypeItem] {
+        match self {
+            Self::Kitchen => KITCHEN,
+            Self::Bedroom => BEDROOM,
+            Self::Bathroom => BATHROOM,
+        }
+    }
+
+    /// Тип помещения (у спальни нет особых климатических норм)
+    pub fn kind(&self) -> Option<RoomKind> {
+        match self {
+            Self::Kitchen => Some(RoomKind::Kitchen),
+            Self::Bedroom => None,
+            Self::Bathroom => Some(RoomKind::Bathroom),
+        }
+    }
+
+    /// Комната с локальными устройствами
+    pub fn build(&self) -> Room {
+        RoomBuilder::new().archetype(*self).build()
+    }
+}
+
+impl fmt::Display for RoomArchetype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl RoomBuilder {
+    /// Локальные устройства типовой комнаты с метаданными и ее тип помещения
+    pub fn archetype(self, archetype: RoomArchetype) -> Self {
+        archetype
+            .items()
+            .iter()
+            .fold(self.archetype_kind(archetype), |builder, item| {
+                let builder = match item.device {
+                    ArchetypeDevice::Socket(power) => builder.socket(item.key, power),
+                    ArchetypeDevice::Therm(temperature) => builder.therm(item.key, temperature),
+                    #[cfg(feature = "blinds")]
+                    ArchetypeDevice::Blinds(position) => builder.blinds(item.key, position),
+                    #[cfg(feature = "leak")]
+                    ArchetypeDevice::Leak => builder.leak_sensor(item.key),
+                    #[cfg(feature = "valve")]
+                    ArchetypeDevice::Valve => builder.valve(item.key),
+                };
+                builder.metadata(item.key, item.metadata())
+            })
+    }
+
+    /// Типовая комната с контроллерами розеток и термометров
+    ///
+    /// Розетки получают адреса `base`, `base + 1`... по порядку, термометры
+    /// слушают следующие порты того же IP. `timeout` - таймаут команд
+    /// розеткам и допустимый возраст показаний термометров. Остальные
+    /// устройства локальные.
+    #[cfg(feature = "net")]
+    pub fn archetype_controllers(
+        self,
+        archetype: RoomArchetype,
+        base: SocketAddr,
+        timeout: Duration,
+    ) -> Self {
+        let mut port = base.port();
+        let mut next_addr = || {
+            let addr = SocketAddr::new(base.ip(), port);
+            port = port.wrapping_add(1);
+            addr
+        };
+
+        let builder = self.archetype_kind(archetype);
+        archetype.items().iter().fold(builder, |builder, item| {
+            let builder = match item.device {
+                ArchetypeDevice::Socket(power) => {
+                    let controller = SocketController::new(next_addr(), power, timeout);
+                    builder.controller(item.key, DeviceController::Socket(controller))
+                }
+                ArchetypeDevice::Therm(temperature) => {
+                    let listen_addr = next_addr().to_string();
+                    let controller = ThermController::new(temperature, &listen_addr, timeout);
+                    builder.controller(item.key, DeviceController::Therm(controller))
+                }
+                #[cfg(feature = "blinds")]
+                ArchetypeDevice::Blinds(position) => builder.blinds(item.key, position),
+                #[cfg(feature = "leak")]
+                ArchetypeDevice::Leak => builder.leak_sensor(item.key),
+                #[cfg(feature = "valve")]
+                ArchetypeDevice::Valve => builder.valve(item.key),
+            };
+            builder.metadata(item.key, item.metadata())
+        })
+    }
+
+    fn archetype_kind(self, archetype: RoomArchetype) -> Self {
+        match archetype.kind() {
+            Some(kind) => self.kind(kind),
+            None => self,
+        }
+    }
+}
+
+impl SmartHouseBuilder {
+    /// Типовая комната с локальными устройствами
+    pub fn archetype(self, key: &str, archetype: RoomArchetype) -> Self {
+        self.with_room(key, archetype.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::house::SmartHouse;
+
+    #[test]
+    fn archetype_rooms() {
+        let house = SmartHouse::builder()
+            .archetype("kitchen", RoomArchetype::Kitchen)
+            .room("bath", |r| {
+                r.archetype(RoomArchetype::Bathroom).socket("radio", 10.0)
+            })
+            .build();
+
+        let kitchen = house.room("kitchen").unwrap();
+        assert_eq!(
+            kitchen.devices_count(),
+            RoomArchetype::Kitchen.items().len()
+        );
+        assert_eq!(kitchen.metadata("fridge").priority, 100);
+        assert!(kitchen.metadata("kettle").has_tag("heavy"));
+
+        let bath = house.room("bath").unwrap();
+        #[cfg(feature = "valve")]
+        assert!(bath.device("water_valve").is_some());
+        assert!(bath.device("radio").is_some());
+        assert_eq!(bath.kind(), Some(RoomKind::Bathroom));
+
+        for archetype in RoomArchetype::ALL {
+            assert!(archetype.build().device("temp").is_some(), "{}", archetype);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "net", feature = "leak", feature = "valve"))]
+    fn archetype_with_controllers() {
+        let base: SocketAddr = "127.0.0.1:4100".parse().unwrap();
+        let room = RoomBuilder::new()
+            .archetype_controllers(RoomArchetype::Kitchen, base, Duration::from_secs(3))
+            .build();
+
+        // чайник, холодильник и термометр
+        assert_eq!(room.controllers_count(), 3);
+        assert_eq!(room.devices_count(), 2);
+        assert_eq!(room.metadata("kettle").priority, 30);
+    }
+}
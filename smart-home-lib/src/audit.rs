@@ -0,0 +1,386 @@
+//! Журнал команд устройствам
+//!
+//! `AuditLog` хранит каждую команду контроллеру, выполненную через
+//! `SmartHouse::execute`: кто ее отправил (`CommandOrigin`), результат и
+//! время выполнения. Журнал только дополняется; выборка - `AuditLog::query`.
+//! Журнал, открытый через `AuditLog::open`, дописывает записи в файл по
+//! одной JSON строке и при следующем запуске читает их обратно - так можно
+//! выяснить, кто включил обогреватель в три часа ночи.
+
+use crate::clock::{SharedClock, system_clock};
+use crate::controllers::{ControllerError, DeviceCommand, DeviceOutput, ErrorContext};
+use crate::house::{SmartHouse, SmartHouseError, SmartHouseResult};
+use crate::path::{DevicePath, DevicePattern};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+/// Кто отправил команду
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum CommandOrigin {
+    /// Пользователь (CLI, панель)
+    User(String),
+    /// Правило автоматизации
+    Rule(String),
+    /// Расписание
+    Schedule(String),
+    /// Служба библиотеки (сверка, климат, энергобюджет)
+    System(String),
+}
+
+impl fmt::Display for CommandOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User(name) => write!(f, "user:{}", name),
+            Self::Rule(name) => write!(f, "rule:{}", name),
+            Self::Schedule(name) => write!(f, "schedule:{}", name),
+            Self::System(name) => write!(f, "system:{}", name),
+        }
+    }
+}
+
+/// Запись журнала
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Время отправки команды, мс с Unix epoch
+    pub timestamp_ms: u64,
+    pub origin: CommandOrigin,
+    pub path: DevicePath,
+    /// Команда (`DeviceCommand` в текстовом виде)
+    pub command: String,
+    pub success: bool,
+    /// Результат команды или текст ошибки
+    pub result: String,
+    /// Время выполнения, мс
+    pub latency_ms: u64,
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "t={} {} {} {}: {} ({} мс)",
+            self.timestamp_ms,
+            self.origin,
+            self.path,
+            self.command,
+            if self.success { "ok" } else { "ошибка" },
+            self.latency_ms
+        )?;
+        if !self.result.is_empty() {
+            write!(f, " {}", self.result)?;
+        }
+        Ok(())
+    }
+}
+
+/// Условия выборки из журнала; пустой запрос выбирает все записи
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pattern: Option<DevicePattern>,
+    origin: Option<CommandOrigin>,
+    range: Option<Range<u64>>,
+    failed_only: bool,
+}
+
+impl AuditQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: только устройства, подходящие под шаблон пути
+    pub fn with_pattern(mut self, pattern: DevicePattern) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Builder: только команды от `origin`
+    pub fn with_origin(mut self, origin: CommandOrigin) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Builder: только команды, отправленные в интервале (мс с Unix epoch)
+    pub fn with_range(mut self, range: Range<u64>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Builder: только неудачные команды
+    pub fn failed_only(mut self) -> Self {
+        self.failed_only = true;
+        self
+    }
+
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.pattern
+            .as_ref()
+            .is_none_or(|pattern| pattern.matches(&entry.path))
+            && self.origin.as_ref().is_none_or(|o| *o == entry.origin)
+            && self
+                .range
+                .as_ref()
+                .is_none_or(|range| range.contains(&entry.timestamp_ms))
+            && (!self.failed_only || !entry.success)
+    }
+}
+
+/// Журнал команд; клоны работают с одним журналом
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    entries: Arc<Mutex<Vec<AuditEntry>>>,
+    /// Файл, в который дописываются записи
+    file: Option<Arc<Mutex<File>>>,
+    clock: SharedClock,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuditLog {
+    /// Журнал в памяти
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Vec::new())),
+            file: None,
+            clock: system_clock(),
+        }
+    }
+
+    /// Журнал в файле JSON Lines: существующие записи читаются, новые дописываются
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut entries = Vec::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry = serde_json::from_str(&line)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                entries.push(entry);
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            entries: Arc::new(Mutex::new(entries)),
+            file: Some(Arc::new(Mutex::new(file))),
+            clock: system_clock(),
+        })
+    }
+
+    /// Builder: источник времени для записей
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Текущее время журнала, мс с Unix epoch
+    pub fn now_ms(&self) -> u64 {
+        self.clock.now_ms()
+    }
+
+    /// Добавляет запись
+    ///
+    /// Запись остается в памяти, даже если дописать ее в файл не удалось.
+    pub fn record(&self, entry: AuditEntry) -> io::Result<()> {
+        let line = serde_json::to_string(&entry).map_err(io::Error::other);
+        self.lock().push(entry);
+
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(file, "{}", line?)?;
+        file.flush()
+    }
+
+    /// Все записи в порядке добавления
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.lock().clone()
+    }
+
+    /// Записи, подходящие под запрос, в порядке добавления
+    pub fn query(&self, query: &AuditQuery) -> Vec<AuditEntry> {
+        self.lock()
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .cloned()
+            .collect()
+    }
+
+    /// Количество записей
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Журнал пуст
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<AuditEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SmartHouse {
+    /// Выполняет команду на контроллере и записывает ее в журнал дома
+    ///
+    /// Журнал задается `SmartHouse::with_audit`; без него команда просто
+    /// выполняется. Ошибка контроллера содержит путь и адрес устройства.
+    pub async fn execute(
+        &mut self,
+        path: &DevicePath,
+        command: DeviceCommand,
+        origin: CommandOrigin,
+    ) -> SmartHouseResult<DeviceOutput> {
+        let audit = self.audit().cloned();
+        let controller = self.controller_at_mut(path)?;
+
+        let timestamp_ms = audit.as_ref().map_or(0, AuditLog::now_ms);
+        let started = Instant::now();
+        let result = controller.execute(command).await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        if let Some(audit) = audit {
+            let entry = AuditEntry {
+                timestamp_ms,
+                origin,
+                path: path.clone(),
+                command: command.to_string(),
+                success: result.is_ok(),
+                result: match &result {
+                    Ok(DeviceOutput::Done) => String::new(),
+                    Ok(DeviceOutput::Temperature(t)) => t.to_string(),
+                    Ok(DeviceOutput::Power(p)) => p.to_string(),
+                    Err(e) => e.to_string(),
+                },
+                latency_ms,
+            };
+            if let Err(e) = audit.record(entry) {
+                eprintln!("⚠️ Не удалось записать журнал команд: {}", e);
+            }
+        }
+
+        result.map_err(|e| {
+            let context = ErrorContext::new(path.room(), path.key(), controller.address(), command);
+            SmartHouseError::from(ControllerError::new(context, e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::controllers::ThermController;
+    use std::time::Duration;
+
+    fn house(audit: AuditLog) -> SmartHouse {
+        let mut house = SmartHouse::default().with_audit(audit);
+        house.add_room("hall", crate::room::Room::new());
+        let therm = ThermController::new(21.0, "127.0.0.1:0", Duration::from_secs(5));
+        therm.feed().push(21.5);
+        house
+            .room_mut("hall")
+            .unwrap()
+            .add_controller("therm", therm.into());
+        house
+    }
+
+    #[tokio::test]
+    async fn records_commands() {
+        let clock = Arc::new(MockClock::starting_at(1_000));
+        let audit = AuditLog::new().with_clock(clock.clone());
+        let mut house = house(audit.clone());
+        let therm: DevicePath = "hall/therm".parse().unwrap();
+
+        let output = house
+            .execute(
+                &therm,
+                DeviceCommand::ReadTemperature,
+                CommandOrigin::User("alice".to_string()),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(output, DeviceOutput::Temperature(_)));
+
+        clock.advance(Duration::from_secs(1));
+        let error = house
+            .execute(
+                &therm,
+                DeviceCommand::TurnOn,
+                CommandOrigin::Rule("night".to_string()),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, SmartHouseError::Controller(_)));
+
+        let missing: DevicePath = "hall/heater".parse().unwrap();
+        assert!(matches!(
+            house
+                .execute(
+                    &missing,
+                    DeviceCommand::TurnOn,
+                    CommandOrigin::User("bob".to_string())
+                )
+                .await,
+            Err(SmartHouseError::DeviceNotFound(_, _))
+        ));
+
+        let entries = audit.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].timestamp_ms, 1_000);
+        assert_eq!(entries[0].result, "21.5°C");
+        assert!(entries[0].success);
+        assert!(!entries[1].success);
+        assert_eq!(entries[1].to_string().split(' ').nth(1), Some("rule:night"));
+
+        let failed = audit.query(&AuditQuery::new().failed_only());
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].command, "включение");
+        let by_user = AuditQuery::new()
+            .with_origin(CommandOrigin::User("alice".to_string()))
+            .with_pattern("*/therm".parse().unwrap())
+            .with_range(0..1_500);
+        assert_eq!(audit.query(&by_user).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn persists_to_file() {
+        let path = std::env::temp_dir().join(format!("audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let audit = AuditLog::open(&path).unwrap();
+        let mut house = house(audit);
+        let therm: DevicePath = "hall/therm".parse().unwrap();
+        house
+            .execute(
+                &therm,
+                DeviceCommand::ReadTemperature,
+                CommandOrigin::Schedule("morning".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let reopened = AuditLog::open(&path).unwrap();
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(
+            reopened.entries()[0].origin,
+            CommandOrigin::Schedule("morning".to_string())
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+//! Готовые сценарии автоматизации
+//!
+//! `LeakShutoff` - перекрытие воды при протечке: при первом событии
+//! протечки от датчика закрывает запорный клапан и рассылает уведомления.
+//! Повторные события той же протечки ничего не делают, пока клапан
+//! закрыт; если закрыть его не удалось, следующее событие протечки
+//! повторяет попытку. После события "сухо" следующая протечка снова
+//! закрывает клапан. Открывает клапан только человек.
+
+use crate::controllers::{ValveController, ValveError};
+use crate::notifications::{Notification, Notifier};
+use crate::protocol::leak_protocol::LeakEvent;
+use std::collections::HashSet;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Что сделал сценарий при протечке
+#[derive(Debug)]
+pub struct LeakIncident {
+    /// Датчик, сообщивший о протечке
+    pub sensor: Option<String>,
+    /// Результат закрытия клапана
+    pub valve: Result<(), ValveError>,
+    /// Доставлено уведомлений
+    pub notified: usize,
+    /// Уведомлений, которые не удалось доставить
+    pub failed_notifications: usize,
+}
+
+/// Перекрытие воды при протечке
+pub struct LeakShutoff {
+    valve: ValveController,
+    notifiers: Vec<Box<dyn Notifier>>,
+    /// Место установки для текста уведомления
+    location: Option<String>,
+    /// Датчики, протечка которых уже перекрыта клапаном
+    handled: HashSet<Option<String>>,
+}
+
+impl LeakShutoff {
+    /// Создает сценарий для клапана `valve`
+    pub fn new(valve: ValveController) -> Self {
+        Self {
+            valve,
+            notifiers: Vec::new(),
+            location: None,
+            handled: HashSet::new(),
+        }
+    }
+
+    /// Builder: добавляет канал уведомлений
+    pub fn with_notifier(mut self, notifier: impl Notifier + 'static) -> Self {
+        self.notifiers.push(Box::new(notifier));
+        self
+    }
+
+    /// Builder: место установки датчика ("ванная")
+    pub fn with_location(mut self, location: &str) -> Self {
+        self.location = Some(location.to_string());
+        self
+    }
+
+    /// Клапан сценария
+    pub fn valve(&self) -> &ValveController {
+        &self.valve
+    }
+
+    /// Обрабатывает событие датчика; возвращает происшествие при новой протечке
+    pub async fn handle(&mut self, event: &LeakEvent) -> Option<LeakIncident> {
+        if !event.leak {
+            self.handled.remove(&event.device_id);
+            return None;
+        }
+        if self.handled.contains(&event.device_id) {
+            return None;
+        }
+
+        let valve = self.valve.close().await;
+        // Протечка считается перекрытой только после закрытия клапана
+        if valve.is_ok() {
+            self.handled.insert(event.device_id.clone());
+        }
+        let notification = self.notification(event, &valve);
+
+        let mut notified = 0;
+        let mut failed_notifications = 0;
+        for notifier in &self.notifiers {
+            match notifier.notify(&notification).await {
+                Ok(()) => notified += 1,
+                Err(e) => {
+                    eprintln!("⚠️ Перекрытие воды: уведомление не отправлено: {}", e);
+                    failed_notifications += 1;
+                }
+            }
+        }
+
+        Some(LeakIncident {
+            sensor: event.device_id.clone(),
+            valve,
+            notified,
+            failed_notifications,
+        })
+    }
+
+    /// Запускает сценарий на событиях датчиков
+    pub fn spawn(mut self, mut events: broadcast::Receiver<LeakEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        self.handle(&event).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        eprintln!("⚠️ Перекрытие воды: пропущено событий: {}", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    fn notification(&self, event: &LeakEvent, valve: &Result<(), ValveError>) -> Notification {
+        let place = match (&self.location, &event.device_id) {
+            (Some(location), _) => location.clone(),
+            (None, Some(sensor)) => format!("датчик {}", sensor),
+            (None, None) => "датчик протечки".to_string(),
+        };
+        let body = match valve {
+            Ok(()) => format!("{}: обнаружена протечка, вода перекрыта", place),
+            Err(e) => format!(
+                "{}: обнаружена протечка, перекрыть воду не удалось: {}",
+                place, e
+            ),
+        };
+        Notification::new("Протечка воды", &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulators::ValveEmulator;
+    use crate::notifications::{NotifyError, NotifyFuture};
+    use crate::protocol::transport::InMemory;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Канал, запоминающий уведомления
+    #[derive(Clone, Default)]
+    struct Inbox(Arc<Mutex<Vec<Notification>>>);
+
+    impl Notifier for Inbox {
+        fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+            self.0.lock().unwrap().push(notification.clone());
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    struct Broken;
+
+    impl Notifier for Broken {
+        fn notify<'a>(&'a self, _notification: &'a Notification) -> NotifyFuture<'a> {
+            Box::pin(async { Err(NotifyError::Timeout) })
+        }
+    }
+
+    fn event(leak: bool, sequence: u64) -> LeakEvent {
+        LeakEvent {
+            leak,
+            sequence,
+            device_id: Some("bath_leak".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn closes_valve_once_per_leak() {
+        let transport = InMemory::new();
+        let mut emulator = ValveEmulator::new("10.0.0.8:7100");
+        emulator.start_in_memory(&transport).await.unwrap();
+        let valve = ValveController::new(emulator.local_addr().unwrap(), Duration::from_secs(1))
+            .with_transport(Arc::new(transport));
+
+        let inbox = Inbox::default();
+        let mut shutoff = LeakShutoff::new(valve)
+            .with_notifier(inbox.clone())
+            .with_notifier(Broken)
+            .with_location("ванная");
+
+        assert!(shutoff.handle(&event(false, 1)).await.is_none());
+        assert!(emulator.is_open());
+
+        let incident = shutoff.handle(&event(true, 2)).await.unwrap();
+        assert!(incident.valve.is_ok());
+        assert_eq!(incident.sensor.as_deref(), Some("bath_leak"));
+        assert_eq!((incident.notified, incident.failed_notifications), (1, 1));
+        assert!(!emulator.is_open());
+        assert_eq!(
+            inbox.0.lock().unwrap()[0],
+            Notification::new(
+                "Протечка воды",
+                "ванная: обнаружена протечка, вода перекрыта"
+            )
+        );
+
+        // Та же протечка - без повторных уведомлений
+        assert!(shutoff.handle(&event(true, 3)).await.is_none());
+        assert!(shutoff.handle(&event(false, 4)).await.is_none());
+        assert!(shutoff.handle(&event(true, 5)).await.is_some());
+        assert_eq!(inbox.0.lock().unwrap().len(), 2);
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn reports_valve_failure() {
+        let transport = InMemory::new();
+        let valve = ValveController::new("10.0.0.9:7100".parse().unwrap(), Duration::from_secs(1))
+            .with_transport(Arc::new(transport.clone()));
+        let inbox = Inbox::default();
+        let mut shutoff = LeakShutoff::new(valve).with_notifier(inbox.clone());
+
+        let incident = shutoff.handle(&event(true, 1)).await.unwrap();
+        assert!(incident.valve.is_err());
+        let body = inbox.0.lock().unwrap()[0].body.clone();
+        assert!(
+            body.starts_with("датчик bath_leak: обнаружена протечка, перекрыть воду не удалось")
+        );
+
+        // Клапан стал доступен: следующее событие той же протечки закрывает его
+        let mut emulator = ValveEmulator::new("10.0.0.9:7100");
+        emulator.start_in_memory(&transport).await.unwrap();
+        let incident = shutoff.handle(&event(true, 2)).await.unwrap();
+        assert!(incident.valve.is_ok());
+        assert!(!emulator.is_open());
+        assert!(shutoff.handle(&event(true, 3)).await.is_none());
+
+        emulator.stop().await;
+    }
+}
@@ -0,0 +1,255 @@
+//! Построители дома и комнат
+//!
+//! Типизированная альтернатива макросам `house!` и `room!`: удобна там,
+//! где макросы неудобны - в циклах и при сборке дома из конфигурации.
+//! Результат тот же, что у макросов с теми же элементами.
+
+use crate::controllers::DeviceController;
+#[cfg(feature = "net")]
+use crate::controllers::{SocketController, ThermController};
+#[cfg(feature = "leak")]
+use crate::devices::LeakSensor;
+#[cfg(feature = "blinds")]
+use crate::devices::SmartBlinds;
+#[cfg(feature = "camera")]
+use crate::devices::SmartCamera;
+#[cfg(feature = "valve")]
+use crate::devices::SmartValve;
+use crate::devices::{Device, SmartMeter, SmartSocket, SmartTherm};
+use crate::group::DeviceGroup;
+use crate::house::SmartHouse;
+use crate::metadata::DeviceMetadata;
+use crate::room::Room;
+use crate::room_kind::RoomKind;
+#[cfg(feature = "net")]
+use std::net::SocketAddr;
+#[cfg(feature = "net")]
+use std::time::Duration;
+
+/// Построитель комнаты
+#[derive(Default)]
+pub struct RoomBuilder {
+    room: Room,
+}
+
+impl RoomBuilder {
+    /// Создает построитель пустой комнаты
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Локальная розетка мощностью `power_rating` Вт
+    pub fn socket(self, key: &str, power_rating: f64) -> Self {
+        self.device(key, Device::Socket(SmartSocket::new(power_rating)))
+    }
+
+    /// Локальный термометр с начальной температурой
+    pub fn therm(self, key: &str, temperature: f64) -> Self {
+        self.device(key, Device::Therm(SmartTherm::new(temperature)))
+    }
+
+    /// Локальная камера с разрешением `width`x`height`
+    #[cfg(feature = "camera")]
+    pub fn camera(self, key: &str, width: u32, height: u32) -> Self {
+        self.device(key, Device::Camera(SmartCamera::new(width, height)))
+    }
+
+    /// Локальные жалюзи в положении `position` (0% - закрыты)
+    #[cfg(feature = "blinds")]
+    pub fn blinds(self, key: &str, position: f64) -> Self {
+        self.device(key, Device::Blinds(SmartBlinds::new(position)))
+    }
+
+    /// Локальный датчик протечки (сухо)
+    #[cfg(feature = "leak")]
+    pub fn leak_sensor(self, key: &str) -> Self {
+        self.device(key, Device::Leak(LeakSensor::new()))
+    }
+
+    /// Локальный запорный клапан (открыт)
+    #[cfg(feature = "valve")]
+    pub fn valve(self, key: &str) -> Self {
+        self.device(key, Device::Valve(SmartValve::new()))
+    }
+
+    /// Локальный счетчик электроэнергии с начальным показанием, кВт·ч
+    pub fn meter(self, key: &str, total_kwh: f64) -> Self {
+        self.device(key, Device::Meter(SmartMeter::new(total_kwh)))
+    }
+
+    /// Произвольное локальное устройство
+    pub fn device(mut self, key: &str, device: Device) -> Self {
+        self.room.add_device(key, device);
+        self
+    }
+
+    /// TCP контроллер розетки
+    #[cfg(feature = "net")]
+    pub fn socket_controller(
+        self,
+        key: &str,
+        addr: SocketAddr,
+        power_rating: f64,
+        timeout: Duration,
+    ) -> Self {
+        let controller = SocketController::new(addr, power_rating, timeout);
+        self.controller(key, DeviceController::Socket(controller))
+    }
+
+    /// UDP контроллер термометра (прием показаний запускает `start`)
+    #[cfg(feature = "net")]
+    pub fn therm_controller(
+        self,
+        key: &str,
+        initial_temp: f64,
+        listen_addr: &str,
+        max_age: Duration,
+    ) -> Self {
+        let controller = ThermController::new(initial_temp, listen_addr, max_age);
+        self.controller(key, DeviceController::Therm(controller))
+    }
+
+    /// Произвольный контроллер
+    pub fn controller(mut self, key: &str, controller: DeviceController) -> Self {
+        self.room.add_controller(key, controller);
+        self
+    }
+
+    /// Метаданные элемента, добавленного ранее
+    pub fn metadata(mut self, key: &str, metadata: DeviceMetadata) -> Self {
+        self.room.set_metadata(key, metadata);
+        self
+    }
+
+    /// Тип помещения
+    pub fn kind(mut self, kind: RoomKind) -> Self {
+        self.room.set_kind(Some(kind));
+        self
+    }
+
+    /// Собирает комнату
+    pub fn build(self) -> Room {
+        self.room
+    }
+}
+
+/// Построитель умного дома
+#[derive(Default)]
+pub struct SmartHouseBuilder {
+    house: SmartHouse,
+}
+
+impl SmartHouseBuilder {
+    /// Создает построитель пустого дома
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Комната, описанная построителем
+    pub fn room(self, key: &str, build: impl FnOnce(RoomBuilder) -> RoomBuilder) -> Self {
+        self.with_room(key, build(RoomBuilder::new()).build())
+    }
+
+    /// Готовая комната
+    pub fn with_room(mut self, key: &str, room: Room) -> Self {
+        self.house.add_room(key, room);
+        self
+    }
+
+    /// Этаж: комнаты и группа с их устройствами (как `floor` в `house!`)
+    pub fn floor(
+        mut self,
+        name: &str,
+        build: impl FnOnce(SmartHouseBuilder) -> SmartHouseBuilder,
+    ) -> Self {
+        let mut floor = build(SmartHouseBuilder::new()).build();
+        let keys = floor.rooms_keys();
+        for key in &keys {
+            if let Some(room) = floor.remove_room(key) {
+                self.house.add_room(key, room);
+            }
+        }
+        for group in floor.groups_keys() {
+            if let Some(members) = floor.remove_group(&group) {
+                self.house.add_group(&group, members);
+            }
+        }
+        self.house.add_room_group(name, &keys);
+        self
+    }
+
+    /// Группа устройств
+    pub fn group(mut self, name: &str, group: DeviceGroup) -> Self {
+        self.house.add_group(name, group);
+        self
+    }
+
+    /// Собирает дом
+    pub fn build(self) -> SmartHouse {
+        self.house
+    }
+}
+
+impl SmartHouse {
+    /// Построитель дома
+    pub fn builder() -> SmartHouseBuilder {
+        SmartHouseBuilder::new()
+    }
+}
+
+impl Room {
+    /// Построитель комнаты
+    pub fn builder() -> RoomBuilder {
+        RoomBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::Reporter;
+
+    #[test]
+    fn matches_macros() {
+        let built = SmartHouse::builder()
+            .room("kitchen", |r| {
+                r.socket("kettle", 2000.0).therm("temp", 22.5)
+            })
+            .with_room("hall", Room::builder().socket("lamp", 60.0).build())
+            .build();
+
+        let expected = crate::house! {
+            "kitchen" => { "kettle" => socket(2000.0), "temp" => therm(22.5) },
+            "hall" => { "lamp" => socket(60.0) },
+        };
+        assert_eq!(built.report(), expected.report());
+    }
+
+    #[test]
+    #[cfg(feature = "net")]
+    fn controllers_and_metadata() {
+        let addr: SocketAddr = "127.0.0.1:3001".parse().unwrap();
+        let room = Room::builder()
+            .socket_controller("kettle", addr, 2000.0, Duration::from_secs(3))
+            .therm_controller("therm", 20.0, "127.0.0.1:0", Duration::from_secs(5))
+            .metadata("kettle", DeviceMetadata::new().with_priority(90))
+            .build();
+
+        assert_eq!(room.controllers_count(), 2);
+        assert_eq!(room.metadata("kettle").priority, 90);
+    }
+
+    #[test]
+    fn floors_in_loop() {
+        let mut builder = SmartHouse::builder();
+        for floor in 1..=2 {
+            builder = builder.floor(&format!("floor{}", floor), |f| {
+                f.room(&format!("room{}", floor), |r| r.socket("lamp", 40.0))
+            });
+        }
+        let house = builder.build();
+
+        assert_eq!(house.rooms_keys(), vec!["room1", "room2"]);
+        assert!(house.group("floor2").unwrap().contains("room2", "lamp"));
+    }
+}
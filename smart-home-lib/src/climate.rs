@@ -0,0 +1,452 @@
+//! Поддержание температуры в комнате
+//!
+//! `RoomClimateController` читает термометр комнаты и управляет ее
+//! обогревателями и охладителями, чтобы держать заданную температуру.
+//! Доступны два закона управления: гистерезис (включить ниже `target -
+//! band`, выключить по достижении `target`) и ПИД-регулятор, который
+//! задает уровень нагрузки розеток (`set_load`). Устройства указываются
+//! ключами комнаты; подходят и контроллеры, и локальные устройства.
+//! Если температура неизвестна, все устройства выключаются.
+
+use crate::controllers::DeviceController;
+use crate::devices::Device;
+use crate::house::SmartHouse;
+use crate::room_kind::RoomKind;
+use crate::units::Celsius;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{MissedTickBehavior, interval};
+
+/// Полоса гистерезиса по умолчанию, °C
+const DEFAULT_BAND: f64 = 0.5;
+
+/// Закон управления
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClimateStrategy {
+    /// Включение ниже `target - band` (выше `target + band` для охлаждения)
+    /// и выключение по достижении цели
+    Hysteresis { band: f64 },
+    /// ПИД-регулятор; выход - нагрузка розеток в процентах
+    Pid { kp: f64, ki: f64, kd: f64 },
+}
+
+/// Что делает климат-контроль
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClimateAction {
+    #[default]
+    Idle,
+    Heating,
+    Cooling,
+}
+
+impl fmt::Display for ClimateAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Idle => write!(f, "ожидание"),
+            Self::Heating => write!(f, "обогрев"),
+            Self::Cooling => write!(f, "охлаждение"),
+        }
+    }
+}
+
+/// Результат одного шага регулирования
+#[derive(Debug, Default)]
+pub struct ClimateReport {
+    /// Температура комнаты (None - термометр недоступен)
+    pub temperature: Option<Celsius>,
+    pub action: ClimateAction,
+    /// Нагрузка включенных устройств, %
+    pub output: u8,
+    /// Ошибки по ключам устройств
+    pub errors: Vec<(String, String)>,
+}
+
+/// Климат-контроль комнаты
+#[derive(Debug, Clone)]
+pub struct RoomClimateController {
+    room: String,
+    therm: String,
+    heaters: Vec<String>,
+    coolers: Vec<String>,
+    target: f64,
+    strategy: ClimateStrategy,
+    action: ClimateAction,
+    /// Накопленная ошибка ПИД-регулятора, °C·с
+    integral: f64,
+    last_error: Option<f64>,
+}
+
+impl RoomClimateController {
+    /// Климат-контроль комнаты `room` по термометру `therm` с целью `target` °C
+    pub fn new(room: &str, therm: &str, target: f64) -> Self {
+        Self {
+            room: room.to_string(),
+            therm: therm.to_string(),
+            heaters: Vec::new(),
+            coolers: Vec::new(),
+            target,
+            strategy: ClimateStrategy::Hysteresis { band: DEFAULT_BAND },
+            action: ClimateAction::Idle,
+            integral: 0.0,
+            last_error: None,
+        }
+    }
+
+    /// Климат-контроль с целью по умолчанию для типа помещения
+    ///
+    /// None, если помещение этого типа не регулируется (улица).
+    pub fn for_kind(room: &str, therm: &str, kind: RoomKind) -> Option<Self> {
+        kind.climate()
+            .target
+            .map(|target| Self::new(room, therm, target))
+    }
+
+    /// Builder: обогреватель комнаты (можно вызывать несколько раз)
+    pub fn with_heater(mut self, key: &str) -> Self {
+        self.heaters.push(key.to_string());
+        self
+    }
+
+    /// Builder: охладитель комнаты (можно вызывать несколько раз)
+    pub fn with_cooler(mut self, key: &str) -> Self {
+        self.coolers.push(key.to_string());
+        self
+    }
+
+    /// Builder: гистерезис с полосой `band` °C
+    pub fn with_hysteresis(mut self, band: f64) -> Self {
+        self.strategy = ClimateStrategy::Hysteresis { band: band.abs() };
+        self
+    }
+
+    /// Builder: ПИД-регулятор
+    pub fn with_pid(mut self, kp: f64, ki: f64, kd: f64) -> Self {
+        self.strategy = ClimateStrategy::Pid { kp, ki, kd };
+        self
+    }
+
+    /// Комната
+    pub fn room(&self) -> &str {
+        &self.room
+    }
+
+    /// Целевая температура
+    pub fn target(&self) -> Celsius {
+        Celsius::new(self.target)
+    }
+
+    /// Меняет целевую температуру
+    pub fn set_target(&mut self, target: f64) {
+        self.target = target;
+    }
+
+    /// Закон управления
+    pub fn strategy(&self) -> ClimateStrategy {
+        self.strategy
+    }
+
+    /// Текущее действие
+    pub fn action(&self) -> ClimateAction {
+        self.action
+    }
+
+    /// Один шаг регулирования; `elapsed` - время с предыдущего шага
+    pub async fn tick(&mut self, house: &mut SmartHouse, elapsed: Duration) -> ClimateReport {
+        let mut report = ClimateReport::default();
+
+        let temperature = match self.read_temperature(house) {
+            Ok(temperature) => temperature,
+            Err(e) => {
+                report.errors.push((self.therm.clone(), e));
+                self.action = ClimateAction::Idle;
+                self.integral = 0.0;
+                self.last_error = None;
+                self.apply(house, 0, 0, &mut report).await;
+                return report;
+            }
+        };
+        report.temperature = Some(temperature);
+
+        let (action, output) = self.decide(temperature.value(), elapsed);
+        self.action = action;
+        report.action = action;
+        report.output = output;
+
+        let (heat, cool) = match action {
+            ClimateAction::Heating => (output, 0),
+            ClimateAction::Cooling => (0, output),
+            ClimateAction::Idle => (0, 0),
+        };
+        self.apply(house, heat, cool, &mut report).await;
+        report
+    }
+
+    /// Периодическое регулирование общего дома; работает, пока задачу не отменят
+    pub async fn run(&mut self, house: Arc<Mutex<SmartHouse>>, period: Duration) {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            let report = self.tick(&mut *house.lock().await, period).await;
+            for (key, e) in &report.errors {
+                eprintln!("⚠️ Климат {}/{}: {}", self.room, key, e);
+            }
+        }
+    }
+
+    /// Выбирает действие и нагрузку по температуре
+    fn decide(&mut self, temperature: f64, elapsed: Duration) -> (ClimateAction, u8) {
+        let can_heat = !self.heaters.is_empty();
+        let can_cool = !self.coolers.is_empty();
+
+        match self.strategy {
+            ClimateStrategy::Hysteresis { band } => {
+                let action = match self.action {
+                    ClimateAction::Heating if temperature < self.target => ClimateAction::Heating,
+                    ClimateAction::Cooling if temperature > self.target => ClimateAction::Cooling,
+                    _ if can_heat && temperature < self.target - band => ClimateAction::Heating,
+                    _ if can_cool && temperature > self.target + band => ClimateAction::Cooling,
+                    _ => ClimateAction::Idle,
+                };
+                let output = if action == ClimateAction::Idle {
+                    0
+                } else {
+                    100
+                };
+                (action, output)
+            }
+            ClimateStrategy::Pid { kp, ki, kd } => {
+                let error = self.target - temperature;
+                let dt = elapsed.as_secs_f64();
+                let derivative = match self.last_error {
+                    Some(last) if dt > 0.0 => (error - last) / dt,
+                    _ => 0.0,
+                };
+                self.last_error = Some(error);
+
+                self.integral += error * dt;
+                // Ограничение интеграла: его вклад не больше полной нагрузки
+                if ki != 0.0 {
+                    let limit = (100.0 / ki).abs();
+                    self.integral = self.integral.clamp(-limit, limit);
+                }
+
+                let signal = kp * error + ki * self.integral + kd * derivative;
+                let output = signal.abs().min(100.0).round() as u8;
+                match signal {
+                    _ if output == 0 => (ClimateAction::Idle, 0),
+                    s if s > 0.0 && can_heat => (ClimateAction::Heating, output),
+                    s if s < 0.0 && can_cool => (ClimateAction::Cooling, output),
+                    _ => (ClimateAction::Idle, 0),
+                }
+            }
+        }
+    }
+
+    /// Температура по термометру комнаты
+    fn read_temperature(&self, house: &SmartHouse) -> Result<Celsius, String> {
+        if let Ok(Device::Therm(therm)) = house.device(&self.room, &self.therm) {
+            return Ok(therm.temperature());
+        }
+        match house.controller(&self.room, &self.therm) {
+            Ok(DeviceController::Therm(therm)) => {
+                therm.temperature().map(|r| r.value).map_err(|e| e.to_string())
+            }
+            Ok(_) => Err("не термометр".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Устанавливает нагрузку обогревателей и охладителей (0 - выключить)
+    async fn apply(&self, house: &mut SmartHouse, heat: u8, cool: u8, report: &mut ClimateReport) {
+        let devices = self
+            .heaters
+            .iter()
+            .map(|key| (key, heat))
+            .chain(self.coolers.iter().map(|key| (key, cool)));
+
+        for (key, load) in devices {
+            if let Err(e) = set_output(house, &self.room, key, load).await {
+                report.errors.push((key.clone(), e));
+            }
+        }
+    }
+}
+
+/// Включает розетку с нагрузкой `load` % или выключает при 0
+async fn set_output(house: &mut SmartHouse, room: &str, key: &str, load: u8) -> Result<(), String> {
+    if let Ok(Device::Socket(socket)) = house.device_mut(room, key) {
+        if load == 0 {
+            socket.turn_off();
+        } else {
+            socket.set_load(load);
+            socket.turn_on();
+        }
+        return Ok(());
+    }
+
+    let controller = match house.controller_mut(room, key) {
+        Ok(DeviceController::Socket(controller)) => controller,
+        Ok(_) => return Err("не розетка".to_string()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let result = if load == 0 {
+        controller.turn_off().await
+    } else {
+        match controller.set_load(load).await {
+            Ok(()) => controller.turn_on().await,
+            Err(e) => Err(e),
+        }
+    };
+    result.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{SmartSocket, SmartTherm};
+
+    fn house(temperature: f64) -> SmartHouse {
+        crate::house! {
+            "bedroom" => {
+                "therm" => therm(temperature),
+                "heater" => socket(2000.0),
+                "ac" => socket(1000.0),
+            },
+        }
+    }
+
+    fn set_temperature(house: &mut SmartHouse, temperature: f64) {
+        if let Ok(Device::Therm(therm)) = house.device_mut("bedroom", "therm") {
+            therm.set_temperature(temperature);
+        }
+    }
+
+    fn socket<'a>(house: &'a SmartHouse, key: &str) -> &'a SmartSocket {
+        match house.device("bedroom", key) {
+            Ok(Device::Socket(socket)) => socket,
+            _ => panic!("Expected socket"),
+        }
+    }
+
+    #[tokio::test]
+    async fn hysteresis() {
+        let mut house = house(19.0);
+        let mut climate = RoomClimateController::new("bedroom", "therm", 21.0)
+            .with_heater("heater")
+            .with_cooler("ac")
+            .with_hysteresis(1.0);
+        let step = Duration::from_secs(60);
+
+        let report = climate.tick(&mut house, step).await;
+        assert_eq!(report.action, ClimateAction::Heating);
+        assert!(socket(&house, "heater").is_active());
+        assert!(!socket(&house, "ac").is_active());
+
+        // Внутри полосы обогрев продолжается до цели
+        set_temperature(&mut house, 20.5);
+        assert_eq!(
+            climate.tick(&mut house, step).await.action,
+            ClimateAction::Heating
+        );
+        set_temperature(&mut house, 21.0);
+        assert_eq!(
+            climate.tick(&mut house, step).await.action,
+            ClimateAction::Idle
+        );
+        assert!(!socket(&house, "heater").is_active());
+
+        set_temperature(&mut house, 22.5);
+        assert_eq!(
+            climate.tick(&mut house, step).await.action,
+            ClimateAction::Cooling
+        );
+        assert!(socket(&house, "ac").is_active());
+    }
+
+    #[tokio::test]
+    async fn pid_sets_load() {
+        let mut house = house(18.0);
+        let mut climate = RoomClimateController::new("bedroom", "therm", 21.0)
+            .with_heater("heater")
+            .with_pid(10.0, 0.0, 0.0);
+
+        let report = climate.tick(&mut house, Duration::from_secs(1)).await;
+        assert_eq!(report.action, ClimateAction::Heating);
+        assert_eq!(report.output, 30);
+        assert_eq!(socket(&house, "heater").load_percent(), 30);
+
+        // Перегрев без охладителей - просто выключить обогрев
+        set_temperature(&mut house, 23.0);
+        let report = climate.tick(&mut house, Duration::from_secs(1)).await;
+        assert_eq!(report.action, ClimateAction::Idle);
+        assert!(!socket(&house, "heater").is_active());
+    }
+
+    #[tokio::test]
+    async fn missing_therm_turns_everything_off() {
+        let mut house = house(15.0);
+        let mut climate =
+            RoomClimateController::new("bedroom", "therm", 21.0).with_heater("heater");
+        climate.tick(&mut house, Duration::from_secs(1)).await;
+        assert!(socket(&house, "heater").is_active());
+
+        house.room_mut("bedroom").unwrap().remove_device("therm");
+        let report = climate.tick(&mut house, Duration::from_secs(1)).await;
+        assert_eq!(report.temperature, None);
+        assert_eq!(report.errors.len(), 1);
+        assert!(!socket(&house, "heater").is_active());
+
+        house
+            .room_mut("bedroom")
+            .unwrap()
+            .add_device("therm", Device::Therm(SmartTherm::new(25.0)));
+        assert_eq!(
+            climate
+                .tick(&mut house, Duration::from_secs(1))
+                .await
+                .action,
+            ClimateAction::Idle
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async networking"]
+    async fn holds_temperature_in_emulated_room() {
+        use crate::emulators::{PhysicsModel, World, WorldSpec};
+
+        let physics = PhysicsModel::new(15.0)
+            .with_time_constant(Duration::from_millis(300))
+            .with_noise(0.0);
+        let spec = WorldSpec::new()
+            .heater("bedroom", "heater", 2000.0, 5.0)
+            .therm("bedroom", "therm", 15.0, physics)
+            .with_update_interval(Duration::from_millis(20));
+        let mut world = World::start(spec).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await; // первые показания
+        let mut climate = RoomClimateController::new("bedroom", "therm", 20.0)
+            .with_heater("heater")
+            .with_hysteresis(0.5);
+
+        let period = Duration::from_millis(50);
+        let mut temperatures = Vec::new();
+        for _ in 0..40 {
+            let report = climate.tick(world.house_mut(), period).await;
+            assert!(report.errors.is_empty(), "{:?}", report.errors);
+            temperatures.extend(report.temperature.map(|t| t.value()));
+            tokio::time::sleep(period).await;
+        }
+
+        // Без регулирования комната нагрелась бы до 25°C
+        let settled = &temperatures[temperatures.len() / 2..];
+        assert!(
+            settled.iter().all(|t| (18.5..=21.5).contains(t)),
+            "{:?}",
+            settled
+        );
+        world.shutdown().await;
+    }
+}
@@ -0,0 +1,166 @@
+//! Источник времени: реальные часы или управляемые из тестов
+//!
+//! `MockClock` позволяет "перематывать" время: эмуляторы и проверки
+//! свежести данных видят сдвиг мгновенно, без реального ожидания.
+
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Максимальное реальное ожидание `MockClock::sleep` до повторной проверки времени
+const MOCK_POLL: Duration = Duration::from_millis(10);
+
+/// Получает текущее время в миллисекундах с Unix epoch
+/// Используется контроллерами для добавления timestamp при получении данных
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Получает текущее время в миллисекундах с Unix epoch (часы браузера)
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Абстракция часов
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// Текущее время в миллисекундах с Unix epoch
+    fn now_ms(&self) -> u64;
+
+    /// Блокирующий сон потока (для `MockClock` - до сдвига времени)
+    fn sleep(&self, duration: Duration);
+}
+
+/// Разделяемые часы
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Реальные системные часы
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        now_ms()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Часы системы по умолчанию
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// Управляемые часы для тестов
+#[derive(Debug)]
+pub struct MockClock {
+    now_ms: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl MockClock {
+    /// Создает часы, стоящие на текущем реальном времени
+    pub fn new() -> Self {
+        Self::starting_at(now_ms())
+    }
+
+    /// Создает часы, стоящие на заданном времени
+    pub fn starting_at(now_ms: u64) -> Self {
+        Self {
+            now_ms: Mutex::new(now_ms),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Сдвигает время вперед и будит спящие потоки
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut now) = self.now_ms.lock() {
+            *now += duration.as_millis() as u64;
+        }
+        self.changed.notify_all();
+    }
+
+    /// Устанавливает время
+    pub fn set(&self, now_ms: u64) {
+        if let Ok(mut now) = self.now_ms.lock() {
+            *now = now_ms;
+        }
+        self.changed.notify_all();
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms.lock().map(|now| *now).unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        // Ждем сдвига времени, но не дольше MOCK_POLL реального времени,
+        // чтобы вызывающий поток мог проверить флаги остановки
+        if let Ok(now) = self.now_ms.lock() {
+            let _ = self.changed.wait_timeout(now, duration.min(MOCK_POLL));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn system_clock_follows_real_time() {
+        let clock = SystemClock;
+        let diff = clock.now_ms().abs_diff(now_ms());
+        assert!(diff < 100);
+    }
+
+    #[test]
+    fn mock_clock_advance_and_set() {
+        let clock = MockClock::starting_at(1_000);
+        assert_eq!(clock.now_ms(), 1_000);
+
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(clock.now_ms(), 3_601_000);
+
+        clock.set(5);
+        assert_eq!(clock.now_ms(), 5);
+    }
+
+    #[test]
+    fn mock_sleep_is_short_in_real_time() {
+        let clock = MockClock::new();
+        let started = Instant::now();
+        clock.sleep(Duration::from_secs(3600));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn mock_sleep_wakes_on_advance() {
+        let clock = Arc::new(MockClock::starting_at(0));
+        let sleeper = Arc::clone(&clock);
+
+        let handle = std::thread::spawn(move || {
+            while sleeper.now_ms() < 1_000 {
+                sleeper.sleep(Duration::from_secs(1));
+            }
+        });
+
+        clock.advance(Duration::from_secs(1));
+        handle.join().unwrap();
+    }
+}
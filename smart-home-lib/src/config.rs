@@ -0,0 +1,714 @@
+//! Конфигурация дома в JSON
+//!
+//! Описывает комнаты, устройства с их адресами, рабочими параметрами
+//! контроллеров и метаданными, группы, энергобюджет, правила
+//! автоматизации и расписание. Из конфигурации собирается `SmartHouse`
+//! с контроллерами, а желаемые состояния розеток передаются в
+//! `Reconciler`.
+//!
+//! `HouseConfig::from_json` сообщает обо всех проблемах сразу: ошибки
+//! разбора каждого устройства, повторяющиеся ключи, некорректные адреса,
+//! занятые дважды порты и значения вне допустимых диапазонов. У каждой
+//! проблемы есть путь к полю и, если известно, строка и столбец в файле.
+//!
+//! Токены уведомлений задаются ссылками на переменные окружения или файлы
+//! (`"${env:BOT_TOKEN}"`, см. `secrets`), а не открытым текстом.
+
+mod diagnostics;
+
+pub use diagnostics::ConfigIssue;
+
+use crate::controllers::ControllerConfig;
+use crate::discovery::{DiscoveredDevice, DiscoveredKind};
+use crate::energy::BudgetManager;
+use crate::group::DeviceGroup;
+use crate::house::{SmartHouse, SmartHouseError};
+use crate::metadata::DeviceMetadata;
+use crate::notifications::{Notifier, NotifyError, TelegramNotifier, WebhookNotifier};
+use crate::reconciler::{DesiredState, Reconciler};
+use crate::room::Room;
+use crate::room_kind::RoomKind;
+use crate::rules::{Rule, RuleSet, Schedule, ScheduleEntry};
+use crate::secrets::Secret;
+use crate::units::Watts;
+use serde::{Deserialize, Serialize};
+use diagnostics::Parser;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+/// Ошибки загрузки конфигурации
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Не удалось прочитать файл
+    Io(PathBuf, std::io::Error),
+    /// Конфигурация содержит ошибки (все найденные сразу)
+    Invalid(Vec<ConfigIssue>),
+    /// Ошибка при сборке дома
+    House(SmartHouseError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "Не удалось прочитать {}: {}", path.display(), e),
+            Self::Invalid(issues) => {
+                write!(f, "Некорректная конфигурация, проблем: {}", issues.len())?;
+                for issue in issues {
+                    write!(f, "\n  - {}", issue)?;
+                }
+                Ok(())
+            }
+            Self::House(e) => write!(f, "Ошибка сборки дома: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<SmartHouseError> for ConfigError {
+    fn from(e: SmartHouseError) -> Self {
+        Self::House(e)
+    }
+}
+
+/// Допустимая начальная температура термометра, °C
+const TEMPERATURE_RANGE: RangeInclusive<f64> = -60.0..=120.0;
+
+/// Устройство в конфигурации: адрес, параметры контроллера и метаданные
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceConfig {
+    #[serde(flatten)]
+    pub device: DiscoveredDevice,
+    #[serde(flatten)]
+    pub metadata: DeviceMetadata,
+    /// Рабочие параметры контроллера (таймауты, перезапуск)
+    #[serde(default, skip_serializing_if = "ControllerConfig::is_default")]
+    pub controller: ControllerConfig,
+    /// Желаемое состояние розетки, которое поддерживает `Reconciler`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desired: Option<DesiredState>,
+}
+
+impl From<DiscoveredDevice> for DeviceConfig {
+    fn from(device: DiscoveredDevice) -> Self {
+        Self {
+            device,
+            metadata: DeviceMetadata::default(),
+            controller: ControllerConfig::default(),
+            desired: None,
+        }
+    }
+}
+
+/// Комната в конфигурации
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoomConfig {
+    /// Тип помещения
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<RoomKind>,
+    #[serde(default)]
+    pub devices: BTreeMap<String, DeviceConfig>,
+}
+
+/// Участник группы в конфигурации
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupMemberConfig {
+    pub room: String,
+    pub device: String,
+}
+
+/// Энергобюджет дома
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Лимит суммарной мощности, Вт
+    pub limit_watts: f64,
+    /// Отключать нагрузку при превышении
+    #[serde(default)]
+    pub auto_shed: bool,
+}
+
+/// Канал уведомлений; токены и адреса с ключами - секреты
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NotifierConfig {
+    /// Telegram бот
+    Telegram {
+        token: Secret,
+        chat_id: String,
+        /// Адрес Bot API сервера (по умолчанию локальный)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_base: Option<String>,
+    },
+    /// HTTP webhook
+    Webhook { url: Secret },
+}
+
+impl NotifierConfig {
+    /// Создает канал уведомлений
+    pub fn build(&self) -> Result<Box<dyn Notifier>, NotifyError> {
+        Ok(match self {
+            Self::Telegram {
+                token,
+                chat_id,
+                api_base,
+            } => {
+                let notifier = TelegramNotifier::new(token.expose(), chat_id);
+                match api_base {
+                    Some(api_base) => Box::new(notifier.with_api_base(api_base)),
+                    None => Box::new(notifier),
+                }
+            }
+            Self::Webhook { url } => Box::new(WebhookNotifier::new(url.expose())?),
+        })
+    }
+}
+
+/// Конфигурация дома
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HouseConfig {
+    #[serde(default)]
+    pub rooms: BTreeMap<String, RoomConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<GroupMemberConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifiers: Vec<NotifierConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<ScheduleEntry>,
+}
+
+impl HouseConfig {
+    /// Загружает конфигурацию из файла
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let json =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        Self::from_json(&json)
+    }
+
+    /// Разбирает и проверяет конфигурацию
+    ///
+    /// Проверки значений выполняются, только если все части документа
+    /// разобрались; ошибки разбора при этом собираются по всем устройствам.
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let mut parser = Parser::new(json);
+        let config = parser.parse();
+        if let Some(config) = &config {
+            config.issues().into_iter().for_each(|issue| parser.locate(issue));
+        }
+
+        match (config, parser.into_issues()) {
+            (Some(config), issues) if issues.is_empty() => Ok(config),
+            (_, issues) => Err(ConfigError::Invalid(issues)),
+        }
+    }
+
+    /// Конфигурация в JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Builder: устройство в комнате
+    pub fn with_device(mut self, room: &str, key: &str, device: impl Into<DeviceConfig>) -> Self {
+        self.rooms
+            .entry(room.to_string())
+            .or_default()
+            .devices
+            .insert(key.to_string(), device.into());
+        self
+    }
+
+    /// Проверяет конфигурацию; ошибка содержит все найденные проблемы
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let issues = self.issues();
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(issues))
+        }
+    }
+
+    /// Проблемы в значениях: адреса, диапазоны, ссылки групп
+    pub fn issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        // Первое устройство на каждом адресе
+        let mut addresses: HashMap<(bool, SocketAddr), String> = HashMap::new();
+
+        for (room_key, room) in &self.rooms {
+            for (key, device) in &room.devices {
+                let path = format!("rooms.{}.devices.{}", room_key, key);
+                let address = device.device.address;
+
+                let is_socket = match device.device.kind {
+                    DiscoveredKind::Socket { power_rating } => {
+                        if !(power_rating.is_finite() && power_rating > 0.0) {
+                            issues.push(ConfigIssue::new(
+                                format!("{}.power_rating", path),
+                                format!("мощность должна быть положительной: {}", power_rating),
+                            ));
+                        }
+                        if address.port() == 0 || address.ip().is_unspecified() {
+                            issues.push(ConfigIssue::new(
+                                format!("{}.address", path),
+                                format!("у розетки должны быть IP и порт: {}", address),
+                            ));
+                        }
+                        true
+                    }
+                    DiscoveredKind::Therm { initial_temp } => {
+                        if !TEMPERATURE_RANGE.contains(&initial_temp) {
+                            issues.push(ConfigIssue::new(
+                                format!("{}.initial_temp", path),
+                                format!(
+                                    "температура вне диапазона {}..{} °C: {}",
+                                    TEMPERATURE_RANGE.start(),
+                                    TEMPERATURE_RANGE.end(),
+                                    initial_temp
+                                ),
+                            ));
+                        }
+                        false
+                    }
+                };
+
+                if device.desired.is_some() && !is_socket {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.desired", path),
+                        "желаемое состояние задано для непереключаемого устройства",
+                    ));
+                }
+
+                // Порт 0 выбирается системой и не конфликтует
+                if address.port() != 0 {
+                    match addresses.get(&(is_socket, address)) {
+                        Some(first) => issues.push(ConfigIssue::new(
+                            format!("{}.address", path),
+                            format!("адрес {} уже использует {}", address, first),
+                        )),
+                        None => {
+                            addresses.insert((is_socket, address), path.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        for (group, members) in &self.groups {
+            for (index, member) in members.iter().enumerate() {
+                let known = self
+                    .rooms
+                    .get(&member.room)
+                    .is_some_and(|room| room.devices.contains_key(&member.device));
+                if !known {
+                    issues.push(ConfigIssue::new(
+                        format!("groups.{}[{}]", group, index),
+                        format!(
+                            "группа ссылается на неизвестное устройство {}/{}",
+                            member.room, member.device
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let Some(budget) = &self.budget
+            && !(budget.limit_watts.is_finite() && budget.limit_watts > 0.0)
+        {
+            issues.push(ConfigIssue::new(
+                "budget.limit_watts",
+                format!("лимит должен быть положительным: {}", budget.limit_watts),
+            ));
+        }
+
+        let mut names = HashMap::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let path = format!("rules[{}]", index);
+            if let Some(first) = names.insert(rule.name.as_str(), index) {
+                issues.push(ConfigIssue::new(
+                    format!("{}.name", path),
+                    format!("правило '{}' уже задано в rules[{}]", rule.name, first),
+                ));
+            }
+            if let Some(delta) = rule.hysteresis
+                && !(delta.is_finite() && delta >= 0.0)
+            {
+                issues.push(ConfigIssue::new(
+                    format!("{}.hysteresis", path),
+                    format!("гистерезис не может быть отрицательным: {}", delta),
+                ));
+            }
+            let paths = rule.when.paths().into_iter().map(|p| ("when", p));
+            let actions = rule.then.iter().map(|action| ("then", action.path()));
+            for (field, device) in paths.chain(actions) {
+                let known = self
+                    .rooms
+                    .get(device.room())
+                    .is_some_and(|room| room.devices.contains_key(device.key()));
+                if !known {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.{}", path, field),
+                        format!("правило ссылается на неизвестное устройство {}", device),
+                    ));
+                }
+            }
+        }
+
+        let mut names = HashMap::new();
+        for (index, entry) in self.schedule.iter().enumerate() {
+            let path = format!("schedule[{}]", index);
+            if let Some(first) = names.insert(entry.name.as_str(), index) {
+                issues.push(ConfigIssue::new(
+                    format!("{}.name", path),
+                    format!("запись '{}' уже задана в schedule[{}]", entry.name, first),
+                ));
+            }
+            for action in &entry.then {
+                let device = action.path();
+                let known = self
+                    .rooms
+                    .get(device.room())
+                    .is_some_and(|room| room.devices.contains_key(device.key()));
+                if !known {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.then", path),
+                        format!("расписание ссылается на неизвестное устройство {}", device),
+                    ));
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Собирает дом: создает контроллеры и регистрирует группы
+    ///
+    /// Контроллеры не запускаются и не занимают порты до
+    /// `SmartHouse::start_controllers`.
+    pub fn build(&self) -> Result<SmartHouse, ConfigError> {
+        self.validate()?;
+
+        let mut house = SmartHouse::default();
+        for (room_key, room) in &self.rooms {
+            if let Some(kind) = room.kind {
+                house.add_room(room_key, Room::builder().kind(kind).build());
+            }
+            for (key, device) in &room.devices {
+                house.adopt_stopped(device.device.clone(), room_key, key, &device.controller)?;
+                if let Some(room) = house.room_mut(room_key) {
+                    room.set_metadata(key, device.metadata.clone());
+                }
+            }
+        }
+
+        for (name, members) in &self.groups {
+            let group = members.iter().fold(DeviceGroup::new(), |group, member| {
+                group.with_member(&member.room, &member.device)
+            });
+            house.add_group(name, group);
+        }
+
+        Ok(house)
+    }
+
+    /// Менеджер энергобюджета, если бюджет задан
+    pub fn budget_manager(&self) -> Option<BudgetManager> {
+        self.budget.as_ref().map(|budget| {
+            BudgetManager::new(Watts::new(budget.limit_watts)).with_auto_shed(budget.auto_shed)
+        })
+    }
+
+    /// Каналы уведомлений из конфигурации
+    pub fn build_notifiers(&self) -> Result<Vec<Box<dyn Notifier>>, NotifyError> {
+        self.notifiers.iter().map(NotifierConfig::build).collect()
+    }
+
+    /// Правила автоматизации
+    pub fn rule_set(&self) -> RuleSet {
+        RuleSet::from(self.rules.clone())
+    }
+
+    /// Расписание действий
+    pub fn schedule(&self) -> Schedule {
+        Schedule::from(self.schedule.clone())
+    }
+
+    /// Сверка желаемых состояний, если они заданы хотя бы для одного устройства
+    pub fn reconciler(&self) -> Option<Reconciler> {
+        let mut reconciler = Reconciler::new();
+        for (room_key, room) in &self.rooms {
+            for (key, device) in &room.devices {
+                if let Some(state) = device.desired {
+                    reconciler.set_desired(room_key, key, state);
+                }
+            }
+        }
+        (reconciler.desired_count() > 0).then_some(reconciler)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::DeviceController;
+    use std::time::Duration;
+
+    const SAMPLE: &str = r#"{
+        "rooms": {
+            "kitchen": {
+                "kind": "kitchen",
+                "devices": {
+                    "kettle": {
+                        "kind": "socket",
+                        "power_rating": 2000.0,
+                        "address": "127.0.0.1:3001",
+                        "priority": 20,
+                        "tags": ["heavy"],
+                        "desired": "off",
+                        "controller": { "timeout_ms": 500 }
+                    },
+                    "therm": {
+                        "kind": "therm",
+                        "initial_temp": 21.0,
+                        "address": "127.0.0.1:0"
+                    }
+                }
+            }
+        },
+        "groups": {
+            "appliances": [{ "room": "kitchen", "device": "kettle" }]
+        },
+        "budget": { "limit_watts": 3000.0, "auto_shed": true },
+        "rules": [{
+            "name": "hot_kitchen",
+            "when": "kitchen/therm.temperature > 30",
+            "then": [{ "turn_off": "kitchen/kettle" }]
+        }],
+        "schedule": [{
+            "name": "morning",
+            "at": "07:30",
+            "then": [{ "turn_on": "kitchen/kettle" }]
+        }]
+    }"#;
+
+    #[test]
+    fn parse_sample() {
+        let config = HouseConfig::from_json(SAMPLE).unwrap();
+        let kettle = &config.rooms["kitchen"].devices["kettle"];
+        assert_eq!(kettle.metadata.priority, 20);
+        assert!(kettle.metadata.has_tag("heavy"));
+        assert_eq!(kettle.controller.timeout(), Duration::from_millis(500));
+        assert_eq!(
+            config.rooms["kitchen"].devices["therm"].metadata,
+            DeviceMetadata::default()
+        );
+        assert_eq!(config.budget_manager().unwrap().limit(), Watts::new(3000.0));
+        assert_eq!(
+            config.reconciler().unwrap().desired("kitchen", "kettle"),
+            Some(DesiredState::Off)
+        );
+        assert_eq!(config.rule_set().rules()[0].name, "hot_kitchen");
+        assert_eq!(config.schedule().entries()[0].at.to_string(), "07:30");
+
+        // Сериализация сохраняет все поля
+        assert_eq!(HouseConfig::from_json(&config.to_json()).unwrap(), config);
+    }
+
+    fn issues(json: &str) -> Vec<ConfigIssue> {
+        match HouseConfig::from_json(json) {
+            Err(ConfigError::Invalid(issues)) => issues,
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_group_member() {
+        let json = r#"{"groups": {"g": [{"room": "hall", "device": "lamp"}]}}"#;
+        let found = issues(json);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "groups.g[0]");
+        assert_eq!(found[0].position, Some((1, 12)));
+
+        let found = issues("{");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "");
+
+        let json = r#"{"rooms": {"hall": {"devices": {"therm": {
+            "kind": "therm", "initial_temp": 20.0, "address": "127.0.0.1:0", "desired": "on"
+        }}}}}"#;
+        assert_eq!(issues(json)[0].path, "rooms.hall.devices.therm.desired");
+    }
+
+    #[test]
+    fn rule_problems() {
+        let json = r#"{"rules": [{"name": "r", "when": "kitchen/therm.temperature => 30"}]}"#;
+        let found = issues(json);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "rules[0].when");
+        assert!(found[0].message.contains("'=='"), "{}", found[0]);
+
+        let json = r#"{"rules": [
+            {"name": "r", "when": "hall/lamp.active"},
+            {"name": "r", "when": "true", "then": [{"turn_on": "hall/lamp"}], "hysteresis": -1}
+        ]}"#;
+        let paths: Vec<String> = issues(json).into_iter().map(|i| i.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "rules[0].when",
+                "rules[1].name",
+                "rules[1].hysteresis",
+                "rules[1].then"
+            ]
+        );
+    }
+
+    #[test]
+    fn schedule_problems() {
+        let json = r#"{"schedule": [{"name": "s", "at": "7:30"}]}"#;
+        let found = issues(json);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "schedule[0].at");
+        assert!(found[0].message.contains("ЧЧ:ММ"), "{}", found[0]);
+
+        let json = r#"{"schedule": [
+            {"name": "s", "at": "07:30"},
+            {"name": "s", "at": "08:00", "then": [{"turn_on": "hall/lamp"}]}
+        ]}"#;
+        let paths: Vec<String> = issues(json).into_iter().map(|i| i.path).collect();
+        assert_eq!(paths, ["schedule[1].name", "schedule[1].then"]);
+    }
+
+    #[test]
+    fn all_problems_reported() {
+        let json = r#"{
+  "rooms": {
+    "kitchen": {
+      "devices": {
+        "kettle": { "kind": "socket", "power_rating": "2000", "address": "127.0.0.1:3001" },
+        "toaster": { "kind": "socket", "power_rating": 800.0, "address": "localhost" }
+      }
+    },
+    "hall": {
+      "devices": {
+        "lamp": { "kind": "socket", "power_rating": 60.0, "address": "127.0.0.1:3002" },
+        "lamp": { "kind": "socket", "power_rating": 40.0, "address": "127.0.0.1:3003" }
+      }
+    }
+  }
+}"#;
+        let found = issues(json);
+        let paths: Vec<&str> = found.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "rooms.kitchen.devices.kettle",
+                "rooms.kitchen.devices.toaster",
+                "rooms.hall.devices.lamp",
+            ]
+        );
+        assert_eq!(found[0].position.map(|p| p.0), Some(5));
+        assert_eq!(found[1].position.map(|p| p.0), Some(6));
+        assert_eq!(found[2].position, Some((12, 17)));
+        assert!(found[2].message.contains("повторяющийся ключ"));
+    }
+
+    #[test]
+    fn value_checks() {
+        let json = r#"{
+  "rooms": {
+    "a": { "devices": {
+      "heater": { "kind": "socket", "power_rating": -5.0, "address": "0.0.0.0:3001" },
+      "therm": { "kind": "therm", "initial_temp": 500.0, "address": "127.0.0.1:4001" }
+    } },
+    "b": { "devices": {
+      "therm": { "kind": "therm", "initial_temp": 20.0, "address": "127.0.0.1:4001" }
+    } }
+  },
+  "budget": { "limit_watts": 0.0 }
+}"#;
+        let found = issues(json);
+        let paths: Vec<&str> = found.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "rooms.a.devices.heater.power_rating",
+                "rooms.a.devices.heater.address",
+                "rooms.a.devices.therm.initial_temp",
+                "rooms.b.devices.therm.address",
+                "budget.limit_watts",
+            ]
+        );
+        assert_eq!(found[0].position, Some((4, 17)));
+        assert!(found[3].message.contains("rooms.a.devices.therm"));
+        assert_eq!(found[4].position.map(|p| p.0), Some(11));
+
+        let message = ConfigError::Invalid(found).to_string();
+        assert_eq!(message.lines().count(), 6);
+    }
+
+    #[test]
+    fn build_house() {
+        let house = HouseConfig::from_json(SAMPLE).unwrap().build().unwrap();
+
+        assert!(matches!(
+            house.controller("kitchen", "kettle"),
+            Ok(DeviceController::Socket(socket)) if socket.timeout() == Duration::from_millis(500)
+        ));
+        assert!(matches!(
+            house.controller("kitchen", "therm"),
+            Ok(DeviceController::Therm(_))
+        ));
+        assert_eq!(
+            house.room("kitchen").unwrap().metadata("kettle").priority,
+            20
+        );
+        assert_eq!(house.room("kitchen").unwrap().kind(), Some(RoomKind::Kitchen));
+        assert!(
+            house
+                .group("appliances")
+                .unwrap()
+                .contains("kitchen", "kettle")
+        );
+    }
+
+    #[test]
+    fn notifier_secrets() {
+        let json = r#"{"notifiers": [
+            {"kind": "telegram", "token": "${env:PATH}", "chat_id": "42"},
+            {"kind": "webhook", "url": "http://127.0.0.1:9000/${env:SMART_HOME_SURELY_UNSET_HOOK}"}
+        ]}"#;
+        let found = issues(json);
+        assert_eq!(found.len(), 1);
+        // Внутренний путь теряется в enum с тегом, остается элемент списка
+        assert_eq!(found[0].path, "notifiers[1]");
+        assert!(found[0].message.contains("SMART_HOME_SURELY_UNSET_HOOK"));
+
+        let json = r#"{"notifiers": [
+            {"kind": "telegram", "token": "${env:PATH}", "chat_id": "42"}
+        ]}"#;
+        let config = HouseConfig::from_json(json).unwrap();
+        assert_eq!(config.build_notifiers().unwrap().len(), 1);
+        // Сохраненная конфигурация содержит ссылку, а не токен
+        let saved = config.to_json();
+        assert!(saved.contains("${env:PATH}"));
+        assert!(!saved.contains(&std::env::var("PATH").unwrap()));
+    }
+
+    #[test]
+    fn builder() {
+        let config = HouseConfig::default().with_device(
+            "hall",
+            "lamp",
+            DiscoveredDevice::socket("127.0.0.1:3002".parse().unwrap(), 60.0),
+        );
+        assert_eq!(config.rooms["hall"].devices.len(), 1);
+        assert!(config.budget_manager().is_none());
+        assert!(config.reconciler().is_none());
+    }
+}
@@ -0,0 +1,300 @@
+//! Разбор конфигурации с позициями ошибок
+//!
+//! Документ разбирается по частям: каждое устройство, группы, бюджет,
+//! уведомления и правила отдельно, поэтому одна ошибка не скрывает
+//! остальные. Части остаются ссылками на исходный текст (`RawValue`), по
+//! ним ошибка serde получает строку и столбец в файле, а
+//! `serde_path_to_error` - путь к полю.
+
+use super::{DeviceConfig, HouseConfig, RoomConfig};
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde_json::value::RawValue;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Проблема в конфигурации
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    /// Путь к значению: `rooms.kitchen.devices.kettle.address`
+    pub path: String,
+    /// Строка и столбец в файле (с 1), если известны
+    pub position: Option<(usize, usize)>,
+    /// Описание проблемы
+    pub message: String,
+}
+
+impl ConfigIssue {
+    pub(super) fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            position: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() { "." } else { &self.path };
+        match self.position {
+            Some((line, column)) => write!(f, "{} ({}:{}): {}", path, line, column, self.message),
+            None => write!(f, "{}: {}", path, self.message),
+        }
+    }
+}
+
+/// Пары ключ-значение объекта в порядке записи, включая повторы
+struct Entries<'a>(Vec<(String, &'a RawValue)>);
+
+impl<'de: 'a, 'a> Deserialize<'de> for Entries<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor<'a>(std::marker::PhantomData<&'a ()>);
+
+        impl<'de: 'a, 'a> Visitor<'de> for EntriesVisitor<'a> {
+            type Value = Entries<'a>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an object")
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Entries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor(std::marker::PhantomData))
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RawHouse<'a> {
+    #[serde(borrow, default)]
+    rooms: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    groups: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    budget: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    notifiers: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    rules: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    schedule: Option<&'a RawValue>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawRoom<'a> {
+    #[serde(borrow, default)]
+    kind: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    devices: Option<&'a RawValue>,
+}
+
+/// Разбор одного документа со сбором проблем
+pub(super) struct Parser<'a> {
+    json: &'a str,
+    issues: Vec<ConfigIssue>,
+    /// Позиции устройств, групп и бюджета по их путям
+    positions: HashMap<String, (usize, usize)>,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(json: &'a str) -> Self {
+        Self {
+            json,
+            issues: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Разбирает документ; `None`, если хотя бы одна часть не разобралась
+    ///
+    /// Повторяющиеся ключи - проблема, но документ при этом разбирается
+    /// (побеждает последнее значение).
+    pub(super) fn parse(&mut self) -> Option<HouseConfig> {
+        let raw: RawHouse = match serde_json::from_str(self.json) {
+            Ok(raw) => raw,
+            Err(e) => {
+                let position = (e.line(), e.column());
+                self.push_error("", position, &e);
+                return None;
+            }
+        };
+
+        let mut config = HouseConfig::default();
+        let mut complete = true;
+
+        for (room_key, room_raw) in raw.rooms.map(|r| self.entries(r, "rooms")).unwrap_or_default()
+        {
+            let path = format!("rooms.{}", room_key);
+            let Some(room) = self.value::<RawRoom>(room_raw, &path) else {
+                complete = false;
+                continue;
+            };
+            let devices_path = format!("{}.devices", path);
+            let devices = room
+                .devices
+                .map(|d| self.entries(d, &devices_path))
+                .unwrap_or_default();
+
+            let kind = room.kind.and_then(|kind| {
+                let kind = self.value(kind, &format!("{}.kind", path));
+                complete &= kind.is_some();
+                kind
+            });
+
+            let room_config: &mut RoomConfig = config.rooms.entry(room_key).or_default();
+            room_config.kind = kind.flatten();
+            for (key, device_raw) in devices {
+                let path = format!("{}.{}", devices_path, key);
+                match self.value::<DeviceConfig>(device_raw, &path) {
+                    Some(device) => {
+                        room_config.devices.insert(key, device);
+                    }
+                    None => complete = false,
+                }
+            }
+        }
+
+        if let Some(groups) = raw.groups {
+            match self.value(groups, "groups") {
+                Some(groups) => config.groups = groups,
+                None => complete = false,
+            }
+        }
+        if let Some(budget) = raw.budget {
+            match self.value(budget, "budget") {
+                Some(budget) => config.budget = budget,
+                None => complete = false,
+            }
+        }
+        if let Some(notifiers) = raw.notifiers {
+            match self.value(notifiers, "notifiers") {
+                Some(notifiers) => config.notifiers = notifiers,
+                None => complete = false,
+            }
+        }
+        if let Some(rules) = raw.rules {
+            match self.value(rules, "rules") {
+                Some(rules) => config.rules = rules,
+                None => complete = false,
+            }
+        }
+        if let Some(schedule) = raw.schedule {
+            match self.value(schedule, "schedule") {
+                Some(schedule) => config.schedule = schedule,
+                None => complete = false,
+            }
+        }
+
+        complete.then_some(config)
+    }
+
+    /// Добавляет проблему, найденную после разбора, с позицией ближайшей части
+    pub(super) fn locate(&mut self, mut issue: ConfigIssue) {
+        issue.position = self
+            .positions
+            .iter()
+            .filter(|(path, _)| {
+                issue.path.strip_prefix(path.as_str()).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with('.') || rest.starts_with('[')
+                })
+            })
+            .max_by_key(|(path, _)| path.len())
+            .map(|(_, position)| *position);
+        self.issues.push(issue);
+    }
+
+    pub(super) fn into_issues(self) -> Vec<ConfigIssue> {
+        self.issues
+    }
+
+    /// Пары объекта; повторный ключ - проблема, побеждает последнее значение
+    fn entries(&mut self, raw: &'a RawValue, path: &str) -> Vec<(String, &'a RawValue)> {
+        let Some(Entries(entries)) = self.value::<Entries>(raw, path) else {
+            return Vec::new();
+        };
+
+        let mut seen = HashMap::new();
+        for (index, (key, value)) in entries.iter().enumerate() {
+            if seen.insert(key.as_str(), index).is_some() {
+                let position = self.offset_position(value, 1, 1);
+                self.issues.push(ConfigIssue {
+                    path: format!("{}.{}", path, key),
+                    position: Some(position),
+                    message: format!("повторяющийся ключ '{}'", key),
+                });
+            }
+        }
+        entries
+            .iter()
+            .enumerate()
+            .filter(|(index, (key, _))| seen.get(key.as_str()) == Some(index))
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+
+    /// Разбирает часть документа, запоминая ее позицию
+    fn value<T: Deserialize<'a>>(&mut self, raw: &'a RawValue, path: &str) -> Option<T> {
+        self.positions
+            .insert(path.to_string(), self.offset_position(raw, 1, 1));
+        let mut deserializer = serde_json::Deserializer::from_str(raw.get());
+        match serde_path_to_error::deserialize(&mut deserializer) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.push_path_error(raw, path, e);
+                None
+            }
+        }
+    }
+
+    fn push_path_error(
+        &mut self,
+        raw: &RawValue,
+        path: &str,
+        e: serde_path_to_error::Error<serde_json::Error>,
+    ) {
+        let inner_path = e.path().to_string();
+        let full_path = match inner_path.as_str() {
+            "." => path.to_string(),
+            inner if path.is_empty() => inner.to_string(),
+            inner if inner.starts_with('[') => format!("{}{}", path, inner),
+            inner => format!("{}.{}", path, inner),
+        };
+        let inner = e.inner();
+        let position = self.offset_position(raw, inner.line(), inner.column());
+        self.push_error(&full_path, position, inner);
+    }
+
+    fn push_error(&mut self, path: &str, position: (usize, usize), e: &serde_json::Error) {
+        // Позиция хранится отдельно, из текста serde она убирается
+        let message = e.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(end) => message[..end].to_string(),
+            None => message,
+        };
+        self.issues.push(ConfigIssue {
+            path: path.to_string(),
+            position: Some(position),
+            message,
+        });
+    }
+
+    /// Позиция в файле для строки и столбца внутри части `raw`
+    fn offset_position(&self, raw: &RawValue, line: usize, column: usize) -> (usize, usize) {
+        let offset = (raw.get().as_ptr() as usize).saturating_sub(self.json.as_ptr() as usize);
+        let before = &self.json[..offset.min(self.json.len())];
+        let base_line = before.matches('\n').count() + 1;
+        let base_column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+
+        if line <= 1 {
+            (base_line, base_column + column.saturating_sub(1))
+        } else {
+            (base_line + line - 1, column)
+        }
+    }
+}
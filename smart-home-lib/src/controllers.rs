@@ -1,31 +1,214 @@
 //! Контроллеры для взаимодействия с внешними устройствами
+//!
+//! Без фичи `net` доступны только `HealthStatus` и пустой
+//! `DeviceController`: в комнатах остаются лишь локальные устройства.
 
 // Экспортируем модули
-pub mod socket_controller;
-pub mod therm_controller;
+pub mod health;
 
-// Реэкспортируем основные типы и функции для удобства
-pub use socket_controller::{SocketController, SocketError};
-pub use therm_controller::{SubscriptionHandle, ThermController, ThermError};
+pub use health::HealthStatus;
+
+cfg_net! {
+    pub mod address;
+    #[cfg(feature = "blinds")]
+    pub mod blinds_controller;
+    #[cfg(feature = "camera")]
+    pub mod camera_controller;
+    #[cfg(feature = "coap")]
+    pub mod coap_controller;
+    pub mod command;
+    pub mod context;
+    #[cfg(feature = "leak")]
+    pub mod leak_controller;
+    #[cfg(any(feature = "blinds", feature = "camera", feature = "valve"))]
+    mod link;
+    pub mod metrics;
+    pub mod sensor_hub;
+    pub mod settings;
+    pub mod socket_controller;
+    pub mod subscription;
+    pub mod supervisor;
+    pub mod therm_controller;
+    #[cfg(feature = "valve")]
+    pub mod valve_controller;
+
+    // Реэкспортируем основные типы и функции для удобства
+    pub use address::{DeviceAddress, ResolvePolicy};
+    #[cfg(feature = "blinds")]
+    pub use blinds_controller::{BlindsController, BlindsError, BlindsProgress};
+    #[cfg(feature = "camera")]
+    pub use camera_controller::{CameraController, CameraError};
+    #[cfg(feature = "coap")]
+    pub use coap_controller::{CoapController, CoapError, CoapObservation};
+    pub use command::{DeviceCommand, DeviceError, DeviceOutput, DeviceResult};
+    pub use context::{ControllerError, ErrorContext};
+    #[cfg(feature = "leak")]
+    pub use leak_controller::LeakSensorController;
+    #[cfg(any(feature = "blinds", feature = "camera", feature = "valve"))]
+    pub use link::LinkError;
+    pub use metrics::ControllerMetrics;
+    pub use sensor_hub::{IngestStats, SensorHub};
+    pub use settings::{ControllerConfig, RestartConfig};
+    pub use socket_controller::{FailoverEvent, PowerReading, SocketController, SocketError};
+    pub use subscription::{BufferedSubscription, OverflowPolicy, SubscriptionStats};
+    pub use supervisor::{ControllerHealth, RestartPolicy, TaskState};
+    pub use therm_controller::{
+        Reading, ReadingQuality, Smoothing, SubscriptionHandle, TemperatureSubscription,
+        ThermController, ThermError, ThermFeed,
+    };
+    #[cfg(feature = "valve")]
+    pub use valve_controller::{ValveController, ValveError};
+}
+
+#[cfg(not(feature = "net"))]
+mod offline;
+#[cfg(not(feature = "net"))]
+pub use offline::DeviceController;
 
 // ---
 
+#[cfg(feature = "net")]
+use crate::report::ReportOptions;
 use crate::traits::Reporter;
+#[cfg(feature = "net")]
+use crate::traits::{AsyncReporter, Lifecycle};
 use std::fmt;
+#[cfg(feature = "net")]
+use std::time::Duration;
 
+#[cfg(feature = "net")]
 /// Универсальный тип для контроллеров
+///
+/// Контроллеры дополнительных устройств подключаются cargo features
+/// (`blinds`, `camera`, `leak`, `valve`, `coap`), чтобы встраиваемые
+/// сборки не тянули ненужные протоколы.
 pub enum DeviceController {
     Socket(SocketController),
     Therm(ThermController),
+    #[cfg(feature = "blinds")]
+    Blinds(BlindsController),
+}
+
+#[cfg(feature = "net")]
+impl DeviceController {
+    /// Название типа устройства
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Socket(_) => "socket",
+            Self::Therm(_) => "therm",
+            #[cfg(feature = "blinds")]
+            Self::Blinds(_) => "blinds",
+        }
+    }
+
+    /// Состояние связи с устройством
+    pub fn health(&self) -> HealthStatus {
+        match self {
+            Self::Socket(socket) => socket.health_status(),
+            Self::Therm(therm) => therm.health_status(),
+            #[cfg(feature = "blinds")]
+            Self::Blinds(blinds) => blinds.health_status(),
+        }
+    }
+
+    /// Сетевой протокол связи с устройством
+    pub fn transport(&self) -> &'static str {
+        match self {
+            Self::Socket(_) => "tcp",
+            Self::Therm(_) => "udp",
+            #[cfg(feature = "blinds")]
+            Self::Blinds(_) => "tcp",
+        }
+    }
+
+    /// Сетевой адрес устройства (розетки) или прослушиваемый адрес (термометра)
+    pub fn address(&self) -> String {
+        match self {
+            Self::Socket(socket) => socket.address().to_string(),
+            Self::Therm(therm) => therm.listen_addr().to_string(),
+            #[cfg(feature = "blinds")]
+            Self::Blinds(blinds) => blinds.address().to_string(),
+        }
+    }
+
+    /// Выполняет команду на устройстве любого типа
+    pub async fn execute(&mut self, command: DeviceCommand) -> DeviceResult {
+        match (self, command) {
+            (Self::Socket(socket), DeviceCommand::TurnOn) => socket.turn_on().await?,
+            (Self::Socket(socket), DeviceCommand::TurnOff) => socket.turn_off().await?,
+            (Self::Socket(socket), DeviceCommand::ReadPower) => {
+                return Ok(DeviceOutput::Power(socket.power().await?));
+            }
+            (Self::Therm(therm), DeviceCommand::ReadTemperature) => {
+                return Ok(DeviceOutput::Temperature(therm.temperature()?.value));
+            }
+            (controller, command) => {
+                return Err(DeviceError::Unsupported {
+                    command,
+                    device: controller.kind(),
+                });
+            }
+        }
+        Ok(DeviceOutput::Done)
+    }
 }
 
+#[cfg(feature = "net")]
 impl Reporter for DeviceController {
     fn report(&self) -> String {
         match self {
             Self::Socket(s) => s.report(),
             Self::Therm(t) => t.report(),
+            #[cfg(feature = "blinds")]
+            Self::Blinds(b) => b.report(),
+        }
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        match self {
+            Self::Socket(s) => s.report_with(options),
+            Self::Therm(t) => t.report_with(options),
+            #[cfg(feature = "blinds")]
+            Self::Blinds(b) => b.report_with(options),
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+impl AsyncReporter for DeviceController {
+    async fn live_report(&mut self, timeout: Duration) -> String {
+        match self {
+            Self::Socket(s) => s.live_report(timeout).await,
+            Self::Therm(t) => t.live_report(timeout).await,
+            #[cfg(feature = "blinds")]
+            Self::Blinds(b) => b.live_report(timeout).await,
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+impl Lifecycle for DeviceController {
+    async fn start(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Socket(s) => Lifecycle::start(s).await,
+            Self::Therm(t) => Lifecycle::start(t).await,
+            #[cfg(feature = "blinds")]
+            Self::Blinds(b) => Lifecycle::start(b).await,
         }
     }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Socket(s) => Lifecycle::stop(s).await,
+            Self::Therm(t) => Lifecycle::stop(t).await,
+            #[cfg(feature = "blinds")]
+            Self::Blinds(b) => Lifecycle::stop(b).await,
+        }
+    }
+
+    fn health(&self) -> HealthStatus {
+        DeviceController::health(self)
+    }
 }
 
 impl fmt::Display for DeviceController {
@@ -34,14 +217,138 @@ impl fmt::Display for DeviceController {
     }
 }
 
+#[cfg(feature = "net")]
 impl From<SocketController> for DeviceController {
     fn from(socket: SocketController) -> Self {
         Self::Socket(socket)
     }
 }
 
+#[cfg(feature = "net")]
 impl From<ThermController> for DeviceController {
     fn from(therm: ThermController) -> Self {
         Self::Therm(therm)
     }
 }
+
+#[cfg(all(feature = "net", feature = "blinds"))]
+impl From<BlindsController> for DeviceController {
+    fn from(blinds: BlindsController) -> Self {
+        Self::Blinds(blinds)
+    }
+}
+
+#[cfg(all(test, feature = "net"))]
+mod tests {
+    use super::*;
+    use crate::units::Watts;
+    use std::net::SocketAddr;
+
+    #[tokio::test]
+    async fn unsupported_commands() {
+        let addr: SocketAddr = "127.0.0.1:9".parse().unwrap();
+        let mut socket: DeviceController =
+            SocketController::new(addr, 100.0, Duration::from_millis(100)).into();
+        let mut therm: DeviceController =
+            ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(1)).into();
+
+        assert!(matches!(
+            socket.execute(DeviceCommand::ReadTemperature).await,
+            Err(DeviceError::Unsupported {
+                command: DeviceCommand::ReadTemperature,
+                device: "socket"
+            })
+        ));
+        for command in [
+            DeviceCommand::TurnOn,
+            DeviceCommand::TurnOff,
+            DeviceCommand::ReadPower,
+        ] {
+            assert!(matches!(
+                therm.execute(command).await,
+                Err(DeviceError::Unsupported {
+                    device: "therm",
+                    ..
+                })
+            ));
+        }
+
+        // Незапущенный термометр поддерживает чтение, но данных еще нет
+        assert!(matches!(
+            therm.execute(DeviceCommand::ReadTemperature).await,
+            Err(DeviceError::Therm(ThermError::NoFreshData))
+        ));
+    }
+
+    #[tokio::test]
+    async fn live_report_marks_unreachable_devices() {
+        let addr: SocketAddr = "127.0.0.1:9".parse().unwrap();
+        let mut socket: DeviceController =
+            SocketController::new(addr, 100.0, Duration::from_millis(100)).into();
+        let mut therm: DeviceController =
+            ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(1)).into();
+
+        let report = socket.live_report(Duration::from_millis(200)).await;
+        assert!(report.starts_with(&socket.report()));
+        assert!(report.contains("[stale: "));
+
+        let report = therm.live_report(Duration::from_millis(50)).await;
+        assert_eq!(
+            report,
+            format!("{} [stale: {}]", therm.report(), ThermError::NoFreshData)
+        );
+    }
+
+    #[cfg(feature = "blinds")]
+    #[tokio::test]
+    async fn blinds_controller_variant() {
+        let addr = "127.0.0.1:9".parse().unwrap();
+        let mut blinds: DeviceController =
+            BlindsController::new(addr, Duration::from_millis(100)).into();
+
+        assert_eq!(blinds.kind(), "blinds");
+        assert_eq!(blinds.transport(), "tcp");
+        assert_eq!(blinds.address(), "127.0.0.1:9");
+        assert!(matches!(
+            blinds.execute(DeviceCommand::ReadPower).await,
+            Err(DeviceError::Unsupported {
+                device: "blinds",
+                ..
+            })
+        ));
+
+        let report = blinds.live_report(Duration::from_millis(200)).await;
+        assert!(report.starts_with(&blinds.report()));
+        assert!(report.contains("[stale: "));
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn socket_commands() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+
+        let mut emulator =
+            SocketEmulator::new(EmulatorConfig::new(500.0).with_address("127.0.0.1:0"));
+        emulator.start().await.unwrap();
+
+        let addr = emulator.local_addr().unwrap();
+        let mut socket: DeviceController =
+            SocketController::new(addr, 500.0, Duration::from_secs(1)).into();
+
+        assert_eq!(
+            socket.execute(DeviceCommand::TurnOn).await.unwrap(),
+            DeviceOutput::Done
+        );
+        assert!(matches!(
+            socket.execute(DeviceCommand::ReadPower).await,
+            Ok(DeviceOutput::Power(power)) if power > Watts::new(0.0)
+        ));
+        socket.execute(DeviceCommand::TurnOff).await.unwrap();
+        assert_eq!(
+            socket.execute(DeviceCommand::ReadPower).await.unwrap(),
+            DeviceOutput::Power(Watts::new(0.0))
+        );
+
+        emulator.stop().await;
+    }
+}
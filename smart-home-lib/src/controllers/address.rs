@@ -0,0 +1,177 @@
+//! Адрес сетевого устройства: IP или имя хоста
+//!
+//! Устройства с адресами из DHCP и mDNS (`kettle.local:3030`) меняют IP,
+//! поэтому имя хранится как есть и разрешается заново при каждом
+//! подключении. Если имя разрешилось в несколько адресов, `ResolvePolicy`
+//! выбирает семейство.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+/// Выбор адреса среди результатов разрешения имени
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolvePolicy {
+    /// Первый адрес в порядке системного резолвера
+    #[default]
+    Any,
+    /// IPv4, если есть, иначе первый
+    PreferIpv4,
+    /// IPv6, если есть, иначе первый
+    PreferIpv6,
+}
+
+impl ResolvePolicy {
+    /// Выбирает адрес из результатов разрешения
+    pub fn select(&self, addrs: impl IntoIterator<Item = SocketAddr>) -> Option<SocketAddr> {
+        let mut first = None;
+        for addr in addrs {
+            let preferred = match self {
+                Self::Any => true,
+                Self::PreferIpv4 => addr.is_ipv4(),
+                Self::PreferIpv6 => addr.is_ipv6(),
+            };
+            if preferred {
+                return Some(addr);
+            }
+            first.get_or_insert(addr);
+        }
+        first
+    }
+}
+
+/// Адрес устройства
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DeviceAddress {
+    Ip(SocketAddr),
+    /// Имя хоста; разрешается при каждом подключении
+    Host { host: String, port: u16 },
+}
+
+impl DeviceAddress {
+    /// Адрес по имени хоста
+    pub fn host(host: &str, port: u16) -> Self {
+        Self::Host {
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    /// IP адрес без разрешения имени (None для имени хоста)
+    pub fn ip(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Ip(addr) => Some(*addr),
+            Self::Host { .. } => None,
+        }
+    }
+
+    /// Разрешает адрес; IP возвращается сразу
+    pub async fn resolve(&self, policy: ResolvePolicy) -> io::Result<SocketAddr> {
+        match self {
+            Self::Ip(addr) => Ok(*addr),
+            Self::Host { host, port } => {
+                let addrs = tokio::net::lookup_host((host.as_str(), *port)).await?;
+                policy.select(addrs).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("No addresses for {}", self),
+                    )
+                })
+            }
+        }
+    }
+}
+
+impl From<SocketAddr> for DeviceAddress {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Ip(addr)
+    }
+}
+
+impl PartialEq<SocketAddr> for DeviceAddress {
+    fn eq(&self, other: &SocketAddr) -> bool {
+        self.ip() == Some(*other)
+    }
+}
+
+impl FromStr for DeviceAddress {
+    type Err = String;
+
+    /// `192.168.1.10:3030`, `[::1]:3030` или `kettle.local:3030`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(addr) = s.parse::<SocketAddr>() {
+            return Ok(Self::Ip(addr));
+        }
+        let (host, port) = s
+            .rsplit_once(':')
+            .ok_or_else(|| format!("Address without port: {}", s))?;
+        let port = port
+            .parse()
+            .map_err(|_| format!("Invalid port in address: {}", s))?;
+        if host.is_empty() || host.contains(':') {
+            return Err(format!("Invalid host in address: {}", s));
+        }
+        Ok(Self::host(host, port))
+    }
+}
+
+impl fmt::Display for DeviceAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip(addr) => write!(f, "{}", addr),
+            Self::Host { host, port } => write!(f, "{}:{}", host, port),
+        }
+    }
+}
+
+impl Serialize for DeviceAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_select() {
+        let ip: DeviceAddress = "[::1]:3030".parse().unwrap();
+        assert_eq!(ip, "[::1]:3030".parse::<SocketAddr>().unwrap());
+        let host: DeviceAddress = "kettle.local:3030".parse().unwrap();
+        assert_eq!(host, DeviceAddress::host("kettle.local", 3030));
+        assert_eq!(host.to_string(), "kettle.local:3030");
+        assert!("kettle.local".parse::<DeviceAddress>().is_err());
+        assert!("::1:3030".parse::<DeviceAddress>().is_err());
+
+        let json = serde_json::to_string(&host).unwrap();
+        assert_eq!(json, r#""kettle.local:3030""#);
+        assert_eq!(serde_json::from_str::<DeviceAddress>(&json).unwrap(), host);
+
+        let v4: SocketAddr = "10.0.0.1:80".parse().unwrap();
+        let v6: SocketAddr = "[fe80::1]:80".parse().unwrap();
+        assert_eq!(ResolvePolicy::Any.select([v6, v4]), Some(v6));
+        assert_eq!(ResolvePolicy::PreferIpv4.select([v6, v4]), Some(v4));
+        assert_eq!(ResolvePolicy::PreferIpv6.select([v4]), Some(v4));
+        assert_eq!(ResolvePolicy::PreferIpv6.select([]), None);
+    }
+
+    #[tokio::test]
+    async fn resolves_localhost() {
+        let addr = DeviceAddress::host("localhost", 3030)
+            .resolve(ResolvePolicy::PreferIpv4)
+            .await
+            .unwrap();
+        assert_eq!(addr, "127.0.0.1:3030".parse::<SocketAddr>().unwrap());
+    }
+}
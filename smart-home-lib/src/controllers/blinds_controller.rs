@@ -0,0 +1,365 @@
+//! Async TCP контроллер для жалюзи
+//!
+//! Команды движения (`open`, `close`, `set_position`) возвращаются сразу
+//! после ответа жалюзи. `move_to` дожидается конца хода, опрашивая
+//! положение; каждое полученное положение публикуется подписчикам
+//! (`subscribe`) как событие хода `BlindsProgress`.
+
+use super::health::HealthStatus;
+use super::link::{DeviceLink, LinkError};
+use crate::devices::SmartBlinds;
+use crate::protocol::blinds_protocol::{
+    BlindsCommand, BlindsData, BlindsResponse, send_blinds_command_and_receive,
+};
+use crate::protocol::socket_protocol::DEFAULT_MAX_MESSAGE_SIZE;
+use crate::protocol::transport::SharedTransport;
+use crate::report::ReportOptions;
+use crate::traits::{AsyncReporter, Lifecycle, Reporter, stale_report};
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::{Instant, timeout};
+
+/// Интервал опроса положения при ожидании конца хода по умолчанию
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Максимальное ожидание конца хода по умолчанию
+pub const DEFAULT_MOVEMENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Размер буфера событий хода
+const PROGRESS_CAPACITY: usize = 64;
+
+/// Ошибки контроллера жалюзи
+#[derive(Debug, Clone)]
+pub enum BlindsError {
+    /// Ошибка связи или ответ жалюзи с ошибкой
+    Link(LinkError),
+    /// Жалюзи не дошли до положения за отведенное время
+    MovementTimeout { position: u8, target: u8 },
+}
+
+impl fmt::Display for BlindsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Link(e) => write!(f, "{}", e),
+            Self::MovementTimeout { position, target } => write!(
+                f,
+                "Жалюзи не дошли до положения {}% (остановились на {}%)",
+                target, position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BlindsError {}
+
+impl From<LinkError> for BlindsError {
+    fn from(e: LinkError) -> Self {
+        Self::Link(e)
+    }
+}
+
+impl BlindsError {
+    /// Вид ошибки: `movement_timeout` или вид ошибки связи
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Link(e) => e.kind(),
+            Self::MovementTimeout { .. } => "movement_timeout",
+        }
+    }
+}
+
+/// Событие хода жалюзи
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlindsProgress {
+    /// Текущее положение, %
+    pub position: u8,
+    /// Положение, к которому движутся жалюзи, %
+    pub target: u8,
+    pub moving: bool,
+}
+
+impl From<&BlindsData> for BlindsProgress {
+    fn from(data: &BlindsData) -> Self {
+        Self {
+            position: data.position,
+            target: data.target,
+            moving: data.moving,
+        }
+    }
+}
+
+/// Async контроллер жалюзи (TCP)
+pub struct BlindsController {
+    /// Модель жалюзи (последнее известное положение)
+    blinds: SmartBlinds,
+    /// Соединение с жалюзи
+    link: DeviceLink,
+    /// Интервал опроса положения в `move_to`
+    poll_interval: Duration,
+    /// Максимальное ожидание конца хода в `move_to`
+    movement_timeout: Duration,
+    /// События хода для подписчиков
+    progress: broadcast::Sender<BlindsProgress>,
+}
+
+impl BlindsController {
+    /// Создает контроллер жалюзи
+    pub fn new(address: SocketAddr, timeout: Duration) -> Self {
+        Self {
+            blinds: SmartBlinds::new(0.0),
+            link: DeviceLink::new(address, timeout),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            movement_timeout: DEFAULT_MOVEMENT_TIMEOUT,
+            progress: broadcast::channel(PROGRESS_CAPACITY).0,
+        }
+    }
+
+    /// Builder: транспорт для подключения к жалюзи
+    pub fn with_transport(mut self, transport: SharedTransport) -> Self {
+        self.link.set_transport(transport);
+        self
+    }
+
+    /// Builder: интервал опроса положения при ожидании конца хода
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Builder: максимальное ожидание конца хода (по умолчанию 60 с)
+    pub fn with_movement_timeout(mut self, limit: Duration) -> Self {
+        self.movement_timeout = limit;
+        self
+    }
+
+    /// Подписка на события хода
+    ///
+    /// Событие публикуется после каждого ответа жалюзи: на команды движения
+    /// и на опросы положения. Отставший подписчик пропускает старые события.
+    pub fn subscribe(&self) -> broadcast::Receiver<BlindsProgress> {
+        self.progress.subscribe()
+    }
+
+    /// Отправляет команду, синхронизирует модель и публикует событие хода
+    async fn command(&mut self, command: BlindsCommand) -> Result<BlindsData, BlindsError> {
+        let result = self.exchange(&command).await;
+        match &result {
+            Ok(data) => {
+                self.link.record_success();
+                self.blinds
+                    .set_state(data.position as f64, data.target as f64, data.moving);
+                let _ = self.progress.send(data.into());
+            }
+            Err(e) => self.link.record_error(e.kind(), e),
+        }
+        result
+    }
+
+    async fn exchange(&mut self, command: &BlindsCommand) -> Result<BlindsData, BlindsError> {
+        let limit = self.link.timeout();
+        let stream = self.link.connect().await?;
+        let exchange = send_blinds_command_and_receive(stream, command, DEFAULT_MAX_MESSAGE_SIZE);
+        let result = timeout(limit, exchange).await;
+
+        match self.link.finish(result)? {
+            BlindsResponse::Ok(data) => Ok(data),
+            BlindsResponse::Error { message } => Err(LinkError::DeviceError(message).into()),
+        }
+    }
+
+    /// Полностью открывает жалюзи (не дожидаясь конца хода)
+    pub async fn open(&mut self) -> Result<BlindsData, BlindsError> {
+        self.command(BlindsCommand::Open).await
+    }
+
+    /// Полностью закрывает жалюзи (не дожидаясь конца хода)
+    pub async fn close(&mut self) -> Result<BlindsData, BlindsError> {
+        self.command(BlindsCommand::Close).await
+    }
+
+    /// Начинает движение к положению `percent` (не дожидаясь конца хода)
+    pub async fn set_position(&mut self, percent: u8) -> Result<BlindsData, BlindsError> {
+        self.command(BlindsCommand::SetPosition {
+            percent: percent.min(100),
+        })
+        .await
+    }
+
+    /// Останавливает жалюзи в текущем положении
+    pub async fn stop(&mut self) -> Result<BlindsData, BlindsError> {
+        self.command(BlindsCommand::Stop).await
+    }
+
+    /// Запрашивает текущее положение
+    pub async fn status(&mut self) -> Result<BlindsData, BlindsError> {
+        self.command(BlindsCommand::Status).await
+    }
+
+    /// Перемещает жалюзи в положение `percent` и дожидается конца хода
+    ///
+    /// Положение опрашивается каждые `poll_interval`. Если жалюзи не дошли
+    /// за `movement_timeout` (например, их остановили кнопкой), возвращается
+    /// `BlindsError::MovementTimeout`. Возвращает итоговое положение.
+    pub async fn move_to(&mut self, percent: u8) -> Result<u8, BlindsError> {
+        let deadline = Instant::now() + self.movement_timeout;
+        let mut data = self.set_position(percent).await?;
+        while data.moving {
+            if Instant::now() >= deadline {
+                let error = BlindsError::MovementTimeout {
+                    position: data.position,
+                    target: data.target,
+                };
+                self.link.record_error(error.kind(), &error);
+                return Err(error);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+            data = self.status().await?;
+        }
+        Ok(data.position)
+    }
+
+    /// Последнее известное состояние жалюзи
+    pub fn device(&self) -> &SmartBlinds {
+        &self.blinds
+    }
+
+    /// Разрывает соединение
+    pub fn disconnect(&mut self) {
+        self.link.disconnect();
+    }
+
+    /// Возвращает адрес жалюзи
+    pub fn address(&self) -> SocketAddr {
+        self.link.address()
+    }
+
+    /// Состояние связи с жалюзи
+    pub fn health_status(&self) -> HealthStatus {
+        self.link.health_status()
+    }
+}
+
+impl Reporter for BlindsController {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        self.blinds.report_with(options)
+    }
+}
+
+impl AsyncReporter for BlindsController {
+    /// Запрашивает положение жалюзи и формирует отчет
+    async fn live_report(&mut self, limit: Duration) -> String {
+        let result = match timeout(limit, self.status()).await {
+            Ok(result) => result.map(|_| ()),
+            Err(_) => Err(LinkError::Timeout.into()),
+        };
+        match result {
+            Ok(()) => self.report(),
+            Err(e) => stale_report(self.report(), e),
+        }
+    }
+}
+
+impl Lifecycle for BlindsController {
+    /// Открывает соединение с жалюзи
+    async fn start(&mut self) -> std::io::Result<()> {
+        self.link.start().await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        self.disconnect();
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        self.health_status()
+    }
+}
+
+impl fmt::Display for BlindsController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulators::blinds_emulator::BlindsEmulator;
+    use crate::protocol::transport::InMemory;
+    use crate::units::Percent;
+    use std::sync::Arc;
+
+    async fn start_blinds(travel_time: Duration) -> (BlindsEmulator, BlindsController) {
+        let transport = InMemory::new();
+        let mut emulator = BlindsEmulator::new("10.0.0.6:7000").with_travel_time(travel_time);
+        emulator.start_in_memory(&transport).await.unwrap();
+        let controller =
+            BlindsController::new(emulator.local_addr().unwrap(), Duration::from_secs(1))
+                .with_transport(Arc::new(transport))
+                .with_poll_interval(Duration::from_millis(10));
+        (emulator, controller)
+    }
+
+    #[tokio::test]
+    async fn move_with_progress() {
+        let (mut emulator, mut controller) = start_blinds(Duration::from_millis(200)).await;
+        let mut progress = controller.subscribe();
+
+        assert_eq!(controller.move_to(100).await.unwrap(), 100);
+        assert_eq!(controller.device().position(), Percent::new(100.0));
+        assert!(!controller.device().is_moving());
+        assert_eq!(emulator.position(), 100.0);
+
+        // Первое событие - начало хода, последнее - конец
+        let mut events = Vec::new();
+        while let Ok(event) = progress.try_recv() {
+            events.push(event);
+        }
+        assert!(events.len() >= 2);
+        assert!(events[0].moving);
+        assert_eq!(events[0].target, 100);
+        assert_eq!(
+            events.last(),
+            Some(&BlindsProgress {
+                position: 100,
+                target: 100,
+                moving: false
+            })
+        );
+        assert!(events.windows(2).all(|w| w[0].position <= w[1].position));
+
+        // Остановка посреди хода
+        let data = controller.close().await.unwrap();
+        assert!(data.moving);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let data = controller.stop().await.unwrap();
+        assert!(!data.moving);
+        assert!(data.position > 0 && data.position < 100);
+        assert!(controller.health_status().connected);
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn movement_timeout() {
+        let (mut emulator, controller) = start_blinds(Duration::from_secs(60)).await;
+        let mut controller = controller.with_movement_timeout(Duration::from_millis(50));
+
+        assert!(matches!(
+            controller.move_to(100).await,
+            Err(BlindsError::MovementTimeout { target: 100, .. })
+        ));
+        assert_eq!(
+            controller.health_status().error_counts["movement_timeout"],
+            1
+        );
+        assert!(emulator.is_moving());
+
+        emulator.stop().await;
+    }
+}
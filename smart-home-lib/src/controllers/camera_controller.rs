@@ -0,0 +1,258 @@
+//! Async TCP контроллер для камеры
+//!
+//! Запрашивает у камеры снимки JPEG (`camera_protocol`) и хранит последний
+//! полученный снимок. Снимок принимается в буфер, который используется
+//! повторно, и отдается как `Bytes`: копии снимка для потребителей не
+//! копируют данные.
+
+use super::health::HealthStatus;
+use super::link::{DeviceLink, LinkError};
+use crate::devices::SmartCamera;
+use crate::protocol::camera_protocol::{CameraResponse, is_jpeg, request_snapshot};
+use crate::protocol::now_ms;
+use crate::protocol::transport::SharedTransport;
+use crate::report::ReportOptions;
+use crate::traits::{Lifecycle, Reporter};
+use bytes::Bytes;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Максимальный размер снимка по умолчанию, байт
+pub const DEFAULT_MAX_SNAPSHOT_SIZE: usize = 8 * 1024 * 1024;
+
+/// Ошибки контроллера камеры
+#[derive(Debug, Clone)]
+pub enum CameraError {
+    /// Ошибка связи или ответ камеры с ошибкой
+    Link(LinkError),
+    /// Полученные данные не похожи на JPEG
+    InvalidSnapshot,
+}
+
+impl fmt::Display for CameraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Link(e) => write!(f, "{}", e),
+            Self::InvalidSnapshot => write!(f, "Снимок не в формате JPEG"),
+        }
+    }
+}
+
+impl std::error::Error for CameraError {}
+
+impl From<LinkError> for CameraError {
+    fn from(e: LinkError) -> Self {
+        Self::Link(e)
+    }
+}
+
+impl CameraError {
+    /// Вид ошибки: `invalid_snapshot` или вид ошибки связи
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Link(e) => e.kind(),
+            Self::InvalidSnapshot => "invalid_snapshot",
+        }
+    }
+}
+
+/// Async контроллер камеры (TCP)
+pub struct CameraController {
+    /// Модель камеры с последним снимком
+    camera: SmartCamera,
+    /// Соединение с камерой; таймаут ограничивает и получение снимка
+    link: DeviceLink,
+    /// Буфер приема снимка
+    buffer: Vec<u8>,
+    /// Максимальный размер снимка, байт
+    max_snapshot_size: usize,
+}
+
+impl CameraController {
+    /// Создает контроллер камеры
+    pub fn new(address: SocketAddr, timeout: Duration) -> Self {
+        Self {
+            camera: SmartCamera::new(0, 0),
+            link: DeviceLink::new(address, timeout),
+            buffer: Vec::new(),
+            max_snapshot_size: DEFAULT_MAX_SNAPSHOT_SIZE,
+        }
+    }
+
+    /// Builder: транспорт для подключения к камере
+    pub fn with_transport(mut self, transport: SharedTransport) -> Self {
+        self.link.set_transport(transport);
+        self
+    }
+
+    /// Builder: максимальный размер снимка (по умолчанию 8 МБ)
+    pub fn with_max_snapshot_size(mut self, bytes: usize) -> Self {
+        self.max_snapshot_size = bytes;
+        self
+    }
+
+    /// Запрашивает новый снимок у камеры
+    ///
+    /// Снимок становится последним (`latest_snapshot`). При ошибке
+    /// последний снимок не меняется, соединение закрывается.
+    pub async fn snapshot(&mut self) -> Result<Bytes, CameraError> {
+        let result = self.fetch_snapshot().await;
+        match &result {
+            Ok(_) => self.link.record_success(),
+            Err(e) => {
+                self.link.record_error(e.kind(), e);
+                self.link.disconnect();
+            }
+        }
+        result
+    }
+
+    async fn fetch_snapshot(&mut self) -> Result<Bytes, CameraError> {
+        let limit = self.link.timeout();
+        let max_size = self.max_snapshot_size;
+        let stream = self.link.connect().await?;
+        let result = timeout(limit, request_snapshot(stream, &mut self.buffer, max_size)).await;
+
+        let info = match self.link.finish(result)? {
+            CameraResponse::Snapshot(info) => info,
+            CameraResponse::Error { message } => {
+                return Err(LinkError::DeviceError(message).into());
+            }
+        };
+        if !is_jpeg(&self.buffer) {
+            return Err(CameraError::InvalidSnapshot);
+        }
+
+        // Снимок - копия данных буфера, сам буфер остается для следующего приема
+        let snapshot = Bytes::copy_from_slice(&self.buffer);
+
+        self.camera.set_snapshot(
+            snapshot.clone(),
+            (info.width, info.height),
+            info.sequence,
+            now_ms(),
+        );
+        Ok(snapshot)
+    }
+
+    /// Последний полученный снимок (пустой, если снимков еще не было)
+    pub fn latest_snapshot(&self) -> Bytes {
+        self.camera.latest_snapshot()
+    }
+
+    /// Модель камеры: разрешение и последний снимок
+    pub fn device(&self) -> &SmartCamera {
+        &self.camera
+    }
+
+    /// Разрывает соединение
+    pub fn disconnect(&mut self) {
+        self.link.disconnect();
+    }
+
+    /// Возвращает адрес камеры
+    pub fn address(&self) -> SocketAddr {
+        self.link.address()
+    }
+
+    /// Состояние связи с камерой
+    pub fn health_status(&self) -> HealthStatus {
+        self.link.health_status()
+    }
+}
+
+impl Reporter for CameraController {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        self.camera.report_with(options)
+    }
+}
+
+impl Lifecycle for CameraController {
+    /// Открывает соединение с камерой
+    async fn start(&mut self) -> std::io::Result<()> {
+        self.link.start().await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        self.disconnect();
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        self.health_status()
+    }
+}
+
+impl fmt::Display for CameraController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulators::camera_emulator::{CameraEmulator, test_pattern};
+    use crate::protocol::transport::InMemory;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn fetches_snapshots() {
+        let transport = InMemory::new();
+        let mut emulator = CameraEmulator::new("10.0.0.5:554").with_resolution(160, 120);
+        emulator.start_in_memory(&transport).await.unwrap();
+
+        let mut controller =
+            CameraController::new(emulator.local_addr().unwrap(), Duration::from_secs(1))
+                .with_transport(Arc::new(transport.clone()));
+        assert!(controller.latest_snapshot().is_empty());
+
+        let first = controller.snapshot().await.unwrap();
+        assert_eq!(first, test_pattern(160, 120, 1));
+        let second = controller.snapshot().await.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(controller.latest_snapshot(), second);
+        assert_eq!(controller.device().resolution(), (160, 120));
+        assert_eq!(controller.device().sequence(), Some(2));
+        assert!(controller.health_status().connected);
+        assert!(
+            controller
+                .report()
+                .starts_with("Smart Camera: 160x120, snapshot")
+        );
+
+        // Снимок больше допустимого отклоняется, последний снимок сохраняется
+        let mut limited =
+            CameraController::new(emulator.local_addr().unwrap(), Duration::from_secs(1))
+                .with_transport(Arc::new(transport))
+                .with_max_snapshot_size(100);
+        assert!(matches!(
+            limited.snapshot().await,
+            Err(CameraError::Link(LinkError::CommandError(_)))
+        ));
+        assert!(limited.latest_snapshot().is_empty());
+        assert_eq!(limited.health_status().error_counts["command"], 1);
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn unreachable_camera() {
+        let mut controller =
+            CameraController::new("10.0.0.9:554".parse().unwrap(), Duration::from_secs(1))
+                .with_transport(Arc::new(InMemory::new()));
+
+        assert!(matches!(
+            controller.snapshot().await,
+            Err(CameraError::Link(LinkError::ConnectionError(_)))
+        ));
+        assert!(Lifecycle::start(&mut controller).await.is_err());
+        assert_eq!(controller.health_status().error_counts["connection"], 2);
+    }
+}
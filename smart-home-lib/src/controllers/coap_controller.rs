@@ -0,0 +1,321 @@
+//! Async CoAP контроллер для устройств с ограниченными ресурсами
+//!
+//! Каждый запрос выполняется отдельным обменом CON/ACK по UDP, подписка
+//! Observe держит собственный сокет и получает уведомления без опроса.
+
+use crate::protocol::bind;
+use crate::protocol::coap::{CoapCode, CoapMessage, CoapType};
+use crate::units::Celsius;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+/// Путь ресурса температуры по умолчанию
+pub const TEMPERATURE_PATH: &str = "temperature";
+
+/// Ошибки CoAP контроллера
+#[derive(Debug, Clone)]
+pub enum CoapError {
+    /// Ошибка сети
+    NetworkError(String),
+    /// Таймаут ожидания ответа
+    Timeout,
+    /// Устройство ответило кодом ошибки
+    ResponseError(CoapCode),
+    /// Некорректная нагрузка ответа
+    InvalidPayload(String),
+}
+
+impl fmt::Display for CoapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NetworkError(msg) => write!(f, "Сетевая ошибка: {}", msg),
+            Self::Timeout => write!(f, "Таймаут операции"),
+            Self::ResponseError(code) => write!(f, "Устройство вернуло код {}", code),
+            Self::InvalidPayload(msg) => write!(f, "Некорректные данные: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CoapError {}
+
+impl From<std::io::Error> for CoapError {
+    fn from(e: std::io::Error) -> Self {
+        Self::NetworkError(e.to_string())
+    }
+}
+
+/// Async контроллер CoAP устройства
+pub struct CoapController {
+    /// Адрес устройства
+    address: SocketAddr,
+    /// Таймаут ожидания ответа
+    timeout: Duration,
+    /// Счетчик message id (он же token запроса)
+    next_message_id: AtomicU16,
+}
+
+impl CoapController {
+    /// Создает новый контроллер
+    pub fn new(address: SocketAddr, timeout: Duration) -> Self {
+        Self {
+            address,
+            timeout,
+            next_message_id: AtomicU16::new(rand::random()),
+        }
+    }
+
+    /// Возвращает адрес устройства
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Возвращает таймаут
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Читает значение ресурса
+    pub async fn get(&self, path: &str) -> Result<String, CoapError> {
+        let request = self.request(CoapCode::GET).with_path(path);
+        let socket = self.connect().await?;
+        let response = self.exchange(&socket, &request).await?;
+        text_of(&response)
+    }
+
+    /// Записывает значение ресурса
+    pub async fn put(&self, path: &str, value: &str) -> Result<(), CoapError> {
+        let request = self.request(CoapCode::PUT).with_path(path).with_text(value);
+        let socket = self.connect().await?;
+        self.exchange(&socket, &request).await?;
+        Ok(())
+    }
+
+    /// Читает температуру с ресурса `temperature`
+    pub async fn temperature(&self) -> Result<Celsius, CoapError> {
+        parse_celsius(&self.get(TEMPERATURE_PATH).await?)
+    }
+
+    /// Подписывается на изменения ресурса (Observe)
+    pub async fn observe(&self, path: &str) -> Result<CoapObservation, CoapError> {
+        let request = self.request(CoapCode::GET).with_path(path).with_observe(0);
+        let socket = self.connect().await?;
+        let response = self.exchange(&socket, &request).await?;
+
+        if response.observe().is_none() {
+            // Устройство не поддерживает Observe для этого ресурса
+            return Err(CoapError::ResponseError(response.code));
+        }
+
+        Ok(CoapObservation {
+            value: text_of(&response)?,
+            socket,
+            token: request.token,
+            path: path.to_string(),
+            sequence: response.observe(),
+        })
+    }
+
+    /// Новый CON запрос с очередным message id
+    fn request(&self, code: CoapCode) -> CoapMessage {
+        let message_id = self.next_message_id.fetch_add(1, Ordering::Relaxed);
+        CoapMessage::new(
+            CoapType::Confirmable,
+            code,
+            message_id,
+            &message_id.to_be_bytes(),
+        )
+    }
+
+    /// Создает UDP сокет, связанный с адресом устройства
+    async fn connect(&self) -> Result<UdpSocket, CoapError> {
+        let socket = UdpSocket::bind(bind::unspecified_for(self.address)).await?;
+        socket.connect(self.address).await?;
+        Ok(socket)
+    }
+
+    /// Отправляет запрос и ждет ответ с тем же token
+    async fn exchange(
+        &self,
+        socket: &UdpSocket,
+        request: &CoapMessage,
+    ) -> Result<CoapMessage, CoapError> {
+        socket.send(&request.encode()).await?;
+
+        let mut buf = [0u8; 1152];
+        let response = timeout(self.timeout, async {
+            loop {
+                let size = socket.recv(&mut buf).await?;
+                if let Ok(message) = CoapMessage::decode(&buf[..size])
+                    && message.token == request.token
+                {
+                    return Ok::<_, CoapError>(message);
+                }
+            }
+        })
+        .await
+        .map_err(|_| CoapError::Timeout)??;
+
+        if response.code.is_success() {
+            Ok(response)
+        } else {
+            Err(CoapError::ResponseError(response.code))
+        }
+    }
+}
+
+/// Активная подписка Observe; отписка выполняется при Drop
+pub struct CoapObservation {
+    socket: UdpSocket,
+    token: Vec<u8>,
+    path: String,
+    value: String,
+    sequence: Option<u32>,
+}
+
+impl CoapObservation {
+    /// Последнее полученное значение
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Ждет следующего уведомления и возвращает новое значение
+    pub async fn next(&mut self) -> Result<String, CoapError> {
+        let mut buf = [0u8; 1152];
+
+        loop {
+            let size = self.socket.recv(&mut buf).await?;
+            let Ok(message) = CoapMessage::decode(&buf[..size]) else {
+                continue;
+            };
+            if message.token != self.token {
+                continue;
+            }
+
+            if message.msg_type == CoapType::Confirmable {
+                let ack = CoapMessage::new(
+                    CoapType::Acknowledgement,
+                    CoapCode::EMPTY,
+                    message.message_id,
+                    &[],
+                );
+                self.socket.send(&ack.encode()).await?;
+            }
+
+            // Устаревшие (переупорядоченные) уведомления пропускаются
+            if let (Some(last), Some(current)) = (self.sequence, message.observe())
+                && current <= last
+            {
+                continue;
+            }
+            self.sequence = message.observe();
+
+            self.value = text_of(&message)?;
+            return Ok(self.value.clone());
+        }
+    }
+
+    /// Ждет следующей температуры
+    pub async fn next_temperature(&mut self) -> Result<Celsius, CoapError> {
+        let value = self.next().await?;
+        parse_celsius(&value)
+    }
+}
+
+impl Drop for CoapObservation {
+    fn drop(&mut self) {
+        // Явная отмена подписки (Observe = 1), без ожидания ответа
+        let cancel = CoapMessage::new(CoapType::NonConfirmable, CoapCode::GET, 0, &self.token)
+            .with_path(&self.path)
+            .with_observe(1);
+        let _ = self.socket.try_send(&cancel.encode());
+    }
+}
+
+fn text_of(message: &CoapMessage) -> Result<String, CoapError> {
+    message
+        .text()
+        .map(str::to_string)
+        .map_err(|e| CoapError::InvalidPayload(e.to_string()))
+}
+
+fn parse_celsius(value: &str) -> Result<Celsius, CoapError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .map(Celsius::new)
+        .map_err(|e| CoapError::InvalidPayload(format!("{}: '{}'", e, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulators::coap_emulator::CoapEmulator;
+
+    #[test]
+    fn parse_temperature_payload() {
+        assert_eq!(parse_celsius(" 21.5 ").unwrap(), Celsius::new(21.5));
+        assert!(matches!(
+            parse_celsius("warm"),
+            Err(CoapError::InvalidPayload(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn timeout_without_device() {
+        // Никто не слушает: ответа не будет
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        let controller = CoapController::new(addr, Duration::from_millis(100));
+
+        assert!(matches!(
+            controller.get("temperature").await,
+            Err(CoapError::Timeout)
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async UDP networking"]
+    async fn get_put_and_observe() {
+        let mut emulator = CoapEmulator::new("127.0.0.1:0")
+            .with_resource("temperature", "21.5")
+            .with_writable_resource("relay", "off");
+        emulator.start().await.unwrap();
+
+        let controller =
+            CoapController::new(emulator.local_addr().unwrap(), Duration::from_secs(1));
+
+        assert_eq!(controller.temperature().await.unwrap(), Celsius::new(21.5));
+
+        controller.put("relay", "on").await.unwrap();
+        assert_eq!(controller.get("relay").await.unwrap(), "on");
+        assert!(matches!(
+            controller.put("temperature", "0").await,
+            Err(CoapError::ResponseError(CoapCode::METHOD_NOT_ALLOWED))
+        ));
+        assert!(matches!(
+            controller.get("missing").await,
+            Err(CoapError::ResponseError(CoapCode::NOT_FOUND))
+        ));
+
+        let mut observation = controller.observe("temperature").await.unwrap();
+        assert_eq!(observation.value(), "21.5");
+        assert_eq!(emulator.observers_count(), 1);
+
+        emulator.set_value("temperature", "22.0");
+        let next = timeout(Duration::from_secs(1), observation.next_temperature())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(next, Celsius::new(22.0));
+
+        drop(observation);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(emulator.observers_count(), 0);
+
+        emulator.stop().await;
+    }
+}
@@ -0,0 +1,88 @@
+//! Типизированные команды для любого контроллера
+//!
+//! `DeviceController::execute` принимает `DeviceCommand` и возвращает
+//! `DeviceResult`, поэтому код уровня дома может управлять устройствами,
+//! не разбирая варианты контроллеров. Команда, которую устройство не
+//! поддерживает, завершается ошибкой `DeviceError::Unsupported`.
+
+use super::{SocketError, ThermError};
+use crate::units::{Celsius, Watts};
+use std::fmt;
+
+/// Команда устройству
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceCommand {
+    /// Включить
+    TurnOn,
+    /// Выключить
+    TurnOff,
+    /// Прочитать температуру
+    ReadTemperature,
+    /// Прочитать потребляемую мощность
+    ReadPower,
+}
+
+impl fmt::Display for DeviceCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TurnOn => write!(f, "включение"),
+            Self::TurnOff => write!(f, "выключение"),
+            Self::ReadTemperature => write!(f, "чтение температуры"),
+            Self::ReadPower => write!(f, "чтение мощности"),
+        }
+    }
+}
+
+/// Результат успешной команды
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceOutput {
+    /// Команда выполнена, данных нет
+    Done,
+    /// Температура термометра
+    Temperature(Celsius),
+    /// Мощность розетки
+    Power(Watts),
+}
+
+/// Ошибка выполнения команды
+#[derive(Debug, Clone)]
+pub enum DeviceError {
+    /// Устройство не поддерживает команду
+    Unsupported {
+        command: DeviceCommand,
+        device: &'static str,
+    },
+    /// Ошибка розетки
+    Socket(SocketError),
+    /// Ошибка термометра
+    Therm(ThermError),
+}
+
+impl fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported { command, device } => {
+                write!(f, "Устройство '{}' не поддерживает {}", device, command)
+            }
+            Self::Socket(e) => write!(f, "{}", e),
+            Self::Therm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DeviceError {}
+
+impl From<SocketError> for DeviceError {
+    fn from(e: SocketError) -> Self {
+        Self::Socket(e)
+    }
+}
+
+impl From<ThermError> for DeviceError {
+    fn from(e: ThermError) -> Self {
+        Self::Therm(e)
+    }
+}
+
+/// Результат команды устройству
+pub type DeviceResult = Result<DeviceOutput, DeviceError>;
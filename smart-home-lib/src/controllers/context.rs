@@ -0,0 +1,138 @@
+//! Ошибки контроллеров с указанием устройства
+//!
+//! `SocketError` и `ThermError` описывают только сбой, но не устройство:
+//! в логе дома с десятком розеток строка "Ошибка подключения" бесполезна.
+//! `ControllerError` добавляет к ошибке `ErrorContext` - комнату, ключ,
+//! сетевой адрес, операцию и время сбоя.
+
+use super::{DeviceCommand, DeviceController, DeviceError, DeviceOutput};
+use crate::protocol::now_ms;
+use serde::Serialize;
+use std::fmt;
+
+/// Устройство и операция, при которой произошла ошибка
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorContext {
+    pub room: String,
+    pub key: String,
+    /// Сетевой адрес контроллера
+    pub address: String,
+    /// Выполнявшаяся операция
+    pub operation: String,
+    /// Время ошибки, мс с Unix epoch
+    pub timestamp_ms: u64,
+}
+
+impl ErrorContext {
+    /// Контекст операции `operation` устройства с адресом `address`; время - текущее
+    pub fn new(
+        room: &str,
+        key: &str,
+        address: impl fmt::Display,
+        operation: impl fmt::Display,
+    ) -> Self {
+        Self {
+            room: room.to_string(),
+            key: key.to_string(),
+            address: address.to_string(),
+            operation: operation.to_string(),
+            timestamp_ms: now_ms(),
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} ({}), {}, t={}",
+            self.room, self.key, self.address, self.operation, self.timestamp_ms
+        )
+    }
+}
+
+/// Ошибка контроллера вместе с устройством, на котором она произошла
+#[derive(Debug, Clone)]
+pub struct ControllerError {
+    pub context: ErrorContext,
+    pub error: DeviceError,
+}
+
+impl ControllerError {
+    pub fn new(context: ErrorContext, error: impl Into<DeviceError>) -> Self {
+        Self {
+            context,
+            error: error.into(),
+        }
+    }
+}
+
+impl fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.context, self.error)
+    }
+}
+
+impl std::error::Error for ControllerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl DeviceController {
+    /// Как `execute`, но ошибка содержит комнату, ключ и адрес устройства
+    pub async fn execute_in(
+        &mut self,
+        room: &str,
+        key: &str,
+        command: DeviceCommand,
+    ) -> Result<DeviceOutput, ControllerError> {
+        match self.execute(command).await {
+            Ok(output) => Ok(output),
+            Err(e) => Err(ControllerError::new(
+                ErrorContext::new(room, key, self.address(), command),
+                e,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::{SocketController, SocketError};
+    use std::error::Error;
+    use std::time::Duration;
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn error_names_device() {
+        // Порт без слушателя - подключение не удастся
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let mut controller: DeviceController =
+            SocketController::new(addr, 1000.0, Duration::from_millis(200)).into();
+        let error = controller
+            .execute_in("kitchen", "kettle", DeviceCommand::TurnOn)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.context.room, "kitchen");
+        assert_eq!(error.context.key, "kettle");
+        assert_eq!(error.context.address, addr.to_string());
+        assert_eq!(error.context.operation, "включение");
+        assert!(error.context.timestamp_ms > 0);
+        assert!(matches!(
+            error.error,
+            DeviceError::Socket(SocketError::ConnectionError(_) | SocketError::Timeout)
+        ));
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!("[kitchen/kettle ({}), включение, t=", addr))
+        );
+        assert!(error.source().is_some());
+    }
+}
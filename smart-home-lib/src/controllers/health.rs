@@ -0,0 +1,60 @@
+//! Здоровье контроллеров
+//!
+//! `HealthStatus` описывает связь с устройством независимо от типа
+//! контроллера: есть ли связь сейчас, когда был последний успешный обмен и
+//! какие ошибки случались. Раньше ошибки были видны, только когда команда
+//! завершалась неудачей.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Состояние связи контроллера с устройством
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HealthStatus {
+    /// Связь с устройством есть
+    pub connected: bool,
+    /// Время последнего успешного обмена, мс с Unix epoch
+    pub last_success_ms: Option<u64>,
+    /// Количество ошибок по видам (`timeout`, `connection`, ...)
+    pub error_counts: BTreeMap<String, u64>,
+    /// Последняя ошибка
+    pub last_error: Option<String>,
+}
+
+impl HealthStatus {
+    /// Контроллер исправен
+    pub fn is_healthy(&self) -> bool {
+        self.connected
+    }
+
+    /// Общее количество ошибок
+    pub fn total_errors(&self) -> u64 {
+        self.error_counts.values().sum()
+    }
+
+    /// Учитывает ошибку вида `kind`
+    #[cfg_attr(not(feature = "net"), allow(dead_code))]
+    pub(crate) fn record_error(&mut self, kind: &str, error: impl fmt::Display) {
+        *self.error_counts.entry(kind.to_string()).or_default() += 1;
+        self.last_error = Some(error.to_string());
+    }
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", if self.connected { "OK" } else { "Unavailable" })?;
+        if !self.error_counts.is_empty() {
+            let counts: Vec<String> = self
+                .error_counts
+                .iter()
+                .map(|(kind, count)| format!("{}={}", kind, count))
+                .collect();
+            write!(f, ", errors: {}", counts.join(" "))?;
+        }
+        if let Some(error) = &self.last_error {
+            write!(f, ", last error: {}", error)?;
+        }
+        Ok(())
+    }
+}
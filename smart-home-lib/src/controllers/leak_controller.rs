@@ -0,0 +1,284 @@
+//! Async UDP контроллер датчика протечки
+//!
+//! Принимает события датчика (`LeakEvent`), обновляет модель датчика и
+//! публикует каждое новое событие подписчикам (`subscribe`). Повторы и
+//! события, пришедшие не по порядку, отбрасываются по номеру события.
+
+use super::health::HealthStatus;
+use crate::devices::LeakSensor;
+use crate::protocol::bind::{self, IpStack};
+use crate::protocol::leak_protocol::LeakEvent;
+use crate::protocol::now_ms;
+use crate::report::ReportOptions;
+use crate::traits::{Lifecycle, Reporter};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::UdpSocket;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Размер буфера событий для подписчиков
+const EVENTS_CAPACITY: usize = 64;
+
+/// Состояние, общее для контроллера и задачи приема
+#[derive(Debug, Default)]
+struct LeakState {
+    sensor: LeakSensor,
+    /// Номер последнего принятого события
+    last_sequence: Option<u64>,
+    health: HealthStatus,
+}
+
+/// Прием событий: общий для задачи приема и тестов
+#[derive(Clone)]
+struct LeakInbox {
+    state: Arc<Mutex<LeakState>>,
+    events: broadcast::Sender<LeakEvent>,
+    /// Принимать события только этого датчика
+    device_id: Option<String>,
+}
+
+impl LeakInbox {
+    /// Обрабатывает пакет; возвращает `true`, если событие принято
+    fn accept(&self, packet: &[u8]) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let event = match LeakEvent::parse(packet) {
+            Ok(event) => event,
+            Err(e) => {
+                state.health.record_error("malformed", e);
+                return false;
+            }
+        };
+        if self.device_id.is_some() && event.device_id != self.device_id {
+            return false;
+        }
+        // Номер 0 - датчик без нумерации событий
+        if event.sequence != 0
+            && state
+                .last_sequence
+                .is_some_and(|last| event.sequence <= last)
+        {
+            return false;
+        }
+
+        let now = now_ms();
+        state.last_sequence = Some(event.sequence);
+        state.sensor.record(event.leak, now);
+        state.health.last_success_ms = Some(now);
+        drop(state);
+
+        let _ = self.events.send(event);
+        true
+    }
+}
+
+/// Async контроллер датчика протечки (UDP)
+pub struct LeakSensorController {
+    /// Адрес для прослушивания UDP
+    listen_addr: String,
+    /// Режим IPv6 сокета приема
+    ip_stack: IpStack,
+    /// Адрес, на котором принимаются события (после start)
+    bound_addr: Option<SocketAddr>,
+    inbox: LeakInbox,
+    /// Задача приема событий
+    task: Option<JoinHandle<()>>,
+}
+
+impl LeakSensorController {
+    /// Создает контроллер, принимающий события на `listen_addr`
+    pub fn new(listen_addr: &str) -> Self {
+        Self {
+            listen_addr: listen_addr.to_string(),
+            ip_stack: IpStack::default(),
+            bound_addr: None,
+            inbox: LeakInbox {
+                state: Arc::new(Mutex::new(LeakState::default())),
+                events: broadcast::channel(EVENTS_CAPACITY).0,
+                device_id: None,
+            },
+            task: None,
+        }
+    }
+
+    /// Builder: принимать события только датчика `device_id`
+    pub fn with_device_id(mut self, device_id: &str) -> Self {
+        self.inbox.device_id = Some(device_id.to_string());
+        self
+    }
+
+    /// Builder: режим IPv6 сокета приема
+    pub fn with_ip_stack(mut self, stack: IpStack) -> Self {
+        self.ip_stack = stack;
+        self
+    }
+
+    /// Подписка на события датчика
+    pub fn subscribe(&self) -> broadcast::Receiver<LeakEvent> {
+        self.inbox.events.subscribe()
+    }
+
+    /// Начинает прием событий
+    pub async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+
+        let socket = bind::bind_udp(self.listen_addr.as_str(), self.ip_stack)?;
+        socket.set_nonblocking(true)?;
+        let socket = UdpSocket::from_std(socket)?;
+        self.bound_addr = Some(socket.local_addr()?);
+
+        let inbox = self.inbox.clone();
+        self.task = Some(tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            loop {
+                match socket.recv_from(&mut buf).await {
+                    Ok((size, _)) => {
+                        inbox.accept(&buf[..size]);
+                    }
+                    Err(e) => {
+                        eprintln!("⚠️ Прием событий протечки остановлен: {}", e);
+                        break;
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    /// Останавливает прием событий
+    pub fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        self.bound_addr = None;
+    }
+
+    /// Прием событий запущен
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    /// Адрес, на котором принимаются события (только после start)
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.bound_addr
+    }
+
+    /// Датчик сообщил о протечке
+    pub fn is_leaking(&self) -> bool {
+        self.lock().sensor.is_leaking()
+    }
+
+    /// Копия модели датчика
+    pub fn device(&self) -> LeakSensor {
+        self.lock().sensor.clone()
+    }
+
+    /// Прием событий идет; последнее событие и ошибки разбора пакетов
+    pub fn health_status(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self.is_running(),
+            ..self.lock().health.clone()
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LeakState> {
+        self.inbox.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for LeakSensorController {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Reporter for LeakSensorController {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        self.lock().sensor.report_with(options)
+    }
+}
+
+impl Lifecycle for LeakSensorController {
+    async fn start(&mut self) -> std::io::Result<()> {
+        LeakSensorController::start(self).await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        LeakSensorController::stop(self);
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        self.health_status()
+    }
+}
+
+impl fmt::Display for LeakSensorController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn accepts_new_events_only() {
+        let controller = LeakSensorController::new("127.0.0.1:0").with_device_id("bath");
+        let mut events = controller.subscribe();
+        let inbox = &controller.inbox;
+
+        assert!(inbox.accept(br#"{"leak":true,"sequence":2,"device_id":"bath"}"#));
+        assert!(controller.is_leaking());
+        // Повтор, старое событие и чужой датчик
+        assert!(!inbox.accept(br#"{"leak":true,"sequence":2,"device_id":"bath"}"#));
+        assert!(!inbox.accept(br#"{"leak":false,"sequence":1,"device_id":"bath"}"#));
+        assert!(!inbox.accept(br#"{"leak":false,"sequence":9,"device_id":"kitchen"}"#));
+        assert!(!inbox.accept(b"garbage"));
+        assert!(controller.is_leaking());
+
+        assert!(inbox.accept(br#"{"leak":false,"sequence":3,"device_id":"bath"}"#));
+        assert_eq!(controller.report(), "Leak Sensor: DRY");
+
+        let received: Vec<bool> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| event.leak)
+            .collect();
+        assert_eq!(received, vec![true, false]);
+        assert_eq!(controller.health_status().error_counts["malformed"], 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with UDP networking"]
+    async fn receives_udp_events() {
+        use crate::emulators::leak_emulator::LeakEmulator;
+
+        let mut controller = LeakSensorController::new("127.0.0.1:0");
+        controller.start().await.unwrap();
+        let mut events = controller.subscribe();
+
+        let addr = controller.local_addr().unwrap();
+        let mut emulator = LeakEmulator::new(&addr.to_string()).with_device_id("bath");
+        emulator.set_leak(true).await.unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(event.leak);
+        assert_eq!(event.device_id.as_deref(), Some("bath"));
+        assert!(controller.is_leaking());
+        assert!(controller.health_status().connected);
+
+        controller.stop();
+        assert!(!controller.health_status().connected);
+    }
+}
@@ -0,0 +1,154 @@
+//! Постоянное TCP соединение простых контроллеров
+//!
+//! `DeviceLink` - общая часть контроллеров жалюзи, камеры и клапана:
+//! соединение открывается при первой команде и переиспользуется, после
+//! сбоя передачи или таймаута закрывается, а следующая команда
+//! подключается заново. Там же ведутся счетчики здоровья. Контроллеру
+//! остается перевести свои команды и ответы в обмен на соединении.
+
+use super::health::HealthStatus;
+use crate::protocol::now_ms;
+use crate::protocol::transport::{Connection, SharedTransport, tokio_transport};
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::time::{error::Elapsed, timeout};
+
+/// Ошибки обмена с устройством по `DeviceLink`
+#[derive(Debug, Clone)]
+pub enum LinkError {
+    /// Ошибка подключения к устройству
+    ConnectionError(String),
+    /// Ошибка обмена с устройством
+    CommandError(String),
+    /// Устройство вернуло ошибку
+    DeviceError(String),
+    /// Таймаут операции
+    Timeout,
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConnectionError(msg) => write!(f, "Ошибка подключения: {}", msg),
+            Self::CommandError(msg) => write!(f, "Ошибка команды: {}", msg),
+            Self::DeviceError(msg) => write!(f, "Ошибка устройства: {}", msg),
+            Self::Timeout => write!(f, "Таймаут операции"),
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+impl LinkError {
+    /// Вид ошибки для счетчиков здоровья
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ConnectionError(_) => "connection",
+            Self::CommandError(_) => "command",
+            Self::DeviceError(_) => "device",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+/// Соединение с устройством и его здоровье
+pub(crate) struct DeviceLink {
+    address: SocketAddr,
+    /// Таймаут подключения и одного обмена
+    timeout: Duration,
+    connection: Option<Box<dyn Connection>>,
+    /// Откуда берутся соединения (по умолчанию TCP tokio)
+    transport: SharedTransport,
+    /// Последний успешный обмен и счетчики ошибок
+    health: HealthStatus,
+}
+
+impl DeviceLink {
+    pub(crate) fn new(address: SocketAddr, timeout: Duration) -> Self {
+        Self {
+            address,
+            timeout,
+            connection: None,
+            transport: tokio_transport(),
+            health: HealthStatus::default(),
+        }
+    }
+
+    pub(crate) fn set_transport(&mut self, transport: SharedTransport) {
+        self.transport = transport;
+    }
+
+    pub(crate) fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub(crate) fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Рабочее соединение; переподключается, если прежнее разорвано
+    pub(crate) async fn connect(&mut self) -> Result<&mut Box<dyn Connection>, LinkError> {
+        if self
+            .connection
+            .as_ref()
+            .is_some_and(|stream| stream.is_alive())
+        {
+            return Ok(self.connection.as_mut().unwrap());
+        }
+        self.connection = None;
+
+        let stream = timeout(self.timeout, self.transport.connect(self.address))
+            .await
+            .map_err(|_| LinkError::Timeout)?
+            .map_err(|e| LinkError::ConnectionError(e.to_string()))?;
+        Ok(self.connection.insert(stream))
+    }
+
+    /// Итог обмена, ограниченного `timeout`; при сбое соединение закрывается
+    pub(crate) fn finish<T>(
+        &mut self,
+        result: Result<std::io::Result<T>, Elapsed>,
+    ) -> Result<T, LinkError> {
+        let error = match result {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(e)) => LinkError::CommandError(e.to_string()),
+            Err(_) => LinkError::Timeout,
+        };
+        self.connection = None;
+        Err(error)
+    }
+
+    pub(crate) fn record_success(&mut self) {
+        self.health.last_success_ms = Some(now_ms());
+    }
+
+    pub(crate) fn record_error(&mut self, kind: &str, error: impl fmt::Display) {
+        self.health.record_error(kind, error);
+    }
+
+    /// Подключение для `Lifecycle::start`; ошибка учитывается в здоровье
+    pub(crate) async fn start(&mut self) -> std::io::Result<()> {
+        match self.connect().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                self.health.record_error(e.kind(), &e);
+                Err(std::io::Error::other(e))
+            }
+        }
+    }
+
+    pub(crate) fn disconnect(&mut self) {
+        self.connection = None;
+    }
+
+    pub(crate) fn health_status(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self
+                .connection
+                .as_ref()
+                .is_some_and(|stream| stream.is_alive()),
+            ..self.health.clone()
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Метрики контроллеров для push-панелей
+//!
+//! Контроллер обновляет `ControllerMetrics` после каждой операции и
+//! публикует их в `watch` канал: панель ждет `changed()` вместо опроса.
+//! Получатель видит только последние значения, промежуточные могут быть
+//! пропущены.
+
+use crate::protocol::now_ms;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Счетчики и последние значения контроллера
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ControllerMetrics {
+    /// Всего операций (команд розетке, показаний термометра)
+    pub operations: u64,
+    /// Из них неудачных
+    pub errors: u64,
+    /// Длительность последней операции
+    pub last_latency: Option<Duration>,
+    /// Время последней успешной операции, мс с Unix epoch
+    pub last_success_ms: Option<u64>,
+    /// Последнее значение: мощность в ваттах или температура в °C
+    pub last_value: Option<f64>,
+}
+
+/// Канал, в который контроллер публикует метрики
+pub type MetricsSender = watch::Sender<ControllerMetrics>;
+
+/// Новый канал метрик без получателей
+pub(crate) fn channel() -> MetricsSender {
+    watch::Sender::new(ControllerMetrics::default())
+}
+
+/// Учитывает успешную операцию
+pub(crate) fn record_success(sender: &MetricsSender, latency: Option<Duration>, value: Option<f64>) {
+    sender.send_modify(|metrics| {
+        metrics.operations += 1;
+        metrics.last_latency = latency.or(metrics.last_latency);
+        metrics.last_success_ms = Some(now_ms());
+        if value.is_some() {
+            metrics.last_value = value;
+        }
+    });
+}
+
+/// Учитывает неудачную операцию
+pub(crate) fn record_error(sender: &MetricsSender, latency: Option<Duration>) {
+    sender.send_modify(|metrics| {
+        metrics.operations += 1;
+        metrics.errors += 1;
+        metrics.last_latency = latency.or(metrics.last_latency);
+    });
+}
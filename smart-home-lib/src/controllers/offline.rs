@@ -0,0 +1,60 @@
+//! `DeviceController` без сетевого слоя
+//!
+//! Тип не имеет значений, поэтому комнаты и дом сохраняют прежний интерфейс
+//! (`Room::controllers_keys`, `SmartHouse::health_report`), но контроллеров
+//! в них нет.
+
+use super::HealthStatus;
+use crate::traits::{AsyncReporter, Lifecycle, Reporter};
+use std::time::Duration;
+
+/// Универсальный тип для контроллеров (без фичи `net` - пустой)
+pub enum DeviceController {}
+
+impl DeviceController {
+    /// Название типа устройства
+    pub fn kind(&self) -> &'static str {
+        match *self {}
+    }
+
+    /// Состояние связи с устройством
+    pub fn health(&self) -> HealthStatus {
+        match *self {}
+    }
+
+    /// Сетевой протокол связи с устройством
+    pub fn transport(&self) -> &'static str {
+        match *self {}
+    }
+
+    /// Сетевой адрес устройства
+    pub fn address(&self) -> String {
+        match *self {}
+    }
+}
+
+impl Reporter for DeviceController {
+    fn report(&self) -> String {
+        match *self {}
+    }
+}
+
+impl AsyncReporter for DeviceController {
+    async fn live_report(&mut self, _timeout: Duration) -> String {
+        match *self {}
+    }
+}
+
+impl Lifecycle for DeviceController {
+    async fn start(&mut self) -> std::io::Result<()> {
+        match *self {}
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        match *self {}
+    }
+
+    fn health(&self) -> HealthStatus {
+        match *self {}
+    }
+}
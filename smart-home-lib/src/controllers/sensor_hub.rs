@@ -0,0 +1,373 @@
+//! Общий UDP приемник показаний датчиков
+//!
+//! `SensorHub` слушает один UDP порт, разбирает пакеты всех датчиков и по
+//! `device_id` передает показания зарегистрированным термометрам
+//! (`ThermController::feed`) или обработчикам. Так многим датчикам не
+//! нужен отдельный порт на каждый контроллер.
+
+use super::supervisor::{self, ControllerHealth, RestartPolicy, SharedHealth};
+use super::therm_controller::{ThermController, ThermFeed, is_idle};
+use crate::protocol::bind::{self, IpStack};
+use crate::protocol::{ThermData, now_ms};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Обработчик показаний датчика
+type SensorHandler = Box<dyn Fn(&ThermData) + Send + 'static>;
+
+/// Получатель показаний одного датчика
+enum Route {
+    Therm(ThermFeed),
+    Handler(SensorHandler),
+}
+
+impl Route {
+    fn deliver(&self, data: &ThermData) {
+        match self {
+            Self::Therm(feed) => feed.push(data.temperature),
+            Self::Handler(handler) => handler(data),
+        }
+    }
+}
+
+/// Статистика приема пакетов
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IngestStats {
+    /// Всего принято пакетов
+    pub received: u64,
+    /// Передано получателям
+    pub routed: u64,
+    /// Разобраны, но получателя нет (или нет `device_id`)
+    pub unrouted: u64,
+    /// Не удалось разобрать
+    pub malformed: u64,
+    /// Переданные пакеты по датчикам
+    pub per_device: BTreeMap<String, u64>,
+    /// Время последнего пакета, мс с Unix epoch
+    pub last_packet_ms: Option<u64>,
+}
+
+/// Общий UDP приемник показаний датчиков
+pub struct SensorHub {
+    /// Адрес для прослушивания UDP
+    listen_addr: String,
+    /// Режим IPv6 сокета приема
+    ip_stack: IpStack,
+    /// Получатели по `device_id`
+    routes: Arc<Mutex<HashMap<String, Route>>>,
+    /// Получатель пакетов без зарегистрированного датчика
+    fallback: Arc<Mutex<Option<SensorHandler>>>,
+    stats: Arc<Mutex<IngestStats>>,
+    /// Флаг работы фонового потока
+    running: Arc<AtomicBool>,
+    /// Handle фонового потока
+    thread_handle: Option<JoinHandle<()>>,
+    /// Политика перезапуска фонового потока
+    restart_policy: RestartPolicy,
+    /// Здоровье фонового потока
+    health: SharedHealth,
+}
+
+impl SensorHub {
+    /// Создает приемник на адресе `listen_addr`
+    pub fn new(listen_addr: &str) -> Self {
+        Self {
+            listen_addr: listen_addr.to_string(),
+            ip_stack: IpStack::default(),
+            routes: Arc::new(Mutex::new(HashMap::new())),
+            fallback: Arc::new(Mutex::new(None)),
+            stats: Arc::new(Mutex::new(IngestStats::default())),
+            running: Arc::new(AtomicBool::new(false)),
+            thread_handle: None,
+            restart_policy: RestartPolicy::default(),
+            health: SharedHealth::default(),
+        }
+    }
+
+    /// Builder: политика перезапуска фонового потока
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// Builder: режим IPv6 сокета приема
+    pub fn with_ip_stack(mut self, stack: IpStack) -> Self {
+        self.ip_stack = stack;
+        self
+    }
+
+    /// Builder: обработчик пакетов, для которых получатель не найден
+    pub fn with_fallback<F>(self, handler: F) -> Self
+    where
+        F: Fn(&ThermData) + Send + 'static,
+    {
+        if let Ok(mut fallback) = self.fallback.lock() {
+            *fallback = Some(Box::new(handler));
+        }
+        self
+    }
+
+    /// Передавать показания датчика `device_id` термометру
+    pub fn register_therm(&self, device_id: &str, controller: &ThermController) {
+        self.add_route(device_id, Route::Therm(controller.feed()));
+    }
+
+    /// Передавать показания датчика `device_id` обработчику
+    pub fn register_handler<F>(&self, device_id: &str, handler: F)
+    where
+        F: Fn(&ThermData) + Send + 'static,
+    {
+        self.add_route(device_id, Route::Handler(Box::new(handler)));
+    }
+
+    /// Удаляет получателя датчика; `false`, если его не было
+    pub fn unregister(&self, device_id: &str) -> bool {
+        self.routes
+            .lock()
+            .map(|mut routes| routes.remove(device_id).is_some())
+            .unwrap_or(false)
+    }
+
+    /// Датчики с зарегистрированными получателями
+    pub fn device_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .routes
+            .lock()
+            .map(|routes| routes.keys().cloned().collect())
+            .unwrap_or_default();
+        ids.sort();
+        ids
+    }
+
+    /// Статистика приема
+    pub fn stats(&self) -> IngestStats {
+        self.stats
+            .lock()
+            .map(|stats| stats.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Состояние фонового потока приема
+    pub fn health(&self) -> ControllerHealth {
+        self.health
+            .lock()
+            .map(|health| health.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Прием запущен
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Запускает прием в фоне
+    pub fn start(&mut self) {
+        if self.running.load(Ordering::Relaxed) {
+            return; // Уже запущен
+        }
+
+        self.running.store(true, Ordering::Relaxed);
+
+        let running = Arc::clone(&self.running);
+        let listen_addr = self.listen_addr.clone();
+        let ip_stack = self.ip_stack;
+        let routes = Arc::clone(&self.routes);
+        let fallback = Arc::clone(&self.fallback);
+        let stats = Arc::clone(&self.stats);
+        let health = Arc::clone(&self.health);
+        let policy = self.restart_policy;
+
+        let handle = thread::spawn(move || {
+            supervisor::supervise(
+                &running,
+                &health,
+                policy,
+                || {
+                    // Паника обработчика могла отравить мьютексы
+                    routes.clear_poison();
+                    fallback.clear_poison();
+                    let socket = bind::bind_udp(listen_addr.as_str(), ip_stack)?;
+                    // Неблокирующее чтение
+                    socket.set_nonblocking(true)?;
+                    Ok(socket)
+                },
+                |socket| {
+                    let mut buf = [0; 1024];
+
+                    while running.load(Ordering::Relaxed) {
+                        match socket.recv_from(&mut buf) {
+                            Ok((size, _)) => {
+                                ingest(&buf[..size], &routes, &fallback, &stats);
+                            }
+                            Err(e) if is_idle(&e) => {
+                                // Нет данных, спим немного
+                                thread::sleep(Duration::from_millis(10));
+                            }
+                            // Сокет неисправен - супервизор создаст новый
+                            Err(e) => return Err(e),
+                        }
+                    }
+
+                    Ok(())
+                },
+            );
+        });
+
+        self.thread_handle = Some(handle);
+    }
+
+    /// Останавливает прием
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn add_route(&self, device_id: &str, route: Route) {
+        if let Ok(mut routes) = self.routes.lock() {
+            routes.insert(device_id.to_string(), route);
+        }
+    }
+}
+
+impl Drop for SensorHub {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Разбирает пакет и передает показание получателю
+fn ingest(
+    packet: &[u8],
+    routes: &Mutex<HashMap<String, Route>>,
+    fallback: &Mutex<Option<SensorHandler>>,
+    stats: &Mutex<IngestStats>,
+) {
+    // `device_id` ссылается на пакет: строка копируется только для статистики
+    let data = ThermData::parse(packet).ok();
+
+    let delivered = data.as_ref().and_then(|data| {
+        let device_id = data.device_id.as_deref()?;
+        let routes = routes.lock().ok()?;
+        routes.get(device_id)?.deliver(data);
+        Some(device_id)
+    });
+
+    if let (Some(data), None) = (&data, &delivered)
+        && let Ok(fallback) = fallback.lock()
+        && let Some(handler) = fallback.as_ref()
+    {
+        handler(data);
+    }
+
+    if let Ok(mut stats) = stats.lock() {
+        stats.received += 1;
+        stats.last_packet_ms = Some(now_ms());
+        match (&data, delivered) {
+            (None, _) => stats.malformed += 1,
+            (Some(_), None) => stats.unrouted += 1,
+            (Some(_), Some(device_id)) => {
+                stats.routed += 1;
+                match stats.per_device.get_mut(device_id) {
+                    Some(count) => *count += 1,
+                    None => {
+                        stats.per_device.insert(device_id.to_string(), 1);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::Celsius;
+    use std::net::UdpSocket;
+    use std::sync::mpsc;
+
+    fn send(addr: &str, temperature: f64, device_id: Option<&str>) {
+        let data = ThermData {
+            temperature,
+            device_id: device_id.map(Into::into),
+        };
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender
+            .send_to(serde_json::to_string(&data).unwrap().as_bytes(), addr)
+            .unwrap();
+    }
+
+    fn wait_for(hub: &SensorHub, received: u64) -> IngestStats {
+        for _ in 0..100 {
+            let stats = hub.stats();
+            if stats.received >= received {
+                return stats;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        hub.stats()
+    }
+
+    #[test]
+    fn routes_by_device_id() {
+        let port = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let addr = format!("127.0.0.1:{}", port);
+
+        let kitchen = ThermController::new(0.0, "127.0.0.1:0", Duration::from_secs(5));
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let (fallback_tx, fallback_rx) = mpsc::channel();
+        let fallback_tx = Mutex::new(fallback_tx);
+
+        let mut hub = SensorHub::new(&addr).with_fallback(move |data| {
+            let _ = fallback_tx
+                .lock()
+                .unwrap()
+                .send(data.device_id.as_deref().map(str::to_string));
+        });
+        hub.register_therm("kitchen", &kitchen);
+        hub.register_handler("garage", move |data| {
+            let _ = tx.lock().unwrap().send(data.temperature);
+        });
+        assert_eq!(hub.device_ids(), vec!["garage", "kitchen"]);
+
+        hub.start();
+        thread::sleep(Duration::from_millis(50));
+        send(&addr, 21.5, Some("kitchen"));
+        send(&addr, 4.0, Some("garage"));
+        send(&addr, 30.0, Some("attic"));
+        send(&addr, 18.0, None);
+        UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .send_to(b"garbage", &addr)
+            .unwrap();
+
+        let stats = wait_for(&hub, 5);
+        assert_eq!(stats.received, 5);
+        assert_eq!(stats.routed, 2);
+        assert_eq!(stats.unrouted, 2);
+        assert_eq!(stats.malformed, 1);
+        assert_eq!(stats.per_device.get("kitchen"), Some(&1));
+        assert!(stats.last_packet_ms.is_some());
+
+        assert_eq!(kitchen.temperature().unwrap().value, Celsius::new(21.5));
+        assert!(kitchen.health_status().is_healthy());
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), 4.0);
+        let unrouted: Vec<_> = fallback_rx.try_iter().collect();
+        assert_eq!(unrouted, vec![Some("attic".to_string()), None]);
+
+        assert!(hub.unregister("garage"));
+        assert!(!hub.unregister("garage"));
+        hub.stop();
+        assert!(!hub.is_running());
+    }
+}
@@ -0,0 +1,206 @@
+//! Рабочие параметры контроллеров
+//!
+//! `ControllerConfig` хранится вместе с конфигурацией дома и применяется
+//! при создании контроллера. Незаданные параметры берут значения по
+//! умолчанию, параметры другого типа устройства игнорируются.
+
+use super::RestartPolicy;
+use super::address::{DeviceAddress, ResolvePolicy};
+use super::socket_controller::DEFAULT_KEEPALIVE_INTERVAL;
+use crate::protocol::IpStack;
+use crate::discovery::{DEFAULT_MAX_AGE, DEFAULT_SOCKET_TIMEOUT};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Политика перезапуска в конфигурации (задержки в миллисекундах)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RestartConfig {
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+}
+
+fn default_multiplier() -> f64 {
+    RestartPolicy::default().multiplier
+}
+
+impl From<RestartConfig> for RestartPolicy {
+    fn from(config: RestartConfig) -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+            multiplier: config.multiplier,
+        }
+    }
+}
+
+impl From<RestartPolicy> for RestartConfig {
+    fn from(policy: RestartPolicy) -> Self {
+        Self {
+            initial_backoff_ms: policy.initial_backoff.as_millis() as u64,
+            max_backoff_ms: policy.max_backoff.as_millis() as u64,
+            multiplier: policy.multiplier,
+        }
+    }
+}
+
+/// Рабочие параметры контроллера устройства
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ControllerConfig {
+    /// Таймаут TCP операций розетки, мс
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Простой соединения розетки, после которого отправляется ping, мс
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive_ms: Option<u64>,
+    /// Максимальный возраст показаний термометра, мс
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_ms: Option<u64>,
+    /// Возраст показаний термометра, после которого они считаются устаревающими, мс
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning_age_ms: Option<u64>,
+    /// Политика перезапуска цикла приема термометра
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<RestartConfig>,
+    /// Резервные адреса розетки (IP или имя хоста)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<DeviceAddress>,
+    /// Выбор семейства адресов при разрешении имени розетки
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolve: Option<ResolvePolicy>,
+    /// Режим IPv6 сокета приема термометра
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_stack: Option<IpStack>,
+}
+
+impl ControllerConfig {
+    /// Создает параметры по умолчанию
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: таймаут TCP операций розетки
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    /// Builder: интервал простоя перед проверкой соединения розетки
+    pub fn with_keepalive_interval(mut self, interval: Duration) -> Self {
+        self.keepalive_ms = Some(interval.as_millis() as u64);
+        self
+    }
+
+    /// Builder: максимальный возраст показаний термометра
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age_ms = Some(max_age.as_millis() as u64);
+        self
+    }
+
+    /// Builder: порог предупреждения о возрасте показаний термометра
+    pub fn with_warning_age(mut self, warning_age: Duration) -> Self {
+        self.warning_age_ms = Some(warning_age.as_millis() as u64);
+        self
+    }
+
+    /// Builder: политика перезапуска цикла приема
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart = Some(policy.into());
+        self
+    }
+
+    /// Builder: резервный адрес розетки (можно вызывать несколько раз)
+    pub fn with_fallback(mut self, address: impl Into<DeviceAddress>) -> Self {
+        self.fallbacks.push(address.into());
+        self
+    }
+
+    /// Builder: выбор семейства адресов при разрешении имени
+    pub fn with_resolve_policy(mut self, policy: ResolvePolicy) -> Self {
+        self.resolve = Some(policy);
+        self
+    }
+
+    /// Builder: режим IPv6 сокета приема термометра
+    pub fn with_ip_stack(mut self, stack: IpStack) -> Self {
+        self.ip_stack = Some(stack);
+        self
+    }
+
+    /// Все параметры по умолчанию
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Таймаут TCP операций розетки
+    pub fn timeout(&self) -> Duration {
+        self.timeout_ms
+            .map_or(DEFAULT_SOCKET_TIMEOUT, Duration::from_millis)
+    }
+
+    /// Интервал простоя перед проверкой соединения розетки
+    pub fn keepalive_interval(&self) -> Duration {
+        self.keepalive_ms
+            .map_or(DEFAULT_KEEPALIVE_INTERVAL, Duration::from_millis)
+    }
+
+    /// Максимальный возраст показаний термометра
+    pub fn max_age(&self) -> Duration {
+        self.max_age_ms
+            .map_or(DEFAULT_MAX_AGE, Duration::from_millis)
+    }
+
+    /// Порог предупреждения о возрасте показаний (по умолчанию половина `max_age`)
+    pub fn warning_age(&self) -> Duration {
+        self.warning_age_ms
+            .map_or(self.max_age() / 2, Duration::from_millis)
+    }
+
+    /// Политика перезапуска цикла приема
+    pub fn restart_policy(&self) -> RestartPolicy {
+        self.restart.map(Into::into).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let config = ControllerConfig::new();
+        assert!(config.is_default());
+        assert_eq!(config.timeout(), DEFAULT_SOCKET_TIMEOUT);
+        assert_eq!(config.max_age(), DEFAULT_MAX_AGE);
+        assert_eq!(config.warning_age(), DEFAULT_MAX_AGE / 2);
+        assert_eq!(config.keepalive_interval(), DEFAULT_KEEPALIVE_INTERVAL);
+        assert_eq!(config.restart_policy(), RestartPolicy::default());
+        assert_eq!(serde_json::to_string(&config).unwrap(), "{}");
+    }
+
+    #[test]
+    fn json_format() {
+        let config: ControllerConfig = serde_json::from_str(
+            r#"{"timeout_ms": 500, "restart": {"initial_backoff_ms": 50, "max_backoff_ms": 1000}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.timeout(), Duration::from_millis(500));
+        assert_eq!(config.max_age(), DEFAULT_MAX_AGE);
+        assert_eq!(
+            config.restart_policy(),
+            RestartPolicy {
+                initial_backoff: Duration::from_millis(50),
+                max_backoff: Duration::from_secs(1),
+                multiplier: 2.0,
+            }
+        );
+        assert_eq!(
+            config,
+            ControllerConfig::new()
+                .with_timeout(Duration::from_millis(500))
+                .with_restart_policy(config.restart_policy())
+        );
+    }
+}
@@ -1,18 +1,45 @@
 //! Async TCP контроллер для умной розетки
+//!
+//! Розетка может быть доступна по нескольким адресам (например, через две
+//! сети). После `with_failover_threshold` неудачных подключений подряд
+//! контроллер переходит на следующий адрес списка и сообщает об этом
+//! подписчикам `subscribe_failovers`. Адрес может быть именем хоста: оно
+//! разрешается заново при каждом переподключении (`DeviceAddress`).
 
+use super::address::{DeviceAddress, ResolvePolicy};
+use super::health::HealthStatus;
+use super::metrics::{self, ControllerMetrics, MetricsSender};
 use crate::devices::SmartSocket;
+use crate::ota::{self, UpdateProgress};
+use crate::protocol::error::ProtocolError;
+use crate::protocol::now_ms;
 use crate::protocol::socket_protocol::{
-    SocketCommand, SocketData, SocketResponse, send_command_and_receive,
+    DEFAULT_MAX_MESSAGE_SIZE, DeviceDescriptor, ErrorCode, FrameReader, OutletData, SocketCommand,
+    SocketData, SocketResponse,
 };
-use crate::traits::Reporter;
+use crate::protocol::transport::{Connection, SharedTransport, tokio_transport};
+use crate::report::ReportOptions;
+use crate::traits::{AsyncReporter, Lifecycle, Reporter, stale_report};
 use crate::units::Watts;
 use std::fmt;
 use std::net::SocketAddr;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use tokio::net::TcpStream;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
 use tokio::time::timeout;
 
+/// Интервал простоя соединения, после которого перед командой отправляется ping
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Пауза перед повтором команды, на которую розетка ответила `Busy`
+const BUSY_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Неудачных подключений подряд до перехода на резервный адрес
+pub const DEFAULT_FAILOVER_THRESHOLD: u32 = 3;
+
+/// Емкость канала событий переключения адреса
+const FAILOVER_CAPACITY: usize = 16;
+
 /// Ошибки контроллера розетки
 #[derive(Debug, Clone)]
 pub enum SocketError {
@@ -20,12 +47,18 @@ pub enum SocketError {
     ConnectionError(String),
     /// Ошибка отправки команды
     CommandError(String),
-    /// Розетка вернула ошибку
-    DeviceError(String),
+    /// Ответ не принят: разрыв соединения или поврежденные данные
+    Protocol(ProtocolError),
+    /// Розетка вернула ошибку с кодом
+    DeviceError { code: ErrorCode, message: String },
     /// Ошибка блокировки
     LockError,
     /// Таймаут операции
     Timeout,
+    /// Сработала защита розетки от перегрузки
+    Tripped,
+    /// Мощность устройства превышает заданный предел
+    PowerLimitExceeded { power: Watts, limit: Watts },
 }
 
 impl std::fmt::Display for SocketError {
@@ -33,43 +66,260 @@ impl std::fmt::Display for SocketError {
         match self {
             Self::ConnectionError(msg) => write!(f, "Ошибка подключения: {}", msg),
             Self::CommandError(msg) => write!(f, "Ошибка команды: {}", msg),
-            Self::DeviceError(msg) => write!(f, "Ошибка устройства: {}", msg),
+            Self::Protocol(e) => write!(f, "Ошибка протокола: {}", e),
+            Self::DeviceError { code, message } => {
+                write!(f, "Ошибка устройства ({}): {}", code, message)
+            }
             Self::LockError => write!(f, "Ошибка блокировки"),
             Self::Timeout => write!(f, "Таймаут операции"),
+            Self::Tripped => write!(f, "Сработала защита от перегрузки"),
+            Self::PowerLimitExceeded { power, limit } => {
+                write!(f, "Превышен предел мощности: {} > {}", power, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SocketError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Protocol(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl SocketError {
+    /// Вид ошибки для счетчиков здоровья
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ConnectionError(_) => "connection",
+            Self::CommandError(_) => "command",
+            Self::Protocol(e) => e.kind(),
+            Self::DeviceError { .. } => "device",
+            Self::LockError => "lock",
+            Self::Timeout => "timeout",
+            Self::Tripped => "tripped",
+            Self::PowerLimitExceeded { .. } => "power_limit",
+        }
+    }
+
+    /// Код ошибки, присланный розеткой
+    pub fn device_code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::DeviceError { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Соединение разорвано: команду можно повторить на новом соединении
+    ///
+    /// Таймаут, поврежденный и неожиданный ответ сюда не относятся - розетка
+    /// могла уже выполнить команду.
+    pub fn is_disconnect(&self) -> bool {
+        matches!(self, Self::Protocol(e) if e.is_disconnect())
+    }
+}
+
+/// Показание мощности с учетом его свежести
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerReading {
+    /// Мощность только что получена от розетки
+    Live(Watts),
+    /// Розетка не ответила; последняя полученная мощность и ее возраст
+    Cached { power: Watts, age: Duration },
+}
+
+impl PowerReading {
+    /// Мощность независимо от источника
+    pub fn power(&self) -> Watts {
+        match self {
+            Self::Live(power) | Self::Cached { power, .. } => *power,
         }
     }
+
+    /// Показание получено от розетки сейчас
+    pub fn is_live(&self) -> bool {
+        matches!(self, Self::Live(_))
+    }
 }
 
-impl std::error::Error for SocketError {}
+/// Переход контроллера на другой адрес розетки
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverEvent {
+    pub from: DeviceAddress,
+    pub to: DeviceAddress,
+    /// Неудачных подключений подряд к `from`
+    pub failures: u32,
+    /// Последняя ошибка подключения
+    pub error: String,
+    /// Время переключения, мс с Unix epoch
+    pub at_ms: u64,
+}
+
+impl fmt::Display for FailoverEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {} после {} ошибок подключения: {}",
+            self.from, self.to, self.failures, self.error
+        )
+    }
+}
+
+/// Адреса розетки: основной и резервные
+struct AddressList {
+    all: Vec<DeviceAddress>,
+    /// Индекс текущего адреса
+    active: usize,
+    /// Неудачных подключений подряд к текущему адресу
+    failures: u32,
+    /// Порог перехода на следующий адрес
+    threshold: u32,
+    events: broadcast::Sender<FailoverEvent>,
+    /// Выбор среди адресов, в которые разрешилось имя
+    policy: ResolvePolicy,
+    /// IP последнего подключения
+    resolved: Option<SocketAddr>,
+}
+
+impl AddressList {
+    fn new(primary: DeviceAddress) -> Self {
+        Self {
+            all: vec![primary],
+            active: 0,
+            failures: 0,
+            threshold: DEFAULT_FAILOVER_THRESHOLD,
+            events: broadcast::channel(FAILOVER_CAPACITY).0,
+            policy: ResolvePolicy::default(),
+            resolved: None,
+        }
+    }
+
+    fn current(&self) -> &DeviceAddress {
+        &self.all[self.active]
+    }
+
+    /// Переходит на следующий адрес, если ошибок подключения достаточно
+    fn fail_over(&mut self, error: &SocketError) -> bool {
+        if self.all.len() < 2 || self.failures < self.threshold {
+            return false;
+        }
+
+        let from = self.current().clone();
+        self.active = (self.active + 1) % self.all.len();
+        let event = FailoverEvent {
+            from,
+            to: self.current().clone(),
+            failures: self.failures,
+            error: error.to_string(),
+            at_ms: now_ms(),
+        };
+        self.failures = 0;
+        let _ = self.events.send(event);
+        true
+    }
+}
+
+/// Соединение с читателем кадров
+type FramedConnection = FrameReader<Box<dyn Connection>>;
 
 /// Async контроллер умной розетки (TCP)
 pub struct SocketController {
     /// Внутренняя розетка (модель состояния)
     socket: Arc<RwLock<SmartSocket>>,
-    /// Адрес розетки для TCP подключения
-    address: SocketAddr,
+    /// Адреса розетки и состояние перехода между ними
+    addresses: Box<AddressList>,
     /// Таймаут для TCP операций
     timeout: Duration,
-    /// Постоянное TCP соединение
-    connection: Option<TcpStream>,
+    /// Постоянное соединение; читатель хранит байты, принятые после ответа
+    connection: Option<Box<FramedConnection>>,
+    /// Откуда берутся соединения (по умолчанию TCP tokio)
+    transport: SharedTransport,
+    /// Интервал простоя, после которого соединение проверяется ping
+    keepalive_interval: Duration,
+    /// Время последнего обмена по соединению
+    last_activity: Option<Instant>,
+    /// Время отклика последнего ping
+    latency: Option<Duration>,
+    /// Последний успешный обмен и счетчики ошибок
+    health: HealthStatus,
+    /// Максимальный размер ответа розетки, байт
+    max_message_size: usize,
+    /// Время последнего успешного чтения мощности, мс с Unix epoch
+    last_power_ms: Option<u64>,
+    /// Последнее известное состояние розеток удлинителя
+    outlets: Vec<OutletData>,
+    /// Описание устройства, полученное `describe`
+    descriptor: Option<Box<DeviceDescriptor>>,
+    /// Метрики, обновляемые после каждого обмена
+    metrics: MetricsSender,
 }
 
 impl SocketController {
     /// Создает новый контроллер розетки
-    pub fn new(address: SocketAddr, power_rating: f64, timeout: Duration) -> Self {
+    ///
+    /// `address` - `SocketAddr` или `DeviceAddress` (в том числе имя хоста).
+    pub fn new(address: impl Into<DeviceAddress>, power_rating: f64, timeout: Duration) -> Self {
         Self {
             socket: Arc::new(RwLock::new(SmartSocket::new(power_rating))),
-            address,
+            addresses: Box::new(AddressList::new(address.into())),
             timeout,
             connection: None,
+            transport: tokio_transport(),
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+            last_activity: None,
+            latency: None,
+            health: HealthStatus::default(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            last_power_ms: None,
+            outlets: Vec::new(),
+            descriptor: None,
+            metrics: metrics::channel(),
         }
     }
 
+    /// Builder: интервал простоя, после которого соединение проверяется ping
+    pub fn with_keepalive_interval(mut self, interval: Duration) -> Self {
+        self.keepalive_interval = interval;
+        self
+    }
+
+    /// Builder: резервный адрес розетки (можно вызывать несколько раз)
+    pub fn with_fallback(mut self, address: impl Into<DeviceAddress>) -> Self {
+        self.addresses.all.push(address.into());
+        self
+    }
+
+    /// Builder: неудачных подключений подряд до перехода на следующий адрес
+    pub fn with_failover_threshold(mut self, failures: u32) -> Self {
+        self.addresses.threshold = failures.max(1);
+        self
+    }
+
+    /// Builder: какое семейство адресов выбирать при разрешении имени
+    pub fn with_resolve_policy(mut self, policy: ResolvePolicy) -> Self {
+        self.addresses.policy = policy;
+        self
+    }
+
+    /// Builder: транспорт для подключения к розетке
+    pub fn with_transport(mut self, transport: SharedTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Builder: максимальный размер ответа розетки (по умолчанию 1 МБ)
+    pub fn with_max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = bytes;
+        self
+    }
+
     /// Обеспечивает наличие соединения (переподключается при необходимости)
-    async fn ensure_connected(&mut self) -> Result<&mut TcpStream, SocketError> {
+    async fn ensure_connected(&mut self) -> Result<&mut FramedConnection, SocketError> {
         // Проверяем существующее соединение
         let need_reconnect = match &self.connection {
-            Some(stream) => !self.is_connection_alive(stream),
+            Some(stream) => !stream.get_ref().is_alive(),
             None => true,
         };
 
@@ -81,19 +331,167 @@ impl SocketController {
         // Переподключаемся
         self.connection = None;
 
-        // Создаем новое соединение с таймаутом
-        let stream = timeout(self.timeout, TcpStream::connect(self.address))
-            .await
-            .map_err(|_| SocketError::Timeout)?
-            .map_err(|e| SocketError::ConnectionError(e.to_string()))?;
+        // Каждый адрес пробуется не больше одного раза за вызов
+        let mut attempts = self.addresses.all.len();
+        loop {
+            // Имя разрешается заново: после переподключения IP мог смениться
+            let address = self.address().clone();
+            let policy = self.addresses.policy;
+            let transport = &self.transport;
+            let connect = async move {
+                let ip = address.resolve(policy).await?;
+                transport.connect(ip).await.map(|stream| (ip, stream))
+            };
+
+            // Создаем новое соединение с таймаутом
+            let error = match timeout(self.timeout, connect).await {
+                Ok(Ok((ip, stream))) => {
+                    self.addresses.failures = 0;
+                    self.addresses.resolved = Some(ip);
+                    self.connection = Some(Box::new(FrameReader::new(stream)));
+                    return Ok(self.connection.as_mut().unwrap());
+                }
+                Ok(Err(e)) => SocketError::ConnectionError(e.to_string()),
+                Err(_) => SocketError::Timeout,
+            };
+
+            self.addresses.failures += 1;
+            attempts -= 1;
+            if !self.addresses.fail_over(&error) || attempts == 0 {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Подписка на переходы между адресами розетки
+    pub fn subscribe_failovers(&self) -> broadcast::Receiver<FailoverEvent> {
+        self.addresses.events.subscribe()
+    }
+
+    /// Проверяет живость соединения без ожидания (`Connection::is_alive`)
+    fn is_connection_alive(stream: &dyn Connection) -> bool {
+        stream.is_alive()
+    }
+
+    /// Отправляет команду и получает ответ
+    ///
+    /// Если команда не прошла по уже открытому соединению, она один раз
+    /// повторяется после переподключения: соединение могло оборваться так,
+    /// что проверка живости этого еще не видела. Ответ `Busy` (лимит
+    /// клиентов или команд) тоже повторяется один раз по новому соединению.
+    async fn exchange(&mut self, command: SocketCommand) -> Result<SocketResponse, SocketError> {
+        let started = Instant::now();
+        let reused = self
+            .connection
+            .as_ref()
+            .is_some_and(|stream| Self::is_connection_alive(stream.get_ref().as_ref()));
+
+        // Переиспользованное соединение могло закрыться, пока простаивало
+        let result = match self.exchange_once(&command).await {
+            Err(e) if reused && e.is_disconnect() => self.exchange_once(&command).await,
+            result => result,
+        };
+
+        let result = match result {
+            Ok(SocketResponse::Error { code, .. }) if code.is_retryable() => {
+                // Занятая розетка закрывает соединение
+                self.connection = None;
+                tokio::time::sleep(BUSY_RETRY_DELAY).await;
+                self.exchange_once(&command).await
+            }
+            result => result,
+        };
+
+        match &result {
+            Ok(_) => self.health.last_success_ms = Some(now_ms()),
+            Err(e) => self.health.record_error(e.kind(), e),
+        }
+        self.record_metrics(&result, started.elapsed());
+        result
+    }
 
-        self.connection = Some(stream);
-        Ok(self.connection.as_mut().unwrap())
+    /// Обновляет метрики по результату обмена
+    fn record_metrics(&self, result: &Result<SocketResponse, SocketError>, latency: Duration) {
+        let latency = Some(latency);
+        match result {
+            Ok(SocketResponse::Ok(data)) => {
+                metrics::record_success(&self.metrics, latency, Some(data.power))
+            }
+            Ok(SocketResponse::Error { .. } | SocketResponse::Tripped(_)) | Err(_) => {
+                metrics::record_error(&self.metrics, latency)
+            }
+            Ok(_) => metrics::record_success(&self.metrics, latency, None),
+        }
+    }
+
+    /// Канал метрик: получатель видит обновление после каждой команды
+    pub fn metrics_watch(&self) -> watch::Receiver<ControllerMetrics> {
+        self.metrics.subscribe()
+    }
+
+    /// Одна попытка обмена
+    ///
+    /// При таймауте или ошибке передачи соединение закрывается, следующая
+    /// попытка подключится заново.
+    async fn exchange_once(
+        &mut self,
+        command: &SocketCommand,
+    ) -> Result<SocketResponse, SocketError> {
+        let cmd_timeout = self.timeout;
+        let max_size = self.max_message_size;
+        let stream = self.ensure_connected().await?;
+
+        let exchange = stream.send_command_and_receive_with_limit(command, max_size);
+        let result = match timeout(cmd_timeout, exchange).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(e)) => Err(SocketError::Protocol(e)),
+            Err(_) => Err(SocketError::Timeout),
+        };
+
+        match result {
+            Ok(_) => self.last_activity = Some(Instant::now()),
+            Err(_) => self.connection = None,
+        }
+        result
+    }
+
+    /// Проверяет соединение ping и возвращает время отклика
+    pub async fn ping(&mut self) -> Result<Duration, SocketError> {
+        let started = Instant::now();
+        match self.exchange(SocketCommand::Ping).await? {
+            SocketResponse::Pong => {
+                let rtt = started.elapsed();
+                self.latency = Some(rtt);
+                Ok(rtt)
+            }
+            SocketResponse::Error { code, message } => {
+                Err(SocketError::DeviceError { code, message })
+            }
+            _ => Err(SocketError::CommandError(
+                "Unexpected response to ping".to_string(),
+            )),
+        }
+    }
+
+    /// Проверяет простаивающее соединение ping
+    ///
+    /// Вызывается автоматически перед каждой командой; владелец контроллера
+    /// может вызывать его периодически. Возвращает время отклика, если ping
+    /// был отправлен. Мертвое соединение закрывается.
+    pub async fn keepalive(&mut self) -> Result<Option<Duration>, SocketError> {
+        let idle = match self.last_activity {
+            Some(last) if self.connection.is_some() => last.elapsed(),
+            _ => return Ok(None),
+        };
+        if idle < self.keepalive_interval {
+            return Ok(None);
+        }
+        self.ping().await.map(Some)
     }
 
-    /// Проверяет живость TCP соединения
-    fn is_connection_alive(&self, stream: &TcpStream) -> bool {
-        stream.peer_addr().is_ok()
+    /// Время отклика последнего успешного ping
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
     }
 
     /// Отправляет команду, получает ответ и синхронизирует состояние
@@ -101,13 +499,10 @@ impl SocketController {
         &mut self,
         command: SocketCommand,
     ) -> Result<SocketData, SocketError> {
-        let cmd_timeout = self.timeout;
-        let stream = self.ensure_connected().await?;
-
-        let response = timeout(cmd_timeout, send_command_and_receive(stream, &command))
-            .await
-            .map_err(|_| SocketError::Timeout)?
-            .map_err(|e| SocketError::CommandError(e.to_string()))?;
+        // Мертвое соединение обнаруживается до отправки команды
+        // и заменяется новым в exchange
+        let _ = self.keepalive().await;
+        let response = self.exchange(command).await?;
 
         match response {
             SocketResponse::Ok(data) => {
@@ -116,36 +511,285 @@ impl SocketController {
 
                 if data.active {
                     socket.turn_on();
+                    // Фактическая мощность зависит от уровня нагрузки диммера
+                    socket.set_current_power(Watts::new(data.power));
                 } else {
                     socket.turn_off();
                 }
+                self.outlets = data.outlets.clone();
 
                 Ok(data)
             }
-            SocketResponse::Error { message } => Err(SocketError::DeviceError(message)),
+            SocketResponse::Error { code, message } => {
+                let error = SocketError::DeviceError { code, message };
+                self.health.record_error(error.kind(), &error);
+                Err(error)
+            }
+            SocketResponse::Tripped(data) => {
+                self.outlets = data.outlets;
+                self.health
+                    .record_error(SocketError::Tripped.kind(), SocketError::Tripped);
+
+                // Защита отключила питание - отражаем это в локальном состоянии
+                let mut socket = self.socket.write().map_err(|_| SocketError::LockError)?;
+                socket.turn_off();
+
+                Err(SocketError::Tripped)
+            }
+            SocketResponse::Pong => Err(SocketError::CommandError(
+                "Unexpected pong response".to_string(),
+            )),
+            SocketResponse::Update(_) => Err(SocketError::CommandError(
+                "Unexpected update response".to_string(),
+            )),
+            SocketResponse::Descriptor(_) => Err(SocketError::CommandError(
+                "Unexpected descriptor response".to_string(),
+            )),
         }
     }
 
     /// Включает розетку
+    ///
+    /// При заданном пределе мощности включение отклоняется, если ожидаемая
+    /// мощность превышает предел. Если превышение видно только по данным
+    /// розетки после включения, она сразу выключается.
     pub async fn turn_on(&mut self) -> Result<(), SocketError> {
-        self.send_command_and_sync(SocketCommand::TurnOn).await?;
-        Ok(())
+        let socket = self.device()?;
+        let expected = socket.expected_power();
+        if let Some(limit) = socket.power_limit()
+            && socket.exceeds_power_limit(expected)
+        {
+            return Err(SocketError::PowerLimitExceeded {
+                power: expected,
+                limit,
+            });
+        }
+
+        let data = self
+            .send_command_and_sync(SocketCommand::TurnOn { outlet: None })
+            .await?;
+        self.enforce_power_limit(&data, None).await
     }
 
     /// Выключает розетку
     pub async fn turn_off(&mut self) -> Result<(), SocketError> {
-        self.send_command_and_sync(SocketCommand::TurnOff).await?;
+        self.send_command_and_sync(SocketCommand::TurnOff { outlet: None })
+            .await?;
+        Ok(())
+    }
+
+    /// Включает розетку удлинителя с номером `outlet`
+    ///
+    /// Мощность отдельных розеток контроллеру заранее не известна, поэтому
+    /// предел мощности проверяется по суммарной мощности после включения.
+    /// При превышении выключается только эта розетка.
+    pub async fn turn_on_outlet(&mut self, outlet: u8) -> Result<(), SocketError> {
+        let command = SocketCommand::TurnOn {
+            outlet: Some(outlet),
+        };
+        let data = self.send_command_and_sync(command).await?;
+        self.enforce_power_limit(&data, Some(outlet)).await
+    }
+
+    /// Выключает розетку удлинителя с номером `outlet`
+    pub async fn turn_off_outlet(&mut self, outlet: u8) -> Result<(), SocketError> {
+        let command = SocketCommand::TurnOff {
+            outlet: Some(outlet),
+        };
+        self.send_command_and_sync(command).await?;
+        Ok(())
+    }
+
+    /// Запрашивает состояние розеток удлинителя (пусто у одиночной розетки)
+    ///
+    /// Суммарная мощность удлинителя доступна через `power`.
+    pub async fn outlets(&mut self) -> Result<Vec<OutletData>, SocketError> {
+        let data = self.send_command_and_sync(SocketCommand::Power).await?;
+        self.last_power_ms = Some(now_ms());
+        Ok(data.outlets)
+    }
+
+    /// Последнее известное состояние розеток удлинителя
+    pub fn cached_outlets(&self) -> &[OutletData] {
+        &self.outlets
+    }
+
+    /// Устанавливает уровень нагрузки диммера (0-100%)
+    pub async fn set_load(&mut self, percent: u8) -> Result<(), SocketError> {
+        let data = self
+            .send_command_and_sync(SocketCommand::SetLoad { percent })
+            .await?;
+
+        {
+            let mut socket = self.socket.write().map_err(|_| SocketError::LockError)?;
+            socket.set_load(percent);
+        }
+
+        self.enforce_power_limit(&data, None).await
+    }
+
+    /// Выключает розетку (или розетку удлинителя `outlet`), если сообщенная
+    /// мощность превышает предел
+    async fn enforce_power_limit(
+        &mut self,
+        data: &SocketData,
+        outlet: Option<u8>,
+    ) -> Result<(), SocketError> {
+        let power = Watts::new(data.power);
+        let limit = {
+            let socket = self.socket.read().map_err(|_| SocketError::LockError)?;
+            match socket.power_limit() {
+                Some(limit) if data.active && socket.exceeds_power_limit(power) => limit,
+                _ => return Ok(()),
+            }
+        };
+
+        self.send_command_and_sync(SocketCommand::TurnOff { outlet })
+            .await?;
+        Err(SocketError::PowerLimitExceeded { power, limit })
+    }
+
+    /// Устанавливает предельную допустимую мощность
+    pub fn set_power_limit(&self, limit: Watts) -> Result<(), SocketError> {
+        let mut socket = self.socket.write().map_err(|_| SocketError::LockError)?;
+        socket.set_power_limit(limit);
+        Ok(())
+    }
+
+    /// Снимает ограничение мощности
+    pub fn clear_power_limit(&self) -> Result<(), SocketError> {
+        let mut socket = self.socket.write().map_err(|_| SocketError::LockError)?;
+        socket.clear_power_limit();
         Ok(())
     }
 
     /// Получает актуальную мощность с железки
     pub async fn power(&mut self) -> Result<Watts, SocketError> {
         let _data = self.send_command_and_sync(SocketCommand::Power).await?;
+        self.last_power_ms = Some(now_ms());
 
         let socket = self.socket.read().map_err(|_| SocketError::LockError)?;
         Ok(socket.current_power())
     }
 
+    /// Запрашивает мощность, а при ошибке возвращает последнее известное значение
+    ///
+    /// `PowerReading::Cached` содержит мощность из последнего успешного
+    /// ответа и его возраст; сама ошибка учитывается в `health_status`.
+    /// Если мощность еще ни разу не была получена, возвращается ошибка.
+    pub async fn try_power_with_fallback(&mut self) -> Result<PowerReading, SocketError> {
+        let error = match self.power().await {
+            Ok(power) => return Ok(PowerReading::Live(power)),
+            Err(e) => e,
+        };
+        let Some(last_power_ms) = self.last_power_ms else {
+            return Err(error);
+        };
+
+        let socket = self.socket.read().map_err(|_| SocketError::LockError)?;
+        Ok(PowerReading::Cached {
+            power: socket.current_power(),
+            age: Duration::from_millis(now_ms().saturating_sub(last_power_ms)),
+        })
+    }
+
+    /// Получает накопленную розеткой энергию в ватт-часах
+    pub async fn energy_wh(&mut self) -> Result<f64, SocketError> {
+        let data = self.send_command_and_sync(SocketCommand::Energy).await?;
+        Ok(data.energy_wh)
+    }
+
+    /// Описание устройства: тип, прошивка и поддерживаемые команды
+    ///
+    /// Запрашивается у розетки один раз, дальше возвращается сохраненное;
+    /// обновление прошивки сбрасывает его. Устройство без `describe`
+    /// отвечает ошибкой `Unsupported`.
+    pub async fn describe(&mut self) -> Result<DeviceDescriptor, SocketError> {
+        if let Some(descriptor) = &self.descriptor {
+            return Ok(DeviceDescriptor::clone(descriptor));
+        }
+
+        match self.exchange(SocketCommand::Describe).await? {
+            SocketResponse::Descriptor(descriptor) => {
+                self.descriptor = Some(Box::new(descriptor.clone()));
+                Ok(descriptor)
+            }
+            SocketResponse::Error { code, message } => {
+                let error = SocketError::DeviceError { code, message };
+                self.health.record_error(error.kind(), &error);
+                Err(error)
+            }
+            _ => Err(SocketError::CommandError(
+                "Unexpected response to describe".to_string(),
+            )),
+        }
+    }
+
+    /// Сохраненное описание устройства, если `describe` уже вызывался
+    pub fn cached_descriptor(&self) -> Option<&DeviceDescriptor> {
+        self.descriptor.as_deref()
+    }
+
+    /// Состояние обновления прошивки розетки
+    pub async fn update_status(&mut self) -> Result<UpdateProgress, SocketError> {
+        self.update_command(SocketCommand::UpdateStatus).await
+    }
+
+    /// Обновляет прошивку розетки до версии `version`
+    ///
+    /// Образ передается частями по `chunk_size` байт (см. `ota`); после
+    /// каждой команды `on_progress` получает состояние обновления. Если
+    /// розетка отвергла образ, возвращается `SocketError::DeviceError`.
+    pub async fn update_firmware<F>(
+        &mut self,
+        version: &str,
+        image: &[u8],
+        chunk_size: usize,
+        mut on_progress: F,
+    ) -> Result<UpdateProgress, SocketError>
+    where
+        F: FnMut(&UpdateProgress),
+    {
+        let chunk_size = chunk_size.max(1);
+        let start = SocketCommand::StartUpdate {
+            version: version.to_string(),
+            size: image.len() as u64,
+            checksum: ota::checksum(image),
+        };
+        on_progress(&self.update_command(start).await?);
+
+        for (index, chunk) in image.chunks(chunk_size).enumerate() {
+            let command = SocketCommand::UpdateChunk {
+                offset: (index * chunk_size) as u64,
+                data: ota::encode_chunk(chunk),
+            };
+            on_progress(&self.update_command(command).await?);
+        }
+
+        let progress = self.update_command(SocketCommand::FinishUpdate).await?;
+        self.descriptor = None;
+        on_progress(&progress);
+        Ok(progress)
+    }
+
+    /// Отправляет команду обновления прошивки
+    async fn update_command(
+        &mut self,
+        command: SocketCommand,
+    ) -> Result<UpdateProgress, SocketError> {
+        match self.exchange(command).await? {
+            SocketResponse::Update(progress) => Ok(progress),
+            SocketResponse::Error { code, message } => {
+                let error = SocketError::DeviceError { code, message };
+                self.health.record_error(error.kind(), &error);
+                Err(error)
+            }
+            _ => Err(SocketError::CommandError(
+                "Unexpected response to update command".to_string(),
+            )),
+        }
+    }
+
     /// Получает копию внутренней розетки
     pub fn device(&self) -> Result<SmartSocket, SocketError> {
         self.socket
@@ -159,15 +803,38 @@ impl SocketController {
         self.connection = None;
     }
 
-    /// Возвращает адрес розетки
-    pub fn address(&self) -> SocketAddr {
-        self.address
+    /// Возвращает текущий адрес розетки
+    pub fn address(&self) -> &DeviceAddress {
+        self.addresses.current()
+    }
+
+    /// IP последнего успешного подключения (для имени хоста - результат разрешения)
+    pub fn resolved_address(&self) -> Option<SocketAddr> {
+        self.addresses.resolved
+    }
+
+    /// Все адреса розетки: основной и резервные
+    pub fn addresses(&self) -> &[DeviceAddress] {
+        &self.addresses.all
     }
 
     /// Возвращает таймаут
     pub fn timeout(&self) -> Duration {
         self.timeout
     }
+
+    /// Состояние связи с розеткой
+    ///
+    /// Связь считается установленной, пока открыто живое соединение.
+    pub fn health_status(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self
+                .connection
+                .as_ref()
+                .is_some_and(|stream| Self::is_connection_alive(stream.get_ref().as_ref())),
+            ..self.health.clone()
+        }
+    }
 }
 
 impl Drop for SocketController {
@@ -178,13 +845,53 @@ impl Drop for SocketController {
 
 impl Reporter for SocketController {
     fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
         match self.device() {
-            Ok(device) => device.report(),
-            Err(_) => format!("SocketController({}) - Error reading state", self.address),
+            Ok(device) => device.report_with(options),
+            Err(_) => format!("SocketController({}) - Error reading state", self.address()),
         }
     }
 }
 
+impl AsyncReporter for SocketController {
+    /// Запрашивает мощность у розетки и формирует отчет
+    async fn live_report(&mut self, limit: Duration) -> String {
+        let result = match timeout(limit, self.power()).await {
+            Ok(result) => result.map(|_| ()),
+            Err(_) => Err(SocketError::Timeout),
+        };
+        match result {
+            Ok(()) => self.report(),
+            Err(e) => stale_report(self.report(), e),
+        }
+    }
+}
+
+impl Lifecycle for SocketController {
+    /// Открывает соединение с розеткой
+    async fn start(&mut self) -> std::io::Result<()> {
+        match self.ensure_connected().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                self.health.record_error(e.kind(), &e);
+                Err(std::io::Error::other(e))
+            }
+        }
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        self.disconnect();
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        self.health_status()
+    }
+}
+
 impl fmt::Display for SocketController {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.report())
@@ -198,10 +905,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_controller_creation() {
-        let addr = "127.0.0.1:8080".parse().unwrap();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
         let controller = SocketController::new(addr, 1500.0, Duration::from_secs(5));
 
-        assert_eq!(controller.address(), addr);
+        assert_eq!(*controller.address(), addr);
         assert_eq!(controller.timeout(), Duration::from_secs(5));
 
         let device = controller.device().unwrap();
@@ -211,7 +918,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_connection_error() {
-        let addr = "127.0.0.1:9999".parse().unwrap();
+        let addr: SocketAddr = "127.0.0.1:9999".parse().unwrap();
         let mut controller = SocketController::new(addr, 1500.0, Duration::from_millis(100));
 
         let result = controller.turn_on().await;
@@ -222,11 +929,25 @@ mod tests {
         } else {
             panic!("Expected Timeout or ConnectionError, got: {:?}", result);
         }
+
+        let health = controller.health_status();
+        assert!(!health.is_healthy());
+        assert_eq!(health.total_errors(), 1);
+        assert_eq!(health.last_success_ms, None);
+        assert!(health.last_error.is_some());
+    }
+
+    #[test]
+    fn test_disconnect_errors() {
+        assert!(SocketError::Protocol(ProtocolError::Closed).is_disconnect());
+        assert!(!SocketError::Protocol(ProtocolError::Framing("broken")).is_disconnect());
+        assert!(!SocketError::CommandError("Unexpected pong response".into()).is_disconnect());
+        assert!(!SocketError::Timeout.is_disconnect());
     }
 
     #[test]
     fn test_sync_device_access() {
-        let addr = "127.0.0.1:8080".parse().unwrap();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
         let controller = SocketController::new(addr, 1500.0, Duration::from_secs(5));
 
         let device = controller.device().unwrap();
@@ -234,9 +955,512 @@ mod tests {
         assert_eq!(device.power_rating(), Watts::new(1500.0));
     }
 
+    #[tokio::test]
+    async fn test_power_limit_refuses_turn_on() {
+        // Адрес недоступен: отказ должен произойти до подключения
+        let addr: SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        let mut controller = SocketController::new(addr, 2000.0, Duration::from_millis(100));
+        controller.set_power_limit(Watts::new(1500.0)).unwrap();
+
+        let result = controller.turn_on().await;
+        assert!(matches!(
+            result,
+            Err(SocketError::PowerLimitExceeded { power, limit })
+                if power == Watts::new(2000.0) && limit == Watts::new(1500.0)
+        ));
+        assert!(!controller.device().unwrap().is_active());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_power_limit_interlock_on_reported_power() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+
+        // Розетка фактически потребляет больше, чем известно контроллеру
+        let config = EmulatorConfig::new(2000.0).with_address("127.0.0.1:0");
+        let mut emulator = SocketEmulator::new(config);
+        emulator.start().await.unwrap();
+
+        let addr = emulator.local_addr().unwrap();
+        let mut controller = SocketController::new(addr, 1000.0, Duration::from_secs(1));
+        controller.set_power_limit(Watts::new(1500.0)).unwrap();
+
+        let result = controller.turn_on().await;
+        assert!(matches!(
+            result,
+            Err(SocketError::PowerLimitExceeded { .. })
+        ));
+        assert!(!controller.device().unwrap().is_active());
+
+        controller.clear_power_limit().unwrap();
+        assert!(controller.turn_on().await.is_ok());
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_power_with_fallback() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+
+        let config = EmulatorConfig::new(1000.0).with_address("127.0.0.1:0");
+        let mut emulator = SocketEmulator::new(config);
+        emulator.start().await.unwrap();
+
+        let addr = emulator.local_addr().unwrap();
+        let mut controller = SocketController::new(addr, 1000.0, Duration::from_millis(200));
+        controller.turn_on().await.unwrap();
+
+        let live = controller.try_power_with_fallback().await.unwrap();
+        assert_eq!(live, PowerReading::Live(Watts::new(1000.0)));
+
+        emulator.stop().await;
+        controller.disconnect();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let cached = controller.try_power_with_fallback().await.unwrap();
+        assert!(!cached.is_live());
+        assert_eq!(cached.power(), Watts::new(1000.0));
+        assert!(
+            matches!(cached, PowerReading::Cached { age, .. } if age >= Duration::from_millis(20))
+        );
+
+        // Без единого успешного чтения подставлять нечего
+        let mut fresh = SocketController::new(addr, 1000.0, Duration::from_millis(200));
+        assert!(fresh.try_power_with_fallback().await.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_health_status() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+
+        let config = EmulatorConfig::new(1000.0).with_address("127.0.0.1:0");
+        let mut emulator = SocketEmulator::new(config);
+        emulator.start().await.unwrap();
+
+        let addr = emulator.local_addr().unwrap();
+        let mut controller = SocketController::new(addr, 1000.0, Duration::from_secs(1));
+        assert!(!controller.health_status().is_healthy());
+
+        controller.power().await.unwrap();
+        let health = controller.health_status();
+        assert!(health.is_healthy());
+        assert!(health.last_success_ms.is_some());
+        assert_eq!(health.total_errors(), 0);
+
+        emulator.stop().await;
+        controller.disconnect();
+        assert!(!controller.health_status().is_healthy());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_max_message_size() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+
+        let config = EmulatorConfig::new(1000.0).with_address("127.0.0.1:0");
+        let mut emulator = SocketEmulator::new(config);
+        emulator.start().await.unwrap();
+        let addr = emulator.local_addr().unwrap();
+
+        // Кадр длиннее лимита пропускается как поврежденный: ответа нет
+        let mut controller = SocketController::new(addr, 1000.0, Duration::from_millis(300))
+            .with_max_message_size(16);
+        let error = controller.power().await.unwrap_err();
+        assert!(matches!(error, SocketError::Timeout));
+
+        let mut controller =
+            SocketController::new(addr, 1000.0, Duration::from_secs(1)).with_max_message_size(4096);
+        assert!(controller.power().await.is_ok());
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_firmware_update() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+        use crate::ota::UpdateState;
+
+        let config = EmulatorConfig::new(1000.0)
+            .with_address("127.0.0.1:0")
+            .with_firmware_version("1.0.0");
+        let mut emulator = SocketEmulator::new(config);
+        emulator.start().await.unwrap();
+
+        let addr = emulator.local_addr().unwrap();
+        let mut controller = SocketController::new(addr, 1000.0, Duration::from_secs(1));
+        let status = controller.update_status().await.unwrap();
+        assert_eq!(status.firmware, "1.0.0");
+        assert_eq!(status.state, UpdateState::Idle);
+
+        let image: Vec<u8> = (0..=255).collect();
+        let mut received = Vec::new();
+        let progress = controller
+            .update_firmware("2.0.0", &image, 100, |p| received.push(p.received))
+            .await
+            .unwrap();
+
+        assert!(progress.is_installed());
+        assert_eq!(progress.firmware, "2.0.0");
+        assert_eq!(received, vec![0, 100, 200, 256, 256]);
+        assert_eq!(controller.update_status().await.unwrap().firmware, "2.0.0");
+
+        // Чужая часть без начатого обновления отвергается розеткой
+        let command = SocketCommand::UpdateChunk {
+            offset: 0,
+            data: "00".to_string(),
+        };
+        assert!(matches!(
+            controller.update_command(command).await,
+            Err(SocketError::DeviceError {
+                code: ErrorCode::Internal,
+                ..
+            })
+        ));
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_ping_and_keepalive() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+
+        let config = EmulatorConfig::new(1000.0).with_address("127.0.0.1:0");
+        let mut emulator = SocketEmulator::new(config);
+        emulator.start().await.unwrap();
+
+        let addr = emulator.local_addr().unwrap();
+        let mut controller = SocketController::new(addr, 1000.0, Duration::from_secs(1))
+            .with_keepalive_interval(Duration::ZERO);
+        assert_eq!(controller.latency(), None);
+
+        // Без соединения проверять нечего
+        assert_eq!(controller.keepalive().await.unwrap(), None);
+
+        let rtt = controller.ping().await.unwrap();
+        assert_eq!(controller.latency(), Some(rtt));
+
+        // Простаивающее соединение проверяется перед командой
+        assert!(controller.keepalive().await.unwrap().is_some());
+        controller.turn_on().await.unwrap();
+        assert!(controller.device().unwrap().is_active());
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_liveness_check() {
+        use tokio::net::{TcpListener, TcpStream};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        assert!(SocketController::is_connection_alive(&client));
+
+        // Соединение закрыто розеткой, хотя peer_addr() еще доступен
+        drop(server);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(client.peer_addr().is_ok());
+        assert!(!SocketController::is_connection_alive(&client));
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_retry_after_broken_connection() {
+        use crate::protocol::socket_protocol::{receive_command, send_response};
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let data = SocketData {
+            active: true,
+            power: 100.0,
+            energy_wh: 0.0,
+            device_id: None,
+            outlets: Vec::new(),
+        };
+
+        let server = tokio::spawn(async move {
+            // Первое соединение: отвечает на одну команду, вторую теряет
+            let (mut stream, _) = listener.accept().await.unwrap();
+            receive_command(&mut stream).await.unwrap();
+            send_response(&mut stream, &SocketResponse::Ok(data.clone()))
+                .await
+                .unwrap();
+            receive_command(&mut stream).await.unwrap();
+            drop(stream);
+
+            // Второе соединение отвечает на повтор
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let command = receive_command(&mut stream).await.unwrap();
+            send_response(&mut stream, &SocketResponse::Ok(data))
+                .await
+                .unwrap();
+            command
+        });
+
+        let mut controller = SocketController::new(addr, 100.0, Duration::from_secs(1));
+        controller.power().await.unwrap();
+        assert_eq!(controller.power().await.unwrap(), Watts::new(100.0));
+        assert_eq!(server.await.unwrap(), SocketCommand::Power);
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn test_corrupt_response_is_not_retried() {
+        use crate::protocol::socket_protocol::{receive_command, send_message, send_response};
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let data = SocketData {
+            active: false,
+            power: 0.0,
+            energy_wh: 0.0,
+            device_id: None,
+            outlets: Vec::new(),
+        };
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            receive_command(&mut stream).await.unwrap();
+            send_response(&mut stream, &SocketResponse::Ok(data))
+                .await
+                .unwrap();
+            // Команда включения принята, но ответ поврежден
+            receive_command(&mut stream).await.unwrap();
+            send_message(&mut stream, "{not json").await.unwrap();
+
+            // Повтор открыл бы новое соединение
+            tokio::time::timeout(Duration::from_millis(300), listener.accept())
+                .await
+                .is_ok()
+        });
+
+        let mut controller = SocketController::new(addr, 100.0, Duration::from_secs(1));
+        controller.power().await.unwrap();
+        assert!(matches!(
+            controller.turn_on().await,
+            Err(SocketError::Protocol(ProtocolError::Json { .. }))
+        ));
+        assert!(!server.await.unwrap(), "command was sent twice");
+    }
+
+    #[tokio::test]
+    async fn test_memory_transport() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+        use crate::protocol::transport::InMemory;
+
+        // Эмулятор в том же процессе: адрес условный, портов нет
+        let transport = InMemory::new();
+        let mut emulator =
+            SocketEmulator::new(EmulatorConfig::new(1500.0).with_address("10.0.0.7:5000"));
+        emulator.start_in_memory(&transport).await.unwrap();
+        let addr = emulator.local_addr().unwrap();
+
+        let mut controller = SocketController::new(addr, 1500.0, Duration::from_secs(1))
+            .with_transport(Arc::new(transport.clone()));
+        controller.turn_on().await.unwrap();
+        assert!(controller.device().unwrap().is_active());
+        controller.ping().await.unwrap();
+        assert!(controller.health_status().connected);
+        assert_eq!(emulator.clients_count(), 1);
+
+        // Адрес без слушателя - подключение отклоняется
+        let mut unreachable = SocketController::new(
+            "10.0.0.9:5000".parse::<SocketAddr>().unwrap(),
+            100.0,
+            Duration::from_secs(1),
+        )
+        .with_transport(Arc::new(transport));
+        assert!(matches!(
+            unreachable.turn_on().await,
+            Err(SocketError::ConnectionError(_))
+        ));
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_power_strip_outlets() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+        use crate::protocol::transport::InMemory;
+
+        let transport = InMemory::new();
+        let mut emulator = SocketEmulator::new(
+            EmulatorConfig::new(0.0)
+                .with_address("10.0.0.8:5000")
+                .with_outlets(&[100.0, 40.0]),
+        );
+        emulator.start_in_memory(&transport).await.unwrap();
+
+        let mut controller = SocketController::new(
+            emulator.local_addr().unwrap(),
+            140.0,
+            Duration::from_secs(1),
+        )
+        .with_transport(Arc::new(transport));
+        controller.turn_on_outlet(0).await.unwrap();
+        controller.turn_on_outlet(1).await.unwrap();
+        assert_eq!(controller.power().await.unwrap(), Watts::new(140.0));
+        assert_eq!(controller.cached_outlets().len(), 2);
+
+        controller.turn_off_outlet(0).await.unwrap();
+        let outlets = controller.outlets().await.unwrap();
+        assert!(!outlets[0].active);
+        assert_eq!(outlets[1].power, 40.0);
+        assert!(controller.device().unwrap().is_active());
+
+        assert!(matches!(
+            controller.turn_on_outlet(5).await,
+            Err(SocketError::DeviceError {
+                code: ErrorCode::Unsupported,
+                ..
+            })
+        ));
+
+        // Предел мощности проверяется по сумме и выключает только включенную розетку
+        controller.set_power_limit(Watts::new(100.0)).unwrap();
+        assert!(matches!(
+            controller.turn_on_outlet(0).await,
+            Err(SocketError::PowerLimitExceeded { .. })
+        ));
+        let outlets = controller.outlets().await.unwrap();
+        assert!(!outlets[0].active);
+        assert!(outlets[1].active);
+        assert!(controller.device().unwrap().is_active());
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_describe_is_cached() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+        use crate::protocol::transport::InMemory;
+
+        let transport = InMemory::new();
+        let mut emulator = SocketEmulator::new(
+            EmulatorConfig::new(800.0)
+                .with_address("10.0.0.10:5000")
+                .with_firmware_version("1.0.0"),
+        );
+        emulator.start_in_memory(&transport).await.unwrap();
+
+        let mut controller = SocketController::new(
+            emulator.local_addr().unwrap(),
+            800.0,
+            Duration::from_secs(1),
+        )
+        .with_transport(Arc::new(transport));
+        assert!(controller.cached_descriptor().is_none());
+
+        let descriptor = controller.describe().await.unwrap();
+        assert_eq!(descriptor.device_type, "socket");
+        assert_eq!(descriptor.firmware, "1.0.0");
+        assert_eq!(descriptor.power_rating, 800.0);
+        assert!(descriptor.supports("set_load"));
+        assert_eq!(controller.cached_descriptor(), Some(&descriptor));
+
+        // Новая прошивка сбрасывает сохраненное описание
+        controller
+            .update_firmware("2.0.0", &[1, 2, 3], 16, |_| {})
+            .await
+            .unwrap();
+        assert!(controller.cached_descriptor().is_none());
+        assert_eq!(controller.describe().await.unwrap().firmware, "2.0.0");
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_busy_response_is_retried() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+        use crate::protocol::transport::InMemory;
+
+        let transport = InMemory::new();
+        let mut emulator = SocketEmulator::new(
+            EmulatorConfig::new(500.0)
+                .with_address("10.0.0.9:5000")
+                .with_max_commands_per_connection(1),
+        );
+        emulator.start_in_memory(&transport).await.unwrap();
+
+        let mut controller = SocketController::new(
+            emulator.local_addr().unwrap(),
+            500.0,
+            Duration::from_secs(1),
+        )
+        .with_transport(Arc::new(transport));
+        let mut metrics = controller.metrics_watch();
+
+        // Вторая команда по соединению получает Busy и уходит по новому
+        controller.turn_on().await.unwrap();
+        assert!(metrics.has_changed().unwrap());
+        assert_eq!(controller.power().await.unwrap(), Watts::new(500.0));
+        assert_eq!(emulator.connection_stats().command_limit_hits, 1);
+
+        let snapshot = metrics.borrow_and_update().clone();
+        assert_eq!((snapshot.operations, snapshot.errors), (2, 0));
+        assert_eq!(snapshot.last_value, Some(500.0));
+        assert!(snapshot.last_latency.is_some() && snapshot.last_success_ms.is_some());
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_backup_address() {
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+        use crate::protocol::transport::InMemory;
+
+        let transport = InMemory::new();
+        let mut emulator =
+            SocketEmulator::new(EmulatorConfig::new(500.0).with_address("10.0.0.12:5000"));
+        emulator.start_in_memory(&transport).await.unwrap();
+
+        let primary = "10.0.0.11:5000".parse().unwrap();
+        let backup = emulator.local_addr().unwrap();
+        let mut controller = SocketController::new(primary, 500.0, Duration::from_secs(1))
+            .with_fallback(backup)
+            .with_failover_threshold(2)
+            .with_transport(Arc::new(transport));
+        let mut failovers = controller.subscribe_failovers();
+
+        // Первая ошибка еще не считается устойчивой
+        assert!(controller.turn_on().await.is_err());
+        assert_eq!(*controller.address(), primary);
+        assert!(failovers.try_recv().is_err());
+
+        // Вторая переключает на резерв, и команда проходит сразу
+        controller.turn_on().await.unwrap();
+        assert_eq!(*controller.address(), backup);
+        assert_eq!(controller.addresses(), &[primary, backup]);
+        let event = failovers.try_recv().unwrap();
+        assert_eq!(event.from, primary);
+        assert_eq!(event.to, backup);
+        assert_eq!(event.failures, 2);
+        assert_eq!(controller.resolved_address(), Some(backup));
+
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_ping_without_device() {
+        let addr: SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        let mut controller = SocketController::new(addr, 1500.0, Duration::from_millis(100));
+
+        assert!(controller.ping().await.is_err());
+        assert_eq!(controller.latency(), None);
+    }
+
     #[test]
     fn test_report() {
-        let addr = "127.0.0.1:8080".parse().unwrap();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
         let controller = SocketController::new(addr, 1500.0, Duration::from_secs(5));
 
         let report = controller.report();
@@ -0,0 +1,267 @@
+//! Буферизованные подписки на показания контроллеров
+//!
+//! Поток приема только кладет значение в очередь подписчика и никогда
+//! не ждет его: при переполнении срабатывает `OverflowPolicy`, а потери
+//! учитываются в `SubscriptionStats`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Поведение при заполненном буфере подписки
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Отбрасывать новое значение (счетчик `dropped`)
+    DropNewest,
+    /// Вытеснять самое старое значение (счетчик `lagged`)
+    DropOldest,
+}
+
+/// Статистика подписки
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubscriptionStats {
+    /// Значений, переданных подписчику
+    pub delivered: u64,
+    /// Новых значений, отброшенных из-за полного буфера
+    pub dropped: u64,
+    /// Старых значений, вытесненных из буфера
+    pub lagged: u64,
+    /// Значений, ожидающих чтения
+    pub queued: usize,
+}
+
+/// Общий буфер между потоком приема и подписчиком
+pub(crate) struct SubscriptionBuffer<T> {
+    queue: Mutex<VecDeque<T>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    notify: Notify,
+    closed: AtomicBool,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    lagged: AtomicU64,
+}
+
+impl<T> SubscriptionBuffer<T> {
+    fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            queue: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            policy,
+            notify: Notify::new(),
+            closed: AtomicBool::new(false),
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            lagged: AtomicU64::new(0),
+        }
+    }
+
+    /// Кладет значение в буфер, не блокируя вызывающий поток надолго
+    pub(crate) fn push(&self, value: T) {
+        let Ok(mut queue) = self.queue.lock() else {
+            return;
+        };
+
+        if queue.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                OverflowPolicy::DropOldest => {
+                    queue.pop_front();
+                    self.lagged.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        queue.push_back(value);
+        drop(queue);
+        self.notify.notify_one();
+    }
+
+    /// Закрывает буфер: после опустошения `recv` вернет `None`
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        self.notify.notify_one();
+    }
+
+    fn pop(&self) -> Option<T> {
+        let value = self.queue.lock().ok()?.pop_front();
+        if value.is_some() {
+            self.delivered.fetch_add(1, Ordering::Relaxed);
+        }
+        value
+    }
+
+    fn len(&self) -> usize {
+        self.queue.lock().map(|queue| queue.len()).unwrap_or(0)
+    }
+}
+
+/// Реестр буферов подписчиков
+pub(crate) type SubscriptionBuffers<T> = Arc<Mutex<HashMap<usize, Arc<SubscriptionBuffer<T>>>>>;
+
+/// Буферизованная подписка; отписка происходит при Drop
+pub struct BufferedSubscription<T> {
+    id: usize,
+    buffer: Arc<SubscriptionBuffer<T>>,
+    registry: SubscriptionBuffers<T>,
+}
+
+impl<T> BufferedSubscription<T> {
+    /// Создает подписку и регистрирует ее буфер
+    pub(crate) fn register(
+        id: usize,
+        capacity: usize,
+        policy: OverflowPolicy,
+        registry: &SubscriptionBuffers<T>,
+    ) -> Self {
+        let buffer = Arc::new(SubscriptionBuffer::new(capacity, policy));
+
+        if let Ok(mut buffers) = registry.lock() {
+            buffers.insert(id, Arc::clone(&buffer));
+        }
+
+        Self {
+            id,
+            buffer,
+            registry: Arc::clone(registry),
+        }
+    }
+
+    /// Забирает значение без ожидания
+    pub fn try_recv(&self) -> Option<T> {
+        self.buffer.pop()
+    }
+
+    /// Ждет следующего значения; `None` - источник остановлен и буфер пуст
+    pub async fn recv(&self) -> Option<T> {
+        loop {
+            if let Some(value) = self.buffer.pop() {
+                return Some(value);
+            }
+            if self.buffer.closed.load(Ordering::Relaxed) {
+                return None;
+            }
+            self.buffer.notify.notified().await;
+        }
+    }
+
+    /// Емкость буфера
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity
+    }
+
+    /// Политика переполнения
+    pub fn policy(&self) -> OverflowPolicy {
+        self.buffer.policy
+    }
+
+    /// Текущая статистика подписки
+    pub fn stats(&self) -> SubscriptionStats {
+        SubscriptionStats {
+            delivered: self.buffer.delivered.load(Ordering::Relaxed),
+            dropped: self.buffer.dropped.load(Ordering::Relaxed),
+            lagged: self.buffer.lagged.load(Ordering::Relaxed),
+            queued: self.buffer.len(),
+        }
+    }
+}
+
+impl<T> Drop for BufferedSubscription<T> {
+    fn drop(&mut self) {
+        if let Ok(mut buffers) = self.registry.lock() {
+            buffers.remove(&self.id);
+        }
+    }
+}
+
+/// Рассылает значение во все буферы подписчиков
+pub(crate) fn broadcast<T: Clone>(registry: &SubscriptionBuffers<T>, value: &T) {
+    if let Ok(buffers) = registry.lock() {
+        for buffer in buffers.values() {
+            buffer.push(value.clone());
+        }
+    }
+}
+
+/// Закрывает все буферы (источник данных остановлен)
+pub(crate) fn close_all<T>(registry: &SubscriptionBuffers<T>) {
+    if let Ok(buffers) = registry.lock() {
+        for buffer in buffers.values() {
+            buffer.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> SubscriptionBuffers<u32> {
+        Arc::new(Mutex::new(HashMap::new()))
+    }
+
+    #[test]
+    fn drop_newest() {
+        let registry = registry();
+        let sub = BufferedSubscription::register(0, 2, OverflowPolicy::DropNewest, &registry);
+
+        for value in 1..=5 {
+            broadcast(&registry, &value);
+        }
+
+        assert_eq!(sub.try_recv(), Some(1));
+        assert_eq!(sub.try_recv(), Some(2));
+        assert_eq!(sub.try_recv(), None);
+
+        let stats = sub.stats();
+        assert_eq!(stats.delivered, 2);
+        assert_eq!(stats.dropped, 3);
+        assert_eq!(stats.lagged, 0);
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let registry = registry();
+        let sub = BufferedSubscription::register(0, 2, OverflowPolicy::DropOldest, &registry);
+
+        for value in 1..=5 {
+            broadcast(&registry, &value);
+        }
+
+        assert_eq!(sub.stats().queued, 2);
+        assert_eq!(sub.try_recv(), Some(4));
+        assert_eq!(sub.try_recv(), Some(5));
+        assert_eq!(sub.stats().lagged, 3);
+    }
+
+    #[test]
+    fn unregister_on_drop() {
+        let registry = registry();
+        let sub = BufferedSubscription::register(7, 1, OverflowPolicy::DropOldest, &registry);
+        assert_eq!(registry.lock().unwrap().len(), 1);
+
+        drop(sub);
+        assert!(registry.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn recv_waits_and_closes() {
+        let registry = registry();
+        let sub = BufferedSubscription::register(0, 4, OverflowPolicy::DropNewest, &registry);
+
+        let producer = Arc::clone(&registry);
+        std::thread::spawn(move || {
+            broadcast(&producer, &42);
+            close_all(&producer);
+        });
+
+        assert_eq!(sub.recv().await, Some(42));
+        assert_eq!(sub.recv().await, None);
+    }
+}
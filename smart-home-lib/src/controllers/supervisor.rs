@@ -0,0 +1,277 @@
+//! Наблюдение за фоновыми задачами контроллеров
+//!
+//! Супервизор перезапускает упавший цикл приема и повторяет неудачную
+//! привязку сокета с экспоненциальной задержкой. Состояние задачи
+//! доступно через `ControllerHealth`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Шаг ожидания при проверке флага остановки
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Политика перезапуска с экспоненциальной задержкой
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RestartPolicy {
+    /// Задержка перед первой повторной попыткой
+    pub initial_backoff: Duration,
+    /// Максимальная задержка
+    pub max_backoff: Duration,
+    /// Множитель задержки после каждой неудачи
+    pub multiplier: f64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Задержка перед попыткой номер `attempt` (с 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(attempt.saturating_sub(1) as i32);
+        self.initial_backoff
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_backoff)
+    }
+}
+
+/// Состояние фоновой задачи
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    /// Задача не запущена
+    Stopped,
+    /// Цикл приема работает
+    Running,
+    /// Ожидание перед повторной попыткой
+    Restarting,
+}
+
+/// Здоровье фоновой задачи контроллера
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControllerHealth {
+    pub state: TaskState,
+    /// Сколько раз цикл был запущен заново после падения или неудачной привязки
+    pub restarts: u32,
+    /// Неудачи подряд; счетчик сбрасывается после стабильной работы
+    /// дольше максимальной задержки
+    pub consecutive_failures: u32,
+    /// Последняя ошибка
+    pub last_error: Option<String>,
+}
+
+impl Default for ControllerHealth {
+    fn default() -> Self {
+        Self {
+            state: TaskState::Stopped,
+            restarts: 0,
+            consecutive_failures: 0,
+            last_error: None,
+        }
+    }
+}
+
+impl ControllerHealth {
+    /// Задача работает
+    pub fn is_healthy(&self) -> bool {
+        self.state == TaskState::Running
+    }
+}
+
+/// Общее состояние здоровья задачи
+pub(crate) type SharedHealth = Arc<Mutex<ControllerHealth>>;
+
+/// Запускает `run` в цикле, пока выставлен `running`
+///
+/// `bind` создает ресурс задачи (сокет); при ошибке привязки и при
+/// выходе или панике `run` задача перезапускается после задержки.
+pub(crate) fn supervise<R, B, F>(
+    running: &AtomicBool,
+    health: &SharedHealth,
+    policy: RestartPolicy,
+    mut bind: B,
+    mut run: F,
+) where
+    B: FnMut() -> std::io::Result<R>,
+    F: FnMut(R) -> std::io::Result<()>,
+{
+    let mut started_once = false;
+
+    while running.load(Ordering::Relaxed) {
+        let failure = match bind() {
+            Ok(resource) => {
+                update(health, |h| {
+                    // Успешный запуск после падения или неудачной привязки
+                    if started_once || h.consecutive_failures > 0 {
+                        h.restarts += 1;
+                    }
+                    h.state = TaskState::Running;
+                });
+                started_once = true;
+
+                let started_at = Instant::now();
+                let outcome =
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run(resource)));
+                if started_at.elapsed() >= policy.max_backoff {
+                    // Цикл долго работал стабильно: задержка начинается заново
+                    update(health, |h| h.consecutive_failures = 0);
+                }
+                match outcome {
+                    Ok(Ok(())) => break, // штатная остановка
+                    Ok(Err(e)) => format!("Цикл приема завершился с ошибкой: {}", e),
+                    Err(_) => "Цикл приема аварийно завершился".to_string(),
+                }
+            }
+            Err(e) => format!("Не удалось привязать сокет: {}", e),
+        };
+
+        eprintln!("⚠️ {}", failure);
+        let attempt = update(health, |h| {
+            h.state = TaskState::Restarting;
+            h.consecutive_failures += 1;
+            h.last_error = Some(failure);
+            h.consecutive_failures
+        });
+
+        sleep_while_running(running, policy.backoff(attempt));
+    }
+
+    update(health, |h| h.state = TaskState::Stopped);
+}
+
+fn update<T>(health: &SharedHealth, f: impl FnOnce(&mut ControllerHealth) -> T) -> T {
+    let mut guard = health.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut guard)
+}
+
+/// Прерываемое ожидание: stop() не ждет окончания задержки
+fn sleep_while_running(running: &AtomicBool, duration: Duration) {
+    let mut remaining = duration;
+    while !remaining.is_zero() && running.load(Ordering::Relaxed) {
+        let step = remaining.min(STOP_POLL_INTERVAL);
+        thread::sleep(step);
+        remaining -= step;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn fast_policy() -> RestartPolicy {
+        RestartPolicy {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            multiplier: 2.0,
+        }
+    }
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        let policy = RestartPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(100), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn retries_bind_until_success() {
+        let running = AtomicBool::new(true);
+        let health = SharedHealth::default();
+        let mut attempts = 0;
+
+        supervise(
+            &running,
+            &health,
+            fast_policy(),
+            || {
+                attempts += 1;
+                if attempts < 3 {
+                    Err(io::Error::new(io::ErrorKind::AddrInUse, "busy"))
+                } else {
+                    Ok(())
+                }
+            },
+            |()| Ok(()),
+        );
+
+        let health = health.lock().unwrap().clone();
+        assert_eq!(attempts, 3);
+        assert_eq!(health.restarts, 1);
+        assert_eq!(health.consecutive_failures, 2);
+        assert_eq!(health.state, TaskState::Stopped);
+        assert!(health.last_error.unwrap().contains("busy"));
+    }
+
+    #[test]
+    fn restarts_after_panic_and_error() {
+        let running = AtomicBool::new(true);
+        let health = SharedHealth::default();
+        let mut runs = 0;
+
+        supervise(
+            &running,
+            &health,
+            fast_policy(),
+            || Ok(()),
+            |()| {
+                runs += 1;
+                match runs {
+                    1 => panic!("callback failed"),
+                    2 => Err(io::Error::other("socket closed")),
+                    _ => Ok(()),
+                }
+            },
+        );
+
+        let health = health.lock().unwrap().clone();
+        assert_eq!(runs, 3);
+        assert_eq!(health.restarts, 2);
+        assert!(health.last_error.unwrap().contains("socket closed"));
+    }
+
+    #[test]
+    fn stop_interrupts_backoff() {
+        let running = Arc::new(AtomicBool::new(true));
+        let health = SharedHealth::default();
+        let policy = RestartPolicy {
+            initial_backoff: Duration::from_secs(60),
+            ..RestartPolicy::default()
+        };
+
+        let handle = {
+            let running = Arc::clone(&running);
+            let health = Arc::clone(&health);
+            thread::spawn(move || {
+                supervise(
+                    &running,
+                    &health,
+                    policy,
+                    || Err::<(), _>(io::Error::other("no network")),
+                    |()| Ok(()),
+                )
+            })
+        };
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(health.lock().unwrap().state, TaskState::Restarting);
+
+        let started = Instant::now();
+        running.store(false, Ordering::Relaxed);
+        handle.join().unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(health.lock().unwrap().state, TaskState::Stopped);
+    }
+}
@@ -1,10 +1,17 @@
 //! UDP контроллер для умного термометра
 
+use super::health::HealthStatus;
+use super::metrics::{self, ControllerMetrics, MetricsSender};
+use super::subscription::{self, BufferedSubscription, OverflowPolicy, SubscriptionBuffers};
+use super::supervisor::{self, ControllerHealth, RestartPolicy, SharedHealth};
+use crate::clock::{SharedClock, system_clock};
 use crate::devices::SmartTherm;
-use crate::protocol::{ThermData, now_ms};
-use crate::traits::Reporter;
+use crate::protocol::ThermData;
+use crate::protocol::bind::{self, IpStack};
+use crate::report::ReportOptions;
+use crate::traits::{AsyncReporter, Lifecycle, Reporter, stale_report};
 use crate::units::Celsius;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::net::UdpSocket;
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
@@ -13,6 +20,12 @@ use std::thread::{self, JoinHandle};
 use std::time::Duration;
 use tokio::sync::watch;
 
+/// Сколько последних показаний хранится для сглаживания
+const READINGS_CAPACITY: usize = 64;
+
+/// Период проверки устаревания показаний, приходящих через `ThermFeed`
+const STALE_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
 /// Ошибки контроллера
 #[derive(Debug, Clone)]
 pub enum ThermError {
@@ -22,6 +35,10 @@ pub enum ThermError {
     NetworkError(String),
     /// Ошибка блокировки
     LockError,
+    /// Условие не выполнилось за отведенное время
+    Timeout,
+    /// Не удалось занять UDP адрес для приема
+    Bind(String),
 }
 
 impl std::fmt::Display for ThermError {
@@ -30,23 +47,116 @@ impl std::fmt::Display for ThermError {
             Self::NoFreshData => write!(f, "Нет свежих данных"),
             Self::NetworkError(msg) => write!(f, "Сетевая ошибка: {}", msg),
             Self::LockError => write!(f, "Ошибка блокировки"),
+            Self::Timeout => write!(f, "Таймаут ожидания"),
+            Self::Bind(msg) => write!(f, "Не удалось привязать сокет: {}", msg),
         }
     }
 }
 
 impl std::error::Error for ThermError {}
 
+/// Качество показания по его возрасту
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingQuality {
+    /// Возраст не превышает порог предупреждения
+    Fresh,
+    /// Показание устаревает, но еще пригодно; внутри - его возраст
+    Aging(Duration),
+    /// Возраст превышает максимальный: датчик, вероятно, не работает
+    Stale,
+}
+
+impl ReadingQuality {
+    /// Оценивает показание возраста `age`
+    pub fn classify(age: Duration, warning_age: Duration, max_age: Duration) -> Self {
+        if age > max_age {
+            Self::Stale
+        } else if age > warning_age {
+            Self::Aging(age)
+        } else {
+            Self::Fresh
+        }
+    }
+
+    /// Показание можно использовать
+    pub fn is_usable(&self) -> bool {
+        *self != Self::Stale
+    }
+}
+
 /// Тип callback функции для уведомлений об изменениях
 type TemperatureCallback = Box<dyn Fn(Result<Celsius, ThermError>) + Send + 'static>;
 
+/// Свежее показание термометра и его возраст
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reading {
+    /// Температура
+    pub value: Celsius,
+    /// Время с момента получения показания
+    pub age: Duration,
+}
+
+impl fmt::Display for Reading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} мс назад)", self.value, self.age.as_millis())
+    }
+}
+
+/// Способ сглаживания по последним `n` показаниям
+///
+/// Медиана устойчивее к одиночным выбросам датчика, среднее - плавнее.
+/// Окно ограничено числом сохраненных показаний (64).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Smoothing {
+    /// Скользящее среднее
+    Mean(usize),
+    /// Скользящая медиана
+    Median(usize),
+}
+
+impl Smoothing {
+    /// Сглаженное значение; `readings` - от старых к новым, не пусто
+    fn apply(&self, readings: &VecDeque<Celsius>) -> Celsius {
+        let (Self::Mean(n) | Self::Median(n)) = *self;
+        let window = n.clamp(1, readings.len());
+        let mut values: Vec<f64> = readings
+            .iter()
+            .skip(readings.len() - window)
+            .map(|t| t.value())
+            .collect();
+
+        match self {
+            Self::Mean(_) => Celsius::new(values.iter().sum::<f64>() / window as f64),
+            Self::Median(_) => {
+                values.sort_by(f64::total_cmp);
+                let mid = window / 2;
+                if window % 2 == 0 {
+                    Celsius::new((values[mid - 1] + values[mid]) / 2.0)
+                } else {
+                    Celsius::new(values[mid])
+                }
+            }
+        }
+    }
+}
+
+/// Буферизованная подписка на показания термометра
+pub type TemperatureSubscription = BufferedSubscription<Result<Celsius, ThermError>>;
+
 /// Контроллер умного термометра (UDP)
 pub struct ThermController {
     /// Внутренний термометр
     therm: Arc<RwLock<SmartTherm>>,
     /// Адрес для прослушивания UDP
     listen_addr: String,
+    /// Режим IPv6 сокета приема
+    ip_stack: IpStack,
     /// Максимальный возраст данных
     max_age: Duration,
+    /// Возраст, после которого показание считается устаревающим
+    warning_age: Duration,
+    /// Источник времени для проверки свежести данных
+    clock: SharedClock,
     /// Время последнего обновления (0 = нет данных, >0 = timestamp в мс)
     last_update: Arc<AtomicU64>,
     /// Флаг работы фонового потока
@@ -58,8 +168,94 @@ pub struct ThermController {
     temp_receiver: watch::Receiver<Option<Result<Celsius, ThermError>>>,
     /// Список callback'ов для уведомлений об изменениях
     callbacks: Arc<Mutex<HashMap<usize, TemperatureCallback>>>,
-    /// Счетчик для SubscriptionHandle
+    /// Буферы подписчиков, не блокирующие поток приема
+    buffers: SubscriptionBuffers<Result<Celsius, ThermError>>,
+    /// Счетчик для SubscriptionHandle и буферизованных подписок
     next_callback_id: Arc<AtomicUsize>,
+    /// Политика перезапуска фонового потока
+    restart_policy: RestartPolicy,
+    /// Здоровье фонового потока
+    health: SharedHealth,
+    /// Счетчик пакетов, которые не удалось разобрать
+    malformed_packets: Arc<AtomicU64>,
+    /// Показания приходят через `ThermFeed` (например, от `SensorHub`)
+    fed: Arc<AtomicBool>,
+    /// Последние показания для сглаживания, от старых к новым
+    readings: Arc<Mutex<VecDeque<Celsius>>>,
+    /// Метрики, обновляемые при каждом показании
+    metrics: MetricsSender,
+    /// Подписчики уже уведомлены об устаревании показаний
+    stale_notified: Arc<AtomicBool>,
+    /// Работает поток проверки устаревания для `feed`
+    watchdog: Arc<AtomicBool>,
+}
+
+/// Точка приема показаний термометра в обход его UDP сокета
+///
+/// Нужна, когда пакеты многих датчиков принимает один порт (`SensorHub`):
+/// показание, переданное в `push`, обрабатывается так же, как пакет,
+/// принятый самим контроллером.
+#[derive(Clone)]
+pub struct ThermFeed {
+    therm: Arc<RwLock<SmartTherm>>,
+    last_update: Arc<AtomicU64>,
+    clock: SharedClock,
+    temp_sender: watch::Sender<Option<Result<Celsius, ThermError>>>,
+    callbacks: Arc<Mutex<HashMap<usize, TemperatureCallback>>>,
+    buffers: SubscriptionBuffers<Result<Celsius, ThermError>>,
+    readings: Arc<Mutex<VecDeque<Celsius>>>,
+    metrics: MetricsSender,
+    stale_notified: Arc<AtomicBool>,
+}
+
+impl ThermFeed {
+    /// Принимает новое показание
+    pub fn push(&self, temperature: f64) {
+        self.last_update
+            .store(self.clock.now_ms(), Ordering::Relaxed);
+
+        // Обновляем термометр
+        if let Ok(mut therm) = self.therm.write() {
+            therm.set_temperature(temperature);
+        }
+        if let Ok(mut readings) = self.readings.lock() {
+            if readings.len() == READINGS_CAPACITY {
+                readings.pop_front();
+            }
+            readings.push_back(Celsius::new(temperature));
+        }
+        metrics::record_success(&self.metrics, None, Some(temperature));
+
+        // Свежее показание: следующее устаревание снова будет уведомлением
+        self.stale_notified.store(false, Ordering::Relaxed);
+        self.publish(Ok(Celsius::new(temperature)));
+    }
+
+    /// Уведомляет async канал, callback'и и буферизованных подписчиков
+    fn publish(&self, result: Result<Celsius, ThermError>) {
+        let _ = self.temp_sender.send(Some(result.clone()));
+
+        if let Ok(callbacks) = self.callbacks.lock() {
+            for (_id, callback) in callbacks.iter() {
+                callback(result.clone());
+            }
+        }
+        subscription::broadcast(&self.buffers, &result);
+    }
+
+    /// Показания устарели
+    fn is_stale(&self, max_age: Duration) -> bool {
+        let last_timestamp = self.last_update.load(Ordering::Relaxed);
+        last_timestamp != 0
+            && self.clock.now_ms().saturating_sub(last_timestamp) > max_age.as_millis() as u64
+    }
+
+    /// Уведомляет `NoFreshData` один раз при переходе от свежих данных к устаревшим
+    fn notify_if_stale(&self, max_age: Duration) {
+        if self.is_stale(max_age) && !self.stale_notified.swap(true, Ordering::Relaxed) {
+            self.publish(Err(ThermError::NoFreshData));
+        }
+    }
 }
 
 impl ThermController {
@@ -70,121 +266,288 @@ impl ThermController {
         Self {
             therm: Arc::new(RwLock::new(SmartTherm::new(initial_temp))),
             listen_addr: listen_addr.to_string(),
+            ip_stack: IpStack::default(),
             max_age,
+            warning_age: max_age / 2,
+            clock: system_clock(),
             last_update: Arc::new(AtomicU64::new(0)),
             running: Arc::new(AtomicBool::new(false)),
             thread_handle: None,
             temp_sender,
             temp_receiver,
             callbacks: Arc::new(Mutex::new(HashMap::new())),
+            buffers: Arc::new(Mutex::new(HashMap::new())),
             next_callback_id: Arc::new(AtomicUsize::new(0)),
+            restart_policy: RestartPolicy::default(),
+            health: SharedHealth::default(),
+            malformed_packets: Arc::new(AtomicU64::new(0)),
+            fed: Arc::new(AtomicBool::new(false)),
+            readings: Arc::new(Mutex::new(VecDeque::with_capacity(READINGS_CAPACITY))),
+            metrics: metrics::channel(),
+            stale_notified: Arc::new(AtomicBool::new(false)),
+            watchdog: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    /// Запускает автоматическое обновление в фоне
-    pub fn start(&mut self) {
-        if self.running.load(Ordering::Relaxed) {
-            return; // Уже запущен
-        }
+    /// Builder: устанавливает источник времени для проверки свежести данных
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
 
-        self.running.store(true, Ordering::Relaxed);
+    /// Builder: порог предупреждения о возрасте показаний (по умолчанию половина `max_age`)
+    pub fn with_warning_age(mut self, warning_age: Duration) -> Self {
+        self.warning_age = warning_age;
+        self
+    }
 
-        let therm = Arc::clone(&self.therm);
-        let last_update = Arc::clone(&self.last_update);
-        let running = Arc::clone(&self.running);
-        let listen_addr = self.listen_addr.clone();
-        let max_age = self.max_age;
-        let temp_sender = self.temp_sender.clone();
-        let callbacks = Arc::clone(&self.callbacks);
+    /// Builder: политика перезапуска фонового потока
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
 
-        let handle = thread::spawn(move || {
-            // Создаем UDP сокет для получения данных
-            let socket = match UdpSocket::bind(&listen_addr) {
-                Ok(s) => s,
-                Err(e) => {
-                    eprintln!("❌ Не удалось привязать UDP сокет {}: {}", listen_addr, e);
-                    return;
+    /// Builder: режим IPv6 сокета приема
+    ///
+    /// `DualStack` на `[::]:port` принимает показания и от IPv4 датчиков.
+    pub fn with_ip_stack(mut self, stack: IpStack) -> Self {
+        self.ip_stack = stack;
+        self
+    }
+
+    /// Состояние фонового потока приема
+    pub fn health(&self) -> ControllerHealth {
+        self.health
+            .lock()
+            .map(|health| health.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Адрес, на котором контроллер принимает данные по UDP
+    ///
+    /// После `start` - фактический адрес сокета (с выбранным портом, если
+    /// был задан порт 0).
+    pub fn listen_addr(&self) -> &str {
+        &self.listen_addr
+    }
+
+    /// Состояние связи с датчиком
+    ///
+    /// Связь считается установленной, пока цикл приема работает (или
+    /// показания приходят через `ThermFeed`) и показания не устарели.
+    pub fn health_status(&self) -> HealthStatus {
+        let health = self.health();
+        let last_update = self.last_update.load(Ordering::Relaxed);
+
+        let mut error_counts = std::collections::BTreeMap::new();
+        let malformed = self.malformed_packets.load(Ordering::Relaxed);
+        if malformed > 0 {
+            error_counts.insert("malformed".to_string(), malformed);
+        }
+        if health.restarts > 0 {
+            error_counts.insert("restart".to_string(), health.restarts as u64);
+        }
+
+        HealthStatus {
+            connected: (health.is_healthy() || self.fed.load(Ordering::Relaxed))
+                && self.temperature().is_ok(),
+            last_success_ms: (last_update != 0).then_some(last_update),
+            error_counts,
+            last_error: health.last_error,
+        }
+    }
+
+    /// Точка приема показаний от внешнего приемника
+    ///
+    /// Собственный прием (`start`) для такого контроллера обычно не нужен.
+    /// Устаревание таких показаний проверяет отдельный поток, работающий
+    /// до удаления контроллера: подписчики получают `NoFreshData` так же,
+    /// как при собственном приеме.
+    pub fn feed(&self) -> ThermFeed {
+        self.fed.store(true, Ordering::Relaxed);
+        let feed = self.make_feed();
+        if !self.watchdog.swap(true, Ordering::Relaxed) {
+            let watchdog = Arc::clone(&self.watchdog);
+            let stale_feed = feed.clone();
+            let max_age = self.max_age;
+            thread::spawn(move || {
+                while watchdog.load(Ordering::Relaxed) {
+                    thread::sleep(STALE_CHECK_INTERVAL);
+                    stale_feed.notify_if_stale(max_age);
                 }
-            };
+            });
+        }
+        feed
+    }
 
-            let mut buf = [0; 1024];
+    fn make_feed(&self) -> ThermFeed {
+        ThermFeed {
+            therm: Arc::clone(&self.therm),
+            last_update: Arc::clone(&self.last_update),
+            clock: Arc::clone(&self.clock),
+            temp_sender: self.temp_sender.clone(),
+            callbacks: Arc::clone(&self.callbacks),
+            buffers: Arc::clone(&self.buffers),
+            readings: Arc::clone(&self.readings),
+            metrics: self.metrics.clone(),
+            stale_notified: Arc::clone(&self.stale_notified),
+        }
+    }
 
-            while running.load(Ordering::Relaxed) {
-                // Неблокирующее чтение
-                socket.set_nonblocking(true).ok();
+    /// Запускает автоматическое обновление в фоне
+    ///
+    /// Сокет привязывается до запуска потока: занятый или неверный адрес -
+    /// ошибка `Bind`, контроллер остается остановленным. Если сокет позже
+    /// перестанет работать, поток привяжет его заново (`RestartPolicy`).
+    pub fn start(&mut self) -> Result<(), ThermError> {
+        if self.running.load(Ordering::Relaxed) {
+            return Ok(()); // Уже запущен
+        }
 
-                match socket.recv_from(&mut buf) {
-                    Ok((size, _)) => {
-                        if let Ok(data_str) = std::str::from_utf8(&buf[..size]) {
-                            if let Ok(therm_data) = serde_json::from_str::<ThermData>(data_str) {
-                                let new_temp = Celsius::new(therm_data.temperature);
+        let socket = Self::bind(&self.listen_addr, self.ip_stack)?;
+        if let Ok(addr) = socket.local_addr() {
+            self.listen_addr = addr.to_string();
+        }
+        let mut initial = Some(socket);
 
-                                last_update.store(now_ms(), Ordering::Relaxed);
+        self.running.store(true, Ordering::Relaxed);
 
-                                // Обновляем термометр
-                                if let Ok(mut therm) = therm.write() {
-                                    therm.set_temperature(therm_data.temperature);
-                                }
+        let feed = self.make_feed();
+        let running = Arc::clone(&self.running);
+        let listen_addr = self.listen_addr.clone();
+        let ip_stack = self.ip_stack;
+        let max_age = self.max_age;
 
-                                // Уведомляем о новых данных
-                                let result = Ok(new_temp);
-                                let _ = temp_sender.send(Some(result.clone()));
+        let health = Arc::clone(&self.health);
+        let policy = self.restart_policy;
+        let malformed_packets = Arc::clone(&self.malformed_packets);
 
-                                // Уведомляем всех подписчиков (callback)
-                                if let Ok(callbacks) = callbacks.lock() {
-                                    for (_id, callback) in callbacks.iter() {
-                                        callback(result.clone());
-                                    }
-                                }
-                            }
-                        }
+        let handle = thread::spawn(move || {
+            // Супервизор повторяет привязку UDP сокета и перезапускает упавший цикл
+            supervisor::supervise(
+                &running,
+                &health,
+                policy,
+                || {
+                    // Паника callback'а могла отравить мьютекс подписчиков
+                    feed.callbacks.clear_poison();
+                    match initial.take() {
+                        Some(socket) => Ok(socket),
+                        None => Self::bind_socket(&listen_addr, ip_stack),
                     }
-                    Err(_) => {
-                        // Нет данных, спим немного
-                        thread::sleep(Duration::from_millis(10));
-
-                        // Проверяем возраст данных
-                        let last_timestamp = last_update.load(Ordering::Relaxed);
-                        if last_timestamp != 0
-                            && (now_ms() - last_timestamp) > max_age.as_millis() as u64
-                        {
-                            // Данные устарели - уведомляем
-                            let error_result = Err(ThermError::NoFreshData);
-                            let _ = temp_sender.send(Some(error_result.clone()));
-
-                            if let Ok(callbacks) = callbacks.lock() {
-                                for (_id, callback) in callbacks.iter() {
-                                    callback(error_result.clone());
+                },
+                |socket| {
+                    let mut buf = [0; 1024];
+
+                    while running.load(Ordering::Relaxed) {
+                        match socket.recv_from(&mut buf) {
+                            Ok((size, _)) => {
+                                // Разбор прямо из буфера, без копии пакета
+                                if let Ok(therm_data) = ThermData::parse(&buf[..size]) {
+                                    feed.push(therm_data.temperature);
+                                } else {
+                                    malformed_packets.fetch_add(1, Ordering::Relaxed);
+                                    metrics::record_error(&feed.metrics, None);
                                 }
                             }
+                            Err(e) if is_idle(&e) => {
+                                // Нет данных, спим немного
+                                thread::sleep(Duration::from_millis(10));
+
+                                // Данные устарели - уведомляем один раз
+                                feed.notify_if_stale(max_age);
+                            }
+                            // Сокет неисправен - супервизор создаст новый
+                            Err(e) => return Err(e),
                         }
                     }
-                }
-            }
+
+                    Ok(())
+                },
+            );
         });
 
         self.thread_handle = Some(handle);
+        Ok(())
     }
 
-    /// Получает текущую температуру
-    pub fn temperature(&self) -> Result<Celsius, ThermError> {
-        // Проверяем возраст данных
-        let last_timestamp = self.last_update.load(Ordering::Relaxed);
+    /// Перезапускает прием на новом адресе
+    ///
+    /// Если новый адрес занять не удалось, работавший контроллер
+    /// возвращается на прежний адрес, а ошибка возвращается вызывающему.
+    pub fn restart(&mut self, listen_addr: &str) -> Result<(), ThermError> {
+        let was_running = self.running.load(Ordering::Relaxed);
+        let previous = std::mem::replace(&mut self.listen_addr, listen_addr.to_string());
+        self.stop();
 
-        if last_timestamp == 0 {
-            // Нет данных
-            return Err(ThermError::NoFreshData);
+        let result = self.start();
+        if result.is_err() {
+            self.listen_addr = previous;
+            if was_running {
+                // Прежний адрес только что освобожден этим же контроллером
+                let _ = self.start();
+            }
+        }
+        result
+    }
+
+    fn bind(listen_addr: &str, stack: IpStack) -> Result<UdpSocket, ThermError> {
+        Self::bind_socket(listen_addr, stack)
+            .map_err(|e| ThermError::Bind(format!("{}: {}", listen_addr, e)))
+    }
+
+    /// Неблокирующий UDP сокет на адресе
+    fn bind_socket(listen_addr: &str, stack: IpStack) -> std::io::Result<UdpSocket> {
+        let socket = bind::bind_udp(listen_addr, stack)?;
+        socket.set_nonblocking(true)?;
+        Ok(socket)
+    }
+
+    /// Получает текущую температуру вместе с возрастом показания
+    pub fn temperature(&self) -> Result<Reading, ThermError> {
+        let age = self.last_update_age().ok_or(ThermError::NoFreshData)?;
+        match self.temperature_with_quality()? {
+            (_, ReadingQuality::Stale) => Err(ThermError::NoFreshData), // Данные устарели
+            (value, _) => Ok(Reading { value, age }),
         }
+    }
 
-        if (now_ms() - last_timestamp) > self.max_age.as_millis() as u64 {
-            // Данные устарели
+    /// Время с последнего показания; `None`, если показаний не было
+    pub fn last_update_age(&self) -> Option<Duration> {
+        match self.last_update.load(Ordering::Relaxed) {
+            0 => None,
+            last => Some(Duration::from_millis(
+                self.clock.now_ms().saturating_sub(last),
+            )),
+        }
+    }
+
+    /// Сглаженная температура по последним показаниям
+    ///
+    /// Шум датчика не дает правилам с порогами переключаться туда-обратно
+    /// на каждом показании. Как и `temperature`, при устаревших данных
+    /// возвращает `NoFreshData`.
+    pub fn smoothed_temperature(&self, smoothing: Smoothing) -> Result<Celsius, ThermError> {
+        self.temperature()?;
+        let readings = self.readings.lock().map_err(|_| ThermError::LockError)?;
+        if readings.is_empty() {
             return Err(ThermError::NoFreshData);
         }
+        Ok(smoothing.apply(&readings))
+    }
+
+    /// Получает последнюю температуру и оценку ее свежести
+    ///
+    /// В отличие от `temperature` устаревшее показание тоже возвращается,
+    /// ошибка `NoFreshData` означает, что данных не было ни разу.
+    pub fn temperature_with_quality(&self) -> Result<(Celsius, ReadingQuality), ThermError> {
+        // Нет данных
+        let age = self.last_update_age().ok_or(ThermError::NoFreshData)?;
+        let quality = ReadingQuality::classify(age, self.warning_age, self.max_age);
 
-        // Получаем температуру
         self.therm
             .read()
-            .map(|therm| therm.temperature())
+            .map(|therm| (therm.temperature(), quality))
             .map_err(|_| ThermError::LockError)
     }
 
@@ -198,9 +561,15 @@ impl ThermController {
 
     /// Останавливает автоматическое обновление
     pub fn stop(&mut self) {
+        let _ = self.shutdown();
+    }
+
+    /// Останавливает поток приема; ошибка - поток завершился паникой
+    fn shutdown(&mut self) -> std::io::Result<()> {
         self.running.store(false, Ordering::Relaxed);
-        if let Some(handle) = self.thread_handle.take() {
-            let _ = handle.join();
+        match self.thread_handle.take().map(|handle| handle.join()) {
+            Some(Err(_)) => Err(std::io::Error::other("Поток приема показаний упал")),
+            _ => Ok(()),
         }
     }
 
@@ -218,6 +587,66 @@ impl ThermController {
         }
     }
 
+    /// Ожидание следующего показания, не занимающее контроллер
+    ///
+    /// В отличие от `wait_for_new_data` уже полученное показание не
+    /// учитывается, поэтому future можно запускать отдельной задачей.
+    pub fn next_reading(&self) -> impl Future<Output = Result<Celsius, ThermError>> + Send + use<> {
+        let mut receiver = self.temp_receiver.clone();
+        receiver.mark_unchanged();
+
+        async move {
+            match receiver.changed().await {
+                Ok(_) => match receiver.borrow().clone() {
+                    Some(result) => result,
+                    None => Err(ThermError::NoFreshData),
+                },
+                Err(_) => Err(ThermError::NetworkError("Channel closed".to_string())),
+            }
+        }
+    }
+
+    /// Ждет температуру, удовлетворяющую `predicate`
+    ///
+    /// Сначала проверяется текущее свежее показание, затем каждое новое.
+    /// Если за `timeout` подходящего показания нет, возвращается
+    /// `ThermError::Timeout`.
+    pub async fn wait_until<F>(&self, predicate: F, timeout: Duration) -> Result<Celsius, ThermError>
+    where
+        F: Fn(Celsius) -> bool,
+    {
+        let mut receiver = self.temp_receiver.clone();
+        receiver.mark_unchanged();
+        if let Ok(reading) = self.temperature()
+            && predicate(reading.value)
+        {
+            return Ok(reading.value);
+        }
+
+        let wait = async {
+            loop {
+                if receiver.changed().await.is_err() {
+                    return Err(ThermError::NetworkError("Channel closed".to_string()));
+                }
+                if let Some(Ok(temperature)) = *receiver.borrow_and_update()
+                    && predicate(temperature)
+                {
+                    return Ok(temperature);
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or(Err(ThermError::Timeout))
+    }
+
+    /// Канал метрик: получатель видит обновление после каждого показания
+    ///
+    /// Неразобранные пакеты учитываются как ошибки.
+    pub fn metrics_watch(&self) -> watch::Receiver<ControllerMetrics> {
+        self.metrics.subscribe()
+    }
+
     /// Подписка на изменения температуры (callback)
     pub fn on_temperature_change<F>(&self, callback: F) -> SubscriptionHandle
     where
@@ -234,11 +663,26 @@ impl ThermController {
             callbacks: Arc::clone(&self.callbacks),
         }
     }
+
+    /// Подписка через буфер: медленный подписчик не задерживает прием данных
+    ///
+    /// При заполнении буфера значения теряются согласно `policy`,
+    /// потери видны в `TemperatureSubscription::stats`.
+    pub fn subscribe_buffered(
+        &self,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> TemperatureSubscription {
+        let id = self.next_callback_id.fetch_add(1, Ordering::Relaxed);
+        BufferedSubscription::register(id, capacity, policy, &self.buffers)
+    }
 }
 
 impl Drop for ThermController {
     fn drop(&mut self) {
         self.stop();
+        self.watchdog.store(false, Ordering::Relaxed);
+        subscription::close_all(&self.buffers);
     }
 }
 
@@ -246,6 +690,42 @@ impl Reporter for ThermController {
     fn report(&self) -> String {
         self.device().report()
     }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        self.device().report_with(options)
+    }
+}
+
+impl AsyncReporter for ThermController {
+    /// Термометр сам присылает показания: если свежих нет, ждет следующего
+    async fn live_report(&mut self, timeout: Duration) -> String {
+        let result = match self.temperature() {
+            Ok(_) => Ok(()),
+            Err(_) => match tokio::time::timeout(timeout, self.wait_for_new_data()).await {
+                Ok(result) => result.map(|_| ()),
+                Err(_) => Err(ThermError::NoFreshData),
+            },
+        };
+        match result {
+            Ok(()) => self.report(),
+            Err(e) => stale_report(self.report(), e),
+        }
+    }
+}
+
+impl Lifecycle for ThermController {
+    /// Запускает прием показаний; занятый адрес - ошибка запуска
+    async fn start(&mut self) -> std::io::Result<()> {
+        ThermController::start(self).map_err(std::io::Error::other)
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        self.shutdown()
+    }
+
+    fn health(&self) -> HealthStatus {
+        self.health_status()
+    }
 }
 
 impl fmt::Display for ThermController {
@@ -254,6 +734,18 @@ impl fmt::Display for ThermController {
     }
 }
 
+/// Ошибки чтения, означающие отсутствие данных, а не неисправность сокета
+pub(super) fn is_idle(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::WouldBlock
+            | std::io::ErrorKind::TimedOut
+            | std::io::ErrorKind::Interrupted
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::ConnectionRefused
+    )
+}
+
 /// Handle подписки
 pub struct SubscriptionHandle {
     callback_id: usize,
@@ -285,9 +777,13 @@ impl Drop for SubscriptionHandle {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::{Clock, MockClock};
+    use crate::controllers::supervisor::TaskState;
+    use crate::protocol::now_ms;
     use std::net::UdpSocket;
     use std::thread;
     use std::time::Duration;
+    use tokio::time::timeout;
 
     fn find_free_port() -> u16 {
         let socket = UdpSocket::bind("127.0.0.1:0").expect("Failed to bind to find free port");
@@ -339,7 +835,7 @@ mod tests {
 
         let result = controller.temperature();
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Celsius::new(25.5));
+        assert_eq!(result.unwrap().value, Celsius::new(25.5));
     }
 
     #[test]
@@ -357,6 +853,70 @@ mod tests {
         assert!(matches!(result, Err(ThermError::NoFreshData)));
     }
 
+    #[test]
+    fn temperature_goes_stale_with_mock_clock() {
+        let port = find_free_port();
+        let addr = format!("127.0.0.1:{}", port);
+        let clock = Arc::new(MockClock::new());
+        let controller =
+            ThermController::new(20.0, &addr, Duration::from_secs(60)).with_clock(clock.clone());
+
+        assert_eq!(controller.last_update_age(), None);
+        controller
+            .last_update
+            .store(clock.now_ms(), Ordering::Relaxed);
+        clock.advance(Duration::from_secs(20));
+        let reading = controller.temperature().unwrap();
+        assert_eq!(reading.value, Celsius::new(20.0));
+        assert_eq!(reading.age, Duration::from_secs(20));
+        assert_eq!(controller.last_update_age(), Some(Duration::from_secs(20)));
+
+        // Час симуляции без реального ожидания
+        clock.advance(Duration::from_secs(3600));
+        assert!(matches!(
+            controller.temperature(),
+            Err(ThermError::NoFreshData)
+        ));
+    }
+
+    #[test]
+    fn temperature_quality_tiers() {
+        let clock = Arc::new(MockClock::new());
+        let controller = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(60))
+            .with_warning_age(Duration::from_secs(10))
+            .with_clock(clock.clone());
+        assert!(matches!(
+            controller.temperature_with_quality(),
+            Err(ThermError::NoFreshData)
+        ));
+
+        controller
+            .last_update
+            .store(clock.now_ms(), Ordering::Relaxed);
+        assert_eq!(
+            controller.temperature_with_quality().unwrap(),
+            (Celsius::new(20.0), ReadingQuality::Fresh)
+        );
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            controller.temperature_with_quality().unwrap().1,
+            ReadingQuality::Aging(Duration::from_secs(30))
+        );
+        assert!(controller.temperature().is_ok());
+
+        // Устаревшее показание доступно, но temperature() его не возвращает
+        clock.advance(Duration::from_secs(31));
+        assert_eq!(
+            controller.temperature_with_quality().unwrap(),
+            (Celsius::new(20.0), ReadingQuality::Stale)
+        );
+        assert!(matches!(
+            controller.temperature(),
+            Err(ThermError::NoFreshData)
+        ));
+    }
+
     #[test]
     fn controller_start_stop_basic() {
         let port = find_free_port();
@@ -367,7 +927,7 @@ mod tests {
         assert!(!controller.running.load(Ordering::Relaxed));
 
         // Запускаем
-        controller.start();
+        controller.start().unwrap();
         assert!(controller.running.load(Ordering::Relaxed));
 
         // Останавливаем БЫСТРО
@@ -376,6 +936,181 @@ mod tests {
         assert!(!controller.running.load(Ordering::Relaxed));
     }
 
+    #[test]
+    fn start_fails_on_busy_address() {
+        // Порт занят другим сокетом
+        let blocker = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = blocker.local_addr().unwrap().to_string();
+
+        let mut controller = ThermController::new(20.0, &addr, Duration::from_secs(5));
+        assert!(matches!(controller.start(), Err(ThermError::Bind(_))));
+        assert!(!controller.running.load(Ordering::Relaxed));
+        assert_eq!(controller.health().state, TaskState::Stopped);
+
+        // Порт освободился - запуск удается
+        drop(blocker);
+        controller.start().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert!(controller.health().is_healthy());
+
+        // Переезд на занятый адрес не удается, прием продолжается на прежнем
+        let blocker = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let busy = blocker.local_addr().unwrap().to_string();
+        assert!(matches!(controller.restart(&busy), Err(ThermError::Bind(_))));
+        assert_eq!(controller.listen_addr(), addr);
+        assert!(controller.running.load(Ordering::Relaxed));
+
+        // Порт 0 заменяется фактическим адресом
+        controller.restart("127.0.0.1:0").unwrap();
+        assert_ne!(controller.listen_addr(), "127.0.0.1:0");
+        assert_ne!(controller.listen_addr(), addr);
+
+        controller.stop();
+        assert_eq!(controller.health().state, TaskState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn wait_until_threshold() {
+        let controller = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(5));
+        let feed = controller.feed();
+
+        let pusher = tokio::spawn(async move {
+            for temperature in [40.0, 70.0, 85.0, 90.0] {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                feed.push(temperature);
+            }
+        });
+        let hot = controller
+            .wait_until(|t| t > Celsius::new(80.0), Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(hot, Celsius::new(85.0));
+        pusher.await.unwrap();
+
+        // Текущее показание уже подходит
+        let now = controller
+            .wait_until(|t| t > Celsius::new(80.0), Duration::from_millis(10))
+            .await;
+        assert_eq!(now.unwrap(), Celsius::new(90.0));
+
+        assert!(matches!(
+            controller
+                .wait_until(|t| t < Celsius::new(0.0), Duration::from_millis(20))
+                .await,
+            Err(ThermError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn smoothed_temperature_windows() {
+        let controller = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(5));
+        assert!(matches!(
+            controller.smoothed_temperature(Smoothing::Mean(3)),
+            Err(ThermError::NoFreshData)
+        ));
+
+        let feed = controller.feed();
+        for temperature in [21.0, 22.0, 40.0, 23.0] {
+            feed.push(temperature);
+        }
+
+        // Выброс 40 сдвигает среднее, но не медиану
+        let mean = controller.smoothed_temperature(Smoothing::Mean(3)).unwrap();
+        assert!((mean.value() - 85.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            controller.smoothed_temperature(Smoothing::Median(3)).unwrap(),
+            Celsius::new(23.0)
+        );
+        assert_eq!(
+            controller.smoothed_temperature(Smoothing::Median(10)).unwrap(),
+            Celsius::new(22.5)
+        );
+        assert_eq!(
+            controller.smoothed_temperature(Smoothing::Mean(0)).unwrap(),
+            Celsius::new(23.0)
+        );
+
+        for _ in 0..READINGS_CAPACITY {
+            feed.push(25.0);
+        }
+        assert_eq!(
+            controller.smoothed_temperature(Smoothing::Mean(1000)).unwrap(),
+            Celsius::new(25.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn metrics_follow_readings() {
+        let controller = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(5));
+        let mut metrics = controller.metrics_watch();
+        assert_eq!(*metrics.borrow(), ControllerMetrics::default());
+
+        let feed = controller.feed();
+        tokio::spawn(async move { feed.push(23.5) });
+        timeout(Duration::from_secs(1), metrics.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        let snapshot = metrics.borrow_and_update().clone();
+        assert_eq!(snapshot.operations, 1);
+        assert_eq!(snapshot.last_value, Some(23.5));
+        assert!(snapshot.last_success_ms.is_some());
+    }
+
+    #[test]
+    fn stale_notification_fires_once() {
+        let clock = Arc::new(MockClock::new());
+        let controller = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(60))
+            .with_clock(clock.clone());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let _subscription = controller.on_temperature_change(move |result| {
+            sink.lock().unwrap().push(result.is_ok());
+        });
+        let feed = controller.feed();
+        let max_age = Duration::from_secs(60);
+
+        // Без показаний устаревать нечему
+        feed.notify_if_stale(max_age);
+        feed.push(21.0);
+        clock.advance(Duration::from_secs(61));
+        for _ in 0..5 {
+            feed.notify_if_stale(max_age);
+        }
+        assert_eq!(*events.lock().unwrap(), vec![true, false]);
+
+        // Восстановление и повторное устаревание
+        feed.push(22.0);
+        feed.notify_if_stale(max_age);
+        clock.advance(Duration::from_secs(61));
+        feed.notify_if_stale(max_age);
+        feed.notify_if_stale(max_age);
+        assert_eq!(*events.lock().unwrap(), vec![true, false, true, false]);
+    }
+
+    #[test]
+    fn fed_readings_go_stale() {
+        let clock = Arc::new(MockClock::new());
+        let controller = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(60))
+            .with_clock(clock.clone());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let _subscription = controller.on_temperature_change(move |result| {
+            sink.lock().unwrap().push(result.is_ok());
+        });
+
+        // Показания только через feed, собственный прием не запущен
+        controller.feed().push(21.0);
+        clock.advance(Duration::from_secs(61));
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(2);
+        while events.lock().unwrap().len() < 2 && std::time::Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(STALE_CHECK_INTERVAL * 3);
+        assert_eq!(*events.lock().unwrap(), vec![true, false]);
+    }
+
     #[test]
     fn subscription_basic() {
         let port = find_free_port();
@@ -404,4 +1139,111 @@ mod tests {
         let callbacks_len = controller.callbacks.lock().map(|cb| cb.len()).unwrap_or(0);
         assert_eq!(callbacks_len, 0);
     }
+
+    #[test]
+    fn buffered_subscription_does_not_block_receive() {
+        let port = find_free_port();
+        let addr = format!("127.0.0.1:{}", port);
+        let mut controller = ThermController::new(20.0, &addr, Duration::from_secs(5));
+        controller.start().unwrap();
+
+        // Подписчик ничего не читает - буфер переполняется
+        let slow = controller.subscribe_buffered(2, OverflowPolicy::DropNewest);
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        thread::sleep(Duration::from_millis(50));
+        for i in 0..5 {
+            let data = ThermData {
+                temperature: 20.0 + i as f64,
+                device_id: Some("test".into()),
+            };
+            let json = serde_json::to_string(&data).unwrap();
+            sender.send_to(json.as_bytes(), &addr).unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        let mut stats = slow.stats();
+        for _ in 0..50 {
+            if stats.queued + stats.dropped as usize == 5 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+            stats = slow.stats();
+        }
+
+        assert_eq!(stats.queued, 2);
+        assert_eq!(stats.dropped, 3);
+        // Прием продолжался, несмотря на переполненный буфер
+        assert_eq!(controller.temperature().unwrap().value, Celsius::new(24.0));
+        assert!(matches!(slow.try_recv(), Some(Ok(t)) if t == Celsius::new(20.0)));
+
+        controller.stop();
+    }
+
+    #[test]
+    fn health_status_counts_malformed_packets() {
+        let port = find_free_port();
+        let addr = format!("127.0.0.1:{}", port);
+        let mut controller = ThermController::new(20.0, &addr, Duration::from_secs(5));
+        assert!(!controller.health_status().is_healthy());
+
+        controller.start().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        thread::sleep(Duration::from_millis(50));
+        sender.send_to(b"not json", &addr).unwrap();
+        let json = serde_json::to_string(&ThermData {
+            temperature: 21.0,
+            device_id: None,
+        })
+        .unwrap();
+        sender.send_to(json.as_bytes(), &addr).unwrap();
+
+        let mut status = controller.health_status();
+        for _ in 0..50 {
+            if status.is_healthy() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+            status = controller.health_status();
+        }
+
+        assert!(status.is_healthy());
+        assert!(status.last_success_ms.is_some());
+        assert_eq!(status.error_counts.get("malformed"), Some(&1));
+
+        controller.stop();
+        assert!(!controller.health_status().is_healthy());
+    }
+
+    #[test]
+    fn dual_stack_receives_ipv4_and_ipv6() {
+        let port = find_free_port();
+        let mut controller =
+            ThermController::new(20.0, &format!("[::]:{}", port), Duration::from_secs(5))
+                .with_ip_stack(IpStack::DualStack);
+        controller.start().unwrap();
+
+        for (host, temperature) in [("[::1]", 21.0), ("127.0.0.1", 22.0)] {
+            let target: std::net::SocketAddr = format!("{}:{}", host, port).parse().unwrap();
+            let sender = UdpSocket::bind(bind::unspecified_for(target)).unwrap();
+            let json = serde_json::to_string(&ThermData {
+                temperature,
+                device_id: None,
+            })
+            .unwrap();
+
+            let mut reading = None;
+            for _ in 0..50 {
+                sender.send_to(json.as_bytes(), target).unwrap();
+                thread::sleep(Duration::from_millis(20));
+                reading = controller.temperature().ok().map(|r| r.value);
+                if reading == Some(Celsius::new(temperature)) {
+                    break;
+                }
+            }
+            assert_eq!(reading, Some(Celsius::new(temperature)), "{}", host);
+        }
+
+        controller.stop();
+    }
 }
@@ -0,0 +1,170 @@
+//! Async TCP контроллер для запорного клапана
+
+use super::health::HealthStatus;
+use super::link::{DeviceLink, LinkError};
+use crate::devices::SmartValve;
+use crate::protocol::socket_protocol::DEFAULT_MAX_MESSAGE_SIZE;
+use crate::protocol::transport::SharedTransport;
+use crate::protocol::valve_protocol::{
+    ValveCommand, ValveData, ValveResponse, send_valve_command_and_receive,
+};
+use crate::report::ReportOptions;
+use crate::traits::{Lifecycle, Reporter};
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Ошибки контроллера клапана
+pub type ValveError = LinkError;
+
+/// Async контроллер запорного клапана (TCP)
+pub struct ValveController {
+    /// Модель клапана (последнее известное состояние)
+    valve: SmartValve,
+    /// Соединение с клапаном
+    link: DeviceLink,
+}
+
+impl ValveController {
+    /// Создает контроллер клапана
+    pub fn new(address: SocketAddr, timeout: Duration) -> Self {
+        Self {
+            valve: SmartValve::new(),
+            link: DeviceLink::new(address, timeout),
+        }
+    }
+
+    /// Builder: транспорт для подключения к клапану
+    pub fn with_transport(mut self, transport: SharedTransport) -> Self {
+        self.link.set_transport(transport);
+        self
+    }
+
+    /// Отправляет команду и синхронизирует модель клапана
+    async fn command(&mut self, command: ValveCommand) -> Result<ValveData, ValveError> {
+        let result = self.exchange(&command).await;
+        match &result {
+            Ok(data) => {
+                self.link.record_success();
+                if data.open {
+                    self.valve.open();
+                } else {
+                    self.valve.close();
+                }
+            }
+            Err(e) => self.link.record_error(e.kind(), e),
+        }
+        result
+    }
+
+    async fn exchange(&mut self, command: &ValveCommand) -> Result<ValveData, ValveError> {
+        let limit = self.link.timeout();
+        let stream = self.link.connect().await?;
+        let exchange = send_valve_command_and_receive(stream, command, DEFAULT_MAX_MESSAGE_SIZE);
+        let result = timeout(limit, exchange).await;
+
+        match self.link.finish(result)? {
+            ValveResponse::Ok(data) => Ok(data),
+            ValveResponse::Error { message } => Err(LinkError::DeviceError(message)),
+        }
+    }
+
+    /// Открывает клапан
+    pub async fn open(&mut self) -> Result<(), ValveError> {
+        self.command(ValveCommand::Open).await.map(|_| ())
+    }
+
+    /// Перекрывает воду
+    pub async fn close(&mut self) -> Result<(), ValveError> {
+        self.command(ValveCommand::Close).await.map(|_| ())
+    }
+
+    /// Запрашивает состояние клапана; возвращает `true`, если он открыт
+    pub async fn status(&mut self) -> Result<bool, ValveError> {
+        self.command(ValveCommand::Status)
+            .await
+            .map(|data| data.open)
+    }
+
+    /// Последнее известное состояние клапана
+    pub fn device(&self) -> &SmartValve {
+        &self.valve
+    }
+
+    /// Разрывает соединение
+    pub fn disconnect(&mut self) {
+        self.link.disconnect();
+    }
+
+    /// Возвращает адрес клапана
+    pub fn address(&self) -> SocketAddr {
+        self.link.address()
+    }
+
+    /// Состояние связи с клапаном
+    pub fn health_status(&self) -> HealthStatus {
+        self.link.health_status()
+    }
+}
+
+impl Reporter for ValveController {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        self.valve.report_with(options)
+    }
+}
+
+impl Lifecycle for ValveController {
+    /// Открывает соединение с клапаном
+    async fn start(&mut self) -> std::io::Result<()> {
+        self.link.start().await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        self.disconnect();
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        self.health_status()
+    }
+}
+
+impl fmt::Display for ValveController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulators::valve_emulator::ValveEmulator;
+    use crate::protocol::transport::InMemory;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn open_and_close() {
+        let transport = InMemory::new();
+        let mut emulator = ValveEmulator::new("10.0.0.8:7100");
+        emulator.start_in_memory(&transport).await.unwrap();
+
+        let mut controller =
+            ValveController::new(emulator.local_addr().unwrap(), Duration::from_secs(1))
+                .with_transport(Arc::new(transport));
+        controller.close().await.unwrap();
+        assert!(!controller.device().is_open());
+        assert!(!emulator.is_open());
+
+        controller.open().await.unwrap();
+        assert!(controller.status().await.unwrap());
+        assert_eq!(controller.report(), "Smart Valve: OPEN");
+        assert!(controller.health_status().connected);
+
+        emulator.stop().await;
+    }
+}
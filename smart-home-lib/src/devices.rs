@@ -1,19 +1,66 @@
 //! Модуль устройств умного дома
 
+use crate::report::ReportOptions;
 use crate::traits::Reporter;
 use std::fmt;
 
+#[cfg(feature = "leak")]
+mod leak_sensor;
+#[cfg(feature = "blinds")]
+mod smart_blinds;
+#[cfg(feature = "camera")]
+mod smart_camera;
+mod smart_meter;
 mod smart_socket;
 mod smart_therm;
-
+#[cfg(feature = "valve")]
+mod smart_valve;
+
+#[cfg(feature = "leak")]
+pub use leak_sensor::LeakSensor;
+#[cfg(feature = "blinds")]
+pub use smart_blinds::SmartBlinds;
+#[cfg(feature = "camera")]
+pub use smart_camera::SmartCamera;
+pub use smart_meter::SmartMeter;
 pub use smart_socket::SmartSocket;
 pub use smart_therm::SmartTherm;
+#[cfg(feature = "valve")]
+pub use smart_valve::SmartValve;
 
 /// Универсальный тип для устройств умного дома
 #[derive(Debug)]
 pub enum Device {
     Socket(SmartSocket),
     Therm(SmartTherm),
+    #[cfg(feature = "camera")]
+    Camera(SmartCamera),
+    #[cfg(feature = "blinds")]
+    Blinds(SmartBlinds),
+    #[cfg(feature = "leak")]
+    Leak(LeakSensor),
+    #[cfg(feature = "valve")]
+    Valve(SmartValve),
+    Meter(SmartMeter),
+}
+
+impl Device {
+    /// Название типа устройства
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Socket(_) => "socket",
+            Self::Therm(_) => "therm",
+            #[cfg(feature = "camera")]
+            Self::Camera(_) => "camera",
+            #[cfg(feature = "blinds")]
+            Self::Blinds(_) => "blinds",
+            #[cfg(feature = "leak")]
+            Self::Leak(_) => "leak",
+            #[cfg(feature = "valve")]
+            Self::Valve(_) => "valve",
+            Self::Meter(_) => "meter",
+        }
+    }
 }
 
 impl Reporter for Device {
@@ -21,6 +68,31 @@ impl Reporter for Device {
         match self {
             Self::Socket(s) => s.report(),
             Self::Therm(t) => t.report(),
+            #[cfg(feature = "camera")]
+            Self::Camera(c) => c.report(),
+            #[cfg(feature = "blinds")]
+            Self::Blinds(b) => b.report(),
+            #[cfg(feature = "leak")]
+            Self::Leak(l) => l.report(),
+            #[cfg(feature = "valve")]
+            Self::Valve(v) => v.report(),
+            Self::Meter(m) => m.report(),
+        }
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        match self {
+            Self::Socket(s) => s.report_with(options),
+            Self::Therm(t) => t.report_with(options),
+            #[cfg(feature = "camera")]
+            Self::Camera(c) => c.report_with(options),
+            #[cfg(feature = "blinds")]
+            Self::Blinds(b) => b.report_with(options),
+            #[cfg(feature = "leak")]
+            Self::Leak(l) => l.report_with(options),
+            #[cfg(feature = "valve")]
+            Self::Valve(v) => v.report_with(options),
+            Self::Meter(m) => m.report_with(options),
         }
     }
 }
@@ -43,6 +115,40 @@ impl From<SmartTherm> for Device {
     }
 }
 
+#[cfg(feature = "camera")]
+impl From<SmartCamera> for Device {
+    fn from(camera: SmartCamera) -> Self {
+        Self::Camera(camera)
+    }
+}
+
+#[cfg(feature = "blinds")]
+impl From<SmartBlinds> for Device {
+    fn from(blinds: SmartBlinds) -> Self {
+        Self::Blinds(blinds)
+    }
+}
+
+#[cfg(feature = "leak")]
+impl From<LeakSensor> for Device {
+    fn from(sensor: LeakSensor) -> Self {
+        Self::Leak(sensor)
+    }
+}
+
+#[cfg(feature = "valve")]
+impl From<SmartValve> for Device {
+    fn from(valve: SmartValve) -> Self {
+        Self::Valve(valve)
+    }
+}
+
+impl From<SmartMeter> for Device {
+    fn from(meter: SmartMeter) -> Self {
+        Self::Meter(meter)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,67 @@
+//! Датчик протечки воды
+
+use super::Reporter;
+use crate::report::ReportOptions;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LeakSensor {
+    leak: bool,                 // Датчик обнаружил воду
+    last_event_ms: Option<u64>, // Время последнего события, мс с Unix epoch
+}
+
+impl LeakSensor {
+    /// Создает сухой датчик без событий
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Датчик обнаружил воду
+    pub fn is_leaking(&self) -> bool {
+        self.leak
+    }
+
+    /// Время последнего события, мс с Unix epoch
+    pub fn last_event_ms(&self) -> Option<u64> {
+        self.last_event_ms
+    }
+
+    /// Учитывает событие датчика
+    pub fn record(&mut self, leak: bool, at_ms: u64) {
+        self.leak = leak;
+        self.last_event_ms = Some(at_ms);
+    }
+}
+
+impl Reporter for LeakSensor {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, _options: &ReportOptions) -> String {
+        format!("Leak Sensor: {}", if self.leak { "LEAK" } else { "DRY" })
+    }
+}
+
+impl fmt::Display for LeakSensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leak_state() {
+        let mut sensor = LeakSensor::new();
+        assert!(!sensor.is_leaking());
+        assert_eq!(sensor.report(), "Leak Sensor: DRY");
+
+        sensor.record(true, 1000);
+        assert!(sensor.is_leaking());
+        assert_eq!(sensor.last_event_ms(), Some(1000));
+        assert_eq!(sensor.report(), "Leak Sensor: LEAK");
+    }
+}
@@ -0,0 +1,111 @@
+//! Умные жалюзи с управлением положением
+
+use super::Reporter;
+use crate::report::ReportOptions;
+use crate::units::Percent;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmartBlinds {
+    position: Percent, // Текущее положение: 0% - закрыты, 100% - открыты
+    target: Percent,   // Положение, к которому движутся жалюзи
+    moving: bool,      // Жалюзи в движении
+}
+
+impl SmartBlinds {
+    /// Создает неподвижные жалюзи в положении `position` (ограничивается 0-100%)
+    pub fn new(position: f64) -> Self {
+        let position = Percent::clamped(position);
+        Self {
+            position,
+            target: position,
+            moving: false,
+        }
+    }
+
+    /// Возвращает текущее положение
+    pub fn position(&self) -> Percent {
+        self.position
+    }
+
+    /// Возвращает положение, к которому движутся жалюзи
+    pub fn target(&self) -> Percent {
+        self.target
+    }
+
+    /// Жалюзи в движении
+    pub fn is_moving(&self) -> bool {
+        self.moving
+    }
+
+    /// Жалюзи хотя бы частично открыты
+    pub fn is_open(&self) -> bool {
+        self.position.value() > 0.0
+    }
+
+    /// Начинает движение к положению `target`
+    pub fn move_to(&mut self, target: f64) {
+        self.target = Percent::clamped(target);
+        self.moving = self.target != self.position;
+    }
+
+    /// Останавливает жалюзи в текущем положении
+    pub fn stop(&mut self) {
+        self.target = self.position;
+        self.moving = false;
+    }
+
+    /// Устанавливает состояние по данным устройства
+    pub fn set_state(&mut self, position: f64, target: f64, moving: bool) {
+        self.position = Percent::clamped(position);
+        self.target = Percent::clamped(target);
+        self.moving = moving;
+    }
+}
+
+impl Reporter for SmartBlinds {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, _options: &ReportOptions) -> String {
+        if self.moving {
+            format!("Smart Blinds: {} -> {}", self.position, self.target)
+        } else {
+            format!("Smart Blinds: {}", self.position)
+        }
+    }
+}
+
+impl fmt::Display for SmartBlinds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_control() {
+        let mut blinds = SmartBlinds::new(0.0);
+        assert!(!blinds.is_open());
+        assert_eq!(blinds.report(), "Smart Blinds: 0.0%");
+
+        blinds.move_to(150.0);
+        assert!(blinds.is_moving());
+        assert_eq!(blinds.target(), Percent::new(100.0));
+
+        blinds.set_state(30.0, 100.0, true);
+        assert_eq!(blinds.report(), "Smart Blinds: 30.0% -> 100.0%");
+        blinds.stop();
+        assert!(!blinds.is_moving());
+        assert!(blinds.is_open());
+        assert_eq!(blinds.target(), Percent::new(30.0));
+
+        // Движение к текущему положению - не движение
+        blinds.move_to(30.0);
+        assert!(!blinds.is_moving());
+    }
+}
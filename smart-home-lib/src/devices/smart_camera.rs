@@ -0,0 +1,105 @@
+//! Умная камера со снимками JPEG
+
+use super::Reporter;
+use crate::report::ReportOptions;
+use bytes::Bytes;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmartCamera {
+    width: u32,               // Ширина кадра в пикселях
+    height: u32,              // Высота кадра в пикселях
+    snapshot: Bytes,          // Последний полученный снимок JPEG
+    snapshot_ms: Option<u64>, // Время получения снимка, мс с Unix epoch
+    sequence: Option<u64>,    // Номер кадра последнего снимка
+}
+
+impl SmartCamera {
+    /// Создает камеру с указанным разрешением, снимков еще нет
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            snapshot: Bytes::new(),
+            snapshot_ms: None,
+            sequence: None,
+        }
+    }
+
+    /// Возвращает разрешение кадра (ширина, высота)
+    pub fn resolution(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Возвращает последний снимок (пустой, если снимков еще не было)
+    ///
+    /// `Bytes` копируется без копирования данных снимка.
+    pub fn latest_snapshot(&self) -> Bytes {
+        self.snapshot.clone()
+    }
+
+    /// Время получения последнего снимка, мс с Unix epoch
+    pub fn snapshot_ms(&self) -> Option<u64> {
+        self.snapshot_ms
+    }
+
+    /// Номер кадра последнего снимка
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    /// Сохраняет новый снимок и обновляет разрешение по данным камеры
+    pub fn set_snapshot(
+        &mut self,
+        snapshot: Bytes,
+        (width, height): (u32, u32),
+        sequence: u64,
+        at_ms: u64,
+    ) {
+        self.snapshot = snapshot;
+        self.width = width;
+        self.height = height;
+        self.sequence = Some(sequence);
+        self.snapshot_ms = Some(at_ms);
+    }
+}
+
+impl Reporter for SmartCamera {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, _options: &ReportOptions) -> String {
+        let snapshot = if self.snapshot.is_empty() {
+            "no snapshot".to_string()
+        } else {
+            format!("snapshot {} B", self.snapshot.len())
+        };
+        format!("Smart Camera: {}x{}, {}", self.width, self.height, snapshot)
+    }
+}
+
+impl fmt::Display for SmartCamera {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_update() {
+        let mut camera = SmartCamera::new(640, 480);
+        assert!(camera.latest_snapshot().is_empty());
+        assert_eq!(camera.report(), "Smart Camera: 640x480, no snapshot");
+
+        camera.set_snapshot(Bytes::from_static(b"jpeg"), (320, 240), 7, 1000);
+        assert_eq!(camera.latest_snapshot(), Bytes::from_static(b"jpeg"));
+        assert_eq!(camera.resolution(), (320, 240));
+        assert_eq!(camera.sequence(), Some(7));
+        assert_eq!(camera.snapshot_ms(), Some(1000));
+        assert_eq!(camera.report(), "Smart Camera: 320x240, snapshot 4 B");
+    }
+}
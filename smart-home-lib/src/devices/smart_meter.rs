@@ -0,0 +1,77 @@
+//! Счетчик электроэнергии с накопительными показаниями
+
+use super::Reporter;
+use crate::report::ReportOptions;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SmartMeter {
+    total_kwh: f64,               // Накопленное показание, кВт·ч
+    last_reading_ms: Option<u64>, // Время последнего показания, мс с Unix epoch
+}
+
+impl SmartMeter {
+    /// Создает счетчик с начальным показанием `total_kwh`
+    pub fn new(total_kwh: f64) -> Self {
+        Self {
+            total_kwh: total_kwh.max(0.0),
+            last_reading_ms: None,
+        }
+    }
+
+    /// Накопленное показание, кВт·ч
+    pub fn total_kwh(&self) -> f64 {
+        self.total_kwh
+    }
+
+    /// Время последнего показания, мс с Unix epoch
+    pub fn last_reading_ms(&self) -> Option<u64> {
+        self.last_reading_ms
+    }
+
+    /// Учитывает показание счетчика; возвращает расход с прошлого показания
+    ///
+    /// Накопительное показание не уменьшается: меньшее значение (сброс или
+    /// замена счетчика) начинает отсчет заново с нулевым расходом.
+    pub fn record(&mut self, total_kwh: f64, at_ms: u64) -> f64 {
+        let consumed = (total_kwh - self.total_kwh).max(0.0);
+        self.total_kwh = total_kwh.max(0.0);
+        self.last_reading_ms = Some(at_ms);
+        consumed
+    }
+}
+
+impl Reporter for SmartMeter {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, _options: &ReportOptions) -> String {
+        format!("Smart Meter: {:.1} kWh", self.total_kwh)
+    }
+}
+
+impl fmt::Display for SmartMeter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cumulative_readings() {
+        let mut meter = SmartMeter::new(100.0);
+        assert_eq!(meter.report(), "Smart Meter: 100.0 kWh");
+
+        assert_eq!(meter.record(102.5, 1000), 2.5);
+        assert_eq!(meter.last_reading_ms(), Some(1000));
+
+        // Сброс счетчика - без отрицательного расхода
+        assert_eq!(meter.record(0.5, 2000), 0.0);
+        assert_eq!(meter.record(1.5, 3000), 1.0);
+        assert_eq!(meter.total_kwh(), 1.5);
+    }
+}
@@ -1,14 +1,17 @@
 //! Умная розетка с возможностью управления и мониторинга
 
 use super::Reporter;
+use crate::report::ReportOptions;
 use crate::units::Watts;
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SmartSocket {
     is_active: bool,
-    power_rating: Watts,  // Номинальная мощность в ваттах
-    current_power: Watts, // Текущая потребляемая мощность в ваттах
+    power_rating: Watts,        // Номинальная мощность в ваттах
+    current_power: Watts,       // Текущая потребляемая мощность в ваттах
+    load_percent: u8,           // Уровень нагрузки диммера (0-100%)
+    power_limit: Option<Watts>, // Предельная допустимая мощность
 }
 
 impl SmartSocket {
@@ -18,13 +21,15 @@ impl SmartSocket {
             is_active: false,
             power_rating: Watts::new(power_rating),
             current_power: Watts::new(0.0),
+            load_percent: 100,
+            power_limit: None,
         }
     }
 
     /// Включает розетку и начинает потребление энергии
     pub fn turn_on(&mut self) {
         self.is_active = true;
-        self.current_power = self.power_rating;
+        self.current_power = self.expected_power();
     }
 
     /// Выключает розетку и останавливает потребление энергии
@@ -52,16 +57,68 @@ impl SmartSocket {
     pub fn set_current_power(&mut self, power: Watts) {
         self.current_power = power;
     }
+
+    /// Возвращает уровень нагрузки диммера в процентах
+    pub fn load_percent(&self) -> u8 {
+        self.load_percent
+    }
+
+    /// Устанавливает уровень нагрузки диммера (значения выше 100 ограничиваются)
+    pub fn set_load(&mut self, percent: u8) {
+        self.load_percent = percent.min(100);
+        if self.is_active {
+            self.current_power = self.expected_power();
+        }
+    }
+
+    /// Мощность, которую розетка потребляет при текущем уровне нагрузки
+    pub fn expected_power(&self) -> Watts {
+        self.power_rating * (self.load_percent as f64 / 100.0)
+    }
+
+    /// Возвращает предельную допустимую мощность
+    pub fn power_limit(&self) -> Option<Watts> {
+        self.power_limit
+    }
+
+    /// Устанавливает предельную допустимую мощность
+    pub fn set_power_limit(&mut self, limit: Watts) {
+        self.power_limit = Some(limit);
+    }
+
+    /// Снимает ограничение мощности
+    pub fn clear_power_limit(&mut self) {
+        self.power_limit = None;
+    }
+
+    /// Проверяет, превышает ли мощность заданный предел
+    pub fn exceeds_power_limit(&self, power: Watts) -> bool {
+        self.power_limit.is_some_and(|limit| power > limit)
+    }
 }
 
 impl Reporter for SmartSocket {
     fn report(&self) -> String {
-        format!(
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        let mut report = format!(
             "Smart Socket: {} | Power: {} (Rated: {})",
             if self.is_active { "ACTIVE" } else { "INACTIVE" },
-            self.current_power,
-            self.power_rating
-        )
+            options.power(self.current_power),
+            options.power(self.power_rating)
+        );
+
+        if self.load_percent < 100 {
+            report.push_str(&format!(" | Load: {}%", self.load_percent));
+        }
+
+        if let Some(limit) = self.power_limit {
+            report.push_str(&format!(" | Limit: {}", options.power(limit)));
+        }
+
+        report
     }
 }
 
@@ -105,6 +162,40 @@ mod tests {
         assert!(socket.report().contains("1500.0W"));
     }
 
+    #[test]
+    fn dimmer_load() {
+        let mut socket = SmartSocket::new(2000.0);
+        assert_eq!(socket.load_percent(), 100);
+
+        socket.set_load(40);
+        assert_eq!(socket.current_power(), Watts::new(0.0)); // выключена
+
+        socket.turn_on();
+        assert_eq!(socket.current_power(), Watts::new(800.0));
+        assert!(socket.report().contains("Load: 40%"));
+
+        socket.set_load(150);
+        assert_eq!(socket.load_percent(), 100);
+        assert_eq!(socket.current_power(), Watts::new(2000.0));
+        assert!(!socket.report().contains("Load"));
+    }
+
+    #[test]
+    fn power_limit() {
+        let mut socket = SmartSocket::new(2000.0);
+        assert!(!socket.exceeds_power_limit(Watts::new(5000.0)));
+
+        socket.set_power_limit(Watts::new(1500.0));
+        assert!(socket.exceeds_power_limit(socket.expected_power()));
+        assert!(socket.report().contains("Limit: 1500.0W"));
+
+        socket.set_load(50);
+        assert!(!socket.exceeds_power_limit(socket.expected_power()));
+
+        socket.clear_power_limit();
+        assert_eq!(socket.power_limit(), None);
+    }
+
     #[test]
     fn set_current_power() {
         let mut socket = SmartSocket::new(1500.0);
@@ -1,6 +1,7 @@
 //! Умный термометр
 
 use super::Reporter;
+use crate::report::ReportOptions;
 use crate::units::Celsius;
 use std::fmt;
 
@@ -30,7 +31,14 @@ impl SmartTherm {
 
 impl Reporter for SmartTherm {
     fn report(&self) -> String {
-        format!("Smart Thermometer: {}", self.temperature)
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        format!(
+            "Smart Thermometer: {}",
+            options.temperature(self.temperature)
+        )
     }
 }
 
@@ -0,0 +1,70 @@
+//! Умный запорный клапан воды
+
+use super::Reporter;
+use crate::report::ReportOptions;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmartValve {
+    open: bool, // Клапан открыт (вода подается)
+}
+
+impl SmartValve {
+    /// Создает открытый клапан
+    pub fn new() -> Self {
+        Self { open: true }
+    }
+
+    /// Открывает клапан
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Перекрывает воду
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Клапан открыт
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+impl Default for SmartValve {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reporter for SmartValve {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, _options: &ReportOptions) -> String {
+        format!("Smart Valve: {}", if self.open { "OPEN" } else { "CLOSED" })
+    }
+}
+
+impl fmt::Display for SmartValve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_close() {
+        let mut valve = SmartValve::new();
+        assert!(valve.is_open());
+        valve.close();
+        assert!(!valve.is_open());
+        assert_eq!(valve.report(), "Smart Valve: CLOSED");
+        valve.open();
+        assert_eq!(valve.to_string(), "Smart Valve: OPEN");
+    }
+}
@@ -0,0 +1,373 @@
+//! Сравнение и объединение домов
+//!
+//! `SmartHouse::diff` показывает, чем один дом отличается от другого:
+//! добавленные и удаленные комнаты, элементы и группы, изменившиеся
+//! элементы. Подходит для аудита изменений между двумя снимками.
+//! `SmartHouse::merge` объединяет дома - например, общий шаблон и
+//! настройки конкретного объекта; совпадающие ключи разрешает
+//! `ConflictPolicy`.
+
+use crate::house::{SmartHouse, SmartHouseError, SmartHouseResult};
+use crate::report::ItemReport;
+use crate::room::Room;
+use serde::Serialize;
+use std::fmt;
+
+/// Что делать с элементом или группой, которые есть в обоих домах
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Оставить элемент исходного дома
+    #[default]
+    KeepExisting,
+    /// Заменить элементом добавляемого дома
+    Overwrite,
+    /// Вернуть ошибку, не меняя дом
+    Fail,
+}
+
+/// Изменение элемента комнаты
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemChange {
+    pub room: String,
+    pub key: String,
+    /// Что изменилось: `kind`, `state` или `metadata`
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+impl fmt::Display for ItemChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "~ {}/{} {}: {} -> {}",
+            self.room, self.key, self.field, self.before, self.after
+        )
+    }
+}
+
+/// Различия двух домов (что нужно изменить в первом, чтобы получить второй)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HouseDiff {
+    pub rooms_added: Vec<String>,
+    pub rooms_removed: Vec<String>,
+    /// Элементы (комната, ключ), появившиеся в существующих комнатах
+    pub items_added: Vec<(String, String)>,
+    /// Элементы (комната, ключ), удаленные из оставшихся комнат
+    pub items_removed: Vec<(String, String)>,
+    pub items_changed: Vec<ItemChange>,
+    pub groups_added: Vec<String>,
+    pub groups_removed: Vec<String>,
+    /// Группы с изменившимся составом
+    pub groups_changed: Vec<String>,
+}
+
+impl HouseDiff {
+    /// Дома не различаются
+    pub fn is_empty(&self) -> bool {
+        self.rooms_added.is_empty()
+            && self.rooms_removed.is_empty()
+            && self.items_added.is_empty()
+            && self.items_removed.is_empty()
+            && self.items_changed.is_empty()
+            && self.groups_added.is_empty()
+            && self.groups_removed.is_empty()
+            && self.groups_changed.is_empty()
+    }
+}
+
+impl fmt::Display for HouseDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        lines.extend(
+            self.rooms_added
+                .iter()
+                .map(|room| format!("+ room {}", room)),
+        );
+        lines.extend(
+            self.rooms_removed
+                .iter()
+                .map(|room| format!("- room {}", room)),
+        );
+        lines.extend(
+            self.items_added
+                .iter()
+                .map(|(room, key)| format!("+ {}/{}", room, key)),
+        );
+        lines.extend(
+            self.items_removed
+                .iter()
+                .map(|(room, key)| format!("- {}/{}", room, key)),
+        );
+        lines.extend(self.items_changed.iter().map(ToString::to_string));
+        lines.extend(
+            self.groups_added
+                .iter()
+                .map(|group| format!("+ group {}", group)),
+        );
+        lines.extend(
+            self.groups_removed
+                .iter()
+                .map(|group| format!("- group {}", group)),
+        );
+        lines.extend(
+            self.groups_changed
+                .iter()
+                .map(|group| format!("~ group {}", group)),
+        );
+
+        if lines.is_empty() {
+            return write!(f, "No changes");
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl SmartHouse {
+    /// Различия между этим домом и `other`
+    ///
+    /// Элементы сравниваются по типу, строке отчета и метаданным, группы -
+    /// по составу. Время снимка не учитывается.
+    pub fn diff(&self, other: &SmartHouse) -> HouseDiff {
+        let mut diff = HouseDiff::default();
+
+        for key in self.rooms_keys() {
+            match (self.room(&key), other.room(&key)) {
+                (Some(before), Some(after)) => diff_rooms(&key, before, after, &mut diff),
+                _ => diff.rooms_removed.push(key),
+            }
+        }
+        diff.rooms_added = other
+            .rooms_keys()
+            .into_iter()
+            .filter(|key| self.room(key).is_none())
+            .collect();
+
+        for name in self.groups_keys() {
+            match (self.group(&name), other.group(&name)) {
+                (Some(before), Some(after)) if before != after => diff.groups_changed.push(name),
+                (Some(_), Some(_)) => {}
+                _ => diff.groups_removed.push(name),
+            }
+        }
+        diff.groups_added = other
+            .groups_keys()
+            .into_iter()
+            .filter(|name| self.group(name).is_none())
+            .collect();
+
+        diff
+    }
+
+    /// Добавляет в дом комнаты, элементы и группы дома `other`
+    ///
+    /// Новые комнаты переносятся целиком, элементы существующих комнат -
+    /// вместе с метаданными. Элементы и группы с совпадающими ключами
+    /// разрешаются по `policy`; при `ConflictPolicy::Fail` дом не меняется.
+    pub fn merge(&mut self, mut other: SmartHouse, policy: ConflictPolicy) -> SmartHouseResult<()> {
+        if policy == ConflictPolicy::Fail {
+            self.check_conflicts(&other)?;
+        }
+
+        for key in other.rooms_keys() {
+            let Some(mut incoming) = other.remove_room(&key) else {
+                continue;
+            };
+            let Some(room) = self.room_mut(&key) else {
+                self.add_room(&key, incoming);
+                continue;
+            };
+            for item_key in incoming.keys() {
+                if room.contains_key(&item_key) && policy != ConflictPolicy::Overwrite {
+                    continue;
+                }
+                if let Some((item, metadata)) = incoming.take_item(&item_key) {
+                    room.put_item(&item_key, item, metadata);
+                }
+            }
+        }
+
+        for name in other.groups_keys() {
+            if self.group(&name).is_some() && policy != ConflictPolicy::Overwrite {
+                continue;
+            }
+            if let Some(group) = other.remove_group(&name) {
+                self.add_group(&name, group);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Первый конфликт ключей с домом `other`
+    fn check_conflicts(&self, other: &SmartHouse) -> SmartHouseResult<()> {
+        for key in other.rooms_keys() {
+            if let (Some(room), Some(incoming)) = (self.room(&key), other.room(&key))
+                && let Some(item_key) = incoming.keys().into_iter().find(|k| room.contains_key(k))
+            {
+                return Err(SmartHouseError::KeyExists(key, item_key));
+            }
+        }
+        match other
+            .groups_keys()
+            .into_iter()
+            .find(|name| self.group(name).is_some())
+        {
+            Some(name) => Err(SmartHouseError::GroupExists(name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Сравнивает элементы комнаты `room`, которая есть в обоих домах
+fn diff_rooms(room: &str, before: &Room, after: &Room, diff: &mut HouseDiff) {
+    let before_items = before.item_reports();
+    let after_items = after.item_reports();
+    let find = |items: &[ItemReport], key: &str| items.iter().find(|item| item.key == key).cloned();
+
+    for old in &before_items {
+        let Some(new) = find(&after_items, &old.key) else {
+            diff.items_removed.push((room.to_string(), old.key.clone()));
+            continue;
+        };
+        let mut change = |field, before: String, after: String| {
+            if before != after {
+                diff.items_changed.push(ItemChange {
+                    room: room.to_string(),
+                    key: old.key.clone(),
+                    field,
+                    before,
+                    after,
+                });
+            }
+        };
+        change("kind", kind(old), kind(&new));
+        change("state", old.state.clone(), new.state);
+        change(
+            "metadata",
+            metadata_json(before, &old.key),
+            metadata_json(after, &old.key),
+        );
+    }
+
+    for new in &after_items {
+        if find(&before_items, &new.key).is_none() {
+            diff.items_added.push((room.to_string(), new.key.clone()));
+        }
+    }
+}
+
+/// Тип элемента с пометкой контроллера
+fn kind(item: &ItemReport) -> String {
+    if item.controller {
+        format!("{} controller", item.kind)
+    } else {
+        item.kind.to_string()
+    }
+}
+
+fn metadata_json(room: &Room, key: &str) -> String {
+    serde_json::to_string(&room.metadata(key)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{Device, SmartSocket};
+    use crate::group::DeviceGroup;
+    use crate::metadata::DeviceMetadata;
+
+    fn template() -> SmartHouse {
+        let mut house = crate::house! {
+            "kitchen" => { "kettle" => socket(2000.0), "therm" => therm(21.0) },
+            "hall" => { "lamp" => socket(60.0) },
+        };
+        house.add_group("lights", DeviceGroup::new().with_member("hall", "lamp"));
+        house
+    }
+
+    fn site() -> SmartHouse {
+        let mut house = crate::house! {
+            "kitchen" => { "kettle" => socket(1800.0), "fridge" => socket(150.0) },
+            "garage" => { "heater" => socket(1000.0) },
+        };
+        house.add_group("lights", DeviceGroup::new().with_member("garage", "heater"));
+        house
+    }
+
+    #[test]
+    fn diff_lists_changes() {
+        let before = template();
+        let mut after = template();
+        after.remove_room("hall");
+        after.add_room("garage", Room::new());
+        let kitchen = after.room_mut("kitchen").unwrap();
+        kitchen.remove_device("therm");
+        kitchen.add_device("fridge", Device::Socket(SmartSocket::new(150.0)));
+        kitchen.set_metadata("kettle", DeviceMetadata::new().with_priority(90));
+        after.add_group("lights", DeviceGroup::new());
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.rooms_added, vec!["garage"]);
+        assert_eq!(diff.rooms_removed, vec!["hall"]);
+        assert_eq!(
+            diff.items_added,
+            vec![("kitchen".to_string(), "fridge".to_string())]
+        );
+        assert_eq!(
+            diff.items_removed,
+            vec![("kitchen".to_string(), "therm".to_string())]
+        );
+        assert_eq!(diff.items_changed.len(), 1);
+        assert_eq!(diff.items_changed[0].field, "metadata");
+        assert_eq!(diff.groups_changed, vec!["lights"]);
+        assert!(diff.to_string().contains("- room hall"));
+
+        assert!(before.diff(&template()).is_empty());
+        assert_eq!(before.diff(&template()).to_string(), "No changes");
+    }
+
+    #[test]
+    fn merge_keeps_existing() {
+        let mut house = template();
+        house.merge(site(), ConflictPolicy::KeepExisting).unwrap();
+
+        assert_eq!(house.rooms_keys(), vec!["kitchen", "hall", "garage"]);
+        assert_eq!(
+            house.room("kitchen").unwrap().keys(),
+            vec!["kettle", "therm", "fridge"]
+        );
+        assert!(matches!(
+            house.device("kitchen", "kettle"),
+            Ok(Device::Socket(s)) if s.power_rating().value() == 2000.0
+        ));
+        assert!(house.group("lights").unwrap().contains("hall", "lamp"));
+    }
+
+    #[test]
+    fn merge_overwrites() {
+        let mut house = template();
+        house.merge(site(), ConflictPolicy::Overwrite).unwrap();
+
+        assert!(matches!(
+            house.device("kitchen", "kettle"),
+            Ok(Device::Socket(s)) if s.power_rating().value() == 1800.0
+        ));
+        assert!(house.group("lights").unwrap().contains("garage", "heater"));
+    }
+
+    #[test]
+    fn merge_fails_without_changes() {
+        let mut house = template();
+        let error = house.merge(site(), ConflictPolicy::Fail).unwrap_err();
+        assert!(
+            matches!(error, SmartHouseError::KeyExists(room, key) if room == "kitchen" && key == "kettle")
+        );
+        assert!(house.diff(&template()).is_empty());
+
+        let mut other = SmartHouse::default();
+        other.add_group("lights", DeviceGroup::new());
+        let error = house.merge(other, ConflictPolicy::Fail).unwrap_err();
+        assert!(matches!(error, SmartHouseError::GroupExists(_)));
+    }
+}
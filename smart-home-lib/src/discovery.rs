@@ -0,0 +1,165 @@
+//! Результаты обнаружения устройств в сети
+//!
+//! `DiscoveredDevice` описывает найденное устройство: транспорт, адрес
+//! и параметры, нужные для создания контроллера. Подключение к дому
+//! выполняется одним вызовом `SmartHouse::adopt`.
+
+use crate::controllers::{ControllerConfig, DeviceController, SocketController, ThermController};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Таймаут TCP операций для подключенных розеток
+pub const DEFAULT_SOCKET_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Максимальный возраст данных для подключенных термометров
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(5);
+
+/// Тип обнаруженного устройства
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiscoveredKind {
+    /// Розетка, управляемая по TCP
+    Socket { power_rating: f64 },
+    /// Термометр, присылающий данные по UDP
+    Therm { initial_temp: f64 },
+}
+
+/// Устройство, найденное при обнаружении
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveredDevice {
+    /// Тип устройства и его параметры
+    #[serde(flatten)]
+    pub kind: DiscoveredKind,
+    /// Адрес розетки (TCP) или адрес, на который термометр шлет данные (UDP)
+    pub address: SocketAddr,
+    /// Идентификатор, сообщенный устройством
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+}
+
+impl DiscoveredDevice {
+    /// Обнаруженная розетка
+    pub fn socket(address: SocketAddr, power_rating: f64) -> Self {
+        Self {
+            kind: DiscoveredKind::Socket { power_rating },
+            address,
+            device_id: None,
+        }
+    }
+
+    /// Обнаруженный термометр
+    pub fn therm(address: SocketAddr, initial_temp: f64) -> Self {
+        Self {
+            kind: DiscoveredKind::Therm { initial_temp },
+            address,
+            device_id: None,
+        }
+    }
+
+    /// Builder: идентификатор устройства
+    pub fn with_device_id(mut self, device_id: &str) -> Self {
+        self.device_id = Some(device_id.to_string());
+        self
+    }
+
+    /// Создает контроллер нужного типа и запускает его фоновые задачи
+    pub fn into_controller(self) -> DeviceController {
+        self.into_controller_with(&ControllerConfig::default())
+    }
+
+    /// Создает контроллер с заданными рабочими параметрами и запускает термометр
+    pub fn into_controller_with(self, settings: &ControllerConfig) -> DeviceController {
+        let address = self.address;
+        let mut controller = self.into_stopped_controller(settings);
+        if let DeviceController::Therm(therm) = &mut controller
+            && let Err(e) = therm.start()
+        {
+            // Остановленный контроллер запустит `SmartHouse::start_controllers`
+            eprintln!("⚠️ Термометр {}: {}", address, e);
+        }
+        controller
+    }
+
+    /// Создает контроллер, не запуская его фоновые задачи
+    ///
+    /// Термометр не занимает UDP порт до `SmartHouse::start_controllers`.
+    pub fn into_stopped_controller(self, settings: &ControllerConfig) -> DeviceController {
+        match self.kind {
+            DiscoveredKind::Socket { power_rating } => {
+                let controller =
+                    SocketController::new(self.address, power_rating, settings.timeout())
+                        .with_keepalive_interval(settings.keepalive_interval())
+                        .with_resolve_policy(settings.resolve.unwrap_or_default());
+                settings
+                    .fallbacks
+                    .iter()
+                    .fold(controller, |controller, address| {
+                        controller.with_fallback(address.clone())
+                    })
+                    .into()
+            }
+            DiscoveredKind::Therm { initial_temp } => ThermController::new(
+                initial_temp,
+                &self.address.to_string(),
+                settings.max_age(),
+            )
+            .with_warning_age(settings.warning_age())
+            .with_restart_policy(settings.restart_policy())
+            .with_ip_stack(settings.ip_stack.unwrap_or_default())
+            .into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_format() {
+        let device = DiscoveredDevice::socket("127.0.0.1:3030".parse().unwrap(), 1500.0)
+            .with_device_id("kettle_001");
+
+        let json = serde_json::to_string(&device).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"socket","power_rating":1500.0,"address":"127.0.0.1:3030","device_id":"kettle_001"}"#
+        );
+
+        let parsed: DiscoveredDevice = serde_json::from_str(
+            r#"{"kind":"therm","initial_temp":21.0,"address":"127.0.0.1:4001"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            DiscoveredDevice::therm("127.0.0.1:4001".parse().unwrap(), 21.0)
+        );
+    }
+
+    #[test]
+    fn controller_type_from_kind() {
+        let socket = DiscoveredDevice::socket("127.0.0.1:3030".parse().unwrap(), 1500.0);
+        assert!(matches!(
+            socket.into_controller(),
+            DeviceController::Socket(_)
+        ));
+
+        let therm = DiscoveredDevice::therm("127.0.0.1:0".parse().unwrap(), 21.0);
+        assert!(matches!(
+            therm.into_controller(),
+            DeviceController::Therm(_)
+        ));
+    }
+
+    #[test]
+    fn controller_settings() {
+        let socket = DiscoveredDevice::socket("127.0.0.1:3030".parse().unwrap(), 1500.0);
+        let settings = ControllerConfig::new().with_timeout(Duration::from_millis(250));
+
+        let DeviceController::Socket(controller) = socket.into_controller_with(&settings) else {
+            panic!("expected socket controller");
+        };
+        assert_eq!(controller.timeout(), Duration::from_millis(250));
+    }
+}
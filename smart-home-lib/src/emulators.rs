@@ -1,9 +1,43 @@
 //! Эмуляторы устройств для тестирования
 
+pub mod admin;
+#[cfg(feature = "blinds")]
+pub mod blinds_emulator;
+#[cfg(feature = "camera")]
+pub mod camera_emulator;
+#[cfg(feature = "coap")]
+pub mod coap_emulator;
+pub mod emulator;
+pub mod fault;
+pub mod fleet;
+#[cfg(feature = "leak")]
+pub mod leak_emulator;
+pub mod loadtest;
+pub mod physics;
 pub mod scenario;
 pub mod socket_emulator;
 pub mod therm_emulator;
+#[cfg(feature = "valve")]
+pub mod valve_emulator;
+pub mod world;
 
-pub use scenario::EmulationScenario;
-pub use socket_emulator::SocketEmulator;
+pub use admin::{AdminServer, AdminTarget};
+#[cfg(feature = "blinds")]
+pub use blinds_emulator::BlindsEmulator;
+#[cfg(feature = "camera")]
+pub use camera_emulator::CameraEmulator;
+#[cfg(feature = "coap")]
+pub use coap_emulator::CoapEmulator;
+pub use emulator::{Emulator, EmulatorFuture};
+pub use fault::{Fault, FaultInjector};
+pub use fleet::{Fleet, FleetSpec};
+#[cfg(feature = "leak")]
+pub use leak_emulator::LeakEmulator;
+pub use loadtest::{LoadTestConfig, LoadTestReport};
+pub use physics::{PhysicsModel, PowerProbe};
+pub use scenario::{EmulationScenario, ScenarioSchedule};
+pub use socket_emulator::{ShutdownReport, SocketEmulator};
 pub use therm_emulator::ThermEmulator;
+#[cfg(feature = "valve")]
+pub use valve_emulator::ValveEmulator;
+pub use world::{World, WorldSpec};
@@ -0,0 +1,357 @@
+//! Административный HTTP интерфейс эмуляторов
+//!
+//! Позволяет тестам и демонстрациям менять поведение запущенного эмулятора
+//! без перезапуска процесса:
+//!
+//! - `GET /state` - текущее состояние эмулятора
+//! - `POST /scenario` с телом `{"scenario": "fire"}` - смена сценария
+//! - `POST /inject-fault` с телом `{"fault": "latency", "ms": 500, "duration_ms": 10000}`
+//! - `DELETE /inject-fault` - снятие неисправности
+//!
+//! Сервер работает в отдельном потоке и не требует tokio runtime.
+
+use super::fault::{Fault, FaultInjector};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Максимальный размер заголовков и тела запроса
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+/// Таймаут чтения запроса
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Эмулятор, управляемый через административный интерфейс
+pub trait AdminTarget: Send + Sync {
+    /// Состояние эмулятора
+    fn state(&self) -> Value;
+
+    /// Переключает сценарий эмуляции
+    fn set_scenario(&self, scenario: &str) -> Result<(), String>;
+
+    /// Переключатель неисправностей эмулятора
+    fn faults(&self) -> &FaultInjector;
+
+    /// Поддерживает ли эмулятор данную неисправность
+    fn supports_fault(&self, _fault: &Fault) -> bool {
+        true
+    }
+}
+
+/// Тело запроса `/scenario`
+#[derive(Deserialize)]
+struct ScenarioRequest {
+    scenario: String,
+}
+
+/// Тело запроса `/inject-fault`
+#[derive(Deserialize)]
+struct FaultRequest {
+    #[serde(flatten)]
+    fault: Fault,
+    /// Через сколько миллисекунд снять неисправность
+    #[serde(default)]
+    duration_ms: Option<u64>,
+}
+
+/// Запущенный административный HTTP сервер; останавливается при Drop
+pub struct AdminServer {
+    addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl AdminServer {
+    /// Запускает сервер для эмулятора
+    pub fn start(bind_address: &str, target: Arc<dyn AdminTarget>) -> io::Result<Self> {
+        let listener = TcpListener::bind(bind_address)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        println!("[Admin] Listening on http://{}", addr);
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = Arc::clone(&running);
+        let handle = thread::spawn(move || {
+            while thread_running.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if let Err(e) = serve_connection(stream, target.as_ref()) {
+                            eprintln!("[Admin] Request error: {}", e);
+                        }
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                    Err(e) => {
+                        eprintln!("[Admin] Accept error: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    /// Адрес сервера
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Останавливает сервер
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for AdminServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Обрабатывает запрос и возвращает HTTP статус и JSON ответ
+pub fn handle_request(
+    target: &dyn AdminTarget,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> (u16, Value) {
+    let path = path.split('?').next().unwrap_or(path);
+
+    match (method, path) {
+        ("GET", "/state") => (200, full_state(target)),
+        ("POST", "/scenario") => {
+            let request: ScenarioRequest = match serde_json::from_str(body) {
+                Ok(request) => request,
+                Err(e) => return bad_request(e),
+            };
+            match target.set_scenario(&request.scenario) {
+                Ok(()) => (200, json!({ "scenario": request.scenario })),
+                Err(e) => bad_request(e),
+            }
+        }
+        ("POST", "/inject-fault") => {
+            let request: FaultRequest = match serde_json::from_str(body) {
+                Ok(request) => request,
+                Err(e) => return bad_request(e),
+            };
+            if !target.supports_fault(&request.fault) {
+                return bad_request(format!("Fault is not supported: {}", request.fault));
+            }
+            target.faults().inject(
+                request.fault.clone(),
+                request.duration_ms.map(Duration::from_millis),
+            );
+            (200, json!({ "fault": request.fault }))
+        }
+        ("DELETE", "/inject-fault") => {
+            target.faults().clear();
+            (200, json!({ "fault": null }))
+        }
+        (_, "/state" | "/scenario" | "/inject-fault") => {
+            (405, json!({ "error": "Method not allowed" }))
+        }
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+/// Состояние эмулятора вместе с активной неисправностью
+pub(super) fn full_state(target: &dyn AdminTarget) -> Value {
+    let mut state = target.state();
+    if let Value::Object(map) = &mut state {
+        map.insert("fault".to_string(), json!(target.faults().active()));
+    }
+    state
+}
+
+fn bad_request(error: impl ToString) -> (u16, Value) {
+    (400, json!({ "error": error.to_string() }))
+}
+
+/// Читает один HTTP/1.1 запрос и отвечает, соединение закрывается
+fn serve_connection(stream: TcpStream, target: &dyn AdminTarget) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    (&mut reader)
+        .take(MAX_REQUEST_SIZE as u64)
+        .read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return write_response(stream, 400, &json!({ "error": "Bad request" }));
+    };
+
+    let mut content_length = 0;
+    let mut header_size = request_line.len();
+    loop {
+        let mut line = String::new();
+        if (&mut reader)
+            .take(MAX_REQUEST_SIZE as u64)
+            .read_line(&mut line)?
+            == 0
+            || line == "\r\n"
+            || line == "\n"
+        {
+            break;
+        }
+        header_size += line.len();
+        if header_size > MAX_REQUEST_SIZE {
+            return write_response(stream, 413, &json!({ "error": "Request too large" }));
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    if content_length > MAX_REQUEST_SIZE {
+        return write_response(stream, 413, &json!({ "error": "Request too large" }));
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body);
+
+    let (status, response) = handle_request(target, method, path, &body);
+    write_response(stream, status, &response)
+}
+
+fn write_response(mut stream: TcpStream, status: u16, body: &Value) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Error",
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::system_clock;
+    use std::sync::Mutex;
+
+    struct FakeEmulator {
+        scenario: Mutex<String>,
+        faults: FaultInjector,
+    }
+
+    impl FakeEmulator {
+        fn new() -> Self {
+            Self {
+                scenario: Mutex::new("normal".to_string()),
+                faults: FaultInjector::new(system_clock()),
+            }
+        }
+    }
+
+    impl AdminTarget for FakeEmulator {
+        fn state(&self) -> Value {
+            json!({ "scenario": *self.scenario.lock().unwrap() })
+        }
+
+        fn set_scenario(&self, scenario: &str) -> Result<(), String> {
+            if scenario == "unknown" {
+                return Err("Unknown scenario".to_string());
+            }
+            *self.scenario.lock().unwrap() = scenario.to_string();
+            Ok(())
+        }
+
+        fn faults(&self) -> &FaultInjector {
+            &self.faults
+        }
+
+        fn supports_fault(&self, fault: &Fault) -> bool {
+            *fault != Fault::Corrupt
+        }
+    }
+
+    #[test]
+    fn routes() {
+        let target = FakeEmulator::new();
+
+        let (status, state) = handle_request(&target, "GET", "/state", "");
+        assert_eq!(status, 200);
+        assert_eq!(state, json!({ "scenario": "normal", "fault": null }));
+
+        let (status, _) = handle_request(&target, "POST", "/scenario", r#"{"scenario":"fire"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(*target.scenario.lock().unwrap(), "fire");
+        let (status, _) = handle_request(&target, "POST", "/scenario", r#"{"scenario":"unknown"}"#);
+        assert_eq!(status, 400);
+
+        let (status, _) = handle_request(
+            &target,
+            "POST",
+            "/inject-fault",
+            r#"{"fault":"latency","ms":100,"duration_ms":60000}"#,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(target.faults.active(), Some(Fault::Latency { ms: 100 }));
+
+        let (status, _) =
+            handle_request(&target, "POST", "/inject-fault", r#"{"fault":"corrupt"}"#);
+        assert_eq!(status, 400);
+
+        let (status, _) = handle_request(&target, "DELETE", "/inject-fault", "");
+        assert_eq!(status, 200);
+        assert_eq!(target.faults.active(), None);
+
+        assert_eq!(handle_request(&target, "PUT", "/state", "").0, 405);
+        assert_eq!(handle_request(&target, "GET", "/missing", "").0, 404);
+        assert_eq!(handle_request(&target, "POST", "/scenario", "{").0, 400);
+    }
+
+    #[test]
+    #[ignore = "integration test with TCP networking"]
+    fn http_roundtrip() {
+        let target = Arc::new(FakeEmulator::new());
+        let mut server = AdminServer::start("127.0.0.1:0", target.clone()).unwrap();
+
+        let body = r#"{"scenario":"freeze"}"#;
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        write!(
+            stream,
+            "POST /scenario HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(r#"{"scenario":"freeze"}"#));
+        assert_eq!(*target.scenario.lock().unwrap(), "freeze");
+
+        server.stop();
+    }
+}
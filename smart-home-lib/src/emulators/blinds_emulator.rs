@@ -0,0 +1,374 @@
+//! Async эмулятор жалюзи (TCP)
+//!
+//! Жалюзи движутся с постоянной скоростью: полный ход от закрытых до
+//! открытых занимает `travel_time`. Положение вычисляется по часам
+//! эмулятора в момент запроса, поэтому с `MockClock` ход проверяется без
+//! реального ожидания.
+
+use super::socket_emulator::Listener;
+use crate::clock::{SharedClock, system_clock};
+use crate::controllers::HealthStatus;
+use crate::protocol::blinds_protocol::{
+    BlindsCommand, BlindsData, BlindsResponse, receive_blinds_command, send_blinds_response,
+};
+use crate::protocol::socket_protocol::DEFAULT_MAX_MESSAGE_SIZE;
+use crate::protocol::transport::{Connection, InMemory};
+use crate::traits::Lifecycle;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// Время полного хода по умолчанию
+pub const DEFAULT_TRAVEL_TIME: Duration = Duration::from_secs(20);
+
+/// Ход жалюзи: откуда, куда и когда начался
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Travel {
+    from: f64,
+    target: f64,
+    started_ms: u64,
+}
+
+impl Travel {
+    fn still(position: f64, now_ms: u64) -> Self {
+        Self {
+            from: position,
+            target: position,
+            started_ms: now_ms,
+        }
+    }
+
+    /// Положение в момент `now_ms` при полном ходе за `travel_time`
+    fn position_at(&self, now_ms: u64, travel_time: Duration) -> f64 {
+        let distance = self.target - self.from;
+        let travel_ms = travel_time.as_millis().max(1) as f64;
+        let moved = now_ms.saturating_sub(self.started_ms) as f64 / travel_ms * 100.0;
+        if moved >= distance.abs() {
+            self.target
+        } else {
+            self.from + moved.copysign(distance)
+        }
+    }
+}
+
+/// Общее состояние жалюзи для всех клиентов
+#[derive(Debug)]
+struct BlindsState {
+    travel: Travel,
+    travel_time: Duration,
+    device_id: Option<String>,
+    clock: SharedClock,
+}
+
+impl BlindsState {
+    fn position(&self) -> f64 {
+        self.travel
+            .position_at(self.clock.now_ms(), self.travel_time)
+    }
+
+    /// Начинает ход к `target` из текущего положения
+    fn move_to(&mut self, target: f64) {
+        let now = self.clock.now_ms();
+        self.travel = Travel {
+            from: self.travel.position_at(now, self.travel_time),
+            target: target.clamp(0.0, 100.0),
+            started_ms: now,
+        };
+    }
+
+    fn execute(&mut self, command: &BlindsCommand) -> BlindsData {
+        match command {
+            BlindsCommand::Open => self.move_to(100.0),
+            BlindsCommand::Close => self.move_to(0.0),
+            BlindsCommand::SetPosition { percent } => self.move_to(*percent as f64),
+            BlindsCommand::Stop => {
+                self.travel = Travel::still(self.position(), self.clock.now_ms());
+            }
+            BlindsCommand::Status => {}
+        }
+        self.data()
+    }
+
+    fn data(&self) -> BlindsData {
+        let position = self.position();
+        BlindsData {
+            position: position.round() as u8,
+            target: self.travel.target.round() as u8,
+            moving: position != self.travel.target,
+            device_id: self.device_id.clone(),
+        }
+    }
+}
+
+/// Async эмулятор жалюзи
+pub struct BlindsEmulator {
+    /// Адрес для прослушивания TCP соединений
+    bind_address: String,
+    /// Положение и ход жалюзи
+    state: Arc<Mutex<BlindsState>>,
+    /// Адрес на котором запущен сервер (после start)
+    bound_addr: Option<SocketAddr>,
+    /// Флаг работы сервера
+    running: Arc<AtomicBool>,
+    /// Handle главной задачи сервера
+    server_handle: Option<JoinHandle<()>>,
+    /// Канал для graceful shutdown
+    shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl BlindsEmulator {
+    /// Создает эмулятор закрытых жалюзи
+    pub fn new(bind_address: &str) -> Self {
+        let clock = system_clock();
+        Self {
+            bind_address: bind_address.to_string(),
+            state: Arc::new(Mutex::new(BlindsState {
+                travel: Travel::still(0.0, clock.now_ms()),
+                travel_time: DEFAULT_TRAVEL_TIME,
+                device_id: None,
+                clock,
+            })),
+            bound_addr: None,
+            running: Arc::new(AtomicBool::new(false)),
+            server_handle: None,
+            shutdown_tx: None,
+        }
+    }
+
+    /// Builder: устанавливает ID устройства
+    pub fn with_device_id(self, device_id: &str) -> Self {
+        self.lock().device_id = Some(device_id.to_string());
+        self
+    }
+
+    /// Builder: время полного хода от закрытых до открытых
+    pub fn with_travel_time(self, travel_time: Duration) -> Self {
+        self.lock().travel_time = travel_time;
+        self
+    }
+
+    /// Builder: начальное положение, %
+    pub fn with_position(self, position: f64) -> Self {
+        {
+            let mut state = self.lock();
+            state.travel = Travel::still(position.clamp(0.0, 100.0), state.clock.now_ms());
+        }
+        self
+    }
+
+    /// Builder: источник времени для движения
+    pub fn with_clock(self, clock: SharedClock) -> Self {
+        {
+            let mut state = self.lock();
+            state.travel = Travel::still(state.position(), clock.now_ms());
+            state.clock = clock;
+        }
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BlindsState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Текущее положение, %
+    pub fn position(&self) -> f64 {
+        self.lock().position()
+    }
+
+    /// Жалюзи в движении
+    pub fn is_moving(&self) -> bool {
+        self.lock().data().moving
+    }
+
+    /// Возвращает локальный адрес сервера (только после start)
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.bound_addr.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "Server not started yet - call start() first",
+            )
+        })
+    }
+
+    /// Проверяет, запущен ли эмулятор
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Запускает async TCP сервер
+    pub async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "Emulator already started",
+            ));
+        }
+
+        let listener = TcpListener::bind(&self.bind_address).await?;
+        self.serve(Listener::Tcp(listener))
+    }
+
+    /// Запускает эмулятор на транспорте в памяти вместо TCP
+    pub async fn start_in_memory(&mut self, transport: &InMemory) -> std::io::Result<()> {
+        if self.is_running() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "Emulator already started",
+            ));
+        }
+
+        let addr = self
+            .bind_address
+            .parse()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        self.serve(Listener::Memory(transport.listen(addr)))
+    }
+
+    fn serve(&mut self, mut listener: Listener) -> std::io::Result<()> {
+        let bound_addr = listener.local_addr()?;
+        println!("[BlindsEmulator] Bound to {}", bound_addr);
+        self.bound_addr = Some(bound_addr);
+
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
+        self.shutdown_tx = Some(shutdown_tx);
+
+        let state = Arc::clone(&self.state);
+        self.running.store(true, Ordering::Relaxed);
+
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    result = listener.accept() => {
+                        match result {
+                            Ok((stream, addr)) => {
+                                let state = Arc::clone(&state);
+                                tokio::spawn(async move {
+                                    if let Err(e) = Self::handle_client(stream, &state).await {
+                                        println!("[BlindsEmulator] Client {} error: {}", addr, e);
+                                    }
+                                });
+                            }
+                            Err(e) => {
+                                eprintln!("[BlindsEmulator] Accept error: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                    _ = &mut shutdown_rx => break,
+                }
+            }
+
+            println!("[BlindsEmulator] Server stopped");
+        });
+        self.server_handle = Some(handle);
+
+        Ok(())
+    }
+
+    /// Останавливает async сервер
+    pub async fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.server_handle.take() {
+            let _ = handle.await;
+        }
+        self.bound_addr = None;
+    }
+
+    /// Async обработка одного клиента
+    async fn handle_client(
+        mut stream: Box<dyn Connection>,
+        state: &Mutex<BlindsState>,
+    ) -> std::io::Result<()> {
+        loop {
+            let command = match receive_blinds_command(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).await
+            {
+                Ok(command) => command,
+                // Клиент закрыл соединение
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            let response = match state.lock() {
+                Ok(mut state) => BlindsResponse::Ok(state.execute(&command)),
+                Err(_) => BlindsResponse::Error {
+                    message: "State lock poisoned".to_string(),
+                },
+            };
+            send_blinds_response(&mut stream, &response).await?;
+        }
+    }
+}
+
+impl Lifecycle for BlindsEmulator {
+    async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+        BlindsEmulator::start(self).await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        BlindsEmulator::stop(self).await;
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self.is_running(),
+            ..HealthStatus::default()
+        }
+    }
+}
+
+impl Drop for BlindsEmulator {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn travel_simulation() {
+        let clock = Arc::new(MockClock::starting_at(1_000));
+        let emulator = BlindsEmulator::new("127.0.0.1:0")
+            .with_clock(clock.clone())
+            .with_travel_time(Duration::from_secs(10));
+
+        let mut state = emulator.lock();
+        let data = state.execute(&BlindsCommand::Open);
+        assert_eq!((data.position, data.target, data.moving), (0, 100, true));
+
+        // Половина хода за 5 секунд
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(state.data().position, 50);
+
+        // Смена направления продолжает ход из текущего положения
+        let data = state.execute(&BlindsCommand::SetPosition { percent: 30 });
+        assert_eq!((data.position, data.target), (50, 30));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(state.data().position, 40);
+
+        let data = state.execute(&BlindsCommand::Stop);
+        assert_eq!((data.position, data.target, data.moving), (40, 40, false));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(state.data().position, 40);
+
+        state.execute(&BlindsCommand::Close);
+        clock.advance(Duration::from_secs(60));
+        let data = state.execute(&BlindsCommand::Status);
+        assert_eq!((data.position, data.moving), (0, false));
+    }
+}
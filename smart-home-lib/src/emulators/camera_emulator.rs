@@ -0,0 +1,469 @@
+//! Async эмулятор камеры (TCP)
+//!
+//! На каждый запрос снимка отдает настоящий JPEG с испытательной таблицей:
+//! вертикальные полосы оттенков серого, сдвигающиеся на одну полосу с
+//! каждым кадром. Таблица состоит из однотонных блоков 8x8, поэтому
+//! кодировщику достаточно DC коэффициентов, а снимок открывается любым
+//! просмотрщиком.
+
+use super::socket_emulator::Listener;
+use crate::controllers::HealthStatus;
+use crate::protocol::camera_protocol::{
+    CameraCommand, DEFAULT_SNAPSHOT_CHUNK_SIZE, JPEG_EOI, JPEG_SOI, SnapshotInfo,
+    receive_camera_command, send_snapshot,
+};
+use crate::protocol::socket_protocol::DEFAULT_MAX_MESSAGE_SIZE;
+use crate::protocol::transport::{Connection, InMemory};
+use crate::traits::Lifecycle;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// Число полос испытательной таблицы
+const PATTERN_BARS: u32 = 8;
+
+/// Async эмулятор камеры
+pub struct CameraEmulator {
+    /// Адрес для прослушивания TCP соединений
+    bind_address: String,
+    /// ID устройства в описании снимка
+    device_id: Option<String>,
+    width: u32,
+    height: u32,
+    /// Размер части снимка при передаче, байт
+    chunk_size: usize,
+    /// Номер следующего кадра
+    sequence: Arc<AtomicU64>,
+    /// Адрес на котором запущен сервер (после start)
+    bound_addr: Option<SocketAddr>,
+    /// Флаг работы сервера
+    running: Arc<AtomicBool>,
+    /// Handle главной задачи сервера
+    server_handle: Option<JoinHandle<()>>,
+    /// Канал для graceful shutdown
+    shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+/// Параметры снимков, общие для всех клиентов
+#[derive(Clone)]
+struct CameraSpec {
+    device_id: Option<String>,
+    width: u32,
+    height: u32,
+    chunk_size: usize,
+    sequence: Arc<AtomicU64>,
+}
+
+impl CameraEmulator {
+    /// Создает эмулятор камеры 320x240
+    pub fn new(bind_address: &str) -> Self {
+        Self {
+            bind_address: bind_address.to_string(),
+            device_id: None,
+            width: 320,
+            height: 240,
+            chunk_size: DEFAULT_SNAPSHOT_CHUNK_SIZE,
+            sequence: Arc::new(AtomicU64::new(0)),
+            bound_addr: None,
+            running: Arc::new(AtomicBool::new(false)),
+            server_handle: None,
+            shutdown_tx: None,
+        }
+    }
+
+    /// Builder: устанавливает ID устройства
+    pub fn with_device_id(mut self, device_id: &str) -> Self {
+        self.device_id = Some(device_id.to_string());
+        self
+    }
+
+    /// Builder: разрешение снимков (нулевые размеры заменяются на 1)
+    pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+        self.width = width.clamp(1, u16::MAX as u32);
+        self.height = height.clamp(1, u16::MAX as u32);
+        self
+    }
+
+    /// Builder: размер части снимка при передаче (по умолчанию 16 КБ)
+    pub fn with_chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    /// Отдано снимков
+    pub fn snapshots_served(&self) -> u64 {
+        self.sequence.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает локальный адрес сервера (только после start)
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.bound_addr.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "Server not started yet - call start() first",
+            )
+        })
+    }
+
+    /// Проверяет, запущен ли эмулятор
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Запускает async TCP сервер
+    pub async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "Emulator already started",
+            ));
+        }
+
+        let listener = TcpListener::bind(&self.bind_address).await?;
+        self.serve(Listener::Tcp(listener))
+    }
+
+    /// Запускает эмулятор на транспорте в памяти вместо TCP
+    pub async fn start_in_memory(&mut self, transport: &InMemory) -> std::io::Result<()> {
+        if self.is_running() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "Emulator already started",
+            ));
+        }
+
+        let addr = self
+            .bind_address
+            .parse()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        self.serve(Listener::Memory(transport.listen(addr)))
+    }
+
+    fn serve(&mut self, mut listener: Listener) -> std::io::Result<()> {
+        let bound_addr = listener.local_addr()?;
+        println!("[CameraEmulator] Bound to {}", bound_addr);
+        self.bound_addr = Some(bound_addr);
+
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
+        self.shutdown_tx = Some(shutdown_tx);
+
+        let spec = CameraSpec {
+            device_id: self.device_id.clone(),
+            width: self.width,
+            height: self.height,
+            chunk_size: self.chunk_size,
+            sequence: Arc::clone(&self.sequence),
+        };
+        self.running.store(true, Ordering::Relaxed);
+
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    result = listener.accept() => {
+                        match result {
+                            Ok((stream, addr)) => {
+                                let spec = spec.clone();
+                                tokio::spawn(async move {
+                                    if let Err(e) = Self::handle_client(stream, &spec).await {
+                                        println!("[CameraEmulator] Client {} error: {}", addr, e);
+                                    }
+                                });
+                            }
+                            Err(e) => {
+                                eprintln!("[CameraEmulator] Accept error: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                    _ = &mut shutdown_rx => break,
+                }
+            }
+
+            println!("[CameraEmulator] Server stopped");
+        });
+        self.server_handle = Some(handle);
+
+        Ok(())
+    }
+
+    /// Останавливает async сервер
+    pub async fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.server_handle.take() {
+            let _ = handle.await;
+        }
+        self.bound_addr = None;
+    }
+
+    /// Async обработка одного клиента: снимок на каждую команду
+    async fn handle_client(
+        mut stream: Box<dyn Connection>,
+        spec: &CameraSpec,
+    ) -> std::io::Result<()> {
+        loop {
+            let command = match receive_camera_command(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).await
+            {
+                Ok(command) => command,
+                // Клиент закрыл соединение
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            match command {
+                CameraCommand::Snapshot => {
+                    let sequence = spec.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+                    let jpeg = test_pattern(spec.width, spec.height, sequence);
+                    let info = SnapshotInfo {
+                        width: spec.width,
+                        height: spec.height,
+                        size: jpeg.len() as u64,
+                        sequence,
+                        device_id: spec.device_id.clone(),
+                    };
+                    send_snapshot(&mut stream, &info, &jpeg, spec.chunk_size).await?;
+                }
+            }
+        }
+    }
+}
+
+impl Lifecycle for CameraEmulator {
+    async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+        CameraEmulator::start(self).await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        CameraEmulator::stop(self).await;
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self.is_running(),
+            ..HealthStatus::default()
+        }
+    }
+}
+
+impl Drop for CameraEmulator {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Испытательная таблица `width`x`height` для кадра `frame` в формате JPEG
+///
+/// Полосы от белой к черной; с каждым кадром таблица сдвигается на одну полосу.
+pub fn test_pattern(width: u32, height: u32, frame: u64) -> Vec<u8> {
+    let width = width.clamp(1, u16::MAX as u32);
+    let height = height.clamp(1, u16::MAX as u32);
+    let shift = (frame % PATTERN_BARS as u64) as u32;
+
+    encode_gray_blocks(width, height, |block_x, _| {
+        let bar = (block_x * 8 * PATTERN_BARS / width + shift) % PATTERN_BARS;
+        (255 - bar * 255 / (PATTERN_BARS - 1)) as u8
+    })
+}
+
+/// Стандартная таблица Хаффмана для DC яркости (ITU T.81, приложение K)
+const DC_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const DC_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+/// Таблица AC из единственного символа EOB (код `0`)
+const AC_BITS: [u8; 16] = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+const AC_VALUES: [u8; 1] = [0x00];
+
+/// Baseline JPEG в оттенках серого из однотонных блоков 8x8
+///
+/// `block(x, y)` - яркость блока с номером столбца `x` и строки `y`.
+/// Блоки за краем изображения декодер обрезает.
+fn encode_gray_blocks(width: u32, height: u32, block: impl Fn(u32, u32) -> u8) -> Vec<u8> {
+    let mut jpeg = JPEG_SOI.to_vec();
+
+    // APP0 JFIF 1.1 без миниатюры
+    jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x10]);
+    jpeg.extend_from_slice(b"JFIF\0");
+    jpeg.extend_from_slice(&[0x01, 0x01, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00]);
+
+    // Таблица квантования из единиц: DC передается без потерь
+    jpeg.extend_from_slice(&[0xFF, 0xDB, 0x00, 0x43, 0x00]);
+    jpeg.extend_from_slice(&[1; 64]);
+
+    // Кадр: 8 бит, одна компонента без прореживания
+    jpeg.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x0B, 0x08]);
+    jpeg.extend_from_slice(&(height as u16).to_be_bytes());
+    jpeg.extend_from_slice(&(width as u16).to_be_bytes());
+    jpeg.extend_from_slice(&[0x01, 0x01, 0x11, 0x00]);
+
+    // Таблицы Хаффмана
+    let length = 2 + (17 + DC_VALUES.len()) + (17 + AC_VALUES.len());
+    jpeg.extend_from_slice(&[0xFF, 0xC4]);
+    jpeg.extend_from_slice(&(length as u16).to_be_bytes());
+    jpeg.push(0x00);
+    jpeg.extend_from_slice(&DC_BITS);
+    jpeg.extend_from_slice(&DC_VALUES);
+    jpeg.push(0x10);
+    jpeg.extend_from_slice(&AC_BITS);
+    jpeg.extend_from_slice(&AC_VALUES);
+
+    // Скан
+    jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00]);
+
+    let dc_codes = huffman_codes(&DC_BITS, &DC_VALUES);
+    let (eob_code, eob_length) = huffman_codes(&AC_BITS, &AC_VALUES)[0];
+    let mut bits = BitWriter::new(&mut jpeg);
+    let mut previous = 0i32;
+    for y in 0..height.div_ceil(8) {
+        for x in 0..width.div_ceil(8) {
+            // DC однотонного блока после сдвига уровня: 8 * (яркость - 128)
+            let dc = 8 * (block(x, y) as i32 - 128);
+            let diff = dc - previous;
+            previous = dc;
+
+            let category = 32 - diff.unsigned_abs().leading_zeros();
+            let (code, length) = dc_codes[category as usize];
+            bits.put(code, length);
+            if category > 0 {
+                let value = if diff < 0 { diff - 1 } else { diff };
+                bits.put(value as u32 & ((1 << category) - 1), category);
+            }
+            bits.put(eob_code, eob_length);
+        }
+    }
+    bits.finish();
+
+    jpeg.extend_from_slice(&JPEG_EOI);
+    jpeg
+}
+
+/// Канонические коды Хаффмана (код, длина) в порядке символов таблицы
+fn huffman_codes(bits: &[u8; 16], values: &[u8]) -> Vec<(u32, u32)> {
+    let mut codes = vec![(0, 0); values.len()];
+    let mut code = 0u32;
+    let mut index = 0;
+    for (length, count) in (1..).zip(bits) {
+        for _ in 0..*count {
+            codes[values[index] as usize] = (code, length);
+            code += 1;
+            index += 1;
+        }
+        code <<= 1;
+    }
+    codes
+}
+
+/// Запись битов энтропийно кодированных данных с байтовой вставкой после 0xFF
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        Self {
+            out,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn put(&mut self, code: u32, length: u32) {
+        for bit in (0..length).rev() {
+            self.buffer = (self.buffer << 1) | ((code >> bit) & 1);
+            self.count += 1;
+            if self.count == 8 {
+                self.flush_byte();
+            }
+        }
+    }
+
+    fn flush_byte(&mut self) {
+        let byte = self.buffer as u8;
+        self.out.push(byte);
+        if byte == 0xFF {
+            self.out.push(0x00);
+        }
+        self.buffer = 0;
+        self.count = 0;
+    }
+
+    /// Дополняет последний байт единицами
+    fn finish(mut self) {
+        if self.count > 0 {
+            let padding = 8 - self.count;
+            self.put((1 << padding) - 1, padding);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::camera_protocol::{CameraResponse, is_jpeg, request_snapshot};
+
+    #[test]
+    fn test_pattern_is_jpeg() {
+        let jpeg = test_pattern(64, 48, 0);
+        assert!(is_jpeg(&jpeg));
+        // Размеры кадра в заголовке SOF0
+        let sof = jpeg.windows(2).position(|w| w == [0xFF, 0xC0]).unwrap();
+        assert_eq!(&jpeg[sof + 5..sof + 9], &[0, 48, 0, 64]);
+
+        // Кадры отличаются сдвигом полос и повторяются через 8 кадров
+        assert_ne!(test_pattern(64, 48, 1), jpeg);
+        assert_eq!(test_pattern(64, 48, 8), jpeg);
+    }
+
+    #[test]
+    fn huffman_codes_are_canonical() {
+        let codes = huffman_codes(&DC_BITS, &DC_VALUES);
+        assert_eq!(codes[0], (0b00, 2));
+        assert_eq!(codes[1], (0b010, 3));
+        assert_eq!(codes[5], (0b110, 3));
+        assert_eq!(codes[6], (0b1110, 4));
+        assert_eq!(codes[11], (0b1_1111_1110, 9));
+    }
+
+    #[tokio::test]
+    async fn serves_snapshots_in_memory() {
+        let transport = InMemory::new();
+        let mut emulator = CameraEmulator::new("10.0.0.5:554")
+            .with_device_id("cam_001")
+            .with_resolution(64, 48)
+            .with_chunk_size(100);
+        emulator.start_in_memory(&transport).await.unwrap();
+
+        let addr = emulator.local_addr().unwrap();
+        let mut stream = crate::protocol::Transport::connect(&transport, addr)
+            .await
+            .unwrap();
+        let mut buf = Vec::new();
+        for sequence in 1..=2 {
+            let response = request_snapshot(&mut stream, &mut buf, 1 << 20)
+                .await
+                .unwrap();
+            let CameraResponse::Snapshot(info) = response else {
+                panic!("unexpected response: {:?}", response);
+            };
+            assert_eq!(info.sequence, sequence);
+            assert_eq!((info.width, info.height), (64, 48));
+            assert_eq!(info.device_id.as_deref(), Some("cam_001"));
+            assert_eq!(buf, test_pattern(64, 48, sequence));
+        }
+        assert_eq!(emulator.snapshots_served(), 2);
+
+        emulator.stop().await;
+        assert!(!emulator.is_running());
+    }
+}
@@ -0,0 +1,470 @@
+//! Async эмулятор CoAP устройства (UDP)
+//!
+//! Хранит набор текстовых ресурсов, отвечает на GET/PUT и рассылает
+//! уведомления подписчикам Observe при каждом изменении значения.
+
+use crate::controllers::HealthStatus;
+use crate::protocol::coap::{CoapCode, CoapMessage, CoapType};
+use crate::traits::Lifecycle;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU16, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// Ресурс устройства
+#[derive(Debug, Clone)]
+struct Resource {
+    value: String,
+    writable: bool,
+}
+
+/// Подписчик на изменения ресурса
+#[derive(Debug, Clone, PartialEq)]
+struct Observer {
+    addr: SocketAddr,
+    token: Vec<u8>,
+    path: String,
+}
+
+/// Общее состояние эмулятора
+#[derive(Debug, Default)]
+struct CoapState {
+    resources: HashMap<String, Resource>,
+    observers: Vec<Observer>,
+}
+
+/// Async эмулятор CoAP устройства
+pub struct CoapEmulator {
+    /// Адрес для прослушивания UDP
+    bind_address: String,
+    /// ID устройства для логирования
+    device_id: String,
+    /// Ресурсы и подписчики
+    state: Arc<Mutex<CoapState>>,
+    /// Сокет сервера (после start)
+    socket: Option<Arc<UdpSocket>>,
+    /// Handle главной задачи сервера
+    server_handle: Option<JoinHandle<()>>,
+    /// Счетчик message id для уведомлений
+    next_message_id: Arc<AtomicU16>,
+    /// Номер последнего уведомления Observe
+    observe_sequence: Arc<AtomicU32>,
+}
+
+impl CoapEmulator {
+    /// Создает эмулятор без ресурсов
+    pub fn new(bind_address: &str) -> Self {
+        Self {
+            bind_address: bind_address.to_string(),
+            device_id: "coap_emulator".to_string(),
+            state: Arc::new(Mutex::new(CoapState::default())),
+            socket: None,
+            server_handle: None,
+            next_message_id: Arc::new(AtomicU16::new(rand::random())),
+            observe_sequence: Arc::new(AtomicU32::new(2)),
+        }
+    }
+
+    /// Builder: Устанавливает ID устройства
+    pub fn with_device_id(mut self, device_id: &str) -> Self {
+        self.device_id = device_id.to_string();
+        self
+    }
+
+    /// Builder: ресурс только для чтения
+    pub fn with_resource(self, path: &str, value: &str) -> Self {
+        self.insert_resource(path, value, false);
+        self
+    }
+
+    /// Builder: ресурс, изменяемый через PUT
+    pub fn with_writable_resource(self, path: &str, value: &str) -> Self {
+        self.insert_resource(path, value, true);
+        self
+    }
+
+    fn insert_resource(&self, path: &str, value: &str, writable: bool) {
+        if let Ok(mut state) = self.state.lock() {
+            state.resources.insert(
+                normalize(path),
+                Resource {
+                    value: value.to_string(),
+                    writable,
+                },
+            );
+        }
+    }
+
+    /// Текущее значение ресурса
+    pub fn value(&self, path: &str) -> Option<String> {
+        let state = self.state.lock().ok()?;
+        state
+            .resources
+            .get(&normalize(path))
+            .map(|resource| resource.value.clone())
+    }
+
+    /// Изменяет значение ресурса (как это сделал бы сам датчик) и уведомляет подписчиков
+    pub fn set_value(&self, path: &str, value: &str) {
+        let path = normalize(path);
+        let observers = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            let Some(resource) = state.resources.get_mut(&path) else {
+                return;
+            };
+            resource.value = value.to_string();
+            observers_of(&state, &path)
+        };
+
+        if let Some(socket) = &self.socket {
+            Self::notify(
+                socket,
+                &observers,
+                value,
+                &self.next_message_id,
+                &self.observe_sequence,
+            );
+        }
+    }
+
+    /// Количество активных подписок Observe
+    pub fn observers_count(&self) -> usize {
+        self.state
+            .lock()
+            .map(|state| state.observers.len())
+            .unwrap_or(0)
+    }
+
+    /// Возвращает локальный адрес UDP сервера (только после start)
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        match &self.socket {
+            Some(socket) => socket.local_addr(),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "Server not started yet - call start() first",
+            )),
+        }
+    }
+
+    /// Проверяет, запущен ли эмулятор
+    pub fn is_running(&self) -> bool {
+        self.server_handle.is_some()
+    }
+
+    /// Запускает async UDP сервер
+    pub async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "Emulator already started",
+            ));
+        }
+
+        let socket = Arc::new(UdpSocket::bind(&self.bind_address).await?);
+        println!(
+            "[{}] CoAP server bound to {}",
+            self.device_id,
+            socket.local_addr()?
+        );
+
+        let server_socket = Arc::clone(&socket);
+        let state = Arc::clone(&self.state);
+        let next_message_id = Arc::clone(&self.next_message_id);
+        let observe_sequence = Arc::clone(&self.observe_sequence);
+        let device_id = self.device_id.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut buf = [0u8; 1152]; // Рекомендуемый RFC 7252 максимум
+
+            loop {
+                let (size, addr) = match server_socket.recv_from(&mut buf).await {
+                    Ok(received) => received,
+                    Err(e) => {
+                        // На некоторых платформах ICMP "port unreachable" приходит как ошибка
+                        println!("[{}] Receive error: {}", device_id, e);
+                        continue;
+                    }
+                };
+
+                let Ok(request) = CoapMessage::decode(&buf[..size]) else {
+                    continue; // Некорректные датаграммы молча отбрасываются
+                };
+
+                let (response, changed) = {
+                    let Ok(mut state) = state.lock() else {
+                        break;
+                    };
+                    Self::handle_request(&mut state, &request, addr, &next_message_id)
+                };
+
+                if let Some(response) = response {
+                    let _ = server_socket.send_to(&response.encode(), addr).await;
+                }
+
+                if let Some((path, value)) = changed {
+                    let observers = match state.lock() {
+                        Ok(state) => observers_of(&state, &path),
+                        Err(_) => break,
+                    };
+                    Self::notify(
+                        &server_socket,
+                        &observers,
+                        &value,
+                        &next_message_id,
+                        &observe_sequence,
+                    );
+                }
+            }
+        });
+
+        self.socket = Some(socket);
+        self.server_handle = Some(handle);
+        Ok(())
+    }
+
+    /// Останавливает сервер
+    pub async fn stop(&mut self) {
+        if let Some(handle) = self.server_handle.take() {
+            handle.abort();
+            let _ = handle.await;
+        }
+        self.socket = None;
+
+        if let Ok(mut state) = self.state.lock() {
+            state.observers.clear();
+        }
+    }
+
+    /// Обрабатывает запрос; возвращает ответ и изменившийся ресурс
+    fn handle_request(
+        state: &mut CoapState,
+        request: &CoapMessage,
+        addr: SocketAddr,
+        next_message_id: &AtomicU16,
+    ) -> (Option<CoapMessage>, Option<(String, String)>) {
+        let message_id = next_message_id.fetch_add(1, Ordering::Relaxed);
+
+        match (request.msg_type, request.code) {
+            // Подписчик отказался от уведомлений
+            (CoapType::Reset, _) => {
+                state.observers.retain(|observer| observer.addr != addr);
+                return (None, None);
+            }
+            (CoapType::Acknowledgement, _) => return (None, None),
+            // CoAP ping
+            (CoapType::Confirmable, CoapCode::EMPTY) => {
+                let reset =
+                    CoapMessage::new(CoapType::Reset, CoapCode::EMPTY, request.message_id, &[]);
+                return (Some(reset), None);
+            }
+            _ => {}
+        }
+
+        let path = request.path();
+        let reply = |code: CoapCode| CoapMessage::response_to(request, code, message_id);
+
+        let Some(resource) = state.resources.get_mut(&path) else {
+            return (Some(reply(CoapCode::NOT_FOUND)), None);
+        };
+
+        match request.code {
+            CoapCode::GET => {
+                let value = resource.value.clone();
+                let mut response = reply(CoapCode::CONTENT);
+
+                let observer = Observer {
+                    addr,
+                    token: request.token.clone(),
+                    path: path.clone(),
+                };
+                match request.observe() {
+                    Some(0) => {
+                        if !state.observers.contains(&observer) {
+                            state.observers.push(observer);
+                        }
+                        response = response.with_observe(1);
+                    }
+                    Some(1) => state.observers.retain(|o| *o != observer),
+                    _ => {}
+                }
+
+                (Some(response.with_text(&value)), None)
+            }
+            CoapCode::PUT if !resource.writable => {
+                (Some(reply(CoapCode::METHOD_NOT_ALLOWED)), None)
+            }
+            CoapCode::PUT => match request.text() {
+                Ok(text) => {
+                    resource.value = text.to_string();
+                    (
+                        Some(reply(CoapCode::CHANGED)),
+                        Some((path, text.to_string())),
+                    )
+                }
+                Err(_) => (Some(reply(CoapCode::BAD_REQUEST)), None),
+            },
+            _ => (Some(reply(CoapCode::METHOD_NOT_ALLOWED)), None),
+        }
+    }
+
+    /// Рассылает уведомления Observe (NON, без ожидания подтверждения)
+    fn notify(
+        socket: &UdpSocket,
+        observers: &[Observer],
+        value: &str,
+        next_message_id: &AtomicU16,
+        observe_sequence: &AtomicU32,
+    ) {
+        let sequence = observe_sequence.fetch_add(1, Ordering::Relaxed);
+
+        for observer in observers {
+            let notification = CoapMessage::new(
+                CoapType::NonConfirmable,
+                CoapCode::CONTENT,
+                next_message_id.fetch_add(1, Ordering::Relaxed),
+                &observer.token,
+            )
+            .with_observe(sequence)
+            .with_text(value);
+
+            let _ = socket.try_send_to(&notification.encode(), observer.addr);
+        }
+    }
+}
+
+impl Lifecycle for CoapEmulator {
+    async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+        CoapEmulator::start(self).await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        CoapEmulator::stop(self).await;
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self.is_running(),
+            ..HealthStatus::default()
+        }
+    }
+}
+
+impl Drop for CoapEmulator {
+    fn drop(&mut self) {
+        if let Some(handle) = self.server_handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+/// Подписчики ресурса
+fn observers_of(state: &CoapState, path: &str) -> Vec<Observer> {
+    state
+        .observers
+        .iter()
+        .filter(|observer| observer.path == path)
+        .cloned()
+        .collect()
+}
+
+/// Приводит путь к виду без начального и конечного `/`
+fn normalize(path: &str) -> String {
+    path.trim_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_state() -> CoapState {
+        let emulator = CoapEmulator::new("127.0.0.1:0")
+            .with_resource("temperature", "21.5")
+            .with_writable_resource("/relay/", "off");
+        let mut state = emulator.state.lock().unwrap();
+        std::mem::take(&mut *state)
+    }
+
+    fn request(code: CoapCode, path: &str) -> CoapMessage {
+        CoapMessage::new(CoapType::Confirmable, code, 10, &[7]).with_path(path)
+    }
+
+    fn addr() -> SocketAddr {
+        "127.0.0.1:5683".parse().unwrap()
+    }
+
+    #[test]
+    fn get_and_put() {
+        let mut state = test_state();
+        let mid = AtomicU16::new(0);
+
+        let (response, _) = CoapEmulator::handle_request(
+            &mut state,
+            &request(CoapCode::GET, "temperature"),
+            addr(),
+            &mid,
+        );
+        let response = response.unwrap();
+        assert_eq!(response.code, CoapCode::CONTENT);
+        assert_eq!(response.msg_type, CoapType::Acknowledgement);
+        assert_eq!(response.text().unwrap(), "21.5");
+
+        let put = request(CoapCode::PUT, "relay").with_text("on");
+        let (response, changed) = CoapEmulator::handle_request(&mut state, &put, addr(), &mid);
+        assert_eq!(response.unwrap().code, CoapCode::CHANGED);
+        assert_eq!(changed, Some(("relay".to_string(), "on".to_string())));
+        assert_eq!(state.resources["relay"].value, "on");
+    }
+
+    #[test]
+    fn errors() {
+        let mut state = test_state();
+        let mid = AtomicU16::new(0);
+
+        let (response, _) = CoapEmulator::handle_request(
+            &mut state,
+            &request(CoapCode::GET, "missing"),
+            addr(),
+            &mid,
+        );
+        assert_eq!(response.unwrap().code, CoapCode::NOT_FOUND);
+
+        let put = request(CoapCode::PUT, "temperature").with_text("99");
+        let (response, changed) = CoapEmulator::handle_request(&mut state, &put, addr(), &mid);
+        assert_eq!(response.unwrap().code, CoapCode::METHOD_NOT_ALLOWED);
+        assert!(changed.is_none());
+    }
+
+    #[test]
+    fn observe_register_and_cancel() {
+        let mut state = test_state();
+        let mid = AtomicU16::new(0);
+
+        let subscribe = request(CoapCode::GET, "temperature").with_observe(0);
+        let (response, _) = CoapEmulator::handle_request(&mut state, &subscribe, addr(), &mid);
+        assert_eq!(response.unwrap().observe(), Some(1));
+        assert_eq!(state.observers.len(), 1);
+
+        let cancel = request(CoapCode::GET, "temperature").with_observe(1);
+        CoapEmulator::handle_request(&mut state, &cancel, addr(), &mid);
+        assert!(state.observers.is_empty());
+    }
+
+    #[test]
+    fn ping_gets_reset() {
+        let mut state = test_state();
+        let ping = CoapMessage::new(CoapType::Confirmable, CoapCode::EMPTY, 99, &[]);
+        let (response, _) =
+            CoapEmulator::handle_request(&mut state, &ping, addr(), &AtomicU16::new(0));
+        let response = response.unwrap();
+        assert_eq!(response.msg_type, CoapType::Reset);
+        assert_eq!(response.message_id, 99);
+    }
+}
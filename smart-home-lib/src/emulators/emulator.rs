@@ -0,0 +1,120 @@
+//! Общий интерфейс эмуляторов устройств
+//!
+//! Парки эмуляторов и тестовые стенды управляют разными эмуляторами через
+//! `Box<dyn Emulator>`: запуск и остановка, адрес, внедрение неисправностей
+//! и состояние в том же формате, что `GET /state` административного
+//! интерфейса. Поэтому методы запуска возвращают `EmulatorFuture`, а не
+//! `impl Future`, как в `Lifecycle`.
+
+use super::fault::{Fault, FaultInjector};
+use serde_json::Value;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+/// Future операций эмулятора
+pub type EmulatorFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Эмулятор устройства
+pub trait Emulator: Send {
+    /// Запускает эмулятор (повторный запуск работающего ничего не делает)
+    fn start(&mut self) -> EmulatorFuture<'_, io::Result<()>>;
+
+    /// Останавливает эмулятор
+    fn stop(&mut self) -> EmulatorFuture<'_, ()>;
+
+    /// Сетевой адрес: где эмулятор принимает команды или куда отправляет данные
+    fn endpoint(&self) -> Option<SocketAddr>;
+
+    /// Текущее состояние с активной неисправностью (как `GET /state`)
+    fn state(&self) -> Value;
+
+    /// Переключатель неисправностей эмулятора
+    fn faults(&self) -> &FaultInjector;
+
+    /// Поддерживает ли эмулятор данную неисправность
+    fn supports_fault(&self, _fault: &Fault) -> bool {
+        true
+    }
+
+    /// Включает неисправность до `clear_fault`; неподдерживаемая - ошибка
+    fn inject(&self, fault: Fault) -> Result<(), String> {
+        if !self.supports_fault(&fault) {
+            return Err(format!("Fault is not supported: {}", fault));
+        }
+        self.faults().inject(fault, None);
+        Ok(())
+    }
+
+    /// Снимает неисправность
+    fn clear_fault(&self) {
+        self.faults().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+    use crate::emulators::therm_emulator::ThermEmulator;
+    use crate::protocol::ThermData;
+    use std::time::Duration;
+
+    #[test]
+    fn faults_through_trait() {
+        let emulators: Vec<Box<dyn Emulator>> = vec![
+            Box::new(SocketEmulator::new(EmulatorConfig::new(1000.0))),
+            Box::new(ThermEmulator::new(21.0)),
+        ];
+
+        for emulator in &emulators {
+            emulator.inject(Fault::Corrupt).unwrap();
+            assert_eq!(emulator.state()["fault"]["fault"], "corrupt");
+            emulator.clear_fault();
+            assert!(emulator.faults().active().is_none());
+        }
+
+        let error = Fault::ErrorResponses {
+            message: "boom".to_string(),
+        };
+        assert!(emulators[0].inject(error.clone()).is_ok());
+        assert!(emulators[1].inject(error).is_err());
+        assert_eq!(emulators[0].endpoint(), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn start_and_stop_generically() {
+        let receiver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut therm = ThermEmulator::new(21.0).with_update_interval(Duration::from_millis(10));
+        therm
+            .connect_to(&receiver.local_addr().unwrap().to_string())
+            .unwrap();
+
+        let mut emulators: Vec<Box<dyn Emulator>> = vec![
+            Box::new(SocketEmulator::new(
+                EmulatorConfig::new(1000.0).with_address("127.0.0.1:0"),
+            )),
+            Box::new(therm),
+        ];
+        for emulator in &mut emulators {
+            emulator.start().await.unwrap();
+            assert!(emulator.endpoint().is_some());
+        }
+
+        let mut buf = [0u8; 256];
+        let len = receiver.recv(&mut buf).unwrap();
+        assert!(serde_json::from_slice::<ThermData>(&buf[..len]).is_ok());
+        let addr = emulators[0].endpoint().unwrap();
+        assert!(tokio::net::TcpStream::connect(addr).await.is_ok());
+
+        for emulator in &mut emulators {
+            emulator.stop().await;
+        }
+        assert_eq!(emulators[1].state()["running"], false);
+    }
+}
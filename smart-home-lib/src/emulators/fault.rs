@@ -0,0 +1,120 @@
+//! Неисправности, внедряемые в эмуляторы во время работы
+
+use crate::clock::SharedClock;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Неисправность эмулируемого устройства
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "fault", rename_all = "snake_case")]
+pub enum Fault {
+    /// Устройство молчит: команды и показания теряются
+    Unresponsive,
+    /// Ответы и показания приходят с задержкой
+    Latency { ms: u64 },
+    /// Устройство отвечает ошибкой на любую команду
+    ErrorResponses { message: String },
+    /// Устройство присылает поврежденные данные
+    Corrupt,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unresponsive => write!(f, "нет ответа"),
+            Self::Latency { ms } => write!(f, "задержка {} мс", ms),
+            Self::ErrorResponses { message } => write!(f, "ошибки: {}", message),
+            Self::Corrupt => write!(f, "поврежденные данные"),
+        }
+    }
+}
+
+/// Активная неисправность со сроком действия
+#[derive(Debug, Clone)]
+struct ActiveFault {
+    fault: Fault,
+    /// Время отмены по часам эмулятора (None - до явной отмены)
+    expires_ms: Option<u64>,
+}
+
+/// Общий для потоков эмулятора переключатель неисправностей
+#[derive(Clone)]
+pub struct FaultInjector {
+    active: Arc<Mutex<Option<ActiveFault>>>,
+    clock: SharedClock,
+}
+
+impl FaultInjector {
+    /// Создает переключатель без активной неисправности
+    pub fn new(clock: SharedClock) -> Self {
+        Self {
+            active: Arc::new(Mutex::new(None)),
+            clock,
+        }
+    }
+
+    /// Включает неисправность; с `duration` она снимается автоматически
+    pub fn inject(&self, fault: Fault, duration: Option<Duration>) {
+        let expires_ms = duration.map(|d| self.clock.now_ms() + d.as_millis() as u64);
+        *self.lock() = Some(ActiveFault { fault, expires_ms });
+    }
+
+    /// Снимает неисправность
+    pub fn clear(&self) {
+        *self.lock() = None;
+    }
+
+    /// Текущая неисправность (истекшая снимается)
+    pub fn active(&self) -> Option<Fault> {
+        let mut active = self.lock();
+        if let Some(current) = active.as_ref()
+            && current
+                .expires_ms
+                .is_some_and(|expires| self.clock.now_ms() >= expires)
+        {
+            *active = None;
+        }
+        active.as_ref().map(|current| current.fault.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<ActiveFault>> {
+        self.active.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn json_format() {
+        let fault: Fault = serde_json::from_str(r#"{"fault":"latency","ms":250}"#).unwrap();
+        assert_eq!(fault, Fault::Latency { ms: 250 });
+        assert_eq!(
+            serde_json::to_string(&Fault::Unresponsive).unwrap(),
+            r#"{"fault":"unresponsive"}"#
+        );
+    }
+
+    #[test]
+    fn fault_expires() {
+        let clock = Arc::new(MockClock::new());
+        let injector = FaultInjector::new(clock.clone());
+        assert_eq!(injector.active(), None);
+
+        injector.inject(Fault::Corrupt, Some(Duration::from_secs(5)));
+        assert_eq!(injector.active(), Some(Fault::Corrupt));
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(injector.active(), None);
+
+        injector.inject(Fault::Unresponsive, None);
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(injector.active(), Some(Fault::Unresponsive));
+        injector.clear();
+        assert_eq!(injector.active(), None);
+    }
+}
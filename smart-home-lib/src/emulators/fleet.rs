@@ -0,0 +1,402 @@
+//! Парк эмуляторов для интеграционных тестов и примеров
+//!
+//! Поднимает набор эмуляторов розеток и термометров на свободных портах
+//! и собирает `SmartHouse` с контроллерами, подключенными к ним.
+
+use super::emulator::Emulator;
+use super::scenario::EmulationScenario;
+use super::socket_emulator::{EmulatorConfig, SocketEmulator};
+use super::therm_emulator::ThermEmulator;
+use crate::clock::{SharedClock, system_clock};
+use crate::controllers::{DeviceController, SocketController, ThermController};
+use crate::house::SmartHouse;
+use crate::room::Room;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Описание эмулятора розетки в парке
+#[derive(Debug, Clone)]
+struct SocketSpec {
+    room: String,
+    key: String,
+    power_rating: f64,
+}
+
+/// Описание эмулятора термометра в парке
+#[derive(Debug, Clone)]
+struct ThermSpec {
+    room: String,
+    key: String,
+    initial_temp: f64,
+    scenario: EmulationScenario,
+}
+
+/// Спецификация парка эмуляторов
+#[derive(Debug, Clone)]
+pub struct FleetSpec {
+    sockets: Vec<SocketSpec>,
+    therms: Vec<ThermSpec>,
+    update_interval: Duration,
+    controller_timeout: Duration,
+    max_age: Duration,
+    seed: Option<u64>,
+    clock: SharedClock,
+}
+
+impl Default for FleetSpec {
+    fn default() -> Self {
+        Self {
+            sockets: Vec::new(),
+            therms: Vec::new(),
+            update_interval: Duration::from_millis(100),
+            controller_timeout: Duration::from_secs(3),
+            max_age: Duration::from_secs(5),
+            seed: None,
+            clock: system_clock(),
+        }
+    }
+}
+
+impl FleetSpec {
+    /// Создает пустую спецификацию
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: добавляет розетку в комнату
+    pub fn socket(mut self, room: &str, key: &str, power_rating: f64) -> Self {
+        self.sockets.push(SocketSpec {
+            room: room.to_string(),
+            key: key.to_string(),
+            power_rating,
+        });
+        self
+    }
+
+    /// Builder: добавляет термометр в комнату
+    pub fn therm(
+        mut self,
+        room: &str,
+        key: &str,
+        initial_temp: f64,
+        scenario: EmulationScenario,
+    ) -> Self {
+        self.therms.push(ThermSpec {
+            room: room.to_string(),
+            key: key.to_string(),
+            initial_temp,
+            scenario,
+        });
+        self
+    }
+
+    /// Builder: интервал отправки данных термометрами
+    pub fn with_update_interval(mut self, interval: Duration) -> Self {
+        self.update_interval = interval;
+        self
+    }
+
+    /// Builder: таймаут TCP операций контроллеров розеток
+    pub fn with_controller_timeout(mut self, timeout: Duration) -> Self {
+        self.controller_timeout = timeout;
+        self
+    }
+
+    /// Builder: максимальный возраст данных контроллеров термометров
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Builder: базовый seed термометров (каждый получает `seed + индекс`)
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Builder: общий источник времени для термометров и их контроллеров
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Количество эмуляторов розеток
+    pub fn sockets_count(&self) -> usize {
+        self.sockets.len()
+    }
+
+    /// Количество эмуляторов термометров
+    pub fn therms_count(&self) -> usize {
+        self.therms.len()
+    }
+}
+
+/// Запущенный эмулятор с его расположением в доме
+pub(super) struct Member<E> {
+    pub(super) room: String,
+    pub(super) key: String,
+    pub(super) addr: SocketAddr,
+    pub(super) emulator: E,
+}
+
+/// Запущенный парк эмуляторов вместе с подключенным домом
+pub struct Fleet {
+    sockets: Vec<Member<SocketEmulator>>,
+    therms: Vec<Member<ThermEmulator>>,
+    house: SmartHouse,
+}
+
+impl Fleet {
+    /// Запускает все эмуляторы из спецификации и собирает дом с контроллерами
+    ///
+    /// Контроллеры запускаются сразу (`SmartHouse::start_all`): термометры
+    /// принимают данные, розетки подключены к эмуляторам. Контроллер
+    /// термометра стартует раньше своего эмулятора, чтобы занять UDP порт.
+    pub async fn start(spec: FleetSpec) -> std::io::Result<Self> {
+        let mut fleet = Self {
+            sockets: Vec::with_capacity(spec.sockets.len()),
+            therms: Vec::with_capacity(spec.therms.len()),
+            house: SmartHouse::default(),
+        };
+
+        for socket in &spec.sockets {
+            let config = EmulatorConfig::new(socket.power_rating)
+                .with_address("127.0.0.1:0")
+                .with_device_id(&format!("{}/{}", socket.room, socket.key));
+
+            let mut emulator = SocketEmulator::new(config);
+            emulator.start().await?;
+            let addr = emulator.local_addr()?;
+
+            let controller =
+                SocketController::new(addr, socket.power_rating, spec.controller_timeout);
+            fleet.add_controller(&socket.room, &socket.key, controller.into());
+
+            fleet.sockets.push(Member {
+                room: socket.room.clone(),
+                key: socket.key.clone(),
+                addr,
+                emulator,
+            });
+        }
+
+        for (index, therm) in spec.therms.iter().enumerate() {
+            let (controller, addr) =
+                therm_controller(therm.initial_temp, spec.max_age, &spec.clock)?;
+            fleet.add_controller(&therm.room, &therm.key, controller.into());
+
+            let mut emulator = ThermEmulator::new(therm.initial_temp)
+                .with_device_id(&format!("{}/{}", therm.room, therm.key))
+                .with_scenario(therm.scenario)
+                .with_update_interval(spec.update_interval)
+                .with_clock(Arc::clone(&spec.clock));
+            if let Some(seed) = spec.seed {
+                emulator = emulator.with_seed(seed.wrapping_add(index as u64));
+            }
+            emulator.connect_to(&addr.to_string())?;
+            emulator.start();
+
+            fleet.therms.push(Member {
+                room: therm.room.clone(),
+                key: therm.key.clone(),
+                addr,
+                emulator,
+            });
+        }
+
+        start_house(&mut fleet.house).await?;
+        Ok(fleet)
+    }
+
+    /// Добавляет контроллер, создавая комнату при необходимости
+    fn add_controller(&mut self, room: &str, key: &str, controller: DeviceController) {
+        if self.house.room(room).is_none() {
+            self.house.add_room(room, Room::new());
+        }
+        if let Some(room) = self.house.room_mut(room) {
+            room.add_controller(key, controller);
+        }
+    }
+
+    /// Дом с контроллерами, подключенными к эмуляторам
+    pub fn house(&self) -> &SmartHouse {
+        &self.house
+    }
+
+    /// Изменяемый доступ к дому
+    pub fn house_mut(&mut self) -> &mut SmartHouse {
+        &mut self.house
+    }
+
+    /// TCP адрес эмулятора розетки
+    pub fn socket_addr(&self, room: &str, key: &str) -> Option<SocketAddr> {
+        find(&self.sockets, room, key).map(|member| member.addr)
+    }
+
+    /// UDP адрес, на который термометр отправляет данные
+    pub fn therm_addr(&self, room: &str, key: &str) -> Option<SocketAddr> {
+        find(&self.therms, room, key).map(|member| member.addr)
+    }
+
+    /// Эмулятор розетки
+    pub fn socket_emulator(&self, room: &str, key: &str) -> Option<&SocketEmulator> {
+        find(&self.sockets, room, key).map(|member| &member.emulator)
+    }
+
+    /// Эмулятор термометра
+    pub fn therm_emulator_mut(&mut self, room: &str, key: &str) -> Option<&mut ThermEmulator> {
+        self.therms
+            .iter_mut()
+            .find(|member| member.room == room && member.key == key)
+            .map(|member| &mut member.emulator)
+    }
+
+    /// Количество запущенных эмуляторов
+    pub fn len(&self) -> usize {
+        self.sockets.len() + self.therms.len()
+    }
+
+    /// Проверяет, пуст ли парк
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Все эмуляторы парка: сначала розетки, затем термометры
+    pub fn emulators(&self) -> impl Iterator<Item = &dyn Emulator> {
+        let sockets = self.sockets.iter().map(|m| &m.emulator as &dyn Emulator);
+        let therms = self.therms.iter().map(|m| &m.emulator as &dyn Emulator);
+        sockets.chain(therms)
+    }
+
+    /// Изменяемый доступ ко всем эмуляторам парка
+    pub fn emulators_mut(&mut self) -> impl Iterator<Item = &mut dyn Emulator> {
+        let sockets = self
+            .sockets
+            .iter_mut()
+            .map(|m| &mut m.emulator as &mut dyn Emulator);
+        let therms = self
+            .therms
+            .iter_mut()
+            .map(|m| &mut m.emulator as &mut dyn Emulator);
+        sockets.chain(therms)
+    }
+
+    /// Останавливает все эмуляторы и контроллеры
+    pub async fn shutdown(mut self) {
+        // Сначала дом: контроллеры останавливаются раньше эмуляторов
+        self.house.stop_all().await;
+
+        for emulator in self.emulators_mut() {
+            emulator.stop().await;
+        }
+    }
+}
+
+/// Запускает контроллеры дома; первая неудача - ошибка запуска
+pub(super) async fn start_house(house: &mut SmartHouse) -> std::io::Result<()> {
+    match house.start_all().await.into_iter().next() {
+        Some(failure) => Err(std::io::Error::other(failure.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Ищет эмулятор по комнате и ключу
+pub(super) fn find<'a, E>(
+    members: &'a [Member<E>],
+    room: &str,
+    key: &str,
+) -> Option<&'a Member<E>> {
+    members
+        .iter()
+        .find(|member| member.room == room && member.key == key)
+}
+
+/// Запущенный контроллер термометра на свободном UDP порту loopback
+///
+/// Контроллер сам привязывается к порту 0 и держит сокет, поэтому между
+/// выбором порта и запуском его не может занять другой процесс.
+pub(super) fn therm_controller(
+    initial_temp: f64,
+    max_age: Duration,
+    clock: &SharedClock,
+) -> std::io::Result<(ThermController, SocketAddr)> {
+    let mut controller =
+        ThermController::new(initial_temp, "127.0.0.1:0", max_age).with_clock(Arc::clone(clock));
+    controller.start().map_err(std::io::Error::other)?;
+    let addr = controller
+        .listen_addr()
+        .parse()
+        .map_err(std::io::Error::other)?;
+    Ok((controller, addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::Reporter;
+
+    #[test]
+    fn spec_builder() {
+        let spec = FleetSpec::new()
+            .socket("kitchen", "kettle", 2000.0)
+            .socket("living", "tv", 150.0)
+            .therm("kitchen", "therm", 22.5, EmulationScenario::Normal)
+            .with_update_interval(Duration::from_millis(20))
+            .with_controller_timeout(Duration::from_secs(1))
+            .with_max_age(Duration::from_secs(2))
+            .with_seed(42);
+
+        assert_eq!(spec.sockets_count(), 2);
+        assert_eq!(spec.therms_count(), 1);
+        assert_eq!(spec.update_interval, Duration::from_millis(20));
+        assert_eq!(spec.controller_timeout, Duration::from_secs(1));
+        assert_eq!(spec.max_age, Duration::from_secs(2));
+        assert_eq!(spec.seed, Some(42));
+    }
+
+    #[tokio::test]
+    async fn empty_fleet() {
+        let fleet = Fleet::start(FleetSpec::new()).await.unwrap();
+        assert!(fleet.is_empty());
+        assert_eq!(fleet.house().rooms_count(), 0);
+        fleet.shutdown().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async networking"]
+    async fn fleet_wires_house() {
+        let spec = FleetSpec::new()
+            .socket("kitchen", "kettle", 2000.0)
+            .therm("kitchen", "therm", 22.5, EmulationScenario::Normal)
+            .therm("bedroom", "therm", 18.0, EmulationScenario::Normal)
+            .with_update_interval(Duration::from_millis(20));
+
+        let mut fleet = Fleet::start(spec).await.unwrap();
+        assert_eq!(fleet.len(), 3);
+        assert_eq!(fleet.house().rooms_count(), 2);
+        assert!(fleet.socket_addr("kitchen", "kettle").is_some());
+        assert!(fleet.therm_addr("bedroom", "therm").is_some());
+        assert!(fleet.socket_addr("kitchen", "missing").is_none());
+
+        if let Ok(DeviceController::Socket(kettle)) =
+            fleet.house_mut().controller_mut("kitchen", "kettle")
+        {
+            kettle.turn_on().await.unwrap();
+            assert_eq!(kettle.power().await.unwrap().value(), 2000.0);
+        } else {
+            panic!("Expected socket controller");
+        }
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        if let Ok(DeviceController::Therm(therm)) = fleet.house().controller("bedroom", "therm") {
+            assert!(therm.temperature().is_ok());
+        } else {
+            panic!("Expected therm controller");
+        }
+
+        assert!(fleet.house().report().contains("Smart Socket: ACTIVE"));
+        fleet.shutdown().await;
+    }
+}
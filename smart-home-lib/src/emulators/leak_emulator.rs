@@ -0,0 +1,95 @@
+//! Эмулятор датчика протечки (UDP)
+//!
+//! Отправляет событие при каждом изменении состояния, как настоящий
+//! датчик. `repeat` повторяет последнее событие с новым номером -
+//! периодическое подтверждение состояния.
+
+use crate::protocol::bind;
+use crate::protocol::leak_protocol::LeakEvent;
+use tokio::net::UdpSocket;
+
+/// Эмулятор датчика протечки
+pub struct LeakEmulator {
+    /// Адрес получателя событий
+    target_addr: String,
+    device_id: Option<String>,
+    leak: bool,
+    /// Номер последнего отправленного события
+    sequence: u64,
+}
+
+impl LeakEmulator {
+    /// Создает сухой датчик, отправляющий события на `target_addr`
+    pub fn new(target_addr: &str) -> Self {
+        Self {
+            target_addr: target_addr.to_string(),
+            device_id: None,
+            leak: false,
+            sequence: 0,
+        }
+    }
+
+    /// Builder: устанавливает ID устройства
+    pub fn with_device_id(mut self, device_id: &str) -> Self {
+        self.device_id = Some(device_id.to_string());
+        self
+    }
+
+    /// Датчик сейчас сообщает о протечке
+    pub fn is_leaking(&self) -> bool {
+        self.leak
+    }
+
+    /// Отправлено событий
+    pub fn events_sent(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Меняет состояние датчика и отправляет событие
+    pub async fn set_leak(&mut self, leak: bool) -> std::io::Result<()> {
+        self.leak = leak;
+        self.repeat().await
+    }
+
+    /// Повторно отправляет текущее состояние
+    pub async fn repeat(&mut self) -> std::io::Result<()> {
+        let event = self.next_event();
+        let packet = serde_json::to_vec(&event)?;
+        let target = bind::resolve_first(self.target_addr.as_str())?;
+        let socket = UdpSocket::bind(bind::unspecified_for(target)).await?;
+        socket.send_to(&packet, target).await?;
+        Ok(())
+    }
+
+    fn next_event(&mut self) -> LeakEvent {
+        self.sequence += 1;
+        LeakEvent {
+            leak: self.leak,
+            sequence: self.sequence,
+            device_id: self.device_id.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_numbered() {
+        let mut emulator = LeakEmulator::new("127.0.0.1:9").with_device_id("bath");
+        assert_eq!(emulator.next_event().sequence, 1);
+
+        emulator.leak = true;
+        let event = emulator.next_event();
+        assert_eq!(
+            event,
+            LeakEvent {
+                leak: true,
+                sequence: 2,
+                device_id: Some("bath".to_string()),
+            }
+        );
+        assert_eq!(emulator.events_sent(), 2);
+    }
+}
@@ -0,0 +1,243 @@
+//! Нагрузочный тест эмулятора розетки
+//!
+//! `run` поднимает один `SocketEmulator` и подключает к нему `clients`
+//! одновременных клиентов, каждый из которых отправляет `commands`
+//! команд по своему соединению. Отчет содержит пропускную способность и
+//! перцентили задержки одной команды - по ним видно, как async сервер
+//! эмулятора и протокол ведут себя под нагрузкой.
+
+use super::socket_emulator::{EmulatorConfig, SocketEmulator};
+use crate::protocol::socket_protocol::{SocketCommand, SocketResponse, send_command_and_receive};
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+
+/// Параметры нагрузочного теста
+#[derive(Debug, Clone)]
+pub struct LoadTestConfig {
+    /// Количество одновременных клиентов
+    clients: usize,
+    /// Команд на одного клиента
+    commands: usize,
+    command: SocketCommand,
+    /// Таймаут одной команды
+    timeout: Duration,
+    /// Номинальная мощность эмулятора
+    power_rating: f64,
+}
+
+impl LoadTestConfig {
+    /// `clients` клиентов по `commands` команд `Power`
+    pub fn new(clients: usize, commands: usize) -> Self {
+        Self {
+            clients,
+            commands,
+            command: SocketCommand::Power,
+            timeout: Duration::from_secs(5),
+            power_rating: 1000.0,
+        }
+    }
+
+    /// Builder: отправляемая команда
+    pub fn with_command(mut self, command: SocketCommand) -> Self {
+        self.command = command;
+        self
+    }
+
+    /// Builder: таймаут одной команды
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Builder: номинальная мощность эмулятора
+    pub fn with_power_rating(mut self, power_rating: f64) -> Self {
+        self.power_rating = power_rating;
+        self
+    }
+}
+
+/// Результаты нагрузочного теста
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadTestReport {
+    pub clients: usize,
+    /// Успешные команды
+    pub completed: usize,
+    /// Команды с ошибкой, таймаутом или ответом `Error`
+    pub failed: usize,
+    /// Длительность всего теста
+    pub elapsed: Duration,
+    /// Задержки успешных команд по возрастанию
+    latencies: Vec<Duration>,
+}
+
+impl LoadTestReport {
+    /// Успешных команд в секунду
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.completed as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Перцентиль задержки (0-100) по методу ближайшего ранга
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.latencies.len() as f64).ceil();
+        let index = (rank as usize).clamp(1, self.latencies.len()) - 1;
+        Some(self.latencies[index])
+    }
+
+    /// Наибольшая задержка
+    pub fn max_latency(&self) -> Option<Duration> {
+        self.latencies.last().copied()
+    }
+}
+
+impl fmt::Display for LoadTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} clients: {} ok, {} failed in {:.2?} ({:.0} cmd/s)",
+            self.clients,
+            self.completed,
+            self.failed,
+            self.elapsed,
+            self.throughput()
+        )?;
+        if let (Some(p50), Some(p95), Some(p99), Some(max)) = (
+            self.percentile(50.0),
+            self.percentile(95.0),
+            self.percentile(99.0),
+            self.max_latency(),
+        ) {
+            write!(
+                f,
+                " | latency p50 {:.2?}, p95 {:.2?}, p99 {:.2?}, max {:.2?}",
+                p50, p95, p99, max
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Запускает эмулятор, нагружает его и останавливает
+pub async fn run(config: LoadTestConfig) -> std::io::Result<LoadTestReport> {
+    let emulator_config = EmulatorConfig::new(config.power_rating).with_address("127.0.0.1:0");
+    let mut emulator = SocketEmulator::new(emulator_config);
+    emulator.start().await?;
+
+    let report = run_against(emulator.local_addr()?, &config).await;
+    emulator.stop().await;
+    Ok(report)
+}
+
+/// Нагружает уже запущенную розетку (или эмулятор) по адресу `addr`
+pub async fn run_against(addr: SocketAddr, config: &LoadTestConfig) -> LoadTestReport {
+    let started = Instant::now();
+    let mut clients = JoinSet::new();
+    for _ in 0..config.clients {
+        clients.spawn(client(addr, config.clone()));
+    }
+
+    let mut latencies = Vec::with_capacity(config.clients * config.commands);
+    let mut failed = 0;
+    while let Some(joined) = clients.join_next().await {
+        match joined {
+            Ok((client_latencies, client_failed)) => {
+                latencies.extend(client_latencies);
+                failed += client_failed;
+            }
+            Err(_) => failed += config.commands,
+        }
+    }
+    latencies.sort();
+
+    LoadTestReport {
+        clients: config.clients,
+        completed: latencies.len(),
+        failed,
+        elapsed: started.elapsed(),
+        latencies,
+    }
+}
+
+/// Один клиент: свое соединение и `commands` команд подряд
+///
+/// Возвращает задержки успешных команд и число неудачных. После ошибки
+/// соединение открывается заново.
+async fn client(addr: SocketAddr, config: LoadTestConfig) -> (Vec<Duration>, usize) {
+    let mut latencies = Vec::with_capacity(config.commands);
+    let mut failed = 0;
+    let mut stream: Option<TcpStream> = None;
+
+    for _ in 0..config.commands {
+        let started = Instant::now();
+        let result = timeout(config.timeout, async {
+            let connection = match &mut stream {
+                Some(connection) => connection,
+                None => stream.insert(TcpStream::connect(addr).await?),
+            };
+            send_command_and_receive(connection, &config.command).await
+        })
+        .await;
+
+        match result {
+            Ok(Ok(SocketResponse::Error { .. })) => failed += 1,
+            Ok(Ok(_)) => latencies.push(started.elapsed()),
+            Ok(Err(_)) | Err(_) => {
+                failed += 1;
+                stream = None;
+            }
+        }
+    }
+    (latencies, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(latencies_ms: &[u64]) -> LoadTestReport {
+        LoadTestReport {
+            clients: 1,
+            completed: latencies_ms.len(),
+            failed: 0,
+            elapsed: Duration::from_secs(2),
+            latencies: latencies_ms
+                .iter()
+                .map(|ms| Duration::from_millis(*ms))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn percentiles() {
+        let report = report(&(1..=100).collect::<Vec<_>>());
+        assert_eq!(report.percentile(50.0), Some(Duration::from_millis(50)));
+        assert_eq!(report.percentile(99.0), Some(Duration::from_millis(99)));
+        assert_eq!(report.percentile(0.0), Some(Duration::from_millis(1)));
+        assert_eq!(report.max_latency(), Some(Duration::from_millis(100)));
+        assert_eq!(report.throughput(), 50.0);
+
+        assert_eq!(self::report(&[]).percentile(50.0), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn load_emulator() {
+        let report = run(LoadTestConfig::new(8, 25)).await.unwrap();
+
+        assert_eq!(report.completed, 200);
+        assert_eq!(report.failed, 0);
+        assert!(report.percentile(95.0).unwrap() <= report.max_latency().unwrap());
+        assert!(report.to_string().contains("p99"));
+    }
+}
@@ -0,0 +1,205 @@
+//! Тепловая модель комнаты для эмулятора термометра
+//!
+//! Вместо случайного блуждания температура стремится к равновесной: к
+//! температуре окружающей среды с суточным циклом плюс вклад обогревателей
+//! и охладителей. Обогреватель связан с эмулятором розетки
+//! (`SocketEmulator::power_probe`), поэтому включение розетки командой
+//! контроллера действительно нагревает комнату. Скорость приближения к
+//! равновесию задает тепловая инерция - постоянная времени комнаты.
+
+use rand::Rng;
+use std::f64::consts::PI;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Миллисекунд в сутках
+const DAY_MS: u64 = 24 * 3600 * 1000;
+
+/// Источник текущей мощности устройства, Вт
+#[derive(Clone)]
+pub struct PowerProbe {
+    read: Arc<dyn Fn() -> f64 + Send + Sync>,
+}
+
+impl PowerProbe {
+    /// Мощность, которую возвращает `read`
+    pub fn new(read: impl Fn() -> f64 + Send + Sync + 'static) -> Self {
+        Self {
+            read: Arc::new(read),
+        }
+    }
+
+    /// Текущая мощность, Вт
+    pub fn power(&self) -> f64 {
+        (self.read)()
+    }
+}
+
+impl fmt::Debug for PowerProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PowerProbe({}W)", self.power())
+    }
+}
+
+/// Источник тепла или холода
+#[derive(Debug, Clone)]
+struct HeatSource {
+    probe: PowerProbe,
+    /// Сдвиг равновесной температуры на киловатт потребляемой мощности, °C
+    degrees_per_kw: f64,
+}
+
+/// Тепловая модель комнаты
+#[derive(Debug, Clone)]
+pub struct PhysicsModel {
+    /// Средняя температура окружающей среды, °C
+    ambient: f64,
+    /// Постоянная времени комнаты: за нее разница с равновесной
+    /// температурой уменьшается примерно в e раз
+    time_constant: Duration,
+    /// Амплитуда суточного цикла, °C
+    diurnal_amplitude: f64,
+    /// Самый теплый час суток (UTC)
+    warmest_hour: f64,
+    /// Амплитуда шума измерений, °C
+    noise: f64,
+    sources: Vec<HeatSource>,
+}
+
+impl PhysicsModel {
+    /// Модель комнаты без суточного цикла с окружающей температурой `ambient`
+    pub fn new(ambient: f64) -> Self {
+        Self {
+            ambient,
+            time_constant: Duration::from_secs(30 * 60),
+            diurnal_amplitude: 0.0,
+            warmest_hour: 15.0,
+            noise: 0.05,
+            sources: Vec::new(),
+        }
+    }
+
+    /// Builder: тепловая инерция (постоянная времени комнаты)
+    pub fn with_time_constant(mut self, time_constant: Duration) -> Self {
+        self.time_constant = time_constant;
+        self
+    }
+
+    /// Builder: суточный цикл с амплитудой `amplitude` и максимумом в `warmest_hour` (UTC)
+    pub fn with_diurnal_cycle(mut self, amplitude: f64, warmest_hour: f64) -> Self {
+        self.diurnal_amplitude = amplitude;
+        self.warmest_hour = warmest_hour;
+        self
+    }
+
+    /// Builder: амплитуда шума измерений
+    pub fn with_noise(mut self, noise: f64) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    /// Builder: обогреватель, поднимающий равновесную температуру на `degrees_per_kw`
+    pub fn with_heater(mut self, probe: PowerProbe, degrees_per_kw: f64) -> Self {
+        self.sources.push(HeatSource {
+            probe,
+            degrees_per_kw: degrees_per_kw.abs(),
+        });
+        self
+    }
+
+    /// Builder: охладитель, снижающий равновесную температуру на `degrees_per_kw`
+    pub fn with_cooler(mut self, probe: PowerProbe, degrees_per_kw: f64) -> Self {
+        self.sources.push(HeatSource {
+            probe,
+            degrees_per_kw: -degrees_per_kw.abs(),
+        });
+        self
+    }
+
+    /// Температура окружающей среды в момент `now_ms` с учетом суточного цикла
+    pub fn ambient_at(&self, now_ms: u64) -> f64 {
+        let hour = (now_ms % DAY_MS) as f64 / 3_600_000.0;
+        let phase = 2.0 * PI * (hour - self.warmest_hour) / 24.0;
+        self.ambient + self.diurnal_amplitude * phase.cos()
+    }
+
+    /// Равновесная температура в момент `now_ms` при текущей мощности источников
+    pub fn equilibrium_at(&self, now_ms: u64) -> f64 {
+        let sources: f64 = self
+            .sources
+            .iter()
+            .map(|source| source.probe.power() / 1000.0 * source.degrees_per_kw)
+            .sum();
+        self.ambient_at(now_ms) + sources
+    }
+
+    /// Температура через `elapsed` после `current` (момент `now_ms` - конец шага)
+    pub fn step<R: Rng>(&self, rng: &mut R, current: f64, elapsed: Duration, now_ms: u64) -> f64 {
+        let tau = self.time_constant.as_secs_f64();
+        let approach = if tau > 0.0 {
+            1.0 - (-elapsed.as_secs_f64() / tau).exp()
+        } else {
+            1.0
+        };
+        let noise = if self.noise > 0.0 {
+            rng.random_range(-self.noise..=self.noise)
+        } else {
+            0.0
+        };
+
+        current + (self.equilibrium_at(now_ms) - current) * approach + noise
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::sync::Mutex;
+
+    #[test]
+    fn approaches_ambient_with_inertia() {
+        let model = PhysicsModel::new(20.0)
+            .with_time_constant(Duration::from_secs(600))
+            .with_noise(0.0);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // За одну постоянную времени разница уменьшается в e раз
+        let after_tau = model.step(&mut rng, 30.0, Duration::from_secs(600), 0);
+        assert!((after_tau - (20.0 + 10.0 / std::f64::consts::E)).abs() < 1e-9);
+
+        let mut temp = 30.0;
+        for _ in 0..100 {
+            temp = model.step(&mut rng, temp, Duration::from_secs(60), 0);
+        }
+        assert!((temp - 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn diurnal_cycle() {
+        let model = PhysicsModel::new(10.0).with_diurnal_cycle(5.0, 15.0);
+        let hour = 3_600_000;
+
+        assert!((model.ambient_at(15 * hour) - 15.0).abs() < 1e-9);
+        assert!((model.ambient_at(3 * hour) - 5.0).abs() < 1e-9);
+        assert!((model.ambient_at(DAY_MS + 15 * hour) - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn heater_raises_equilibrium() {
+        let heater_power = Arc::new(Mutex::new(2000.0));
+        let heater = {
+            let power = Arc::clone(&heater_power);
+            PowerProbe::new(move || *power.lock().unwrap())
+        };
+        let model = PhysicsModel::new(18.0)
+            .with_heater(heater, 2.0)
+            .with_cooler(PowerProbe::new(|| 500.0), 4.0);
+        assert!((model.equilibrium_at(0) - 20.0).abs() < 1e-9);
+
+        *heater_power.lock().unwrap() = 0.0;
+        assert!((model.equilibrium_at(0) - 16.0).abs() < 1e-9);
+    }
+}
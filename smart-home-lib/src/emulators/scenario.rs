@@ -1,6 +1,8 @@
 //! Сценарии эмуляции термометра
 
 use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
 
 /// Сценарии эмуляции термометра
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -15,6 +17,32 @@ pub enum EmulationScenario {
     Fluctuate,
 }
 
+impl EmulationScenario {
+    /// Имя сценария в командной строке и JSON
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Fire => "fire",
+            Self::Freeze => "freeze",
+            Self::Fluctuate => "fluctuate",
+        }
+    }
+}
+
+impl FromStr for EmulationScenario {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "normal" => Ok(Self::Normal),
+            "fire" => Ok(Self::Fire),
+            "freeze" => Ok(Self::Freeze),
+            "fluctuate" => Ok(Self::Fluctuate),
+            other => Err(format!("Unknown scenario: '{}'", other)),
+        }
+    }
+}
+
 impl fmt::Display for EmulationScenario {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let description = match self {
@@ -27,6 +55,52 @@ impl fmt::Display for EmulationScenario {
     }
 }
 
+/// Расписание смены сценариев работающего эмулятора
+///
+/// Шаги задаются смещением от запуска эмулятора; каждый шаг применяется
+/// один раз, поэтому сценарий, выбранный вручную (`set_scenario`),
+/// действует до следующего шага.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScenarioSchedule {
+    /// Шаги (смещение от запуска, сценарий), упорядоченные по времени
+    steps: Vec<(Duration, EmulationScenario)>,
+}
+
+impl ScenarioSchedule {
+    /// Создает пустое расписание
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: переключиться на `scenario` через `offset` после запуска
+    pub fn at(mut self, offset: Duration, scenario: EmulationScenario) -> Self {
+        let index = self.steps.partition_point(|(at, _)| *at <= offset);
+        self.steps.insert(index, (offset, scenario));
+        self
+    }
+
+    /// Шаги расписания в порядке выполнения
+    pub fn steps(&self) -> &[(Duration, EmulationScenario)] {
+        &self.steps
+    }
+
+    /// Количество шагов, наступивших к моменту `elapsed` после запуска
+    pub fn due(&self, elapsed: Duration) -> usize {
+        self.steps.partition_point(|(at, _)| *at <= elapsed)
+    }
+
+    /// Сценарий по расписанию к моменту `elapsed` (None - шаги еще не наступили)
+    pub fn scenario_at(&self, elapsed: Duration) -> Option<EmulationScenario> {
+        let due = self.due(elapsed);
+        (due > 0).then(|| self.steps[due - 1].1)
+    }
+
+    /// В расписании нет шагов
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,6 +116,39 @@ mod tests {
         assert_eq!(format!("{}", EmulationScenario::Fluctuate), "📈 Колебания");
     }
 
+    #[test]
+    fn scenario_from_name() {
+        for scenario in [
+            EmulationScenario::Normal,
+            EmulationScenario::Fire,
+            EmulationScenario::Freeze,
+            EmulationScenario::Fluctuate,
+        ] {
+            assert_eq!(scenario.name().parse::<EmulationScenario>(), Ok(scenario));
+        }
+        assert_eq!("FIRE".parse(), Ok(EmulationScenario::Fire));
+        assert!("flood".parse::<EmulationScenario>().is_err());
+    }
+
+    #[test]
+    fn schedule_steps() {
+        let schedule = ScenarioSchedule::new()
+            .at(Duration::from_secs(20), EmulationScenario::Normal)
+            .at(Duration::from_secs(10), EmulationScenario::Fire);
+
+        assert_eq!(schedule.steps()[0].1, EmulationScenario::Fire);
+        assert_eq!(schedule.scenario_at(Duration::from_secs(5)), None);
+        assert_eq!(
+            schedule.scenario_at(Duration::from_secs(10)),
+            Some(EmulationScenario::Fire)
+        );
+        assert_eq!(
+            schedule.scenario_at(Duration::from_secs(60)),
+            Some(EmulationScenario::Normal)
+        );
+        assert_eq!(schedule.due(Duration::from_secs(15)), 1);
+    }
+
     #[test]
     fn scenario_debug() {
         assert_eq!(format!("{:?}", EmulationScenario::Normal), "Normal");
@@ -1,22 +1,66 @@
 //! Async эмулятор умной розетки для TCP тестирования
-
+//!
+//! С `EmulatorConfig::with_outlets` эмулятор представляет удлинитель:
+//! розетки включаются и выключаются по номеру, защита от перегрузки
+//! срабатывает по их суммарной мощности и отключает весь удлинитель.
+
+use super::admin::{AdminServer, AdminTarget, full_state};
+use super::emulator::{Emulator, EmulatorFuture};
+use super::fault::{Fault, FaultInjector};
+use super::physics::PowerProbe;
+use crate::clock::{SharedClock, system_clock};
+use crate::controllers::HealthStatus;
+use crate::ota::{DEFAULT_FIRMWARE_VERSION, FirmwareReceiver};
+use crate::protocol::bind::{self, IpStack};
 use crate::protocol::socket_protocol::{
-    SocketCommand, SocketData, SocketResponse, receive_command, send_response,
+    DEFAULT_MAX_MESSAGE_SIZE, DeviceDescriptor, ErrorCode, FrameReader, OutletData, SocketCommand,
+    SocketData, SocketResponse, send_message, send_response, send_traced_response,
 };
-use std::sync::atomic::{AtomicBool, Ordering};
+use crate::protocol::trace::CommandSpan;
+use crate::protocol::transport::{Connection, InMemory, InMemoryListener};
+use crate::traits::Lifecycle;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use tokio::net::{TcpListener, TcpStream};
-use tokio::task::JoinHandle;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, JoinSet};
+
+/// Время на завершение клиентов при остановке по умолчанию
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// Конфигурация эмулятора
 #[derive(Debug, Clone)]
 pub struct EmulatorConfig {
     /// Адрес для прослушивания TCP соединений
     pub bind_address: String,
+    /// Режим IPv6 слушающего сокета
+    pub ip_stack: IpStack,
     /// Номинальная мощность устройства (в ваттах)
     pub power_rating: f64,
     /// ID устройства для логирования
     pub device_id: String,
+    /// Предельная нагрузка (в ваттах), выше которой срабатывает защита
+    pub power_limit: Option<f64>,
+    /// Источник времени для учета потребленной энергии
+    pub clock: SharedClock,
+    /// Максимум одновременных клиентов (лишние получают отказ)
+    pub max_clients: Option<usize>,
+    /// Время бездействия, после которого соединение закрывается
+    pub idle_timeout: Option<Duration>,
+    /// Максимум команд в одном соединении
+    pub max_commands_per_connection: Option<u64>,
+    /// Максимальный размер команды, байт
+    pub max_message_size: usize,
+    /// Версия прошивки до обновлений
+    pub firmware_version: String,
+    /// Номинальные мощности розеток удлинителя (пусто - одиночная розетка)
+    pub outlet_ratings: Vec<f64>,
+    /// Сколько `stop` ждет завершения клиентов перед принудительным закрытием
+    pub shutdown_timeout: Duration,
 }
 
 impl EmulatorConfig {
@@ -24,8 +68,18 @@ impl EmulatorConfig {
     pub fn new(power_rating: f64) -> Self {
         Self {
             bind_address: "127.0.0.1:0".to_string(),
+            ip_stack: IpStack::default(),
             power_rating,
             device_id: "socket_emulator".to_string(),
+            power_limit: None,
+            clock: system_clock(),
+            max_clients: None,
+            idle_timeout: None,
+            max_commands_per_connection: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            firmware_version: DEFAULT_FIRMWARE_VERSION.to_string(),
+            outlet_ratings: Vec::new(),
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
         }
     }
 
@@ -35,19 +89,211 @@ impl EmulatorConfig {
         self
     }
 
+    /// Builder: Устанавливает режим IPv6 (`DualStack` на `[::]:port` принимает и IPv4)
+    pub fn with_ip_stack(mut self, stack: IpStack) -> Self {
+        self.ip_stack = stack;
+        self
+    }
+
     /// Builder: Устанавливает ID устройства
     pub fn with_device_id(mut self, device_id: &str) -> Self {
         self.device_id = device_id.to_string();
         self
     }
+
+    /// Builder: Устанавливает предельную нагрузку для защиты от перегрузки
+    pub fn with_power_limit(mut self, limit: f64) -> Self {
+        self.power_limit = Some(limit);
+        self
+    }
+
+    /// Builder: Устанавливает источник времени
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Builder: Ограничивает число одновременных клиентов
+    pub fn with_max_clients(mut self, max_clients: usize) -> Self {
+        self.max_clients = Some(max_clients);
+        self
+    }
+
+    /// Builder: Закрывает соединения без команд дольше `timeout`
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Builder: Ограничивает число команд в одном соединении
+    pub fn with_max_commands_per_connection(mut self, max_commands: u64) -> Self {
+        self.max_commands_per_connection = Some(max_commands);
+        self
+    }
+
+    /// Builder: Сколько `stop` ждет клиентов, дорабатывающих команду (по умолчанию 1 с)
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Builder: Устанавливает версию прошивки
+    pub fn with_firmware_version(mut self, version: &str) -> Self {
+        self.firmware_version = version.to_string();
+        self
+    }
+
+    /// Builder: Режим удлинителя с розетками заданной номинальной мощности
+    ///
+    /// Мощность удлинителя - сумма мощностей включенных розеток,
+    /// `power_rating` в этом режиме не используется.
+    pub fn with_outlets(mut self, ratings: &[f64]) -> Self {
+        self.outlet_ratings = ratings.to_vec();
+        self
+    }
+
+    /// Builder: Максимальный размер команды в байтах (по умолчанию 1 МБ)
+    pub fn with_max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = bytes;
+        self
+    }
+}
+
+/// Сессия подключенного клиента
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientSession {
+    /// Порядковый номер соединения
+    pub id: u64,
+    /// Адрес клиента
+    pub peer: SocketAddr,
+    /// Время подключения (мс)
+    pub connected_ms: u64,
+    /// Обработано команд в этом соединении
+    pub commands: u64,
+    /// Время последней команды (мс, 0 - команд еще не было)
+    pub last_command_ms: u64,
+}
+
+/// Счетчики соединений эмулятора
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// Принятые соединения
+    pub accepted: u64,
+    /// Отклоненные из-за лимита клиентов
+    pub rejected: u64,
+    /// Закрытые по таймауту бездействия
+    pub idle_timeouts: u64,
+    /// Закрытые по лимиту команд
+    pub command_limit_hits: u64,
+}
+
+/// Итог остановки эмулятора
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Клиенты, закрывшие соединение сами до таймаута
+    pub drained: usize,
+    /// Клиенты, задачи которых прерваны по таймауту
+    pub force_closed: usize,
+}
+
+/// Активные сессии и счетчики, общие для задач сервера
+#[derive(Default)]
+struct Sessions {
+    active: Mutex<HashMap<u64, ClientSession>>,
+    next_id: AtomicU64,
+    stats: Mutex<ConnectionStats>,
 }
 
+impl Sessions {
+    /// Регистрирует клиента, если лимит позволяет
+    fn open(&self, peer: SocketAddr, now_ms: u64, max_clients: Option<usize>) -> Option<u64> {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        if max_clients.is_some_and(|max| active.len() >= max) {
+            self.update_stats(|stats| stats.rejected += 1);
+            return None;
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        active.insert(
+            id,
+            ClientSession {
+                id,
+                peer,
+                connected_ms: now_ms,
+                commands: 0,
+                last_command_ms: 0,
+            },
+        );
+        self.update_stats(|stats| stats.accepted += 1);
+        Some(id)
+    }
+
+    /// Учитывает команду и возвращает их число в сессии
+    fn record_command(&self, id: u64, now_ms: u64) -> u64 {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        match active.get_mut(&id) {
+            Some(session) => {
+                session.commands += 1;
+                session.last_command_ms = now_ms;
+                session.commands
+            }
+            None => 0,
+        }
+    }
+
+    fn close(&self, id: u64) {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        active.remove(&id);
+    }
+
+    /// Закрывает все сессии (после остановки сервера)
+    fn close_all(&self) {
+        self.active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    fn update_stats(&self, f: impl FnOnce(&mut ConnectionStats)) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut stats);
+    }
+}
+
+/// Розетка удлинителя
+#[derive(Debug, Clone)]
+struct Outlet {
+    active: bool,
+    power_rating: f64, // В ваттах
+}
+
+/// Команды, которые понимает эмулятор (имена из протокола)
+const SUPPORTED_COMMANDS: [&str; 11] = [
+    "turn_on",
+    "turn_off",
+    "power",
+    "energy",
+    "set_load",
+    "ping",
+    "start_update",
+    "update_chunk",
+    "finish_update",
+    "update_status",
+    "describe",
+];
+
 /// Состояние эмулируемой розетки
 #[derive(Debug, Clone)]
 struct SocketState {
     active: bool,
     current_power: f64, // В ваттах
+    energy_wh: f64,     // Накопленная энергия в ватт-часах
+    last_update_ms: u64,
+    tripped: bool,    // Сработала защита от перегрузки
+    load_percent: u8, // Уровень нагрузки диммера (0-100%)
     device_id: Option<String>,
+    firmware: FirmwareReceiver, // Прошивка и прием ее обновления
+    outlets: Vec<Outlet>,       // Розетки удлинителя (пусто у одиночной розетки)
 }
 
 impl SocketState {
@@ -56,7 +302,13 @@ impl SocketState {
         Self {
             active: false,
             current_power: 0.0,
+            energy_wh: 0.0,
+            last_update_ms: 0,
+            tripped: false,
+            load_percent: 100,
             device_id: None,
+            firmware: FirmwareReceiver::new(DEFAULT_FIRMWARE_VERSION),
+            outlets: Vec::new(),
         }
     }
 
@@ -66,27 +318,215 @@ impl SocketState {
         self
     }
 
+    /// Builder: Режим удлинителя с розетками заданной мощности
+    fn with_outlets(mut self, ratings: &[f64]) -> Self {
+        self.outlets = ratings
+            .iter()
+            .map(|&power_rating| Outlet {
+                active: false,
+                power_rating,
+            })
+            .collect();
+        self
+    }
+
+    /// Builder: Устанавливает версию прошивки
+    fn with_firmware(mut self, version: &str) -> Self {
+        self.firmware = FirmwareReceiver::new(version);
+        self
+    }
+
+    /// Применяет команду обновления прошивки
+    fn update(
+        &mut self,
+        result: impl FnOnce(&mut FirmwareReceiver) -> Result<(), String>,
+    ) -> SocketResponse {
+        let before = self.firmware.firmware().to_string();
+        if let Err(message) = result(&mut self.firmware) {
+            return SocketResponse::error(ErrorCode::Internal, message);
+        }
+
+        if self.firmware.firmware() != before {
+            let id = self.device_id.as_deref().unwrap_or("socket");
+            println!(
+                "[{}] Firmware updated {} -> {}",
+                id,
+                before,
+                self.firmware.firmware()
+            );
+        }
+        SocketResponse::Update(self.firmware.progress())
+    }
+
     fn turn_on(&mut self, power_rating: f64) {
-        self.active = true;
-        self.current_power = power_rating;
+        if self.outlets.is_empty() {
+            self.active = true;
+            self.current_power = power_rating * self.load_percent as f64 / 100.0;
+        } else {
+            self.outlets
+                .iter_mut()
+                .for_each(|outlet| outlet.active = true);
+            self.refresh_outlets();
+        }
+
+        let id = self.device_id.as_deref().unwrap_or("socket");
+        println!("[{}] Socket turned ON - {}W", id, self.current_power);
+    }
+
+    /// Включает или выключает одну розетку удлинителя
+    fn switch_outlet(&mut self, outlet: u8, active: bool) -> Result<(), String> {
+        let count = self.outlets.len();
+        let Some(target) = self.outlets.get_mut(outlet as usize) else {
+            return Err(format!("Unknown outlet {} ({} outlets)", outlet, count));
+        };
+        target.active = active;
+        self.refresh_outlets();
+
+        let id = self.device_id.as_deref().unwrap_or("socket");
+        let state = if active { "ON" } else { "OFF" };
+        println!(
+            "[{}] Outlet {} turned {} - {}W total",
+            id, outlet, state, self.current_power
+        );
+        Ok(())
+    }
+
+    /// Пересчитывает состояние удлинителя по его розеткам
+    fn refresh_outlets(&mut self) {
+        let load = self.load_percent as f64 / 100.0;
+        self.active = self.outlets.iter().any(|outlet| outlet.active);
+        self.current_power = self
+            .outlets
+            .iter()
+            .filter(|outlet| outlet.active)
+            .map(|outlet| outlet.power_rating * load)
+            .sum();
+    }
+
+    /// Устанавливает уровень нагрузки диммера
+    fn set_load(&mut self, percent: u8, power_rating: f64) {
+        self.load_percent = percent;
+        if !self.outlets.is_empty() {
+            self.refresh_outlets();
+        } else if self.active {
+            self.current_power = power_rating * percent as f64 / 100.0;
+        }
 
         let id = self.device_id.as_deref().unwrap_or("socket");
-        println!("[{}] Socket turned ON - {}W", id, power_rating);
+        println!("[{}] Load set to {}%", id, percent);
     }
 
     fn turn_off(&mut self) {
+        self.outlets
+            .iter_mut()
+            .for_each(|outlet| outlet.active = false);
         self.active = false;
         self.current_power = 0.0;
+        self.tripped = false;
 
         let id = self.device_id.as_deref().unwrap_or("socket");
         println!("[{}] Socket turned OFF", id);
     }
 
+    /// Отключает питание из-за перегрузки (сбрасывается командой TurnOff)
+    fn trip(&mut self, load: f64, limit: f64) {
+        self.outlets
+            .iter_mut()
+            .for_each(|outlet| outlet.active = false);
+        self.active = false;
+        self.current_power = 0.0;
+        self.tripped = true;
+
+        let id = self.device_id.as_deref().unwrap_or("socket");
+        println!("[{}] Overload {}W > {}W - socket TRIPPED", id, load, limit);
+    }
+
+    /// Начисляет энергию, потребленную с момента последнего обновления
+    fn accumulate(&mut self, now_ms: u64) {
+        let elapsed_ms = now_ms.saturating_sub(self.last_update_ms);
+        if self.last_update_ms != 0 {
+            self.energy_wh += self.current_power * elapsed_ms as f64 / 3_600_000.0;
+        }
+        self.last_update_ms = now_ms;
+    }
+
+    /// Описание устройства; мощность удлинителя - сумма его розеток
+    fn descriptor(&self, power_rating: f64) -> DeviceDescriptor {
+        let (device_type, power_rating) = if self.outlets.is_empty() {
+            ("socket", power_rating)
+        } else {
+            let total = self.outlets.iter().map(|o| o.power_rating).sum();
+            ("power_strip", total)
+        };
+        DeviceDescriptor {
+            device_type: device_type.to_string(),
+            firmware: self.firmware.firmware().to_string(),
+            supported_commands: SUPPORTED_COMMANDS.iter().map(|c| c.to_string()).collect(),
+            power_rating,
+        }
+    }
+
+    /// Ответ с текущим состоянием (с учетом сработавшей защиты)
+    fn response(&self) -> SocketResponse {
+        if self.tripped {
+            SocketResponse::Tripped(self.to_data())
+        } else {
+            SocketResponse::Ok(self.to_data())
+        }
+    }
+
     fn to_data(&self) -> SocketData {
         SocketData {
             active: self.active,
             power: self.current_power,
+            energy_wh: self.energy_wh,
             device_id: self.device_id.clone(),
+            outlets: self.outlet_data(),
+        }
+    }
+
+    fn outlet_data(&self) -> Vec<OutletData> {
+        let load = self.load_percent as f64 / 100.0;
+        self.outlets
+            .iter()
+            .enumerate()
+            .map(|(index, outlet)| OutletData {
+                outlet: index as u8,
+                active: outlet.active,
+                power: if outlet.active {
+                    outlet.power_rating * load
+                } else {
+                    0.0
+                },
+            })
+            .collect()
+    }
+}
+
+/// Источник входящих соединений эмулятора (общий для TCP эмуляторов)
+pub(super) enum Listener {
+    Tcp(TcpListener),
+    Memory(InMemoryListener),
+}
+
+impl Listener {
+    pub(super) fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        match self {
+            Self::Tcp(listener) => listener.local_addr(),
+            Self::Memory(listener) => Ok(listener.local_addr()),
+        }
+    }
+
+    pub(super) async fn accept(&mut self) -> std::io::Result<(Box<dyn Connection>, SocketAddr)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                Ok((Box::new(stream), addr))
+            }
+            Self::Memory(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                Ok((Box::new(stream), addr))
+            }
         }
     }
 }
@@ -102,9 +542,13 @@ pub struct SocketEmulator {
     /// Флаг работы сервера
     running: Arc<AtomicBool>,
     /// Handle главной задачи сервера
-    server_handle: Option<JoinHandle<()>>,
+    server_handle: Option<JoinHandle<ShutdownReport>>,
     /// Канал для graceful shutdown
     shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+    /// Сессии подключенных клиентов
+    sessions: Arc<Sessions>,
+    /// Внедряемые неисправности
+    faults: FaultInjector,
 }
 
 impl SocketEmulator {
@@ -112,16 +556,76 @@ impl SocketEmulator {
     pub fn new(config: EmulatorConfig) -> Self {
         Self {
             state: Arc::new(Mutex::new(
-                SocketState::new().with_device_id(config.device_id.clone()),
+                SocketState::new()
+                    .with_device_id(config.device_id.clone())
+                    .with_firmware(&config.firmware_version)
+                    .with_outlets(&config.outlet_ratings),
             )),
-            config,
             bound_addr: None,
             running: Arc::new(AtomicBool::new(false)),
             server_handle: None,
             shutdown_tx: None,
+            sessions: Arc::new(Sessions::default()),
+            faults: FaultInjector::new(Arc::clone(&config.clock)),
+            config,
+        }
+    }
+
+    /// Переключатель неисправностей эмулятора
+    pub fn faults(&self) -> &FaultInjector {
+        &self.faults
+    }
+
+    /// Запускает административный HTTP интерфейс (`/state`, `/inject-fault`)
+    pub fn serve_admin(&self, bind_address: &str) -> std::io::Result<AdminServer> {
+        AdminServer::start(bind_address, Arc::new(self.admin_target()))
+    }
+
+    fn admin_target(&self) -> SocketAdmin {
+        SocketAdmin {
+            state: Arc::clone(&self.state),
+            config: self.config.clone(),
+            sessions: Arc::clone(&self.sessions),
+            faults: self.faults.clone(),
         }
     }
 
+    /// Текущая мощность розетки для тепловой модели (`PhysicsModel::with_heater`)
+    pub fn power_probe(&self) -> PowerProbe {
+        let state = Arc::clone(&self.state);
+        PowerProbe::new(move || state.lock().map(|state| state.current_power).unwrap_or(0.0))
+    }
+
+    /// Снимок активных сессий клиентов (по возрастанию id)
+    pub fn sessions(&self) -> Vec<ClientSession> {
+        let active = self
+            .sessions
+            .active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut sessions: Vec<_> = active.values().cloned().collect();
+        sessions.sort_by_key(|session| session.id);
+        sessions
+    }
+
+    /// Число подключенных клиентов
+    pub fn clients_count(&self) -> usize {
+        self.sessions
+            .active
+            .lock()
+            .map(|active| active.len())
+            .unwrap_or(0)
+    }
+
+    /// Счетчики соединений
+    pub fn connection_stats(&self) -> ConnectionStats {
+        *self
+            .sessions
+            .stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Возвращает локальный адрес TCP сервера (только после start)
     pub fn local_addr(&self) -> std::io::Result<std::net::SocketAddr> {
         self.bound_addr.ok_or_else(|| {
@@ -142,7 +646,34 @@ impl SocketEmulator {
         }
 
         // Bind TCP listener при старте
-        let listener = TcpListener::bind(&self.config.bind_address).await?;
+        let listener = bind::bind_tcp(self.config.bind_address.as_str(), self.config.ip_stack)?;
+        listener.set_nonblocking(true)?;
+        self.serve(Listener::Tcp(TcpListener::from_std(listener)?))
+    }
+
+    /// Запускает эмулятор на транспорте в памяти вместо TCP
+    ///
+    /// Эмулятор занимает `bind_address` в `transport`; контроллер с тем же
+    /// транспортом (`SocketController::with_transport`) подключается к
+    /// `local_addr()` без реальных портов.
+    pub async fn start_in_memory(&mut self, transport: &InMemory) -> std::io::Result<()> {
+        if self.is_running() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "Emulator already started",
+            ));
+        }
+
+        let addr = self
+            .config
+            .bind_address
+            .parse()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        self.serve(Listener::Memory(transport.listen(addr)))
+    }
+
+    /// Запускает прием клиентов на уже занятом адресе
+    fn serve(&mut self, mut listener: Listener) -> std::io::Result<()> {
         let bound_addr = listener.local_addr()?;
         println!("[SocketEmulator] Bound to {}", bound_addr);
 
@@ -156,6 +687,11 @@ impl SocketEmulator {
         let state = Arc::clone(&self.state);
         let running = Arc::clone(&self.running);
         let config = self.config.clone();
+        let sessions = Arc::clone(&self.sessions);
+        let faults = self.faults.clone();
+        // Задачи клиентов и сигнал им закончить работу при остановке
+        let mut clients = JoinSet::new();
+        let (closing_tx, closing_rx) = watch::channel(false);
 
         // Помечаем что запустились
         running.store(true, Ordering::Relaxed);
@@ -166,18 +702,50 @@ impl SocketEmulator {
 
             loop {
                 tokio::select! {
-                    // Принимаем TCP соединения
+                    // Принимаем соединения
                     result = listener.accept() => {
                         match result {
-                            Ok((stream, addr)) => {
+                            Ok((mut stream, addr)) => {
+                                let Some(session_id) =
+                                    sessions.open(addr, config.clock.now_ms(), config.max_clients)
+                                else {
+                                    // Как у настоящего устройства: отказ и закрытие соединения
+                                    println!("[SocketEmulator] Client {} rejected: too many clients", addr);
+                                    let rejection = SocketResponse::error(
+                                        ErrorCode::Busy,
+                                        format!(
+                                            "Too many clients (limit {})",
+                                            config.max_clients.unwrap_or_default()
+                                        ),
+                                    );
+                                    tokio::spawn(async move {
+                                        let _ = send_response(&mut stream, &rejection).await;
+                                    });
+                                    continue;
+                                };
                                 println!("[SocketEmulator] New client: {}", addr);
 
                                 let client_state = Arc::clone(&state);
                                 let client_config = config.clone();
+                                let client_sessions = Arc::clone(&sessions);
+                                let client_faults = faults.clone();
+                                let mut client_closing = closing_rx.clone();
 
                                 // Каждый клиент в отдельной async задаче
-                                tokio::spawn(async move {
-                                    if let Err(e) = Self::handle_client(stream, client_state, client_config).await {
+                                clients.spawn(async move {
+                                    let result = Self::handle_client(
+                                        stream,
+                                        client_state,
+                                        client_config,
+                                        &client_sessions,
+                                        session_id,
+                                        &client_faults,
+                                        &mut client_closing,
+                                    )
+                                    .await;
+                                    client_sessions.close(session_id);
+
+                                    if let Err(e) = result {
                                         println!("[SocketEmulator] Client {} error: {}", addr, e);
                                     } else {
                                         println!("[SocketEmulator] Client {} disconnected", addr);
@@ -190,6 +758,8 @@ impl SocketEmulator {
                             }
                         }
                     }
+                    // Убираем завершившиеся задачи клиентов
+                    Some(_) = clients.join_next(), if !clients.is_empty() => {}
                     // Ждем сигнал graceful shutdown
                     _ = &mut shutdown_rx => {
                         println!("[SocketEmulator] Shutdown signal received");
@@ -198,7 +768,14 @@ impl SocketEmulator {
                 }
             }
 
-            println!("[SocketEmulator] Server stopped");
+            let _ = closing_tx.send(true);
+            let report = Self::drain_clients(clients, config.shutdown_timeout).await;
+            sessions.close_all();
+            println!(
+                "[SocketEmulator] Server stopped ({} clients drained, {} force-closed)",
+                report.drained, report.force_closed
+            );
+            report
         });
 
         // Сохраняем handle
@@ -208,7 +785,11 @@ impl SocketEmulator {
     }
 
     /// Останавливает async сервер (graceful shutdown)
-    pub async fn stop(&mut self) {
+    ///
+    /// Сервер перестает принимать соединения, клиенты закрываются после
+    /// текущей команды. Задачи клиентов, не завершившиеся за
+    /// `shutdown_timeout`, прерываются; их число - в отчете.
+    pub async fn stop(&mut self) -> ShutdownReport {
         println!("[SocketEmulator] Stopping...");
         self.running.store(false, Ordering::Relaxed);
 
@@ -218,14 +799,35 @@ impl SocketEmulator {
         }
 
         // Graceful shutdown - ждем завершения задачи
+        let mut report = ShutdownReport::default();
         if let Some(handle) = self.server_handle.take() {
-            let _ = handle.await;
+            report = handle.await.unwrap_or_default();
         }
 
         // Очищаем адрес
         self.bound_addr = None;
 
         println!("[SocketEmulator] Stopped");
+        report
+    }
+
+    /// Ждет завершения клиентов до таймаута и прерывает оставшихся
+    async fn drain_clients(mut clients: JoinSet<()>, timeout: Duration) -> ShutdownReport {
+        let mut report = ShutdownReport::default();
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            match tokio::time::timeout_at(deadline, clients.join_next()).await {
+                Ok(Some(_)) => report.drained += 1,
+                Ok(None) => break,
+                Err(_) => {
+                    report.force_closed = clients.len();
+                    clients.shutdown().await;
+                    break;
+                }
+            }
+        }
+        report
     }
 
     /// Проверяет, запущен ли эмулятор
@@ -233,36 +835,96 @@ impl SocketEmulator {
         self.running.load(Ordering::Relaxed)
     }
 
-    /// Async обработка одного TCP клиента
+    /// Async обработка одного клиента
     async fn handle_client(
-        mut stream: TcpStream,
+        stream: Box<dyn Connection>,
         state: Arc<Mutex<SocketState>>,
         config: EmulatorConfig,
+        sessions: &Sessions,
+        session_id: u64,
+        faults: &FaultInjector,
+        closing: &mut watch::Receiver<bool>,
     ) -> std::io::Result<()> {
+        // Один читатель на соединение: байты после поврежденного кадра не теряются
+        let mut frames = FrameReader::new(stream);
         loop {
-            let command = match receive_command(&mut stream).await {
-                Ok(cmd) => cmd,
+            let receive = frames.receive_traced_command_with_limit(config.max_message_size);
+            let received = tokio::select! {
+                received = Self::within(config.idle_timeout, receive) => received,
+                // Сервер остановлен: соединение закрывается между командами
+                _ = closing.wait_for(|closing| *closing) => break,
+            };
+            let Some(received) = received else {
+                sessions.update_stats(|stats| stats.idle_timeouts += 1);
+                let idle = config.idle_timeout.unwrap_or_default();
+                let idle_response = SocketResponse::error(
+                    ErrorCode::Busy,
+                    format!("Idle timeout ({} ms)", idle.as_millis()),
+                );
+                let _ = send_response(frames.get_mut(), &idle_response).await;
+                break;
+            };
+
+            let (command, parent) = match received {
+                Ok(frame) => frame,
                 Err(e) => {
                     // Ошибка чтения команды (клиент отключился или невалидная команда)
-                    if e.kind() == std::io::ErrorKind::UnexpectedEof {
+                    if e.is_disconnect() {
                         // Клиент закрыл соединение
                         break;
                     }
 
                     // Невалидная команда - отправляем ошибку
-                    let error_response = SocketResponse::Error {
-                        message: format!("Invalid command: {}", e),
-                    };
+                    let error_response = SocketResponse::error(
+                        ErrorCode::Unsupported,
+                        format!("Invalid command: {}", e),
+                    );
 
                     // Пытаемся отправить ошибку (если stream еще жив)
-                    let _ = send_response(&mut stream, &error_response).await;
+                    let _ = send_response(frames.get_mut(), &error_response).await;
                     continue;
                 }
             };
 
+            let commands = sessions.record_command(session_id, config.clock.now_ms());
+            if let Some(max) = config.max_commands_per_connection
+                && commands > max
+            {
+                sessions.update_stats(|stats| stats.command_limit_hits += 1);
+                let limit_response = SocketResponse::error(
+                    ErrorCode::Busy,
+                    format!("Command limit reached ({} per connection)", max),
+                );
+                let _ = send_response(frames.get_mut(), &limit_response).await;
+                break;
+            }
+
+            match faults.active() {
+                Some(Fault::Unresponsive) => continue, // команда теряется
+                Some(Fault::Latency { ms }) => {
+                    tokio::time::sleep(Duration::from_millis(ms)).await;
+                }
+                Some(Fault::ErrorResponses { message }) => {
+                    let response = SocketResponse::error(ErrorCode::Internal, message);
+                    send_response(frames.get_mut(), &response).await?;
+                    continue;
+                }
+                Some(Fault::Corrupt) => {
+                    send_message(frames.get_mut(), "{\"result\":\"ok\",\"act").await?;
+                    continue;
+                }
+                None => {}
+            }
+
+            // Продолжаем трассу контроллера, если он передал traceparent
+            let mut span = CommandSpan::server("socket.handle", parent);
             let response = Self::process_command(command, &state, &config);
+            if let SocketResponse::Error { message, .. } = &response {
+                span.record_error(message);
+            }
 
-            if let Err(e) = send_response(&mut stream, &response).await {
+            let trace = parent.map(|_| *span.context());
+            if let Err(e) = send_traced_response(frames.get_mut(), &response, trace.as_ref()).await {
                 // Ошибка отправки - клиент отключился
                 println!("[SocketEmulator] Send error: {}", e);
                 break;
@@ -272,32 +934,197 @@ impl SocketEmulator {
         Ok(())
     }
 
+    /// Результат `future`, если он готов за `limit` (без лимита - всегда)
+    async fn within<F: Future>(limit: Option<Duration>, future: F) -> Option<F::Output> {
+        match limit {
+            Some(limit) => tokio::time::timeout(limit, future).await.ok(),
+            None => Some(future.await),
+        }
+    }
+
     /// Обрабатывает команду и возвращает ответ
     fn process_command(
         command: SocketCommand,
         state: &Arc<Mutex<SocketState>>,
         config: &EmulatorConfig,
     ) -> SocketResponse {
+        if command == SocketCommand::Ping {
+            return SocketResponse::Pong;
+        }
+
         let mut state_guard = match state.lock() {
             Ok(guard) => guard,
             Err(_) => {
-                return SocketResponse::Error {
-                    message: "Internal state lock error".to_string(),
-                };
+                return SocketResponse::error(ErrorCode::Internal, "Internal state lock error");
             }
         };
 
+        state_guard.accumulate(config.clock.now_ms());
+
         match command {
-            SocketCommand::TurnOn => {
-                state_guard.turn_on(config.power_rating);
-                SocketResponse::Ok(state_guard.to_data())
+            SocketCommand::TurnOn { outlet } => {
+                if state_guard.tripped {
+                    return state_guard.response();
+                }
+
+                match outlet {
+                    None => state_guard.turn_on(config.power_rating),
+                    Some(outlet) => {
+                        if let Err(message) = state_guard.switch_outlet(outlet, true) {
+                            return SocketResponse::error(ErrorCode::Unsupported, message);
+                        }
+                    }
+                }
+                Self::check_overload(&mut state_guard, config)
             }
-            SocketCommand::TurnOff => {
+            SocketCommand::TurnOff { outlet: None } => {
                 state_guard.turn_off();
-                SocketResponse::Ok(state_guard.to_data())
+                state_guard.response()
+            }
+            SocketCommand::TurnOff {
+                outlet: Some(outlet),
+            } => match state_guard.switch_outlet(outlet, false) {
+                Ok(()) => state_guard.response(),
+                Err(message) => SocketResponse::error(ErrorCode::Unsupported, message),
+            },
+            SocketCommand::SetLoad { percent } => {
+                if percent > 100 {
+                    return SocketResponse::error(
+                        ErrorCode::Unsupported,
+                        format!("Invalid load: {}% (expected 0-100)", percent),
+                    );
+                }
+
+                state_guard.set_load(percent, config.power_rating);
+                Self::check_overload(&mut state_guard, config)
+            }
+            SocketCommand::Power | SocketCommand::Energy => state_guard.response(),
+            SocketCommand::Ping => SocketResponse::Pong,
+            SocketCommand::StartUpdate {
+                version,
+                size,
+                checksum,
+            } => state_guard.update(|firmware| firmware.start(&version, size, checksum)),
+            SocketCommand::UpdateChunk { offset, data } => {
+                state_guard.update(|firmware| firmware.chunk(offset, &data))
             }
-            SocketCommand::Power => SocketResponse::Ok(state_guard.to_data()),
+            SocketCommand::FinishUpdate => state_guard.update(FirmwareReceiver::finish),
+            SocketCommand::UpdateStatus => state_guard.update(|_| Ok(())),
+            SocketCommand::Describe => {
+                SocketResponse::Descriptor(state_guard.descriptor(config.power_rating))
+            }
+        }
+    }
+
+    /// Срабатывание защиты, если текущая нагрузка превышает предел
+    fn check_overload(state: &mut SocketState, config: &EmulatorConfig) -> SocketResponse {
+        if let Some(limit) = config.power_limit
+            && state.current_power > limit
+        {
+            state.trip(state.current_power, limit);
         }
+
+        state.response()
+    }
+}
+
+/// Административный интерфейс розетки
+struct SocketAdmin {
+    state: Arc<Mutex<SocketState>>,
+    config: EmulatorConfig,
+    sessions: Arc<Sessions>,
+    faults: FaultInjector,
+}
+
+impl AdminTarget for SocketAdmin {
+    fn state(&self) -> Value {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = *self
+            .sessions
+            .stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let clients = self
+            .sessions
+            .active
+            .lock()
+            .map(|active| active.len())
+            .unwrap_or(0);
+
+        json!({
+            "device_id": self.config.device_id,
+            "active": state.active,
+            "power": state.current_power,
+            "power_rating": self.config.power_rating,
+            "energy_wh": state.energy_wh,
+            "load_percent": state.load_percent,
+            "tripped": state.tripped,
+            "firmware": state.firmware.firmware(),
+            "outlets": state.outlet_data(),
+            "clients": clients,
+            "connections": {
+                "accepted": stats.accepted,
+                "rejected": stats.rejected,
+                "idle_timeouts": stats.idle_timeouts,
+                "command_limit_hits": stats.command_limit_hits,
+            },
+        })
+    }
+
+    fn set_scenario(&self, scenario: &str) -> Result<(), String> {
+        Err(format!(
+            "Socket emulator has no scenarios (requested '{}')",
+            scenario
+        ))
+    }
+
+    fn faults(&self) -> &FaultInjector {
+        &self.faults
+    }
+}
+
+impl Lifecycle for SocketEmulator {
+    async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+        SocketEmulator::start(self).await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        SocketEmulator::stop(self).await;
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self.is_running(),
+            ..HealthStatus::default()
+        }
+    }
+}
+
+impl Emulator for SocketEmulator {
+    fn start(&mut self) -> EmulatorFuture<'_, std::io::Result<()>> {
+        Box::pin(Lifecycle::start(self))
+    }
+
+    fn stop(&mut self) -> EmulatorFuture<'_, ()> {
+        Box::pin(async move {
+            SocketEmulator::stop(self).await;
+        })
+    }
+
+    fn endpoint(&self) -> Option<SocketAddr> {
+        self.bound_addr
+    }
+
+    fn state(&self) -> Value {
+        full_state(&self.admin_target())
+    }
+
+    fn faults(&self) -> &FaultInjector {
+        &self.faults
     }
 }
 
@@ -376,7 +1203,11 @@ mod tests {
         let config = EmulatorConfig::new(1500.0);
 
         // Test TurnOn command
-        let response = SocketEmulator::process_command(SocketCommand::TurnOn, &state, &config);
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOn { outlet: None },
+            &state,
+            &config,
+        );
 
         if let SocketResponse::Ok(data) = response {
             assert!(data.active);
@@ -396,7 +1227,11 @@ mod tests {
         }
 
         // Test TurnOff command
-        let response = SocketEmulator::process_command(SocketCommand::TurnOff, &state, &config);
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOff { outlet: None },
+            &state,
+            &config,
+        );
 
         if let SocketResponse::Ok(data) = response {
             assert!(!data.active);
@@ -406,6 +1241,225 @@ mod tests {
         }
     }
 
+    #[test]
+    fn energy_accumulation() {
+        let mut state = SocketState::new();
+        state.accumulate(1_000);
+        state.turn_on(1000.0);
+
+        // Полчаса при 1000W = 500 Wh
+        state.accumulate(1_000 + 1_800_000);
+        assert!((state.energy_wh - 500.0).abs() < 1e-9);
+
+        state.turn_off();
+        state.accumulate(1_000 + 3_600_000);
+        assert!((state.energy_wh - 500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn energy_command_with_mock_clock() {
+        use crate::clock::MockClock;
+        use std::time::Duration;
+
+        let clock = Arc::new(MockClock::new());
+        let state = Arc::new(Mutex::new(SocketState::new()));
+        let config = EmulatorConfig::new(2000.0).with_clock(clock.clone());
+
+        SocketEmulator::process_command(SocketCommand::TurnOn { outlet: None }, &state, &config);
+        clock.advance(Duration::from_secs(3600));
+
+        match SocketEmulator::process_command(SocketCommand::Energy, &state, &config) {
+            SocketResponse::Ok(data) => assert!((data.energy_wh - 2000.0).abs() < 1e-9),
+            other => panic!("Expected Ok response, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn overload_trips_until_turned_off() {
+        let state = Arc::new(Mutex::new(SocketState::new()));
+        let config = EmulatorConfig::new(3000.0).with_power_limit(2500.0);
+
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOn { outlet: None },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Tripped(ref data) if !data.active));
+
+        // Защита держится до явного выключения
+        let response = SocketEmulator::process_command(SocketCommand::Power, &state, &config);
+        assert!(matches!(response, SocketResponse::Tripped(_)));
+
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOff { outlet: None },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Ok(_)));
+        assert!(!state.lock().unwrap().tripped);
+    }
+
+    #[test]
+    fn dimmer_load() {
+        let state = Arc::new(Mutex::new(SocketState::new()));
+        let config = EmulatorConfig::new(2000.0);
+
+        SocketEmulator::process_command(SocketCommand::SetLoad { percent: 40 }, &state, &config);
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOn { outlet: None },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Ok(ref data) if data.power == 800.0));
+
+        let response = SocketEmulator::process_command(
+            SocketCommand::SetLoad { percent: 75 },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Ok(ref data) if data.power == 1500.0));
+
+        let response = SocketEmulator::process_command(
+            SocketCommand::SetLoad { percent: 101 },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Error { .. }));
+    }
+
+    #[test]
+    fn dimmer_overload_trips() {
+        let state = Arc::new(Mutex::new(SocketState::new()));
+        let config = EmulatorConfig::new(3000.0).with_power_limit(2500.0);
+
+        // 50% от 3000W укладывается в предел
+        SocketEmulator::process_command(SocketCommand::SetLoad { percent: 50 }, &state, &config);
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOn { outlet: None },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Ok(ref data) if data.power == 1500.0));
+
+        // 90% - уже перегрузка
+        let response = SocketEmulator::process_command(
+            SocketCommand::SetLoad { percent: 90 },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Tripped(ref data) if !data.active));
+    }
+
+    #[test]
+    fn load_within_limit() {
+        let state = Arc::new(Mutex::new(SocketState::new()));
+        let config = EmulatorConfig::new(2000.0).with_power_limit(2500.0);
+
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOn { outlet: None },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Ok(ref data) if data.active));
+    }
+
+    #[test]
+    fn power_strip_outlets() {
+        let config = EmulatorConfig::new(0.0)
+            .with_outlets(&[100.0, 60.0, 2000.0])
+            .with_power_limit(1500.0);
+        let state = Arc::new(Mutex::new(
+            SocketState::new().with_outlets(&config.outlet_ratings),
+        ));
+        let command = |command| SocketEmulator::process_command(command, &state, &config);
+
+        let SocketResponse::Ok(data) = command(SocketCommand::TurnOn { outlet: Some(1) }) else {
+            panic!("Expected Ok response");
+        };
+        assert!(data.active);
+        assert_eq!(data.power, 60.0);
+        assert_eq!(data.outlets.len(), 3);
+        assert!(data.outlets[1].active && !data.outlets[0].active);
+
+        command(SocketCommand::TurnOn { outlet: Some(0) });
+        let SocketResponse::Ok(data) = command(SocketCommand::SetLoad { percent: 50 }) else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(data.power, 80.0);
+        assert_eq!(data.outlets[0].power, 50.0);
+
+        let SocketResponse::Ok(data) = command(SocketCommand::TurnOff { outlet: Some(1) }) else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(data.power, 50.0);
+
+        assert!(matches!(
+            command(SocketCommand::TurnOn { outlet: Some(7) }),
+            SocketResponse::Error { .. }
+        ));
+
+        // Суммарная перегрузка отключает весь удлинитель
+        command(SocketCommand::SetLoad { percent: 100 });
+        let SocketResponse::Tripped(data) = command(SocketCommand::TurnOn { outlet: Some(2) })
+        else {
+            panic!("Expected Tripped response");
+        };
+        assert!(!data.active);
+        assert!(data.outlets.iter().all(|outlet| !outlet.active));
+    }
+
+    #[test]
+    fn single_socket_has_no_outlets() {
+        let state = Arc::new(Mutex::new(SocketState::new()));
+        let config = EmulatorConfig::new(1000.0);
+
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOn { outlet: Some(0) },
+            &state,
+            &config,
+        );
+        assert!(matches!(
+            response,
+            SocketResponse::Error {
+                code: ErrorCode::Unsupported,
+                ..
+            }
+        ));
+        let response = SocketEmulator::process_command(
+            SocketCommand::TurnOn { outlet: None },
+            &state,
+            &config,
+        );
+        assert!(matches!(response, SocketResponse::Ok(ref data) if data.outlets.is_empty()));
+    }
+
+    #[test]
+    fn describe_device() {
+        let state = Arc::new(Mutex::new(SocketState::new()));
+        let config = EmulatorConfig::new(1000.0);
+        let SocketResponse::Descriptor(descriptor) =
+            SocketEmulator::process_command(SocketCommand::Describe, &state, &config)
+        else {
+            panic!("Expected Descriptor response");
+        };
+        assert_eq!(descriptor.device_type, "socket");
+        assert_eq!(descriptor.firmware, DEFAULT_FIRMWARE_VERSION);
+        assert_eq!(descriptor.power_rating, 1000.0);
+        assert!(descriptor.supports("set_load") && descriptor.supports("describe"));
+
+        let config = EmulatorConfig::new(0.0).with_outlets(&[100.0, 40.0]);
+        let state = Arc::new(Mutex::new(
+            SocketState::new().with_outlets(&config.outlet_ratings),
+        ));
+        let SocketResponse::Descriptor(descriptor) =
+            SocketEmulator::process_command(SocketCommand::Describe, &state, &config)
+        else {
+            panic!("Expected Descriptor response");
+        };
+        assert_eq!(descriptor.device_type, "power_strip");
+        assert_eq!(descriptor.power_rating, 140.0);
+    }
+
     #[tokio::test]
     #[ignore = "integration test with async TCP server"]
     async fn emulator_lifecycle() {
@@ -427,6 +1481,35 @@ mod tests {
         assert!(emulator.local_addr().is_err());
     }
 
+    #[tokio::test]
+    #[ignore = "integration test with async TCP server"]
+    async fn emulator_dual_stack() {
+        use crate::protocol::socket_protocol::{receive_response, send_command};
+
+        let config = EmulatorConfig::new(1000.0)
+            .with_address("[::]:0")
+            .with_ip_stack(IpStack::DualStack);
+        let mut emulator = SocketEmulator::new(config);
+        emulator.start().await.expect("Failed to start emulator");
+        let port = emulator.local_addr().unwrap().port();
+
+        // Один слушатель принимает IPv6 и IPv4 клиентов
+        for addr in [format!("[::1]:{}", port), format!("127.0.0.1:{}", port)] {
+            let mut stream = tokio::net::TcpStream::connect(&addr).await.unwrap();
+            send_command(&mut stream, &SocketCommand::Ping)
+                .await
+                .unwrap();
+            assert_eq!(
+                receive_response(&mut stream).await.unwrap(),
+                SocketResponse::Pong,
+                "{}",
+                addr
+            );
+        }
+
+        emulator.stop().await;
+    }
+
     #[tokio::test]
     #[ignore = "integration test with async TCP server"]
     async fn emulator_double_start_fails() {
@@ -471,7 +1554,7 @@ mod tests {
         // Test TurnOn command
         let response = timeout(
             Duration::from_secs(2),
-            send_command_and_receive(&mut client, &SocketCommand::TurnOn),
+            send_command_and_receive(&mut client, &SocketCommand::TurnOn { outlet: None }),
         )
         .await
         .expect("Command timeout")
@@ -504,7 +1587,7 @@ mod tests {
         // Test TurnOff command
         let response = timeout(
             Duration::from_secs(2),
-            send_command_and_receive(&mut client, &SocketCommand::TurnOff),
+            send_command_and_receive(&mut client, &SocketCommand::TurnOff { outlet: None }),
         )
         .await
         .expect("Command timeout")
@@ -541,9 +1624,10 @@ mod tests {
             .expect("Client2 connection failed");
 
         // Client1
-        let response1 = send_command_and_receive(&mut client1, &SocketCommand::TurnOn)
-            .await
-            .expect("Client1 command failed");
+        let response1 =
+            send_command_and_receive(&mut client1, &SocketCommand::TurnOn { outlet: None })
+                .await
+                .expect("Client1 command failed");
 
         if let SocketResponse::Ok(data) = response1 {
             assert!(data.active);
@@ -576,4 +1660,201 @@ mod tests {
 
         drop(emulator);
     }
+
+    #[test]
+    fn sessions_limit_and_counters() {
+        let sessions = Sessions::default();
+        let peer: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+
+        let first = sessions.open(peer, 10, Some(1)).unwrap();
+        assert!(sessions.open(peer, 11, Some(1)).is_none());
+
+        assert_eq!(sessions.record_command(first, 20), 1);
+        assert_eq!(sessions.record_command(first, 30), 2);
+        let session = sessions.active.lock().unwrap()[&first].clone();
+        assert_eq!(session.commands, 2);
+        assert_eq!(session.last_command_ms, 30);
+
+        sessions.close(first);
+        assert!(sessions.open(peer, 40, Some(1)).is_some());
+
+        let stats = *sessions.stats.lock().unwrap();
+        assert_eq!(stats.accepted, 2);
+        assert_eq!(stats.rejected, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn connection_limits() {
+        use crate::protocol::socket_protocol::{receive_response, send_command_and_receive};
+        use tokio::net::TcpStream;
+
+        let config = EmulatorConfig::new(1000.0)
+            .with_max_clients(1)
+            .with_idle_timeout(Duration::from_millis(200))
+            .with_max_commands_per_connection(2);
+        let mut emulator = SocketEmulator::new(config);
+        emulator.start().await.unwrap();
+        let addr = emulator.local_addr().unwrap();
+
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        send_command_and_receive(&mut first, &SocketCommand::Power)
+            .await
+            .unwrap();
+        assert_eq!(emulator.clients_count(), 1);
+        assert_eq!(emulator.sessions()[0].commands, 1);
+
+        // Второй клиент сверх лимита получает отказ
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let rejection = timeout(Duration::from_secs(1), receive_response(&mut second))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(rejection, SocketResponse::Error { code: ErrorCode::Busy, ref message } if message.contains("Too many clients"))
+        );
+
+        // Третья команда превышает лимит соединения
+        send_command_and_receive(&mut first, &SocketCommand::Power)
+            .await
+            .unwrap();
+        let response = send_command_and_receive(&mut first, &SocketCommand::Power)
+            .await
+            .unwrap();
+        assert!(
+            matches!(response, SocketResponse::Error { code: ErrorCode::Busy, ref message } if message.contains("Command limit"))
+        );
+
+        // Молчащий клиент отключается по таймауту
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut idle = TcpStream::connect(addr).await.unwrap();
+        let response = timeout(Duration::from_secs(1), receive_response(&mut idle))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(response, SocketResponse::Error { ref message, .. } if message.contains("Idle timeout"))
+        );
+
+        let stats = emulator.connection_stats();
+        assert_eq!(stats.rejected, 1);
+        assert_eq!(stats.command_limit_hits, 1);
+        assert_eq!(stats.idle_timeouts, 1);
+
+        emulator.stop().await;
+    }
+
+    #[test]
+    fn admin_state() {
+        use crate::emulators::admin::handle_request;
+
+        let emulator = SocketEmulator::new(EmulatorConfig::new(1200.0).with_device_id("kettle"));
+        let admin = emulator.admin_target();
+
+        let (status, state) = handle_request(&admin, "GET", "/state", "");
+        assert_eq!(status, 200);
+        assert_eq!(state["device_id"], "kettle");
+        assert_eq!(state["power_rating"], 1200.0);
+        assert_eq!(state["active"], false);
+        assert_eq!(state["fault"], Value::Null);
+
+        // Сценариев у розетки нет
+        let (status, _) = handle_request(&admin, "POST", "/scenario", r#"{"scenario":"fire"}"#);
+        assert_eq!(status, 400);
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn injected_faults() {
+        use crate::protocol::socket_protocol::send_command_and_receive;
+        use tokio::net::TcpStream;
+
+        let mut emulator = SocketEmulator::new(EmulatorConfig::new(1000.0));
+        emulator.start().await.unwrap();
+        let mut client = TcpStream::connect(emulator.local_addr().unwrap())
+            .await
+            .unwrap();
+
+        emulator.faults().inject(
+            Fault::ErrorResponses {
+                message: "relay stuck".to_string(),
+            },
+            None,
+        );
+        let response =
+            send_command_and_receive(&mut client, &SocketCommand::TurnOn { outlet: None })
+                .await
+                .unwrap();
+        assert_eq!(
+            response,
+            SocketResponse::error(ErrorCode::Internal, "relay stuck")
+        );
+
+        // Поврежденный ответ не разбирается
+        emulator.faults().inject(Fault::Corrupt, None);
+        assert!(
+            send_command_and_receive(&mut client, &SocketCommand::Power)
+                .await
+                .is_err()
+        );
+
+        // Молчащее устройство
+        emulator.faults().inject(Fault::Unresponsive, None);
+        assert!(
+            timeout(
+                Duration::from_millis(200),
+                send_command_and_receive(&mut client, &SocketCommand::Power)
+            )
+            .await
+            .is_err()
+        );
+
+        emulator.faults().clear();
+        emulator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn stop_closes_clients() {
+        use crate::protocol::socket_protocol::{
+            receive_response, send_command, send_command_and_receive,
+        };
+        use crate::protocol::transport::Transport;
+
+        let transport = InMemory::new();
+        let mut emulator = SocketEmulator::new(
+            EmulatorConfig::new(1000.0).with_shutdown_timeout(Duration::from_millis(100)),
+        );
+        emulator.start_in_memory(&transport).await.unwrap();
+        let addr = emulator.local_addr().unwrap();
+
+        // Ждущий команды клиент закрывается сразу
+        let mut idle = transport.connect(addr).await.unwrap();
+        send_command_and_receive(&mut idle, &SocketCommand::Ping)
+            .await
+            .unwrap();
+        // Клиент с командой, которая не успевает выполниться, прерывается
+        let mut slow = transport.connect(addr).await.unwrap();
+        send_command_and_receive(&mut slow, &SocketCommand::Ping)
+            .await
+            .unwrap();
+        emulator
+            .faults()
+            .inject(Fault::Latency { ms: 10_000 }, None);
+        send_command(&mut slow, &SocketCommand::Power).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(emulator.clients_count(), 2);
+
+        let report = timeout(Duration::from_secs(2), emulator.stop())
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ShutdownReport {
+                drained: 1,
+                force_closed: 1,
+            }
+        );
+        assert_eq!(emulator.clients_count(), 0);
+        assert!(receive_response(&mut idle).await.is_err());
+    }
 }
@@ -1,37 +1,75 @@
 //! Простой эмулятор термометра
 
-use super::scenario::EmulationScenario;
-use crate::protocol::ThermData;
-use rand::Rng;
-use serde_json;
-use std::net::UdpSocket;
-use std::sync::Arc;
+use super::admin::{AdminServer, AdminTarget, full_state};
+use super::emulator::{Emulator, EmulatorFuture};
+use super::fault::{Fault, FaultInjector};
+use super::physics::PhysicsModel;
+use super::scenario::{EmulationScenario, ScenarioSchedule};
+use crate::clock::{SharedClock, system_clock};
+use crate::controllers::HealthStatus;
+use crate::protocol::{ThermData, bind};
+use crate::traits::Lifecycle;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{self, Value, json};
+use std::net::{SocketAddr, UdpSocket};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
+/// Максимальный сон потока между проверками флага остановки
+const MAX_SLEEP: Duration = Duration::from_millis(100);
+
+/// Состояние работающего эмулятора, изменяемое извне потока
+#[derive(Debug, Clone)]
+struct LiveState {
+    scenario: EmulationScenario,
+    /// Последняя отправленная температура
+    temperature: Option<f64>,
+    /// Отправлено показаний
+    sent: u64,
+}
+
 /// Простой эмулятор термометра
 pub struct ThermEmulator {
     initial_temp: f64,
     device_id: Option<String>,
     scenario: EmulationScenario,
+    schedule: ScenarioSchedule,
+    physics: Option<PhysicsModel>,
     interval: Duration,
+    seed: Option<u64>,
+    clock: SharedClock,
     target_addr: Option<String>,
     running: Arc<AtomicBool>,
     thread_handle: Option<JoinHandle<()>>,
+    live: Arc<Mutex<LiveState>>,
+    faults: FaultInjector,
 }
 
 impl ThermEmulator {
     /// Создает новый эмулятор
     pub fn new(initial_temp: f64) -> Self {
+        let clock = system_clock();
         Self {
             initial_temp,
             device_id: None,
             scenario: EmulationScenario::Normal,
+            schedule: ScenarioSchedule::new(),
+            physics: None,
             interval: Duration::from_secs(1),
+            seed: None,
+            faults: FaultInjector::new(Arc::clone(&clock)),
+            clock,
             target_addr: None,
             running: Arc::new(AtomicBool::new(false)),
             thread_handle: None,
+            live: Arc::new(Mutex::new(LiveState {
+                scenario: EmulationScenario::Normal,
+                temperature: None,
+                sent: 0,
+            })),
         }
     }
 
@@ -44,21 +82,93 @@ impl ThermEmulator {
     /// Builder: устанавливает сценарий
     pub fn with_scenario(mut self, scenario: EmulationScenario) -> Self {
         self.scenario = scenario;
+        self.live_state().scenario = scenario;
+        self
+    }
+
+    /// Builder: расписание смены сценариев после запуска
+    pub fn with_schedule(mut self, schedule: ScenarioSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Builder: тепловая модель вместо случайного блуждания температуры
+    ///
+    /// Модель действует в сценарии `Normal`; аварийные сценарии (пожар,
+    /// заморозка, колебания) по-прежнему меняют температуру сами.
+    pub fn with_physics(mut self, physics: PhysicsModel) -> Self {
+        self.physics = Some(physics);
         self
     }
 
+    /// Меняет сценарий, в том числе работающего эмулятора
+    ///
+    /// Новый сценарий действует со следующего показания и до следующего
+    /// шага расписания, если оно задано.
+    pub fn set_scenario(&self, scenario: EmulationScenario) {
+        self.live_state().scenario = scenario;
+        println!("[ThermEmulator] Scenario switched to {}", scenario);
+    }
+
+    /// Текущий сценарий
+    pub fn scenario(&self) -> EmulationScenario {
+        self.live_state().scenario
+    }
+
     /// Builder: устанавливает интервал обновления
     pub fn with_update_interval(mut self, interval: Duration) -> Self {
         self.interval = interval;
         self
     }
 
+    /// Builder: фиксирует seed генератора случайных чисел для воспроизводимых прогонов
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Builder: устанавливает источник времени для интервалов обновления
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.faults = FaultInjector::new(Arc::clone(&clock));
+        self.clock = clock;
+        self
+    }
+
     /// Устанавливает адрес для отправки данных
     pub fn connect_to(&mut self, addr: &str) -> Result<(), std::io::Error> {
         self.target_addr = Some(addr.to_string());
         Ok(())
     }
 
+    /// Переключатель неисправностей эмулятора
+    pub fn faults(&self) -> &FaultInjector {
+        &self.faults
+    }
+
+    /// Запускает административный HTTP интерфейс (`/state`, `/scenario`, `/inject-fault`)
+    pub fn serve_admin(&self, bind_address: &str) -> std::io::Result<AdminServer> {
+        AdminServer::start(bind_address, Arc::new(self.admin_target()))
+    }
+
+    fn admin_target(&self) -> ThermAdmin {
+        ThermAdmin {
+            device_id: self.device_id.clone(),
+            target_addr: self.target_addr.clone(),
+            running: Arc::clone(&self.running),
+            live: Arc::clone(&self.live),
+            faults: self.faults.clone(),
+        }
+    }
+
+    fn live_state(&self) -> std::sync::MutexGuard<'_, LiveState> {
+        self.live.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Проверяет, работает ли поток эмуляции
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
     /// Запускает поток эмуляции
     pub fn start(&mut self) {
         if self.running.load(Ordering::Relaxed) {
@@ -70,13 +180,25 @@ impl ThermEmulator {
         let running = Arc::clone(&self.running);
         let target_addr = self.target_addr.clone();
         let device_id = self.device_id.clone();
-        let scenario = self.scenario;
-        let interval = self.interval;
+        let live = Arc::clone(&self.live);
+        let faults = self.faults.clone();
+        let interval_ms = (self.interval.as_millis() as u64).max(1);
+        let clock = Arc::clone(&self.clock);
+        let schedule = self.schedule.clone();
+        let physics = self.physics.clone();
+        let started_at = clock.now_ms();
+        let mut applied_steps = 0;
+        let mut next_tick = started_at;
         let mut current_temp = self.initial_temp;
+        let mut rng = Self::make_rng(self.seed);
 
         let handle = thread::spawn(move || {
-            // Создаем UDP сокет для отправки
-            let socket = match UdpSocket::bind("0.0.0.0:0") {
+            // Создаем UDP сокет для отправки того же семейства адресов, что получатель
+            let local = target_addr
+                .as_deref()
+                .and_then(|addr| bind::resolve_first(addr).ok())
+                .map_or(SocketAddr::from(([0, 0, 0, 0], 0)), bind::unspecified_for);
+            let socket = match UdpSocket::bind(local) {
                 Ok(s) => s,
                 Err(e) => {
                     eprintln!("[ThermEmulator] UDP socket error: {}", e);
@@ -85,16 +207,68 @@ impl ThermEmulator {
             };
 
             while running.load(Ordering::Relaxed) {
-                // Обновляем температуру согласно сценарию
-                current_temp = Self::update_temperature(current_temp, scenario);
+                // Ждем следующего такта по часам эмулятора
+                let now = clock.now_ms();
+                if now < next_tick {
+                    clock.sleep(Duration::from_millis(next_tick - now).min(MAX_SLEEP));
+                    continue;
+                }
 
-                // Отправляем данные по UDP
+                // Наступивший шаг расписания меняет сценарий
+                let elapsed = Duration::from_millis(now - started_at);
+                let due = schedule.due(elapsed);
+                if due > applied_steps {
+                    applied_steps = due;
+                    if let (Some(scenario), Ok(mut live)) =
+                        (schedule.scenario_at(elapsed), live.lock())
+                    {
+                        live.scenario = scenario;
+                        println!("[ThermEmulator] Scheduled scenario: {}", scenario);
+                    }
+                }
+
+                // Обновляем температуру согласно сценарию (его можно сменить на ходу)
+                let scenario = live
+                    .lock()
+                    .map_or(EmulationScenario::Normal, |l| l.scenario);
+                current_temp = match (&physics, scenario) {
+                    (Some(physics), EmulationScenario::Normal) => physics.step(
+                        &mut rng,
+                        current_temp,
+                        Duration::from_millis(interval_ms),
+                        next_tick,
+                    ),
+                    _ => Self::update_temperature(&mut rng, current_temp, scenario),
+                };
+
+                // Отправляем данные по UDP с учетом внедренной неисправности
                 if let Some(ref addr) = target_addr {
-                    let _ =
-                        Self::send_temperature_data(&socket, addr, current_temp, device_id.clone());
+                    let sent = match faults.active() {
+                        Some(Fault::Unresponsive) => false,
+                        Some(Fault::Corrupt) => socket.send_to(b"{\"temperature\":", addr).is_ok(),
+                        fault => {
+                            if let Some(Fault::Latency { ms }) = fault {
+                                thread::sleep(Duration::from_millis(ms));
+                            }
+                            Self::send_temperature_data(
+                                &socket,
+                                addr,
+                                current_temp,
+                                device_id.as_deref(),
+                            )
+                            .is_ok()
+                        }
+                    };
+
+                    if sent && let Ok(mut live) = live.lock() {
+                        live.temperature = Some(current_temp);
+                        live.sent += 1;
+                    }
                 }
 
-                thread::sleep(interval);
+                // Такты отсчитываются от расписания, а не от текущего времени,
+                // поэтому перемотка часов выполняет все пропущенные шаги
+                next_tick += interval_ms;
             }
 
             println!("[ThermEmulator] Server stopped");
@@ -115,10 +289,20 @@ impl ThermEmulator {
         println!("[ThermEmulator] Stopped");
     }
 
-    /// Обновляет температуру согласно сценарию
-    fn update_temperature(current_temp: f64, scenario: EmulationScenario) -> f64 {
-        let mut rng = rand::rng();
+    /// Создает генератор: детерминированный при заданном seed, иначе из энтропии ОС
+    fn make_rng(seed: Option<u64>) -> StdRng {
+        match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        }
+    }
 
+    /// Обновляет температуру согласно сценарию
+    fn update_temperature<R: Rng>(
+        rng: &mut R,
+        current_temp: f64,
+        scenario: EmulationScenario,
+    ) -> f64 {
         match scenario {
             EmulationScenario::Normal => {
                 // Небольшие случайные колебания ±0.5°C
@@ -144,11 +328,11 @@ impl ThermEmulator {
         socket: &UdpSocket,
         addr: &str,
         temperature: f64,
-        device_id: Option<String>,
+        device_id: Option<&str>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let data = ThermData {
             temperature,
-            device_id,
+            device_id: device_id.map(Into::into),
         };
 
         let json_data = serde_json::to_string(&data)?;
@@ -159,6 +343,93 @@ impl ThermEmulator {
     }
 }
 
+/// Административный интерфейс термометра
+struct ThermAdmin {
+    device_id: Option<String>,
+    target_addr: Option<String>,
+    running: Arc<AtomicBool>,
+    live: Arc<Mutex<LiveState>>,
+    faults: FaultInjector,
+}
+
+impl AdminTarget for ThermAdmin {
+    fn state(&self) -> Value {
+        let live = self.live.lock().unwrap_or_else(|e| e.into_inner());
+        json!({
+            "device_id": self.device_id,
+            "target": self.target_addr,
+            "running": self.running.load(Ordering::Relaxed),
+            "scenario": live.scenario.name(),
+            "temperature": live.temperature,
+            "sent": live.sent,
+        })
+    }
+
+    fn set_scenario(&self, scenario: &str) -> Result<(), String> {
+        let scenario: EmulationScenario = scenario.parse()?;
+        self.live.lock().unwrap_or_else(|e| e.into_inner()).scenario = scenario;
+        println!("[ThermEmulator] Scenario switched to {}", scenario);
+        Ok(())
+    }
+
+    fn faults(&self) -> &FaultInjector {
+        &self.faults
+    }
+
+    fn supports_fault(&self, fault: &Fault) -> bool {
+        // Термометр только отправляет данные, ответов на команды у него нет
+        !matches!(fault, Fault::ErrorResponses { .. })
+    }
+}
+
+impl Lifecycle for ThermEmulator {
+    async fn start(&mut self) -> std::io::Result<()> {
+        if !self.is_running() {
+            ThermEmulator::start(self);
+        }
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        ThermEmulator::stop(self);
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self.is_running(),
+            ..HealthStatus::default()
+        }
+    }
+}
+
+impl Emulator for ThermEmulator {
+    fn start(&mut self) -> EmulatorFuture<'_, std::io::Result<()>> {
+        Box::pin(Lifecycle::start(self))
+    }
+
+    fn stop(&mut self) -> EmulatorFuture<'_, ()> {
+        Box::pin(async move { ThermEmulator::stop(self) })
+    }
+
+    /// Адрес получателя показаний (после `connect_to`)
+    fn endpoint(&self) -> Option<SocketAddr> {
+        self.target_addr.as_deref()?.parse().ok()
+    }
+
+    fn state(&self) -> Value {
+        full_state(&self.admin_target())
+    }
+
+    fn faults(&self) -> &FaultInjector {
+        &self.faults
+    }
+
+    fn supports_fault(&self, fault: &Fault) -> bool {
+        self.admin_target().supports_fault(fault)
+    }
+}
+
 impl Drop for ThermEmulator {
     fn drop(&mut self) {
         self.stop();
@@ -177,6 +448,7 @@ mod tests {
         assert_eq!(emulator.device_id, None);
         assert!(matches!(emulator.scenario, EmulationScenario::Normal));
         assert_eq!(emulator.interval, Duration::from_secs(1));
+        assert_eq!(emulator.seed, None);
         assert_eq!(emulator.target_addr, None);
         assert!(!emulator.running.load(Ordering::Relaxed));
     }
@@ -194,6 +466,95 @@ mod tests {
         assert!(matches!(emulator.scenario, EmulationScenario::Fire));
     }
 
+    #[test]
+    fn set_scenario_updates_live_state() {
+        let emulator = ThermEmulator::new(18.0).with_scenario(EmulationScenario::Fire);
+        assert_eq!(emulator.scenario(), EmulationScenario::Fire);
+
+        emulator.set_scenario(EmulationScenario::Normal);
+        assert_eq!(emulator.scenario(), EmulationScenario::Normal);
+        assert_eq!(emulator.admin_target().state()["scenario"], "normal");
+    }
+
+    #[test]
+    #[ignore = "integration test with threading"]
+    fn integration_schedule_switches_scenarios() {
+        use crate::clock::MockClock;
+
+        let clock = Arc::new(MockClock::new());
+        let schedule = ScenarioSchedule::new()
+            .at(Duration::from_secs(10), EmulationScenario::Fire)
+            .at(Duration::from_secs(20), EmulationScenario::Normal);
+        let mut emulator = ThermEmulator::new(20.0)
+            .with_schedule(schedule)
+            .with_clock(clock.clone());
+        emulator.start();
+
+        let wait_for = |scenario| {
+            for _ in 0..100 {
+                if emulator.scenario() == scenario {
+                    return true;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            false
+        };
+
+        clock.advance(Duration::from_secs(10));
+        assert!(wait_for(EmulationScenario::Fire));
+        clock.advance(Duration::from_secs(10));
+        assert!(wait_for(EmulationScenario::Normal));
+
+        // Ручное переключение действует до следующего шага (его нет)
+        emulator.set_scenario(EmulationScenario::Freeze);
+        clock.advance(Duration::from_secs(10));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(emulator.scenario(), EmulationScenario::Freeze);
+        emulator.stop();
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn integration_physics_follows_heater() {
+        use crate::clock::MockClock;
+        use crate::controllers::SocketController;
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+
+        let mut heater =
+            SocketEmulator::new(EmulatorConfig::new(2000.0).with_address("127.0.0.1:0"));
+        heater.start().await.unwrap();
+        let mut controller =
+            SocketController::new(heater.local_addr().unwrap(), 2000.0, Duration::from_secs(1));
+        controller.turn_on().await.unwrap();
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .unwrap();
+        let physics = PhysicsModel::new(15.0)
+            .with_time_constant(Duration::from_secs(60))
+            .with_noise(0.0)
+            .with_heater(heater.power_probe(), 5.0);
+        let mut emulator = ThermEmulator::new(15.0)
+            .with_update_interval(Duration::from_secs(60))
+            .with_physics(physics)
+            .with_clock(Arc::new(MockClock::new()));
+        emulator
+            .connect_to(&receiver.local_addr().unwrap().to_string())
+            .unwrap();
+        emulator.start();
+
+        // Включенный обогреватель 2 кВт поднимает равновесие до 25°C
+        let mut buf = [0u8; 256];
+        let (size, _) = receiver.recv_from(&mut buf).unwrap();
+        let data: ThermData = serde_json::from_slice(&buf[..size]).unwrap();
+        let expected = 25.0 - 10.0 / std::f64::consts::E;
+        assert!((data.temperature - expected).abs() < 1e-9);
+
+        emulator.stop();
+        heater.stop().await;
+    }
+
     #[test]
     fn builder_pattern_interval() {
         let custom_interval = Duration::from_millis(500);
@@ -214,6 +575,33 @@ mod tests {
         assert_eq!(emulator.interval, Duration::from_millis(200));
     }
 
+    #[test]
+    fn builder_pattern_seed() {
+        let emulator = ThermEmulator::new(21.0).with_seed(42);
+        assert_eq!(emulator.seed, Some(42));
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let run = |seed| {
+            let mut rng = ThermEmulator::make_rng(Some(seed));
+            let mut temp = 20.0;
+            (0..20)
+                .map(|_| {
+                    temp = ThermEmulator::update_temperature(
+                        &mut rng,
+                        temp,
+                        EmulationScenario::Fluctuate,
+                    );
+                    temp
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
     #[test]
     fn connect_to_sets_target_address() {
         let mut emulator = ThermEmulator::new(22.0);
@@ -225,10 +613,14 @@ mod tests {
     #[test]
     fn update_temperature_normal_scenario() {
         let initial_temp = 20.0;
+        let mut rng = rand::rng();
         // Тестируем несколько итераций
         for _ in 0..10 {
-            let new_temp =
-                ThermEmulator::update_temperature(initial_temp, EmulationScenario::Normal);
+            let new_temp = ThermEmulator::update_temperature(
+                &mut rng,
+                initial_temp,
+                EmulationScenario::Normal,
+            );
             assert!(new_temp >= initial_temp - 0.5);
             assert!(new_temp <= initial_temp + 0.5);
         }
@@ -237,8 +629,10 @@ mod tests {
     #[test]
     fn update_temperature_fire_scenario() {
         let initial_temp = 20.0;
+        let mut rng = rand::rng();
         for _ in 0..10 {
-            let new_temp = ThermEmulator::update_temperature(initial_temp, EmulationScenario::Fire);
+            let new_temp =
+                ThermEmulator::update_temperature(&mut rng, initial_temp, EmulationScenario::Fire);
             assert!(new_temp >= initial_temp + 1.0);
             assert!(new_temp <= initial_temp + 3.0);
         }
@@ -247,9 +641,13 @@ mod tests {
     #[test]
     fn update_temperature_freeze_scenario() {
         let initial_temp = 20.0;
+        let mut rng = rand::rng();
         for _ in 0..10 {
-            let new_temp =
-                ThermEmulator::update_temperature(initial_temp, EmulationScenario::Freeze);
+            let new_temp = ThermEmulator::update_temperature(
+                &mut rng,
+                initial_temp,
+                EmulationScenario::Freeze,
+            );
             assert!(new_temp >= initial_temp - 3.0);
             assert!(new_temp <= initial_temp - 1.0);
         }
@@ -258,9 +656,13 @@ mod tests {
     #[test]
     fn update_temperature_fluctuate_scenario() {
         let initial_temp = 20.0;
+        let mut rng = rand::rng();
         for _ in 0..10 {
-            let new_temp =
-                ThermEmulator::update_temperature(initial_temp, EmulationScenario::Fluctuate);
+            let new_temp = ThermEmulator::update_temperature(
+                &mut rng,
+                initial_temp,
+                EmulationScenario::Fluctuate,
+            );
             assert!(new_temp >= initial_temp - 2.0);
             assert!(new_temp <= initial_temp + 2.0);
         }
@@ -271,7 +673,7 @@ mod tests {
         // Тестируем только сериализацию, без сетевых операций
         let data = ThermData {
             temperature: 23.5,
-            device_id: Some("test_device".to_string()),
+            device_id: Some("test_device".into()),
         };
 
         let json = serde_json::to_string(&data).expect("Failed to serialize");
@@ -280,7 +682,7 @@ mod tests {
 
         let parsed: ThermData = serde_json::from_str(&json).expect("Failed to deserialize");
         assert_eq!(parsed.temperature, 23.5);
-        assert_eq!(parsed.device_id, Some("test_device".to_string()));
+        assert_eq!(parsed.device_id.as_deref(), Some("test_device"));
     }
 
     #[test]
@@ -339,7 +741,7 @@ mod tests {
             &socket,
             &receiver_addr.to_string(),
             test_temp,
-            test_device_id.clone(),
+            test_device_id.as_deref(),
         );
         assert!(result.is_ok());
 
@@ -354,7 +756,7 @@ mod tests {
                 serde_json::from_str(received_json).expect("Failed to parse JSON");
 
             assert_eq!(parsed.temperature, test_temp);
-            assert_eq!(parsed.device_id, test_device_id);
+            assert_eq!(parsed.device_id.as_deref(), test_device_id.as_deref());
         }
     }
 
@@ -382,6 +784,40 @@ mod tests {
         assert!(!emulator.running.load(Ordering::Relaxed));
     }
 
+    #[test]
+    #[ignore = "integration test with networking"]
+    fn integration_mock_clock_fast_forward() {
+        use crate::clock::MockClock;
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").expect("Failed to bind receiver");
+        receiver
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .ok();
+        let receiver_addr = receiver.local_addr().unwrap().to_string();
+
+        let clock = Arc::new(MockClock::new());
+        let mut emulator = ThermEmulator::new(20.0)
+            .with_update_interval(Duration::from_secs(1))
+            .with_clock(clock.clone());
+        emulator.connect_to(&receiver_addr).unwrap();
+        emulator.start();
+
+        // Минута симуляции без реального ожидания
+        clock.advance(Duration::from_secs(59));
+
+        let mut buf = [0; 1024];
+        let mut received = 0;
+        while receiver.recv_from(&mut buf).is_ok() {
+            received += 1;
+            if received == 60 {
+                break;
+            }
+        }
+
+        emulator.stop();
+        assert_eq!(received, 60);
+    }
+
     #[test]
     #[ignore = "integration test with threading"]
     #[should_panic(expected = "Emulator already running!")]
@@ -394,4 +830,65 @@ mod tests {
         emulator.start();
         emulator.start(); // Должно вызвать панику
     }
+
+    #[test]
+    fn admin_switches_scenario_and_faults() {
+        use crate::emulators::admin::handle_request;
+
+        let emulator = ThermEmulator::new(21.0).with_device_id("therm_admin");
+        let admin = emulator.admin_target();
+
+        let (status, _) = handle_request(&admin, "POST", "/scenario", r#"{"scenario":"fire"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(emulator.live_state().scenario, EmulationScenario::Fire);
+
+        let (status, state) = handle_request(&admin, "GET", "/state", "");
+        assert_eq!(status, 200);
+        assert_eq!(state["scenario"], "fire");
+        assert_eq!(state["device_id"], "therm_admin");
+        assert_eq!(state["running"], false);
+
+        let (status, _) = handle_request(&admin, "POST", "/scenario", r#"{"scenario":"flood"}"#);
+        assert_eq!(status, 400);
+
+        let error_fault = r#"{"fault":"error_responses","message":"x"}"#;
+        assert_eq!(
+            handle_request(&admin, "POST", "/inject-fault", error_fault).0,
+            400
+        );
+        assert_eq!(
+            handle_request(
+                &admin,
+                "POST",
+                "/inject-fault",
+                r#"{"fault":"unresponsive"}"#
+            )
+            .0,
+            200
+        );
+        assert_eq!(emulator.faults().active(), Some(Fault::Unresponsive));
+    }
+
+    #[test]
+    #[ignore = "integration test with UDP networking"]
+    fn unresponsive_fault_stops_readings() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_millis(300)))
+            .unwrap();
+
+        let mut emulator = ThermEmulator::new(21.0).with_update_interval(Duration::from_millis(20));
+        emulator
+            .connect_to(&receiver.local_addr().unwrap().to_string())
+            .unwrap();
+        emulator.faults().inject(Fault::Unresponsive, None);
+        emulator.start();
+
+        let mut buf = [0u8; 256];
+        assert!(receiver.recv_from(&mut buf).is_err());
+
+        emulator.faults().clear();
+        assert!(receiver.recv_from(&mut buf).is_ok());
+        emulator.stop();
+    }
 }
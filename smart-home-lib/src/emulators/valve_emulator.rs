@@ -0,0 +1,234 @@
+//! Async эмулятор запорного клапана (TCP)
+
+use super::socket_emulator::Listener;
+use crate::controllers::HealthStatus;
+use crate::protocol::socket_protocol::DEFAULT_MAX_MESSAGE_SIZE;
+use crate::protocol::transport::{Connection, InMemory};
+use crate::protocol::valve_protocol::{
+    ValveCommand, ValveData, ValveResponse, receive_valve_command, send_valve_response,
+};
+use crate::traits::Lifecycle;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// Общее состояние клапана для всех клиентов
+#[derive(Debug)]
+struct ValveState {
+    open: bool,
+    device_id: Option<String>,
+}
+
+impl ValveState {
+    fn execute(&mut self, command: &ValveCommand) -> ValveData {
+        match command {
+            ValveCommand::Open => self.open = true,
+            ValveCommand::Close => self.open = false,
+            ValveCommand::Status => {}
+        }
+        ValveData {
+            open: self.open,
+            device_id: self.device_id.clone(),
+        }
+    }
+}
+
+/// Async эмулятор клапана
+pub struct ValveEmulator {
+    /// Адрес для прослушивания TCP соединений
+    bind_address: String,
+    /// Положение клапана
+    state: Arc<Mutex<ValveState>>,
+    /// Адрес на котором запущен сервер (после start)
+    bound_addr: Option<SocketAddr>,
+    /// Флаг работы сервера
+    running: Arc<AtomicBool>,
+    /// Handle главной задачи сервера
+    server_handle: Option<JoinHandle<()>>,
+    /// Канал для graceful shutdown
+    shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl ValveEmulator {
+    /// Создает эмулятор открытого клапана
+    pub fn new(bind_address: &str) -> Self {
+        Self {
+            bind_address: bind_address.to_string(),
+            state: Arc::new(Mutex::new(ValveState {
+                open: true,
+                device_id: None,
+            })),
+            bound_addr: None,
+            running: Arc::new(AtomicBool::new(false)),
+            server_handle: None,
+            shutdown_tx: None,
+        }
+    }
+
+    /// Builder: устанавливает ID устройства
+    pub fn with_device_id(self, device_id: &str) -> Self {
+        self.lock().device_id = Some(device_id.to_string());
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ValveState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Клапан открыт
+    pub fn is_open(&self) -> bool {
+        self.lock().open
+    }
+
+    /// Возвращает локальный адрес сервера (только после start)
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.bound_addr.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "Server not started yet - call start() first",
+            )
+        })
+    }
+
+    /// Проверяет, запущен ли эмулятор
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Запускает async TCP сервер
+    pub async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "Emulator already started",
+            ));
+        }
+
+        let listener = TcpListener::bind(&self.bind_address).await?;
+        self.serve(Listener::Tcp(listener))
+    }
+
+    /// Запускает эмулятор на транспорте в памяти вместо TCP
+    pub async fn start_in_memory(&mut self, transport: &InMemory) -> std::io::Result<()> {
+        if self.is_running() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "Emulator already started",
+            ));
+        }
+
+        let addr = self
+            .bind_address
+            .parse()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        self.serve(Listener::Memory(transport.listen(addr)))
+    }
+
+    fn serve(&mut self, mut listener: Listener) -> std::io::Result<()> {
+        let bound_addr = listener.local_addr()?;
+        println!("[ValveEmulator] Bound to {}", bound_addr);
+        self.bound_addr = Some(bound_addr);
+
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
+        self.shutdown_tx = Some(shutdown_tx);
+
+        let state = Arc::clone(&self.state);
+        self.running.store(true, Ordering::Relaxed);
+
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    result = listener.accept() => {
+                        match result {
+                            Ok((stream, addr)) => {
+                                let state = Arc::clone(&state);
+                                tokio::spawn(async move {
+                                    if let Err(e) = Self::handle_client(stream, &state).await {
+                                        println!("[ValveEmulator] Client {} error: {}", addr, e);
+                                    }
+                                });
+                            }
+                            Err(e) => {
+                                eprintln!("[ValveEmulator] Accept error: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                    _ = &mut shutdown_rx => break,
+                }
+            }
+
+            println!("[ValveEmulator] Server stopped");
+        });
+        self.server_handle = Some(handle);
+
+        Ok(())
+    }
+
+    /// Останавливает async сервер
+    pub async fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.server_handle.take() {
+            let _ = handle.await;
+        }
+        self.bound_addr = None;
+    }
+
+    /// Async обработка одного клиента
+    async fn handle_client(
+        mut stream: Box<dyn Connection>,
+        state: &Mutex<ValveState>,
+    ) -> std::io::Result<()> {
+        loop {
+            let command = match receive_valve_command(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).await {
+                Ok(command) => command,
+                // Клиент закрыл соединение
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            let response = match state.lock() {
+                Ok(mut state) => ValveResponse::Ok(state.execute(&command)),
+                Err(_) => ValveResponse::Error {
+                    message: "State lock poisoned".to_string(),
+                },
+            };
+            send_valve_response(&mut stream, &response).await?;
+        }
+    }
+}
+
+impl Lifecycle for ValveEmulator {
+    async fn start(&mut self) -> std::io::Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+        ValveEmulator::start(self).await
+    }
+
+    async fn stop(&mut self) -> std::io::Result<()> {
+        ValveEmulator::stop(self).await;
+        Ok(())
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus {
+            connected: self.is_running(),
+            ..HealthStatus::default()
+        }
+    }
+}
+
+impl Drop for ValveEmulator {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Совместная эмуляция розеток и термометров
+//!
+//! `World` поднимает эмуляторы, как `Fleet`, но связывает их тепловой
+//! моделью: розетки-обогреватели и охладители комнаты влияют на
+//! температуру ее термометров (`PhysicsModel`). Команда контроллеру
+//! розетки меняет показания термометра той же комнаты, поэтому
+//! автоматизацию термостата можно проверить целиком в одном процессе.
+
+use super::fleet::{Member, find, start_house, therm_controller};
+use super::physics::PhysicsModel;
+use super::socket_emulator::{EmulatorConfig, SocketEmulator};
+use super::therm_emulator::ThermEmulator;
+use crate::clock::{SharedClock, system_clock};
+use crate::controllers::{DeviceController, SocketController};
+use crate::house::SmartHouse;
+use crate::room::Room;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Описание розетки, влияющей на температуру комнаты
+#[derive(Debug, Clone)]
+struct ClimateSocketSpec {
+    room: String,
+    key: String,
+    power_rating: f64,
+    /// Сдвиг равновесной температуры на киловатт: > 0 - обогреватель, < 0 - охладитель
+    degrees_per_kw: f64,
+}
+
+/// Описание термометра с тепловой моделью комнаты
+#[derive(Debug, Clone)]
+struct WorldThermSpec {
+    room: String,
+    key: String,
+    initial_temp: f64,
+    physics: PhysicsModel,
+}
+
+/// Спецификация связанной эмуляции
+#[derive(Debug, Clone)]
+pub struct WorldSpec {
+    sockets: Vec<ClimateSocketSpec>,
+    therms: Vec<WorldThermSpec>,
+    update_interval: Duration,
+    controller_timeout: Duration,
+    max_age: Duration,
+    clock: SharedClock,
+}
+
+impl Default for WorldSpec {
+    fn default() -> Self {
+        Self {
+            sockets: Vec::new(),
+            therms: Vec::new(),
+            update_interval: Duration::from_millis(100),
+            controller_timeout: Duration::from_secs(3),
+            max_age: Duration::from_secs(5),
+            clock: system_clock(),
+        }
+    }
+}
+
+impl WorldSpec {
+    /// Создает пустую спецификацию
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: розетка-обогреватель комнаты
+    pub fn heater(self, room: &str, key: &str, power_rating: f64, degrees_per_kw: f64) -> Self {
+        self.climate_socket(room, key, power_rating, degrees_per_kw.abs())
+    }
+
+    /// Builder: розетка-охладитель комнаты (кондиционер)
+    pub fn cooler(self, room: &str, key: &str, power_rating: f64, degrees_per_kw: f64) -> Self {
+        self.climate_socket(room, key, power_rating, -degrees_per_kw.abs())
+    }
+
+    /// Builder: розетка, не влияющая на температуру
+    pub fn socket(self, room: &str, key: &str, power_rating: f64) -> Self {
+        self.climate_socket(room, key, power_rating, 0.0)
+    }
+
+    /// Builder: термометр комнаты с тепловой моделью
+    ///
+    /// Обогреватели и охладители той же комнаты добавляются в модель при запуске.
+    pub fn therm(
+        mut self,
+        room: &str,
+        key: &str,
+        initial_temp: f64,
+        physics: PhysicsModel,
+    ) -> Self {
+        self.therms.push(WorldThermSpec {
+            room: room.to_string(),
+            key: key.to_string(),
+            initial_temp,
+            physics,
+        });
+        self
+    }
+
+    /// Builder: интервал отправки данных термометрами
+    pub fn with_update_interval(mut self, interval: Duration) -> Self {
+        self.update_interval = interval;
+        self
+    }
+
+    /// Builder: таймаут TCP операций контроллеров розеток
+    pub fn with_controller_timeout(mut self, timeout: Duration) -> Self {
+        self.controller_timeout = timeout;
+        self
+    }
+
+    /// Builder: максимальный возраст данных контроллеров термометров
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Builder: общий источник времени для термометров и их контроллеров
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn climate_socket(
+        mut self,
+        room: &str,
+        key: &str,
+        power_rating: f64,
+        degrees_per_kw: f64,
+    ) -> Self {
+        self.sockets.push(ClimateSocketSpec {
+            room: room.to_string(),
+            key: key.to_string(),
+            power_rating,
+            degrees_per_kw,
+        });
+        self
+    }
+}
+
+/// Запущенная связанная эмуляция вместе с подключенным домом
+pub struct World {
+    sockets: Vec<Member<SocketEmulator>>,
+    therms: Vec<Member<ThermEmulator>>,
+    house: SmartHouse,
+}
+
+impl World {
+    /// Запускает эмуляторы, связывает их тепловыми моделями и собирает дом
+    pub async fn start(spec: WorldSpec) -> std::io::Result<Self> {
+        let mut world = Self {
+            sockets: Vec::with_capacity(spec.sockets.len()),
+            therms: Vec::with_capacity(spec.therms.len()),
+            house: SmartHouse::default(),
+        };
+
+        for socket in &spec.sockets {
+            let config = EmulatorConfig::new(socket.power_rating)
+                .with_address("127.0.0.1:0")
+                .with_device_id(&format!("{}/{}", socket.room, socket.key));
+
+            let mut emulator = SocketEmulator::new(config);
+            emulator.start().await?;
+            let addr = emulator.local_addr()?;
+
+            let controller =
+                SocketController::new(addr, socket.power_rating, spec.controller_timeout);
+            world.add_controller(&socket.room, &socket.key, controller.into());
+
+            world.sockets.push(Member {
+                room: socket.room.clone(),
+                key: socket.key.clone(),
+                addr,
+                emulator,
+            });
+        }
+
+        for therm in &spec.therms {
+            let mut physics = therm.physics.clone();
+            for (socket, member) in spec.sockets.iter().zip(&world.sockets) {
+                if socket.room != therm.room || socket.degrees_per_kw == 0.0 {
+                    continue;
+                }
+                let probe = member.emulator.power_probe();
+                physics = if socket.degrees_per_kw > 0.0 {
+                    physics.with_heater(probe, socket.degrees_per_kw)
+                } else {
+                    physics.with_cooler(probe, socket.degrees_per_kw)
+                };
+            }
+
+            let (controller, addr) =
+                therm_controller(therm.initial_temp, spec.max_age, &spec.clock)?;
+            world.add_controller(&therm.room, &therm.key, controller.into());
+
+            let mut emulator = ThermEmulator::new(therm.initial_temp)
+                .with_device_id(&format!("{}/{}", therm.room, therm.key))
+                .with_update_interval(spec.update_interval)
+                .with_physics(physics)
+                .with_clock(Arc::clone(&spec.clock));
+            emulator.connect_to(&addr.to_string())?;
+            emulator.start();
+
+            world.therms.push(Member {
+                room: therm.room.clone(),
+                key: therm.key.clone(),
+                addr,
+                emulator,
+            });
+        }
+
+        start_house(&mut world.house).await?;
+        Ok(world)
+    }
+
+    /// Добавляет контроллер, создавая комнату при необходимости
+    fn add_controller(&mut self, room: &str, key: &str, controller: DeviceController) {
+        if self.house.room(room).is_none() {
+            self.house.add_room(room, Room::new());
+        }
+        if let Some(room) = self.house.room_mut(room) {
+            room.add_controller(key, controller);
+        }
+    }
+
+    /// Дом с контроллерами, подключенными к эмуляторам
+    pub fn house(&self) -> &SmartHouse {
+        &self.house
+    }
+
+    /// Изменяемый доступ к дому
+    pub fn house_mut(&mut self) -> &mut SmartHouse {
+        &mut self.house
+    }
+
+    /// Забирает дом (например, чтобы передать его задаче автоматизации)
+    ///
+    /// Эмуляторы продолжают работать, пока `World` не остановлен.
+    pub fn take_house(&mut self) -> SmartHouse {
+        std::mem::take(&mut self.house)
+    }
+
+    /// TCP адрес эмулятора розетки
+    pub fn socket_addr(&self, room: &str, key: &str) -> Option<SocketAddr> {
+        find(&self.sockets, room, key).map(|member| member.addr)
+    }
+
+    /// Эмулятор розетки
+    pub fn socket_emulator(&self, room: &str, key: &str) -> Option<&SocketEmulator> {
+        find(&self.sockets, room, key).map(|member| &member.emulator)
+    }
+
+    /// Эмулятор термометра
+    pub fn therm_emulator(&self, room: &str, key: &str) -> Option<&ThermEmulator> {
+        find(&self.therms, room, key).map(|member| &member.emulator)
+    }
+
+    /// Останавливает все эмуляторы и контроллеры
+    pub async fn shutdown(mut self) {
+        self.house.stop_all().await;
+
+        for member in &mut self.sockets {
+            member.emulator.stop().await;
+        }
+
+        for member in &mut self.therms {
+            member.emulator.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room_model() -> PhysicsModel {
+        PhysicsModel::new(15.0)
+            .with_time_constant(Duration::from_millis(200))
+            .with_noise(0.0)
+    }
+
+    /// Ждет показание термометра комнаты, удовлетворяющее `predicate`
+    async fn wait_for(world: &World, room: &str, predicate: impl Fn(f64) -> bool) {
+        match world.house().controller(room, "therm") {
+            Ok(DeviceController::Therm(therm)) => therm
+                .wait_until(|t| predicate(t.value()), Duration::from_secs(5))
+                .await
+                .map(|_| ())
+                .unwrap_or_else(|e| panic!("{}: {}", room, e)),
+            _ => panic!("Expected therm controller"),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async networking"]
+    async fn heater_warms_linked_room() {
+        let spec = WorldSpec::new()
+            .heater("bedroom", "heater", 2000.0, 5.0)
+            .therm("bedroom", "therm", 15.0, room_model())
+            .cooler("office", "ac", 1000.0, 5.0)
+            .therm("office", "therm", 15.0, room_model())
+            .with_update_interval(Duration::from_millis(20));
+        let mut world = World::start(spec).await.unwrap();
+        assert!(world.socket_addr("bedroom", "heater").is_some());
+
+        for room in ["bedroom", "office"] {
+            let key = if room == "bedroom" { "heater" } else { "ac" };
+            match world.house_mut().controller_mut(room, key) {
+                Ok(DeviceController::Socket(socket)) => socket.turn_on().await.unwrap(),
+                _ => panic!("Expected socket controller"),
+            }
+        }
+
+        // Равновесие: 15 + 2 кВт * 5 = 25°C в спальне, 15 - 5 = 10°C в офисе
+        wait_for(&world, "bedroom", |t| t > 24.0).await;
+        wait_for(&world, "office", |t| t < 11.0).await;
+        world.shutdown().await;
+    }
+}
@@ -0,0 +1,292 @@
+//! Контроль бюджета мощности дома
+//!
+//! `BudgetManager` суммирует мощность всех контроллеров розеток и при
+//! превышении лимита отключает устройства с наименьшим приоритетом
+//! (см. `DeviceMetadata::priority`).
+
+use crate::controllers::{DeviceController, SocketError};
+use crate::house::SmartHouse;
+use crate::units::Watts;
+
+/// Потребитель мощности
+#[derive(Debug, Clone, PartialEq)]
+pub struct Consumer {
+    pub room: String,
+    pub key: String,
+    pub power: Watts,
+    pub priority: u8,
+}
+
+/// Текущее потребление относительно лимита
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetUsage {
+    /// Суммарная мощность включенных розеток
+    pub total: Watts,
+    /// Лимит мощности
+    pub limit: Watts,
+    /// Включенные розетки
+    pub consumers: Vec<Consumer>,
+}
+
+impl BudgetUsage {
+    /// Проверяет превышение лимита
+    pub fn is_exceeded(&self) -> bool {
+        self.total > self.limit
+    }
+
+    /// Превышение лимита (0, если лимит соблюден)
+    pub fn excess(&self) -> Watts {
+        self.total - self.limit
+    }
+}
+
+/// Результат проверки бюджета
+#[derive(Debug, Clone)]
+pub struct BudgetReport {
+    /// Потребление до отключений
+    pub usage: BudgetUsage,
+    /// Отключенные устройства
+    pub shed: Vec<Consumer>,
+    /// Ошибки опроса и отключения
+    pub errors: Vec<(String, String, SocketError)>,
+}
+
+/// Менеджер бюджета мощности
+#[derive(Debug, Clone)]
+pub struct BudgetManager {
+    limit: Watts,
+    auto_shed: bool,
+}
+
+impl BudgetManager {
+    /// Создает менеджер с лимитом; автоматическое отключение включено
+    pub fn new(limit: Watts) -> Self {
+        Self {
+            limit,
+            auto_shed: true,
+        }
+    }
+
+    /// Builder: включает или выключает автоматическое отключение нагрузки
+    pub fn with_auto_shed(mut self, auto_shed: bool) -> Self {
+        self.auto_shed = auto_shed;
+        self
+    }
+
+    /// Возвращает лимит мощности
+    pub fn limit(&self) -> Watts {
+        self.limit
+    }
+
+    /// Устанавливает лимит мощности
+    pub fn set_limit(&mut self, limit: Watts) {
+        self.limit = limit;
+    }
+
+    /// Потребление по последнему известному состоянию контроллеров
+    pub fn usage(&self, house: &SmartHouse) -> BudgetUsage {
+        let mut consumers = Vec::new();
+
+        for room_key in house.rooms_keys() {
+            let Some(room) = house.room(&room_key) else {
+                continue;
+            };
+            for key in room.controllers_keys() {
+                if let Some(DeviceController::Socket(controller)) = room.controller(&key)
+                    && let Ok(socket) = controller.device()
+                    && socket.is_active()
+                {
+                    consumers.push(Consumer {
+                        room: room_key.clone(),
+                        key: key.clone(),
+                        power: socket.current_power(),
+                        priority: room.metadata(&key).priority,
+                    });
+                }
+            }
+        }
+
+        let total = consumers
+            .iter()
+            .fold(Watts::new(0.0), |sum, consumer| sum + consumer.power);
+
+        BudgetUsage {
+            total,
+            limit: self.limit,
+            consumers,
+        }
+    }
+
+    /// Устройства, которые нужно отключить, чтобы уложиться в лимит
+    ///
+    /// Порядок: по возрастанию приоритета, при равном приоритете -
+    /// сначала более мощные.
+    pub fn plan_shedding(&self, usage: &BudgetUsage) -> Vec<Consumer> {
+        let mut candidates = usage.consumers.clone();
+        candidates.sort_by(|a, b| {
+            a.priority
+                .cmp(&b.priority)
+                .then(b.power.value().total_cmp(&a.power.value()))
+        });
+
+        let mut total = usage.total;
+        let mut plan = Vec::new();
+        for consumer in candidates {
+            if total <= self.limit {
+                break;
+            }
+            total = total - consumer.power;
+            plan.push(consumer);
+        }
+
+        plan
+    }
+
+    /// Опрашивает мощность всех контроллеров розеток
+    pub async fn refresh(&self, house: &mut SmartHouse) -> Vec<(String, String, SocketError)> {
+        let mut errors = Vec::new();
+
+        for room_key in house.rooms_keys() {
+            let Some(room) = house.room_mut(&room_key) else {
+                continue;
+            };
+            for key in room.controllers_keys() {
+                if let Some(DeviceController::Socket(controller)) = room.controller_mut(&key)
+                    && let Err(e) = controller.power().await
+                {
+                    errors.push((room_key.clone(), key, e));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Опрашивает контроллеры и при превышении лимита отключает нагрузку
+    pub async fn enforce(&self, house: &mut SmartHouse) -> BudgetReport {
+        let mut errors = self.refresh(house).await;
+        let usage = self.usage(house);
+        let mut shed = Vec::new();
+
+        if self.auto_shed && usage.is_exceeded() {
+            for consumer in self.plan_shedding(&usage) {
+                if let Ok(DeviceController::Socket(controller)) =
+                    house.controller_mut(&consumer.room, &consumer.key)
+                {
+                    match controller.turn_off().await {
+                        Ok(()) => shed.push(consumer),
+                        Err(e) => errors.push((consumer.room, consumer.key, e)),
+                    }
+                }
+            }
+        }
+
+        BudgetReport {
+            usage,
+            shed,
+            errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consumer(key: &str, power: f64, priority: u8) -> Consumer {
+        Consumer {
+            room: "room".to_string(),
+            key: key.to_string(),
+            power: Watts::new(power),
+            priority,
+        }
+    }
+
+    fn usage(consumers: Vec<Consumer>, limit: f64) -> BudgetUsage {
+        let total = consumers
+            .iter()
+            .fold(Watts::new(0.0), |sum, c| sum + c.power);
+        BudgetUsage {
+            total,
+            limit: Watts::new(limit),
+            consumers,
+        }
+    }
+
+    #[test]
+    fn within_budget() {
+        let manager = BudgetManager::new(Watts::new(3000.0));
+        let usage = usage(vec![consumer("kettle", 2000.0, 50)], 3000.0);
+
+        assert!(!usage.is_exceeded());
+        assert_eq!(usage.excess(), Watts::new(0.0));
+        assert!(manager.plan_shedding(&usage).is_empty());
+    }
+
+    #[test]
+    fn sheds_lowest_priority_first() {
+        let manager = BudgetManager::new(Watts::new(3000.0));
+        let usage = usage(
+            vec![
+                consumer("fridge", 300.0, 100),
+                consumer("heater", 2000.0, 10),
+                consumer("kettle", 2000.0, 50),
+                consumer("lamp", 100.0, 10),
+            ],
+            3000.0,
+        );
+        assert_eq!(usage.excess(), Watts::new(1400.0));
+
+        let plan = manager.plan_shedding(&usage);
+        let keys: Vec<_> = plan.iter().map(|c| c.key.as_str()).collect();
+        // Обогревателя достаточно: лампу с тем же приоритетом не трогаем
+        assert_eq!(keys, vec!["heater"]);
+    }
+
+    #[test]
+    fn empty_house() {
+        let manager = BudgetManager::new(Watts::new(1000.0)).with_auto_shed(false);
+        let usage = manager.usage(&SmartHouse::default());
+        assert_eq!(usage.total, Watts::new(0.0));
+        assert!(usage.consumers.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async networking"]
+    async fn enforce_sheds_load() {
+        use crate::emulators::{Fleet, FleetSpec};
+        use crate::metadata::DeviceMetadata;
+
+        let spec = FleetSpec::new()
+            .socket("kitchen", "kettle", 2000.0)
+            .socket("living", "heater", 1500.0)
+            .socket("living", "fridge", 300.0);
+        let mut fleet = Fleet::start(spec).await.unwrap();
+
+        let house = fleet.house_mut();
+        if let Some(room) = house.room_mut("living") {
+            room.set_metadata("heater", DeviceMetadata::new().with_priority(10));
+            room.set_metadata("fridge", DeviceMetadata::new().with_priority(100));
+        }
+        for (room, key) in [
+            ("kitchen", "kettle"),
+            ("living", "heater"),
+            ("living", "fridge"),
+        ] {
+            if let Ok(DeviceController::Socket(c)) = house.controller_mut(room, key) {
+                c.turn_on().await.unwrap();
+            }
+        }
+
+        let manager = BudgetManager::new(Watts::new(3000.0));
+        let report = manager.enforce(house).await;
+
+        assert_eq!(report.usage.total, Watts::new(3800.0));
+        assert!(report.errors.is_empty());
+        assert_eq!(report.shed.len(), 1);
+        assert_eq!(report.shed[0].key, "heater");
+        assert_eq!(manager.usage(house).total, Watts::new(2300.0));
+
+        fleet.shutdown().await;
+    }
+}
@@ -0,0 +1,381 @@
+//! События дома
+//!
+//! Службы дома (сверка состояния, энергобюджет и т.п.) публикуют события в
+//! общую шину, подписчики получают их через `tokio::sync::broadcast`.
+//! Отставший подписчик пропускает старые события, а не блокирует остальных.
+//!
+//! Шумные датчики присылают почти одинаковые показания несколько раз в
+//! секунду. `DedupPolicy` ограничивает `TemperatureUpdated`: событие
+//! публикуется, только если значение изменилось на порог или с прошлой
+//! публикации прошел интервал.
+
+use crate::clock::{SharedClock, system_clock};
+#[cfg(feature = "net")]
+use crate::controllers::ErrorContext;
+use crate::units::Celsius;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Размер буфера шины по умолчанию
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Погрешность сравнения изменения с порогом (0.1 не представимо точно)
+const DELTA_EPSILON: f64 = 1e-9;
+
+/// Событие дома
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HouseEvent {
+    /// Фактическое состояние устройства расходится с желаемым
+    DriftDetected {
+        room: String,
+        key: String,
+        desired: String,
+        actual: String,
+    },
+    /// Расхождение устранено корректирующей командой
+    DriftCorrected {
+        room: String,
+        key: String,
+        state: String,
+    },
+    /// Корректирующая команда не удалась
+    #[cfg(feature = "net")]
+    CorrectionFailed {
+        #[serde(flatten)]
+        context: ErrorContext,
+        error: String,
+    },
+    /// Устройство или контроллер перенесены в другую комнату
+    DeviceMoved {
+        key: String,
+        from_room: String,
+        to_room: String,
+    },
+    /// Состав дома изменен транзакцией (`SmartHouse::transaction`)
+    TopologyChanged { changes: Vec<TopologyChange> },
+    /// Комната выдана для изменения (`room_mut`, `device_mut`, `controller_mut`)
+    RoomChanged { room: String },
+    /// Новое показание термометра (`SmartHouse::forward_temperatures`)
+    TemperatureUpdated {
+        room: String,
+        key: String,
+        value: Celsius,
+    },
+}
+
+/// Одно изменение состава дома в транзакции
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum TopologyChange {
+    RoomAdded {
+        room: String,
+    },
+    RoomRemoved {
+        room: String,
+    },
+    RoomRenamed {
+        from: String,
+        to: String,
+    },
+    ItemRenamed {
+        room: String,
+        from: String,
+        to: String,
+    },
+    ItemMoved {
+        key: String,
+        from_room: String,
+        to_room: String,
+    },
+    GroupAdded {
+        group: String,
+    },
+    GroupRemoved {
+        group: String,
+    },
+}
+
+impl TopologyChange {
+    /// Комнаты, которых касается изменение
+    pub fn rooms(&self) -> Vec<&str> {
+        match self {
+            Self::RoomAdded { room } | Self::RoomRemoved { room } => vec![room],
+            Self::RoomRenamed { from, to } => vec![from, to],
+            Self::ItemRenamed { room, .. } => vec![room],
+            Self::ItemMoved {
+                from_room, to_room, ..
+            } => vec![from_room, to_room],
+            Self::GroupAdded { .. } | Self::GroupRemoved { .. } => Vec::new(),
+        }
+    }
+}
+
+impl HouseEvent {
+    /// Комнаты, которых касается событие
+    pub fn rooms(&self) -> Vec<&str> {
+        match self {
+            Self::DriftDetected { room, .. }
+            | Self::DriftCorrected { room, .. }
+            | Self::RoomChanged { room }
+            | Self::TemperatureUpdated { room, .. } => vec![room],
+            #[cfg(feature = "net")]
+            Self::CorrectionFailed { context, .. } => vec![&context.room],
+            Self::DeviceMoved {
+                from_room, to_room, ..
+            } => vec![from_room, to_room],
+            Self::TopologyChanged { changes } => {
+                changes.iter().flat_map(TopologyChange::rooms).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for TopologyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RoomAdded { room } => write!(f, "+ комната {}", room),
+            Self::RoomRemoved { room } => write!(f, "- комната {}", room),
+            Self::RoomRenamed { from, to } => write!(f, "комната {} -> {}", from, to),
+            Self::ItemRenamed { room, from, to } => write!(f, "{}/{} -> {}", room, from, to),
+            Self::ItemMoved {
+                key,
+                from_room,
+                to_room,
+            } => write!(f, "{}/{} -> {}/{}", from_room, key, to_room, key),
+            Self::GroupAdded { group } => write!(f, "+ группа {}", group),
+            Self::GroupRemoved { group } => write!(f, "- группа {}", group),
+        }
+    }
+}
+
+impl fmt::Display for HouseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DriftDetected {
+                room,
+                key,
+                desired,
+                actual,
+            } => write!(
+                f,
+                "{}/{}: расхождение состояния (ожидается {}, фактически {})",
+                room, key, desired, actual
+            ),
+            Self::DriftCorrected { room, key, state } => {
+                write!(f, "{}/{}: состояние восстановлено ({})", room, key, state)
+            }
+            #[cfg(feature = "net")]
+            Self::CorrectionFailed { context, error } => write!(
+                f,
+                "{}/{}: не удалось исправить состояние ({}, {}): {}",
+                context.room, context.key, context.address, context.operation, error
+            ),
+            Self::DeviceMoved {
+                key,
+                from_room,
+                to_room,
+            } => write!(f, "{}/{}: перенесено в комнату {}", from_room, key, to_room),
+            Self::TopologyChanged { changes } => {
+                let changes: Vec<String> = changes.iter().map(ToString::to_string).collect();
+                write!(f, "состав дома изменен: {}", changes.join("; "))
+            }
+            Self::RoomChanged { room } => write!(f, "{}: комната изменена", room),
+            Self::TemperatureUpdated { room, key, value } => {
+                write!(f, "{}/{}: {}", room, key, value)
+            }
+        }
+    }
+}
+
+/// Порог публикации `TemperatureUpdated`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DedupPolicy {
+    /// Минимальное изменение показания, °C
+    pub min_delta: f64,
+    /// Интервал, после которого публикуется и неизменившееся показание
+    pub min_interval: Duration,
+}
+
+impl Default for DedupPolicy {
+    fn default() -> Self {
+        Self {
+            min_delta: 0.1,
+            min_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Последние опубликованные показания по (комната, ключ): значение и время, мс
+type LastPublished = HashMap<(String, String), (f64, u64)>;
+
+/// Шина событий дома; клоны публикуют в одну и ту же шину
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<HouseEvent>,
+    dedup: Option<DedupPolicy>,
+    clock: SharedClock,
+    last: Arc<Mutex<LastPublished>>,
+    suppressed: Arc<AtomicU64>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    /// Создает шину с буфером на `capacity` событий
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            dedup: None,
+            clock: system_clock(),
+            last: Arc::new(Mutex::new(HashMap::new())),
+            suppressed: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Builder: публиковать `TemperatureUpdated` только при изменении на порог
+    /// или по истечении интервала
+    pub fn with_dedup(mut self, policy: DedupPolicy) -> Self {
+        self.dedup = Some(policy);
+        self
+    }
+
+    /// Builder: источник времени для интервала `DedupPolicy`
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Публикует событие; без подписчиков событие отбрасывается
+    ///
+    /// `TemperatureUpdated` ниже порога `DedupPolicy` не публикуется.
+    pub fn publish(&self, event: HouseEvent) {
+        if !self.passes_dedup(&event) {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let _ = self.sender.send(event);
+    }
+
+    /// Сколько событий отброшено `DedupPolicy`
+    pub fn suppressed_count(&self) -> u64 {
+        self.suppressed.load(Ordering::Relaxed)
+    }
+
+    /// Проверяет порог и запоминает показание, если событие публикуется
+    fn passes_dedup(&self, event: &HouseEvent) -> bool {
+        let (Some(policy), HouseEvent::TemperatureUpdated { room, key, value }) =
+            (self.dedup, event)
+        else {
+            return true;
+        };
+        let now = self.clock.now_ms();
+        let value = value.value();
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let id = (room.clone(), key.clone());
+        if let Some(&(previous, at_ms)) = last.get(&id) {
+            let changed = (value - previous).abs() + DELTA_EPSILON >= policy.min_delta;
+            let due = now.saturating_sub(at_ms) >= policy.min_interval.as_millis() as u64;
+            if !changed && !due {
+                return false;
+            }
+        }
+        last.insert(id, (value, now));
+        true
+    }
+
+    /// Подписка на события, опубликованные после вызова
+    pub fn subscribe(&self) -> broadcast::Receiver<HouseEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Количество подписчиков
+    pub fn subscribers_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    fn corrected(key: &str) -> HouseEvent {
+        HouseEvent::DriftCorrected {
+            room: "hall".to_string(),
+            key: key.to_string(),
+            state: "on".to_string(),
+        }
+    }
+
+    #[test]
+    fn publish_and_subscribe() {
+        let bus = EventBus::default();
+        bus.publish(corrected("lost")); // подписчиков еще нет
+
+        let mut events = bus.subscribe();
+        bus.clone().publish(corrected("lamp"));
+
+        assert_eq!(bus.subscribers_count(), 1);
+        assert_eq!(events.try_recv().unwrap(), corrected("lamp"));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn json_format() {
+        assert_eq!(
+            serde_json::to_string(&corrected("lamp")).unwrap(),
+            r#"{"event":"drift_corrected","room":"hall","key":"lamp","state":"on"}"#
+        );
+    }
+
+    #[test]
+    fn dedup_temperature_updates() {
+        let clock = Arc::new(MockClock::new());
+        let bus = EventBus::default()
+            .with_clock(clock.clone())
+            .with_dedup(DedupPolicy {
+                min_delta: 0.1,
+                min_interval: Duration::from_secs(10),
+            });
+        let mut events = bus.subscribe();
+        let updated = |key: &str, value: f64| HouseEvent::TemperatureUpdated {
+            room: "hall".to_string(),
+            key: key.to_string(),
+            value: Celsius::new(value),
+        };
+
+        for value in [21.1, 21.15, 21.12, 21.2, 21.2] {
+            bus.publish(updated("temp", value));
+        }
+        bus.publish(updated("outdoor", 21.15)); // свой порог у каждого термометра
+        bus.publish(corrected("lamp")); // другие события не ограничиваются
+        bus.publish(corrected("lamp"));
+        clock.advance(Duration::from_secs(10));
+        bus.publish(updated("temp", 21.2));
+
+        let received: Vec<HouseEvent> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+        assert_eq!(
+            received,
+            vec![
+                updated("temp", 21.1),
+                updated("temp", 21.2),
+                updated("outdoor", 21.15),
+                corrected("lamp"),
+                corrected("lamp"),
+                updated("temp", 21.2),
+            ]
+        );
+        assert_eq!(bus.suppressed_count(), 3);
+    }
+}
@@ -0,0 +1,374 @@
+//! Группы устройств, объединяющие устройства из разных комнат
+//!
+//! Группа ("все обогреватели", "уличное освещение") хранит только ссылки
+//! вида комната/ключ, поэтому устройство может входить в несколько групп.
+
+#[cfg(feature = "net")]
+use crate::controllers::DeviceController;
+use crate::devices::Device;
+use crate::house::SmartHouse;
+use crate::report::ReportOptions;
+use crate::traits::Reporter;
+use crate::units::{Celsius, Watts};
+use std::fmt;
+
+/// Группа устройств
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceGroup {
+    members: Vec<(String, String)>,
+}
+
+impl DeviceGroup {
+    /// Создает пустую группу
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: добавляет участника группы
+    pub fn with_member(mut self, room: &str, key: &str) -> Self {
+        self.add_member(room, key);
+        self
+    }
+
+    /// Добавляет участника (повторное добавление игнорируется)
+    pub fn add_member(&mut self, room: &str, key: &str) {
+        if !self.contains(room, key) {
+            self.members.push((room.to_string(), key.to_string()));
+        }
+    }
+
+    /// Удаляет участника, возвращает `true` если он был в группе
+    pub fn remove_member(&mut self, room: &str, key: &str) -> bool {
+        let before = self.members.len();
+        self.members.retain(|(r, k)| r != room || k != key);
+        self.members.len() != before
+    }
+
+    /// Заменяет ссылки на переименованную комнату
+    pub(crate) fn rename_room(&mut self, old: &str, new: &str) {
+        for (room, _) in self.members.iter_mut().filter(|(room, _)| room == old) {
+            *room = new.to_string();
+        }
+    }
+
+    /// Заменяет ссылку на переименованное устройство
+    pub(crate) fn rename_member(&mut self, room: &str, old: &str, new: &str) {
+        for (_, key) in self
+            .members
+            .iter_mut()
+            .filter(|(r, key)| r == room && key == old)
+        {
+            *key = new.to_string();
+        }
+    }
+
+    /// Заменяет ссылку на устройство, перенесенное в другую комнату
+    pub(crate) fn move_member(&mut self, from: &str, key: &str, to: &str) {
+        if self.contains(to, key) {
+            self.remove_member(from, key);
+        } else if let Some(member) = self.members.iter_mut().find(|(r, k)| r == from && k == key) {
+            member.0 = to.to_string();
+        }
+    }
+
+    /// Проверяет, входит ли устройство в группу
+    pub fn contains(&self, room: &str, key: &str) -> bool {
+        self.members.iter().any(|(r, k)| r == room && k == key)
+    }
+
+    /// Участники группы в порядке добавления
+    pub fn members(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.members.iter().map(|(r, k)| (r.as_str(), k.as_str()))
+    }
+
+    /// Количество участников
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Проверяет, пуста ли группа
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Собирает сводное состояние участников группы
+    pub fn status(&self, house: &SmartHouse) -> GroupStatus {
+        let mut status = GroupStatus {
+            members: self.members.len(),
+            ..GroupStatus::default()
+        };
+        let mut temperatures = Vec::new();
+
+        for (room, key) in &self.members {
+            if let Ok(device) = house.device(room, key) {
+                match device {
+                    Device::Socket(s) => status.add_socket(s.is_active(), s.current_power()),
+                    Device::Therm(t) => temperatures.push(t.temperature().value()),
+                    // Остальные устройства не участвуют в сводке по мощности и температуре
+                    _ => {}
+                }
+            } else if let Ok(controller) = house.controller(room, key) {
+                match controller {
+                    #[cfg(feature = "net")]
+                    DeviceController::Socket(c) => match c.device() {
+                        Ok(s) => status.add_socket(s.is_active(), s.current_power()),
+                        Err(_) => status.unavailable.push((room.clone(), key.clone())),
+                    },
+                    #[cfg(feature = "net")]
+                    DeviceController::Therm(c) => match c.temperature() {
+                        Ok(t) => temperatures.push(t.value.value()),
+                        Err(_) => status.unavailable.push((room.clone(), key.clone())),
+                    },
+                    #[cfg(all(feature = "net", feature = "blinds"))]
+                    DeviceController::Blinds(_) => {}
+                    #[cfg(not(feature = "net"))]
+                    _ => {}
+                }
+            } else {
+                status.missing.push((room.clone(), key.clone()));
+            }
+        }
+
+        status.therms = temperatures.len();
+        if !temperatures.is_empty() {
+            let average = temperatures.iter().sum::<f64>() / temperatures.len() as f64;
+            status.average_temperature = Some(Celsius::new(average));
+        }
+
+        status
+    }
+
+    /// Включает все розетки группы
+    pub async fn turn_on(&self, house: &mut SmartHouse) -> Vec<GroupFailure> {
+        self.switch(house, true).await
+    }
+
+    /// Выключает все розетки группы
+    pub async fn turn_off(&self, house: &mut SmartHouse) -> Vec<GroupFailure> {
+        self.switch(house, false).await
+    }
+
+    /// Переключает розетки группы; термометры пропускаются
+    async fn switch(&self, house: &mut SmartHouse, on: bool) -> Vec<GroupFailure> {
+        let mut failures = Vec::new();
+
+        for (room, key) in &self.members {
+            let result = if let Ok(device) = house.device_mut(room, key) {
+                if let Device::Socket(s) = device {
+                    if on { s.turn_on() } else { s.turn_off() }
+                }
+                Ok(())
+            } else {
+                match house.controller_mut(room, key) {
+                    #[cfg(feature = "net")]
+                    Ok(DeviceController::Socket(c)) => {
+                        let result = if on {
+                            c.turn_on().await
+                        } else {
+                            c.turn_off().await
+                        };
+                        result.map_err(|e| e.to_string())
+                    }
+                    #[cfg(feature = "net")]
+                    Ok(DeviceController::Therm(_)) => Ok(()),
+                    #[cfg(all(feature = "net", feature = "blinds"))]
+                    Ok(DeviceController::Blinds(_)) => Ok(()),
+                    #[cfg(not(feature = "net"))]
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(e.to_string()),
+                }
+            };
+
+            if let Err(reason) = result {
+                failures.push(GroupFailure {
+                    room: room.clone(),
+                    key: key.clone(),
+                    reason,
+                });
+            }
+        }
+
+        failures
+    }
+}
+
+/// Ошибка выполнения групповой команды для одного участника
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupFailure {
+    pub room: String,
+    pub key: String,
+    pub reason: String,
+}
+
+impl fmt::Display for GroupFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}: {}", self.room, self.key, self.reason)
+    }
+}
+
+/// Сводное состояние группы
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupStatus {
+    /// Количество участников группы
+    pub members: usize,
+    /// Количество розеток с известным состоянием
+    pub sockets: usize,
+    /// Количество включенных розеток
+    pub active_sockets: usize,
+    /// Суммарная потребляемая мощность
+    pub total_power: Watts,
+    /// Количество термометров со свежими показаниями
+    pub therms: usize,
+    /// Средняя температура по термометрам группы
+    pub average_temperature: Option<Celsius>,
+    /// Участники, отсутствующие в доме
+    pub missing: Vec<(String, String)>,
+    /// Контроллеры без актуальных данных
+    pub unavailable: Vec<(String, String)>,
+}
+
+impl Default for GroupStatus {
+    fn default() -> Self {
+        Self {
+            members: 0,
+            sockets: 0,
+            active_sockets: 0,
+            total_power: Watts::new(0.0),
+            therms: 0,
+            average_temperature: None,
+            missing: Vec::new(),
+            unavailable: Vec::new(),
+        }
+    }
+}
+
+impl GroupStatus {
+    fn add_socket(&mut self, active: bool, power: Watts) {
+        self.sockets += 1;
+        if active {
+            self.active_sockets += 1;
+            self.total_power = self.total_power + power;
+        }
+    }
+}
+
+impl Reporter for GroupStatus {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        let mut report = format!(
+            "Group: {} members | Sockets: {}/{} active, {}",
+            self.members,
+            self.active_sockets,
+            self.sockets,
+            options.power(self.total_power)
+        );
+        if let Some(t) = self.average_temperature {
+            report.push_str(&format!(" | Avg temperature: {}", options.temperature(t)));
+        }
+        let problems = self.missing.len() + self.unavailable.len();
+        if problems > 0 {
+            report.push_str(&format!(" | Unavailable: {}", problems));
+        }
+        report
+    }
+}
+
+impl fmt::Display for GroupStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{SmartSocket, SmartTherm};
+    use crate::room;
+
+    fn test_house() -> SmartHouse {
+        crate::house![
+            (
+                "kitchen",
+                room![
+                    ("heater", Device::Socket(SmartSocket::new(1000.0))),
+                    ("therm", Device::Therm(SmartTherm::new(20.0)))
+                ]
+            ),
+            (
+                "bedroom",
+                room![
+                    ("heater", Device::Socket(SmartSocket::new(1500.0))),
+                    ("therm", Device::Therm(SmartTherm::new(23.0)))
+                ]
+            )
+        ]
+    }
+
+    #[test]
+    fn members() {
+        let mut group = DeviceGroup::new()
+            .with_member("kitchen", "heater")
+            .with_member("bedroom", "heater")
+            .with_member("kitchen", "heater");
+        assert_eq!(group.len(), 2);
+        assert!(group.contains("bedroom", "heater"));
+
+        assert!(group.remove_member("kitchen", "heater"));
+        assert!(!group.remove_member("kitchen", "heater"));
+        assert_eq!(
+            group.members().collect::<Vec<_>>(),
+            vec![("bedroom", "heater")]
+        );
+    }
+
+    #[tokio::test]
+    async fn group_commands() {
+        let mut house = test_house();
+        let heaters = DeviceGroup::new()
+            .with_member("kitchen", "heater")
+            .with_member("bedroom", "heater")
+            .with_member("kitchen", "therm");
+
+        let failures = heaters.turn_on(&mut house).await;
+        assert!(failures.is_empty());
+
+        let status = heaters.status(&house);
+        assert_eq!(status.active_sockets, 2);
+        assert_eq!(status.total_power, Watts::new(2500.0));
+
+        heaters.turn_off(&mut house).await;
+        assert_eq!(heaters.status(&house).active_sockets, 0);
+    }
+
+    #[tokio::test]
+    async fn missing_members() {
+        let mut house = test_house();
+        let group = DeviceGroup::new()
+            .with_member("kitchen", "heater")
+            .with_member("garage", "heater");
+
+        let failures = group.turn_on(&mut house).await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].room, "garage");
+
+        let status = group.status(&house);
+        assert_eq!(status.missing, vec![("garage".into(), "heater".into())]);
+        assert!(status.report().contains("Unavailable: 1"));
+    }
+
+    #[test]
+    fn average_temperature() {
+        let house = test_house();
+        let therms = DeviceGroup::new()
+            .with_member("kitchen", "therm")
+            .with_member("bedroom", "therm");
+
+        let status = therms.status(&house);
+        assert_eq!(status.therms, 2);
+        assert_eq!(status.average_temperature, Some(Celsius::new(21.5)));
+        assert!(status.report().contains("21.5°C"));
+    }
+}
@@ -0,0 +1,559 @@
+//! История показаний устройств
+//!
+//! `History` хранит показания в памяти по рядам (устройство + вид
+//! показания). Свежие показания хранятся как есть, более старые
+//! сворачиваются в поминутные агрегаты и удаляются по истечении срока
+//! хранения (`RetentionPolicy`). Свертку выполняет `compact`, вручную или
+//! в фоновой задаче `spawn_compaction`. Для анализа вне приложения историю
+//! можно выгрузить в CSV или Parquet (`History::export`), а с feature
+//! `charts` - нарисовать график в SVG (`History::render_chart`).
+
+#[cfg(feature = "charts")]
+mod chart;
+mod export;
+
+#[cfg(feature = "charts")]
+pub use chart::{ChartError, ChartOptions};
+pub use export::{ExportError, ExportFormat};
+
+use crate::clock::{SharedClock, system_clock};
+#[cfg(feature = "net")]
+use crate::controllers::DeviceController;
+use crate::devices::Device;
+use crate::house::SmartHouse;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{MissedTickBehavior, interval};
+
+/// Вид показания
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadingKind {
+    /// Температура, °C
+    Temperature,
+    /// Мощность, Вт
+    Power,
+    /// Накопительное показание счетчика, кВт·ч
+    Energy,
+}
+
+impl fmt::Display for ReadingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Temperature => write!(f, "temperature"),
+            Self::Power => write!(f, "power"),
+            Self::Energy => write!(f, "energy"),
+        }
+    }
+}
+
+/// Показание устройства
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Идентификатор устройства (для дома - `комната/ключ`)
+    pub device_id: String,
+    /// Время показания, мс с Unix epoch
+    pub timestamp_ms: u64,
+    pub kind: ReadingKind,
+    pub value: f64,
+}
+
+/// Агрегат показаний за интервал
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Bucket {
+    /// Начало интервала, мс с Unix epoch
+    pub start_ms: u64,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    pub count: u64,
+}
+
+impl Bucket {
+    fn new(start_ms: u64, value: f64) -> Self {
+        Self {
+            start_ms,
+            min: value,
+            max: value,
+            sum: value,
+            count: 1,
+        }
+    }
+
+    /// Среднее значение
+    pub fn avg(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+
+    fn merge(&mut self, other: &Bucket) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+        self.count += other.count;
+    }
+}
+
+/// Сроки хранения истории
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+    /// Сколько хранить исходные показания
+    pub raw: Duration,
+    /// Интервал агрегатов для старых показаний
+    pub rollup_bucket: Duration,
+    /// Сколько хранить агрегаты
+    pub rollup: Duration,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            raw: Duration::from_secs(24 * 3600),
+            rollup_bucket: Duration::from_secs(60),
+            rollup: Duration::from_secs(30 * 24 * 3600),
+        }
+    }
+}
+
+/// Ряд показаний одного устройства и вида
+#[derive(Debug, Default)]
+struct Series {
+    /// Исходные показания по возрастанию времени
+    raw: VecDeque<(u64, f64)>,
+    /// Агрегаты по возрастанию времени
+    rollups: VecDeque<Bucket>,
+}
+
+type SeriesKey = (String, ReadingKind);
+
+/// История показаний; клоны работают с одним хранилищем
+#[derive(Debug, Clone)]
+pub struct History {
+    series: Arc<Mutex<HashMap<SeriesKey, Series>>>,
+    policy: RetentionPolicy,
+    clock: SharedClock,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(RetentionPolicy::default())
+    }
+}
+
+impl History {
+    /// Создает пустую историю
+    pub fn new(policy: RetentionPolicy) -> Self {
+        Self {
+            series: Arc::new(Mutex::new(HashMap::new())),
+            policy,
+            clock: system_clock(),
+        }
+    }
+
+    /// Builder: источник времени
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Сроки хранения
+    pub fn policy(&self) -> RetentionPolicy {
+        self.policy
+    }
+
+    /// Записывает показание с текущим временем
+    pub fn record(&self, device_id: &str, kind: ReadingKind, value: f64) {
+        self.record_at(device_id, kind, self.clock.now_ms(), value);
+    }
+
+    /// Записывает показание с заданным временем
+    pub fn record_at(&self, device_id: &str, kind: ReadingKind, timestamp_ms: u64, value: f64) {
+        let mut series = self.lock();
+        let raw = &mut series.entry((device_id.to_string(), kind)).or_default().raw;
+
+        // Показания обычно приходят по порядку; запоздавшие встают на свое место
+        let position = raw.partition_point(|(ts, _)| *ts <= timestamp_ms);
+        raw.insert(position, (timestamp_ms, value));
+    }
+
+    /// Записывает мощность розеток, температуру термометров и показания счетчиков дома
+    ///
+    /// Для контроллеров используются последние известные показания.
+    pub fn record_house(&self, house: &SmartHouse) {
+        let now = self.clock.now_ms();
+        for room_key in house.rooms_keys() {
+            let Some(room) = house.room(&room_key) else {
+                continue;
+            };
+            for key in room.devices_keys() {
+                let device_id = format!("{}/{}", room_key, key);
+                match room.device(&key) {
+                    Some(Device::Socket(socket)) => self.record_at(
+                        &device_id,
+                        ReadingKind::Power,
+                        now,
+                        socket.current_power().value(),
+                    ),
+                    Some(Device::Therm(therm)) => self.record_at(
+                        &device_id,
+                        ReadingKind::Temperature,
+                        now,
+                        therm.temperature().value(),
+                    ),
+                    Some(Device::Meter(meter)) => {
+                        self.record_at(&device_id, ReadingKind::Energy, now, meter.total_kwh())
+                    }
+                    // Остальные устройства показаний не дают
+                    _ => {}
+                }
+            }
+            #[cfg(feature = "net")]
+            for key in room.controllers_keys() {
+                let device_id = format!("{}/{}", room_key, key);
+                match room.controller(&key) {
+                    Some(DeviceController::Socket(socket)) => {
+                        if let Ok(socket) = socket.device() {
+                            let power = socket.current_power().value();
+                            self.record_at(&device_id, ReadingKind::Power, now, power);
+                        }
+                    }
+                    Some(DeviceController::Therm(therm)) => {
+                        // Без данных от датчика записывать нечего
+                        if let Ok(reading) = therm.temperature() {
+                            let value = reading.value.value();
+                            self.record_at(&device_id, ReadingKind::Temperature, now, value);
+                        }
+                    }
+                    #[cfg(feature = "blinds")]
+                    Some(DeviceController::Blinds(_)) => {}
+                    None => {}
+                }
+            }
+        }
+    }
+
+    /// Исходные показания за интервал времени
+    pub fn query(&self, device_id: &str, kind: ReadingKind, range: Range<u64>) -> Vec<Sample> {
+        let series = self.lock();
+        let Some(series) = series.get(&(device_id.to_string(), kind)) else {
+            return Vec::new();
+        };
+
+        series
+            .raw
+            .iter()
+            .filter(|(ts, _)| range.contains(ts))
+            .map(|&(timestamp_ms, value)| Sample {
+                device_id: device_id.to_string(),
+                timestamp_ms,
+                kind,
+                value,
+            })
+            .collect()
+    }
+
+    /// Показания ряда за интервал по времени, свернутые - средним агрегата
+    #[cfg(feature = "net")]
+    pub(crate) fn points(
+        &self,
+        device_id: &str,
+        kind: ReadingKind,
+        range: Range<u64>,
+    ) -> Vec<(u64, f64)> {
+        let series = self.lock();
+        let Some(series) = series.get(&(device_id.to_string(), kind)) else {
+            return Vec::new();
+        };
+        let rollups = series
+            .rollups
+            .iter()
+            .map(|bucket| (bucket.start_ms, bucket.avg()));
+        rollups
+            .chain(series.raw.iter().copied())
+            .filter(|(ts, _)| range.contains(ts))
+            .collect()
+    }
+
+    /// Последнее известное значение на момент `timestamp_ms`
+    ///
+    /// Для свернутых показаний возвращается среднее агрегата.
+    pub fn value_at(&self, device_id: &str, kind: ReadingKind, timestamp_ms: u64) -> Option<f64> {
+        let series = self.lock();
+        let series = series.get(&(device_id.to_string(), kind))?;
+
+        let raw = series.raw.partition_point(|(ts, _)| *ts <= timestamp_ms);
+        if raw > 0 {
+            return Some(series.raw[raw - 1].1);
+        }
+        let rollup = series
+            .rollups
+            .partition_point(|bucket| bucket.start_ms <= timestamp_ms);
+        (rollup > 0).then(|| series.rollups[rollup - 1].avg())
+    }
+
+    /// Показания, сгруппированные в интервалы `bucket`
+    ///
+    /// Учитываются и исходные показания, и агрегаты; агрегат целиком
+    /// попадает в интервал, содержащий его начало.
+    pub fn query_downsampled(
+        &self,
+        device_id: &str,
+        kind: ReadingKind,
+        bucket: Duration,
+    ) -> Vec<Bucket> {
+        let bucket_ms = (bucket.as_millis() as u64).max(1);
+        let series = self.lock();
+        let Some(series) = series.get(&(device_id.to_string(), kind)) else {
+            return Vec::new();
+        };
+
+        let mut buckets: BTreeMap<u64, Bucket> = BTreeMap::new();
+        let raw = series.raw.iter().map(|&(ts, value)| Bucket::new(ts, value));
+        for point in series.rollups.iter().copied().chain(raw) {
+            let start_ms = point.start_ms - point.start_ms % bucket_ms;
+            buckets
+                .entry(start_ms)
+                .and_modify(|bucket| bucket.merge(&point))
+                .or_insert(Bucket { start_ms, ..point });
+        }
+        buckets.into_values().collect()
+    }
+
+    /// Идентификаторы устройств и виды показаний в истории
+    pub fn series(&self) -> Vec<(String, ReadingKind)> {
+        let mut keys: Vec<_> = self.lock().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Количество исходных показаний и агрегатов
+    pub fn counts(&self) -> (usize, usize) {
+        self.lock().values().fold((0, 0), |(raw, rollups), series| {
+            (raw + series.raw.len(), rollups + series.rollups.len())
+        })
+    }
+
+    /// История пуста
+    pub fn is_empty(&self) -> bool {
+        self.counts() == (0, 0)
+    }
+
+    /// Сворачивает устаревшие показания в агрегаты и удаляет просроченные агрегаты
+    pub fn compact(&self) {
+        let now = self.clock.now_ms();
+        let raw_cutoff = now.saturating_sub(self.policy.raw.as_millis() as u64);
+        let rollup_cutoff = now.saturating_sub(self.policy.rollup.as_millis() as u64);
+        let bucket_ms = (self.policy.rollup_bucket.as_millis() as u64).max(1);
+
+        let mut all = self.lock();
+        for series in all.values_mut() {
+            while let Some(&(ts, value)) = series.raw.front()
+                && ts < raw_cutoff
+            {
+                series.raw.pop_front();
+                let start_ms = ts - ts % bucket_ms;
+                match series.rollups.back_mut() {
+                    Some(last) if last.start_ms == start_ms => {
+                        last.merge(&Bucket::new(start_ms, value))
+                    }
+                    _ => series.rollups.push_back(Bucket::new(start_ms, value)),
+                }
+            }
+
+            while series
+                .rollups
+                .front()
+                .is_some_and(|bucket| bucket.start_ms < rollup_cutoff)
+            {
+                series.rollups.pop_front();
+            }
+        }
+        all.retain(|_, series| !series.raw.is_empty() || !series.rollups.is_empty());
+    }
+
+    /// Запускает периодическую свертку; задача завершается вместе с историей
+    pub fn spawn_compaction(&self, period: Duration) -> JoinHandle<()> {
+        let series: Weak<_> = Arc::downgrade(&self.series);
+        let policy = self.policy;
+        let clock = self.clock.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = interval(period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(series) = series.upgrade() else {
+                    break;
+                };
+                History {
+                    series,
+                    policy,
+                    clock: clock.clone(),
+                }
+                .compact();
+            }
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<SeriesKey, Series>> {
+        self.series.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::devices::{SmartMeter, SmartSocket, SmartTherm};
+    use crate::room::Room;
+
+    const MINUTE: u64 = 60_000;
+
+    fn policy() -> RetentionPolicy {
+        RetentionPolicy {
+            raw: Duration::from_secs(3600),
+            rollup_bucket: Duration::from_secs(60),
+            rollup: Duration::from_secs(24 * 3600),
+        }
+    }
+
+    #[test]
+    fn record_and_query() {
+        let history = History::new(policy());
+        history.record_at("hall/therm", ReadingKind::Temperature, 2_000, 21.0);
+        history.record_at("hall/therm", ReadingKind::Temperature, 1_000, 20.0);
+        history.record_at("hall/therm", ReadingKind::Temperature, 3_000, 22.0);
+
+        let samples = history.query("hall/therm", ReadingKind::Temperature, 0..3_000);
+        let values: Vec<_> = samples.iter().map(|s| s.value).collect();
+        assert_eq!(values, vec![20.0, 21.0]);
+        assert!(
+            history
+                .query("hall/therm", ReadingKind::Power, 0..u64::MAX)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn value_at() {
+        let history = History::new(policy());
+        history.record_at("tv", ReadingKind::Power, 1_000, 100.0);
+        history.record_at("tv", ReadingKind::Power, 5_000, 0.0);
+
+        assert_eq!(history.value_at("tv", ReadingKind::Power, 999), None);
+        assert_eq!(
+            history.value_at("tv", ReadingKind::Power, 1_000),
+            Some(100.0)
+        );
+        assert_eq!(
+            history.value_at("tv", ReadingKind::Power, 4_999),
+            Some(100.0)
+        );
+        assert_eq!(history.value_at("tv", ReadingKind::Power, 9_000), Some(0.0));
+        assert_eq!(history.value_at("lamp", ReadingKind::Power, 9_000), None);
+    }
+
+    #[test]
+    fn downsampling() {
+        let history = History::new(policy());
+        for (ts, value) in [(0, 10.0), (30_000, 20.0), (MINUTE, 30.0), (90_000, 50.0)] {
+            history.record_at("kettle", ReadingKind::Power, ts, value);
+        }
+
+        let buckets =
+            history.query_downsampled("kettle", ReadingKind::Power, Duration::from_secs(60));
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].avg(), 15.0);
+        assert_eq!((buckets[1].min, buckets[1].max), (30.0, 50.0));
+
+        let total =
+            history.query_downsampled("kettle", ReadingKind::Power, Duration::from_secs(3600));
+        assert_eq!(total.len(), 1);
+        assert_eq!(total[0].count, 4);
+    }
+
+    #[test]
+    fn retention() {
+        let clock = Arc::new(MockClock::starting_at(0));
+        let history = History::new(policy()).with_clock(clock.clone());
+
+        history.record("tv", ReadingKind::Power, 100.0);
+        clock.advance(Duration::from_secs(30));
+        history.record("tv", ReadingKind::Power, 200.0);
+        clock.advance(Duration::from_secs(2 * 3600));
+        history.record("tv", ReadingKind::Power, 300.0);
+
+        // Два старых показания свернулись в один поминутный агрегат
+        history.compact();
+        assert_eq!(history.counts(), (1, 1));
+        let buckets = history.query_downsampled("tv", ReadingKind::Power, Duration::from_secs(60));
+        assert_eq!(buckets[0].avg(), 150.0);
+        assert_eq!(
+            history.query("tv", ReadingKind::Power, 0..u64::MAX).len(),
+            1
+        );
+
+        // Агрегаты старше суток удаляются
+        clock.advance(Duration::from_secs(23 * 3600));
+        history.compact();
+        assert_eq!(history.counts(), (0, 1));
+        clock.advance(Duration::from_secs(24 * 3600));
+        history.compact();
+        assert!(history.is_empty());
+        assert!(history.series().is_empty());
+    }
+
+    #[test]
+    fn record_house() {
+        let clock = Arc::new(MockClock::starting_at(5_000));
+        let history = History::default().with_clock(clock);
+
+        let mut socket = SmartSocket::new(500.0);
+        socket.turn_on();
+        let mut room = Room::new();
+        room.add_device("lamp", Device::Socket(socket));
+        room.add_device("therm", Device::Therm(SmartTherm::new(19.5)));
+        room.add_device("meter", Device::Meter(SmartMeter::new(1250.0)));
+        let mut house = SmartHouse::default();
+        house.add_room("hall", room);
+
+        history.record_house(&house);
+        assert_eq!(
+            history.series(),
+            vec![
+                ("hall/lamp".to_string(), ReadingKind::Power),
+                ("hall/meter".to_string(), ReadingKind::Energy),
+                ("hall/therm".to_string(), ReadingKind::Temperature),
+            ]
+        );
+        assert_eq!(
+            history.value_at("hall/meter", ReadingKind::Energy, 5_000),
+            Some(1250.0)
+        );
+        let samples = history.query("hall/lamp", ReadingKind::Power, 0..u64::MAX);
+        assert_eq!(samples[0].value, 500.0);
+        assert_eq!(samples[0].timestamp_ms, 5_000);
+    }
+
+    #[tokio::test]
+    async fn background_compaction_stops_with_history() {
+        let clock = Arc::new(MockClock::starting_at(0));
+        let history = History::new(policy()).with_clock(clock.clone());
+        history.record("tv", ReadingKind::Power, 100.0);
+        clock.advance(Duration::from_secs(2 * 3600));
+
+        let task = history.spawn_compaction(Duration::from_millis(5));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(history.counts(), (0, 1));
+
+        drop(history);
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
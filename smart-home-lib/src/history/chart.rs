@@ -0,0 +1,185 @@
+//! Графики истории показаний (feature `charts`)
+//!
+//! `History::render_chart` рисует показания устройства за интервал в SVG:
+//! по панели на каждый вид показаний с собственной шкалой, так что
+//! температура и мощность одного устройства не делят одну ось. Свернутые
+//! показания рисуются средним значением агрегата. SVG можно вставить в
+//! HTML отчет или отдать по HTTP как есть.
+
+use super::{History, ReadingKind, Sample};
+use plotters::prelude::*;
+use std::fmt;
+use std::ops::Range;
+
+/// Размеры графика
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartOptions {
+    /// Ширина, пикселей
+    pub width: u32,
+    /// Высота панели одного вида показаний, пикселей
+    pub panel_height: u32,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            panel_height: 300,
+        }
+    }
+}
+
+/// Ошибка построения графика
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartError {
+    /// Нет показаний устройства за интервал
+    NoData(String),
+    /// Ошибка отрисовки
+    Render(String),
+}
+
+impl fmt::Display for ChartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoData(device_id) => write!(f, "Нет показаний для графика: {}", device_id),
+            Self::Render(e) => write!(f, "Ошибка отрисовки графика: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ChartError {}
+
+impl History {
+    /// График показаний устройства за интервал времени в SVG
+    pub fn render_chart(&self, device_id: &str, range: Range<u64>) -> Result<String, ChartError> {
+        self.render_chart_with(device_id, range, &ChartOptions::default())
+    }
+
+    /// Как `render_chart`, но с заданными размерами
+    pub fn render_chart_with(
+        &self,
+        device_id: &str,
+        range: Range<u64>,
+        options: &ChartOptions,
+    ) -> Result<String, ChartError> {
+        let samples: Vec<Sample> = self
+            .samples(range)
+            .into_iter()
+            .filter(|sample| sample.device_id == device_id)
+            .collect();
+        let mut kinds: Vec<ReadingKind> = samples.iter().map(|sample| sample.kind).collect();
+        kinds.sort();
+        kinds.dedup();
+        if kinds.is_empty() {
+            return Err(ChartError::NoData(device_id.to_string()));
+        }
+
+        let mut svg = String::new();
+        {
+            let size = (options.width, options.panel_height * kinds.len() as u32);
+            let root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+            root.fill(&WHITE).map_err(render_error)?;
+            let panels = root.split_evenly((kinds.len(), 1));
+            for (panel, &kind) in panels.iter().zip(&kinds) {
+                let points: Vec<(u64, f64)> = samples
+                    .iter()
+                    .filter(|sample| sample.kind == kind)
+                    .map(|sample| (sample.timestamp_ms, sample.value))
+                    .collect();
+                draw_panel(panel, device_id, kind, &points)?;
+            }
+            root.present().map_err(render_error)?;
+        }
+        Ok(svg)
+    }
+}
+
+/// Рисует ряд одного вида показаний (точки упорядочены по времени)
+fn draw_panel<DB: DrawingBackend>(
+    area: &DrawingArea<DB, plotters::coord::Shift>,
+    device_id: &str,
+    kind: ReadingKind,
+    points: &[(u64, f64)],
+) -> Result<(), ChartError> {
+    let start = points.first().map_or(0, |point| point.0);
+    let end = points.last().map_or(start, |point| point.0).max(start + 1);
+    let (min, max) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), point| {
+            (min.min(point.1), max.max(point.1))
+        });
+    // Постоянное значение рисуется посередине панели
+    let margin = ((max - min) * 0.1).max(0.5);
+
+    let mut chart = ChartBuilder::on(area)
+        .caption(format!("{}: {}", device_id, kind), ("sans-serif", 18))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .build_cartesian_2d(start..end, (min - margin)..(max + margin))
+        .map_err(render_error)?;
+    chart
+        .configure_mesh()
+        .x_label_formatter(&|ms| clock_label(*ms))
+        .y_desc(unit(kind))
+        .draw()
+        .map_err(render_error)?;
+    chart
+        .draw_series(LineSeries::new(points.iter().copied(), &BLUE))
+        .map_err(render_error)?;
+    Ok(())
+}
+
+/// Время `ЧЧ:ММ` по UTC
+fn clock_label(timestamp_ms: u64) -> String {
+    let minutes = timestamp_ms / 60_000 % (24 * 60);
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Единица шкалы показаний
+fn unit(kind: ReadingKind) -> &'static str {
+    match kind {
+        ReadingKind::Temperature => "°C",
+        ReadingKind::Power => "Вт",
+        ReadingKind::Energy => "кВт·ч",
+    }
+}
+
+fn render_error(e: impl fmt::Display) -> ChartError {
+    ChartError::Render(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_panel_per_kind() {
+        let history = History::default();
+        for minute in 0..30 {
+            let ts = minute * 60_000;
+            history.record_at(
+                "hall/heater",
+                ReadingKind::Temperature,
+                ts,
+                20.0 + minute as f64 * 0.1,
+            );
+            history.record_at("hall/heater", ReadingKind::Power, ts, 1500.0);
+        }
+        history.record_at("hall/lamp", ReadingKind::Power, 0, 60.0);
+
+        let svg = history.render_chart("hall/heater", 0..u64::MAX).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("hall/heater: temperature"));
+        assert!(svg.contains("hall/heater: power"));
+        assert!(svg.contains("00:10"));
+        // Линии рядов синие, сетка - серая
+        assert_eq!(svg.matches(r##"stroke="#0000FF""##).count(), 2);
+
+        assert_eq!(
+            history.render_chart("hall/heater", 3_600_000..7_200_000),
+            Err(ChartError::NoData("hall/heater".to_string()))
+        );
+        assert_eq!(clock_label(13 * 3_600_000 + 5 * 60_000), "13:05");
+    }
+}
@@ -0,0 +1,268 @@
+//! Выгрузка истории показаний
+//!
+//! Показания выгружаются одной таблицей с колонками `device_id`,
+//! `timestamp_ms`, `kind`, `value` в CSV или (с feature `parquet`) в
+//! Parquet - для анализа в pandas, DuckDB и т.п.
+
+use super::{History, Sample};
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Заголовок CSV
+const CSV_HEADER: &str = "device_id,timestamp_ms,kind,value";
+
+/// Формат выгрузки
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// CSV с заголовком
+    Csv,
+    /// Parquet, одна группа строк
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+/// Ошибка выгрузки
+#[derive(Debug)]
+pub enum ExportError {
+    /// Ошибка записи
+    Io(io::Error),
+    /// Ошибка формирования Parquet
+    #[cfg(feature = "parquet")]
+    Parquet(parquet::errors::ParquetError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Ошибка записи выгрузки: {}", e),
+            #[cfg(feature = "parquet")]
+            Self::Parquet(e) => write!(f, "Ошибка формирования Parquet: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<io::Error> for ExportError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for ExportError {
+    fn from(e: parquet::errors::ParquetError) -> Self {
+        Self::Parquet(e)
+    }
+}
+
+impl History {
+    /// Выгружает показания всех рядов за интервал времени
+    ///
+    /// Строки упорядочены по времени, затем по устройству и виду.
+    /// Показания, уже свернутые в агрегаты, выгружаются средним значением
+    /// на начало агрегата. Возвращает количество выгруженных строк.
+    pub fn export<W: Write + Send>(
+        &self,
+        range: Range<u64>,
+        format: ExportFormat,
+        writer: W,
+    ) -> Result<usize, ExportError> {
+        let samples = self.samples(range);
+        match format {
+            ExportFormat::Csv => write_csv(&samples, writer)?,
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => write_parquet(&samples, writer)?,
+        }
+        Ok(samples.len())
+    }
+
+    /// Показания всех рядов за интервал, включая средние значения агрегатов
+    pub(super) fn samples(&self, range: Range<u64>) -> Vec<Sample> {
+        let series = self.lock();
+        let mut samples = Vec::new();
+        for ((device_id, kind), series) in series.iter() {
+            let rollups = series.rollups.iter().map(|b| (b.start_ms, b.avg()));
+            let raw = series.raw.iter().copied();
+            for (timestamp_ms, value) in rollups.chain(raw) {
+                if range.contains(&timestamp_ms) {
+                    samples.push(Sample {
+                        device_id: device_id.clone(),
+                        timestamp_ms,
+                        kind: *kind,
+                        value,
+                    });
+                }
+            }
+        }
+        samples.sort_by(|a, b| {
+            (a.timestamp_ms, &a.device_id, a.kind).cmp(&(b.timestamp_ms, &b.device_id, b.kind))
+        });
+        samples
+    }
+}
+
+fn write_csv<W: Write>(samples: &[Sample], writer: W) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(writer);
+    writeln!(writer, "{}", CSV_HEADER)?;
+    for sample in samples {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            csv_field(&sample.device_id),
+            sample.timestamp_ms,
+            sample.kind,
+            sample.value
+        )?;
+    }
+    writer.flush()
+}
+
+/// Поле CSV, при необходимости в кавычках (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet<W: Write + Send>(
+    samples: &[Sample],
+    writer: W,
+) -> Result<(), parquet::errors::ParquetError> {
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    let schema = parse_message_type(
+        "message sample {
+            REQUIRED BYTE_ARRAY device_id (UTF8);
+            REQUIRED INT64 timestamp_ms (TIMESTAMP(MILLIS, true));
+            REQUIRED BYTE_ARRAY kind (UTF8);
+            REQUIRED DOUBLE value;
+        }",
+    )?;
+    let properties = WriterProperties::builder().build();
+    let mut file = SerializedFileWriter::new(writer, Arc::new(schema), Arc::new(properties))?;
+
+    let device_ids: Vec<ByteArray> = samples
+        .iter()
+        .map(|s| s.device_id.as_str().into())
+        .collect();
+    let timestamps: Vec<i64> = samples.iter().map(|s| s.timestamp_ms as i64).collect();
+    let kinds: Vec<ByteArray> = samples
+        .iter()
+        .map(|s| s.kind.to_string().into_bytes().into())
+        .collect();
+    let values: Vec<f64> = samples.iter().map(|s| s.value).collect();
+
+    let mut row_group = file.next_row_group()?;
+    let mut index = 0;
+    while let Some(mut column) = row_group.next_column()? {
+        match index {
+            0 => column
+                .typed::<ByteArrayType>()
+                .write_batch(&device_ids, None, None)?,
+            1 => column
+                .typed::<Int64Type>()
+                .write_batch(&timestamps, None, None)?,
+            2 => column
+                .typed::<ByteArrayType>()
+                .write_batch(&kinds, None, None)?,
+            _ => column
+                .typed::<DoubleType>()
+                .write_batch(&values, None, None)?,
+        };
+        column.close()?;
+        index += 1;
+    }
+    row_group.close()?;
+    file.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::history::{ReadingKind, RetentionPolicy};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn history() -> History {
+        let history = History::new(RetentionPolicy::default());
+        history.record_at("hall/socket", ReadingKind::Power, 2_000, 1500.0);
+        history.record_at("hall/therm", ReadingKind::Temperature, 1_000, 21.5);
+        history.record_at("hall/therm", ReadingKind::Temperature, 3_000, 22.0);
+        history.record_at("hall, east/therm", ReadingKind::Temperature, 2_000, 19.0);
+        history
+    }
+
+    #[test]
+    fn csv_export() {
+        let mut out = Vec::new();
+        let rows = history()
+            .export(0..3_000, ExportFormat::Csv, &mut out)
+            .unwrap();
+
+        assert_eq!(rows, 3);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "device_id,timestamp_ms,kind,value\n\
+             hall/therm,1000,temperature,21.5\n\
+             \"hall, east/therm\",2000,temperature,19\n\
+             hall/socket,2000,power,1500\n"
+        );
+    }
+
+    #[test]
+    fn export_includes_rollups() {
+        let clock = Arc::new(MockClock::starting_at(0));
+        let history = History::new(RetentionPolicy {
+            raw: Duration::from_secs(60),
+            rollup_bucket: Duration::from_secs(60),
+            rollup: Duration::from_secs(3600),
+        })
+        .with_clock(clock.clone());
+        history.record_at("hall/therm", ReadingKind::Temperature, 1_000, 20.0);
+        history.record_at("hall/therm", ReadingKind::Temperature, 2_000, 22.0);
+        clock.advance(Duration::from_secs(120));
+        history.compact();
+
+        let mut out = Vec::new();
+        history
+            .export(0..u64::MAX, ExportFormat::Csv, &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap().lines().nth(1),
+            Some("hall/therm,0,temperature,21")
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_export() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::RowAccessor;
+
+        let path = std::env::temp_dir().join(format!("history-{}.parquet", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let rows = history()
+            .export(0..u64::MAX, ExportFormat::Parquet, file)
+            .unwrap();
+        assert_eq!(rows, 4);
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 4);
+        let first = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        assert_eq!(first.get_string(0).unwrap(), "hall/therm");
+        assert_eq!(first.get_string(2).unwrap(), "temperature");
+        assert_eq!(first.get_double(3).unwrap(), 21.5);
+        std::fs::remove_file(path).unwrap();
+    }
+}
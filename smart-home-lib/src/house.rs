@@ -1,14 +1,40 @@
 //! Модуль для работы с умным домом
 
+#[cfg(feature = "net")]
+use crate::audit::AuditLog;
+use crate::clock::now_ms;
 use crate::controllers::DeviceController;
+#[cfg(feature = "net")]
+use crate::controllers::{ControllerConfig, ControllerError, SubscriptionHandle, ThermError};
 use crate::devices::Device;
-use crate::room::Room;
-use crate::traits::Reporter;
+#[cfg(feature = "net")]
+use crate::discovery::DiscoveredDevice;
+use crate::events::{EventBus, HouseEvent, TopologyChange};
+use crate::group::{DeviceGroup, GroupFailure};
+use crate::report::{DeviceHealth, HealthReport, HouseReport, ReportOptions, RoomReport};
+use crate::room::{RenameError, Room, rename_key};
+use crate::traits::{AsyncReporter, Lifecycle, Reporter};
+#[cfg(feature = "net")]
+use crate::units::Celsius;
+use indexmap::IndexMap;
+#[cfg(feature = "net")]
 use std::collections::HashMap;
 use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
+#[cfg(feature = "net")]
+use tokio::task::JoinSet;
+use tokio::time::{MissedTickBehavior, interval};
+use tokio_stream::wrappers::IntervalStream;
+use tokio_stream::{Stream, StreamExt};
 
 /// Макрос для упрощенного создания умного дома с комнатами
+///
+/// Принимает пары `(ключ, комната)` или описание в виде `ключ => комната`,
+/// где комната - выражение `Room` или содержимое `room!` в фигурных
+/// скобках. Комнаты можно объединять в этажи: `floor "имя" { ... }`
+/// добавляет комнаты в дом и создает группу с их устройствами:
+/// `house! { "кухня" => { "чайник" => socket(2000.0) }, floor "2" { ... } }`.
 #[macro_export]
 macro_rules! house {
     ($(($key:expr, $room:expr)),* $(,)?) => {{
@@ -18,6 +44,33 @@ macro_rules! house {
         )*
         house
     }};
+
+    (@rooms $house:ident;) => {};
+    (@rooms $house:ident; floor $floor:literal { $($rooms:tt)* } $(,)? $($rest:tt)*) => {
+        let existing = $house.rooms_keys();
+        $crate::house!(@rooms $house; $($rooms)*);
+        let floor_rooms: Vec<String> = $house
+            .rooms_keys()
+            .into_iter()
+            .filter(|room| !existing.contains(room))
+            .collect();
+        $house.add_room_group($floor, &floor_rooms);
+        $crate::house!(@rooms $house; $($rest)*);
+    };
+    (@rooms $house:ident; $key:literal => { $($items:tt)* } $(, $($rest:tt)*)?) => {
+        $house.add_room($key, $crate::room!($($items)*));
+        $crate::house!(@rooms $house; $($($rest)*)?);
+    };
+    (@rooms $house:ident; $key:literal => $room:expr $(, $($rest:tt)*)?) => {
+        $house.add_room($key, $room);
+        $crate::house!(@rooms $house; $($($rest)*)?);
+    };
+
+    ($($rooms:tt)+) => {{
+        let mut house = $crate::house::SmartHouse::default();
+        $crate::house!(@rooms house; $($rooms)+);
+        house
+    }};
 }
 
 /// Ошибки, возникающие при работе с умным домом
@@ -28,21 +81,112 @@ pub enum SmartHouseError {
 
     #[error("Device '{1}' not found in room '{0}'")]
     DeviceNotFound(String, String),
+
+    #[error("Key '{1}' is already used in room '{0}'")]
+    KeyExists(String, String),
+
+    #[error("Group not found: '{0}'")]
+    GroupNotFound(String),
+
+    #[error("Room '{0}' already exists")]
+    RoomExists(String),
+
+    #[error("Group '{0}' already exists")]
+    GroupExists(String),
+
+    #[error("Address of '{1}' in room '{0}' is out of range")]
+    AddressOutOfRange(String, String),
+
+    #[error("Transaction step {index} failed: {source}")]
+    Transaction {
+        index: usize,
+        source: Box<SmartHouseError>,
+    },
+
+    #[cfg(feature = "net")]
+    #[error("{0}")]
+    Controller(Box<ControllerError>),
+
+    #[error("Controllers failed: {}", format_failures(.0))]
+    Controllers(Vec<GroupFailure>),
+}
+
+fn format_failures(failures: &[GroupFailure]) -> String {
+    failures
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Результат операции над всеми контроллерами
+fn controllers_result(failures: Vec<GroupFailure>) -> SmartHouseResult<()> {
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(SmartHouseError::Controllers(failures))
+    }
+}
+
+#[cfg(feature = "net")]
+impl From<ControllerError> for SmartHouseError {
+    fn from(e: ControllerError) -> Self {
+        Self::Controller(Box::new(e))
+    }
 }
 
 /// Результат выполнения операции
 pub type SmartHouseResult<T> = Result<T, SmartHouseError>;
 
 /// Умный дом, содержащий список комнат
+///
+/// Комнаты и группы хранятся в порядке добавления: отчеты и списки
+/// ключей не меняются от запуска к запуску.
 #[derive(Default)]
 pub struct SmartHouse {
-    rooms: HashMap<String, Room>,
+    rooms: IndexMap<String, Room>,
+    groups: IndexMap<String, DeviceGroup>,
+    /// Шина событий изменения состава дома
+    events: EventBus,
+    /// Журнал команд `execute`
+    #[cfg(feature = "net")]
+    audit: Option<AuditLog>,
 }
 
 impl SmartHouse {
-    /// Создает новый дом с заданными комнатами
-    pub fn new(rooms: HashMap<String, Room>) -> Self {
-        Self { rooms }
+    /// Создает новый дом с заданными комнатами (в порядке итератора)
+    pub fn new(rooms: impl IntoIterator<Item = (String, Room)>) -> Self {
+        Self {
+            rooms: rooms.into_iter().collect(),
+            groups: IndexMap::new(),
+            events: EventBus::default(),
+            #[cfg(feature = "net")]
+            audit: None,
+        }
+    }
+
+    /// Builder: шина, в которую публикуются события изменения состава дома
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Шина событий дома
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Builder: журнал команд, выполняемых через `execute`
+    #[cfg(feature = "net")]
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Журнал команд дома
+    #[cfg(feature = "net")]
+    pub fn audit(&self) -> Option<&AuditLog> {
+        self.audit.as_ref()
     }
 
     /// Возвращает неизменяемую ссылку на комнату по индексу
@@ -51,18 +195,208 @@ impl SmartHouse {
     }
 
     /// Возвращает изменяемую ссылку на комнату по индексу
+    ///
+    /// В шину публикуется `HouseEvent::RoomChanged`: изменения через
+    /// ссылку не отслеживаются, поэтому комната считается измененной.
     pub fn room_mut(&mut self, key: &str) -> Option<&mut Room> {
-        self.rooms.get_mut(key)
+        let room = self.rooms.get_mut(key)?;
+        self.events.publish(HouseEvent::RoomChanged {
+            room: key.to_string(),
+        });
+        Some(room)
     }
 
-    /// Добавляет комнату в дом
+    /// Добавляет комнату в дом, заменяя комнату с тем же ключом
+    ///
+    /// В шину публикуется `HouseEvent::TopologyChanged` с `RoomAdded`.
     pub fn add_room(&mut self, key: &str, room: Room) {
+        self.add_room_silently(key, room);
+        self.events.publish(HouseEvent::TopologyChanged {
+            changes: vec![TopologyChange::RoomAdded {
+                room: key.to_string(),
+            }],
+        });
+    }
+
+    /// Добавляет комнату без публикации события
+    pub(crate) fn add_room_silently(&mut self, key: &str, room: Room) {
         self.rooms.insert(key.to_string(), room);
     }
 
     /// Удаляет комнату из дома
+    ///
+    /// Если комната была, в шину публикуется `HouseEvent::TopologyChanged`
+    /// с `RoomRemoved`.
     pub fn remove_room(&mut self, key: &str) -> Option<Room> {
-        self.rooms.remove(key)
+        let room = self.remove_room_silently(key)?;
+        self.events.publish(HouseEvent::TopologyChanged {
+            changes: vec![TopologyChange::RoomRemoved {
+                room: key.to_string(),
+            }],
+        });
+        Some(room)
+    }
+
+    /// Удаляет комнату без публикации события
+    pub(crate) fn remove_room_silently(&mut self, key: &str) -> Option<Room> {
+        self.rooms.shift_remove(key)
+    }
+
+    /// Переименовывает комнату и обновляет ссылки на ее устройства в группах
+    ///
+    /// Позиция комнаты сохраняется. Если имя занято, дом не меняется.
+    /// В шину публикуется `HouseEvent::TopologyChanged` с `RoomRenamed`.
+    pub fn rename_room(&mut self, old: &str, new: &str) -> SmartHouseResult<()> {
+        self.rename_room_silently(old, new)?;
+        self.events.publish(HouseEvent::TopologyChanged {
+            changes: vec![TopologyChange::RoomRenamed {
+                from: old.to_string(),
+                to: new.to_string(),
+            }],
+        });
+        Ok(())
+    }
+
+    /// Переименовывает комнату без публикации события
+    pub(crate) fn rename_room_silently(&mut self, old: &str, new: &str) -> SmartHouseResult<()> {
+        if !self.rooms.contains_key(old) {
+            return Err(SmartHouseError::RoomNotFound(old.to_string()));
+        }
+        if old == new {
+            return Ok(());
+        }
+        if self.rooms.contains_key(new) {
+            return Err(SmartHouseError::RoomExists(new.to_string()));
+        }
+
+        rename_key(&mut self.rooms, old, new);
+        for group in self.groups.values_mut() {
+            group.rename_room(old, new);
+        }
+        Ok(())
+    }
+
+    /// Переименовывает устройство или контроллер комнаты и обновляет ссылки в группах
+    ///
+    /// Если ключ занят, дом не меняется. В шину публикуется
+    /// `HouseEvent::RoomChanged`.
+    pub fn rename_item(&mut self, room_key: &str, old: &str, new: &str) -> SmartHouseResult<()> {
+        self.rename_item_silently(room_key, old, new)?;
+        self.events.publish(HouseEvent::RoomChanged {
+            room: room_key.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Переименовывает элемент без публикации события
+    pub(crate) fn rename_item_silently(
+        &mut self,
+        room_key: &str,
+        old: &str,
+        new: &str,
+    ) -> SmartHouseResult<()> {
+        let room = self
+            .rooms
+            .get_mut(room_key)
+            .ok_or(SmartHouseError::RoomNotFound(room_key.to_string()))?;
+        room.rename_item(old, new).map_err(|e| match e {
+            RenameError::NotFound(key) => {
+                SmartHouseError::DeviceNotFound(room_key.to_string(), key)
+            }
+            RenameError::KeyExists(key) => SmartHouseError::KeyExists(room_key.to_string(), key),
+        })?;
+
+        for group in self.groups.values_mut() {
+            group.rename_member(room_key, old, new);
+        }
+        Ok(())
+    }
+
+    /// Переносит локальное устройство в другую комнату
+    ///
+    /// Устройство переносится вместе с метаданными, ссылки в группах
+    /// обновляются, в шину публикуется `HouseEvent::DeviceMoved`. Если ключ
+    /// в целевой комнате занят, дом не меняется.
+    pub fn move_device(
+        &mut self,
+        from_room: &str,
+        key: &str,
+        to_room: &str,
+    ) -> SmartHouseResult<()> {
+        self.move_item(from_room, key, to_room, |room, key| {
+            room.device(key).is_some()
+        })
+    }
+
+    /// Переносит контроллер в другую комнату (как `move_device`)
+    ///
+    /// Контроллер переносится вместе с соединением и фоновыми задачами.
+    pub fn move_controller(
+        &mut self,
+        from_room: &str,
+        key: &str,
+        to_room: &str,
+    ) -> SmartHouseResult<()> {
+        self.move_item(from_room, key, to_room, |room, key| {
+            room.controller(key).is_some()
+        })
+    }
+
+    fn move_item(
+        &mut self,
+        from_room: &str,
+        key: &str,
+        to_room: &str,
+        exists: fn(&Room, &str) -> bool,
+    ) -> SmartHouseResult<()> {
+        let target = self
+            .rooms
+            .get(to_room)
+            .ok_or(SmartHouseError::RoomNotFound(to_room.to_string()))?;
+        let source = self
+            .rooms
+            .get(from_room)
+            .ok_or(SmartHouseError::RoomNotFound(from_room.to_string()))?;
+        if !exists(source, key) {
+            return Err(SmartHouseError::DeviceNotFound(
+                from_room.to_string(),
+                key.to_string(),
+            ));
+        }
+        if from_room == to_room {
+            return Ok(());
+        }
+        if target.contains_key(key) {
+            return Err(SmartHouseError::KeyExists(
+                to_room.to_string(),
+                key.to_string(),
+            ));
+        }
+
+        self.relocate_item(from_room, key, to_room);
+        self.events.publish(HouseEvent::DeviceMoved {
+            key: key.to_string(),
+            from_room: from_room.to_string(),
+            to_room: to_room.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Переносит проверенный элемент без публикации события
+    pub(crate) fn relocate_item(&mut self, from_room: &str, key: &str, to_room: &str) {
+        let Some((item, metadata)) = self
+            .rooms
+            .get_mut(from_room)
+            .and_then(|room| room.take_item(key))
+        else {
+            return;
+        };
+        if let Some(room) = self.rooms.get_mut(to_room) {
+            room.put_item(key, item, metadata);
+        }
+        for group in self.groups.values_mut() {
+            group.move_member(from_room, key, to_room);
+        }
     }
 
     /// Получает прямую ссылку на устройство по имени комнаты и устройства
@@ -121,18 +455,373 @@ impl SmartHouse {
             ))
     }
 
+    /// Возвращает группу устройств по имени
+    pub fn group(&self, name: &str) -> Option<&DeviceGroup> {
+        self.groups.get(name)
+    }
+
+    /// Возвращает изменяемую группу устройств по имени
+    pub fn group_mut(&mut self, name: &str) -> Option<&mut DeviceGroup> {
+        self.groups.get_mut(name)
+    }
+
+    /// Добавляет группу устройств
+    pub fn add_group(&mut self, name: &str, group: DeviceGroup) {
+        self.groups.insert(name.to_string(), group);
+    }
+
+    /// Создает группу из всех устройств и контроллеров комнат (например, этажа)
+    ///
+    /// Элементы, добавленные в комнаты позже, в группу не попадают.
+    pub fn add_room_group<S: AsRef<str>>(&mut self, name: &str, rooms: &[S]) {
+        let mut group = DeviceGroup::new();
+        for room_key in rooms {
+            let room_key = room_key.as_ref();
+            if let Some(room) = self.rooms.get(room_key) {
+                for key in room.keys() {
+                    group.add_member(room_key, &key);
+                }
+            }
+        }
+        self.add_group(name, group);
+    }
+
+    /// Удаляет группу устройств
+    pub fn remove_group(&mut self, name: &str) -> Option<DeviceGroup> {
+        self.groups.shift_remove(name)
+    }
+
+    /// Возвращает список имен всех групп
+    pub fn groups_keys(&self) -> Vec<String> {
+        self.groups.keys().cloned().collect()
+    }
+
+    /// Включает или выключает все розетки группы
+    pub async fn switch_group(
+        &mut self,
+        name: &str,
+        on: bool,
+    ) -> SmartHouseResult<Vec<GroupFailure>> {
+        let group = self
+            .group(name)
+            .cloned()
+            .ok_or(SmartHouseError::GroupNotFound(name.to_string()))?;
+
+        Ok(if on {
+            group.turn_on(self).await
+        } else {
+            group.turn_off(self).await
+        })
+    }
+
+    /// Подключает обнаруженное устройство: создает контроллер нужного типа,
+    /// запускает его и регистрирует в комнате (комната создается при необходимости)
+    ///
+    /// В шину публикуется `RoomChanged` или, для новой комнаты,
+    /// `TopologyChanged` с `RoomAdded`.
+    #[cfg(feature = "net")]
+    pub fn adopt(
+        &mut self,
+        discovered: DiscoveredDevice,
+        room_key: &str,
+        key: &str,
+    ) -> SmartHouseResult<()> {
+        self.adopt_with(discovered, room_key, key, &ControllerConfig::default())
+    }
+
+    /// Подключает обнаруженное устройство с заданными рабочими параметрами контроллера
+    #[cfg(feature = "net")]
+    pub fn adopt_with(
+        &mut self,
+        discovered: DiscoveredDevice,
+        room_key: &str,
+        key: &str,
+        settings: &ControllerConfig,
+    ) -> SmartHouseResult<()> {
+        self.add_adopted(room_key, key, || discovered.into_controller_with(settings))
+    }
+
+    /// Подключает обнаруженное устройство, не запуская контроллер
+    #[cfg(feature = "net")]
+    pub(crate) fn adopt_stopped(
+        &mut self,
+        discovered: DiscoveredDevice,
+        room_key: &str,
+        key: &str,
+        settings: &ControllerConfig,
+    ) -> SmartHouseResult<()> {
+        self.add_adopted(room_key, key, || {
+            discovered.into_stopped_controller(settings)
+        })
+    }
+
+    /// Контроллер создается только под свободный ключ
+    #[cfg(feature = "net")]
+    fn add_adopted(
+        &mut self,
+        room_key: &str,
+        key: &str,
+        controller: impl FnOnce() -> DeviceController,
+    ) -> SmartHouseResult<()> {
+        let room_added = !self.rooms.contains_key(room_key);
+        let room = self.rooms.entry(room_key.to_string()).or_default();
+        if room.device(key).is_some() || room.controller(key).is_some() {
+            return Err(SmartHouseError::KeyExists(
+                room_key.to_string(),
+                key.to_string(),
+            ));
+        }
+
+        room.add_controller(key, controller());
+        let room = room_key.to_string();
+        self.events.publish(if room_added {
+            HouseEvent::TopologyChanged {
+                changes: vec![TopologyChange::RoomAdded { room }],
+            }
+        } else {
+            HouseEvent::RoomChanged { room }
+        });
+        Ok(())
+    }
+
     /// Формирует текстовый отчет о состоянии всех комнат в доме
     pub fn report_lines(&self) -> Vec<String> {
+        self.report_lines_with(&ReportOptions::default())
+    }
+
+    /// Как `report_lines`, но с заданными единицами и точностью
+    pub fn report_lines_with(&self, options: &ReportOptions) -> Vec<String> {
         self.rooms
             .iter()
-            .flat_map(|(key, room)| {
-                let mut report = vec![format!("Room: {}", key)];
-                report.extend(room.report_lines().iter().map(|s| format!("  {}", s)));
-                report
-            })
+            .flat_map(|(key, room)| room_report_lines(key, room, options))
             .collect()
     }
 
+    /// Как `report_lines`, но контроллеры предварительно опрашиваются
+    ///
+    /// Каждый контроллер опрашивается не дольше `timeout`.
+    pub async fn live_report_lines(&mut self, timeout: Duration) -> Vec<String> {
+        let mut report = Vec::new();
+        for (key, room) in self.rooms.iter_mut() {
+            report.push(format!("Room: {}", key));
+            let lines = room.live_report_lines(timeout).await;
+            report.extend(lines.iter().map(|s| format!("  {}", s)));
+        }
+        report
+    }
+
+    /// Структурированный отчет о текущем состоянии дома
+    pub fn snapshot(&self) -> HouseReport {
+        self.snapshot_with(&ReportOptions::default())
+    }
+
+    /// Как `snapshot`, но строки отчета формируются с `options`
+    pub fn snapshot_with(&self, options: &ReportOptions) -> HouseReport {
+        HouseReport {
+            timestamp_ms: now_ms(),
+            rooms: self
+                .rooms
+                .iter()
+                .map(|(key, room)| RoomReport {
+                    room: key.clone(),
+                    items: room.item_reports_with(options),
+                    summary: room.summary(),
+                })
+                .collect(),
+            options: *options,
+        }
+    }
+
+    /// Состояние связи всех контроллеров дома
+    ///
+    /// Неисправные контроллеры идут первыми, внутри групп сохраняется
+    /// порядок комнат и контроллеров.
+    pub fn health_report(&self) -> HealthReport {
+        let mut devices: Vec<DeviceHealth> = self
+            .rooms
+            .iter()
+            .flat_map(|(room_key, room)| {
+                room.controllers_keys().into_iter().filter_map(move |key| {
+                    let controller = room.controller(&key)?;
+                    Some(DeviceHealth {
+                        room: room_key.clone(),
+                        kind: controller.kind(),
+                        status: controller.health(),
+                        key,
+                    })
+                })
+            })
+            .collect();
+        devices.sort_by_key(|device| device.status.is_healthy());
+
+        HealthReport {
+            timestamp_ms: now_ms(),
+            devices,
+        }
+    }
+
+    /// Поток отчетов: первый сразу, затем раз в `period`, если состояние изменилось
+    ///
+    /// Контроллеры термометров обновляются сами, состояние розеток - после
+    /// их опроса (например, `BudgetManager::refresh`).
+    pub fn watch_reports(&self, period: Duration) -> impl Stream<Item = HouseReport> + '_ {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut last: Option<HouseReport> = None;
+        IntervalStream::new(ticker).filter_map(move |_| {
+            let report = self.snapshot();
+            if last.as_ref().is_some_and(|last| last.same_state(&report)) {
+                return None;
+            }
+            last = Some(report.clone());
+            Some(report)
+        })
+    }
+
+    /// Опрашивает все термометры дома одновременно
+    ///
+    /// Остановленные контроллеры термометров запускаются. Контроллер без
+    /// свежего показания ждет следующего не дольше `timeout` (все
+    /// контроллеры ждут параллельно), локальные термометры возвращают
+    /// текущее значение.
+    #[cfg(feature = "net")]
+    pub async fn poll_all_temperatures(
+        &mut self,
+        timeout: Duration,
+    ) -> HashMap<(String, String), Result<Celsius, ThermError>> {
+        let mut readings = HashMap::new();
+        let mut pending = JoinSet::new();
+
+        for (room_key, room) in self.rooms.iter_mut() {
+            for key in room.devices_keys() {
+                if let Some(Device::Therm(therm)) = room.device(&key) {
+                    readings.insert((room_key.clone(), key), Ok(therm.temperature()));
+                }
+            }
+
+            for key in room.controllers_keys() {
+                let Some(DeviceController::Therm(therm)) = room.controller_mut(&key) else {
+                    continue;
+                };
+                if let Err(e) = therm.start() {
+                    readings.insert((room_key.clone(), key), Err(e));
+                    continue;
+                }
+                if let Ok(reading) = therm.temperature() {
+                    readings.insert((room_key.clone(), key), Ok(reading.value));
+                    continue;
+                }
+
+                let reading = therm.next_reading();
+                let id = (room_key.clone(), key);
+                pending.spawn(async move {
+                    let result = tokio::time::timeout(timeout, reading)
+                        .await
+                        .unwrap_or(Err(ThermError::NoFreshData));
+                    (id, result)
+                });
+            }
+        }
+
+        while let Some(joined) = pending.join_next().await {
+            if let Ok((id, result)) = joined {
+                readings.insert(id, result);
+            }
+        }
+        readings
+    }
+
+    /// Публикует показания контроллеров термометров в шину событий
+    ///
+    /// Каждое новое показание становится `HouseEvent::TemperatureUpdated`
+    /// (с учетом `DedupPolicy` шины). Публикация идет, пока живы
+    /// возвращенные подписки. Контроллеры, добавленные позже, не
+    /// подписываются.
+    #[cfg(feature = "net")]
+    pub fn forward_temperatures(&self) -> Vec<SubscriptionHandle> {
+        let mut handles = Vec::new();
+        for (room_key, room) in &self.rooms {
+            for key in room.controllers_keys() {
+                let Some(DeviceController::Therm(therm)) = room.controller(&key) else {
+                    continue;
+                };
+                let events = self.events.clone();
+                let room = room_key.clone();
+                handles.push(therm.on_temperature_change(move |reading| {
+                    if let Ok(value) = reading {
+                        events.publish(HouseEvent::TemperatureUpdated {
+                            room: room.clone(),
+                            key: key.clone(),
+                            value,
+                        });
+                    }
+                }));
+            }
+        }
+        handles
+    }
+
+    /// Запускает фоновую работу всех контроллеров дома (`Lifecycle::start`)
+    ///
+    /// Прием UDP, опрос и соединения запускаются по очереди; неудача одного
+    /// контроллера не мешает остальным. Ошибка `Controllers` перечисляет
+    /// все контроллеры, которые запустить не удалось.
+    pub async fn start_controllers(&mut self) -> SmartHouseResult<()> {
+        let mut failures = Vec::new();
+        for (room_key, room) in self.rooms.iter_mut() {
+            for key in room.controllers_keys() {
+                let Some(controller) = room.controller_mut(&key) else {
+                    continue;
+                };
+                if let Err(e) = Lifecycle::start(controller).await {
+                    failures.push(GroupFailure {
+                        room: room_key.clone(),
+                        key,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+        controllers_result(failures)
+    }
+
+    /// Останавливает фоновую работу всех контроллеров дома (`Lifecycle::stop`)
+    ///
+    /// Останавливаются все контроллеры; ошибка `Controllers` перечисляет
+    /// те, чья работа завершилась аварийно.
+    pub async fn stop_controllers(&mut self) -> SmartHouseResult<()> {
+        let mut failures = Vec::new();
+        for (room_key, room) in self.rooms.iter_mut() {
+            for key in room.controllers_keys() {
+                let Some(controller) = room.controller_mut(&key) else {
+                    continue;
+                };
+                if let Err(e) = Lifecycle::stop(controller).await {
+                    failures.push(GroupFailure {
+                        room: room_key.clone(),
+                        key,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+        controllers_result(failures)
+    }
+
+    /// Как `start_controllers`, но возвращает список неудач
+    pub async fn start_all(&mut self) -> Vec<GroupFailure> {
+        match self.start_controllers().await {
+            Err(SmartHouseError::Controllers(failures)) => failures,
+            _ => Vec::new(),
+        }
+    }
+
+    /// Как `stop_controllers`, но без ошибок остановки
+    pub async fn stop_all(&mut self) {
+        let _ = self.stop_controllers().await;
+    }
+
     /// Возвращает количество комнат в доме
     pub fn rooms_count(&self) -> usize {
         self.rooms.len()
@@ -144,10 +833,31 @@ impl SmartHouse {
     }
 }
 
+/// Строки отчета дома об одной комнате: заголовок и отчеты ее элементов
+pub(crate) fn room_report_lines(key: &str, room: &Room, options: &ReportOptions) -> Vec<String> {
+    let mut report = vec![format!("Room: {}", key)];
+    report.extend(
+        room.report_lines_with(options)
+            .iter()
+            .map(|s| format!("  {}", s)),
+    );
+    report
+}
+
 impl Reporter for SmartHouse {
     fn report(&self) -> String {
         self.report_lines().join("\n")
     }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        self.report_lines_with(options).join("\n")
+    }
+}
+
+impl AsyncReporter for SmartHouse {
+    async fn live_report(&mut self, timeout: Duration) -> String {
+        self.live_report_lines(timeout).await.join("\n")
+    }
 }
 
 impl fmt::Display for SmartHouse {
@@ -159,6 +869,7 @@ impl fmt::Display for SmartHouse {
 #[cfg(test)]
 mod tests {
     use crate::devices::{Device, SmartSocket, SmartTherm};
+    use crate::metadata::DeviceMetadata;
     use crate::room;
 
     use super::*;
@@ -249,8 +960,8 @@ mod tests {
 
         let report_lines = house.report_lines();
 
-        // Проверяем количество строк в отчете (2 комнаты x (1 заголовок + 1 устройство))
-        assert_eq!(report_lines.len(), 4);
+        // Проверяем количество строк в отчете (2 комнаты x (заголовок + устройство + сводка))
+        assert_eq!(report_lines.len(), 6);
 
         // Проверяем содержимое отчета
         let contains_kitchen = report_lines.iter().any(|s| s.contains("Room: kitchen"));
@@ -264,6 +975,118 @@ mod tests {
         assert!(contains_socket);
     }
 
+    #[test]
+    fn insertion_order() {
+        let mut house = SmartHouse::new(vec![
+            ("porch".to_string(), Room::new()),
+            ("attic".to_string(), Room::new()),
+        ]);
+        house.add_room("kitchen", Room::new());
+        house.add_room("bathroom", Room::new());
+        house.remove_room("attic");
+
+        assert_eq!(house.rooms_keys(), vec!["porch", "kitchen", "bathroom"]);
+        let headers: Vec<_> = house
+            .report_lines()
+            .into_iter()
+            .filter(|line| line.starts_with("Room:"))
+            .collect();
+        assert_eq!(
+            headers,
+            vec!["Room: porch", "Room: kitchen", "Room: bathroom"]
+        );
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut house = test_house();
+        if let Ok(Device::Socket(s)) = house.device_mut("living_room", "socket") {
+            s.turn_on();
+        }
+
+        let snapshot = house.snapshot();
+        assert_eq!(snapshot.to_string(), house.report());
+
+        let living_room = snapshot.room("living_room").unwrap();
+        assert_eq!(living_room.items[0].kind, "socket");
+        assert!(!living_room.items[0].controller);
+        assert_eq!(living_room.summary.total_power.value(), 1500.0);
+        assert!(snapshot.same_state(&house.snapshot()));
+    }
+
+    #[test]
+    fn report_with_options() {
+        use crate::report::{Locale, TemperatureUnit};
+
+        let mut house = test_house();
+        if let Ok(Device::Socket(s)) = house.device_mut("living_room", "socket") {
+            s.turn_on();
+        }
+        let options = ReportOptions::default()
+            .with_temperature_unit(TemperatureUnit::Fahrenheit)
+            .with_power_precision(0)
+            .with_locale(Locale::Ru);
+
+        let report = house.report_with(&options);
+        assert!(report.contains("Smart Thermometer: 72,5°F"));
+        assert!(report.contains("Power: 1500W (Rated: 1500W)"));
+        assert!(report.contains("Temperature: min 72,5°F / avg 72,5°F"));
+
+        let snapshot = house.snapshot_with(&options);
+        assert_eq!(snapshot.to_string(), report);
+        assert_eq!(
+            snapshot.room("kitchen").unwrap().summary.avg_temperature,
+            Some(crate::units::Celsius::new(22.5))
+        );
+        assert_eq!(house.report_with(&ReportOptions::default()), house.report());
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    #[ignore = "integration test with UDP networking"]
+    async fn poll_all_temperatures() {
+        use crate::controllers::ThermController;
+
+        let mut house = test_house();
+        let waiting = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(5));
+        let feed = waiting.feed();
+        let silent = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(5));
+        let room = house.room_mut("living_room").unwrap();
+        room.add_controller("therm", waiting.into());
+        room.add_controller("silent", silent.into());
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            feed.push(19.5);
+        });
+        let readings = house
+            .poll_all_temperatures(Duration::from_millis(300))
+            .await;
+
+        let reading =
+            |room: &str, key: &str| readings[&(room.to_string(), key.to_string())].clone();
+        assert_eq!(readings.len(), 3);
+        assert_eq!(reading("kitchen", "therm").unwrap(), Celsius::new(22.5));
+        assert_eq!(reading("living_room", "therm").unwrap(), Celsius::new(19.5));
+        assert!(matches!(
+            reading("living_room", "silent"),
+            Err(ThermError::NoFreshData)
+        ));
+    }
+
+    #[tokio::test]
+    async fn watch_reports_skips_unchanged() {
+        let house = test_house();
+        let mut reports = Box::pin(house.watch_reports(Duration::from_millis(5)));
+
+        let first = reports.next().await.unwrap();
+        assert_eq!(first.rooms.len(), 2);
+
+        // Состояние не меняется - новых отчетов нет
+        let next = tokio::time::timeout(Duration::from_millis(50), reports.next()).await;
+        assert!(next.is_err());
+    }
+
     #[test]
     fn report() {
         let mut house = test_house();
@@ -281,7 +1104,7 @@ mod tests {
         assert!(report.contains("1500.0W"));
 
         // Проверяем, что в отчете правильное количество строк
-        assert_eq!(report.matches("\n").count(), 3); // 4 строки = 3 переноса
+        assert_eq!(report.matches("\n").count(), 5); // 6 строк = 5 переносов
     }
 
     #[test]
@@ -296,6 +1119,212 @@ mod tests {
         assert!(display_output.contains("socket"));
     }
 
+    #[cfg(feature = "net")]
+    #[test]
+    fn adopt_discovered() {
+        let mut house = test_house();
+        let mut events = house.events().subscribe();
+
+        let socket = DiscoveredDevice::socket("127.0.0.1:3030".parse().unwrap(), 2000.0);
+        house.adopt(socket, "kitchen", "kettle").unwrap();
+        assert!(matches!(
+            house.controller("kitchen", "kettle"),
+            Ok(DeviceController::Socket(_))
+        ));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            HouseEvent::RoomChanged {
+                room: "kitchen".to_string()
+            }
+        );
+
+        // Комната создается автоматически
+        let therm = DiscoveredDevice::therm("127.0.0.1:0".parse().unwrap(), 19.0);
+        house.adopt(therm, "garage", "therm").unwrap();
+        assert!(matches!(
+            house.controller("garage", "therm"),
+            Ok(DeviceController::Therm(_))
+        ));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            HouseEvent::TopologyChanged {
+                changes: vec![TopologyChange::RoomAdded {
+                    room: "garage".to_string()
+                }],
+            }
+        );
+
+        // Ключ уже занят устройством
+        let socket = DiscoveredDevice::socket("127.0.0.1:3031".parse().unwrap(), 100.0);
+        let error = house.adopt(socket, "kitchen", "therm").unwrap_err();
+        assert!(matches!(error, SmartHouseError::KeyExists(_, _)));
+    }
+
+    #[tokio::test]
+    async fn groups() {
+        let mut house = test_house();
+        house.add_group(
+            "all",
+            DeviceGroup::new()
+                .with_member("living_room", "socket")
+                .with_member("kitchen", "therm"),
+        );
+        assert_eq!(house.groups_keys(), vec!["all".to_string()]);
+
+        let failures = house.switch_group("all", true).await.unwrap();
+        assert!(failures.is_empty());
+        assert!(matches!(
+            house.device("living_room", "socket"),
+            Ok(Device::Socket(s)) if s.is_active()
+        ));
+
+        let error = house.switch_group("missing", true).await.unwrap_err();
+        assert!(matches!(error, SmartHouseError::GroupNotFound(_)));
+
+        assert!(house.remove_group("all").is_some());
+        assert!(house.group("all").is_none());
+    }
+
+    #[test]
+    fn rename_keeps_group_references() {
+        let mut house = test_house();
+        house.add_group(
+            "all",
+            DeviceGroup::new()
+                .with_member("living_room", "socket")
+                .with_member("kitchen", "therm"),
+        );
+
+        let mut events = house.events().subscribe();
+
+        house.rename_room("kitchen", "galley").unwrap();
+        assert_eq!(house.rooms_keys(), vec!["galley", "living_room"]);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            HouseEvent::TopologyChanged {
+                changes: vec![TopologyChange::RoomRenamed {
+                    from: "kitchen".to_string(),
+                    to: "galley".to_string(),
+                }],
+            }
+        );
+        house.rename_item("galley", "therm", "thermometer").unwrap();
+        assert!(house.device("galley", "thermometer").is_ok());
+        let group = house.group("all").unwrap();
+        assert!(group.contains("galley", "thermometer"));
+        assert!(!group.contains("kitchen", "therm"));
+
+        let error = house.rename_room("galley", "living_room").unwrap_err();
+        assert!(matches!(error, SmartHouseError::RoomExists(_)));
+        let error = house.rename_item("galley", "missing", "x").unwrap_err();
+        assert!(matches!(error, SmartHouseError::DeviceNotFound(_, _)));
+        assert!(house.room("galley").is_some());
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn forward_temperatures_with_dedup() {
+        use crate::controllers::ThermController;
+        use crate::events::DedupPolicy;
+
+        let therm = ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(5));
+        let feed = therm.feed();
+        let mut house = SmartHouse::new([("hall".to_string(), Room::default())])
+            .with_events(EventBus::default().with_dedup(DedupPolicy::default()));
+        house
+            .room_mut("hall")
+            .unwrap()
+            .add_controller("temp", DeviceController::Therm(therm));
+        let mut events = house.events().subscribe();
+
+        let handles = house.forward_temperatures();
+        assert_eq!(handles.len(), 1);
+        for value in [21.0, 21.03, 21.5] {
+            feed.push(value);
+        }
+        drop(handles);
+        feed.push(30.0);
+
+        let values: Vec<Celsius> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter_map(|event| match event {
+                HouseEvent::TemperatureUpdated { room, key, value } => {
+                    assert_eq!((room.as_str(), key.as_str()), ("hall", "temp"));
+                    Some(value)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(values, vec![Celsius::new(21.0), Celsius::new(21.5)]);
+        assert_eq!(house.events().suppressed_count(), 1);
+    }
+
+    #[test]
+    fn move_device_between_rooms() {
+        let mut house = test_house();
+        house.add_group(
+            "climate",
+            DeviceGroup::new().with_member("kitchen", "therm"),
+        );
+        house
+            .room_mut("kitchen")
+            .unwrap()
+            .set_metadata("therm", DeviceMetadata::new().with_priority(5));
+        let mut events = house.events().subscribe();
+
+        house
+            .move_device("kitchen", "therm", "living_room")
+            .unwrap();
+        assert!(house.device("kitchen", "therm").is_err());
+        assert!(house.device("living_room", "therm").is_ok());
+        assert_eq!(
+            house
+                .room("living_room")
+                .unwrap()
+                .metadata("therm")
+                .priority,
+            5
+        );
+        assert!(
+            house
+                .group("climate")
+                .unwrap()
+                .contains("living_room", "therm")
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            HouseEvent::DeviceMoved {
+                key: "therm".to_string(),
+                from_room: "kitchen".to_string(),
+                to_room: "living_room".to_string(),
+            }
+        );
+
+        house
+            .room_mut("kitchen")
+            .unwrap()
+            .add_device("socket", Device::Socket(SmartSocket::new(100.0)));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            HouseEvent::RoomChanged {
+                room: "kitchen".to_string(),
+            }
+        );
+        let error = house
+            .move_device("kitchen", "socket", "living_room")
+            .unwrap_err();
+        assert!(matches!(error, SmartHouseError::KeyExists(_, _)));
+        assert!(house.device("kitchen", "socket").is_ok());
+        let error = house
+            .move_controller("living_room", "therm", "kitchen")
+            .unwrap_err();
+        assert!(matches!(error, SmartHouseError::DeviceNotFound(_, _)));
+        let error = house
+            .move_device("kitchen", "socket", "garage")
+            .unwrap_err();
+        assert!(matches!(error, SmartHouseError::RoomNotFound(_)));
+        assert!(events.try_recv().is_err());
+    }
+
     #[test]
     fn rooms_count() {
         let house = test_house();
@@ -319,4 +1348,182 @@ mod tests {
         assert!(house.room("room1").is_some());
         assert!(house.room("room2").is_some());
     }
+
+    #[test]
+    fn inline_macro_syntax() {
+        let hall = room![("lamp", Device::Socket(SmartSocket::new(60.0)))];
+        let house = crate::house! {
+            "kitchen" => { "kettle" => socket(2000.0) },
+            "hall" => hall,
+            floor "second" {
+                "bedroom" => { "night_light" => socket(60.0), "therm" => therm(21.0) },
+                "study" => {},
+            }
+            "attic" => Room::new(),
+        };
+
+        assert_eq!(
+            house.rooms_keys(),
+            vec!["kitchen", "hall", "bedroom", "study", "attic"]
+        );
+        let floor = house.group("second").unwrap();
+        assert_eq!(floor.len(), 2);
+        assert!(floor.contains("bedroom", "therm"));
+        assert!(house.device("kitchen", "kettle").is_ok());
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn health_report_lists_unhealthy_first() {
+        use crate::controllers::ThermController;
+        use crate::emulators::{Fleet, FleetSpec};
+
+        let spec = FleetSpec::new()
+            .socket("hall", "lamp", 100.0)
+            .with_controller_timeout(Duration::from_secs(1));
+        let mut fleet = Fleet::start(spec).await.unwrap();
+        let house = fleet.house_mut();
+        let mut kitchen = Room::new();
+        // Прием показаний не запущен - связи с датчиком нет
+        kitchen.add_controller(
+            "therm",
+            ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(5)).into(),
+        );
+        house.add_room("kitchen", kitchen);
+
+        if let Ok(DeviceController::Socket(lamp)) = house.controller_mut("hall", "lamp") {
+            lamp.power().await.unwrap();
+        }
+
+        let report = house.health_report();
+        let order: Vec<_> = report.devices.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(order, vec!["therm", "lamp"]);
+        assert!(!report.is_healthy());
+        assert_eq!(report.unhealthy().count(), 1);
+        assert!(
+            report
+                .to_string()
+                .starts_with("[kitchen/therm] therm: Unavailable")
+        );
+
+        fleet.shutdown().await;
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn live_report_queries_controllers() {
+        use crate::controllers::SocketController;
+        use crate::emulators::{Fleet, FleetSpec};
+        use crate::traits::AsyncReporter;
+
+        let spec = FleetSpec::new()
+            .socket("hall", "lamp", 100.0)
+            .with_controller_timeout(Duration::from_secs(1));
+        let mut fleet = Fleet::start(spec).await.unwrap();
+        let addr = fleet.socket_addr("hall", "lamp").unwrap();
+        let house = fleet.house_mut();
+
+        // Розетку включили в обход дома - кэш контроллера об этом не знает
+        let mut other = SocketController::new(addr, 100.0, Duration::from_secs(1));
+        other.turn_on().await.unwrap();
+        assert!(house.report().contains("INACTIVE"));
+
+        let report = house.live_report(Duration::from_secs(1)).await;
+        assert!(report.contains("Smart Socket: ACTIVE"));
+        assert!(!report.contains("[stale"));
+        assert_eq!(house.report(), report);
+
+        fleet.shutdown().await;
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn start_and_stop_all() {
+        use crate::controllers::SocketController;
+        use crate::emulators::socket_emulator::{EmulatorConfig, SocketEmulator};
+        use crate::protocol::transport::InMemory;
+        use std::sync::Arc;
+
+        let transport = InMemory::new();
+        let mut emulator =
+            SocketEmulator::new(EmulatorConfig::new(100.0).with_address("10.0.0.7:5000"));
+        emulator.start_in_memory(&transport).await.unwrap();
+        let addr = emulator.local_addr().unwrap();
+
+        let mut hall = Room::new();
+        hall.add_controller(
+            "lamp",
+            SocketController::new(addr, 100.0, Duration::from_secs(1))
+                .with_transport(Arc::new(transport.clone()))
+                .into(),
+        );
+        // Слушателя по этому адресу нет - запуск завершится ошибкой
+        hall.add_controller(
+            "missing",
+            SocketController::new(
+                "10.0.0.9:5000".parse::<std::net::SocketAddr>().unwrap(),
+                100.0,
+                Duration::from_secs(1),
+            )
+            .with_transport(Arc::new(transport))
+            .into(),
+        );
+        let mut house = SmartHouse::new([("hall".to_string(), hall)]);
+
+        let failures = house.start_all().await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].key, "missing");
+        let lamp = house.controller("hall", "lamp").unwrap();
+        assert!(Lifecycle::health(lamp).connected);
+
+        house.stop_all().await;
+        let lamp = house.controller("hall", "lamp").unwrap();
+        assert!(!Lifecycle::health(lamp).connected);
+
+        emulator.stop().await;
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn start_and_stop_controllers() {
+        use crate::controllers::ThermController;
+
+        // Порт занят - приемник термометра не запустится
+        let busy = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut kitchen = Room::new();
+        kitchen.add_controller(
+            "therm",
+            ThermController::new(20.0, "127.0.0.1:0", Duration::from_secs(1)).into(),
+        );
+        kitchen.add_controller(
+            "busy",
+            ThermController::new(
+                20.0,
+                &busy.local_addr().unwrap().to_string(),
+                Duration::from_secs(1),
+            )
+            .into(),
+        );
+        let mut house = SmartHouse::new([("kitchen".to_string(), kitchen)]);
+
+        let Err(SmartHouseError::Controllers(failures)) = house.start_controllers().await else {
+            panic!("Expected start failure");
+        };
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            (failures[0].room.as_str(), failures[0].key.as_str()),
+            ("kitchen", "busy")
+        );
+        // Запущенный приемник занимает порт, остановленный освобождает
+        let Ok(DeviceController::Therm(therm)) = house.controller("kitchen", "therm") else {
+            panic!("Expected therm controller");
+        };
+        let addr = therm.listen_addr().to_string();
+        assert!(std::net::UdpSocket::bind(&addr).is_err());
+
+        assert!(house.stop_controllers().await.is_ok());
+        assert!(std::net::UdpSocket::bind(&addr).is_ok());
+    }
 }
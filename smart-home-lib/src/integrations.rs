@@ -0,0 +1,7 @@
+//! Интеграции со сторонними протоколами и системами
+
+pub mod modbus;
+pub mod weather;
+
+pub use modbus::{ModbusClient, ModbusError, ModbusSocket, ModbusTherm};
+pub use weather::{WeatherClient, WeatherError, WeatherProvider, WeatherSensor};
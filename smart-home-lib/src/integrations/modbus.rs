@@ -0,0 +1,669 @@
+//! Мост Modbus TCP для промышленных реле и датчиков
+//!
+//! `ModbusClient` выполняет запросы к шлюзу или устройству, а
+//! `ModbusSocket` и `ModbusTherm` отображают катушки и регистры на API,
+//! знакомый по `SocketController` и `ThermController`.
+
+use crate::devices::{SmartSocket, SmartTherm};
+use crate::report::ReportOptions;
+use crate::traits::Reporter;
+use crate::units::{Celsius, Watts};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// Чтение катушек (coils)
+const READ_COILS: u8 = 0x01;
+/// Чтение holding регистров
+const READ_HOLDING_REGISTERS: u8 = 0x03;
+/// Чтение input регистров
+const READ_INPUT_REGISTERS: u8 = 0x04;
+/// Запись одной катушки
+const WRITE_SINGLE_COIL: u8 = 0x05;
+/// Запись одного регистра
+const WRITE_SINGLE_REGISTER: u8 = 0x06;
+
+/// Максимальная длина PDU по спецификации
+const MAX_PDU_LEN: usize = 253;
+
+/// Ошибки Modbus
+#[derive(Debug, Clone)]
+pub enum ModbusError {
+    /// Ошибка подключения или обмена
+    ConnectionError(String),
+    /// Таймаут операции
+    Timeout,
+    /// Устройство вернуло исключение Modbus (код исключения)
+    Exception(u8),
+    /// Ответ не соответствует запросу
+    InvalidResponse(String),
+    /// Ошибка блокировки
+    LockError,
+}
+
+impl fmt::Display for ModbusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConnectionError(msg) => write!(f, "Ошибка подключения: {}", msg),
+            Self::Timeout => write!(f, "Таймаут операции"),
+            Self::Exception(code) => write!(f, "Исключение Modbus: 0x{:02X}", code),
+            Self::InvalidResponse(msg) => write!(f, "Некорректный ответ: {}", msg),
+            Self::LockError => write!(f, "Ошибка блокировки"),
+        }
+    }
+}
+
+impl std::error::Error for ModbusError {}
+
+/// Тип регистра для чтения значения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterKind {
+    /// Holding регистр (функция 0x03)
+    Holding,
+    /// Input регистр (функция 0x04)
+    Input,
+}
+
+/// Клиент Modbus TCP с постоянным соединением
+pub struct ModbusClient {
+    address: SocketAddr,
+    timeout: Duration,
+    connection: Option<TcpStream>,
+    next_transaction: u16,
+}
+
+/// Клиент, разделяемый устройствами за одним шлюзом
+pub type SharedModbusClient = Arc<tokio::sync::Mutex<ModbusClient>>;
+
+impl ModbusClient {
+    /// Создает клиента (подключение выполняется при первом запросе)
+    pub fn new(address: SocketAddr, timeout: Duration) -> Self {
+        Self {
+            address,
+            timeout,
+            connection: None,
+            next_transaction: 1,
+        }
+    }
+
+    /// Создает клиента для совместного использования несколькими устройствами
+    pub fn shared(address: SocketAddr, timeout: Duration) -> SharedModbusClient {
+        Arc::new(tokio::sync::Mutex::new(Self::new(address, timeout)))
+    }
+
+    /// Возвращает адрес устройства
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Читает катушки
+    pub async fn read_coils(
+        &mut self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<bool>, ModbusError> {
+        let data = self.read_request(unit, READ_COILS, address, count).await?;
+
+        Ok((0..count as usize)
+            .map(|i| {
+                data.get(i / 8)
+                    .is_some_and(|byte| byte & (1 << (i % 8)) != 0)
+            })
+            .collect())
+    }
+
+    /// Читает holding или input регистры
+    pub async fn read_registers(
+        &mut self,
+        unit: u8,
+        kind: RegisterKind,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, ModbusError> {
+        let function = match kind {
+            RegisterKind::Holding => READ_HOLDING_REGISTERS,
+            RegisterKind::Input => READ_INPUT_REGISTERS,
+        };
+        let data = self.read_request(unit, function, address, count).await?;
+
+        if data.len() != count as usize * 2 {
+            return Err(ModbusError::InvalidResponse(format!(
+                "expected {} registers, got {} bytes",
+                count,
+                data.len()
+            )));
+        }
+
+        Ok(data
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect())
+    }
+
+    /// Записывает катушку
+    pub async fn write_coil(
+        &mut self,
+        unit: u8,
+        address: u16,
+        value: bool,
+    ) -> Result<(), ModbusError> {
+        let value: u16 = if value { 0xFF00 } else { 0x0000 };
+        self.write_request(unit, WRITE_SINGLE_COIL, address, value)
+            .await
+    }
+
+    /// Записывает holding регистр
+    pub async fn write_register(
+        &mut self,
+        unit: u8,
+        address: u16,
+        value: u16,
+    ) -> Result<(), ModbusError> {
+        self.write_request(unit, WRITE_SINGLE_REGISTER, address, value)
+            .await
+    }
+
+    /// Запрос чтения: возвращает байты данных после счетчика
+    async fn read_request(
+        &mut self,
+        unit: u8,
+        function: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u8>, ModbusError> {
+        let pdu = request_pdu(function, address, count);
+        let response = self.transact(unit, &pdu).await?;
+
+        let byte_count = *response
+            .get(1)
+            .ok_or_else(|| ModbusError::InvalidResponse("missing byte count".to_string()))?
+            as usize;
+        response
+            .get(2..2 + byte_count)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| ModbusError::InvalidResponse("truncated data".to_string()))
+    }
+
+    /// Запрос записи: ответ должен повторять запрос
+    async fn write_request(
+        &mut self,
+        unit: u8,
+        function: u8,
+        address: u16,
+        value: u16,
+    ) -> Result<(), ModbusError> {
+        let pdu = request_pdu(function, address, value);
+        let response = self.transact(unit, &pdu).await?;
+
+        if response != pdu {
+            return Err(ModbusError::InvalidResponse(
+                "write echo mismatch".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Отправляет PDU и возвращает PDU ответа (с проверкой исключений)
+    async fn transact(&mut self, unit: u8, pdu: &[u8]) -> Result<Vec<u8>, ModbusError> {
+        let transaction = self.next_transaction;
+        self.next_transaction = self.next_transaction.wrapping_add(1);
+        let frame = encode_frame(transaction, unit, pdu);
+        let op_timeout = self.timeout;
+
+        let result = timeout(op_timeout, async {
+            let stream = self.ensure_connected().await?;
+            stream.write_all(&frame).await.map_err(connection_error)?;
+            read_frame(stream).await
+        })
+        .await
+        .map_err(|_| ModbusError::Timeout)
+        .and_then(|result| result);
+
+        let (response_transaction, response_unit, response) = match result {
+            Ok(frame) => frame,
+            Err(e) => {
+                // После ошибки обмена состояние потока неизвестно - переподключаемся
+                self.connection = None;
+                return Err(e);
+            }
+        };
+
+        if response_transaction != transaction || response_unit != unit {
+            self.connection = None;
+            return Err(ModbusError::InvalidResponse(
+                "transaction or unit mismatch".to_string(),
+            ));
+        }
+
+        match response.first() {
+            Some(function) if *function == pdu[0] | 0x80 => Err(ModbusError::Exception(
+                response.get(1).copied().unwrap_or(0),
+            )),
+            Some(function) if *function == pdu[0] => Ok(response),
+            _ => Err(ModbusError::InvalidResponse(
+                "unexpected function code".to_string(),
+            )),
+        }
+    }
+
+    /// Обеспечивает наличие соединения
+    async fn ensure_connected(&mut self) -> Result<&mut TcpStream, ModbusError> {
+        if self.connection.is_none() {
+            let stream = TcpStream::connect(self.address)
+                .await
+                .map_err(connection_error)?;
+            self.connection = Some(stream);
+        }
+
+        self.connection
+            .as_mut()
+            .ok_or_else(|| ModbusError::ConnectionError("not connected".to_string()))
+    }
+}
+
+/// PDU из кода функции и двух 16-битных полей
+fn request_pdu(function: u8, first: u16, second: u16) -> Vec<u8> {
+    let mut pdu = vec![function];
+    pdu.extend_from_slice(&first.to_be_bytes());
+    pdu.extend_from_slice(&second.to_be_bytes());
+    pdu
+}
+
+/// Кадр Modbus TCP: заголовок MBAP + PDU
+fn encode_frame(transaction: u16, unit: u8, pdu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(7 + pdu.len());
+    frame.extend_from_slice(&transaction.to_be_bytes());
+    frame.extend_from_slice(&0u16.to_be_bytes()); // protocol id
+    frame.extend_from_slice(&(pdu.len() as u16 + 1).to_be_bytes());
+    frame.push(unit);
+    frame.extend_from_slice(pdu);
+    frame
+}
+
+/// Читает кадр: (transaction id, unit id, PDU)
+async fn read_frame(stream: &mut TcpStream) -> Result<(u16, u8, Vec<u8>), ModbusError> {
+    let mut header = [0u8; 7];
+    stream
+        .read_exact(&mut header)
+        .await
+        .map_err(connection_error)?;
+
+    let transaction = u16::from_be_bytes([header[0], header[1]]);
+    let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+    if length < 2 || length - 1 > MAX_PDU_LEN {
+        return Err(ModbusError::InvalidResponse(format!(
+            "invalid frame length {}",
+            length
+        )));
+    }
+
+    let mut pdu = vec![0u8; length - 1];
+    stream
+        .read_exact(&mut pdu)
+        .await
+        .map_err(connection_error)?;
+    Ok((transaction, header[6], pdu))
+}
+
+fn connection_error(e: std::io::Error) -> ModbusError {
+    ModbusError::ConnectionError(e.to_string())
+}
+
+/// Отображение промышленного реле на регистры
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketRegisterMap {
+    /// Адрес устройства на шине (unit id)
+    pub unit: u8,
+    /// Катушка реле
+    pub relay_coil: u16,
+    /// Регистр мощности (holding) и множитель в ватты
+    pub power_register: Option<(u16, f64)>,
+}
+
+impl SocketRegisterMap {
+    /// Реле без измерения мощности
+    pub fn new(unit: u8, relay_coil: u16) -> Self {
+        Self {
+            unit,
+            relay_coil,
+            power_register: None,
+        }
+    }
+
+    /// Builder: регистр мощности и множитель (`ватты = значение * scale`)
+    pub fn with_power_register(mut self, register: u16, scale: f64) -> Self {
+        self.power_register = Some((register, scale));
+        self
+    }
+}
+
+/// Реле Modbus с API розетки
+pub struct ModbusSocket {
+    client: SharedModbusClient,
+    map: SocketRegisterMap,
+    socket: Arc<RwLock<SmartSocket>>,
+}
+
+impl ModbusSocket {
+    /// Создает реле с номинальной мощностью нагрузки
+    pub fn new(client: SharedModbusClient, map: SocketRegisterMap, power_rating: f64) -> Self {
+        Self {
+            client,
+            map,
+            socket: Arc::new(RwLock::new(SmartSocket::new(power_rating))),
+        }
+    }
+
+    /// Включает реле
+    pub async fn turn_on(&mut self) -> Result<(), ModbusError> {
+        self.switch(true).await
+    }
+
+    /// Выключает реле
+    pub async fn turn_off(&mut self) -> Result<(), ModbusError> {
+        self.switch(false).await
+    }
+
+    async fn switch(&mut self, on: bool) -> Result<(), ModbusError> {
+        self.client
+            .lock()
+            .await
+            .write_coil(self.map.unit, self.map.relay_coil, on)
+            .await?;
+
+        let mut socket = self.socket.write().map_err(|_| ModbusError::LockError)?;
+        if on {
+            socket.turn_on();
+        } else {
+            socket.turn_off();
+        }
+        Ok(())
+    }
+
+    /// Читает состояние реле и мощность с устройства
+    pub async fn power(&mut self) -> Result<Watts, ModbusError> {
+        let mut client = self.client.lock().await;
+        let active = client
+            .read_coils(self.map.unit, self.map.relay_coil, 1)
+            .await?
+            .first()
+            .copied()
+            .unwrap_or(false);
+
+        let measured = match self.map.power_register {
+            Some((register, scale)) if active => {
+                let raw = client
+                    .read_registers(self.map.unit, RegisterKind::Holding, register, 1)
+                    .await?;
+                Some(Watts::new(raw[0] as f64 * scale))
+            }
+            _ => None,
+        };
+        drop(client);
+
+        let mut socket = self.socket.write().map_err(|_| ModbusError::LockError)?;
+        if active {
+            socket.turn_on();
+            if let Some(power) = measured {
+                socket.set_current_power(power);
+            }
+        } else {
+            socket.turn_off();
+        }
+        Ok(socket.current_power())
+    }
+
+    /// Получает копию внутренней розетки
+    pub fn device(&self) -> Result<SmartSocket, ModbusError> {
+        self.socket
+            .read()
+            .map(|socket| socket.clone())
+            .map_err(|_| ModbusError::LockError)
+    }
+
+    /// Отображение регистров
+    pub fn map(&self) -> &SocketRegisterMap {
+        &self.map
+    }
+}
+
+impl Reporter for ModbusSocket {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        match self.device() {
+            Ok(socket) => socket.report_with(options),
+            Err(e) => format!("ModbusSocket: {}", e),
+        }
+    }
+}
+
+impl fmt::Display for ModbusSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+/// Отображение датчика температуры (RTD) на регистр
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermRegisterMap {
+    /// Адрес устройства на шине (unit id)
+    pub unit: u8,
+    /// Регистр температуры
+    pub register: u16,
+    /// Тип регистра
+    pub kind: RegisterKind,
+    /// Множитель (`°C = значение * scale`), обычно 0.1
+    pub scale: f64,
+}
+
+impl ThermRegisterMap {
+    /// Input регистр со знаковым значением в десятых долях градуса
+    pub fn new(unit: u8, register: u16) -> Self {
+        Self {
+            unit,
+            register,
+            kind: RegisterKind::Input,
+            scale: 0.1,
+        }
+    }
+
+    /// Builder: тип регистра
+    pub fn with_kind(mut self, kind: RegisterKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Builder: множитель
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// Датчик температуры Modbus с API термометра
+pub struct ModbusTherm {
+    client: SharedModbusClient,
+    map: ThermRegisterMap,
+    therm: RwLock<SmartTherm>,
+}
+
+impl ModbusTherm {
+    /// Создает датчик
+    pub fn new(client: SharedModbusClient, map: ThermRegisterMap, initial_temp: f64) -> Self {
+        Self {
+            client,
+            map,
+            therm: RwLock::new(SmartTherm::new(initial_temp)),
+        }
+    }
+
+    /// Читает температуру с устройства
+    pub async fn temperature(&self) -> Result<Celsius, ModbusError> {
+        let raw = self
+            .client
+            .lock()
+            .await
+            .read_registers(self.map.unit, self.map.kind, self.map.register, 1)
+            .await?;
+
+        // Температура передается как знаковое 16-битное число
+        let value = raw[0] as i16 as f64 * self.map.scale;
+
+        let mut therm = self.therm.write().map_err(|_| ModbusError::LockError)?;
+        therm.set_temperature(value);
+        Ok(therm.temperature())
+    }
+
+    /// Получает копию внутреннего термометра (последнее прочитанное значение)
+    pub fn device(&self) -> SmartTherm {
+        self.therm
+            .read()
+            .map(|therm| therm.clone())
+            .unwrap_or_else(|_| SmartTherm::new(0.0))
+    }
+
+    /// Отображение регистров
+    pub fn map(&self) -> &ThermRegisterMap {
+        &self.map
+    }
+}
+
+impl Reporter for ModbusTherm {
+    fn report(&self) -> String {
+        self.device().report()
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        self.device().report_with(options)
+    }
+}
+
+impl fmt::Display for ModbusTherm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    /// Простейшее Modbus TCP устройство для тестов
+    #[derive(Default)]
+    struct FakeDevice {
+        coils: HashMap<u16, bool>,
+        registers: HashMap<u16, u16>,
+    }
+
+    impl FakeDevice {
+        fn handle(&mut self, pdu: &[u8]) -> Vec<u8> {
+            let function = pdu[0];
+            let first = u16::from_be_bytes([pdu[1], pdu[2]]);
+            let second = u16::from_be_bytes([pdu[3], pdu[4]]);
+
+            match function {
+                READ_COILS => {
+                    let mut byte = 0u8;
+                    for i in 0..second.min(8) {
+                        if *self.coils.get(&(first + i)).unwrap_or(&false) {
+                            byte |= 1 << i;
+                        }
+                    }
+                    vec![function, 1, byte]
+                }
+                READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => {
+                    let mut response = vec![function, (second * 2) as u8];
+                    for i in 0..second {
+                        let value = *self.registers.get(&(first + i)).unwrap_or(&0);
+                        response.extend_from_slice(&value.to_be_bytes());
+                    }
+                    response
+                }
+                WRITE_SINGLE_COIL => {
+                    self.coils.insert(first, second == 0xFF00);
+                    pdu.to_vec()
+                }
+                WRITE_SINGLE_REGISTER if first < 100 => {
+                    self.registers.insert(first, second);
+                    pdu.to_vec()
+                }
+                _ => vec![function | 0x80, 0x02], // illegal data address
+            }
+        }
+    }
+
+    async fn start_device(device: Arc<Mutex<FakeDevice>>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            while let Ok((transaction, unit, pdu)) = read_frame(&mut stream).await {
+                let response = device.lock().unwrap().handle(&pdu);
+                let frame = encode_frame(transaction, unit, &response);
+                if stream.write_all(&frame).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        addr
+    }
+
+    #[test]
+    fn frame_encoding() {
+        let frame = encode_frame(0x0102, 17, &request_pdu(READ_COILS, 0x0013, 0x0025));
+        assert_eq!(
+            frame,
+            vec![
+                0x01, 0x02, 0x00, 0x00, 0x00, 0x06, 0x11, 0x01, 0x00, 0x13, 0x00, 0x25
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn relay_and_power() {
+        let device = Arc::new(Mutex::new(FakeDevice::default()));
+        device.lock().unwrap().registers.insert(10, 1234);
+        let addr = start_device(Arc::clone(&device)).await;
+
+        let client = ModbusClient::shared(addr, Duration::from_secs(1));
+        let map = SocketRegisterMap::new(1, 0).with_power_register(10, 1.0);
+        let mut relay = ModbusSocket::new(client, map, 2000.0);
+
+        relay.turn_on().await.unwrap();
+        assert_eq!(device.lock().unwrap().coils.get(&0), Some(&true));
+        assert_eq!(relay.power().await.unwrap(), Watts::new(1234.0));
+
+        relay.turn_off().await.unwrap();
+        assert_eq!(relay.power().await.unwrap(), Watts::new(0.0));
+        assert!(!relay.device().unwrap().is_active());
+    }
+
+    #[tokio::test]
+    async fn rtd_temperature_and_exception() {
+        let device = Arc::new(Mutex::new(FakeDevice::default()));
+        // -12.5°C в десятых долях, дополнительный код
+        device.lock().unwrap().registers.insert(3, (-125i16) as u16);
+        let addr = start_device(Arc::clone(&device)).await;
+
+        let client = ModbusClient::shared(addr, Duration::from_secs(1));
+        let therm = ModbusTherm::new(Arc::clone(&client), ThermRegisterMap::new(2, 3), 20.0);
+        assert_eq!(therm.temperature().await.unwrap(), Celsius::new(-12.5));
+        assert!(therm.report().contains("-12.5°C"));
+
+        let result = client.lock().await.write_register(2, 500, 1).await;
+        assert!(matches!(result, Err(ModbusError::Exception(0x02))));
+    }
+}
@@ -0,0 +1,451 @@
+//! Погодный сервис как виртуальный уличный датчик
+//!
+//! `WeatherClient` запрашивает текущую температуру у Open-Meteo или
+//! OpenWeatherMap, а `WeatherSensor` периодически опрашивает сервис и
+//! отдает показания через API, знакомый по `ThermController`. Так правила
+//! вида "на улице прохладнее, чем в комнате - откройте окна" работают с
+//! уличной температурой так же, как с показаниями комнатных термометров.
+//!
+//! HTTP клиент не поддерживает TLS, поэтому запросы идут по `http://`.
+
+use crate::clock::{SharedClock, system_clock};
+use crate::controllers::{ReadingQuality, ThermError};
+use crate::devices::SmartTherm;
+use crate::notifications::NotifyError;
+use crate::notifications::http::{self, HttpUrl};
+use crate::report::ReportOptions;
+use crate::traits::Reporter;
+use crate::units::Celsius;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{MissedTickBehavior, interval};
+
+/// Адрес Open-Meteo по умолчанию
+const OPEN_METEO_API: &str = "http://api.open-meteo.com";
+/// Адрес OpenWeatherMap по умолчанию
+const OPEN_WEATHER_MAP_API: &str = "http://api.openweathermap.org";
+
+/// Период опроса сервиса по умолчанию
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Максимальный возраст показаний по умолчанию
+pub const DEFAULT_WEATHER_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Ошибки погодного сервиса
+#[derive(Debug, Clone)]
+pub enum WeatherError {
+    /// Ошибка запроса
+    RequestError(String),
+    /// Таймаут запроса
+    Timeout,
+    /// Сервис вернул код ошибки
+    Status(u16),
+    /// Ответ не содержит температуры
+    InvalidResponse(String),
+}
+
+impl fmt::Display for WeatherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RequestError(msg) => write!(f, "Ошибка запроса: {}", msg),
+            Self::Timeout => write!(f, "Таймаут запроса"),
+            Self::Status(code) => write!(f, "Сервис вернул статус {}", code),
+            Self::InvalidResponse(msg) => write!(f, "Некорректный ответ: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for WeatherError {}
+
+impl From<NotifyError> for WeatherError {
+    fn from(e: NotifyError) -> Self {
+        match e {
+            NotifyError::Timeout => Self::Timeout,
+            other => Self::RequestError(other.to_string()),
+        }
+    }
+}
+
+/// Погодный сервис
+#[derive(Debug, Clone, PartialEq)]
+pub enum WeatherProvider {
+    /// [Open-Meteo](https://open-meteo.com), ключ не нужен
+    OpenMeteo,
+    /// [OpenWeatherMap](https://openweathermap.org), нужен ключ API
+    OpenWeatherMap { api_key: String },
+}
+
+/// Клиент погодного сервиса для заданных координат
+#[derive(Debug, Clone)]
+pub struct WeatherClient {
+    provider: WeatherProvider,
+    latitude: f64,
+    longitude: f64,
+    api_base: String,
+    timeout: Duration,
+}
+
+impl WeatherClient {
+    /// Клиент Open-Meteo
+    pub fn open_meteo(latitude: f64, longitude: f64) -> Self {
+        Self::new(WeatherProvider::OpenMeteo, latitude, longitude)
+    }
+
+    /// Клиент OpenWeatherMap
+    pub fn open_weather_map(api_key: &str, latitude: f64, longitude: f64) -> Self {
+        let provider = WeatherProvider::OpenWeatherMap {
+            api_key: api_key.to_string(),
+        };
+        Self::new(provider, latitude, longitude)
+    }
+
+    /// Создает клиент сервиса `provider`
+    pub fn new(provider: WeatherProvider, latitude: f64, longitude: f64) -> Self {
+        let api_base = match provider {
+            WeatherProvider::OpenMeteo => OPEN_METEO_API,
+            WeatherProvider::OpenWeatherMap { .. } => OPEN_WEATHER_MAP_API,
+        };
+        Self {
+            provider,
+            latitude,
+            longitude,
+            api_base: api_base.to_string(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Builder: адрес API (прокси или тестовый сервер)
+    pub fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Builder: таймаут запроса
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Погодный сервис
+    pub fn provider(&self) -> &WeatherProvider {
+        &self.provider
+    }
+
+    /// Запрашивает текущую температуру воздуха
+    pub async fn current_temperature(&self) -> Result<Celsius, WeatherError> {
+        let url = HttpUrl::parse(&self.request_url())?;
+        let (status, body) = http::get(&url, self.timeout).await?;
+        if !(200..300).contains(&status) {
+            return Err(WeatherError::Status(status));
+        }
+        self.parse_temperature(&body)
+    }
+
+    /// URL запроса текущей погоды
+    fn request_url(&self) -> String {
+        match &self.provider {
+            WeatherProvider::OpenMeteo => format!(
+                "{}/v1/forecast?latitude={}&longitude={}&current=temperature_2m",
+                self.api_base, self.latitude, self.longitude
+            ),
+            WeatherProvider::OpenWeatherMap { api_key } => format!(
+                "{}/data/2.5/weather?lat={}&lon={}&units=metric&appid={}",
+                self.api_base, self.latitude, self.longitude, api_key
+            ),
+        }
+    }
+
+    /// Извлекает температуру из ответа сервиса
+    fn parse_temperature(&self, body: &[u8]) -> Result<Celsius, WeatherError> {
+        let json: serde_json::Value = serde_json::from_slice(body)
+            .map_err(|e| WeatherError::InvalidResponse(e.to_string()))?;
+
+        let pointer = match self.provider {
+            WeatherProvider::OpenMeteo => "/current/temperature_2m",
+            WeatherProvider::OpenWeatherMap { .. } => "/main/temp",
+        };
+        json.pointer(pointer)
+            .and_then(serde_json::Value::as_f64)
+            .map(Celsius::new)
+            .ok_or_else(|| WeatherError::InvalidResponse(format!("нет поля {}", pointer)))
+    }
+}
+
+/// Виртуальный уличный термометр на данных погодного сервиса
+pub struct WeatherSensor {
+    client: WeatherClient,
+    /// Последнее полученное значение
+    therm: Arc<RwLock<SmartTherm>>,
+    /// Время последнего обновления (0 = нет данных, >0 = timestamp в мс)
+    last_update: Arc<AtomicU64>,
+    poll_interval: Duration,
+    max_age: Duration,
+    warning_age: Duration,
+    clock: SharedClock,
+    /// Задача периодического опроса
+    task: Option<JoinHandle<()>>,
+}
+
+impl WeatherSensor {
+    /// Создает датчик; до первого опроса данных нет
+    pub fn new(client: WeatherClient) -> Self {
+        Self {
+            client,
+            therm: Arc::new(RwLock::new(SmartTherm::new(0.0))),
+            last_update: Arc::new(AtomicU64::new(0)),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_age: DEFAULT_WEATHER_MAX_AGE,
+            warning_age: DEFAULT_WEATHER_MAX_AGE / 2,
+            clock: system_clock(),
+            task: None,
+        }
+    }
+
+    /// Builder: период опроса сервиса
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Builder: максимальный возраст показаний (порог предупреждения - половина)
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self.warning_age = max_age / 2;
+        self
+    }
+
+    /// Builder: возраст, после которого показание считается устаревающим
+    pub fn with_warning_age(mut self, warning_age: Duration) -> Self {
+        self.warning_age = warning_age;
+        self
+    }
+
+    /// Builder: источник времени для проверки свежести данных
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Клиент погодного сервиса
+    pub fn client(&self) -> &WeatherClient {
+        &self.client
+    }
+
+    /// Запрашивает температуру у сервиса и сохраняет ее
+    pub async fn refresh(&self) -> Result<Celsius, WeatherError> {
+        let temperature = self.client.current_temperature().await?;
+        store(&self.therm, &self.last_update, &self.clock, temperature);
+        Ok(temperature)
+    }
+
+    /// Запускает периодический опрос сервиса
+    ///
+    /// Ошибки опроса не сбрасывают последнее значение: оно устаревает
+    /// по `max_age`, как показания термометра без связи.
+    pub fn start(&mut self) {
+        if self.task.is_some() {
+            return;
+        }
+
+        let client = self.client.clone();
+        let therm = self.therm.clone();
+        let last_update = self.last_update.clone();
+        let clock = self.clock.clone();
+        let poll_interval = self.poll_interval;
+
+        self.task = Some(tokio::spawn(async move {
+            let mut ticker = interval(poll_interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                match client.current_temperature().await {
+                    Ok(temperature) => store(&therm, &last_update, &clock, temperature),
+                    Err(e) => eprintln!("⚠️ Погода: ошибка запроса: {}", e),
+                }
+            }
+        }));
+    }
+
+    /// Останавливает опрос
+    pub fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+
+    /// Опрос запущен
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    /// Получает текущую уличную температуру
+    pub fn temperature(&self) -> Result<Celsius, ThermError> {
+        match self.temperature_with_quality()? {
+            (_, ReadingQuality::Stale) => Err(ThermError::NoFreshData),
+            (temperature, _) => Ok(temperature),
+        }
+    }
+
+    /// Получает последнюю температуру и оценку ее свежести
+    pub fn temperature_with_quality(&self) -> Result<(Celsius, ReadingQuality), ThermError> {
+        let last_timestamp = self.last_update.load(Ordering::Relaxed);
+        if last_timestamp == 0 {
+            return Err(ThermError::NoFreshData);
+        }
+
+        let age = Duration::from_millis(self.clock.now_ms().saturating_sub(last_timestamp));
+        let quality = ReadingQuality::classify(age, self.warning_age, self.max_age);
+
+        self.therm
+            .read()
+            .map(|therm| (therm.temperature(), quality))
+            .map_err(|_| ThermError::LockError)
+    }
+
+    /// На улице прохладнее, чем `indoor` (например, стоит открыть окна)
+    pub fn is_cooler_than(&self, indoor: Celsius) -> Result<bool, ThermError> {
+        Ok(self.temperature()? < indoor)
+    }
+
+    /// Получает копию внутреннего термометра
+    pub fn device(&self) -> SmartTherm {
+        self.therm
+            .read()
+            .map(|therm| therm.clone())
+            .unwrap_or_else(|_| SmartTherm::new(0.0))
+    }
+}
+
+/// Сохраняет полученную температуру и время обновления
+fn store(
+    therm: &RwLock<SmartTherm>,
+    last_update: &AtomicU64,
+    clock: &SharedClock,
+    temperature: Celsius,
+) {
+    if let Ok(mut therm) = therm.write() {
+        therm.set_temperature(temperature.value());
+        // 0 означает отсутствие данных, поэтому время не может быть нулевым
+        last_update.store(clock.now_ms().max(1), Ordering::Relaxed);
+    }
+}
+
+impl Drop for WeatherSensor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Reporter for WeatherSensor {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        match self.temperature() {
+            Ok(_) => self.device().report_with(options),
+            Err(e) => format!("Outdoor: {}", e),
+        }
+    }
+}
+
+impl fmt::Display for WeatherSensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn request_urls() {
+        let meteo = WeatherClient::open_meteo(55.75, 37.62);
+        assert_eq!(
+            meteo.request_url(),
+            "http://api.open-meteo.com/v1/forecast?latitude=55.75&longitude=37.62&current=temperature_2m"
+        );
+
+        let owm = WeatherClient::open_weather_map("KEY", 55.75, 37.62)
+            .with_api_base("http://localhost:8080/");
+        assert_eq!(
+            owm.request_url(),
+            "http://localhost:8080/data/2.5/weather?lat=55.75&lon=37.62&units=metric&appid=KEY"
+        );
+    }
+
+    #[test]
+    fn response_parsing() {
+        let meteo = WeatherClient::open_meteo(0.0, 0.0);
+        let body = br#"{"current": {"time": "2025-06-01T12:00", "temperature_2m": 18.4}}"#;
+        assert_eq!(meteo.parse_temperature(body).unwrap().value(), 18.4);
+
+        let owm = WeatherClient::open_weather_map("KEY", 0.0, 0.0);
+        let body = br#"{"main": {"temp": -3.5, "humidity": 80}}"#;
+        assert_eq!(owm.parse_temperature(body).unwrap().value(), -3.5);
+
+        assert!(matches!(
+            owm.parse_temperature(br#"{"cod": 401}"#),
+            Err(WeatherError::InvalidResponse(_))
+        ));
+        assert!(meteo.parse_temperature(b"not json").is_err());
+    }
+
+    #[test]
+    fn freshness_follows_max_age() {
+        let clock = Arc::new(MockClock::starting_at(1_000));
+        let sensor = WeatherSensor::new(WeatherClient::open_meteo(0.0, 0.0))
+            .with_max_age(Duration::from_secs(60))
+            .with_clock(clock.clone());
+        assert!(matches!(sensor.temperature(), Err(ThermError::NoFreshData)));
+
+        store(
+            &sensor.therm,
+            &sensor.last_update,
+            &sensor.clock,
+            Celsius::new(15.0),
+        );
+        assert_eq!(
+            sensor.temperature_with_quality().unwrap(),
+            (Celsius::new(15.0), ReadingQuality::Fresh)
+        );
+        assert!(sensor.is_cooler_than(Celsius::new(22.0)).unwrap());
+
+        clock.advance(Duration::from_secs(61));
+        assert!(sensor.temperature().is_err());
+        assert!(sensor.is_cooler_than(Celsius::new(22.0)).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async networking"]
+    async fn refresh_from_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await.unwrap();
+            let body = r#"{"current": {"temperature_2m": 12.5}}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let client =
+            WeatherClient::open_meteo(55.75, 37.62).with_api_base(&format!("http://{}", addr));
+        let sensor = WeatherSensor::new(client);
+
+        assert_eq!(sensor.refresh().await.unwrap().value(), 12.5);
+        assert_eq!(sensor.temperature().unwrap().value(), 12.5);
+    }
+}
@@ -1,28 +1,109 @@
 //! # Smart Home Library
+//!
+//! Модель дома (`SmartHouse`, `Room`, `Device`) и отчеты собираются на любой
+//! платформе, включая `wasm32-unknown-unknown`. Сетевой слой - протоколы,
+//! контроллеры, эмуляторы и службы поверх них - подключается фичей `net`
+//! (включена по умолчанию).
 
+/// Элементы, которые собираются только с сетевым слоем (фича `net`)
+macro_rules! cfg_net {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "net")]
+            $item
+        )*
+    };
+}
+
+pub mod archetype;
+pub mod builder;
+pub mod clock;
 pub mod controllers;
 pub mod devices;
-pub mod emulators;
+pub mod diff;
+pub mod events;
+pub mod group;
+pub mod history;
 pub mod house;
-pub mod protocol;
+pub mod metadata;
+pub mod path;
+pub mod report;
+pub mod report_cache;
 pub mod room;
+pub mod room_kind;
+pub mod rules;
+pub mod statistics;
+pub mod tariff;
 pub mod traits;
+pub mod transaction;
 pub mod units;
+pub mod view;
+
+cfg_net! {
+    pub mod audit;
+    #[cfg(all(feature = "leak", feature = "valve"))]
+    pub mod automation;
+    pub mod climate;
+    pub mod config;
+    pub mod discovery;
+    pub mod emulators;
+    pub mod energy;
+    pub mod integrations;
+    pub mod notifications;
+    pub mod ota;
+    pub mod protocol;
+    pub mod reconciler;
+    pub mod secrets;
+    pub mod summary;
+    pub mod template;
+    pub mod testkit;
+    pub mod vacation;
+}
 
 pub mod prelude {
     pub use super::{
+        archetype::RoomArchetype,
+        builder::{RoomBuilder, SmartHouseBuilder},
+        controllers::DeviceController,
+        devices::{Device, SmartMeter, SmartSocket, SmartTherm},
+        diff::{ConflictPolicy, HouseDiff},
+        events::{DedupPolicy, EventBus, HouseEvent},
+        group::{DeviceGroup, GroupStatus},
+        history::{ExportFormat, History, ReadingKind, RetentionPolicy},
+        house, // макрос
+        house::{SmartHouse, SmartHouseError},
+        metadata::DeviceMetadata,
+        path::{DevicePath, DevicePattern},
+        report::{HealthReport, HouseReport, ReportOptions, TemperatureUnit},
+        report_cache::ReportCache,
+        room, // макрос
+        room::{Room, RoomSummary},
+        room_kind::{ClimateAlert, RoomKind},
+        rules::{Condition, HouseSnapshot, Rule, RuleSet},
+        tariff::{EnergyCost, Tariff},
+        traits::{AsyncReporter, Reporter},
+        transaction::Transaction,
+        units::{Celsius, Lux, Pascal, Percent, Watts},
+        view::HouseView,
+    };
+
+    #[cfg(feature = "net")]
+    pub use super::{
+        audit::{AuditLog, CommandOrigin},
+        climate::RoomClimateController,
+        config::HouseConfig,
         controllers::{
-            DeviceController, SocketController, SocketError, SubscriptionHandle, ThermController,
-            ThermError,
+            DeviceAddress, DeviceCommand, DeviceOutput, SocketController, SocketError,
+            SubscriptionHandle, ThermController, ThermError,
         },
-        devices::{Device, SmartSocket, SmartTherm},
+        discovery::DiscoveredDevice,
         emulators::{EmulationScenario, SocketEmulator, ThermEmulator},
-        house, // макрос
-        house::{SmartHouse, SmartHouseError},
+        energy::BudgetManager,
+        notifications::{MessageTemplate, Notification, Notifier},
         protocol::{SocketCommand, SocketData, SocketResponse, ThermData, send_command},
-        room, // макрос
-        room::Room,
-        traits::Reporter,
-        units::{Celsius, Watts},
+        reconciler::{DesiredState, Reconciler},
+        summary::{HouseSummary, SummaryFormat, SummaryJob, SummaryPeriod},
+        template::{AddressPattern, RoomTemplate},
+        vacation::VacationMode,
     };
 }
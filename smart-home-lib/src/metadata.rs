@@ -0,0 +1,71 @@
+//! Метаданные устройств и контроллеров комнаты
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Приоритет по умолчанию
+pub const DEFAULT_PRIORITY: u8 = 50;
+
+/// Метаданные элемента комнаты
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceMetadata {
+    /// Приоритет: при нехватке мощности первыми отключаются устройства с меньшим
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+    /// Произвольные метки
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+}
+
+fn default_priority() -> u8 {
+    DEFAULT_PRIORITY
+}
+
+impl Default for DeviceMetadata {
+    fn default() -> Self {
+        Self {
+            priority: DEFAULT_PRIORITY,
+            tags: BTreeSet::new(),
+        }
+    }
+}
+
+impl DeviceMetadata {
+    /// Создает метаданные с приоритетом по умолчанию
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: приоритет
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Builder: метка
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.insert(tag.to_string());
+        self
+    }
+
+    /// Проверяет наличие метки
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_and_defaults() {
+        let meta = DeviceMetadata::new().with_priority(90).with_tag("heater");
+        assert_eq!(meta.priority, 90);
+        assert!(meta.has_tag("heater"));
+        assert!(!meta.has_tag("light"));
+
+        let parsed: DeviceMetadata = serde_json::from_str("{}").unwrap();
+        assert_eq!(parsed, DeviceMetadata::default());
+    }
+}
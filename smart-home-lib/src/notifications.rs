@@ -0,0 +1,98 @@
+//! Уведомления пользователей (webhook, email, Telegram)
+
+pub mod http;
+pub mod smtp;
+pub mod telegram;
+pub mod template;
+pub mod webhook;
+
+pub use smtp::SmtpNotifier;
+pub use telegram::TelegramNotifier;
+pub use template::MessageTemplate;
+pub use webhook::WebhookNotifier;
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use thiserror::Error;
+
+/// Ошибки отправки уведомлений
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    #[error("Invalid URL: '{0}'")]
+    InvalidUrl(String),
+
+    #[error("Unsupported URL scheme: '{0}' (only http:// is supported)")]
+    UnsupportedScheme(String),
+
+    #[error("Invalid email address: {0:?}")]
+    InvalidAddress(String),
+
+    #[error("Network error: {0}")]
+    Network(#[from] std::io::Error),
+
+    #[error("Timeout while sending notification")]
+    Timeout,
+
+    #[error("Remote side rejected notification: {0}")]
+    Rejected(String),
+}
+
+/// Результат отправки уведомления
+pub type NotifyResult = Result<(), NotifyError>;
+
+/// Future, возвращаемый `Notifier::notify`
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = NotifyResult> + Send + 'a>>;
+
+/// Уведомление для человека
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    /// Краткий заголовок
+    pub subject: String,
+    /// Текст уведомления
+    pub body: String,
+}
+
+impl Notification {
+    /// Создает уведомление с заголовком и текстом
+    pub fn new(subject: &str, body: &str) -> Self {
+        Self {
+            subject: subject.to_string(),
+            body: body.to_string(),
+        }
+    }
+}
+
+/// Канал доставки уведомлений
+pub trait Notifier: Send + Sync {
+    /// Отправляет уведомление
+    fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notification_creation() {
+        let notification = Notification::new("Пожар", "Температура на кухне 80°C");
+        assert_eq!(notification.subject, "Пожар");
+        assert_eq!(notification.body, "Температура на кухне 80°C");
+    }
+
+    #[test]
+    fn notification_serialization() {
+        let notification = Notification::new("Alert", "Kettle is on");
+        let json = serde_json::to_string(&notification).unwrap();
+        assert_eq!(json, r#"{"subject":"Alert","body":"Kettle is on"}"#);
+    }
+
+    #[test]
+    fn notifier_is_object_safe() {
+        let notifiers: Vec<Box<dyn Notifier>> = vec![
+            Box::new(WebhookNotifier::new("http://127.0.0.1:9/hook").unwrap()),
+            Box::new(TelegramNotifier::new("token", "42")),
+        ];
+        assert_eq!(notifiers.len(), 2);
+    }
+}
@@ -0,0 +1,272 @@
+//! Минимальный HTTP/1.1 клиент для уведомлений и интеграций (без TLS)
+
+use super::NotifyError;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// Разобранный `http://` адрес
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpUrl {
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl HttpUrl {
+    /// Разбирает строку вида `http://host[:port][/path]`
+    pub fn parse(url: &str) -> Result<Self, NotifyError> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| NotifyError::InvalidUrl(url.to_string()))?;
+
+        if scheme != "http" {
+            return Err(NotifyError::UnsupportedScheme(scheme.to_string()));
+        }
+
+        let (authority, path) = match rest.find('/') {
+            Some(pos) => (&rest[..pos], &rest[pos..]),
+            None => (rest, "/"),
+        };
+
+        // IPv6 адрес в квадратных скобках: [::1]:8080
+        let (host, port) = if let Some(stripped) = authority.strip_prefix('[') {
+            let (host, tail) = stripped
+                .split_once(']')
+                .ok_or_else(|| NotifyError::InvalidUrl(url.to_string()))?;
+            let port = tail.strip_prefix(':').unwrap_or("80");
+            (host, port)
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host, port),
+                None => (authority, "80"),
+            }
+        };
+
+        if host.is_empty() {
+            return Err(NotifyError::InvalidUrl(url.to_string()));
+        }
+
+        let port = port
+            .parse::<u16>()
+            .map_err(|_| NotifyError::InvalidUrl(url.to_string()))?;
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// Адрес для TCP подключения
+    fn connect_addr(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+/// Отправляет POST запрос и возвращает HTTP статус ответа
+pub async fn post(
+    url: &HttpUrl,
+    content_type: &str,
+    body: &[u8],
+    request_timeout: Duration,
+) -> Result<u16, NotifyError> {
+    timeout(request_timeout, post_inner(url, content_type, body))
+        .await
+        .map_err(|_| NotifyError::Timeout)?
+}
+
+async fn post_inner(url: &HttpUrl, content_type: &str, body: &[u8]) -> Result<u16, NotifyError> {
+    let mut stream = TcpStream::connect(url.connect_addr()).await?;
+
+    let head = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        url.path,
+        url.connect_addr(),
+        content_type,
+        body.len()
+    );
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.flush().await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+
+    parse_status(&response)
+}
+
+/// Отправляет GET запрос и возвращает HTTP статус и тело ответа
+pub async fn get(url: &HttpUrl, request_timeout: Duration) -> Result<(u16, Vec<u8>), NotifyError> {
+    timeout(request_timeout, get_inner(url))
+        .await
+        .map_err(|_| NotifyError::Timeout)?
+}
+
+async fn get_inner(url: &HttpUrl) -> Result<(u16, Vec<u8>), NotifyError> {
+    let mut stream = TcpStream::connect(url.connect_addr()).await?;
+
+    let head = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: application/json\r\nConnection: close\r\n\r\n",
+        url.path,
+        url.connect_addr()
+    );
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.flush().await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+
+    let status = parse_status(&response)?;
+    Ok((status, parse_body(&response)))
+}
+
+/// Извлекает тело ответа, раскрывая `Transfer-Encoding: chunked`
+fn parse_body(response: &[u8]) -> Vec<u8> {
+    let Some(split) = response.windows(4).position(|w| w == b"\r\n\r\n") else {
+        return Vec::new();
+    };
+    let head = String::from_utf8_lossy(&response[..split]).to_ascii_lowercase();
+    let body = &response[split + 4..];
+
+    if !head.contains("transfer-encoding: chunked") {
+        return body.to_vec();
+    }
+
+    let mut decoded = Vec::new();
+    let mut rest = body;
+    while let Some(line_end) = rest.windows(2).position(|w| w == b"\r\n") {
+        let size_line = String::from_utf8_lossy(&rest[..line_end]);
+        let size_hex = size_line.split(';').next().unwrap_or_default().trim();
+        let Ok(size) = usize::from_str_radix(size_hex, 16) else {
+            break;
+        };
+        let start = line_end + 2;
+        if size == 0 || start + size > rest.len() {
+            break;
+        }
+        decoded.extend_from_slice(&rest[start..start + size]);
+        rest = &rest[(start + size + 2).min(rest.len())..];
+    }
+    decoded
+}
+
+/// Извлекает код ответа из строки статуса `HTTP/1.1 200 OK`
+fn parse_status(response: &[u8]) -> Result<u16, NotifyError> {
+    let text = String::from_utf8_lossy(response);
+    let status_line = text.lines().next().unwrap_or_default();
+
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| NotifyError::Rejected(format!("Malformed HTTP response: '{}'", status_line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parse_full_url() {
+        let url = HttpUrl::parse("http://example.com:8080/hooks/alert").unwrap();
+        assert_eq!(url.host, "example.com");
+        assert_eq!(url.port, 8080);
+        assert_eq!(url.path, "/hooks/alert");
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let url = HttpUrl::parse("http://localhost").unwrap();
+        assert_eq!(url.port, 80);
+        assert_eq!(url.path, "/");
+    }
+
+    #[test]
+    fn parse_ipv6() {
+        let url = HttpUrl::parse("http://[::1]:9000/x").unwrap();
+        assert_eq!(url.host, "::1");
+        assert_eq!(url.port, 9000);
+        assert_eq!(url.connect_addr(), "[::1]:9000");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            HttpUrl::parse("https://example.com"),
+            Err(NotifyError::UnsupportedScheme(_))
+        ));
+        assert!(matches!(
+            HttpUrl::parse("example.com"),
+            Err(NotifyError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            HttpUrl::parse("http://host:port/"),
+            Err(NotifyError::InvalidUrl(_))
+        ));
+    }
+
+    #[test]
+    fn status_parsing() {
+        assert_eq!(
+            parse_status(b"HTTP/1.1 204 No Content\r\n\r\n").unwrap(),
+            204
+        );
+        assert!(parse_status(b"garbage").is_err());
+    }
+
+    #[test]
+    fn body_parsing() {
+        assert_eq!(
+            parse_body(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}"),
+            b"{}"
+        );
+        assert_eq!(
+            parse_body(
+                b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n"
+            ),
+            b"{\"a\":1}"
+        );
+        assert!(parse_body(b"garbage").is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async networking"]
+    async fn post_to_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"hello") {
+                let size = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..size]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let url = HttpUrl::parse(&format!("http://{}/hook", addr)).unwrap();
+        let status = post(&url, "text/plain", b"hello", Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(status, 200);
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hook HTTP/1.1"));
+        assert!(request.ends_with("hello"));
+    }
+}
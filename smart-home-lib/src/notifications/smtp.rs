@@ -0,0 +1,326 @@
+//! Отправка уведомлений по email через SMTP relay (без TLS и авторизации)
+
+use super::{Notification, Notifier, NotifyError, NotifyFuture};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// Уведомления по email через локальный SMTP relay
+#[derive(Debug, Clone)]
+pub struct SmtpNotifier {
+    server: String,
+    from: String,
+    to: Vec<String>,
+    timeout: Duration,
+}
+
+impl SmtpNotifier {
+    /// Создает отправителя с адресом SMTP сервера (`host:port`) и адресом отправителя
+    ///
+    /// Адрес с управляющими символами (CR, LF...) или `<>` отклоняется: он попадает в
+    /// команды `MAIL FROM` и заголовки письма как есть.
+    pub fn new(server: &str, from: &str) -> Result<Self, NotifyError> {
+        Ok(Self {
+            server: server.to_string(),
+            from: checked_address(from)?,
+            to: Vec::new(),
+            timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// Builder: добавляет получателя (адрес проверяется как в `new`)
+    pub fn with_recipient(mut self, to: &str) -> Result<Self, NotifyError> {
+        self.to.push(checked_address(to)?);
+        Ok(self)
+    }
+
+    /// Builder: устанавливает таймаут всей SMTP сессии
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Формирует текст письма (заголовки + тело с dot-stuffing)
+    fn message(&self, notification: &Notification) -> String {
+        let mut message = format!(
+            "From: <{}>\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+            self.from,
+            self.to
+                .iter()
+                .map(|to| format!("<{}>", to))
+                .collect::<Vec<_>>()
+                .join(", "),
+            encode_header(&notification.subject)
+        );
+
+        for line in notification.body.lines() {
+            // Строки, начинающиеся с точки, экранируются (RFC 5321, 4.5.2)
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+
+        message.push_str(".\r\n");
+        message
+    }
+
+    /// Проводит SMTP диалог
+    async fn send(&self, notification: &Notification) -> Result<(), NotifyError> {
+        if self.to.is_empty() {
+            return Err(NotifyError::Rejected(
+                "No recipients configured".to_string(),
+            ));
+        }
+
+        let stream = TcpStream::connect(&self.server).await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+
+        expect_reply(&mut reader, 220).await?;
+
+        command(&mut writer, "HELO smart-home\r\n").await?;
+        expect_reply(&mut reader, 250).await?;
+
+        command(&mut writer, &format!("MAIL FROM:<{}>\r\n", self.from)).await?;
+        expect_reply(&mut reader, 250).await?;
+
+        for to in &self.to {
+            command(&mut writer, &format!("RCPT TO:<{}>\r\n", to)).await?;
+            expect_reply(&mut reader, 250).await?;
+        }
+
+        command(&mut writer, "DATA\r\n").await?;
+        expect_reply(&mut reader, 354).await?;
+
+        command(&mut writer, &self.message(notification)).await?;
+        expect_reply(&mut reader, 250).await?;
+
+        command(&mut writer, "QUIT\r\n").await?;
+
+        Ok(())
+    }
+}
+
+/// Адрес для SMTP команд и заголовков: без управляющих символов и `<>`
+fn checked_address(address: &str) -> Result<String, NotifyError> {
+    if address.is_empty() || address.chars().any(|c| c.is_control() || c == '<' || c == '>') {
+        return Err(NotifyError::InvalidAddress(address.to_string()));
+    }
+    Ok(address.to_string())
+}
+
+/// Кодирует заголовок по RFC 2047, если в нем есть не-ASCII или управляющие
+/// символы (CR/LF в заголовке иначе начали бы новый заголовок)
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() && !value.chars().any(|c| c.is_ascii_control()) {
+        return value.to_string();
+    }
+
+    format!("=?UTF-8?B?{}?=", base64(value.as_bytes()))
+}
+
+/// Кодирует байты в base64 (RFC 4648)
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (triple >> (18 - 6 * i)) & 0x3f;
+                encoded.push(ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Отправляет строку команды серверу
+async fn command<W>(writer: &mut W, line: &str) -> std::io::Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await
+}
+
+/// Читает (возможно многострочный) ответ сервера и проверяет код
+async fn expect_reply<R>(reader: &mut R, expected: u16) -> Result<(), NotifyError>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(NotifyError::Rejected(
+                "SMTP server closed connection".to_string(),
+            ));
+        }
+
+        let code = line.get(..3).and_then(|c| c.parse::<u16>().ok());
+        if code != Some(expected) {
+            return Err(NotifyError::Rejected(format!(
+                "Expected SMTP {}, got '{}'",
+                expected,
+                line.trim_end()
+            )));
+        }
+
+        // "250-..." означает продолжение многострочного ответа
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+impl Notifier for SmtpNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            timeout(self.timeout, self.send(notification))
+                .await
+                .map_err(|_| NotifyError::Timeout)?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn message_format() {
+        let smtp = SmtpNotifier::new("127.0.0.1:25", "house@example.com")
+            .unwrap()
+            .with_recipient("owner@example.com")
+            .unwrap();
+        let message = smtp.message(&Notification::new("Alert", "line1\n.hidden"));
+
+        assert!(message.starts_with("From: <house@example.com>\r\nTo: <owner@example.com>\r\n"));
+        assert!(message.contains("Subject: Alert\r\n"));
+        assert!(message.contains("\r\n\r\nline1\r\n..hidden\r\n"));
+        assert!(message.ends_with("\r\n.\r\n"));
+    }
+
+    #[test]
+    fn header_encoding() {
+        assert_eq!(encode_header("Alert"), "Alert");
+        assert_eq!(encode_header("Жар"), "=?UTF-8?B?0JbQsNGA?=");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+    }
+
+    #[test]
+    fn header_injection() {
+        // CR/LF в теме кодируется и не начинает новый заголовок
+        let smtp = SmtpNotifier::new("127.0.0.1:25", "house@example.com")
+            .unwrap()
+            .with_recipient("owner@example.com")
+            .unwrap();
+        let message = smtp.message(&Notification::new("Alert\r\nBcc: evil@example.com", "x"));
+        assert!(message.contains("Subject: =?UTF-8?B?"));
+        assert!(!message.contains("\r\nBcc:"));
+
+        assert!(matches!(
+            SmtpNotifier::new("127.0.0.1:25", "house@example.com>\r\nRCPT TO:<evil@example.com"),
+            Err(NotifyError::InvalidAddress(_))
+        ));
+        let smtp = SmtpNotifier::new("127.0.0.1:25", "house@example.com").unwrap();
+        assert!(matches!(
+            smtp.with_recipient("owner@example.com\nBcc: evil@example.com"),
+            Err(NotifyError::InvalidAddress(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn no_recipients() {
+        let smtp = SmtpNotifier::new("127.0.0.1:25", "house@example.com").unwrap();
+        let result = smtp.notify(&Notification::new("a", "b")).await;
+        assert!(matches!(result, Err(NotifyError::Rejected(_))));
+    }
+
+    #[tokio::test]
+    async fn multiline_reply() {
+        let mut reader = BufReader::new(&b"250-smtp.local\r\n250-SIZE 1000\r\n250 OK\r\n"[..]);
+        assert!(expect_reply(&mut reader, 250).await.is_ok());
+
+        let mut reader = BufReader::new(&b"550 No such user\r\n"[..]);
+        assert!(matches!(
+            expect_reply(&mut reader, 250).await,
+            Err(NotifyError::Rejected(_))
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async networking"]
+    async fn smtp_dialogue() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            let mut transcript = Vec::new();
+
+            writer.write_all(b"220 test\r\n").await.unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                transcript.push(line.clone());
+
+                let reply: &[u8] = if in_data {
+                    if line == ".\r\n" {
+                        in_data = false;
+                        b"250 queued\r\n"
+                    } else {
+                        continue;
+                    }
+                } else if line.starts_with("DATA") {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line.starts_with("QUIT") {
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+            transcript
+        });
+
+        let smtp = SmtpNotifier::new(&addr.to_string(), "house@example.com")
+            .unwrap()
+            .with_recipient("a@example.com")
+            .unwrap()
+            .with_recipient("b@example.com")
+            .unwrap();
+        smtp.notify(&Notification::new("Пожар", "Кухня: 80°C"))
+            .await
+            .unwrap();
+
+        let transcript = server.await.unwrap();
+        assert_eq!(transcript[0], "HELO smart-home\r\n");
+        assert_eq!(transcript[1], "MAIL FROM:<house@example.com>\r\n");
+        assert_eq!(transcript[2], "RCPT TO:<a@example.com>\r\n");
+        assert_eq!(transcript[3], "RCPT TO:<b@example.com>\r\n");
+        assert!(transcript.iter().any(|l| l.contains("Кухня: 80°C")));
+        assert_eq!(transcript.last().unwrap(), "QUIT\r\n");
+    }
+}
@@ -0,0 +1,108 @@
+//! Уведомления через Telegram бота
+//!
+//! Клиент не поддерживает TLS, поэтому по умолчанию обращается к локальному
+//! [Bot API серверу](https://github.com/tdlib/telegram-bot-api), который
+//! слушает HTTP на порту 8081 и сам проксирует запросы в Telegram.
+
+use super::http::{self, HttpUrl};
+use super::{Notification, Notifier, NotifyError, NotifyFuture};
+use std::time::Duration;
+
+/// Адрес локального Bot API сервера по умолчанию
+const DEFAULT_API_BASE: &str = "http://127.0.0.1:8081";
+
+/// Уведомления через Telegram Bot API (`sendMessage`)
+#[derive(Debug, Clone)]
+pub struct TelegramNotifier {
+    token: String,
+    chat_id: String,
+    api_base: String,
+    timeout: Duration,
+}
+
+impl TelegramNotifier {
+    /// Создает бота с токеном и ID чата получателя
+    pub fn new(token: &str, chat_id: &str) -> Self {
+        Self {
+            token: token.to_string(),
+            chat_id: chat_id.to_string(),
+            api_base: DEFAULT_API_BASE.to_string(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Builder: устанавливает адрес Bot API сервера
+    pub fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Builder: устанавливает таймаут запроса
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// URL метода `sendMessage`
+    fn send_message_url(&self) -> String {
+        format!("{}/bot{}/sendMessage", self.api_base, self.token)
+    }
+
+    /// Тело запроса `sendMessage`
+    fn payload(&self, notification: &Notification) -> serde_json::Value {
+        serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": format!("{}\n\n{}", notification.subject, notification.body),
+        })
+    }
+}
+
+impl Notifier for TelegramNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let url = HttpUrl::parse(&self.send_message_url())?;
+            let body = self.payload(notification).to_string();
+
+            let status =
+                http::post(&url, "application/json", body.as_bytes(), self.timeout).await?;
+
+            if (200..300).contains(&status) {
+                Ok(())
+            } else {
+                Err(NotifyError::Rejected(format!(
+                    "Telegram API status {}",
+                    status
+                )))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_message_url() {
+        let bot = TelegramNotifier::new("123:ABC", "42").with_api_base("http://localhost:8081/");
+        assert_eq!(
+            bot.send_message_url(),
+            "http://localhost:8081/bot123:ABC/sendMessage"
+        );
+    }
+
+    #[test]
+    fn payload_format() {
+        let bot = TelegramNotifier::new("token", "-100500");
+        let payload = bot.payload(&Notification::new("Утечка", "Ванная: вода на полу"));
+
+        assert_eq!(payload["chat_id"], "-100500");
+        assert_eq!(payload["text"], "Утечка\n\nВанная: вода на полу");
+    }
+
+    #[test]
+    fn default_api_base_is_plain_http() {
+        let bot = TelegramNotifier::new("token", "1");
+        assert!(HttpUrl::parse(&bot.send_message_url()).is_ok());
+    }
+}
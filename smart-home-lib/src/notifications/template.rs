@@ -0,0 +1,167 @@
+//! Шаблоны сообщений с подстановкой состояния устройств
+//!
+//! Синтаксис подстановок:
+//! - `{комната/ключ}` - отчет устройства или контроллера из дома
+//! - `{имя}` - значение переменной, заданной через `with_var`
+//! - `{{` и `}}` - литеральные фигурные скобки
+
+use super::Notification;
+use crate::house::SmartHouse;
+use crate::traits::Reporter;
+use std::collections::HashMap;
+
+/// Шаблон уведомления
+#[derive(Debug, Clone, Default)]
+pub struct MessageTemplate {
+    subject: String,
+    body: String,
+    vars: HashMap<String, String>,
+}
+
+impl MessageTemplate {
+    /// Создает шаблон с заголовком и телом
+    pub fn new(subject: &str, body: &str) -> Self {
+        Self {
+            subject: subject.to_string(),
+            body: body.to_string(),
+            vars: HashMap::new(),
+        }
+    }
+
+    /// Builder: задает значение переменной `{name}`
+    pub fn with_var(mut self, name: &str, value: impl ToString) -> Self {
+        self.vars.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Формирует уведомление, подставляя состояние устройств дома
+    pub fn render(&self, house: &SmartHouse) -> Notification {
+        Notification {
+            subject: self.render_text(&self.subject, house),
+            body: self.render_text(&self.body, house),
+        }
+    }
+
+    /// Выполняет подстановки в строке
+    fn render_text(&self, text: &str, house: &SmartHouse) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    output.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    output.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+
+                    if closed {
+                        output.push_str(&self.resolve(name.trim(), house));
+                    } else {
+                        // Незакрытая скобка выводится как есть
+                        output.push('{');
+                        output.push_str(&name);
+                    }
+                }
+                _ => output.push(c),
+            }
+        }
+
+        output
+    }
+
+    /// Возвращает значение подстановки
+    fn resolve(&self, name: &str, house: &SmartHouse) -> String {
+        if let Some(value) = self.vars.get(name) {
+            return value.clone();
+        }
+
+        if let Some((room, key)) = name.split_once('/') {
+            if let Ok(device) = house.device(room, key) {
+                return device.report();
+            }
+            if let Ok(controller) = house.controller(room, key) {
+                return controller.report();
+            }
+        }
+
+        format!("<{}?>", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{Device, SmartSocket, SmartTherm};
+    use crate::room;
+
+    fn test_house() -> SmartHouse {
+        crate::house![(
+            "kitchen",
+            room![
+                ("therm", Device::Therm(SmartTherm::new(31.5))),
+                ("kettle", Device::Socket(SmartSocket::new(2000.0)))
+            ]
+        )]
+    }
+
+    #[test]
+    fn device_substitution() {
+        let house = test_house();
+        let template = MessageTemplate::new("Жарко", "Кухня: {kitchen/therm}");
+
+        let notification = template.render(&house);
+        assert_eq!(notification.subject, "Жарко");
+        assert_eq!(notification.body, "Кухня: Smart Thermometer: 31.5°C");
+    }
+
+    #[test]
+    fn variable_substitution() {
+        let house = test_house();
+        let template = MessageTemplate::new("{level}: {room}", "Порог {limit}°C превышен")
+            .with_var("level", "WARN")
+            .with_var("room", "kitchen")
+            .with_var("limit", 30);
+
+        let notification = template.render(&house);
+        assert_eq!(notification.subject, "WARN: kitchen");
+        assert_eq!(notification.body, "Порог 30°C превышен");
+    }
+
+    #[test]
+    fn unknown_and_escaped() {
+        let house = test_house();
+        let template = MessageTemplate::new("", "{{raw}} {kitchen/missing} {nothing} {open");
+
+        let notification = template.render(&house);
+        assert_eq!(
+            notification.body,
+            "{raw} <kitchen/missing?> <nothing?> {open"
+        );
+    }
+
+    #[test]
+    fn socket_state_in_message() {
+        let mut house = test_house();
+        if let Ok(Device::Socket(s)) = house.device_mut("kitchen", "kettle") {
+            s.turn_on();
+        }
+
+        let notification = MessageTemplate::new("", "{kitchen/kettle}").render(&house);
+        assert!(notification.body.contains("ACTIVE"));
+        assert!(notification.body.contains("2000.0W"));
+    }
+}
@@ -0,0 +1,92 @@
+//! Отправка уведомлений на HTTP webhook (JSON POST)
+
+use super::http::{self, HttpUrl};
+use super::{Notification, Notifier, NotifyError, NotifyFuture};
+use std::time::Duration;
+
+/// Уведомления через HTTP webhook
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    url: HttpUrl,
+    timeout: Duration,
+}
+
+impl WebhookNotifier {
+    /// Создает webhook с адресом вида `http://host:port/path`
+    pub fn new(url: &str) -> Result<Self, NotifyError> {
+        Ok(Self {
+            url: HttpUrl::parse(url)?,
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// Builder: устанавливает таймаут запроса
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Возвращает адрес webhook
+    pub fn url(&self) -> &HttpUrl {
+        &self.url
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::to_vec(notification)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+            let status = http::post(&self.url, "application/json", &body, self.timeout).await?;
+
+            if (200..300).contains(&status) {
+                Ok(())
+            } else {
+                Err(NotifyError::Rejected(format!("HTTP status {}", status)))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn webhook_creation() {
+        let webhook = WebhookNotifier::new("http://127.0.0.1:8080/alerts")
+            .unwrap()
+            .with_timeout(Duration::from_secs(1));
+
+        assert_eq!(webhook.url().port, 8080);
+        assert_eq!(webhook.url().path, "/alerts");
+        assert_eq!(webhook.timeout, Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async networking"]
+    async fn webhook_rejected_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"}") {
+                let size = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..size]);
+            }
+            let _ = stream
+                .write_all(b"HTTP/1.1 500 Internal Server Error\r\n\r\n")
+                .await;
+        });
+
+        let webhook = WebhookNotifier::new(&format!("http://{}/", addr)).unwrap();
+        let result = webhook.notify(&Notification::new("a", "b")).await;
+        assert!(matches!(result, Err(NotifyError::Rejected(_))));
+    }
+}
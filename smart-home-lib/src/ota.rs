@@ -0,0 +1,271 @@
+//! Обновление прошивки розеток по сети (OTA)
+//!
+//! Образ прошивки передается по протоколу розетки: `StartUpdate` с версией,
+//! размером и CRC32 образа, затем части `UpdateChunk` по порядку и
+//! `FinishUpdate`, после которого розетка проверяет образ и устанавливает
+//! прошивку. На каждую команду розетка отвечает состоянием обновления
+//! (`UpdateProgress`). Передачу ведет `SocketController::update_firmware`,
+//! прием и проверку имитирует эмулятор - этого достаточно, чтобы проверять
+//! логику оркестрации обновлений без настоящих устройств.
+
+use crate::protocol::socket_protocol::crc32;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Версия прошивки эмулятора по умолчанию
+pub const DEFAULT_FIRMWARE_VERSION: &str = "1.0.0";
+/// Размер части образа по умолчанию, байт
+pub const DEFAULT_CHUNK_SIZE: usize = 4096;
+/// Максимальный размер образа, который принимает эмулятор, байт
+pub const MAX_FIRMWARE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Стадия обновления
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateState {
+    /// Обновление не начиналось
+    Idle,
+    /// Идет прием образа
+    Receiving,
+    /// Образ проверен, прошивка установлена
+    Installed,
+    /// Обновление прервано
+    Failed { reason: String },
+}
+
+/// Состояние обновления прошивки
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateProgress {
+    /// Установленная версия прошивки
+    pub firmware: String,
+    /// Устанавливаемая версия
+    pub version: Option<String>,
+    /// Принято байт образа
+    pub received: u64,
+    /// Размер образа, байт
+    pub size: u64,
+    pub state: UpdateState,
+}
+
+impl UpdateProgress {
+    /// Доля принятого образа, 0.0-1.0
+    pub fn fraction(&self) -> f64 {
+        if self.size == 0 {
+            return 0.0;
+        }
+        self.received as f64 / self.size as f64
+    }
+
+    /// Обновление завершено установкой прошивки
+    pub fn is_installed(&self) -> bool {
+        self.state == UpdateState::Installed
+    }
+}
+
+impl fmt::Display for UpdateProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.state, &self.version) {
+            (UpdateState::Receiving, Some(version)) => write!(
+                f,
+                "Updating {} -> {}: {}/{} bytes ({:.0}%)",
+                self.firmware,
+                version,
+                self.received,
+                self.size,
+                self.fraction() * 100.0
+            ),
+            (UpdateState::Failed { reason }, _) => {
+                write!(f, "Firmware {}, update failed: {}", self.firmware, reason)
+            }
+            _ => write!(f, "Firmware {}", self.firmware),
+        }
+    }
+}
+
+/// Контрольная сумма образа прошивки (CRC32)
+pub fn checksum(image: &[u8]) -> u32 {
+    crc32(&[image])
+}
+
+/// Кодирует часть образа для передачи в JSON
+pub(crate) fn encode_chunk(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Декодирует часть образа; `None` при неверной кодировке
+pub(crate) fn decode_chunk(data: &str) -> Option<Vec<u8>> {
+    if !data.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..data.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(data.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Прием образа прошивки на стороне устройства
+#[derive(Debug, Clone)]
+pub(crate) struct FirmwareReceiver {
+    firmware: String,
+    version: Option<String>,
+    size: u64,
+    checksum: u32,
+    image: Vec<u8>,
+    state: UpdateState,
+}
+
+impl FirmwareReceiver {
+    pub(crate) fn new(firmware: &str) -> Self {
+        Self {
+            firmware: firmware.to_string(),
+            version: None,
+            size: 0,
+            checksum: 0,
+            image: Vec::new(),
+            state: UpdateState::Idle,
+        }
+    }
+
+    /// Установленная версия прошивки
+    pub(crate) fn firmware(&self) -> &str {
+        &self.firmware
+    }
+
+    pub(crate) fn progress(&self) -> UpdateProgress {
+        UpdateProgress {
+            firmware: self.firmware.clone(),
+            version: self.version.clone(),
+            received: match self.state {
+                UpdateState::Installed => self.size,
+                _ => self.image.len() as u64,
+            },
+            size: self.size,
+            state: self.state.clone(),
+        }
+    }
+
+    /// Начинает прием образа (прерывает незавершенный прием)
+    pub(crate) fn start(&mut self, version: &str, size: u64, checksum: u32) -> Result<(), String> {
+        if size > MAX_FIRMWARE_SIZE {
+            return Err(format!(
+                "Firmware image too large: {} bytes (max {})",
+                size, MAX_FIRMWARE_SIZE
+            ));
+        }
+
+        self.version = Some(version.to_string());
+        self.size = size;
+        self.checksum = checksum;
+        self.image = Vec::with_capacity(size as usize);
+        self.state = UpdateState::Receiving;
+        Ok(())
+    }
+
+    /// Принимает очередную часть образа
+    pub(crate) fn chunk(&mut self, offset: u64, data: &str) -> Result<(), String> {
+        if self.state != UpdateState::Receiving {
+            return Err("No firmware update in progress".to_string());
+        }
+        let Some(data) = decode_chunk(data) else {
+            return self.fail("Invalid chunk encoding".to_string());
+        };
+        // Повтор уже принятой части (например, после переподключения)
+        let end = offset as usize + data.len();
+        if end <= self.image.len() && self.image[offset as usize..end] == data[..] {
+            return Ok(());
+        }
+        if offset != self.image.len() as u64 {
+            return self.fail(format!(
+                "Unexpected chunk offset {} (expected {})",
+                offset,
+                self.image.len()
+            ));
+        }
+        if offset + data.len() as u64 > self.size {
+            return self.fail(format!("Chunk exceeds image size {}", self.size));
+        }
+
+        self.image.extend(data);
+        Ok(())
+    }
+
+    /// Проверяет принятый образ и устанавливает прошивку
+    pub(crate) fn finish(&mut self) -> Result<(), String> {
+        if self.state != UpdateState::Receiving {
+            return Err("No firmware update in progress".to_string());
+        }
+        if self.image.len() as u64 != self.size {
+            return self.fail(format!(
+                "Incomplete image: {}/{} bytes",
+                self.image.len(),
+                self.size
+            ));
+        }
+        if checksum(&self.image) != self.checksum {
+            return self.fail("Checksum mismatch".to_string());
+        }
+
+        if let Some(version) = &self.version {
+            self.firmware = version.clone();
+        }
+        self.image = Vec::new();
+        self.state = UpdateState::Installed;
+        Ok(())
+    }
+
+    /// Прерывает прием с ошибкой
+    fn fail(&mut self, reason: String) -> Result<(), String> {
+        self.image = Vec::new();
+        self.state = UpdateState::Failed {
+            reason: reason.clone(),
+        };
+        Err(reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_encoding_round_trip() {
+        let data = [0x00, 0x7f, 0xff, 0x10];
+        assert_eq!(encode_chunk(&data), "007fff10");
+        assert_eq!(decode_chunk("007fff10").unwrap(), data);
+        assert_eq!(decode_chunk("0"), None);
+        assert_eq!(decode_chunk("zz"), None);
+    }
+
+    #[test]
+    fn receiver_validates_image() {
+        let image = b"firmware image".to_vec();
+        let mut receiver = FirmwareReceiver::new("1.0.0");
+
+        receiver
+            .start("1.1.0", image.len() as u64, checksum(&image))
+            .unwrap();
+        receiver.chunk(0, &encode_chunk(&image[..8])).unwrap();
+        receiver.chunk(0, &encode_chunk(&image[..8])).unwrap();
+        assert_eq!(receiver.progress().received, 8);
+        assert!(receiver.chunk(0, &encode_chunk(&image[8..])).is_err());
+        assert!(matches!(
+            receiver.progress().state,
+            UpdateState::Failed { .. }
+        ));
+        assert_eq!(receiver.firmware(), "1.0.0");
+
+        receiver
+            .start("1.1.0", image.len() as u64, checksum(b"other"))
+            .unwrap();
+        receiver.chunk(0, &encode_chunk(&image)).unwrap();
+        assert_eq!(receiver.finish(), Err("Checksum mismatch".to_string()));
+
+        receiver
+            .start("1.1.0", image.len() as u64, checksum(&image))
+            .unwrap();
+        receiver.chunk(0, &encode_chunk(&image)).unwrap();
+        receiver.finish().unwrap();
+        assert!(receiver.progress().is_installed());
+        assert_eq!(receiver.firmware(), "1.1.0");
+    }
+}
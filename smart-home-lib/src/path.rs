@@ -0,0 +1,371 @@
+//! Адрес устройства в доме
+//!
+//! `DevicePath` заменяет пару строк (комната, ключ): путь вида
+//! `"kitchen/kettle"` разбирается и проверяется один раз, после чего его
+//! можно передавать в методы дома (`SmartHouse::device_at`). В конфигурации
+//! путь хранится строкой. `DevicePattern` (`"*/therm*"`, `"kitchen/*"`)
+//! выбирает сразу много устройств: `SmartHouse::select`.
+
+use crate::controllers::DeviceController;
+use crate::devices::Device;
+use crate::house::{SmartHouse, SmartHouseResult};
+use crate::report::ReportOptions;
+use crate::traits::Reporter;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Разделитель комнаты и ключа в пути
+const SEPARATOR: char = '/';
+
+/// Ошибка разбора пути устройства
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    /// В пути нет разделителя `/`
+    MissingSeparator(String),
+    /// Пустое имя комнаты или ключ
+    EmptySegment(String),
+    /// Разделитель встречается больше одного раза
+    TooManySegments(String),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSeparator(path) => {
+                write!(f, "Путь '{}' должен иметь вид комната/устройство", path)
+            }
+            Self::EmptySegment(path) => write!(f, "Пустая часть пути '{}'", path),
+            Self::TooManySegments(path) => write!(f, "Лишний '/' в пути '{}'", path),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Путь к устройству или контроллеру: комната и ключ
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DevicePath {
+    room: String,
+    key: String,
+}
+
+impl DevicePath {
+    /// Путь из комнаты и ключа
+    pub fn new(room: &str, key: &str) -> Result<Self, PathError> {
+        format!("{}{}{}", room, SEPARATOR, key).parse()
+    }
+
+    pub fn room(&self) -> &str {
+        &self.room
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl FromStr for DevicePath {
+    type Err = PathError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let (room, key) = split(path)?;
+        Ok(Self {
+            room: room.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+/// Разбивает путь на комнату и ключ
+fn split(path: &str) -> Result<(&str, &str), PathError> {
+    let Some((room, key)) = path.split_once(SEPARATOR) else {
+        return Err(PathError::MissingSeparator(path.to_string()));
+    };
+    if key.contains(SEPARATOR) {
+        return Err(PathError::TooManySegments(path.to_string()));
+    }
+    if room.trim().is_empty() || key.trim().is_empty() {
+        return Err(PathError::EmptySegment(path.to_string()));
+    }
+    Ok((room, key))
+}
+
+impl TryFrom<String> for DevicePath {
+    type Error = PathError;
+
+    fn try_from(path: String) -> Result<Self, Self::Error> {
+        path.parse()
+    }
+}
+
+impl From<DevicePath> for String {
+    fn from(path: DevicePath) -> Self {
+        path.to_string()
+    }
+}
+
+impl fmt::Display for DevicePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.room, SEPARATOR, self.key)
+    }
+}
+
+/// Шаблон путей: `*` - любая часть имени, `?` - один символ
+///
+/// Шаблон применяется к комнате и ключу по отдельности, `*` не
+/// захватывает разделитель `/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevicePattern {
+    room: String,
+    key: String,
+}
+
+impl DevicePattern {
+    /// Путь соответствует шаблону
+    pub fn matches(&self, path: &DevicePath) -> bool {
+        glob_match(&self.room, path.room()) && glob_match(&self.key, path.key())
+    }
+}
+
+impl FromStr for DevicePattern {
+    type Err = PathError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let (room, key) = split(pattern)?;
+        Ok(Self {
+            room: room.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for DevicePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.room, SEPARATOR, self.key)
+    }
+}
+
+/// Сопоставление строки с шаблоном из `*` и `?`
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Позиция последней `*` в шаблоне и символ текста, с которого она совпадает
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Расширяем совпадение последней `*` на один символ
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Найденный по шаблону элемент комнаты
+#[derive(Clone, Copy)]
+pub enum ItemRef<'a> {
+    Device(&'a Device),
+    Controller(&'a DeviceController),
+}
+
+impl ItemRef<'_> {
+    /// Название типа устройства
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Device(device) => device.kind(),
+            Self::Controller(controller) => controller.kind(),
+        }
+    }
+}
+
+impl Reporter for ItemRef<'_> {
+    fn report(&self) -> String {
+        match self {
+            Self::Device(device) => device.report(),
+            Self::Controller(controller) => controller.report(),
+        }
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        match self {
+            Self::Device(device) => device.report_with(options),
+            Self::Controller(controller) => controller.report_with(options),
+        }
+    }
+}
+
+impl SmartHouse {
+    /// Устройства и контроллеры, пути которых соответствуют шаблону `pattern`
+    ///
+    /// Порядок - порядок комнат, внутри комнаты сначала устройства, затем
+    /// контроллеры.
+    pub fn select(&self, pattern: &str) -> Result<Vec<(DevicePath, ItemRef<'_>)>, PathError> {
+        let pattern: DevicePattern = pattern.parse()?;
+        let mut selected = Vec::new();
+
+        for room_key in self.rooms_keys() {
+            let Some(room) = self.room(&room_key) else {
+                continue;
+            };
+            let devices = room
+                .devices_keys()
+                .into_iter()
+                .filter_map(|key| Some((room.device(&key).map(ItemRef::Device)?, key)));
+            let controllers = room
+                .controllers_keys()
+                .into_iter()
+                .filter_map(|key| Some((room.controller(&key).map(ItemRef::Controller)?, key)));
+
+            for (item, key) in devices.chain(controllers) {
+                let path = DevicePath {
+                    room: room_key.clone(),
+                    key,
+                };
+                if pattern.matches(&path) {
+                    selected.push((path, item));
+                }
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Устройство по пути
+    pub fn device_at(&self, path: &DevicePath) -> SmartHouseResult<&Device> {
+        self.device(path.room(), path.key())
+    }
+
+    /// Изменяемое устройство по пути
+    pub fn device_at_mut(&mut self, path: &DevicePath) -> SmartHouseResult<&mut Device> {
+        self.device_mut(path.room(), path.key())
+    }
+
+    /// Контроллер по пути
+    pub fn controller_at(&self, path: &DevicePath) -> SmartHouseResult<&DeviceController> {
+        self.controller(path.room(), path.key())
+    }
+
+    /// Изменяемый контроллер по пути
+    pub fn controller_at_mut(
+        &mut self,
+        path: &DevicePath,
+    ) -> SmartHouseResult<&mut DeviceController> {
+        self.controller_mut(path.room(), path.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::house::SmartHouseError;
+
+    #[test]
+    fn parse_and_display() {
+        let path: DevicePath = "kitchen/kettle".parse().unwrap();
+        assert_eq!(path.room(), "kitchen");
+        assert_eq!(path.key(), "kettle");
+        assert_eq!(path.to_string(), "kitchen/kettle");
+        assert_eq!(DevicePath::new("kitchen", "kettle").unwrap(), path);
+
+        assert!(matches!(
+            "kitchen".parse::<DevicePath>(),
+            Err(PathError::MissingSeparator(_))
+        ));
+        assert!(matches!(
+            "kitchen/".parse::<DevicePath>(),
+            Err(PathError::EmptySegment(_))
+        ));
+        assert!(matches!(
+            DevicePath::new("kitchen", "a/b"),
+            Err(PathError::TooManySegments(_))
+        ));
+    }
+
+    #[test]
+    fn serde_as_string() {
+        let path = DevicePath::new("hall", "lamp").unwrap();
+        assert_eq!(serde_json::to_string(&path).unwrap(), "\"hall/lamp\"");
+        assert_eq!(
+            serde_json::from_str::<DevicePath>("\"hall/lamp\"").unwrap(),
+            path
+        );
+        assert!(serde_json::from_str::<DevicePath>("\"hall\"").is_err());
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*", "kitchen"));
+        assert!(glob_match("therm*", "therm_2"));
+        assert!(glob_match("*therm", "wall_therm"));
+        assert!(glob_match("t?erm", "therm"));
+        assert!(glob_match("*a*b", "xaxxab"));
+        assert!(!glob_match("therm*", "heater"));
+        assert!(!glob_match("t?erm", "tterm2"));
+    }
+
+    #[test]
+    fn select_by_pattern() {
+        let house = crate::house! {
+            "kitchen" => { "kettle" => socket(2000.0), "therm" => therm(21.0) },
+            "hall" => { "lamp" => socket(60.0), "therm_2" => therm(19.0) },
+        };
+
+        let paths = |pattern: &str| -> Vec<String> {
+            house
+                .select(pattern)
+                .unwrap()
+                .iter()
+                .map(|(path, _)| path.to_string())
+                .collect()
+        };
+        assert_eq!(paths("*/therm*"), vec!["kitchen/therm", "hall/therm_2"]);
+        assert_eq!(paths("kitchen/*"), vec!["kitchen/kettle", "kitchen/therm"]);
+        assert!(paths("garage/*").is_empty());
+
+        let (_, lamp) = &house.select("hall/lamp").unwrap()[0];
+        assert_eq!(lamp.kind(), "socket");
+        assert!(lamp.report().contains("Smart Socket"));
+        assert!(matches!(
+            house.select("*"),
+            Err(PathError::MissingSeparator(_))
+        ));
+    }
+
+    #[test]
+    fn house_access() {
+        let mut house = crate::house! {
+            "hall" => { "lamp" => socket(60.0) },
+        };
+        let lamp: DevicePath = "hall/lamp".parse().unwrap();
+
+        assert_eq!(house.device_at(&lamp).unwrap().kind(), "socket");
+        if let Ok(Device::Socket(socket)) = house.device_at_mut(&lamp) {
+            socket.turn_on();
+        }
+        assert!(matches!(
+            house.device_at(&lamp),
+            Ok(Device::Socket(socket)) if socket.is_active()
+        ));
+        assert!(matches!(
+            house.controller_at(&lamp),
+            Err(SmartHouseError::DeviceNotFound(_, _))
+        ));
+    }
+}
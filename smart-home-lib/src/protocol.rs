@@ -1,23 +1,46 @@
 //! Протокол обмена данными между устройствами и контроллерами
 
+#[cfg(feature = "blinds")]
+pub mod blinds_protocol;
+pub mod bind;
+#[cfg(feature = "camera")]
+pub mod camera_protocol;
+#[cfg(feature = "coap")]
+pub mod coap;
+pub mod error;
+#[cfg(feature = "leak")]
+pub mod leak_protocol;
+pub mod sniffer;
 pub mod socket_protocol;
+pub mod stats;
 pub mod therm_protocol;
+pub mod trace;
+pub mod transport;
+#[cfg(feature = "valve")]
+pub mod valve_protocol;
 
+#[cfg(feature = "blinds")]
+pub use blinds_protocol::{BlindsCommand, BlindsData, BlindsResponse};
+pub use bind::IpStack;
+#[cfg(feature = "camera")]
+pub use camera_protocol::{CameraCommand, CameraResponse, SnapshotInfo};
+pub use error::ProtocolError;
+#[cfg(feature = "leak")]
+pub use leak_protocol::LeakEvent;
+pub use sniffer::{Direction, SniffedFrame, Sniffer};
 pub use socket_protocol::{
-    SocketCommand, SocketData, SocketResponse, receive_message, send_command,
+    FrameReader, OutletData, SocketCommand, SocketData, SocketResponse, receive_message,
+    receive_message_into, send_command,
 };
+pub use stats::{ProtocolStats, protocol_stats, reset_protocol_stats};
 pub use therm_protocol::ThermData;
+pub use trace::TraceContext;
+pub use transport::{InMemory, SharedTransport, Transport};
+#[cfg(feature = "valve")]
+pub use valve_protocol::{ValveCommand, ValveData, ValveResponse};
 
-use std::time::{SystemTime, UNIX_EPOCH};
-
-/// Получает текущее время в миллисекундах с Unix epoch
-/// Используется контроллерами для добавления timestamp при получении данных
-pub fn now_ms() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis() as u64
-}
+/// Время для отметок в данных устройств (см. `clock::now_ms`)
+pub use crate::clock::now_ms;
 
 #[cfg(test)]
 mod tests {
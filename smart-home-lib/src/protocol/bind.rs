@@ -0,0 +1,102 @@
+//! Привязка сокетов с явным режимом IPv6
+//!
+//! Будет ли сокет на `[::]` принимать и IPv4 (dual-stack), зависит от ОС
+//! (`net.ipv6.bindv6only` в Linux, всегда только IPv6 в Windows до
+//! настройки). `IpStack` задает режим явно; для IPv4 адресов он не
+//! действует. Исходящим сокетам `unspecified_for` подбирает адрес того же
+//! семейства, что у получателя.
+
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, ToSocketAddrs, UdpSocket};
+
+/// Очередь ожидающих подключений TCP
+const LISTEN_BACKLOG: i32 = 1024;
+
+/// Режим IPv6 сокета
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpStack {
+    /// Как настроено в ОС
+    #[default]
+    System,
+    /// Только IPv6
+    V6Only,
+    /// IPv6 и IPv4 (адреса вида `::ffff:127.0.0.1`) на одном сокете
+    DualStack,
+}
+
+/// Адрес `0.0.0.0:0` или `[::]:0` для исходящего сокета к `target`
+pub fn unspecified_for(target: SocketAddr) -> SocketAddr {
+    match target {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    }
+}
+
+/// Первый адрес, в который разрешился `addr`
+pub fn resolve_first(addr: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+    addr.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No address to bind"))
+}
+
+/// UDP сокет на `addr` в режиме `stack`
+pub fn bind_udp(addr: impl ToSocketAddrs, stack: IpStack) -> io::Result<UdpSocket> {
+    let addr = resolve_first(addr)?;
+    let socket = socket(addr, Type::DGRAM, Protocol::UDP, stack)?;
+    socket.bind(&addr.into())?;
+    Ok(socket.into())
+}
+
+/// Слушающий TCP сокет на `addr` в режиме `stack`
+pub fn bind_tcp(addr: impl ToSocketAddrs, stack: IpStack) -> io::Result<TcpListener> {
+    let addr = resolve_first(addr)?;
+    let socket = socket(addr, Type::STREAM, Protocol::TCP, stack)?;
+    // Как у std: порт можно занять сразу после остановки прежнего слушателя
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(LISTEN_BACKLOG)?;
+    Ok(socket.into())
+}
+
+fn socket(addr: SocketAddr, ty: Type, protocol: Protocol, stack: IpStack) -> io::Result<Socket> {
+    let socket = Socket::new(Domain::for_address(addr), ty, Some(protocol))?;
+    if addr.is_ipv6() {
+        match stack {
+            IpStack::System => {}
+            IpStack::V6Only => socket.set_only_v6(true)?,
+            IpStack::DualStack => socket.set_only_v6(false)?,
+        }
+    }
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn udp_stacks() {
+        let dual = bind_udp("[::]:0", IpStack::DualStack).unwrap();
+        dual.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+        let port = dual.local_addr().unwrap().port();
+
+        // IPv4 и IPv6 отправители попадают в один сокет
+        for target in [format!("127.0.0.1:{}", port), format!("[::1]:{}", port)] {
+            let target = resolve_first(target.as_str()).unwrap();
+            let sender = UdpSocket::bind(unspecified_for(target)).unwrap();
+            sender.send_to(b"ping", target).unwrap();
+            let mut buf = [0u8; 8];
+            assert_eq!(dual.recv(&mut buf).unwrap(), 4);
+        }
+
+        let v6 = bind_udp("[::1]:0", IpStack::V6Only).unwrap();
+        assert!(v6.local_addr().unwrap().is_ipv6());
+        let v4 = bind_udp("127.0.0.1:0", IpStack::V6Only).unwrap();
+        assert!(v4.local_addr().unwrap().is_ipv4());
+    }
+}
@@ -0,0 +1,110 @@
+//! Async протокол TCP для жалюзи
+//!
+//! Положение задается в процентах: 0 - закрыты, 100 - полностью открыты.
+//! Команды движения отвечают сразу, не дожидаясь конца хода; ход
+//! отслеживается запросами `Status`. Каждый ответ несет текущее положение
+//! и цель хода.
+
+use super::socket_protocol::{receive_json, request_json, send_json};
+use serde::{Deserialize, Serialize};
+use std::io::Result as IoResult;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Команды жалюзи
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "command")]
+pub enum BlindsCommand {
+    #[serde(rename = "open")]
+    Open,
+    #[serde(rename = "close")]
+    Close,
+    /// Движение к положению в процентах (значения выше 100 ограничиваются)
+    #[serde(rename = "set_position")]
+    SetPosition { percent: u8 },
+    /// Остановка в текущем положении
+    #[serde(rename = "stop")]
+    Stop,
+    /// Запрос положения без движения
+    #[serde(rename = "status")]
+    Status,
+}
+
+/// Ответы жалюзи
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "result")]
+pub enum BlindsResponse {
+    #[serde(rename = "ok")]
+    Ok(BlindsData),
+    #[serde(rename = "error")]
+    Error { message: String },
+}
+
+/// Данные от жалюзи
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlindsData {
+    /// Текущее положение, %
+    pub position: u8,
+    /// Положение, к которому движутся жалюзи, %
+    pub target: u8,
+    pub moving: bool,
+    pub device_id: Option<String>,
+}
+
+/// Async отправка команды и получение ответа
+pub async fn send_blinds_command_and_receive<S>(
+    stream: &mut S,
+    command: &BlindsCommand,
+    max_size: usize,
+) -> IoResult<BlindsResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    request_json(stream, command, max_size).await
+}
+
+/// Async получение команды жалюзи
+pub async fn receive_blinds_command<R>(reader: &mut R, max_size: usize) -> IoResult<BlindsCommand>
+where
+    R: AsyncRead + Unpin,
+{
+    receive_json(reader, max_size).await
+}
+
+/// Async отправка ответа жалюзи
+pub async fn send_blinds_response<W>(writer: &mut W, response: &BlindsResponse) -> IoResult<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    send_json(writer, response).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blinds_messages_serialization() {
+        let json = serde_json::to_string(&BlindsCommand::SetPosition { percent: 40 }).unwrap();
+        assert_eq!(json, r#"{"command":"set_position","percent":40}"#);
+        assert_eq!(
+            serde_json::from_str::<BlindsCommand>(r#"{"command":"stop"}"#).unwrap(),
+            BlindsCommand::Stop
+        );
+
+        let response = BlindsResponse::Ok(BlindsData {
+            position: 10,
+            target: 100,
+            moving: true,
+            device_id: None,
+        });
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(
+            json,
+            r#"{"result":"ok","position":10,"target":100,"moving":true,"device_id":null}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<BlindsResponse>(&json).unwrap(),
+            response
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Async протокол TCP для камеры
+//!
+//! На команду `Snapshot` камера отвечает описанием снимка в JSON, а сразу
+//! за ним отправляет сам JPEG отдельным двоичным сообщением частями
+//! (`send_bytes_chunked`). Получатель собирает снимок в
+//! буфер (`receive_message_into`) и сверяет размер с описанием.
+
+use super::error::ProtocolError;
+use super::socket_protocol::{FrameReader, receive_json, send_bytes_chunked, send_json};
+use super::stats::from_json;
+use serde::{Deserialize, Serialize};
+use std::io::Result as IoResult;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Размер части снимка по умолчанию, байт
+pub const DEFAULT_SNAPSHOT_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Маркер начала изображения JPEG (SOI)
+pub const JPEG_SOI: [u8; 2] = [0xFF, 0xD8];
+/// Маркер конца изображения JPEG (EOI)
+pub const JPEG_EOI: [u8; 2] = [0xFF, 0xD9];
+
+/// Команды камеры
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "command")]
+pub enum CameraCommand {
+    /// Запрос текущего снимка
+    #[serde(rename = "snapshot")]
+    Snapshot,
+}
+
+/// Ответы камеры
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "result")]
+pub enum CameraResponse {
+    /// Снимок готов, данные JPEG идут следующим сообщением
+    #[serde(rename = "snapshot")]
+    Snapshot(SnapshotInfo),
+    #[serde(rename = "error")]
+    Error { message: String },
+}
+
+/// Описание снимка
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotInfo {
+    pub width: u32,
+    pub height: u32,
+    /// Размер JPEG, байт
+    pub size: u64,
+    /// Номер кадра камеры
+    pub sequence: u64,
+    pub device_id: Option<String>,
+}
+
+/// Данные похожи на JPEG: есть маркеры начала и конца изображения
+pub fn is_jpeg(data: &[u8]) -> bool {
+    data.starts_with(&JPEG_SOI) && data.ends_with(&JPEG_EOI)
+}
+
+/// Async отправка команды камере
+pub async fn send_camera_command<W>(writer: &mut W, command: &CameraCommand) -> IoResult<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    send_json(writer, command).await
+}
+
+/// Async получение команды камерой
+pub async fn receive_camera_command<R>(reader: &mut R, max_size: usize) -> IoResult<CameraCommand>
+where
+    R: AsyncRead + Unpin,
+{
+    receive_json(reader, max_size).await
+}
+
+/// Async отправка ответа с ошибкой
+pub async fn send_camera_error<W>(writer: &mut W, message: &str) -> IoResult<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let response = CameraResponse::Error {
+        message: message.to_string(),
+    };
+    send_json(writer, &response).await
+}
+
+/// Async отправка снимка: описание и JPEG частями не больше `chunk_size`
+pub async fn send_snapshot<W>(
+    writer: &mut W,
+    info: &SnapshotInfo,
+    jpeg: &[u8],
+    chunk_size: usize,
+) -> IoResult<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    send_json(writer, &CameraResponse::Snapshot(info.clone())).await?;
+    send_bytes_chunked(writer, jpeg, chunk_size).await
+}
+
+/// Async запрос снимка
+///
+/// Данные снимка принимаются в `buf` (ограничение `max_size` действует на
+/// них и на ответ; снимок больше `max_size` отклоняется по описанию, не
+/// читая данных). Ответ `Error` возвращается как есть, буфер при этом пуст.
+pub async fn request_snapshot<S>(
+    stream: &mut S,
+    buf: &mut Vec<u8>,
+    max_size: usize,
+) -> IoResult<CameraResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    send_camera_command(stream, &CameraCommand::Snapshot).await?;
+
+    // Описание и данные идут подряд: их принимает один читатель
+    let mut frames = FrameReader::new(stream);
+    let json = frames.receive_message_with_limit(max_size).await?;
+    let response: CameraResponse = from_json(json.as_bytes())?;
+    let CameraResponse::Snapshot(info) = &response else {
+        buf.clear();
+        return Ok(response);
+    };
+    if info.size > max_size as u64 {
+        buf.clear();
+        return Err(ProtocolError::TooLarge {
+            size: info.size as usize,
+            limit: max_size,
+        }
+        .into());
+    }
+
+    let size = frames
+        .receive_message_into_with_limit(buf, max_size)
+        .await?;
+    if size as u64 != info.size {
+        buf.clear();
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Snapshot size mismatch: {} != {}", size, info.size),
+        ));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::duplex;
+
+    #[test]
+    fn camera_messages_serialization() {
+        let json = serde_json::to_string(&CameraCommand::Snapshot).unwrap();
+        assert_eq!(json, r#"{"command":"snapshot"}"#);
+
+        let response = CameraResponse::Snapshot(SnapshotInfo {
+            width: 64,
+            height: 48,
+            size: 1000,
+            sequence: 3,
+            device_id: None,
+        });
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.starts_with(r#"{"result":"snapshot","width":64"#));
+        assert_eq!(
+            serde_json::from_str::<CameraResponse>(&json).unwrap(),
+            response
+        );
+    }
+
+    #[tokio::test]
+    async fn snapshot_roundtrip() {
+        let (mut client, mut camera) = duplex(1024);
+        let mut jpeg = JPEG_SOI.to_vec();
+        jpeg.extend((0..5000).map(|i| (i % 251) as u8));
+        jpeg.extend_from_slice(&JPEG_EOI);
+        let info = SnapshotInfo {
+            width: 8,
+            height: 8,
+            size: jpeg.len() as u64,
+            sequence: 1,
+            device_id: Some("cam_001".to_string()),
+        };
+
+        let sent = jpeg.clone();
+        let expected = info.clone();
+        let server = tokio::spawn(async move {
+            let command = receive_camera_command(&mut camera, 1024).await.unwrap();
+            assert_eq!(command, CameraCommand::Snapshot);
+            // Снимок больше части - уходит несколькими кадрами
+            send_snapshot(&mut camera, &expected, &sent, 1000)
+                .await
+                .unwrap();
+
+            receive_camera_command(&mut camera, 1024).await.unwrap();
+            send_camera_error(&mut camera, "Lens covered")
+                .await
+                .unwrap();
+        });
+
+        let mut buf = Vec::new();
+        let response = request_snapshot(&mut client, &mut buf, 64 * 1024)
+            .await
+            .unwrap();
+        assert_eq!(response, CameraResponse::Snapshot(info));
+        assert_eq!(buf, jpeg);
+        assert!(is_jpeg(&buf));
+
+        let response = request_snapshot(&mut client, &mut buf, 64 * 1024)
+            .await
+            .unwrap();
+        assert!(matches!(response, CameraResponse::Error { message } if message == "Lens covered"));
+        assert!(buf.is_empty());
+
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,394 @@
+//! Минимальная реализация CoAP (RFC 7252) поверх UDP
+//!
+//! Поддерживается то, что нужно простым датчикам: заголовок, token,
+//! опции Uri-Path, Content-Format и Observe (RFC 7641), полезная нагрузка
+//! в виде короткого текста вместо JSON.
+
+use std::fmt;
+use std::io;
+
+/// Версия протокола
+const VERSION: u8 = 1;
+
+/// Маркер начала полезной нагрузки
+const PAYLOAD_MARKER: u8 = 0xFF;
+
+/// Номер опции Observe
+pub const OPTION_OBSERVE: u16 = 6;
+/// Номер опции Uri-Path
+pub const OPTION_URI_PATH: u16 = 11;
+/// Номер опции Content-Format
+pub const OPTION_CONTENT_FORMAT: u16 = 12;
+
+/// Content-Format text/plain; charset=utf-8
+pub const FORMAT_TEXT_PLAIN: u16 = 0;
+
+/// Тип сообщения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoapType {
+    Confirmable,
+    NonConfirmable,
+    Acknowledgement,
+    Reset,
+}
+
+impl CoapType {
+    fn to_bits(self) -> u8 {
+        match self {
+            Self::Confirmable => 0,
+            Self::NonConfirmable => 1,
+            Self::Acknowledgement => 2,
+            Self::Reset => 3,
+        }
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0x03 {
+            0 => Self::Confirmable,
+            1 => Self::NonConfirmable,
+            2 => Self::Acknowledgement,
+            _ => Self::Reset,
+        }
+    }
+}
+
+/// Код запроса или ответа в формате `класс.детали`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoapCode(pub u8);
+
+impl CoapCode {
+    pub const EMPTY: Self = Self::new(0, 0);
+    pub const GET: Self = Self::new(0, 1);
+    pub const PUT: Self = Self::new(0, 3);
+    pub const CHANGED: Self = Self::new(2, 4);
+    pub const CONTENT: Self = Self::new(2, 5);
+    pub const BAD_REQUEST: Self = Self::new(4, 0);
+    pub const NOT_FOUND: Self = Self::new(4, 4);
+    pub const METHOD_NOT_ALLOWED: Self = Self::new(4, 5);
+
+    /// Создает код из класса (0-7) и деталей (0-31)
+    pub const fn new(class: u8, detail: u8) -> Self {
+        Self((class << 5) | (detail & 0x1F))
+    }
+
+    /// Класс кода: 0 - запрос, 2 - успех, 4/5 - ошибки
+    pub fn class(&self) -> u8 {
+        self.0 >> 5
+    }
+
+    /// Проверяет, что код означает успешный ответ
+    pub fn is_success(&self) -> bool {
+        self.class() == 2
+    }
+}
+
+impl fmt::Display for CoapCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}", self.class(), self.0 & 0x1F)
+    }
+}
+
+/// Сообщение CoAP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoapMessage {
+    pub msg_type: CoapType,
+    pub code: CoapCode,
+    pub message_id: u16,
+    pub token: Vec<u8>,
+    /// Опции в порядке добавления (при кодировании сортируются по номеру)
+    pub options: Vec<(u16, Vec<u8>)>,
+    pub payload: Vec<u8>,
+}
+
+impl CoapMessage {
+    /// Создает сообщение без опций и нагрузки
+    pub fn new(msg_type: CoapType, code: CoapCode, message_id: u16, token: &[u8]) -> Self {
+        Self {
+            msg_type,
+            code,
+            message_id,
+            token: token.to_vec(),
+            options: Vec::new(),
+            payload: Vec::new(),
+        }
+    }
+
+    /// Ответ на запрос: ACK с тем же message id для CON, иначе NON
+    pub fn response_to(request: &CoapMessage, code: CoapCode, message_id: u16) -> Self {
+        match request.msg_type {
+            CoapType::Confirmable => Self::new(
+                CoapType::Acknowledgement,
+                code,
+                request.message_id,
+                &request.token,
+            ),
+            _ => Self::new(CoapType::NonConfirmable, code, message_id, &request.token),
+        }
+    }
+
+    /// Builder: путь ресурса (каждый сегмент - отдельная опция Uri-Path)
+    pub fn with_path(mut self, path: &str) -> Self {
+        for segment in path.split('/').filter(|s| !s.is_empty()) {
+            self.options
+                .push((OPTION_URI_PATH, segment.as_bytes().to_vec()));
+        }
+        self
+    }
+
+    /// Builder: опция Observe (0 - подписаться, 1 - отписаться, иначе номер уведомления)
+    pub fn with_observe(mut self, sequence: u32) -> Self {
+        self.options.retain(|(number, _)| *number != OPTION_OBSERVE);
+        self.options
+            .push((OPTION_OBSERVE, encode_uint(sequence & 0x00FF_FFFF)));
+        self
+    }
+
+    /// Builder: текстовая нагрузка (с Content-Format text/plain)
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.options
+            .retain(|(number, _)| *number != OPTION_CONTENT_FORMAT);
+        self.options
+            .push((OPTION_CONTENT_FORMAT, encode_uint(FORMAT_TEXT_PLAIN as u32)));
+        self.payload = text.as_bytes().to_vec();
+        self
+    }
+
+    /// Путь ресурса, собранный из опций Uri-Path
+    pub fn path(&self) -> String {
+        self.options
+            .iter()
+            .filter(|(number, _)| *number == OPTION_URI_PATH)
+            .map(|(_, value)| String::from_utf8_lossy(value))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Значение опции Observe
+    pub fn observe(&self) -> Option<u32> {
+        self.options
+            .iter()
+            .find(|(number, _)| *number == OPTION_OBSERVE)
+            .map(|(_, value)| decode_uint(value))
+    }
+
+    /// Нагрузка в виде текста
+    pub fn text(&self) -> io::Result<&str> {
+        std::str::from_utf8(&self.payload).map_err(|e| invalid(&e.to_string()))
+    }
+
+    /// Кодирует сообщение в датаграмму
+    pub fn encode(&self) -> Vec<u8> {
+        let token_len = self.token.len().min(8);
+        let mut buf = Vec::with_capacity(4 + token_len + self.payload.len() + 16);
+
+        buf.push((VERSION << 6) | (self.msg_type.to_bits() << 4) | token_len as u8);
+        buf.push(self.code.0);
+        buf.extend_from_slice(&self.message_id.to_be_bytes());
+        buf.extend_from_slice(&self.token[..token_len]);
+
+        let mut options: Vec<&(u16, Vec<u8>)> = self.options.iter().collect();
+        options.sort_by_key(|(number, _)| *number); // стабильная сортировка
+
+        let mut previous = 0u16;
+        for (number, value) in options {
+            let (delta, delta_ext) = option_nibble(number - previous);
+            let (length, length_ext) = option_nibble(value.len() as u16);
+            buf.push((delta << 4) | length);
+            buf.extend_from_slice(&delta_ext);
+            buf.extend_from_slice(&length_ext);
+            buf.extend_from_slice(value);
+            previous = *number;
+        }
+
+        if !self.payload.is_empty() {
+            buf.push(PAYLOAD_MARKER);
+            buf.extend_from_slice(&self.payload);
+        }
+
+        buf
+    }
+
+    /// Декодирует датаграмму
+    pub fn decode(data: &[u8]) -> io::Result<Self> {
+        if data.len() < 4 {
+            return Err(invalid("Message shorter than header"));
+        }
+        if data[0] >> 6 != VERSION {
+            return Err(invalid("Unsupported CoAP version"));
+        }
+
+        let token_len = (data[0] & 0x0F) as usize;
+        if token_len > 8 {
+            return Err(invalid("Token length above 8"));
+        }
+        if data.len() < 4 + token_len {
+            return Err(invalid("Truncated token"));
+        }
+
+        let mut message = Self::new(
+            CoapType::from_bits(data[0] >> 4),
+            CoapCode(data[1]),
+            u16::from_be_bytes([data[2], data[3]]),
+            &data[4..4 + token_len],
+        );
+
+        let mut pos = 4 + token_len;
+        let mut number = 0u16;
+        while pos < data.len() {
+            let byte = data[pos];
+            pos += 1;
+
+            if byte == PAYLOAD_MARKER {
+                if pos == data.len() {
+                    return Err(invalid("Payload marker without payload"));
+                }
+                message.payload = data[pos..].to_vec();
+                break;
+            }
+
+            let delta = read_extended(byte >> 4, data, &mut pos)?;
+            let length = read_extended(byte & 0x0F, data, &mut pos)? as usize;
+            if pos + length > data.len() {
+                return Err(invalid("Truncated option value"));
+            }
+
+            number = number
+                .checked_add(delta)
+                .ok_or_else(|| invalid("Option number overflow"))?;
+            message
+                .options
+                .push((number, data[pos..pos + length].to_vec()));
+            pos += length;
+        }
+
+        Ok(message)
+    }
+}
+
+/// Кодирует беззнаковое целое минимальным числом байт
+fn encode_uint(value: u32) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count();
+    bytes[skip..].to_vec()
+}
+
+/// Декодирует беззнаковое целое (до 4 байт)
+fn decode_uint(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .take(4)
+        .fold(0u32, |acc, b| (acc << 8) | *b as u32)
+}
+
+/// Полубайт заголовка опции и расширенные байты для значения
+fn option_nibble(value: u16) -> (u8, Vec<u8>) {
+    match value {
+        0..=12 => (value as u8, Vec::new()),
+        13..=268 => (13, vec![(value - 13) as u8]),
+        _ => (14, (value - 269).to_be_bytes().to_vec()),
+    }
+}
+
+/// Читает значение полубайта опции с учетом расширенных байт
+fn read_extended(nibble: u8, data: &[u8], pos: &mut usize) -> io::Result<u16> {
+    match nibble {
+        0..=12 => Ok(nibble as u16),
+        13 => {
+            let byte = *data.get(*pos).ok_or_else(|| invalid("Truncated option"))?;
+            *pos += 1;
+            Ok(byte as u16 + 13)
+        }
+        14 => {
+            let bytes = data
+                .get(*pos..*pos + 2)
+                .ok_or_else(|| invalid("Truncated option"))?;
+            *pos += 2;
+            u16::from_be_bytes([bytes[0], bytes[1]])
+                .checked_add(269)
+                .ok_or_else(|| invalid("Option value overflow"))
+        }
+        _ => Err(invalid("Reserved option nibble 15")),
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_format() {
+        assert_eq!(CoapCode::GET.to_string(), "0.01");
+        assert_eq!(CoapCode::CONTENT.to_string(), "2.05");
+        assert_eq!(CoapCode::NOT_FOUND.to_string(), "4.04");
+        assert!(CoapCode::CHANGED.is_success());
+        assert!(!CoapCode::BAD_REQUEST.is_success());
+    }
+
+    #[test]
+    fn encode_get_request() {
+        let message = CoapMessage::new(CoapType::Confirmable, CoapCode::GET, 0x1234, &[0xAB])
+            .with_path("temperature");
+
+        let bytes = message.encode();
+        assert_eq!(&bytes[..5], &[0x41, 0x01, 0x12, 0x34, 0xAB]);
+        // Uri-Path (11), длина 11
+        assert_eq!(bytes[5], 0xBB);
+        assert_eq!(&bytes[6..], b"temperature");
+    }
+
+    #[test]
+    fn roundtrip_with_options_and_payload() {
+        let message = CoapMessage::new(CoapType::NonConfirmable, CoapCode::CONTENT, 7, b"tok")
+            .with_path("/sensors/kitchen/temperature")
+            .with_observe(300)
+            .with_text("22.50");
+
+        let decoded = CoapMessage::decode(&message.encode()).unwrap();
+        assert_eq!(decoded.msg_type, CoapType::NonConfirmable);
+        assert_eq!(decoded.code, CoapCode::CONTENT);
+        assert_eq!(decoded.token, b"tok");
+        assert_eq!(decoded.path(), "sensors/kitchen/temperature");
+        assert_eq!(decoded.observe(), Some(300));
+        assert_eq!(decoded.text().unwrap(), "22.50");
+    }
+
+    #[test]
+    fn long_option_values() {
+        let segment = "x".repeat(300);
+        let message =
+            CoapMessage::new(CoapType::Confirmable, CoapCode::GET, 1, &[]).with_path(&segment);
+
+        let decoded = CoapMessage::decode(&message.encode()).unwrap();
+        assert_eq!(decoded.path(), segment);
+    }
+
+    #[test]
+    fn observe_zero_is_empty_option() {
+        let message =
+            CoapMessage::new(CoapType::Confirmable, CoapCode::GET, 1, &[]).with_observe(0);
+        let decoded = CoapMessage::decode(&message.encode()).unwrap();
+        assert_eq!(decoded.observe(), Some(0));
+    }
+
+    #[test]
+    fn piggybacked_response() {
+        let request = CoapMessage::new(CoapType::Confirmable, CoapCode::GET, 42, &[1, 2]);
+        let response = CoapMessage::response_to(&request, CoapCode::CONTENT, 100);
+        assert_eq!(response.msg_type, CoapType::Acknowledgement);
+        assert_eq!(response.message_id, 42);
+        assert_eq!(response.token, vec![1, 2]);
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(CoapMessage::decode(&[0x40, 0x01]).is_err());
+        assert!(CoapMessage::decode(&[0x80, 0x01, 0, 0]).is_err()); // версия 2
+        assert!(CoapMessage::decode(&[0x49, 0x01, 0, 0]).is_err()); // token 9 байт
+        assert!(CoapMessage::decode(&[0x40, 0x01, 0, 0, 0xFF]).is_err());
+        assert!(CoapMessage::decode(&[0x40, 0x01, 0, 0, 0xB5, b'a']).is_err());
+    }
+}
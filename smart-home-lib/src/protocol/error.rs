@@ -0,0 +1,168 @@
+//! Ошибки приема сообщений протокола
+//!
+//! `ProtocolError` отделяет поврежденные данные (кадр, размер, UTF-8,
+//! JSON) от разрыва соединения, чтобы вызывающий мог решить: пропустить
+//! сообщение или переподключиться.
+
+use std::io;
+use std::str::Utf8Error;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Ошибка приема сообщения
+#[derive(Debug, Clone, Error)]
+pub enum ProtocolError {
+    /// Нарушена последовательность кадров сообщения
+    #[error("framing error: {0}")]
+    Framing(&'static str),
+
+    /// Сообщение больше допустимого размера
+    #[error("message too large: {size} > {limit} bytes")]
+    TooLarge { size: usize, limit: usize },
+
+    /// Сообщение не в UTF-8
+    #[error("message is not valid UTF-8: {0}")]
+    Utf8(#[from] Utf8Error),
+
+    /// Некорректный JSON; `field` - поле, на котором остановился разбор
+    #[error("invalid JSON at {line}:{column}: {message}")]
+    Json {
+        message: String,
+        field: Option<String>,
+        line: usize,
+        column: usize,
+    },
+
+    /// Соединение закрыто другой стороной
+    #[error("connection closed")]
+    Closed,
+
+    /// Прочие ошибки ввода-вывода
+    #[error(transparent)]
+    Io(Arc<io::Error>),
+}
+
+impl ProtocolError {
+    /// Соединение потеряно: нужно переподключиться
+    pub fn is_disconnect(&self) -> bool {
+        match self {
+            Self::Closed => true,
+            Self::Io(e) => matches!(
+                e.kind(),
+                io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::NotConnected
+            ),
+            _ => false,
+        }
+    }
+
+    /// Соединение в порядке, но пришли поврежденные данные
+    pub fn is_corrupt(&self) -> bool {
+        matches!(
+            self,
+            Self::Framing(_) | Self::TooLarge { .. } | Self::Utf8(_) | Self::Json { .. }
+        )
+    }
+
+    /// Вид ошибки для счетчиков здоровья
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Framing(_) => "framing",
+            Self::TooLarge { .. } => "too_large",
+            Self::Utf8(_) => "utf8",
+            Self::Json { .. } => "json",
+            Self::Closed => "closed",
+            Self::Io(_) => "io",
+        }
+    }
+}
+
+impl From<io::Error> for ProtocolError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof | io::ErrorKind::BrokenPipe => Self::Closed,
+            _ => Self::Io(Arc::new(e)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ProtocolError {
+    fn from(e: serde_json::Error) -> Self {
+        let message = e.to_string();
+        Self::Json {
+            field: json_field(&message),
+            line: e.line(),
+            column: e.column(),
+            message,
+        }
+    }
+}
+
+/// Для кода поверх `io::Result`: поврежденные данные - `InvalidData`,
+/// закрытое соединение - `UnexpectedEof`
+impl From<ProtocolError> for io::Error {
+    fn from(e: ProtocolError) -> Self {
+        match e {
+            ProtocolError::Closed => io::Error::new(io::ErrorKind::UnexpectedEof, e),
+            ProtocolError::Io(e) => {
+                Arc::try_unwrap(e).unwrap_or_else(|e| io::Error::new(e.kind(), e.to_string()))
+            }
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// Имя поля из сообщения serde (`missing field `x``, `unknown variant `y``)
+fn json_field(message: &str) -> Option<String> {
+    ["missing field `", "unknown field `", "duplicate field `"]
+        .iter()
+        .find_map(|prefix| {
+            let rest = &message[message.find(prefix)? + prefix.len()..];
+            Some(rest[..rest.find('`')?].to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Sample {
+        power: f64,
+    }
+
+    #[test]
+    fn json_field_info() {
+        let error: ProtocolError = serde_json::from_str::<Sample>("{}").unwrap_err().into();
+        match error {
+            ProtocolError::Json { field, line, .. } => {
+                assert_eq!(field.as_deref(), Some("power"));
+                assert_eq!(line, 1);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let error: ProtocolError = serde_json::from_str::<Sample>("{").unwrap_err().into();
+        assert!(matches!(error, ProtocolError::Json { field: None, .. }));
+        assert!(error.is_corrupt());
+    }
+
+    #[test]
+    fn io_conversions() {
+        let eof = io::Error::new(io::ErrorKind::UnexpectedEof, "eof");
+        let error = ProtocolError::from(eof);
+        assert!(matches!(error, ProtocolError::Closed));
+        assert!(error.is_disconnect());
+        assert_eq!(io::Error::from(error).kind(), io::ErrorKind::UnexpectedEof);
+
+        let reset = ProtocolError::from(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert!(reset.is_disconnect() && !reset.is_corrupt());
+        assert_eq!(io::Error::from(reset).kind(), io::ErrorKind::ConnectionReset);
+
+        let framing = io::Error::from(ProtocolError::Framing("Chunk sequence broken"));
+        assert_eq!(framing.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,51 @@
+//! UDP протокол датчика протечки
+//!
+//! Датчик отправляет JSON пакет при каждом изменении состояния и
+//! периодически подтверждает текущее состояние. Номер события растет с
+//! каждым пакетом: по нему получатель отбрасывает повторы и пакеты,
+//! пришедшие не по порядку.
+
+use serde::{Deserialize, Serialize};
+
+/// Событие датчика протечки
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeakEvent {
+    /// Датчик обнаружил воду
+    pub leak: bool,
+    /// Номер события датчика
+    #[serde(default)]
+    pub sequence: u64,
+    pub device_id: Option<String>,
+}
+
+impl LeakEvent {
+    /// Разбор пакета
+    pub fn parse(packet: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(packet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leak_event_serialization() {
+        let event = LeakEvent {
+            leak: true,
+            sequence: 3,
+            device_id: Some("bath_leak".to_string()),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            json,
+            r#"{"leak":true,"sequence":3,"device_id":"bath_leak"}"#
+        );
+        assert_eq!(LeakEvent::parse(json.as_bytes()).unwrap(), event);
+
+        // Номер события необязателен
+        let event = LeakEvent::parse(br#"{"leak":false,"device_id":null}"#).unwrap();
+        assert_eq!(event.sequence, 0);
+        assert!(LeakEvent::parse(b"water!").is_err());
+    }
+}
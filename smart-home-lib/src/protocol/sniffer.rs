@@ -0,0 +1,317 @@
+//! Анализатор трафика TCP протокола розеток
+//!
+//! `Sniffer` встает TCP прокси между контроллером и устройством: байты
+//! пересылаются без изменений, а их копия разбирается тем же приемом
+//! кадров, что у контроллера. Каждое сообщение публикуется как
+//! `SniffedFrame` с отметкой времени, направлением и номером соединения.
+//! Поврежденные кадры прием пропускает, поэтому в журнал попадают только
+//! целые сообщения (части собираются в одно).
+
+use super::error::ProtocolError;
+use super::socket_protocol::receive_message;
+use crate::clock::now_ms;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Емкость канала сообщений
+const FRAMES_CAPACITY: usize = 256;
+
+/// Буфер между пересылкой и разбором копии трафика
+const TAP_BUFFER: usize = 64 * 1024;
+
+/// Направление сообщения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// От контроллера к устройству (команды)
+    ToDevice,
+    /// От устройства к контроллеру (ответы)
+    ToController,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ToDevice => write!(f, "controller -> device"),
+            Self::ToController => write!(f, "device -> controller"),
+        }
+    }
+}
+
+/// Перехваченное сообщение
+#[derive(Debug, Clone)]
+pub struct SniffedFrame {
+    /// Время приема, мс Unix
+    pub at_ms: u64,
+    /// Номер соединения (по порядку подключения, с 1)
+    pub connection: u64,
+    pub direction: Direction,
+    /// Сообщение или ошибка его приема
+    pub message: Result<String, ProtocolError>,
+}
+
+impl fmt::Display for SniffedFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] #{} {}: ", self.at_ms, self.connection, self.direction)?;
+        match &self.message {
+            Ok(message) => write!(f, "{}", message),
+            Err(e) => write!(f, "<{}>", e),
+        }
+    }
+}
+
+/// TCP прокси с разбором сообщений
+pub struct Sniffer {
+    target: SocketAddr,
+    frames: broadcast::Sender<SniffedFrame>,
+    local_addr: Option<SocketAddr>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Sniffer {
+    /// Прокси к устройству `target`
+    pub fn new(target: SocketAddr) -> Self {
+        let (frames, _) = broadcast::channel(FRAMES_CAPACITY);
+        Self {
+            target,
+            frames,
+            local_addr: None,
+            handle: None,
+        }
+    }
+
+    /// Адрес устройства
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Адрес, к которому подключается контроллер (после `start`)
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Подписка на перехваченные сообщения
+    pub fn subscribe(&self) -> broadcast::Receiver<SniffedFrame> {
+        self.frames.subscribe()
+    }
+
+    /// Начинает принимать контроллеры на `listen_addr`
+    ///
+    /// Для каждого контроллера открывается отдельное соединение с
+    /// устройством. Повторный запуск работающего прокси ничего не делает.
+    pub async fn start(&mut self, listen_addr: &str) -> io::Result<SocketAddr> {
+        if let Some(addr) = self.local_addr.filter(|_| self.handle.is_some()) {
+            return Ok(addr);
+        }
+
+        let listener = TcpListener::bind(listen_addr).await?;
+        let addr = listener.local_addr()?;
+        let target = self.target;
+        let frames = self.frames.clone();
+
+        self.handle = Some(tokio::spawn(async move {
+            let mut connection = 0;
+            loop {
+                let Ok((client, _)) = listener.accept().await else {
+                    continue;
+                };
+                connection += 1;
+                let frames = frames.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = proxy(client, target, connection, frames).await {
+                        eprintln!("⚠️ Сниффер: соединение #{} с {}: {}", connection, target, e);
+                    }
+                });
+            }
+        }));
+        self.local_addr = Some(addr);
+        Ok(addr)
+    }
+
+    /// Останавливает прием новых соединений
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for Sniffer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Соединяет контроллер с устройством и пересылает трафик в обе стороны
+async fn proxy(
+    client: TcpStream,
+    target: SocketAddr,
+    connection: u64,
+    frames: broadcast::Sender<SniffedFrame>,
+) -> io::Result<()> {
+    let device = TcpStream::connect(target).await?;
+    let (client_read, client_write) = client.into_split();
+    let (device_read, device_write) = device.into_split();
+
+    let (to_device, to_controller) = tokio::join!(
+        relay(
+            client_read,
+            device_write,
+            connection,
+            Direction::ToDevice,
+            frames.clone()
+        ),
+        relay(
+            device_read,
+            client_write,
+            connection,
+            Direction::ToController,
+            frames
+        ),
+    );
+    to_device.and(to_controller).map(|_| ())
+}
+
+/// Пересылает байты из `from` в `to` и публикует разобранные сообщения
+///
+/// Возвращает количество пересланных байтов. Закрытие `from` закрывает
+/// запись в `to`.
+async fn relay<R, W>(
+    mut from: R,
+    mut to: W,
+    connection: u64,
+    direction: Direction,
+    frames: broadcast::Sender<SniffedFrame>,
+) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let (tap, mut tapped) = tokio::io::duplex(TAP_BUFFER);
+
+    let forward = async move {
+        // Разбор не должен останавливать пересылку: после его ошибки копия не пишется
+        let mut tap = Some(tap);
+        let mut buf = vec![0u8; 8192];
+        let mut total = 0;
+        loop {
+            let n = from.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            to.write_all(&buf[..n]).await?;
+            total += n as u64;
+            if let Some(writer) = tap.as_mut()
+                && writer.write_all(&buf[..n]).await.is_err()
+            {
+                tap = None;
+            }
+        }
+        to.shutdown().await?;
+        Ok(total)
+    };
+
+    let decode = async move {
+        loop {
+            let message = receive_message(&mut tapped).await;
+            if matches!(message, Err(ProtocolError::Closed)) {
+                break;
+            }
+            let fatal = matches!(message, Err(ProtocolError::Io(_)));
+            let _ = frames.send(SniffedFrame {
+                at_ms: now_ms(),
+                connection,
+                direction,
+                message,
+            });
+            if fatal {
+                break;
+            }
+        }
+    };
+
+    let (forwarded, ()) = tokio::join!(forward, decode);
+    forwarded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::socket_protocol::{
+        SocketCommand, SocketResponse, receive_command, send_command, send_response,
+    };
+    use tokio::io::duplex;
+
+    #[tokio::test]
+    async fn relay_forwards_and_decodes() {
+        let (mut controller, from) = duplex(1024);
+        let (to, mut device) = duplex(1024);
+        let (frames, mut rx) = broadcast::channel(16);
+
+        let relay = tokio::spawn(relay(from, to, 7, Direction::ToDevice, frames));
+        send_command(&mut controller, &SocketCommand::Power)
+            .await
+            .unwrap();
+        controller.write_all(b"noise").await.unwrap();
+        send_command(&mut controller, &SocketCommand::Ping)
+            .await
+            .unwrap();
+        drop(controller);
+
+        assert_eq!(
+            receive_command(&mut device).await.unwrap(),
+            SocketCommand::Power
+        );
+        assert_eq!(
+            receive_command(&mut device).await.unwrap(),
+            SocketCommand::Ping
+        );
+        assert!(relay.await.unwrap().unwrap() > 0);
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(first.connection, 7);
+        assert_eq!(first.direction, Direction::ToDevice);
+        assert!(first.message.unwrap().contains("power"));
+        assert!(rx.recv().await.unwrap().message.unwrap().contains("ping"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn sniffs_both_directions() {
+        let device = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = device.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = device.accept().await.unwrap();
+            while receive_command(&mut stream).await.is_ok() {
+                send_response(&mut stream, &SocketResponse::Pong)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let mut sniffer = Sniffer::new(target);
+        let mut frames = sniffer.subscribe();
+        let addr = sniffer.start("127.0.0.1:0").await.unwrap();
+
+        let mut controller = TcpStream::connect(addr).await.unwrap();
+        send_command(&mut controller, &SocketCommand::Ping)
+            .await
+            .unwrap();
+        let response = receive_message(&mut controller).await.unwrap();
+        assert!(response.contains("pong"));
+
+        let command = frames.recv().await.unwrap();
+        assert_eq!(command.direction, Direction::ToDevice);
+        let reply = frames.recv().await.unwrap();
+        assert_eq!(reply.direction, Direction::ToController);
+        assert_eq!(reply.connection, 1);
+        assert_eq!(reply.message.unwrap(), response);
+
+        sniffer.stop();
+    }
+}
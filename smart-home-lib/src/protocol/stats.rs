@@ -0,0 +1,126 @@
+//! Счетчики производительности протокола
+//!
+//! Глобальные счетчики времени сериализации и десериализации JSON и
+//! копирований данных кадров между буферами. По ним видно, во что
+//! обходится обмен с устройствами и помогает ли повторное использование
+//! буфера (`receive_message_into`). Счетчики общие для всего процесса,
+//! `reset_protocol_stats` обнуляет их перед замером.
+
+use super::error::ProtocolError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Result as IoResult;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static SERIALIZATIONS: AtomicU64 = AtomicU64::new(0);
+static SERIALIZE_NS: AtomicU64 = AtomicU64::new(0);
+static DESERIALIZATIONS: AtomicU64 = AtomicU64::new(0);
+static DESERIALIZE_NS: AtomicU64 = AtomicU64::new(0);
+static FRAME_COPIES: AtomicU64 = AtomicU64::new(0);
+static BYTES_COPIED: AtomicU64 = AtomicU64::new(0);
+
+/// Снимок счетчиков протокола
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProtocolStats {
+    /// Сериализованные сообщения
+    pub serializations: u64,
+    pub serialize_time: Duration,
+    /// Разобранные сообщения (включая неудачные попытки)
+    pub deserializations: u64,
+    pub deserialize_time: Duration,
+    /// Копирования данных кадров между буферами
+    pub frame_copies: u64,
+    /// Скопировано байт
+    pub bytes_copied: u64,
+}
+
+impl fmt::Display for ProtocolStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ser {} ({:.2?}), de {} ({:.2?}), copies {} ({} B)",
+            self.serializations,
+            self.serialize_time,
+            self.deserializations,
+            self.deserialize_time,
+            self.frame_copies,
+            self.bytes_copied
+        )
+    }
+}
+
+/// Текущие значения счетчиков
+pub fn protocol_stats() -> ProtocolStats {
+    ProtocolStats {
+        serializations: SERIALIZATIONS.load(Ordering::Relaxed),
+        serialize_time: Duration::from_nanos(SERIALIZE_NS.load(Ordering::Relaxed)),
+        deserializations: DESERIALIZATIONS.load(Ordering::Relaxed),
+        deserialize_time: Duration::from_nanos(DESERIALIZE_NS.load(Ordering::Relaxed)),
+        frame_copies: FRAME_COPIES.load(Ordering::Relaxed),
+        bytes_copied: BYTES_COPIED.load(Ordering::Relaxed),
+    }
+}
+
+/// Обнуляет счетчики
+pub fn reset_protocol_stats() {
+    for counter in [
+        &SERIALIZATIONS,
+        &SERIALIZE_NS,
+        &DESERIALIZATIONS,
+        &DESERIALIZE_NS,
+        &FRAME_COPIES,
+        &BYTES_COPIED,
+    ] {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Учитывает копирование `bytes` байт данных кадра
+pub(crate) fn record_copy(bytes: usize) {
+    FRAME_COPIES.fetch_add(1, Ordering::Relaxed);
+    BYTES_COPIED.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+/// JSON значения с учетом времени сериализации
+pub(crate) fn to_json<T: Serialize + ?Sized>(value: &T) -> IoResult<String> {
+    let started = Instant::now();
+    let json = serde_json::to_string(value);
+    SERIALIZE_NS.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    SERIALIZATIONS.fetch_add(1, Ordering::Relaxed);
+
+    json.map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Разбор JSON с учетом времени десериализации
+pub(crate) fn from_json<T: for<'de> Deserialize<'de>>(json: &[u8]) -> Result<T, ProtocolError> {
+    let started = Instant::now();
+    let value = serde_json::from_slice(json);
+    DESERIALIZE_NS.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    DESERIALIZATIONS.fetch_add(1, Ordering::Relaxed);
+
+    value.map_err(ProtocolError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Счетчики общие для параллельных тестов, поэтому проверяются приращения
+
+    #[test]
+    fn counts_json() {
+        let before = protocol_stats();
+        let json = to_json(&vec![1, 2, 3]).unwrap();
+        let parsed: Vec<u32> = from_json(json.as_bytes()).unwrap();
+        assert_eq!(parsed, vec![1, 2, 3]);
+        assert!(from_json::<Vec<u32>>(b"not json").is_err());
+        record_copy(10);
+
+        let after = protocol_stats();
+        assert!(after.serializations > before.serializations);
+        assert!(after.deserializations >= before.deserializations + 2);
+        assert!(after.bytes_copied >= before.bytes_copied + 10);
+        assert!(after.to_string().starts_with("ser "));
+    }
+}
@@ -1,10 +1,44 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
 
 /// Данные от термометра по UDP
-#[derive(Serialize, Deserialize)]
-pub struct ThermData {
+///
+/// `device_id` при разборе из буфера (`ThermData::parse`) ссылается на
+/// буфер пакета и не выделяет память, если в строке нет escape-символов.
+/// `into_owned` отвязывает данные от буфера.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThermData<'a> {
     pub temperature: f64,
-    pub device_id: Option<String>,
+    #[serde(borrow, default, deserialize_with = "borrow_str")]
+    pub device_id: Option<Cow<'a, str>>,
+}
+
+/// `Option<Cow<str>>` со ссылкой на буфер
+///
+/// serde заимствует только `Cow<str>` без обертки, `Option` всегда копирует строку.
+fn borrow_str<'de: 'a, 'a, D>(deserializer: D) -> Result<Option<Cow<'a, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Borrowed>::deserialize(deserializer)?.map(|borrowed| borrowed.0))
+}
+
+impl<'a> ThermData<'a> {
+    /// Разбор пакета прямо из буфера приема
+    pub fn parse(packet: &'a [u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(packet)
+    }
+
+    /// Данные, не зависящие от буфера пакета
+    pub fn into_owned(self) -> ThermData<'static> {
+        ThermData {
+            temperature: self.temperature,
+            device_id: self.device_id.map(|id| Cow::Owned(id.into_owned())),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -16,7 +50,7 @@ mod tests {
         // Тест полных данных
         let data = ThermData {
             temperature: 22.5,
-            device_id: Some("kitchen_001".to_string()),
+            device_id: Some("kitchen_001".into()),
         };
 
         let json = serde_json::to_string(&data).expect("Failed to serialize");
@@ -44,7 +78,7 @@ mod tests {
         let data: ThermData = serde_json::from_str(json).expect("Failed to deserialize");
 
         assert_eq!(data.temperature, 22.5);
-        assert_eq!(data.device_id, Some("kitchen_001".to_string()));
+        assert_eq!(data.device_id.as_deref(), Some("kitchen_001"));
     }
 
     #[test]
@@ -62,7 +96,7 @@ mod tests {
         // Тест полного цикла: сериализация -> десериализация
         let original = ThermData {
             temperature: 99.99,
-            device_id: Some("test_device_123".to_string()),
+            device_id: Some("test_device_123".into()),
         };
 
         let json = serde_json::to_string(&original).expect("Failed to serialize");
@@ -72,6 +106,22 @@ mod tests {
         assert_eq!(original.device_id, restored.device_id);
     }
 
+    #[test]
+    fn therm_data_borrows_packet() {
+        let packet = br#"{"temperature":21.0,"device_id":"hall"}"#.to_vec();
+        let data = ThermData::parse(&packet).expect("Failed to parse");
+        assert!(matches!(data.device_id, Some(Cow::Borrowed("hall"))));
+
+        // Escape-последовательности требуют копии строки
+        let escaped = br#"{"temperature":21.0,"device_id":"h\u0061ll"}"#;
+        let data = ThermData::parse(escaped).expect("Failed to parse");
+        assert!(matches!(data.device_id, Some(Cow::Owned(_))));
+
+        let owned = ThermData::parse(&packet).unwrap().into_owned();
+        drop(packet);
+        assert_eq!(owned.device_id.as_deref(), Some("hall"));
+    }
+
     #[test]
     fn invalid_json_handling() {
         // Тест обработки невалидного JSON
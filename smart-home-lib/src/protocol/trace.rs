@@ -0,0 +1,304 @@
+//! Контекст трассировки W3C (`traceparent`) для кадров протокола
+//!
+//! Контроллер передает контекст вместе с командой, эмулятор продолжает
+//! ту же трассу при обработке. С фичей `otel` спаны создаются через
+//! глобальный tracer OpenTelemetry, и приложение может выгружать их в
+//! Jaeger/Tempo любым экспортером.
+
+use std::fmt;
+
+/// Версия формата `traceparent`
+const TRACEPARENT_VERSION: &str = "00";
+
+/// Контекст трассировки: идентификаторы трассы и текущего спана
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceContext {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// Новая трасса со случайными идентификаторами
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: random_nonzero(),
+            span_id: random_nonzero(),
+            sampled: true,
+        }
+    }
+
+    /// Дочерний спан той же трассы
+    pub fn child(&self) -> Self {
+        Self {
+            span_id: random_nonzero(),
+            ..*self
+        }
+    }
+
+    /// Разбирает заголовок `traceparent` (`00-<trace>-<span>-<flags>`)
+    pub fn parse(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = decode_hex::<16>(parts.next()?)?;
+        let span_id = decode_hex::<8>(parts.next()?)?;
+        let flags = decode_hex::<1>(parts.next()?)?;
+
+        // Версия ff запрещена, у версии 00 нет дополнительных полей
+        if version.len() != 2
+            || version == "ff"
+            || (version == TRACEPARENT_VERSION && parts.next().is_some())
+        {
+            return None;
+        }
+        if trace_id == [0; 16] || span_id == [0; 8] {
+            return None;
+        }
+
+        Some(Self {
+            trace_id,
+            span_id,
+            sampled: flags[0] & 0x01 != 0,
+        })
+    }
+
+    /// Заголовок `traceparent`
+    pub fn traceparent(&self) -> String {
+        format!(
+            "{}-{}-{}-{:02x}",
+            TRACEPARENT_VERSION,
+            encode_hex(&self.trace_id),
+            encode_hex(&self.span_id),
+            self.sampled as u8
+        )
+    }
+
+    /// Идентификатор трассы в hex (для логов)
+    pub fn trace_id_hex(&self) -> String {
+        encode_hex(&self.trace_id)
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.traceparent())
+    }
+}
+
+/// Активный спан обработки команды; завершается при Drop
+pub struct CommandSpan {
+    context: TraceContext,
+    #[cfg(feature = "otel")]
+    span: otel::Span,
+}
+
+impl CommandSpan {
+    /// Спан отправки команды контроллером
+    ///
+    /// С фичей `otel` родителем становится текущий контекст OpenTelemetry,
+    /// иначе начинается новая трасса.
+    pub fn client(name: &str) -> Self {
+        #[cfg(feature = "otel")]
+        {
+            let (span, context) = otel::start_client(name);
+            Self { context, span }
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            let _ = name;
+            Self {
+                context: TraceContext::new_root(),
+            }
+        }
+    }
+
+    /// Спан обработки команды устройством с родителем из кадра
+    pub fn server(name: &str, parent: Option<TraceContext>) -> Self {
+        #[cfg(feature = "otel")]
+        {
+            let (span, context) = otel::start_server(name, parent);
+            Self { context, span }
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            let _ = name;
+            Self {
+                context: parent.map_or_else(TraceContext::new_root, |p| p.child()),
+            }
+        }
+    }
+
+    /// Контекст спана для передачи в кадре
+    pub fn context(&self) -> &TraceContext {
+        &self.context
+    }
+
+    /// Отмечает спан как завершившийся ошибкой
+    pub fn record_error(&mut self, message: &str) {
+        #[cfg(feature = "otel")]
+        otel::record_error(&mut self.span, message);
+        #[cfg(not(feature = "otel"))]
+        let _ = message;
+    }
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use super::TraceContext;
+    use opentelemetry::trace::{
+        Span as _, SpanContext, SpanId, SpanKind, Status, TraceContextExt, TraceFlags, TraceId,
+        TraceState, Tracer,
+    };
+    use opentelemetry::{Context, global};
+
+    pub(super) type Span = global::BoxedSpan;
+
+    const TRACER_NAME: &str = "smart-home-lib";
+
+    pub(super) fn start_client(name: &str) -> (Span, TraceContext) {
+        start(name, SpanKind::Client, &Context::current())
+    }
+
+    pub(super) fn start_server(name: &str, parent: Option<TraceContext>) -> (Span, TraceContext) {
+        let parent_cx = match parent {
+            Some(parent) => {
+                let flags = if parent.sampled {
+                    TraceFlags::SAMPLED
+                } else {
+                    TraceFlags::default()
+                };
+                let remote = SpanContext::new(
+                    TraceId::from_bytes(parent.trace_id),
+                    SpanId::from_bytes(parent.span_id),
+                    flags,
+                    true,
+                    TraceState::default(),
+                );
+                Context::new().with_remote_span_context(remote)
+            }
+            None => Context::new(),
+        };
+
+        start(name, SpanKind::Server, &parent_cx)
+    }
+
+    pub(super) fn record_error(span: &mut Span, message: &str) {
+        span.set_status(Status::error(message.to_string()));
+    }
+
+    fn start(name: &str, kind: SpanKind, parent_cx: &Context) -> (Span, TraceContext) {
+        let tracer = global::tracer(TRACER_NAME);
+        let span = tracer
+            .span_builder(name.to_string())
+            .with_kind(kind)
+            .start_with_context(&tracer, parent_cx);
+
+        // Без установленного SDK спан не записывается и не получает своих
+        // идентификаторов - тогда генерируем их сами
+        let span_context = span.span_context();
+        let context = if span.is_recording() && span_context.is_valid() {
+            TraceContext {
+                trace_id: span_context.trace_id().to_bytes(),
+                span_id: span_context.span_id().to_bytes(),
+                sampled: span_context.is_sampled(),
+            }
+        } else {
+            let parent = parent_cx.span().span_context().clone();
+            if parent.is_valid() {
+                TraceContext {
+                    trace_id: parent.trace_id().to_bytes(),
+                    span_id: parent.span_id().to_bytes(),
+                    sampled: parent.is_sampled(),
+                }
+                .child()
+            } else {
+                TraceContext::new_root()
+            }
+        };
+
+        (span, context)
+    }
+}
+
+fn random_nonzero<const N: usize>() -> [u8; N] {
+    loop {
+        let bytes: [u8; N] = std::array::from_fn(|_| rand::random());
+        if bytes != [0; N] {
+            return bytes;
+        }
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+    if text.len() != N * 2 || !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    if text.bytes().any(|b| b.is_ascii_uppercase()) {
+        return None; // W3C требует нижний регистр
+    }
+
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn parse_and_format() {
+        let context = TraceContext::parse(SAMPLE).unwrap();
+        assert!(context.sampled);
+        assert_eq!(context.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.traceparent(), SAMPLE);
+        assert_eq!(context.to_string(), SAMPLE);
+    }
+
+    #[test]
+    fn reject_invalid() {
+        assert!(TraceContext::parse("").is_none());
+        assert!(
+            TraceContext::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(TraceContext::parse(&format!("{}-extra", SAMPLE)).is_none());
+    }
+
+    #[test]
+    fn child_keeps_trace() {
+        let root = TraceContext::new_root();
+        let child = root.child();
+        assert_eq!(child.trace_id, root.trace_id);
+        assert_ne!(child.span_id, root.span_id);
+        assert_eq!(TraceContext::parse(&child.traceparent()), Some(child));
+    }
+
+    #[test]
+    fn server_span_continues_client_trace() {
+        let client = CommandSpan::client("socket.command");
+        let server = CommandSpan::server("socket.handle", Some(*client.context()));
+        assert_eq!(server.context().trace_id, client.context().trace_id);
+        assert_ne!(server.context().span_id, client.context().span_id);
+    }
+}
@@ -0,0 +1,217 @@
+//! Сетевой транспорт контроллеров
+//!
+//! Контроллер розетки не открывает TCP соединения сам, а получает их от
+//! `Transport`. По умолчанию это `TokioTransport` (`tokio::net::TcpStream`);
+//! интегратор может подключить другой ввод-вывод (например, tokio-uring
+//! через адаптер `AsyncRead`/`AsyncWrite`), а тесты - `InMemory`,
+//! который соединяет контроллер с эмулятором в том же процессе без сети.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+/// Размер буфера соединения `InMemory` в каждую сторону
+const MEMORY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Соединение с устройством: двунаправленный поток байт
+pub trait Connection: AsyncRead + AsyncWrite + Unpin + Send + Sync {
+    /// Соединение пригодно для следующей команды
+    ///
+    /// Проверка не ждет: между командами устройство ничего не присылает,
+    /// поэтому закрытое соединение или лишние данные означают, что
+    /// соединение нужно открыть заново. Транспорт, который не умеет это
+    /// проверить, считает соединение живым - обрыв обнаружит сама команда.
+    fn is_alive(&self) -> bool {
+        true
+    }
+}
+
+/// Future, возвращаемый `Transport::connect`
+pub type ConnectFuture<'a> =
+    Pin<Box<dyn Future<Output = io::Result<Box<dyn Connection>>> + Send + 'a>>;
+
+/// Способ подключения к устройству
+pub trait Transport: Send + Sync + fmt::Debug {
+    /// Открывает соединение с `addr`
+    fn connect(&self, addr: SocketAddr) -> ConnectFuture<'_>;
+}
+
+/// Разделяемый транспорт
+pub type SharedTransport = Arc<dyn Transport>;
+
+/// TCP соединения tokio
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioTransport;
+
+impl Transport for TokioTransport {
+    fn connect(&self, addr: SocketAddr) -> ConnectFuture<'_> {
+        Box::pin(async move {
+            let stream = TcpStream::connect(addr).await?;
+            Ok(Box::new(stream) as Box<dyn Connection>)
+        })
+    }
+}
+
+impl Connection for TcpStream {
+    fn is_alive(&self) -> bool {
+        // Живо, только если чтение еще не готово: конец потока или данные
+        // без запроса означают закрытие или рассинхронизацию протокола
+        let mut buf = [0u8; 1];
+        let mut buf = ReadBuf::new(&mut buf);
+        let mut cx = Context::from_waker(Waker::noop());
+        matches!(self.poll_peek(&mut cx, &mut buf), Poll::Pending)
+    }
+}
+
+/// Транспорт по умолчанию
+pub fn tokio_transport() -> SharedTransport {
+    Arc::new(TokioTransport)
+}
+
+/// Канал подключений к слушателю: серверная половина и адрес клиента
+type IncomingSender = mpsc::UnboundedSender<(DuplexStream, SocketAddr)>;
+
+/// Соединения внутри процесса: контроллер и эмулятор без сети
+///
+/// Сервер занимает адрес через `listen` (порт 0 - свободный условный порт),
+/// подключения к адресу без слушателя отклоняются с `ConnectionRefused`.
+/// Клиенту назначается условный адрес, его видит сервер в `accept`.
+/// Клоны транспорта работают с одним набором адресов.
+#[derive(Debug, Clone, Default)]
+pub struct InMemory {
+    listeners: Arc<Mutex<HashMap<SocketAddr, IncomingSender>>>,
+    /// Счетчик условных портов
+    next_port: Arc<AtomicU16>,
+}
+
+impl InMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Клиентская и серверная половины одного соединения
+    pub fn pair() -> (DuplexStream, DuplexStream) {
+        tokio::io::duplex(MEMORY_BUFFER_SIZE)
+    }
+
+    /// Занимает адрес `addr`; прежний слушатель адреса отключается
+    pub fn listen(&self, mut addr: SocketAddr) -> InMemoryListener {
+        if addr.port() == 0 {
+            addr.set_port(self.ephemeral_port());
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        if let Ok(mut listeners) = self.listeners.lock() {
+            listeners.insert(addr, tx);
+        }
+        InMemoryListener { addr, incoming: rx }
+    }
+
+    /// Условный порт из динамического диапазона (49152-65535)
+    fn ephemeral_port(&self) -> u16 {
+        49152 + self.next_port.fetch_add(1, Ordering::Relaxed) % 16384
+    }
+}
+
+impl Transport for InMemory {
+    fn connect(&self, addr: SocketAddr) -> ConnectFuture<'_> {
+        Box::pin(async move {
+            let listener = self
+                .listeners
+                .lock()
+                .ok()
+                .and_then(|listeners| listeners.get(&addr).cloned());
+
+            let (client, server) = Self::pair();
+            let peer = SocketAddr::new(addr.ip(), self.ephemeral_port());
+            match listener {
+                Some(listener) if listener.send((server, peer)).is_ok() => {
+                    Ok(Box::new(client) as Box<dyn Connection>)
+                }
+                _ => Err(io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    format!("Nothing listens on {}", addr),
+                )),
+            }
+        })
+    }
+}
+
+impl Connection for DuplexStream {}
+
+/// Слушатель адреса `InMemory`
+#[derive(Debug)]
+pub struct InMemoryListener {
+    addr: SocketAddr,
+    incoming: mpsc::UnboundedReceiver<(DuplexStream, SocketAddr)>,
+}
+
+impl InMemoryListener {
+    /// Ждет следующее подключение: серверная половина и адрес клиента
+    ///
+    /// Ошибка `ConnectionAborted` - адрес занял другой слушатель.
+    pub async fn accept(&mut self) -> io::Result<(DuplexStream, SocketAddr)> {
+        self.incoming.recv().await.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                format!("Address {} taken by another listener", self.addr),
+            )
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::socket_protocol::{receive_message, send_message};
+
+    #[tokio::test]
+    async fn memory_connection() {
+        let transport = InMemory::new();
+        let addr: SocketAddr = "10.0.0.5:7000".parse().unwrap();
+
+        let error = transport.connect(addr).await.err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+
+        let mut listener = transport.listen(addr);
+        let mut client = transport.connect(addr).await.unwrap();
+        let (mut server, peer) = listener.accept().await.unwrap();
+        assert_eq!(peer.ip(), addr.ip());
+        assert!(peer.port() >= 49152);
+        assert!(client.is_alive());
+
+        send_message(&mut client, "ping").await.unwrap();
+        assert_eq!(receive_message(&mut server).await.unwrap(), "ping");
+
+        // Новый слушатель того же адреса отключает прежний
+        let _replacement = transport.listen(addr);
+        assert_eq!(
+            listener.accept().await.unwrap_err().kind(),
+            io::ErrorKind::ConnectionAborted
+        );
+    }
+
+    #[tokio::test]
+    async fn ephemeral_listen_port() {
+        let transport = InMemory::new();
+        let first = transport.listen("127.0.0.1:0".parse().unwrap());
+        let second = transport.listen("127.0.0.1:0".parse().unwrap());
+        assert_ne!(first.local_addr().port(), 0);
+        assert_ne!(first.local_addr(), second.local_addr());
+
+        let (mut client, mut server) = InMemory::pair();
+        send_message(&mut client, "direct").await.unwrap();
+        assert_eq!(receive_message(&mut server).await.unwrap(), "direct");
+    }
+}
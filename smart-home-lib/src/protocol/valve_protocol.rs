@@ -0,0 +1,92 @@
+//! Async протокол TCP для запорного клапана
+//!
+//! Клапан понимает команды `open`, `close` и `status` и на каждую отвечает
+//! своим состоянием. Обмен идет запросами `socket_protocol::request_json`.
+
+use super::socket_protocol::{receive_json, request_json, send_json};
+use serde::{Deserialize, Serialize};
+use std::io::Result as IoResult;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Команды клапана
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "command")]
+pub enum ValveCommand {
+    #[serde(rename = "open")]
+    Open,
+    #[serde(rename = "close")]
+    Close,
+    #[serde(rename = "status")]
+    Status,
+}
+
+/// Ответы клапана
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "result")]
+pub enum ValveResponse {
+    #[serde(rename = "ok")]
+    Ok(ValveData),
+    #[serde(rename = "error")]
+    Error { message: String },
+}
+
+/// Данные от клапана
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValveData {
+    /// Клапан открыт (вода подается)
+    pub open: bool,
+    pub device_id: Option<String>,
+}
+
+/// Async отправка команды и получение ответа
+pub async fn send_valve_command_and_receive<S>(
+    stream: &mut S,
+    command: &ValveCommand,
+    max_size: usize,
+) -> IoResult<ValveResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    request_json(stream, command, max_size).await
+}
+
+/// Async получение команды клапана
+pub async fn receive_valve_command<R>(reader: &mut R, max_size: usize) -> IoResult<ValveCommand>
+where
+    R: AsyncRead + Unpin,
+{
+    receive_json(reader, max_size).await
+}
+
+/// Async отправка ответа клапана
+pub async fn send_valve_response<W>(writer: &mut W, response: &ValveResponse) -> IoResult<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    send_json(writer, response).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valve_messages_serialization() {
+        let json = serde_json::to_string(&ValveCommand::Close).unwrap();
+        assert_eq!(json, r#"{"command":"close"}"#);
+
+        let response = ValveResponse::Ok(ValveData {
+            open: false,
+            device_id: Some("main_valve".to_string()),
+        });
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(
+            json,
+            r#"{"result":"ok","open":false,"device_id":"main_valve"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ValveResponse>(&json).unwrap(),
+            response
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Сверка желаемого и фактического состояния устройств
+//!
+//! `Reconciler` хранит желаемые состояния розеток (из конфигурации или
+//! заданные программно), опрашивает контроллеры и при расхождении
+//! отправляет корректирующую команду. Каждое расхождение и результат
+//! исправления публикуются в шину событий.
+
+use crate::controllers::{
+    ControllerError, DeviceCommand, DeviceController, ErrorContext, SocketError,
+};
+use crate::events::{EventBus, HouseEvent};
+use crate::house::SmartHouse;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{MissedTickBehavior, interval};
+
+/// Желаемое состояние розетки
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DesiredState {
+    On,
+    Off,
+}
+
+impl DesiredState {
+    /// Состояние по флагу включения
+    pub fn from_active(active: bool) -> Self {
+        if active { Self::On } else { Self::Off }
+    }
+
+    /// Розетка должна быть включена
+    pub fn is_on(self) -> bool {
+        self == Self::On
+    }
+}
+
+impl fmt::Display for DesiredState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::On => write!(f, "включена"),
+            Self::Off => write!(f, "выключена"),
+        }
+    }
+}
+
+/// Обнаруженное расхождение
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    pub room: String,
+    pub key: String,
+    pub desired: DesiredState,
+    pub actual: DesiredState,
+    /// Корректирующая команда выполнена успешно
+    pub corrected: bool,
+}
+
+/// Результат одного прохода сверки
+#[derive(Debug, Default)]
+pub struct ReconcileReport {
+    /// Сколько устройств опрошено
+    pub checked: usize,
+    /// Найденные расхождения
+    pub drifts: Vec<Drift>,
+    /// Устройства, для которых задано состояние, но нет контроллера розетки
+    pub missing: Vec<(String, String)>,
+    /// Ошибки опроса и корректирующих команд
+    pub errors: Vec<ControllerError>,
+}
+
+impl ReconcileReport {
+    /// Фактическое состояние совпало с желаемым или было исправлено
+    pub fn is_converged(&self) -> bool {
+        self.errors.is_empty() && self.drifts.iter().all(|drift| drift.corrected)
+    }
+}
+
+/// Контроллер контроллеров: приводит дом к желаемому состоянию
+#[derive(Debug, Clone, Default)]
+pub struct Reconciler {
+    desired: BTreeMap<(String, String), DesiredState>,
+    events: EventBus,
+}
+
+impl Reconciler {
+    /// Создает сверку без желаемых состояний
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: желаемое состояние устройства
+    pub fn with_desired(mut self, room: &str, key: &str, state: DesiredState) -> Self {
+        self.set_desired(room, key, state);
+        self
+    }
+
+    /// Builder: шина, в которую публикуются события
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Задает желаемое состояние устройства
+    pub fn set_desired(&mut self, room: &str, key: &str, state: DesiredState) {
+        self.desired
+            .insert((room.to_string(), key.to_string()), state);
+    }
+
+    /// Перестает следить за устройством
+    pub fn remove_desired(&mut self, room: &str, key: &str) -> Option<DesiredState> {
+        self.desired.remove(&(room.to_string(), key.to_string()))
+    }
+
+    /// Желаемое состояние устройства
+    pub fn desired(&self, room: &str, key: &str) -> Option<DesiredState> {
+        self.desired
+            .get(&(room.to_string(), key.to_string()))
+            .copied()
+    }
+
+    /// Количество отслеживаемых устройств
+    pub fn desired_count(&self) -> usize {
+        self.desired.len()
+    }
+
+    /// Шина событий сверки
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Один проход: опрос устройств и исправление расхождений
+    pub async fn reconcile(&self, house: &mut SmartHouse) -> ReconcileReport {
+        let mut report = ReconcileReport::default();
+
+        for ((room, key), &desired) in &self.desired {
+            let Ok(DeviceController::Socket(controller)) = house.controller_mut(room, key) else {
+                report.missing.push((room.clone(), key.clone()));
+                continue;
+            };
+
+            let address = controller.address().clone();
+            let context = |command| ErrorContext::new(room, key, address, command);
+
+            // Опрос синхронизирует локальное состояние с розеткой
+            let actual = match controller.power().await {
+                Ok(_) | Err(SocketError::Tripped) => match controller.device() {
+                    Ok(socket) => DesiredState::from_active(socket.is_active()),
+                    Err(e) => {
+                        let context = context(DeviceCommand::ReadPower);
+                        report.errors.push(ControllerError::new(context, e));
+                        continue;
+                    }
+                },
+                Err(e) => {
+                    let context = context(DeviceCommand::ReadPower);
+                    report.errors.push(ControllerError::new(context, e));
+                    continue;
+                }
+            };
+            report.checked += 1;
+
+            if actual == desired {
+                continue;
+            }
+
+            self.events.publish(HouseEvent::DriftDetected {
+                room: room.clone(),
+                key: key.clone(),
+                desired: desired.to_string(),
+                actual: actual.to_string(),
+            });
+
+            let (command, result) = if desired.is_on() {
+                (DeviceCommand::TurnOn, controller.turn_on().await)
+            } else {
+                (DeviceCommand::TurnOff, controller.turn_off().await)
+            };
+
+            let corrected = match result {
+                Ok(()) => {
+                    self.events.publish(HouseEvent::DriftCorrected {
+                        room: room.clone(),
+                        key: key.clone(),
+                        state: desired.to_string(),
+                    });
+                    true
+                }
+                Err(e) => {
+                    let context = context(command);
+                    self.events.publish(HouseEvent::CorrectionFailed {
+                        context: context.clone(),
+                        error: e.to_string(),
+                    });
+                    report.errors.push(ControllerError::new(context, e));
+                    false
+                }
+            };
+
+            report.drifts.push(Drift {
+                room: room.clone(),
+                key: key.clone(),
+                desired,
+                actual,
+                corrected,
+            });
+        }
+
+        report
+    }
+
+    /// Периодическая сверка общего дома; работает, пока задачу не отменят
+    pub async fn run(&self, house: Arc<Mutex<SmartHouse>>, period: Duration) {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            let report = self.reconcile(&mut *house.lock().await).await;
+            for e in &report.errors {
+                eprintln!("⚠️ Сверка {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::SocketController;
+    use crate::emulators::{Fleet, FleetSpec};
+    use crate::room::Room;
+
+    #[test]
+    fn desired_states() {
+        let mut reconciler = Reconciler::new()
+            .with_desired("hall", "lamp", DesiredState::On)
+            .with_desired("hall", "lamp", DesiredState::Off);
+
+        assert_eq!(reconciler.desired_count(), 1);
+        assert_eq!(reconciler.desired("hall", "lamp"), Some(DesiredState::Off));
+        assert_eq!(
+            reconciler.remove_desired("hall", "lamp"),
+            Some(DesiredState::Off)
+        );
+        assert_eq!(reconciler.desired("hall", "lamp"), None);
+
+        let state: DesiredState = serde_json::from_str(r#""on""#).unwrap();
+        assert_eq!(state, DesiredState::On);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_devices() {
+        let mut house = SmartHouse::default();
+        house.add_room("hall", Room::new());
+
+        let reconciler = Reconciler::new().with_desired("hall", "lamp", DesiredState::On);
+        let report = reconciler.reconcile(&mut house).await;
+
+        assert_eq!(report.checked, 0);
+        assert_eq!(
+            report.missing,
+            vec![("hall".to_string(), "lamp".to_string())]
+        );
+        assert!(report.is_converged());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn corrects_drift() {
+        let spec = FleetSpec::new()
+            .socket("hall", "lamp", 100.0)
+            .with_controller_timeout(Duration::from_secs(1));
+        let mut fleet = Fleet::start(spec).await.unwrap();
+        let addr = fleet.socket_addr("hall", "lamp").unwrap();
+
+        let reconciler = Reconciler::new().with_desired("hall", "lamp", DesiredState::Off);
+        let mut events = reconciler.events().subscribe();
+
+        // Розетку включили в обход дома
+        let mut outsider = SocketController::new(addr, 100.0, Duration::from_secs(1));
+        outsider.turn_on().await.unwrap();
+
+        let report = reconciler.reconcile(fleet.house_mut()).await;
+        assert_eq!(report.checked, 1);
+        assert_eq!(report.drifts.len(), 1);
+        assert_eq!(report.drifts[0].actual, DesiredState::On);
+        assert!(report.is_converged());
+
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            HouseEvent::DriftDetected { .. }
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            HouseEvent::DriftCorrected { .. }
+        ));
+
+        // Повторный проход расхождений не находит
+        let report = reconciler.reconcile(fleet.house_mut()).await;
+        assert!(report.drifts.is_empty());
+        outsider.power().await.unwrap();
+        assert!(!outsider.device().unwrap().is_active());
+
+        fleet.shutdown().await;
+    }
+}
@@ -0,0 +1,216 @@
+//! Структурированные отчеты о доме
+//!
+//! `HouseReport` содержит те же сведения, что и текстовый отчет, но в виде
+//! данных: панели мониторинга получают их из `SmartHouse::watch_reports`
+//! и не разбирают вывод `format!("{}", house)`. `HealthReport` собирает
+//! состояние связи всех контроллеров дома. `ReportOptions` задает единицы
+//! и точность значений в текстовых отчетах (`Reporter::report_with`).
+
+use crate::controllers::HealthStatus;
+use crate::room::RoomSummary;
+use crate::traits::Reporter;
+use crate::units::{Celsius, Watts};
+use serde::Serialize;
+use std::fmt;
+
+/// Единица температуры в отчетах
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+/// Языковые настройки чисел в отчетах
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum Locale {
+    /// Десятичная точка: `21.5`
+    #[default]
+    En,
+    /// Десятичная запятая: `21,5`
+    Ru,
+}
+
+/// Настройки текстовых отчетов
+///
+/// Значения по умолчанию дают тот же вывод, что и `Reporter::report`.
+/// Числовые поля структурированных отчетов (`RoomSummary`) всегда хранятся
+/// в °C и Вт, настройки влияют только на строки.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ReportOptions {
+    pub temperature_unit: TemperatureUnit,
+    /// Количество знаков после запятой для мощности
+    pub power_unit_precision: usize,
+    pub locale: Locale,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            temperature_unit: TemperatureUnit::Celsius,
+            power_unit_precision: 1,
+            locale: Locale::En,
+        }
+    }
+}
+
+impl ReportOptions {
+    /// Builder: единица температуры
+    pub fn with_temperature_unit(mut self, unit: TemperatureUnit) -> Self {
+        self.temperature_unit = unit;
+        self
+    }
+
+    /// Builder: знаков после запятой для мощности
+    pub fn with_power_precision(mut self, precision: usize) -> Self {
+        self.power_unit_precision = precision;
+        self
+    }
+
+    /// Builder: языковые настройки чисел
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Температура в выбранной единице
+    pub fn temperature(&self, temperature: Celsius) -> String {
+        let text = match self.temperature_unit {
+            TemperatureUnit::Celsius => format!("{:.1}°C", temperature.value()),
+            TemperatureUnit::Fahrenheit => format!("{:.1}°F", temperature.fahrenheit()),
+        };
+        self.localize(text)
+    }
+
+    /// Мощность с выбранной точностью
+    pub fn power(&self, power: Watts) -> String {
+        self.localize(format!("{:.*}W", self.power_unit_precision, power.value()))
+    }
+
+    fn localize(&self, text: String) -> String {
+        match self.locale {
+            Locale::En => text,
+            Locale::Ru => text.replace('.', ","),
+        }
+    }
+}
+
+/// Состояние устройства или контроллера в отчете
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemReport {
+    pub key: String,
+    /// Тип устройства (`socket`, `therm`)
+    pub kind: &'static str,
+    /// Элемент - сетевой контроллер, а не локальное устройство
+    pub controller: bool,
+    /// Строка отчета устройства
+    pub state: String,
+}
+
+impl fmt::Display for ItemReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = if self.controller {
+            "Controller"
+        } else {
+            "Device"
+        };
+        write!(f, "[{}:{}] {}", label, self.key, self.state)
+    }
+}
+
+/// Отчет о комнате
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoomReport {
+    pub room: String,
+    pub items: Vec<ItemReport>,
+    pub summary: RoomSummary,
+}
+
+/// Отчет о доме на момент `timestamp_ms`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HouseReport {
+    /// Время формирования отчета, мс с Unix epoch
+    pub timestamp_ms: u64,
+    pub rooms: Vec<RoomReport>,
+    /// Настройки, с которыми сформированы строки отчета
+    pub options: ReportOptions,
+}
+
+impl HouseReport {
+    /// Отчеты описывают одинаковое состояние (время формирования не учитывается)
+    pub fn same_state(&self, other: &HouseReport) -> bool {
+        self.rooms == other.rooms
+    }
+
+    /// Отчет о комнате
+    pub fn room(&self, room: &str) -> Option<&RoomReport> {
+        self.rooms.iter().find(|report| report.room == room)
+    }
+}
+
+impl fmt::Display for HouseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for room in &self.rooms {
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+
+            write!(f, "Room: {}", room.room)?;
+            for item in &room.items {
+                write!(f, "\n  {}", item)?;
+            }
+            write!(f, "\n  {}", room.summary.report_with(&self.options))?;
+        }
+        Ok(())
+    }
+}
+
+/// Состояние связи контроллера в отчете о здоровье
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceHealth {
+    pub room: String,
+    pub key: String,
+    /// Тип устройства (`socket`, `therm`)
+    pub kind: &'static str,
+    pub status: HealthStatus,
+}
+
+/// Отчет о здоровье контроллеров дома; неисправные идут первыми
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    /// Время формирования отчета, мс с Unix epoch
+    pub timestamp_ms: u64,
+    pub devices: Vec<DeviceHealth>,
+}
+
+impl HealthReport {
+    /// Все контроллеры исправны
+    pub fn is_healthy(&self) -> bool {
+        self.devices.iter().all(|device| device.status.is_healthy())
+    }
+
+    /// Неисправные контроллеры
+    pub fn unhealthy(&self) -> impl Iterator<Item = &DeviceHealth> {
+        self.devices
+            .iter()
+            .filter(|device| !device.status.is_healthy())
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self
+            .devices
+            .iter()
+            .map(|device| {
+                format!(
+                    "[{}/{}] {}: {}",
+                    device.room, device.key, device.kind, device.status
+                )
+            })
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
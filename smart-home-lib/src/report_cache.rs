@@ -0,0 +1,273 @@
+//! Кэш текстового отчета дома
+//!
+//! `ReportCache` хранит строки отчета по комнатам и при следующем вызове
+//! формирует заново только измененные комнаты. Изменения берутся из шины
+//! событий дома: `room_mut`, `device_mut` и `controller_mut` публикуют
+//! `HouseEvent::RoomChanged`, добавление и удаление комнат, подключение
+//! устройств (`adopt`), переносы и транзакции - свои события.
+//! Показания контроллеров меняются в фоне без событий, поэтому комнаты с
+//! контроллерами формируются заново, если кэш старше `max_age`.
+
+use crate::clock::{SharedClock, system_clock};
+use crate::events::HouseEvent;
+use crate::house::{SmartHouse, room_report_lines};
+use crate::report::ReportOptions;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+/// Допустимый возраст отчета комнаты с контроллерами по умолчанию
+pub const DEFAULT_LIVE_MAX_AGE: Duration = Duration::from_secs(1);
+
+/// Счетчики кэша
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReportCacheStats {
+    /// Комнаты, взятые из кэша
+    pub hits: u64,
+    /// Комнаты, сформированные заново
+    pub renders: u64,
+}
+
+/// Отчет комнаты в кэше
+struct CachedRoom {
+    lines: Vec<String>,
+    rendered_at_ms: u64,
+    live: bool,
+}
+
+/// Кэш отчета с пересборкой только измененных комнат
+pub struct ReportCache {
+    events: broadcast::Receiver<HouseEvent>,
+    rooms: HashMap<String, CachedRoom>,
+    dirty: HashSet<String>,
+    options: ReportOptions,
+    max_age: Duration,
+    clock: SharedClock,
+    stats: ReportCacheStats,
+}
+
+impl ReportCache {
+    /// Кэш отчета `house`, подписанный на его шину событий
+    ///
+    /// Кэш должен использоваться с тем же домом: события другой шины
+    /// он не увидит.
+    pub fn new(house: &SmartHouse) -> Self {
+        Self {
+            events: house.events().subscribe(),
+            rooms: HashMap::new(),
+            dirty: HashSet::new(),
+            options: ReportOptions::default(),
+            max_age: DEFAULT_LIVE_MAX_AGE,
+            clock: system_clock(),
+            stats: ReportCacheStats::default(),
+        }
+    }
+
+    /// Builder: единицы и точность значений в отчете
+    pub fn with_options(mut self, options: ReportOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Builder: допустимый возраст отчета комнаты с контроллерами
+    ///
+    /// `Duration::ZERO` - такие комнаты формируются при каждом вызове.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Builder: источник времени для возраста отчетов
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Строки отчета, как у `SmartHouse::report_lines_with`
+    pub fn report_lines(&mut self, house: &SmartHouse) -> Vec<String> {
+        self.drain_events();
+        let now = self.clock.now_ms();
+        let max_age = self.max_age.as_millis() as u64;
+
+        self.rooms.retain(|key, _| house.room(key).is_some());
+
+        let mut report = Vec::new();
+        for key in house.rooms_keys() {
+            let Some(room) = house.room(&key) else {
+                continue;
+            };
+            let fresh = self.rooms.get(&key).is_some_and(|cached| {
+                let expired = cached.live && now.saturating_sub(cached.rendered_at_ms) >= max_age;
+                !expired && !self.dirty.contains(&key)
+            });
+            if fresh {
+                self.stats.hits += 1;
+            } else {
+                self.stats.renders += 1;
+                self.dirty.remove(&key);
+                self.rooms.insert(
+                    key.clone(),
+                    CachedRoom {
+                        lines: room_report_lines(&key, room, &self.options),
+                        rendered_at_ms: now,
+                        live: room.controllers_count() > 0,
+                    },
+                );
+            }
+            if let Some(cached) = self.rooms.get(&key) {
+                report.extend(cached.lines.iter().cloned());
+            }
+        }
+        report
+    }
+
+    /// Отчет одной строкой, как `Reporter::report_with`
+    pub fn report(&mut self, house: &SmartHouse) -> String {
+        self.report_lines(house).join("\n")
+    }
+
+    /// Помечает комнату измененной (для изменений в обход `SmartHouse`)
+    pub fn invalidate(&mut self, room: &str) {
+        self.dirty.insert(room.to_string());
+    }
+
+    /// Сбрасывает весь кэш
+    pub fn invalidate_all(&mut self) {
+        self.rooms.clear();
+        self.dirty.clear();
+    }
+
+    /// Счетчики попаданий и пересборок
+    pub fn stats(&self) -> ReportCacheStats {
+        self.stats
+    }
+
+    /// Помечает комнаты из накопившихся событий
+    fn drain_events(&mut self) {
+        loop {
+            match self.events.try_recv() {
+                Ok(event) => {
+                    for room in event.rooms() {
+                        self.dirty.insert(room.to_string());
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                // Пропущенные события могли касаться любой комнаты
+                Err(TryRecvError::Lagged(_)) => self.invalidate_all(),
+                Err(TryRecvError::Closed) => {
+                    self.invalidate_all();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::Device;
+    use crate::events::EventBus;
+    use crate::room::Room;
+    use crate::traits::Reporter;
+
+    fn stats(hits: u64, renders: u64) -> ReportCacheStats {
+        ReportCacheStats { hits, renders }
+    }
+
+    fn house() -> SmartHouse {
+        SmartHouse::builder()
+            .room("kitchen", |r| {
+                r.socket("kettle", 2000.0).therm("temp", 21.0)
+            })
+            .room("hall", |r| r.socket("lamp", 60.0))
+            .build()
+    }
+
+    #[test]
+    fn renders_only_changed_rooms() {
+        let mut house = house();
+        let mut cache = ReportCache::new(&house);
+
+        assert_eq!(cache.report(&house), house.report());
+        assert_eq!(cache.stats().renders, 2);
+
+        cache.report(&house);
+        assert_eq!(cache.stats(), stats(2, 2));
+
+        if let Ok(Device::Socket(kettle)) = house.device_mut("kitchen", "kettle") {
+            kettle.turn_on();
+        }
+        assert_eq!(cache.report(&house), house.report());
+        assert_eq!(cache.stats(), stats(3, 3));
+
+        house.rename_room("hall", "corridor").unwrap();
+        assert_eq!(cache.report(&house), house.report());
+        assert_eq!(cache.stats().renders, 4);
+
+        // Замена комнаты по тому же ключу
+        house.add_room("corridor", Room::builder().socket("heater", 1500.0).build());
+        assert_eq!(cache.report(&house), house.report());
+        assert_eq!(cache.stats().renders, 5);
+
+        house.remove_room("corridor");
+        house.add_room("corridor", Room::new());
+        assert_eq!(cache.report(&house), house.report());
+        assert_eq!(cache.stats().renders, 6);
+    }
+
+    #[test]
+    fn lagged_events_reset_cache() {
+        let mut house = house().with_events(EventBus::new(1));
+        let mut cache = ReportCache::new(&house);
+        cache.report(&house);
+
+        house.room_mut("hall");
+        house.room_mut("hall");
+        cache.report(&house);
+        assert_eq!(cache.stats().renders, 4);
+    }
+
+    #[test]
+    #[cfg(feature = "net")]
+    fn adopt_renders_room() {
+        use crate::discovery::DiscoveredDevice;
+
+        let mut house = house();
+        let mut cache = ReportCache::new(&house);
+        cache.report(&house);
+
+        let heater = DiscoveredDevice::socket("127.0.0.1:1".parse().unwrap(), 1000.0);
+        house.adopt(heater, "hall", "heater").unwrap();
+        let report = cache.report(&house);
+        assert_eq!(report, house.report());
+        assert!(report.contains("heater"));
+        assert_eq!(cache.stats(), stats(1, 3));
+    }
+
+    #[test]
+    #[cfg(feature = "net")]
+    fn live_rooms_expire() {
+        use crate::clock::MockClock;
+        use crate::controllers::{DeviceController, SocketController};
+        use std::sync::Arc;
+
+        let clock = Arc::new(MockClock::new());
+        let mut house = house();
+        let addr: std::net::SocketAddr = "127.0.0.1:1".parse().unwrap();
+        house.room_mut("hall").unwrap().add_controller(
+            "heater",
+            DeviceController::Socket(SocketController::new(addr, 1000.0, Duration::from_secs(1))),
+        );
+        let mut cache = ReportCache::new(&house).with_clock(clock.clone());
+
+        cache.report(&house);
+        clock.advance(Duration::from_millis(500));
+        cache.report(&house);
+        assert_eq!(cache.stats(), stats(2, 2));
+
+        clock.advance(Duration::from_millis(500));
+        cache.report(&house);
+        assert_eq!(cache.stats(), stats(3, 3));
+    }
+}
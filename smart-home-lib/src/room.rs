@@ -2,27 +2,152 @@
 
 use crate::controllers::DeviceController;
 use crate::devices::Device;
-use crate::traits::Reporter;
+use crate::metadata::DeviceMetadata;
+use crate::report::{ItemReport, ReportOptions};
+use crate::room_kind::RoomKind;
+use crate::traits::{AsyncReporter, Reporter};
+use crate::units::{Celsius, Watts};
+use indexmap::IndexMap;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fmt;
+use std::time::Duration;
 
 /// Макрос для упрощения создания комнаты с устройствами
+///
+/// Принимает пары `(ключ, устройство)` или описание в виде
+/// `ключ => элемент`, где элемент - выражение (`Device`, `DeviceController`)
+/// или одна из сокращенных форм:
+///
+/// - `socket(мощность)`, `therm(температура)` - локальные устройства;
+/// - `socket_controller(адрес, мощность, таймаут)` - TCP контроллер розетки;
+/// - `therm_controller(температура, адрес, max_age)` - UDP контроллер
+///   термометра (прием показаний запускает `start`).
+///
+/// Интервалы записываются литералами `250ms`, `3s`, `5m`, `1h` или
+/// выражением `Duration`. Метаданные задаются атрибутами перед ключом:
+/// `#[priority = 90, tag = "кухня"] "чайник" => socket(2000.0)`.
 #[macro_export]
 macro_rules! room {
     ($(($key:expr, $device:expr)),* $(,)?) => {{
-        let mut room = Room::default();
+        #[allow(unused_mut)]
+        let mut room = $crate::room::Room::default();
         $(
             room.add_item($key, $device);
         )*
         room
     }};
+
+    (@items $room:ident;) => {};
+    (@items $room:ident; $(#[$($meta:tt)*])* $key:literal => socket($power:expr) $(, $($rest:tt)*)?) => {
+        $crate::room!(@add $room; [$([$($meta)*])*] $key,
+            $crate::devices::Device::Socket($crate::devices::SmartSocket::new($power)));
+        $crate::room!(@items $room; $($($rest)*)?);
+    };
+    (@items $room:ident; $(#[$($meta:tt)*])* $key:literal => therm($temp:expr) $(, $($rest:tt)*)?) => {
+        $crate::room!(@add $room; [$([$($meta)*])*] $key,
+            $crate::devices::Device::Therm($crate::devices::SmartTherm::new($temp)));
+        $crate::room!(@items $room; $($($rest)*)?);
+    };
+    (@items $room:ident; $(#[$($meta:tt)*])* $key:literal
+        => socket_controller($addr:expr, $power:expr, $($timeout:tt)+) $(, $($rest:tt)*)?) => {
+        $crate::room!(@add $room; [$([$($meta)*])*] $key,
+            $crate::controllers::DeviceController::Socket($crate::controllers::SocketController::new(
+                $crate::room::resolve_addr($addr),
+                $power,
+                $crate::room!(@duration $($timeout)+),
+            )));
+        $crate::room!(@items $room; $($($rest)*)?);
+    };
+    (@items $room:ident; $(#[$($meta:tt)*])* $key:literal
+        => therm_controller($temp:expr, $addr:expr, $($max_age:tt)+) $(, $($rest:tt)*)?) => {
+        $crate::room!(@add $room; [$([$($meta)*])*] $key,
+            $crate::controllers::DeviceController::Therm($crate::controllers::ThermController::new(
+                $temp,
+                $addr,
+                $crate::room!(@duration $($max_age)+),
+            )));
+        $crate::room!(@items $room; $($($rest)*)?);
+    };
+    (@items $room:ident; $(#[$($meta:tt)*])* $key:literal => $item:expr $(, $($rest:tt)*)?) => {
+        $crate::room!(@add $room; [$([$($meta)*])*] $key, $item);
+        $crate::room!(@items $room; $($($rest)*)?);
+    };
+
+    (@add $room:ident; [$([$($meta:tt)*])*] $key:expr, $item:expr) => {
+        $room.add_item($key, $item);
+        $(
+            let metadata = $crate::room!(@meta $room.metadata($key); $($meta)*);
+            $room.set_metadata($key, metadata);
+        )*
+    };
+
+    (@meta $metadata:expr;) => { $metadata };
+    (@meta $metadata:expr; priority = $priority:expr $(, $($rest:tt)*)?) => {
+        $crate::room!(@meta $metadata.with_priority($priority); $($($rest)*)?)
+    };
+    (@meta $metadata:expr; tag = $tag:expr $(, $($rest:tt)*)?) => {
+        $crate::room!(@meta $metadata.with_tag($tag); $($($rest)*)?)
+    };
+
+    (@duration $duration:literal) => {
+        const { $crate::room::duration_literal(stringify!($duration)) }
+    };
+    (@duration $duration:expr) => { $duration };
+
+    ($($items:tt)+) => {{
+        let mut room = $crate::room::Room::default();
+        $crate::room!(@items room; $($items)+);
+        room
+    }};
+}
+
+/// Интервал из литерала `room!` (`250ms`, `3s`, `5m`, `1h`)
+#[doc(hidden)]
+pub const fn duration_literal(literal: &str) -> std::time::Duration {
+    let bytes = literal.as_bytes();
+    let mut value = 0u64;
+    let mut i = 0;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        value = value * 10 + (bytes[i] - b'0') as u64;
+        i += 1;
+    }
+    if i == 0 {
+        panic!("Интервал должен начинаться с числа");
+    }
+
+    let suffix = bytes.len() - i;
+    if suffix == 2 && bytes[i] == b'm' && bytes[i + 1] == b's' {
+        std::time::Duration::from_millis(value)
+    } else if suffix == 1 && bytes[i] == b's' {
+        std::time::Duration::from_secs(value)
+    } else if suffix == 1 && bytes[i] == b'm' {
+        std::time::Duration::from_secs(value * 60)
+    } else if suffix == 1 && bytes[i] == b'h' {
+        std::time::Duration::from_secs(value * 3600)
+    } else {
+        panic!("Единица интервала должна быть ms, s, m или h")
+    }
+}
+
+/// Адрес контроллера из `room!` (строка `"host:port"` или `SocketAddr`)
+#[doc(hidden)]
+pub fn resolve_addr(addr: impl std::net::ToSocketAddrs + fmt::Debug) -> std::net::SocketAddr {
+    match addr.to_socket_addrs().map(|mut addrs| addrs.next()) {
+        Ok(Some(resolved)) => resolved,
+        _ => panic!("Некорректный адрес контроллера: {:?}", addr),
+    }
 }
 
 /// Комната умного дома, содержащая список устройств
+///
+/// Устройства и контроллеры хранятся в порядке добавления.
 #[derive(Default)]
 pub struct Room {
-    devices: HashMap<String, Device>,
-    controllers: HashMap<String, DeviceController>,
+    devices: IndexMap<String, Device>,
+    controllers: IndexMap<String, DeviceController>,
+    metadata: IndexMap<String, DeviceMetadata>,
+    kind: Option<RoomKind>,
 }
 
 impl Room {
@@ -31,6 +156,16 @@ impl Room {
         Self::default()
     }
 
+    /// Тип помещения (None - не задан)
+    pub fn kind(&self) -> Option<RoomKind> {
+        self.kind
+    }
+
+    /// Задает тип помещения
+    pub fn set_kind(&mut self, kind: Option<RoomKind>) {
+        self.kind = kind;
+    }
+
     /// Возвращает неизменяемую ссылку на устройство по ключу
     pub fn device(&self, key: &str) -> Option<&Device> {
         self.devices.get(key)
@@ -48,7 +183,8 @@ impl Room {
 
     /// Удаляет устройство из комнаты
     pub fn remove_device(&mut self, key: &str) -> Option<Device> {
-        self.devices.remove(key)
+        self.metadata.shift_remove(key);
+        self.devices.shift_remove(key)
     }
 
     /// Возвращает неизменяемую ссылку на контроллер по ключу
@@ -68,7 +204,75 @@ impl Room {
 
     /// Удаляет контроллер из комнаты
     pub fn remove_controller(&mut self, key: &str) -> Option<DeviceController> {
-        self.controllers.remove(key)
+        self.metadata.shift_remove(key);
+        self.controllers.shift_remove(key)
+    }
+
+    /// Переименовывает устройство или контроллер вместе с его метаданными
+    ///
+    /// Позиция элемента в комнате сохраняется. Ссылки на элемент в группах
+    /// дома обновляет `SmartHouse::rename_item`.
+    pub fn rename_item(&mut self, old: &str, new: &str) -> Result<(), RenameError> {
+        if !self.contains_key(old) {
+            return Err(RenameError::NotFound(old.to_string()));
+        }
+        if old == new {
+            return Ok(());
+        }
+        if self.contains_key(new) {
+            return Err(RenameError::KeyExists(new.to_string()));
+        }
+
+        rename_key(&mut self.devices, old, new);
+        rename_key(&mut self.controllers, old, new);
+        rename_key(&mut self.metadata, old, new);
+        Ok(())
+    }
+
+    /// Извлекает элемент вместе с метаданными (для переноса в другую комнату)
+    pub(crate) fn take_item(&mut self, key: &str) -> Option<(RoomItem, Option<DeviceMetadata>)> {
+        let item = match self.devices.shift_remove(key) {
+            Some(device) => RoomItem::Device(device),
+            None => RoomItem::Controller(self.controllers.shift_remove(key)?),
+        };
+        Some((item, self.metadata.shift_remove(key)))
+    }
+
+    /// Добавляет элемент, извлеченный `take_item`
+    ///
+    /// Элемент с тем же ключом заменяется вместе с метаданными; элемент того
+    /// же вида сохраняет позицию.
+    pub(crate) fn put_item(&mut self, key: &str, item: RoomItem, metadata: Option<DeviceMetadata>) {
+        match &item {
+            RoomItem::Device(_) => {
+                self.controllers.shift_remove(key);
+            }
+            RoomItem::Controller(_) => {
+                self.devices.shift_remove(key);
+            }
+        }
+        self.add_item(key, item);
+        match metadata {
+            Some(metadata) => self.set_metadata(key, metadata),
+            None => {
+                self.metadata.shift_remove(key);
+            }
+        }
+    }
+
+    /// Ключ занят устройством или контроллером
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.devices.contains_key(key) || self.controllers.contains_key(key)
+    }
+
+    /// Возвращает метаданные устройства или контроллера (по умолчанию, если не заданы)
+    pub fn metadata(&self, key: &str) -> DeviceMetadata {
+        self.metadata.get(key).cloned().unwrap_or_default()
+    }
+
+    /// Задает метаданные устройства или контроллера
+    pub fn set_metadata(&mut self, key: &str, metadata: DeviceMetadata) {
+        self.metadata.insert(key.to_string(), metadata);
     }
 
     /// Универсальный метод для добавления любого элемента в комнату
@@ -83,18 +287,131 @@ impl Room {
     }
 
     /// Формирует текстовый отчет о состоянии всех устройств и контроллеров в комнате
+    ///
+    /// Последняя строка - сводка по комнате (см. `summary`)
     pub fn report_lines(&self) -> Vec<String> {
-        let mut lines = Vec::new();
+        self.report_lines_with(&ReportOptions::default())
+    }
+
+    /// Как `report_lines`, но с заданными единицами и точностью
+    pub fn report_lines_with(&self, options: &ReportOptions) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .item_reports_with(options)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        lines.push(self.summary().report_with(options));
+        lines
+    }
+
+    /// Как `report_lines`, но контроллеры предварительно опрашиваются
+    ///
+    /// Каждый контроллер опрашивается не дольше `timeout`.
+    pub async fn live_report_lines(&mut self, timeout: Duration) -> Vec<String> {
+        let mut states = HashMap::new();
+        for (key, controller) in self.controllers.iter_mut() {
+            states.insert(key.clone(), controller.live_report(timeout).await);
+        }
+
+        let mut lines: Vec<String> = self
+            .item_reports()
+            .into_iter()
+            .map(|mut item| {
+                if let Some(state) = item.controller.then(|| states.remove(&item.key)).flatten() {
+                    item.state = state;
+                }
+                item.to_string()
+            })
+            .collect();
+        lines.push(self.summary().to_string());
+        lines
+    }
+
+    /// Состояние устройств и контроллеров комнаты в виде данных
+    pub fn item_reports(&self) -> Vec<ItemReport> {
+        self.item_reports_with(&ReportOptions::default())
+    }
+
+    /// Как `item_reports`, но строки состояния формируются с `options`
+    pub fn item_reports_with(&self, options: &ReportOptions) -> Vec<ItemReport> {
+        let devices = self.devices.iter().map(|(key, device)| ItemReport {
+            key: key.clone(),
+            kind: device.kind(),
+            controller: false,
+            state: device.report_with(options),
+        });
+        let controllers = self.controllers.iter().map(|(key, controller)| ItemReport {
+            key: key.clone(),
+            kind: controller.kind(),
+            controller: true,
+            state: controller.report_with(options),
+        });
+        devices.chain(controllers).collect()
+    }
+
+    /// Сводка по устройствам и контроллерам комнаты
+    ///
+    /// Для контроллеров используются последние известные показания.
+    pub fn summary(&self) -> RoomSummary {
+        let mut power = Vec::new();
+        let temperatures: Vec<Celsius> = self
+            .temperatures()
+            .into_iter()
+            .map(|(_, temperature)| temperature)
+            .collect();
+
+        for device in self.devices.values() {
+            if let Device::Socket(socket) = device {
+                power.push(socket.current_power());
+            }
+        }
 
+        #[cfg(feature = "net")]
+        for controller in self.controllers.values() {
+            if let DeviceController::Socket(socket) = controller
+                && let Ok(socket) = socket.device()
+            {
+                power.push(socket.current_power());
+            }
+        }
+
+        let min_temperature = temperatures
+            .iter()
+            .copied()
+            .min_by(|a, b| a.value().total_cmp(&b.value()));
+        let avg_temperature = (!temperatures.is_empty()).then(|| {
+            let sum: f64 = temperatures.iter().map(Celsius::value).sum();
+            Celsius::new(sum / temperatures.len() as f64)
+        });
+
+        RoomSummary {
+            items: self.items_count(),
+            sockets: power.len(),
+            therms: temperatures.len(),
+            total_power: power
+                .into_iter()
+                .fold(Watts::new(0.0), |sum, power| sum + power),
+            min_temperature,
+            avg_temperature,
+        }
+    }
+
+    /// Температуры термометров и контроллеров термометров по ключам
+    pub(crate) fn temperatures(&self) -> Vec<(String, Celsius)> {
+        let mut temperatures = Vec::new();
         for (key, device) in &self.devices {
-            lines.push(format!("[Device:{}] {}", key, device));
+            if let Device::Therm(therm) = device {
+                temperatures.push((key.clone(), therm.temperature()));
+            }
         }
 
+        #[cfg(feature = "net")]
         for (key, controller) in &self.controllers {
-            lines.push(format!("[Controller:{}] {}", key, controller));
+            if let DeviceController::Therm(therm) = controller {
+                temperatures.push((key.clone(), therm.device().temperature()));
+            }
         }
-
-        lines
+        temperatures
     }
 
     /// Возвращает количество устройств в комнате
@@ -131,7 +448,54 @@ impl Room {
     }
 }
 
+/// Сводка по комнате
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoomSummary {
+    /// Количество устройств и контроллеров
+    pub items: usize,
+    /// Количество розеток
+    pub sockets: usize,
+    /// Количество термометров
+    pub therms: usize,
+    /// Суммарная мощность розеток
+    pub total_power: Watts,
+    /// Минимальная температура (None - термометров нет)
+    pub min_temperature: Option<Celsius>,
+    /// Средняя температура (None - термометров нет)
+    pub avg_temperature: Option<Celsius>,
+}
+
+impl Reporter for RoomSummary {
+    fn report(&self) -> String {
+        self.report_with(&ReportOptions::default())
+    }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        let mut report = format!(
+            "Summary: {} devices, Power: {}",
+            self.items,
+            options.power(self.total_power)
+        );
+        if let (Some(min), Some(avg)) = (self.min_temperature, self.avg_temperature) {
+            report.push_str(&format!(
+                ", Temperature: min {} / avg {}",
+                options.temperature(min),
+                options.temperature(avg)
+            ));
+        }
+        report
+    }
+}
+
+impl fmt::Display for RoomSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
 /// Универсальный элемент комнаты
+// Соотношение размеров вариантов зависит от набора фич устройств
+#[allow(clippy::large_enum_variant)]
 pub enum RoomItem {
     Device(Device),
     Controller(DeviceController),
@@ -149,10 +513,50 @@ impl From<DeviceController> for RoomItem {
     }
 }
 
+/// Ошибка переименования элемента комнаты
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// Элемента с таким ключом нет
+    NotFound(String),
+    /// Новый ключ уже занят
+    KeyExists(String),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(key) => write!(f, "Элемент '{}' не найден", key),
+            Self::KeyExists(key) => write!(f, "Ключ '{}' уже занят", key),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// Меняет ключ записи, сохраняя ее позицию; `false`, если записи нет
+pub(crate) fn rename_key<V>(map: &mut IndexMap<String, V>, old: &str, new: &str) -> bool {
+    let Some((index, _, value)) = map.shift_remove_full(old) else {
+        return false;
+    };
+    let (last, _) = map.insert_full(new.to_string(), value);
+    map.move_index(last, index);
+    true
+}
+
 impl Reporter for Room {
     fn report(&self) -> String {
         self.report_lines().join("\n")
     }
+
+    fn report_with(&self, options: &ReportOptions) -> String {
+        self.report_lines_with(options).join("\n")
+    }
+}
+
+impl AsyncReporter for Room {
+    async fn live_report(&mut self, timeout: Duration) -> String {
+        self.live_report_lines(timeout).await.join("\n")
+    }
 }
 
 impl fmt::Display for Room {
@@ -164,6 +568,7 @@ impl fmt::Display for Room {
 #[cfg(test)]
 mod tests {
     use crate::devices::{Device, SmartSocket, SmartTherm};
+    use std::time::Duration;
 
     use super::*;
 
@@ -213,6 +618,40 @@ mod tests {
         assert!(room.device("bedroom_therm").is_none());
     }
 
+    #[test]
+    fn metadata() {
+        let mut room = test_room();
+        assert_eq!(room.metadata("living_socket"), DeviceMetadata::default());
+
+        room.set_metadata("living_socket", DeviceMetadata::new().with_priority(10));
+        assert_eq!(room.metadata("living_socket").priority, 10);
+
+        room.remove_device("living_socket");
+        assert_eq!(room.metadata("living_socket"), DeviceMetadata::default());
+    }
+
+    #[test]
+    fn rename_item() {
+        let mut room = test_room();
+        room.add_device("lamp", Device::Socket(SmartSocket::new(60.0)));
+        room.set_metadata("living_socket", DeviceMetadata::new().with_priority(10));
+
+        room.rename_item("living_socket", "tv").unwrap();
+        assert_eq!(room.devices_keys(), vec!["kitchen_therm", "tv", "lamp"]);
+        assert_eq!(room.metadata("tv").priority, 10);
+        assert_eq!(room.metadata("living_socket"), DeviceMetadata::default());
+
+        assert_eq!(
+            room.rename_item("tv", "lamp"),
+            Err(RenameError::KeyExists("lamp".to_string()))
+        );
+        assert!(room.device("tv").is_some());
+        assert_eq!(
+            room.rename_item("missing", "other"),
+            Err(RenameError::NotFound("missing".to_string()))
+        );
+    }
+
     #[test]
     fn report_lines() {
         let mut room = test_room();
@@ -222,7 +661,7 @@ mod tests {
         }
 
         let report_lines = room.report_lines();
-        assert_eq!(report_lines.len(), 2);
+        assert_eq!(report_lines.len(), 3);
 
         // Проверяем что отчет содержит информацию об устройствах
         let contains_socket = report_lines
@@ -252,8 +691,8 @@ mod tests {
         assert!(report.contains("kitchen_therm"));
         assert!(report.contains("22.5°C"));
 
-        // Проверяем, что в отчете есть две строки (один перенос строки)
-        assert_eq!(report.matches("\n").count(), 1);
+        // Проверяем, что в отчете две строки устройств и сводка (два переноса строки)
+        assert_eq!(report.matches("\n").count(), 2);
     }
 
     #[test]
@@ -268,6 +707,46 @@ mod tests {
         assert!(display_output.contains("1500.0W"));
     }
 
+    #[test]
+    fn summary() {
+        let mut room = test_room();
+        room.add_device("bedroom_therm", Device::Therm(SmartTherm::new(19.5)));
+        if let Some(Device::Socket(s)) = room.device_mut("living_socket") {
+            s.turn_on();
+        }
+
+        let summary = room.summary();
+        assert_eq!(summary.items, 3);
+        assert_eq!(summary.sockets, 1);
+        assert_eq!(summary.therms, 2);
+        assert_eq!(summary.total_power, Watts::new(1500.0));
+        assert_eq!(summary.min_temperature, Some(Celsius::new(19.5)));
+        assert_eq!(summary.avg_temperature, Some(Celsius::new(21.0)));
+
+        assert_eq!(
+            room.report_lines().last().unwrap(),
+            "Summary: 3 devices, Power: 1500.0W, Temperature: min 19.5°C / avg 21.0°C"
+        );
+
+        let empty = Room::new().summary();
+        assert_eq!(empty.min_temperature, None);
+        assert_eq!(empty.to_string(), "Summary: 0 devices, Power: 0.0W");
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut room = Room::new();
+        for key in ["zeta", "alpha", "mid"] {
+            room.add_device(key, Device::Socket(SmartSocket::new(100.0)));
+        }
+        room.remove_device("alpha");
+        room.add_device("alpha", Device::Therm(SmartTherm::new(20.0)));
+
+        assert_eq!(room.devices_keys(), vec!["zeta", "mid", "alpha"]);
+        assert!(room.report_lines()[0].starts_with("[Device:zeta]"));
+        assert!(room.report_lines()[2].starts_with("[Device:alpha]"));
+    }
+
     #[test]
     fn devices_count() {
         let room = test_room();
@@ -285,4 +764,42 @@ mod tests {
         assert!(room.device("socket1").is_some());
         assert!(room.device("therm1").is_some());
     }
+
+    #[test]
+    #[cfg(feature = "net")]
+    fn inline_macro_syntax() {
+        let lamp = Device::Socket(SmartSocket::new(40.0));
+        let room = crate::room! {
+            #[priority = 90]
+            "fridge" => socket(150.0),
+            #[tag = "kitchen", priority = 10]
+            #[tag = "heavy"]
+            "kettle" => socket_controller("127.0.0.1:3001", 2000.0, 3s),
+            "therm" => therm(22.5),
+            "sensor" => therm_controller(20.0, "127.0.0.1:0", Duration::from_secs(5)),
+            "lamp" => lamp,
+        };
+
+        assert_eq!(
+            room.keys(),
+            vec!["fridge", "therm", "lamp", "kettle", "sensor"]
+        );
+        assert_eq!(room.metadata("fridge").priority, 90);
+        let kettle = room.metadata("kettle");
+        assert_eq!(kettle.priority, 10);
+        assert!(kettle.has_tag("kitchen") && kettle.has_tag("heavy"));
+        assert_eq!(room.metadata("therm"), DeviceMetadata::default());
+        assert!(matches!(
+            room.controller("kettle"),
+            Some(DeviceController::Socket(_))
+        ));
+    }
+
+    #[test]
+    fn duration_literals() {
+        assert_eq!(duration_literal("250ms"), Duration::from_millis(250));
+        assert_eq!(duration_literal("3s"), Duration::from_secs(3));
+        assert_eq!(duration_literal("5m"), Duration::from_secs(300));
+        assert_eq!(duration_literal("1h"), Duration::from_secs(3600));
+    }
 }
@@ -0,0 +1,239 @@
+//! Типы помещений и климатические нормы по умолчанию
+//!
+//! `RoomKind` - необязательный тип комнаты. Он задает `ClimateProfile`:
+//! целевую температуру для климат-контроля и пороги тревог по температуре
+//! и влажности. Ванной допустима более высокая влажность, чем кухне, гараж
+//! лишь защищается от заморозков, а улица не регулируется совсем.
+
+use crate::house::SmartHouse;
+use crate::room::Room;
+use crate::units::Celsius;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Тип помещения
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomKind {
+    Kitchen,
+    Bathroom,
+    Garage,
+    Outdoor,
+}
+
+/// Климатические нормы помещения
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClimateProfile {
+    /// Целевая температура климат-контроля, °C (None - не регулируется)
+    pub target: Option<f64>,
+    /// Ниже - тревога, °C
+    pub min_temperature: f64,
+    /// Выше - тревога, °C
+    pub max_temperature: f64,
+    /// Предел относительной влажности, % (None - не проверяется)
+    pub max_humidity: Option<f64>,
+}
+
+/// Выход показания за климатическую норму
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClimateBreach {
+    TooCold {
+        value: Celsius,
+        limit: Celsius,
+    },
+    TooHot {
+        value: Celsius,
+        limit: Celsius,
+    },
+    /// Влажность, %
+    TooHumid {
+        value: f64,
+        limit: f64,
+    },
+}
+
+/// Нарушение нормы в комнате дома
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClimateAlert {
+    pub room: String,
+    /// Ключ термометра
+    pub key: String,
+    pub breach: ClimateBreach,
+}
+
+impl RoomKind {
+    /// Все типы помещений
+    pub const ALL: [RoomKind; 4] = [Self::Kitchen, Self::Bathroom, Self::Garage, Self::Outdoor];
+
+    /// Имя типа в конфигурации
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Kitchen => "kitchen",
+            Self::Bathroom => "bathroom",
+            Self::Garage => "garage",
+            Self::Outdoor => "outdoor",
+        }
+    }
+
+    /// Климатические нормы по умолчанию
+    pub fn climate(&self) -> ClimateProfile {
+        match self {
+            Self::Kitchen => ClimateProfile {
+                target: Some(21.0),
+                min_temperature: 16.0,
+                max_temperature: 30.0,
+                max_humidity: Some(70.0),
+            },
+            Self::Bathroom => ClimateProfile {
+                target: Some(24.0),
+                min_temperature: 18.0,
+                max_temperature: 32.0,
+                max_humidity: Some(80.0),
+            },
+            Self::Garage => ClimateProfile {
+                target: Some(8.0),
+                min_temperature: 3.0,
+                max_temperature: 35.0,
+                max_humidity: Some(85.0),
+            },
+            Self::Outdoor => ClimateProfile {
+                target: None,
+                min_temperature: -30.0,
+                max_temperature: 40.0,
+                max_humidity: None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for RoomKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl ClimateProfile {
+    /// Проверяет температуру; граничные значения в норме
+    pub fn check_temperature(&self, value: Celsius) -> Option<ClimateBreach> {
+        if value.value() < self.min_temperature {
+            Some(ClimateBreach::TooCold {
+                value,
+                limit: Celsius::new(self.min_temperature),
+            })
+        } else if value.value() > self.max_temperature {
+            Some(ClimateBreach::TooHot {
+                value,
+                limit: Celsius::new(self.max_temperature),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Проверяет относительную влажность, %
+    pub fn check_humidity(&self, value: f64) -> Option<ClimateBreach> {
+        self.max_humidity
+            .filter(|&limit| value > limit)
+            .map(|limit| ClimateBreach::TooHumid { value, limit })
+    }
+}
+
+impl fmt::Display for ClimateBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooCold { value, limit } => write!(f, "холодно: {} < {}", value, limit),
+            Self::TooHot { value, limit } => write!(f, "жарко: {} > {}", value, limit),
+            Self::TooHumid { value, limit } => {
+                write!(f, "влажность {:.0}% > {:.0}%", value, limit)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ClimateAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}: {}", self.room, self.key, self.breach)
+    }
+}
+
+impl Room {
+    /// Показания термометров комнаты за пределами норм ее типа
+    ///
+    /// Для контроллеров используются последние известные показания.
+    /// Комната без типа не проверяется.
+    pub fn climate_breaches(&self) -> Vec<(String, ClimateBreach)> {
+        let Some(profile) = self.kind().map(|kind| kind.climate()) else {
+            return Vec::new();
+        };
+        self.temperatures()
+            .into_iter()
+            .filter_map(|(key, value)| Some((key, profile.check_temperature(value)?)))
+            .collect()
+    }
+}
+
+impl SmartHouse {
+    /// Нарушения климатических норм во всех комнатах с заданным типом
+    pub fn climate_alerts(&self) -> Vec<ClimateAlert> {
+        let mut alerts = Vec::new();
+        for room_key in self.rooms_keys() {
+            let Some(room) = self.room(&room_key) else {
+                continue;
+            };
+            alerts.extend(
+                room.climate_breaches()
+                    .into_iter()
+                    .map(|(key, breach)| ClimateAlert {
+                        room: room_key.clone(),
+                        key,
+                        breach,
+                    }),
+            );
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn climate_defaults() {
+        let bathroom = RoomKind::Bathroom.climate();
+        let kitchen = RoomKind::Kitchen.climate();
+        assert_eq!(bathroom.check_humidity(75.0), None);
+        assert!(kitchen.check_humidity(75.0).is_some());
+        assert_eq!(RoomKind::Outdoor.climate().target, None);
+        assert_eq!(RoomKind::Outdoor.climate().check_humidity(100.0), None);
+
+        assert_eq!(
+            bathroom.check_temperature(Celsius::new(15.0)),
+            Some(ClimateBreach::TooCold {
+                value: Celsius::new(15.0),
+                limit: Celsius::new(18.0),
+            })
+        );
+        assert_eq!(bathroom.check_temperature(Celsius::new(18.0)), None);
+
+        for kind in RoomKind::ALL {
+            let json = serde_json::to_string(&kind).unwrap();
+            assert_eq!(json, format!("\"{}\"", kind));
+        }
+    }
+
+    #[test]
+    fn house_climate_alerts() {
+        let house = SmartHouse::builder()
+            .room("bath", |r| r.kind(RoomKind::Bathroom).therm("temp", 15.0))
+            .room("garage", |r| r.kind(RoomKind::Garage).therm("temp", 5.0))
+            .room("hall", |r| r.therm("temp", -5.0))
+            .build();
+
+        let alerts = house.climate_alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].room, "bath");
+        assert_eq!(alerts[0].key, "temp");
+        assert!(matches!(alerts[0].breach, ClimateBreach::TooCold { .. }));
+    }
+}
@@ -0,0 +1,533 @@
+//! Правила автоматизации
+//!
+//! `Rule` - условие (`Condition`) и действия, которые выполняются при
+//! каждом запуске `RuleSet::run`, пока условие истинно. Условие задается
+//! методами или строкой, поэтому правила хранятся в конфигурации дома
+//! как есть:
+//!
+//! ```json
+//! {
+//!     "name": "hot_kitchen",
+//!     "when": "kitchen/therm.temperature > 30 && living/tv.active",
+//!     "then": [{ "turn_on": "kitchen/fan" }]
+//! }
+//! ```
+//!
+//! Перед включением набор правил можно проверить без выполнения
+//! действий: `RuleSet::evaluate_against` вычисляет условия на снимке
+//! (`HouseSnapshot`) и сообщает, какие правила сработали бы.
+//!
+//! Чтобы пороговые правила не переключались туда-обратно, у правила есть
+//! модификаторы: гистерезис (`with_hysteresis`, условие остается истинным,
+//! пока значение не отойдет от порога на `delta`), задержка срабатывания
+//! (`for_at_least`, условие должно держаться заданное время) и пауза
+//! между срабатываниями (`with_cooldown`). Их состояние хранит `RuleSet`
+//! между запусками `run`.
+//!
+//! Действия по времени суток, а не по условию, задает расписание
+//! (`Schedule`).
+
+pub mod condition;
+pub mod schedule;
+pub mod snapshot;
+
+pub use condition::{CompareOp, Condition, EvalError, Operand, ParseError, PropertySource, Value};
+pub use schedule::{Schedule, ScheduleEntry, ScheduleReport, TimeOfDay};
+pub use snapshot::HouseSnapshot;
+
+use crate::clock::{SharedClock, system_clock};
+#[cfg(feature = "net")]
+use crate::controllers::DeviceCommand;
+use crate::devices::Device;
+use crate::house::SmartHouse;
+use crate::path::DevicePath;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Действие правила
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Включить розетку
+    TurnOn(DevicePath),
+    /// Выключить розетку
+    TurnOff(DevicePath),
+}
+
+impl RuleAction {
+    /// Устройство, которым управляет действие
+    pub fn path(&self) -> &DevicePath {
+        match self {
+            Self::TurnOn(path) | Self::TurnOff(path) => path,
+        }
+    }
+
+    /// Выполняет действие на локальном устройстве или контроллере
+    pub async fn execute(&self, house: &mut SmartHouse) -> Result<(), RuleError> {
+        let path = self.path();
+        let on = matches!(self, Self::TurnOn(_));
+
+        if let Ok(device) = house.device_at_mut(path) {
+            let kind = device.kind();
+            let Device::Socket(socket) = device else {
+                return Err(RuleError::Unsupported {
+                    path: path.clone(),
+                    kind,
+                });
+            };
+            if on {
+                socket.turn_on();
+            } else {
+                socket.turn_off();
+            }
+            return Ok(());
+        }
+
+        #[cfg(feature = "net")]
+        if let Ok(controller) = house.controller_at_mut(path) {
+            let command = if on {
+                DeviceCommand::TurnOn
+            } else {
+                DeviceCommand::TurnOff
+            };
+            return controller
+                .execute(command)
+                .await
+                .map(|_| ())
+                .map_err(|e| RuleError::Command {
+                    path: path.clone(),
+                    message: e.to_string(),
+                });
+        }
+
+        Err(RuleError::UnknownDevice(path.clone()))
+    }
+}
+
+impl fmt::Display for RuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TurnOn(path) => write!(f, "включить {}", path),
+            Self::TurnOff(path) => write!(f, "выключить {}", path),
+        }
+    }
+}
+
+/// Правило: условие и действия
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    pub when: Condition,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub then: Vec<RuleAction>,
+    /// Насколько значение должно отойти от порога, чтобы условие стало ложным
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hysteresis: Option<f64>,
+    /// Сколько условие должно держаться до срабатывания, мс
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub for_at_least_ms: Option<u64>,
+    /// Минимальный интервал между срабатываниями, мс
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown_ms: Option<u64>,
+}
+
+impl Rule {
+    /// Правило без действий
+    pub fn new(name: &str, when: Condition) -> Self {
+        Self {
+            name: name.to_string(),
+            when,
+            then: Vec::new(),
+            hysteresis: None,
+            for_at_least_ms: None,
+            cooldown_ms: None,
+        }
+    }
+
+    /// Builder: действие правила
+    pub fn with_action(mut self, action: RuleAction) -> Self {
+        self.then.push(action);
+        self
+    }
+
+    /// Builder: гистерезис порогов
+    ///
+    /// Сработавшее правило остается активным, пока значения не отойдут от
+    /// порогов `>`, `>=`, `<`, `<=` на `delta` в обратную сторону.
+    pub fn with_hysteresis(mut self, delta: f64) -> Self {
+        self.hysteresis = Some(delta);
+        self
+    }
+
+    /// Builder: условие должно держаться `duration` без перерыва
+    pub fn for_at_least(mut self, duration: Duration) -> Self {
+        self.for_at_least_ms = Some(duration.as_millis() as u64);
+        self
+    }
+
+    /// Builder: после срабатывания правило молчит `duration`
+    pub fn with_cooldown(mut self, duration: Duration) -> Self {
+        self.cooldown_ms = Some(duration.as_millis() as u64);
+        self
+    }
+
+    /// Задержка срабатывания
+    pub fn min_duration(&self) -> Duration {
+        Duration::from_millis(self.for_at_least_ms.unwrap_or(0))
+    }
+
+    /// Пауза между срабатываниями
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_millis(self.cooldown_ms.unwrap_or(0))
+    }
+}
+
+/// Ошибка правила
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleError {
+    /// Условие не вычислилось
+    Condition(EvalError),
+    /// В доме нет устройства действия
+    UnknownDevice(DevicePath),
+    /// Устройство не переключается
+    Unsupported {
+        path: DevicePath,
+        kind: &'static str,
+    },
+    /// Контроллер не выполнил команду
+    Command { path: DevicePath, message: String },
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Condition(e) => write!(f, "{}", e),
+            Self::UnknownDevice(path) => write!(f, "Нет устройства '{}'", path),
+            Self::Unsupported { path, kind } => {
+                write!(f, "Устройство '{}' ({}) не переключается", path, kind)
+            }
+            Self::Command { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl From<EvalError> for RuleError {
+    fn from(e: EvalError) -> Self {
+        Self::Condition(e)
+    }
+}
+
+/// Результат запуска правил
+#[derive(Debug, Default)]
+pub struct RuleReport {
+    /// Сработавшие правила
+    pub fired: Vec<String>,
+    /// Правила с истинным условием, которые ждут `for_at_least` или `cooldown`
+    pub waiting: Vec<String>,
+    /// Ошибки условий и действий по именам правил
+    pub errors: Vec<(String, RuleError)>,
+}
+
+/// Результат проверки правила без выполнения действий
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleEvaluation {
+    pub rule: String,
+    /// Значение условия или ошибка его вычисления
+    pub outcome: Result<bool, EvalError>,
+    /// Действия, которые были бы выполнены (пусто, если правило не сработало)
+    pub actions: Vec<RuleAction>,
+}
+
+impl RuleEvaluation {
+    /// Правило сработало бы
+    pub fn fires(&self) -> bool {
+        self.outcome == Ok(true)
+    }
+}
+
+/// Состояние правила между запусками
+#[derive(Debug, Clone, Copy, Default)]
+struct RuleState {
+    /// Условие истинно с учетом гистерезиса
+    active: bool,
+    /// С какого момента условие истинно без перерыва, мс
+    since_ms: Option<u64>,
+    /// Последнее срабатывание, мс
+    fired_ms: Option<u64>,
+}
+
+/// Набор правил дома
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    states: Vec<RuleState>,
+    clock: SharedClock,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::from(Vec::new())
+    }
+}
+
+impl RuleSet {
+    /// Пустой набор
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: источник времени для `for_at_least` и `cooldown`
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Builder: правило
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.add(rule);
+        self
+    }
+
+    /// Добавляет правило
+    pub fn add(&mut self, rule: Rule) {
+        self.rules.push(rule);
+        self.states.push(RuleState::default());
+    }
+
+    /// Правила в порядке добавления
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Проверяет условия и выполняет действия сработавших правил
+    ///
+    /// Правило срабатывает при каждом запуске, пока условие истинно, с
+    /// учетом модификаторов. Правила проверяются по порядку, поэтому
+    /// действия предыдущих правил видны в условиях следующих. Ошибка
+    /// вычисления условия не меняет состояние правила.
+    pub async fn run(&mut self, house: &mut SmartHouse) -> RuleReport {
+        let now = self.clock.now_ms();
+        let mut report = RuleReport::default();
+        for (rule, state) in self.rules.iter().zip(&mut self.states) {
+            let slack = if state.active {
+                rule.hysteresis.unwrap_or(0.0)
+            } else {
+                0.0
+            };
+            match rule.when.evaluate_relaxed(&*house, slack) {
+                Ok(true) => state.active = true,
+                Ok(false) => {
+                    state.active = false;
+                    state.since_ms = None;
+                    continue;
+                }
+                Err(e) => {
+                    report.errors.push((rule.name.clone(), e.into()));
+                    continue;
+                }
+            }
+
+            let since = *state.since_ms.get_or_insert(now);
+            let held = now.saturating_sub(since) >= rule.for_at_least_ms.unwrap_or(0);
+            let cooled = state
+                .fired_ms
+                .is_none_or(|fired| now.saturating_sub(fired) >= rule.cooldown_ms.unwrap_or(0));
+            if !(held && cooled) {
+                report.waiting.push(rule.name.clone());
+                continue;
+            }
+
+            state.fired_ms = Some(now);
+            report.fired.push(rule.name.clone());
+            for action in &rule.then {
+                if let Err(e) = action.execute(house).await {
+                    report.errors.push((rule.name.clone(), e));
+                }
+            }
+        }
+        report
+    }
+
+    /// Проверяет условия всех правил на снимке, не выполняя действий
+    ///
+    /// В отличие от `run`, действия сработавших правил не меняют значения
+    /// для следующих: все условия вычисляются на одном и том же снимке.
+    /// Модификаторы правил не учитываются - у снимка нет истории.
+    /// Проверить можно и сам дом - он тоже `PropertySource`.
+    pub fn evaluate_against<S: PropertySource + ?Sized>(
+        &self,
+        snapshot: &S,
+    ) -> Vec<RuleEvaluation> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let outcome = rule.when.evaluate(snapshot);
+                let actions = if outcome == Ok(true) {
+                    rule.then.clone()
+                } else {
+                    Vec::new()
+                };
+                RuleEvaluation {
+                    rule: rule.name.clone(),
+                    outcome,
+                    actions,
+                }
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<Rule>> for RuleSet {
+    fn from(rules: Vec<Rule>) -> Self {
+        Self {
+            states: vec![RuleState::default(); rules.len()],
+            rules,
+            clock: system_clock(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn run_rules_from_json() {
+        let mut house = SmartHouse::builder()
+            .room("kitchen", |r| r.therm("therm", 31.0).socket("fan", 40.0))
+            .room("living", |r| r.socket("tv", 100.0))
+            .build();
+        let json = r#"[
+            {"name": "cool_kitchen", "when": "kitchen/therm.temperature > 30",
+             "then": [{"turn_on": "kitchen/fan"}]},
+            {"name": "tv_off", "when": "kitchen/fan.active && living/tv.active",
+             "then": [{"turn_off": "living/tv"}]},
+            {"name": "broken", "when": "hall/lamp.active"}
+        ]"#;
+        let rules: Vec<Rule> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            rules[0].then,
+            [RuleAction::TurnOn("kitchen/fan".parse().unwrap())]
+        );
+        let mut rules = RuleSet::from(rules);
+
+        let report = rules.run(&mut house).await;
+        assert_eq!(report.fired, ["cool_kitchen"]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, "broken");
+        assert!(matches!(
+            house.device("kitchen", "fan"),
+            Ok(Device::Socket(fan)) if fan.is_active()
+        ));
+
+        let bad = r#"{"name": "x", "when": "kitchen/fan.active &"}"#;
+        let error = serde_json::from_str::<Rule>(bad).unwrap_err();
+        assert!(error.to_string().contains("ожидалось '&&'"));
+
+        let unsupported = RuleAction::TurnOn("kitchen/therm".parse().unwrap());
+        assert!(matches!(
+            unsupported.execute(&mut house).await,
+            Err(RuleError::Unsupported { kind: "therm", .. })
+        ));
+    }
+
+    #[test]
+    fn dry_run_on_snapshot() {
+        let house = SmartHouse::builder()
+            .room("kitchen", |r| r.therm("therm", 21.0).socket("fan", 40.0))
+            .build();
+        let fan: DevicePath = "kitchen/fan".parse().unwrap();
+        let therm: DevicePath = "kitchen/therm".parse().unwrap();
+        let rules = RuleSet::new()
+            .with_rule(
+                Rule::new("cool", "kitchen/therm.temperature > 30".parse().unwrap())
+                    .with_action(RuleAction::TurnOn(fan.clone())),
+            )
+            .with_rule(Rule::new("fan_on", "kitchen/fan.active".parse().unwrap()))
+            .with_rule(Rule::new(
+                "humid",
+                "kitchen/therm.humidity > 60".parse().unwrap(),
+            ));
+
+        let snapshot = HouseSnapshot::capture(&house).with_value(therm, "temperature", 35.0);
+        let results = rules.evaluate_against(&snapshot);
+        let fired: Vec<&str> = results
+            .iter()
+            .filter(|r| r.fires())
+            .map(|r| r.rule.as_str())
+            .collect();
+        assert_eq!(fired, ["cool"]);
+        assert_eq!(results[0].actions, [RuleAction::TurnOn(fan)]);
+        // Включение вентилятора не видно следующему правилу
+        assert_eq!(results[1].outcome, Ok(false));
+        assert!(matches!(
+            results[2].outcome,
+            Err(EvalError::Unavailable { .. })
+        ));
+
+        // Дом не изменился
+        assert!(matches!(
+            house.device("kitchen", "fan"),
+            Ok(Device::Socket(fan)) if !fan.is_active()
+        ));
+        assert!(!rules.evaluate_against(&house)[0].fires());
+    }
+
+    fn set_temperature(house: &mut SmartHouse, value: f64) {
+        if let Ok(Device::Therm(therm)) = house.device_mut("kitchen", "therm") {
+            therm.set_temperature(value);
+        }
+    }
+
+    #[tokio::test]
+    async fn hysteresis_and_timing() {
+        let mut house = SmartHouse::builder()
+            .room("kitchen", |r| r.therm("therm", 31.0).socket("fan", 40.0))
+            .build();
+        let clock = Arc::new(MockClock::starting_at(0));
+        let hot: Condition = "kitchen/therm.temperature > 30".parse().unwrap();
+        let mut rules = RuleSet::new()
+            .with_rule(Rule::new("hysteresis", hot.clone()).with_hysteresis(1.0))
+            .with_rule(Rule::new("sustained", hot.clone()).for_at_least(Duration::from_secs(60)))
+            .with_rule(Rule::new("cooldown", hot).with_cooldown(Duration::from_secs(600)))
+            .with_clock(clock.clone());
+
+        let report = rules.run(&mut house).await;
+        assert_eq!(report.fired, ["hysteresis", "cooldown"]);
+        assert_eq!(report.waiting, ["sustained"]);
+
+        // Ниже порога, но в пределах гистерезиса
+        clock.advance(Duration::from_secs(60));
+        set_temperature(&mut house, 29.5);
+        let report = rules.run(&mut house).await;
+        assert_eq!(report.fired, ["hysteresis"]);
+        assert!(report.waiting.is_empty());
+
+        set_temperature(&mut house, 31.0);
+        let report = rules.run(&mut house).await;
+        assert_eq!(report.fired, ["hysteresis"]);
+        assert_eq!(report.waiting, ["sustained", "cooldown"]);
+
+        clock.advance(Duration::from_secs(60));
+        let report = rules.run(&mut house).await;
+        assert_eq!(report.fired, ["hysteresis", "sustained"]);
+
+        clock.advance(Duration::from_secs(600));
+        set_temperature(&mut house, 28.9);
+        assert!(rules.run(&mut house).await.fired.is_empty());
+        set_temperature(&mut house, 29.5);
+        assert!(rules.run(&mut house).await.fired.is_empty());
+
+        set_temperature(&mut house, 31.0);
+        let report = rules.run(&mut house).await;
+        assert_eq!(report.fired, ["hysteresis", "cooldown"]);
+
+        let json = serde_json::to_string(&rules.rules()[1]).unwrap();
+        assert!(json.contains(r#""for_at_least_ms":60000"#));
+    }
+}
@@ -0,0 +1,895 @@
+//! Условия правил и их текстовая запись
+//!
+//! Условие сравнивает свойства устройств с числами и друг с другом и
+//! объединяет сравнения через `&&`, `||` и `!`:
+//! `kitchen/therm.temperature > 30 && living/tv.active`. Свойство пишется
+//! как путь устройства и имя через точку; свойство без сравнения должно
+//! быть логическим. Условие собирается методами
+//! (`Operand::property(path, "temperature").gt(30.0)`) или разбирается из
+//! строки; ошибка разбора указывает позицию и что ожидалось.
+
+use crate::devices::{Device, SmartSocket};
+use crate::house::SmartHouse;
+use crate::path::{DevicePath, ItemRef};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "net")]
+use crate::controllers::DeviceController;
+#[cfg(feature = "blinds")]
+use crate::devices::SmartBlinds;
+
+const SOCKET_PROPERTIES: &[&str] = &["active", "power"];
+const THERM_PROPERTIES: &[&str] = &["temperature"];
+#[cfg(feature = "blinds")]
+const BLINDS_PROPERTIES: &[&str] = &["position", "open", "moving"];
+#[cfg(feature = "leak")]
+const LEAK_PROPERTIES: &[&str] = &["leak"];
+#[cfg(feature = "valve")]
+const VALVE_PROPERTIES: &[&str] = &["open"];
+const METER_PROPERTIES: &[&str] = &["energy"];
+
+/// Значение свойства или литерала
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Number(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "{}", value),
+            Self::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// Операция сравнения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+        }
+    }
+
+    /// Сравнивает значения; порог сдвигается на `slack` в сторону выполнения
+    fn apply(&self, left: Value, right: Value, slack: f64) -> Result<bool, EvalError> {
+        match (left, right) {
+            (Value::Number(l), Value::Number(r)) => Ok(match self {
+                Self::Gt => l + slack > r,
+                Self::Ge => l + slack >= r,
+                Self::Lt => l - slack < r,
+                Self::Le => l - slack <= r,
+                Self::Eq => l == r,
+                Self::Ne => l != r,
+            }),
+            (Value::Bool(l), Value::Bool(r)) if matches!(self, Self::Eq | Self::Ne) => {
+                Ok((l == r) == (*self == Self::Eq))
+            }
+            (left, right) => Err(EvalError::TypeMismatch(format!(
+                "{} {} {}",
+                left,
+                self.symbol(),
+                right
+            ))),
+        }
+    }
+}
+
+/// Операнд сравнения: литерал или свойство устройства
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Literal(Value),
+    Property { path: DevicePath, name: String },
+}
+
+impl Operand {
+    /// Свойство `name` устройства `path`
+    pub fn property(path: DevicePath, name: &str) -> Self {
+        Self::Property {
+            path,
+            name: name.to_string(),
+        }
+    }
+
+    /// Свойство больше `value`
+    pub fn gt(self, value: f64) -> Condition {
+        self.compare(CompareOp::Gt, value)
+    }
+
+    /// Свойство не меньше `value`
+    pub fn ge(self, value: f64) -> Condition {
+        self.compare(CompareOp::Ge, value)
+    }
+
+    /// Свойство меньше `value`
+    pub fn lt(self, value: f64) -> Condition {
+        self.compare(CompareOp::Lt, value)
+    }
+
+    /// Свойство не больше `value`
+    pub fn le(self, value: f64) -> Condition {
+        self.compare(CompareOp::Le, value)
+    }
+
+    /// Логическое свойство истинно
+    pub fn is_true(self) -> Condition {
+        Condition::Is(self)
+    }
+
+    fn compare(self, op: CompareOp, value: f64) -> Condition {
+        Condition::Compare {
+            left: self,
+            op,
+            right: Operand::Literal(Value::Number(value)),
+        }
+    }
+
+    fn evaluate<S: PropertySource + ?Sized>(&self, source: &S) -> Result<Value, EvalError> {
+        match self {
+            Self::Literal(value) => Ok(*value),
+            Self::Property { path, name } => source.property(path, name),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(value) => write!(f, "{}", value),
+            Self::Property { path, name } => write!(f, "{}.{}", path, name),
+        }
+    }
+}
+
+/// Условие правила
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Condition {
+    Compare {
+        left: Operand,
+        op: CompareOp,
+        right: Operand,
+    },
+    /// Логический операнд без сравнения
+    Is(Operand),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    /// Оба условия
+    pub fn and(self, other: Condition) -> Self {
+        Self::And(Box::new(self), Box::new(other))
+    }
+
+    /// Хотя бы одно из условий
+    pub fn or(self, other: Condition) -> Self {
+        Self::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Вычисляет условие; `&&` и `||` не вычисляют правую часть без необходимости
+    pub fn evaluate<S: PropertySource + ?Sized>(&self, source: &S) -> Result<bool, EvalError> {
+        self.evaluate_relaxed(source, 0.0)
+    }
+
+    /// Вычисляет условие с допуском `slack` для порогов (гистерезис)
+    ///
+    /// Сравнения `>`, `>=`, `<` и `<=` выполняются так, будто порог сдвинут
+    /// на `slack` в сторону выполнения условия; под `!` сдвиг меняет знак.
+    /// Равенства и логические значения не меняются.
+    pub(crate) fn evaluate_relaxed<S: PropertySource + ?Sized>(
+        &self,
+        source: &S,
+        slack: f64,
+    ) -> Result<bool, EvalError> {
+        match self {
+            Self::Compare { left, op, right } => {
+                op.apply(left.evaluate(source)?, right.evaluate(source)?, slack)
+            }
+            Self::Is(operand) => match operand.evaluate(source)? {
+                Value::Bool(value) => Ok(value),
+                value => Err(EvalError::TypeMismatch(format!(
+                    "{} = {} не логическое значение",
+                    operand, value
+                ))),
+            },
+            Self::Not(inner) => Ok(!inner.evaluate_relaxed(source, -slack)?),
+            Self::And(left, right) => {
+                Ok(left.evaluate_relaxed(source, slack)?
+                    && right.evaluate_relaxed(source, slack)?)
+            }
+            Self::Or(left, right) => {
+                Ok(left.evaluate_relaxed(source, slack)?
+                    || right.evaluate_relaxed(source, slack)?)
+            }
+        }
+    }
+
+    /// Устройства, свойства которых читает условие
+    pub fn paths(&self) -> Vec<&DevicePath> {
+        let mut paths = Vec::new();
+        self.collect_paths(&mut paths);
+        paths
+    }
+
+    fn collect_paths<'a>(&'a self, paths: &mut Vec<&'a DevicePath>) {
+        let mut push = |operand: &'a Operand| {
+            if let Operand::Property { path, .. } = operand
+                && !paths.contains(&path)
+            {
+                paths.push(path);
+            }
+        };
+        match self {
+            Self::Compare { left, right, .. } => {
+                push(left);
+                push(right);
+            }
+            Self::Is(operand) => push(operand),
+            Self::Not(inner) => inner.collect_paths(paths),
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.collect_paths(paths);
+                right.collect_paths(paths);
+            }
+        }
+    }
+
+    /// Приоритет операции для записи: `||` < `&&` < остальное
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Or(..) => 1,
+            Self::And(..) => 2,
+            _ => 3,
+        }
+    }
+
+    fn write_with(&self, f: &mut fmt::Formatter<'_>, min_precedence: u8) -> fmt::Result {
+        let parens = self.precedence() < min_precedence;
+        if parens {
+            write!(f, "(")?;
+        }
+        match self {
+            Self::Compare { left, op, right } => write!(f, "{} {} {}", left, op.symbol(), right)?,
+            Self::Is(operand) => write!(f, "{}", operand)?,
+            Self::Not(inner) => {
+                write!(f, "!")?;
+                match inner.as_ref() {
+                    Self::Is(_) | Self::Not(_) => inner.write_with(f, 3)?,
+                    _ => inner.write_with(f, 4)?,
+                }
+            }
+            Self::And(left, right) | Self::Or(left, right) => {
+                let precedence = self.precedence();
+                let op = if precedence == 1 { "||" } else { "&&" };
+                left.write_with(f, precedence)?;
+                write!(f, " {} ", op)?;
+                right.write_with(f, precedence + 1)?;
+            }
+        }
+        if parens {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::ops::Not for Condition {
+    type Output = Condition;
+
+    fn not(self) -> Self::Output {
+        Self::Not(Box::new(self))
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(f, 0)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(input)?;
+        let mut parser = ConditionParser {
+            input,
+            tokens,
+            next: 0,
+        };
+        let condition = parser.or()?;
+        match parser.peek() {
+            None => Ok(condition),
+            Some(token) => Err(parser.error(
+                token.position,
+                format!("лишний {} после условия", token.kind),
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Condition> for String {
+    fn from(condition: Condition) -> Self {
+        condition.to_string()
+    }
+}
+
+/// Ошибка разбора условия
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub input: String,
+    /// Позиция ошибки в символах, с 0
+    pub position: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Условие и указатель `^` на место ошибки в двух строках
+    pub fn pointer(&self) -> String {
+        format!("{}\n{}^", self.input, " ".repeat(self.position))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (позиция {} в '{}')",
+            self.message,
+            self.position + 1,
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Ошибка вычисления условия
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// В доме нет устройства или контроллера по пути
+    UnknownDevice(DevicePath),
+    /// У устройства нет такого свойства
+    UnknownProperty {
+        path: DevicePath,
+        name: String,
+        available: &'static [&'static str],
+    },
+    /// Значение свойства сейчас неизвестно (нет связи, нет данных)
+    Unavailable {
+        path: DevicePath,
+        name: String,
+        reason: String,
+    },
+    /// Сравнение или операция с значениями разных типов
+    TypeMismatch(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownDevice(path) => write!(f, "Нет устройства '{}'", path),
+            Self::UnknownProperty {
+                path,
+                name,
+                available,
+            } => write!(
+                f,
+                "У '{}' нет свойства '{}' (есть: {})",
+                path,
+                name,
+                available.join(", ")
+            ),
+            Self::Unavailable { path, name, reason } => {
+                write!(f, "Свойство {}.{} недоступно: {}", path, name, reason)
+            }
+            Self::TypeMismatch(expression) => write!(f, "Несовместимые типы: {}", expression),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Источник значений свойств устройств
+pub trait PropertySource {
+    /// Значение свойства `name` устройства `path`
+    fn property(&self, path: &DevicePath, name: &str) -> Result<Value, EvalError>;
+}
+
+impl PropertySource for SmartHouse {
+    /// Свойства локальных устройств и последние известные показания контроллеров
+    fn property(&self, path: &DevicePath, name: &str) -> Result<Value, EvalError> {
+        let unknown = |available| EvalError::UnknownProperty {
+            path: path.clone(),
+            name: name.to_string(),
+            available,
+        };
+        if let Ok(device) = self.device_at(path) {
+            let (value, available) = device_property(device, name);
+            return value.ok_or_else(|| unknown(available));
+        }
+
+        #[cfg(feature = "net")]
+        if let Ok(controller) = self.controller_at(path) {
+            let unavailable = |reason: String| EvalError::Unavailable {
+                path: path.clone(),
+                name: name.to_string(),
+                reason,
+            };
+            return match controller {
+                DeviceController::Socket(socket) => {
+                    let value = socket_property(
+                        &socket.device().map_err(|e| unavailable(e.to_string()))?,
+                        name,
+                    );
+                    value.ok_or_else(|| unknown(SOCKET_PROPERTIES))
+                }
+                DeviceController::Therm(therm) => match name {
+                    "temperature" => therm
+                        .temperature()
+                        .map(|reading| Value::Number(reading.value.value()))
+                        .map_err(|e| unavailable(e.to_string())),
+                    _ => Err(unknown(THERM_PROPERTIES)),
+                },
+                #[cfg(feature = "blinds")]
+                DeviceController::Blinds(blinds) => {
+                    blinds_property(blinds.device(), name).ok_or_else(|| unknown(BLINDS_PROPERTIES))
+                }
+            };
+        }
+
+        Err(EvalError::UnknownDevice(path.clone()))
+    }
+}
+
+/// Имена свойств устройства или контроллера
+pub(crate) fn property_names(item: &ItemRef<'_>) -> &'static [&'static str] {
+    match item {
+        ItemRef::Device(device) => device_property(device, "").1,
+        #[cfg(feature = "net")]
+        ItemRef::Controller(controller) => match controller {
+            DeviceController::Socket(_) => SOCKET_PROPERTIES,
+            DeviceController::Therm(_) => THERM_PROPERTIES,
+            #[cfg(feature = "blinds")]
+            DeviceController::Blinds(_) => BLINDS_PROPERTIES,
+        },
+        #[cfg(not(feature = "net"))]
+        ItemRef::Controller(controller) => match **controller {},
+    }
+}
+
+/// Свойство локального устройства и список свойств его типа
+fn device_property(device: &Device, name: &str) -> (Option<Value>, &'static [&'static str]) {
+    match device {
+        Device::Socket(socket) => (socket_property(socket, name), SOCKET_PROPERTIES),
+        Device::Therm(therm) => (
+            (name == "temperature").then(|| Value::Number(therm.temperature().value())),
+            THERM_PROPERTIES,
+        ),
+        #[cfg(feature = "blinds")]
+        Device::Blinds(blinds) => (blinds_property(blinds, name), BLINDS_PROPERTIES),
+        #[cfg(feature = "leak")]
+        Device::Leak(sensor) => (
+            (name == "leak").then(|| Value::Bool(sensor.is_leaking())),
+            LEAK_PROPERTIES,
+        ),
+        #[cfg(feature = "valve")]
+        Device::Valve(valve) => (
+            (name == "open").then(|| Value::Bool(valve.is_open())),
+            VALVE_PROPERTIES,
+        ),
+        Device::Meter(meter) => (
+            (name == "energy").then(|| Value::Number(meter.total_kwh())),
+            METER_PROPERTIES,
+        ),
+        #[cfg(feature = "camera")]
+        Device::Camera(_) => (None, &[]),
+    }
+}
+
+fn socket_property(socket: &SmartSocket, name: &str) -> Option<Value> {
+    match name {
+        "active" => Some(Value::Bool(socket.is_active())),
+        "power" => Some(Value::Number(socket.current_power().value())),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "blinds")]
+fn blinds_property(blinds: &SmartBlinds, name: &str) -> Option<Value> {
+    match name {
+        "position" => Some(Value::Number(blinds.position().value())),
+        "open" => Some(Value::Bool(blinds.is_open())),
+        "moving" => Some(Value::Bool(blinds.is_moving())),
+        _ => None,
+    }
+}
+
+// --- разбор строки ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Operand(Operand),
+    Compare(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Operand(operand) => write!(f, "операнд '{}'", operand),
+            Self::Compare(op) => write!(f, "оператор '{}'", op.symbol()),
+            Self::And => write!(f, "оператор '&&'"),
+            Self::Or => write!(f, "оператор '||'"),
+            Self::Not => write!(f, "оператор '!'"),
+            Self::Open => write!(f, "'('"),
+            Self::Close => write!(f, "')'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Позиция первого символа, с 0
+    position: usize,
+}
+
+/// Символы операнда: путь, имя свойства, число или литерал
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let error = |position, message: &str| ParseError {
+        input: input.to_string(),
+        position,
+        message: message.to_string(),
+    };
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let position = i;
+        let (kind, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (TokenKind::Open, 1),
+            (')', _) => (TokenKind::Close, 1),
+            ('&', Some('&')) => (TokenKind::And, 2),
+            ('|', Some('|')) => (TokenKind::Or, 2),
+            ('&', _) => return Err(error(position, "ожидалось '&&'")),
+            ('|', _) => return Err(error(position, "ожидалось '||'")),
+            ('>', Some('=')) => (TokenKind::Compare(CompareOp::Ge), 2),
+            ('<', Some('=')) => (TokenKind::Compare(CompareOp::Le), 2),
+            ('=', Some('=')) => (TokenKind::Compare(CompareOp::Eq), 2),
+            ('!', Some('=')) => (TokenKind::Compare(CompareOp::Ne), 2),
+            ('>', _) => (TokenKind::Compare(CompareOp::Gt), 1),
+            ('<', _) => (TokenKind::Compare(CompareOp::Lt), 1),
+            ('=', _) => return Err(error(position, "для сравнения используйте '=='")),
+            ('!', _) => (TokenKind::Not, 1),
+            (c, _) if is_word_char(c) => {
+                let len = chars[i..].iter().take_while(|c| is_word_char(**c)).count();
+                let word: String = chars[i..i + len].iter().collect();
+                let operand = operand(&word).map_err(|message| error(position, &message))?;
+                (TokenKind::Operand(operand), len)
+            }
+            (c, _) => return Err(error(position, &format!("неожиданный символ '{}'", c))),
+        };
+        tokens.push(Token { kind, position });
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// Операнд из слова: число, `true`/`false` или `комната/устройство.свойство`
+fn operand(word: &str) -> Result<Operand, String> {
+    match word {
+        "true" => return Ok(Operand::Literal(Value::Bool(true))),
+        "false" => return Ok(Operand::Literal(Value::Bool(false))),
+        _ => {}
+    }
+    if word.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+        return match word.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(Operand::Literal(Value::Number(value))),
+            _ => Err(format!("некорректное число '{}'", word)),
+        };
+    }
+    if !word.contains('/') {
+        return Err(format!(
+            "ожидалось свойство вида комната/устройство.свойство, а не '{}'",
+            word
+        ));
+    }
+    let (path, name) = word
+        .rsplit_once('.')
+        .filter(|(_, name)| !name.is_empty() && !name.contains('/'))
+        .ok_or_else(|| format!("у '{}' не указано свойство (например, .temperature)", word))?;
+    let path: DevicePath = path.parse().map_err(|e| format!("{}", e))?;
+    Ok(Operand::property(path, name))
+}
+
+/// Рекурсивный спуск: `||` < `&&` < `!` < сравнение
+struct ConditionParser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl ConditionParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        token
+    }
+
+    /// Позиция конца строки - для ошибок "условие оборвалось"
+    fn end(&self) -> usize {
+        self.input.chars().count()
+    }
+
+    fn error(&self, position: usize, message: String) -> ParseError {
+        ParseError {
+            input: self.input.to_string(),
+            position,
+            message,
+        }
+    }
+
+    fn or(&mut self) -> Result<Condition, ParseError> {
+        let mut condition = self.and()?;
+        while self.peek().is_some_and(|t| t.kind == TokenKind::Or) {
+            self.advance();
+            condition = condition.or(self.and()?);
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition, ParseError> {
+        let mut condition = self.unary()?;
+        while self.peek().is_some_and(|t| t.kind == TokenKind::And) {
+            self.advance();
+            condition = condition.and(self.unary()?);
+        }
+        Ok(condition)
+    }
+
+    fn unary(&mut self) -> Result<Condition, ParseError> {
+        if self.peek().is_some_and(|t| t.kind == TokenKind::Not) {
+            self.advance();
+            return Ok(!self.unary()?);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Condition, ParseError> {
+        let Some(token) = self.advance() else {
+            return Err(self.error(self.end(), "условие оборвалось: ожидался операнд".into()));
+        };
+        let left = match token.kind {
+            TokenKind::Open => {
+                let condition = self.or()?;
+                return match self.advance() {
+                    Some(Token {
+                        kind: TokenKind::Close,
+                        ..
+                    }) => Ok(condition),
+                    Some(other) => Err(self.error(
+                        other.position,
+                        format!("ожидалась ')', а не {}", other.kind),
+                    )),
+                    None => Err(self.error(self.end(), "не закрыта скобка".into())),
+                };
+            }
+            TokenKind::Operand(operand) => operand,
+            other => {
+                return Err(self.error(token.position, format!("ожидался операнд, а не {}", other)));
+            }
+        };
+
+        let Some(TokenKind::Compare(op)) = self.peek().map(|t| t.kind.clone()) else {
+            return match left {
+                Operand::Literal(Value::Number(_)) => Err(self.error(
+                    token.position,
+                    "число не является условием: добавьте сравнение".into(),
+                )),
+                left => Ok(Condition::Is(left)),
+            };
+        };
+        self.advance();
+        match self.advance() {
+            Some(Token {
+                kind: TokenKind::Operand(right),
+                ..
+            }) => Ok(Condition::Compare { left, op, right }),
+            Some(other) => Err(self.error(
+                other.position,
+                format!(
+                    "после '{}' ожидался операнд, а не {}",
+                    op.symbol(),
+                    other.kind
+                ),
+            )),
+            None => Err(self.error(
+                self.end(),
+                format!("условие оборвалось после '{}'", op.symbol()),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(path: &str, name: &str) -> Operand {
+        Operand::property(path.parse().unwrap(), name)
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let condition: Condition = "kitchen/therm.temperature > 30 && living/tv.active"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            condition,
+            property("kitchen/therm", "temperature")
+                .gt(30.0)
+                .and(property("living/tv", "active").is_true())
+        );
+        assert_eq!(
+            condition.to_string(),
+            "kitchen/therm.temperature > 30 && living/tv.active"
+        );
+
+        for text in [
+            "a/b.power >= -1.5 || !(c/d.active && e/f.open)",
+            "(a/b.active || c/d.active) && !e/f.leak",
+            "a/b.active == false || a/b.power != c/d.power",
+            "!(a/b.power < 10)",
+        ] {
+            let condition: Condition = text.parse().unwrap();
+            assert_eq!(condition.to_string(), text);
+            assert_eq!(
+                condition.to_string().parse::<Condition>().unwrap(),
+                condition
+            );
+        }
+
+        // && связывает сильнее ||
+        let condition: Condition = "a/b.active || c/d.active && e/f.active".parse().unwrap();
+        assert!(matches!(condition, Condition::Or(..)));
+
+        let json = serde_json::to_string(&condition).unwrap();
+        assert_eq!(json, r#""a/b.active || c/d.active && e/f.active""#);
+        assert_eq!(serde_json::from_str::<Condition>(&json).unwrap(), condition);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = "kitchen/therm.temperature = 30"
+            .parse::<Condition>()
+            .unwrap_err();
+        assert_eq!(error.position, 26);
+        assert!(error.message.contains("=="));
+        assert_eq!(
+            error.pointer(),
+            "kitchen/therm.temperature = 30\n                          ^"
+        );
+
+        let cases = [
+            ("kitchen/therm.temperature >", 27, "оборвалось"),
+            ("(a/b.active", 11, "скобка"),
+            ("a/b.active &", 11, "&&"),
+            ("temperature > 30", 0, "комната/устройство"),
+            ("kitchen/therm > 30", 0, "свойство"),
+            ("30", 0, "сравнение"),
+            ("a/b.active a/b.active", 11, "лишний"),
+            ("a/b.power > && c/d.active", 12, "ожидался операнд"),
+            ("кухня/чайник.power # 3", 19, "неожиданный символ"),
+        ];
+        for (text, position, message) in cases {
+            let error = text.parse::<Condition>().unwrap_err();
+            assert_eq!(error.position, position, "{}", text);
+            assert!(error.message.contains(message), "{}: {}", text, error);
+        }
+    }
+
+    #[test]
+    fn evaluate_against_house() {
+        let mut house = SmartHouse::builder()
+            .room("kitchen", |r| {
+                r.therm("therm", 31.0).socket("kettle", 2000.0)
+            })
+            .room("living", |r| r.socket("tv", 100.0))
+            .build();
+        let condition: Condition = "kitchen/therm.temperature > 30 && living/tv.active"
+            .parse()
+            .unwrap();
+        assert_eq!(condition.evaluate(&house), Ok(false));
+        if let Ok(Device::Socket(tv)) = house.device_mut("living", "tv") {
+            tv.turn_on();
+        }
+        assert_eq!(condition.evaluate(&house), Ok(true));
+
+        let paths: Vec<String> = condition.paths().iter().map(|p| p.to_string()).collect();
+        assert_eq!(paths, ["kitchen/therm", "living/tv"]);
+
+        let missing: Condition = "hall/lamp.active".parse().unwrap();
+        assert!(matches!(
+            missing.evaluate(&house),
+            Err(EvalError::UnknownDevice(_))
+        ));
+        let error = "kitchen/kettle.temperature > 1"
+            .parse::<Condition>()
+            .unwrap()
+            .evaluate(&house)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "У 'kitchen/kettle' нет свойства 'temperature' (есть: active, power)"
+        );
+        let mismatch: Condition = "kitchen/therm.temperature".parse().unwrap();
+        assert!(matches!(
+            mismatch.evaluate(&house),
+            Err(EvalError::TypeMismatch(_))
+        ));
+
+        // Правая часть || не вычисляется, если левая истинна
+        let short: Condition = "living/tv.active || hall/lamp.active".parse().unwrap();
+        assert_eq!(short.evaluate(&house), Ok(true));
+    }
+}
@@ -0,0 +1,278 @@
+//! Расписание действий по времени суток
+//!
+//! `ScheduleEntry` выполняет действия правила один раз в сутки в заданное
+//! время (UTC). Записи хранятся в конфигурации дома рядом с правилами:
+//!
+//! ```json
+//! { "name": "morning", "at": "07:30", "then": [{ "turn_on": "kitchen/kettle" }] }
+//! ```
+//!
+//! `Schedule::run` вызывается периодически (например, из цикла опроса
+//! демона) и выполняет записи, время которых наступило с прошлого запуска.
+//! Первый запуск только запоминает время: пропущенные до старта записи
+//! не выполняются.
+
+use super::{RuleAction, RuleError};
+use crate::clock::{SharedClock, system_clock};
+use crate::house::SmartHouse;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const MINUTE_MS: u64 = 60_000;
+const DAY_MS: u64 = 24 * 60 * MINUTE_MS;
+
+/// Время суток с точностью до минуты, "ЧЧ:ММ"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay {
+    hour: u8,
+    minute: u8,
+}
+
+impl TimeOfDay {
+    /// Время суток; `None` для часа больше 23 или минуты больше 59
+    pub fn new(hour: u8, minute: u8) -> Option<Self> {
+        (hour < 24 && minute < 60).then_some(Self { hour, minute })
+    }
+
+    /// Смещение от начала суток, мс
+    fn offset_ms(self) -> u64 {
+        (self.hour as u64 * 60 + self.minute as u64) * MINUTE_MS
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Некорректное время '{}': ожидается ЧЧ:ММ", s);
+        let (hour, minute) = s.split_once(':').ok_or_else(invalid)?;
+        if hour.len() != 2 || minute.len() != 2 {
+            return Err(invalid());
+        }
+        let hour = hour.parse().map_err(|_| invalid())?;
+        let minute = minute.parse().map_err(|_| invalid())?;
+        Self::new(hour, minute).ok_or_else(invalid)
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+/// Запись расписания: действия в заданное время суток
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    pub name: String,
+    pub at: TimeOfDay,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub then: Vec<RuleAction>,
+}
+
+impl ScheduleEntry {
+    pub fn new(name: &str, at: TimeOfDay) -> Self {
+        Self {
+            name: name.to_string(),
+            at,
+            then: Vec::new(),
+        }
+    }
+
+    /// Builder: действие
+    pub fn with_action(mut self, action: RuleAction) -> Self {
+        self.then.push(action);
+        self
+    }
+
+    /// Наступило ли время записи в промежутке (`after`, `until`]
+    fn due(&self, after: u64, until: u64) -> bool {
+        let day_start = after - after % DAY_MS;
+        let mut next = day_start + self.at.offset_ms();
+        if next <= after {
+            next += DAY_MS;
+        }
+        next <= until
+    }
+}
+
+/// Результат запуска расписания
+#[derive(Debug, Default)]
+pub struct ScheduleReport {
+    /// Выполненные записи
+    pub fired: Vec<String>,
+    /// Ошибки действий по именам записей
+    pub errors: Vec<(String, RuleError)>,
+}
+
+/// Расписание дома
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    entries: Vec<ScheduleEntry>,
+    /// Время прошлого запуска `run`
+    checked_ms: Option<u64>,
+    clock: SharedClock,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::from(Vec::new())
+    }
+}
+
+impl Schedule {
+    /// Пустое расписание
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: источник времени
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Builder: запись
+    pub fn with_entry(mut self, entry: ScheduleEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Записи в порядке добавления
+    pub fn entries(&self) -> &[ScheduleEntry] {
+        &self.entries
+    }
+
+    /// Выполняет записи, время которых наступило с прошлого запуска
+    ///
+    /// Если между запусками прошло больше суток, запись все равно
+    /// выполняется один раз.
+    pub async fn run(&mut self, house: &mut SmartHouse) -> ScheduleReport {
+        let now = self.clock.now_ms();
+        let mut report = ScheduleReport::default();
+        let Some(after) = self.checked_ms.replace(now) else {
+            return report;
+        };
+
+        for entry in self.entries.iter().filter(|entry| entry.due(after, now)) {
+            report.fired.push(entry.name.clone());
+            for action in &entry.then {
+                if let Err(e) = action.execute(house).await {
+                    report.errors.push((entry.name.clone(), e));
+                }
+            }
+        }
+        report
+    }
+}
+
+impl From<Vec<ScheduleEntry>> for Schedule {
+    fn from(entries: Vec<ScheduleEntry>) -> Self {
+        Self {
+            entries,
+            checked_ms: None,
+            clock: system_clock(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::devices::Device;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn is_on(house: &SmartHouse) -> bool {
+        matches!(house.device("kitchen", "kettle"), Ok(Device::Socket(kettle)) if kettle.is_active())
+    }
+
+    #[test]
+    fn parse_time() {
+        let time: TimeOfDay = "07:05".parse().unwrap();
+        assert_eq!(time, TimeOfDay::new(7, 5).unwrap());
+        assert_eq!(time.to_string(), "07:05");
+        for invalid in ["7:05", "24:00", "12:60", "12-30", "ab:cd"] {
+            assert!(invalid.parse::<TimeOfDay>().is_err(), "{}", invalid);
+        }
+
+        let entry: ScheduleEntry =
+            serde_json::from_str(r#"{"name": "m", "at": "23:59", "then": [{"turn_off": "a/b"}]}"#)
+                .unwrap();
+        assert_eq!(entry.at, TimeOfDay::new(23, 59).unwrap());
+        assert!(serde_json::from_str::<ScheduleEntry>(r#"{"name": "m", "at": "25:00"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn fires_once_a_day() {
+        let mut house = SmartHouse::builder()
+            .room("kitchen", |r| r.socket("kettle", 2000.0))
+            .build();
+        // 07:00 UTC
+        let clock = Arc::new(MockClock::starting_at(7 * 60 * MINUTE_MS));
+        let mut schedule = Schedule::new()
+            .with_entry(
+                ScheduleEntry::new("morning", "07:30".parse().unwrap())
+                    .with_action(RuleAction::TurnOn("kitchen/kettle".parse().unwrap())),
+            )
+            .with_entry(
+                ScheduleEntry::new("night", "00:00".parse().unwrap())
+                    .with_action(RuleAction::TurnOff("kitchen/kettle".parse().unwrap())),
+            )
+            .with_clock(clock.clone());
+
+        // Первый запуск только запоминает время
+        assert!(schedule.run(&mut house).await.fired.is_empty());
+        clock.advance(Duration::from_secs(29 * 60));
+        assert!(schedule.run(&mut house).await.fired.is_empty());
+
+        clock.advance(Duration::from_secs(60));
+        let report = schedule.run(&mut house).await;
+        assert_eq!(report.fired, ["morning"]);
+        assert!(report.errors.is_empty());
+        assert!(is_on(&house));
+        assert!(schedule.run(&mut house).await.fired.is_empty());
+
+        // Переход через полночь
+        clock.advance(Duration::from_secs(17 * 3600));
+        assert_eq!(schedule.run(&mut house).await.fired, ["night"]);
+        assert!(!is_on(&house));
+    }
+
+    #[tokio::test]
+    async fn reports_action_errors() {
+        let mut house = SmartHouse::default();
+        let clock = Arc::new(MockClock::starting_at(0));
+        let mut schedule = Schedule::from(vec![
+            ScheduleEntry::new("morning", TimeOfDay::new(0, 1).unwrap())
+                .with_action(RuleAction::TurnOn("kitchen/kettle".parse().unwrap())),
+        ])
+        .with_clock(clock.clone());
+
+        schedule.run(&mut house).await;
+        clock.advance(Duration::from_secs(60));
+        let report = schedule.run(&mut house).await;
+        assert_eq!(report.fired, ["morning"]);
+        assert!(matches!(
+            report.errors.as_slice(),
+            [(name, RuleError::UnknownDevice(_))] if name == "morning"
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+//! Снимок свойств дома для проверки правил
+//!
+//! `HouseSnapshot` хранит значения свойств устройств на момент снятия:
+//! `kitchen/therm` → `temperature = 31`. Снимок снимается с дома
+//! (`HouseSnapshot::capture`), дополняется или собирается вручную и
+//! хранится в JSON, поэтому на нем можно проверить набор правил
+//! (`RuleSet::evaluate_against`), не выполняя действий.
+
+use super::condition::{EvalError, PropertySource, Value, property_names};
+use crate::house::SmartHouse;
+use crate::path::DevicePath;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Значения свойств устройств дома
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HouseSnapshot {
+    values: BTreeMap<DevicePath, BTreeMap<String, Value>>,
+}
+
+impl HouseSnapshot {
+    /// Пустой снимок
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Снимает все свойства устройств и контроллеров дома
+    ///
+    /// Для контроллеров берутся последние известные значения; недоступные
+    /// (нет связи, нет данных) в снимок не попадают.
+    pub fn capture(house: &SmartHouse) -> Self {
+        let mut snapshot = Self::new();
+        let items = house.select("*/*").unwrap_or_default();
+        for (path, item) in items {
+            for name in property_names(&item) {
+                if let Ok(value) = house.property(&path, name) {
+                    snapshot.set(path.clone(), name, value);
+                }
+            }
+        }
+        snapshot
+    }
+
+    /// Builder: значение свойства
+    pub fn with_value(mut self, path: DevicePath, name: &str, value: impl Into<Value>) -> Self {
+        self.set(path, name, value);
+        self
+    }
+
+    /// Задает значение свойства
+    pub fn set(&mut self, path: DevicePath, name: &str, value: impl Into<Value>) {
+        self.values
+            .entry(path)
+            .or_default()
+            .insert(name.to_string(), value.into());
+    }
+
+    /// Значение свойства, если оно есть в снимке
+    pub fn get(&self, path: &DevicePath, name: &str) -> Option<Value> {
+        self.values.get(path)?.get(name).copied()
+    }
+
+    /// Устройства в снимке
+    pub fn paths(&self) -> impl Iterator<Item = &DevicePath> {
+        self.values.keys()
+    }
+}
+
+impl PropertySource for HouseSnapshot {
+    fn property(&self, path: &DevicePath, name: &str) -> Result<Value, EvalError> {
+        let properties = self
+            .values
+            .get(path)
+            .ok_or_else(|| EvalError::UnknownDevice(path.clone()))?;
+        properties
+            .get(name)
+            .copied()
+            .ok_or_else(|| EvalError::Unavailable {
+                path: path.clone(),
+                name: name.to_string(),
+                reason: "нет в снимке".to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_and_json() {
+        let house = SmartHouse::builder()
+            .room("kitchen", |r| {
+                r.therm("therm", 21.5).socket("kettle", 2000.0)
+            })
+            .room("hall", |r| r.socket("lamp", 60.0))
+            .build();
+        let snapshot = HouseSnapshot::capture(&house);
+        let kettle: DevicePath = "kitchen/kettle".parse().unwrap();
+        assert_eq!(snapshot.get(&kettle, "active"), Some(Value::Bool(false)));
+        assert_eq!(snapshot.paths().count(), 3);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains(r#""kitchen/therm":{"temperature":21.5}"#));
+        assert_eq!(
+            serde_json::from_str::<HouseSnapshot>(&json).unwrap(),
+            snapshot
+        );
+
+        assert!(matches!(
+            snapshot.property(&kettle, "temperature"),
+            Err(EvalError::Unavailable { .. })
+        ));
+    }
+}
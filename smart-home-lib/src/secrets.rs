@@ -0,0 +1,173 @@
+//! Секреты в конфигурации: токены и пароли по ссылкам
+//!
+//! Вместо самого значения в конфигурации записывается ссылка:
+//! `"${env:SOCKET_TOKEN}"` - переменная окружения, `"${file:/run/secrets/bot}"` -
+//! содержимое файла (без завершающего перевода строки). Ссылка может быть
+//! частью строки: `"http://hooks.local/${env:HOOK_ID}"`. Строка без ссылок
+//! используется как есть.
+//!
+//! `Secret` разрешает ссылки при разборе конфигурации, а при сериализации
+//! и в отладочном выводе показывает исходную запись, поэтому сохраненная
+//! конфигурация не содержит значений секретов.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Ошибки разрешения секретов
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("environment variable '{0}' is not set")]
+    MissingEnv(String),
+
+    #[error("cannot read secret file {path}: {source}", path = .path.display())]
+    File {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("unknown secret source '{0}' (expected env: or file:)")]
+    UnknownSource(String),
+
+    #[error("unterminated secret reference in '{0}'")]
+    Unterminated(String),
+}
+
+/// Подставляет значения вместо всех ссылок `${env:...}` и `${file:...}`
+pub fn resolve(text: &str) -> Result<String, SecretError> {
+    let mut resolved = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        resolved.push_str(&rest[..start]);
+        let reference = &rest[start + 2..];
+        let end = reference
+            .find('}')
+            .ok_or_else(|| SecretError::Unterminated(text.to_string()))?;
+        resolved.push_str(&lookup(&reference[..end])?);
+        rest = &reference[end + 1..];
+    }
+
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+/// Значение одной ссылки (`env:NAME` или `file:PATH`)
+fn lookup(reference: &str) -> Result<String, SecretError> {
+    if let Some(name) = reference.strip_prefix("env:") {
+        return std::env::var(name).map_err(|_| SecretError::MissingEnv(name.to_string()));
+    }
+    if let Some(path) = reference.strip_prefix("file:") {
+        let content = std::fs::read_to_string(path).map_err(|source| SecretError::File {
+            path: PathBuf::from(path),
+            source,
+        })?;
+        return Ok(content.trim_end_matches(['\r', '\n']).to_string());
+    }
+    Err(SecretError::UnknownSource(reference.to_string()))
+}
+
+/// Секрет из конфигурации: значение и исходная запись со ссылками
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret {
+    source: String,
+    value: String,
+}
+
+impl Secret {
+    /// Разрешает ссылки в записи `source`
+    pub fn new(source: &str) -> Result<Self, SecretError> {
+        Ok(Self {
+            value: resolve(source)?,
+            source: source.to_string(),
+        })
+    }
+
+    /// Значение секрета
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+
+    /// Исходная запись, как в конфигурации
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Значение записано в конфигурации открытым текстом
+    pub fn is_plaintext(&self) -> bool {
+        !self.source.contains("${")
+    }
+}
+
+/// Значение не выводится: только запись со ссылками или `***`
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_plaintext() {
+            f.write_str("Secret(***)")
+        } else {
+            write!(f, "Secret({:?})", self.source)
+        }
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Self::new(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn resolve_references() {
+        let path = std::env::temp_dir().join(format!("smart-home-secret-{}", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, "s3cret").unwrap();
+
+        // PATH задана в любом окружении, где запускаются тесты
+        let path_var = std::env::var("PATH").unwrap();
+        let text = format!("${{file:{}}}|${{env:PATH}}|plain", path.display());
+        assert_eq!(resolve(&text).unwrap(), format!("s3cret|{}|plain", path_var));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            resolve("${env:SMART_HOME_SURELY_UNSET_VARIABLE}"),
+            Err(SecretError::MissingEnv(name)) if name == "SMART_HOME_SURELY_UNSET_VARIABLE"
+        ));
+        assert!(matches!(
+            resolve("${vault:x}"),
+            Err(SecretError::UnknownSource(_))
+        ));
+        assert!(matches!(
+            resolve("${env:PATH"),
+            Err(SecretError::Unterminated(_))
+        ));
+        assert!(matches!(
+            resolve("${file:/nonexistent/secret}"),
+            Err(SecretError::File { .. })
+        ));
+    }
+
+    #[test]
+    fn secret_is_not_leaked() {
+        let secret: Secret = serde_json::from_str(r#""${env:PATH}""#).unwrap();
+        assert_eq!(secret.expose(), std::env::var("PATH").unwrap());
+        assert_eq!(serde_json::to_string(&secret).unwrap(), r#""${env:PATH}""#);
+        assert_eq!(format!("{:?}", secret), r#"Secret("${env:PATH}")"#);
+
+        let plain = Secret::new("hunter2").unwrap();
+        assert!(plain.is_plaintext());
+        assert!(!format!("{:?}", plain).contains("hunter2"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Статистика состава дома для планирования
+//!
+//! `SmartHouse::statistics` считает устройства по типам, контроллеры по
+//! сетевым протоколам, комнаты в группах (этажи `floor` макроса `house!` -
+//! это группы комнат) и грубо оценивает занимаемую домом память. Оценка
+//! учитывает структуры устройств, контроллеров и ключи, но не буферы
+//! соединений и накладные расходы аллокатора.
+
+use crate::controllers::DeviceController;
+use crate::devices::Device;
+use crate::house::SmartHouse;
+use crate::room::Room;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::mem::size_of;
+
+/// Состав дома в числах
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HouseStatistics {
+    pub rooms: usize,
+    /// Локальные устройства
+    pub devices: usize,
+    /// Сетевые контроллеры
+    pub controllers: usize,
+    /// Устройства и контроллеры по типам (`socket`, `therm`)
+    pub by_kind: BTreeMap<String, usize>,
+    /// Контроллеры по сетевым протоколам (`tcp`, `udp`)
+    pub controllers_by_transport: BTreeMap<String, usize>,
+    /// Число разных комнат в каждой группе
+    pub rooms_per_group: BTreeMap<String, usize>,
+    /// Больше всего элементов в одной комнате
+    pub max_room_items: usize,
+    /// Оценка памяти, занимаемой домом, байт
+    pub estimated_bytes: usize,
+}
+
+impl HouseStatistics {
+    /// Все устройства и контроллеры
+    pub fn items(&self) -> usize {
+        self.devices + self.controllers
+    }
+}
+
+impl fmt::Display for HouseStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |counts: &BTreeMap<String, usize>| {
+            counts
+                .iter()
+                .map(|(name, count)| format!("{}: {}", name, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        writeln!(
+            f,
+            "Комнат: {}, устройств: {}, контроллеров: {}",
+            self.rooms, self.devices, self.controllers
+        )?;
+        writeln!(f, "По типам: {}", join(&self.by_kind))?;
+        writeln!(f, "По протоколам: {}", join(&self.controllers_by_transport))?;
+        if !self.rooms_per_group.is_empty() {
+            writeln!(f, "Комнат в группах: {}", join(&self.rooms_per_group))?;
+        }
+        write!(
+            f,
+            "Больше всего в комнате: {}, память: ~{} КиБ",
+            self.max_room_items,
+            self.estimated_bytes.div_ceil(1024)
+        )
+    }
+}
+
+/// Оценка памяти под элемент комнаты: запись в таблице и ключ
+fn entry_bytes<T>(key: &str) -> usize {
+    size_of::<T>() + size_of::<String>() + size_of::<u64>() + key.len()
+}
+
+impl SmartHouse {
+    /// Количество устройств и контроллеров во всех комнатах
+    pub fn device_count(&self) -> usize {
+        self.rooms_keys()
+            .iter()
+            .filter_map(|key| self.room(key))
+            .map(Room::items_count)
+            .sum()
+    }
+
+    /// Статистика состава дома
+    pub fn statistics(&self) -> HouseStatistics {
+        let mut stats = HouseStatistics {
+            estimated_bytes: size_of::<SmartHouse>(),
+            ..HouseStatistics::default()
+        };
+
+        for room_key in self.rooms_keys() {
+            let Some(room) = self.room(&room_key) else {
+                continue;
+            };
+            stats.rooms += 1;
+            stats.max_room_items = stats.max_room_items.max(room.items_count());
+            stats.estimated_bytes += entry_bytes::<Room>(&room_key);
+
+            for key in room.devices_keys() {
+                if let Some(device) = room.device(&key) {
+                    stats.devices += 1;
+                    *stats.by_kind.entry(device.kind().to_string()).or_default() += 1;
+                    stats.estimated_bytes += entry_bytes::<Device>(&key);
+                }
+            }
+            for key in room.controllers_keys() {
+                if let Some(controller) = room.controller(&key) {
+                    stats.controllers += 1;
+                    *stats
+                        .by_kind
+                        .entry(controller.kind().to_string())
+                        .or_default() += 1;
+                    *stats
+                        .controllers_by_transport
+                        .entry(controller.transport().to_string())
+                        .or_default() += 1;
+                    stats.estimated_bytes += entry_bytes::<DeviceController>(&key);
+                }
+            }
+        }
+
+        for name in self.groups_keys() {
+            let Some(group) = self.group(&name) else {
+                continue;
+            };
+            let rooms: BTreeSet<&str> = group.members().map(|(room, _)| room).collect();
+            stats.estimated_bytes += entry_bytes::<Vec<(String, String)>>(&name)
+                + group
+                    .members()
+                    .map(|(room, key)| 2 * size_of::<String>() + room.len() + key.len())
+                    .sum::<usize>();
+            stats.rooms_per_group.insert(name, rooms.len());
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn counts_by_kind_transport_and_floor() {
+        use crate::controllers::ThermController;
+        use std::time::Duration;
+
+        let mut house = crate::house! {
+            "kitchen" => { "kettle" => socket(2000.0), "therm" => therm(21.0) },
+            floor "second" {
+                "bedroom" => { "lamp" => socket(60.0) },
+                "office" => { "heater" => socket(1500.0), "pc" => socket(300.0) },
+            }
+        };
+        house.room_mut("office").unwrap().add_controller(
+            "window",
+            ThermController::new(18.0, "127.0.0.1:0", Duration::from_secs(5)).into(),
+        );
+
+        let stats = house.statistics();
+        assert_eq!(house.device_count(), 6);
+        assert_eq!(stats.items(), 6);
+        assert_eq!(stats.rooms, 3);
+        assert_eq!((stats.devices, stats.controllers), (5, 1));
+        assert_eq!(stats.by_kind["socket"], 4);
+        assert_eq!(stats.by_kind["therm"], 2);
+        assert_eq!(stats.controllers_by_transport["udp"], 1);
+        assert!(!stats.controllers_by_transport.contains_key("tcp"));
+        assert_eq!(stats.rooms_per_group["second"], 2);
+        assert_eq!(stats.max_room_items, 3);
+        assert!(stats.estimated_bytes > 6 * size_of::<Device>());
+        assert!(stats.to_string().contains("Комнат в группах: second: 2"));
+    }
+
+    #[test]
+    fn empty_house() {
+        let empty = SmartHouse::default().statistics();
+        assert_eq!(empty.items(), 0);
+        assert_eq!(empty.estimated_bytes, size_of::<SmartHouse>());
+    }
+}
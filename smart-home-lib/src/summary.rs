@@ -0,0 +1,636 @@
+//! Сводные отчеты за сутки и неделю
+//!
+//! `HouseSummary` собирается по истории показаний за период: для каждой
+//! комнаты - минимальная, максимальная и средняя температура, потребленная
+//! энергия, время работы розеток и число тревог. Энергия берется из
+//! счетчиков комнаты, а без них - из мощности розеток. Тревоги - это
+//! выходы температуры за нормы типа комнаты (`RoomKind`) и события
+//! расхождений, которые `SummaryJob` считает по шине событий дома.
+//!
+//! `SummaryJob` формирует сводку на границах периодов по UTC (полночь,
+//! полночь понедельника) и рассылает ее через `Notifier` в виде текста,
+//! Markdown или JSON.
+
+use crate::clock::{SharedClock, system_clock};
+use crate::events::HouseEvent;
+use crate::history::{History, ReadingKind};
+use crate::house::SmartHouse;
+use crate::notifications::{Notification, Notifier};
+use crate::units::Celsius;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Мощность, выше которой розетка считается работающей, Вт
+const ON_THRESHOLD: f64 = 1.0;
+/// Миллисекунд в сутках
+const DAY_MS: u64 = 24 * 3600 * 1000;
+/// 1970-01-01 - четверг; столько дней от понедельника
+const EPOCH_WEEKDAY: u64 = 3;
+
+/// Период сводки
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryPeriod {
+    /// Сутки с полуночи UTC
+    Day,
+    /// Неделя с понедельника UTC
+    Week,
+}
+
+impl SummaryPeriod {
+    /// Длительность периода
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.len_ms())
+    }
+
+    /// Последний завершившийся к `now_ms` период
+    pub fn last_complete(&self, now_ms: u64) -> Range<u64> {
+        let end = self.start_of(now_ms);
+        end.saturating_sub(self.len_ms())..end
+    }
+
+    /// Начало следующего периода после `now_ms`
+    pub fn next_boundary(&self, now_ms: u64) -> u64 {
+        self.start_of(now_ms) + self.len_ms()
+    }
+
+    fn len_ms(&self) -> u64 {
+        match self {
+            Self::Day => DAY_MS,
+            Self::Week => 7 * DAY_MS,
+        }
+    }
+
+    /// Начало периода, в который попадает `timestamp_ms`
+    fn start_of(&self, timestamp_ms: u64) -> u64 {
+        let day = timestamp_ms / DAY_MS;
+        match self {
+            Self::Day => day * DAY_MS,
+            Self::Week => {
+                let monday = (day + EPOCH_WEEKDAY) / 7 * 7;
+                monday.saturating_sub(EPOCH_WEEKDAY) * DAY_MS
+            }
+        }
+    }
+}
+
+/// Формат сводки
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SummaryFormat {
+    #[default]
+    Text,
+    Markdown,
+    Json,
+}
+
+/// Температура комнаты за период
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TemperatureStats {
+    pub min: Celsius,
+    pub max: Celsius,
+    pub avg: Celsius,
+}
+
+/// Время работы розетки за период
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceUptime {
+    pub key: String,
+    /// Часы с мощностью выше 1 Вт
+    pub on_hours: f64,
+    /// Доля времени работы среди времени с показаниями, 0..=1
+    pub share: f64,
+}
+
+/// Сводка по комнате
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoomPeriodSummary {
+    pub room: String,
+    /// None - за период нет показаний термометров
+    pub temperature: Option<TemperatureStats>,
+    /// Потребленная энергия, кВт·ч
+    pub energy_kwh: f64,
+    pub uptime: Vec<DeviceUptime>,
+    /// Выходы температуры за нормы и события расхождений
+    pub alerts: usize,
+}
+
+/// Сводка по дому за период
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HouseSummary {
+    /// Начало периода, мс Unix
+    pub from_ms: u64,
+    /// Конец периода (не включая), мс Unix
+    pub to_ms: u64,
+    pub rooms: Vec<RoomPeriodSummary>,
+}
+
+impl HouseSummary {
+    /// Собирает сводку по истории за интервал `range`
+    ///
+    /// `event_alerts` - число тревог с шины событий по комнатам; они
+    /// добавляются к выходам температуры за нормы.
+    pub fn build(
+        house: &SmartHouse,
+        history: &History,
+        range: Range<u64>,
+        event_alerts: &BTreeMap<String, usize>,
+    ) -> Self {
+        let series = history.series();
+        let mut rooms: BTreeSet<String> = house.rooms_keys().into_iter().collect();
+        rooms.extend(event_alerts.keys().cloned());
+
+        let rooms = rooms
+            .into_iter()
+            .map(|room| {
+                let of_room = |kind: ReadingKind| -> Vec<(String, Vec<(u64, f64)>)> {
+                    series
+                        .iter()
+                        .filter(|(_, k)| *k == kind)
+                        .filter_map(|(device_id, _)| {
+                            let (r, key) = device_id.split_once('/')?;
+                            (r == room).then(|| {
+                                let points = history.points(device_id, kind, range.clone());
+                                (key.to_string(), points)
+                            })
+                        })
+                        .collect()
+                };
+                let temperatures = of_room(ReadingKind::Temperature);
+                let power = of_room(ReadingKind::Power);
+                let energy = of_room(ReadingKind::Energy);
+
+                let profile = house
+                    .room(&room)
+                    .and_then(|r| r.kind())
+                    .map(|kind| kind.climate());
+                let breaches: usize = match profile {
+                    Some(profile) => temperatures
+                        .iter()
+                        .map(|(_, points)| {
+                            count_episodes(points, |v| {
+                                profile.check_temperature(Celsius::new(v)).is_some()
+                            })
+                        })
+                        .sum(),
+                    None => 0,
+                };
+
+                let energy_kwh = if energy.is_empty() {
+                    power.iter().map(|(_, points)| integrate_kwh(points)).sum()
+                } else {
+                    energy.iter().map(|(_, points)| meter_delta(points)).sum()
+                };
+
+                RoomPeriodSummary {
+                    temperature: temperature_stats(&temperatures),
+                    energy_kwh,
+                    uptime: power
+                        .iter()
+                        .filter_map(|(key, points)| uptime(key, points))
+                        .collect(),
+                    alerts: breaches + event_alerts.get(&room).copied().unwrap_or(0),
+                    room,
+                }
+            })
+            .collect();
+
+        Self {
+            from_ms: range.start,
+            to_ms: range.end,
+            rooms,
+        }
+    }
+
+    /// Сводка в заданном формате
+    pub fn render(&self, format: SummaryFormat) -> String {
+        match format {
+            SummaryFormat::Text => self.to_string(),
+            SummaryFormat::Markdown => self.markdown(),
+            SummaryFormat::Json => {
+                serde_json::to_string_pretty(self).expect("summary is always serializable")
+            }
+        }
+    }
+
+    /// Уведомление со сводкой
+    pub fn to_notification(&self, format: SummaryFormat) -> Notification {
+        let subject = format!(
+            "Сводка {} — {}",
+            utc_datetime(self.from_ms),
+            utc_datetime(self.to_ms)
+        );
+        Notification::new(&subject, &self.render(format))
+    }
+
+    /// Общее потребление дома, кВт·ч
+    pub fn total_energy_kwh(&self) -> f64 {
+        self.rooms.iter().map(|r| r.energy_kwh).sum()
+    }
+
+    fn markdown(&self) -> String {
+        let mut out = format!(
+            "## Сводка {} — {} UTC\n\n",
+            utc_datetime(self.from_ms),
+            utc_datetime(self.to_ms)
+        );
+        out.push_str("| Комната | Мин | Макс | Сред | Энергия, кВт·ч | Тревоги |\n");
+        out.push_str("|---|---|---|---|---|---|\n");
+        for room in &self.rooms {
+            let (min, max, avg) = match &room.temperature {
+                Some(t) => (t.min.to_string(), t.max.to_string(), t.avg.to_string()),
+                None => ("—".to_string(), "—".to_string(), "—".to_string()),
+            };
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {:.2} | {} |\n",
+                room.room, min, max, avg, room.energy_kwh, room.alerts
+            ));
+        }
+
+        let uptime: Vec<_> = self
+            .rooms
+            .iter()
+            .flat_map(|r| r.uptime.iter().map(move |u| (&r.room, u)))
+            .collect();
+        if !uptime.is_empty() {
+            out.push_str("\n**Время работы**\n\n");
+            for (room, u) in uptime {
+                out.push_str(&format!(
+                    "- {}/{}: {:.1} ч ({:.0}%)\n",
+                    room,
+                    u.key,
+                    u.on_hours,
+                    u.share * 100.0
+                ));
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for HouseSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Сводка {} — {} UTC",
+            utc_datetime(self.from_ms),
+            utc_datetime(self.to_ms)
+        )?;
+        for room in &self.rooms {
+            writeln!(f, "Room: {}", room.room)?;
+            match &room.temperature {
+                Some(t) => writeln!(
+                    f,
+                    "  Температура: мин {}, макс {}, сред {}",
+                    t.min, t.max, t.avg
+                )?,
+                None => writeln!(f, "  Температура: нет данных")?,
+            }
+            writeln!(f, "  Энергия: {:.2} кВт·ч", room.energy_kwh)?;
+            for u in &room.uptime {
+                writeln!(
+                    f,
+                    "  Работа {}: {:.1} ч ({:.0}%)",
+                    u.key,
+                    u.on_hours,
+                    u.share * 100.0
+                )?;
+            }
+            writeln!(f, "  Тревоги: {}", room.alerts)?;
+        }
+        Ok(())
+    }
+}
+
+/// Рассылка сводок по расписанию
+pub struct SummaryJob {
+    history: History,
+    period: SummaryPeriod,
+    format: SummaryFormat,
+    notifiers: Vec<Box<dyn Notifier>>,
+    clock: SharedClock,
+}
+
+/// Результат рассылки сводки
+#[derive(Debug)]
+pub struct SummaryDelivery {
+    pub summary: HouseSummary,
+    /// Сколько каналов доставили уведомление
+    pub notified: usize,
+    pub failed_notifications: usize,
+}
+
+impl SummaryJob {
+    /// Сводки за `period` по истории `history`
+    pub fn new(history: History, period: SummaryPeriod) -> Self {
+        Self {
+            history,
+            period,
+            format: SummaryFormat::default(),
+            notifiers: Vec::new(),
+            clock: system_clock(),
+        }
+    }
+
+    /// Builder: формат сводки
+    pub fn with_format(mut self, format: SummaryFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Builder: канал доставки сводок
+    pub fn with_notifier(mut self, notifier: impl Notifier + 'static) -> Self {
+        self.notifiers.push(Box::new(notifier));
+        self
+    }
+
+    /// Builder: источник времени
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Сводка за последний завершившийся период
+    pub fn summary(
+        &self,
+        house: &SmartHouse,
+        event_alerts: &BTreeMap<String, usize>,
+    ) -> HouseSummary {
+        let range = self.period.last_complete(self.clock.now_ms());
+        HouseSummary::build(house, &self.history, range, event_alerts)
+    }
+
+    /// Формирует сводку за последний завершившийся период и рассылает ее
+    pub async fn send(
+        &self,
+        house: &SmartHouse,
+        event_alerts: &BTreeMap<String, usize>,
+    ) -> SummaryDelivery {
+        let summary = self.summary(house, event_alerts);
+        let notification = summary.to_notification(self.format);
+
+        let mut notified = 0;
+        let mut failed_notifications = 0;
+        for notifier in &self.notifiers {
+            match notifier.notify(&notification).await {
+                Ok(()) => notified += 1,
+                Err(e) => {
+                    eprintln!("⚠️ Сводка: уведомление не отправлено: {}", e);
+                    failed_notifications += 1;
+                }
+            }
+        }
+
+        SummaryDelivery {
+            summary,
+            notified,
+            failed_notifications,
+        }
+    }
+
+    /// Рассылает сводки на границах периодов; работает, пока задачу не отменят
+    ///
+    /// Тревоги с шины событий считаются с момента запуска, поэтому первая
+    /// сводка может учитывать их лишь за часть периода.
+    pub async fn run(&self, house: Arc<Mutex<SmartHouse>>) {
+        let mut events = house.lock().await.events().subscribe();
+        let mut alerts = BTreeMap::new();
+
+        loop {
+            let now = self.clock.now_ms();
+            let wait = self.period.next_boundary(now) - now;
+            let deadline = tokio::time::sleep(Duration::from_millis(wait));
+            tokio::pin!(deadline);
+
+            loop {
+                tokio::select! {
+                    () = &mut deadline => break,
+                    event = events.recv() => match event {
+                        Ok(event) => count_alert(&mut alerts, &event),
+                        Err(RecvError::Lagged(skipped)) => {
+                            eprintln!("⚠️ Сводка: пропущено событий: {}", skipped);
+                        }
+                        Err(RecvError::Closed) => {
+                            (&mut deadline).await;
+                            break;
+                        }
+                    },
+                }
+            }
+
+            let alerts = std::mem::take(&mut alerts);
+            self.send(&*house.lock().await, &alerts).await;
+        }
+    }
+}
+
+/// Учитывает событие-тревогу в счетчиках по комнатам
+fn count_alert(alerts: &mut BTreeMap<String, usize>, event: &HouseEvent) {
+    let room = match event {
+        HouseEvent::DriftDetected { room, .. } => room,
+        HouseEvent::CorrectionFailed { context, .. } => &context.room,
+        _ => return,
+    };
+    *alerts.entry(room.clone()).or_default() += 1;
+}
+
+/// Мин/макс/среднее по всем термометрам комнаты
+fn temperature_stats(series: &[(String, Vec<(u64, f64)>)]) -> Option<TemperatureStats> {
+    let values: Vec<f64> = series
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|(_, v)| *v))
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let avg = values.iter().sum::<f64>() / values.len() as f64;
+    Some(TemperatureStats {
+        min: Celsius::new(min),
+        max: Celsius::new(max),
+        avg: Celsius::new(avg),
+    })
+}
+
+/// Число эпизодов, когда `breach` становится истинным
+fn count_episodes(points: &[(u64, f64)], breach: impl Fn(f64) -> bool) -> usize {
+    let mut inside = false;
+    let mut episodes = 0;
+    for (_, value) in points {
+        let now = breach(*value);
+        if now && !inside {
+            episodes += 1;
+        }
+        inside = now;
+    }
+    episodes
+}
+
+/// Энергия по показаниям мощности: мощность держится до следующего показания
+fn integrate_kwh(points: &[(u64, f64)]) -> f64 {
+    points
+        .windows(2)
+        .map(|w| w[0].1 * (w[1].0 - w[0].0) as f64)
+        .sum::<f64>()
+        / 3.6e9
+}
+
+/// Прирост накопительного счетчика; сброс счетчика не дает отрицательных значений
+fn meter_delta(points: &[(u64, f64)]) -> f64 {
+    points.windows(2).map(|w| (w[1].1 - w[0].1).max(0.0)).sum()
+}
+
+/// Время работы розетки; None без двух показаний
+fn uptime(key: &str, points: &[(u64, f64)]) -> Option<DeviceUptime> {
+    let (first, last) = (points.first()?.0, points.last()?.0);
+    if last == first {
+        return None;
+    }
+    let on_ms: u64 = points
+        .windows(2)
+        .filter(|w| w[0].1 > ON_THRESHOLD)
+        .map(|w| w[1].0 - w[0].0)
+        .sum();
+    Some(DeviceUptime {
+        key: key.to_string(),
+        on_hours: on_ms as f64 / 3.6e6,
+        share: on_ms as f64 / (last - first) as f64,
+    })
+}
+
+/// Дата и время `ГГГГ-ММ-ДД ЧЧ:ММ` по UTC
+fn utc_datetime(timestamp_ms: u64) -> String {
+    let minutes = timestamp_ms / 60_000;
+    // Обратный days_from_civil (H. Hinnant) для дней с 1970-01-01
+    let z = (minutes / 1440) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes % 1440 / 60,
+        minutes % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::notifications::NotifyFuture;
+    use crate::room_kind::RoomKind;
+    use std::sync::Mutex as StdMutex;
+
+    #[derive(Clone, Default)]
+    struct Inbox(Arc<StdMutex<Vec<Notification>>>);
+
+    impl Notifier for Inbox {
+        fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+            self.0.lock().unwrap().push(notification.clone());
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    const HOUR: u64 = 3600 * 1000;
+    /// 2023-11-14 00:00 UTC, вторник
+    const TUESDAY: u64 = 1_699_920_000_000;
+
+    #[test]
+    fn period_boundaries() {
+        assert_eq!(utc_datetime(0), "1970-01-01 00:00");
+        assert_eq!(utc_datetime(1_700_000_000_000), "2023-11-14 22:13");
+
+        let now = TUESDAY + 5 * HOUR;
+        assert_eq!(
+            SummaryPeriod::Day.last_complete(now),
+            TUESDAY - DAY_MS..TUESDAY
+        );
+        assert_eq!(SummaryPeriod::Day.next_boundary(now), TUESDAY + DAY_MS);
+        let monday = TUESDAY - DAY_MS;
+        assert_eq!(
+            SummaryPeriod::Week.last_complete(now),
+            monday - 7 * DAY_MS..monday
+        );
+        assert_eq!(utc_datetime(monday), "2023-11-13 00:00");
+    }
+
+    #[tokio::test]
+    async fn daily_summary() {
+        let house = SmartHouse::builder()
+            .room("kitchen", |r| {
+                r.kind(RoomKind::Kitchen)
+                    .therm("temp", 21.0)
+                    .socket("kettle", 2000.0)
+            })
+            .room("hall", |r| r.socket("lamp", 60.0))
+            .build();
+        let history = History::default();
+        let day = TUESDAY - DAY_MS;
+        for (hour, temperature, kettle) in [
+            (0, 20.0, 0.0),
+            (6, 31.0, 2000.0),
+            (7, 22.0, 0.0),
+            (12, 33.0, 0.0),
+            (23, 24.0, 0.0),
+        ] {
+            let at = day + hour * HOUR;
+            history.record_at("kitchen/temp", ReadingKind::Temperature, at, temperature);
+            history.record_at("kitchen/kettle", ReadingKind::Power, at, kettle);
+        }
+        // Показание следующих суток в сводку не попадает
+        history.record_at("kitchen/temp", ReadingKind::Temperature, TUESDAY, 50.0);
+
+        let inbox = Inbox::default();
+        let job = SummaryJob::new(history, SummaryPeriod::Day)
+            .with_format(SummaryFormat::Markdown)
+            .with_notifier(inbox.clone())
+            .with_clock(Arc::new(MockClock::starting_at(TUESDAY + HOUR)));
+        let alerts = BTreeMap::from([("hall".to_string(), 2)]);
+        let delivery = job.send(&house, &alerts).await;
+        assert_eq!(delivery.notified, 1);
+
+        let kitchen = &delivery.summary.rooms[1];
+        assert_eq!(kitchen.room, "kitchen");
+        let temperature = kitchen.temperature.unwrap();
+        assert_eq!(temperature.min, Celsius::new(20.0));
+        assert_eq!(temperature.max, Celsius::new(33.0));
+        assert!((temperature.avg.value() - 26.0).abs() < 1e-9);
+        // Чайник работал час при 2 кВт
+        assert!((kitchen.energy_kwh - 2.0).abs() < 1e-9);
+        assert_eq!(kitchen.uptime[0].key, "kettle");
+        assert!((kitchen.uptime[0].on_hours - 1.0).abs() < 1e-9);
+        // Два выхода выше 30°C
+        assert_eq!(kitchen.alerts, 2);
+
+        let hall = &delivery.summary.rooms[0];
+        assert_eq!(hall.temperature, None);
+        assert_eq!(hall.alerts, 2);
+
+        let sent = inbox.0.lock().unwrap();
+        assert_eq!(
+            sent[0].subject,
+            "Сводка 2023-11-13 00:00 — 2023-11-14 00:00"
+        );
+        assert!(sent[0].body.contains("| kitchen | 20.0°C | 33.0°C"));
+        assert!(sent[0].body.contains("- kitchen/kettle: 1.0 ч (4%)"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&delivery.summary.render(SummaryFormat::Json)).unwrap();
+        assert_eq!(json["rooms"][1]["alerts"], 2);
+        assert!(delivery.summary.to_string().contains("Room: kitchen"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Тарифы на электроэнергию и стоимость потребления
+//!
+//! `Tariff` задает цены за кВт·ч в пиковые и непиковые часы.
+//! `SmartHouse::energy_cost` считает стоимость по накопительным показаниям
+//! счетчиков (`ReadingKind::Energy`) из `History`: расход между соседними
+//! показаниями распределяется по часам пропорционально времени, поэтому
+//! интервал, пересекающий границу тарифа, оплачивается по обеим ценам.
+
+use crate::devices::Device;
+use crate::history::{History, ReadingKind};
+use crate::house::SmartHouse;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+
+const HOUR_MS: i64 = 3_600_000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+/// Двухзонный тариф
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tariff {
+    peak_price: f64,
+    off_peak_price: f64,
+    peak_start: u8,
+    peak_end: u8,
+    utc_offset_minutes: i32,
+}
+
+impl Tariff {
+    /// Создает тариф с ценами за кВт·ч; пиковые часы по умолчанию 7..23 UTC
+    pub fn new(peak_price: f64, off_peak_price: f64) -> Self {
+        Self {
+            peak_price,
+            off_peak_price,
+            peak_start: 7,
+            peak_end: 23,
+            utc_offset_minutes: 0,
+        }
+    }
+
+    /// Тариф с единой ценой в любое время суток
+    pub fn flat(price: f64) -> Self {
+        Self::new(price, price)
+    }
+
+    /// Builder: пиковые часы местного времени с `start` до `end`
+    ///
+    /// Начало больше конца означает интервал через полночь (`22, 6`).
+    pub fn with_peak_hours(mut self, start: u8, end: u8) -> Self {
+        self.peak_start = start.min(24);
+        self.peak_end = end.min(24);
+        self
+    }
+
+    /// Builder: смещение местного времени относительно UTC, минуты
+    pub fn with_utc_offset(mut self, minutes: i32) -> Self {
+        self.utc_offset_minutes = minutes;
+        self
+    }
+
+    /// Цена за кВт·ч в пиковые часы
+    pub fn peak_price(&self) -> f64 {
+        self.peak_price
+    }
+
+    /// Цена за кВт·ч в непиковые часы
+    pub fn off_peak_price(&self) -> f64 {
+        self.off_peak_price
+    }
+
+    /// Начало и конец пиковых часов местного времени
+    pub fn peak_hours(&self) -> (u8, u8) {
+        (self.peak_start, self.peak_end)
+    }
+
+    /// Проверяет, действует ли пиковая цена в момент `timestamp_ms`
+    pub fn is_peak(&self, timestamp_ms: u64) -> bool {
+        let local = self.local_ms(timestamp_ms);
+        let hour = (local.rem_euclid(DAY_MS) / HOUR_MS) as u8;
+        let (start, end) = self.peak_hours();
+        if start <= end {
+            (start..end).contains(&hour)
+        } else {
+            hour >= start || hour < end
+        }
+    }
+
+    /// Цена за кВт·ч в момент `timestamp_ms`
+    pub fn price_at(&self, timestamp_ms: u64) -> f64 {
+        if self.is_peak(timestamp_ms) {
+            self.peak_price
+        } else {
+            self.off_peak_price
+        }
+    }
+
+    fn local_ms(&self, timestamp_ms: u64) -> i64 {
+        timestamp_ms as i64 + self.utc_offset_minutes as i64 * 60_000
+    }
+
+    /// Начало следующего часа после `timestamp_ms` (зоны меняются только на границе часа)
+    fn next_hour_ms(&self, timestamp_ms: u64) -> u64 {
+        let local = self.local_ms(timestamp_ms);
+        timestamp_ms + (HOUR_MS - local.rem_euclid(HOUR_MS)) as u64
+    }
+}
+
+/// Стоимость потребления за интервал
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EnergyCost {
+    /// Расход в пиковые часы, кВт·ч
+    pub peak_kwh: f64,
+    /// Расход в непиковые часы, кВт·ч
+    pub off_peak_kwh: f64,
+    /// Итоговая стоимость
+    pub cost: f64,
+    /// Стоимость по счетчикам (`комната/ключ`)
+    pub by_device: BTreeMap<String, f64>,
+}
+
+impl EnergyCost {
+    /// Суммарный расход, кВт·ч
+    pub fn total_kwh(&self) -> f64 {
+        self.peak_kwh + self.off_peak_kwh
+    }
+
+    /// Учитывает расход `kwh` между моментами `from_ms` и `to_ms`
+    fn add_consumption(
+        &mut self,
+        device_id: &str,
+        tariff: &Tariff,
+        from_ms: u64,
+        to_ms: u64,
+        kwh: f64,
+    ) {
+        let mut cost = 0.0;
+        if to_ms <= from_ms {
+            cost += self.add_part(tariff, from_ms, kwh);
+        } else {
+            let span = (to_ms - from_ms) as f64;
+            let mut start = from_ms;
+            while start < to_ms {
+                let end = tariff.next_hour_ms(start).min(to_ms);
+                let part = kwh * (end - start) as f64 / span;
+                cost += self.add_part(tariff, start, part);
+                start = end;
+            }
+        }
+        self.cost += cost;
+        *self.by_device.entry(device_id.to_string()).or_default() += cost;
+    }
+
+    fn add_part(&mut self, tariff: &Tariff, at_ms: u64, kwh: f64) -> f64 {
+        if tariff.is_peak(at_ms) {
+            self.peak_kwh += kwh;
+        } else {
+            self.off_peak_kwh += kwh;
+        }
+        kwh * tariff.price_at(at_ms)
+    }
+}
+
+impl fmt::Display for EnergyCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} kWh (peak {:.2}, off-peak {:.2}), cost {:.2}",
+            self.total_kwh(),
+            self.peak_kwh,
+            self.off_peak_kwh,
+            self.cost
+        )
+    }
+}
+
+impl SmartHouse {
+    /// Стоимость электроэнергии по счетчикам дома за интервал `range`, мс
+    ///
+    /// Учитываются показания счетчиков внутри интервала; уменьшение
+    /// показания (сброс или замена счетчика) не дает отрицательного расхода.
+    pub fn energy_cost(&self, history: &History, tariff: &Tariff, range: Range<u64>) -> EnergyCost {
+        let mut total = EnergyCost::default();
+
+        for room_key in self.rooms_keys() {
+            let Some(room) = self.room(&room_key) else {
+                continue;
+            };
+            for key in room.devices_keys() {
+                if !matches!(room.device(&key), Some(Device::Meter(_))) {
+                    continue;
+                }
+                let device_id = format!("{}/{}", room_key, key);
+                let samples = history.query(&device_id, ReadingKind::Energy, range.clone());
+                total.by_device.entry(device_id.clone()).or_default();
+                for pair in samples.windows(2) {
+                    let kwh = (pair[1].value - pair[0].value).max(0.0);
+                    let (from, to) = (pair[0].timestamp_ms, pair[1].timestamp_ms);
+                    total.add_consumption(&device_id, tariff, from, to, kwh);
+                }
+            }
+        }
+
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::SmartMeter;
+    use crate::room::Room;
+
+    const HOUR: u64 = HOUR_MS as u64;
+
+    fn house_with_meter() -> SmartHouse {
+        let mut room = Room::new();
+        room.add_device("meter", Device::Meter(SmartMeter::new(0.0)));
+        let mut house = SmartHouse::default();
+        house.add_room("hall", room);
+        house
+    }
+
+    #[test]
+    fn peak_hours() {
+        let tariff = Tariff::new(6.0, 3.0).with_peak_hours(7, 23);
+        assert!(!tariff.is_peak(6 * HOUR));
+        assert!(tariff.is_peak(7 * HOUR));
+        assert!(!tariff.is_peak(23 * HOUR));
+        assert_eq!(tariff.price_at(12 * HOUR), 6.0);
+
+        // Интервал через полночь и смещение часового пояса
+        let night = Tariff::new(6.0, 3.0)
+            .with_peak_hours(22, 6)
+            .with_utc_offset(180);
+        assert!(night.is_peak(20 * HOUR)); // 23:00 местного
+        assert!(!night.is_peak(4 * HOUR)); // 07:00 местного
+    }
+
+    #[test]
+    fn cost_split_across_tariff_boundary() {
+        let house = house_with_meter();
+        let history = History::default();
+        // 06:00 -> 08:00: час непиковый, час пиковый
+        history.record_at("hall/meter", ReadingKind::Energy, 6 * HOUR, 100.0);
+        history.record_at("hall/meter", ReadingKind::Energy, 8 * HOUR, 104.0);
+
+        let tariff = Tariff::new(6.0, 3.0);
+        let cost = house.energy_cost(&history, &tariff, 0..u64::MAX);
+        assert_eq!(cost.off_peak_kwh, 2.0);
+        assert_eq!(cost.peak_kwh, 2.0);
+        assert_eq!(cost.cost, 18.0);
+        assert_eq!(cost.by_device["hall/meter"], 18.0);
+    }
+
+    #[test]
+    fn meter_reset_and_range() {
+        let house = house_with_meter();
+        let history = History::default();
+        history.record_at("hall/meter", ReadingKind::Energy, 10 * HOUR, 50.0);
+        history.record_at("hall/meter", ReadingKind::Energy, 11 * HOUR, 0.5);
+        history.record_at("hall/meter", ReadingKind::Energy, 12 * HOUR, 2.5);
+        history.record_at("hall/meter", ReadingKind::Energy, 13 * HOUR, 4.5);
+
+        let tariff = Tariff::flat(5.0);
+        let cost = house.energy_cost(&history, &tariff, 0..u64::MAX);
+        assert_eq!(cost.total_kwh(), 4.0);
+        assert_eq!(cost.cost, 20.0);
+
+        // Только показания внутри интервала
+        let cost = house.energy_cost(&history, &tariff, 11 * HOUR..12 * HOUR + 1);
+        assert_eq!(cost.total_kwh(), 2.0);
+    }
+}
@@ -0,0 +1,353 @@
+//! Шаблоны комнат для домов из множества одинаковых комнат
+//!
+//! Гостиница или общежитие - это десятки одинаковых номеров, которые
+//! отличаются только адресами устройств. `RoomTemplate` описывает состав
+//! комнаты один раз, а `SmartHouse::instantiate` создает по нему комнаты
+//! с заданными именами. Адрес контроллера задается `AddressPattern`:
+//! порт и/или последняя часть IP адреса сдвигаются на номер комнаты.
+
+use crate::controllers::ControllerConfig;
+use crate::devices::{Device, SmartSocket, SmartTherm};
+use crate::discovery::{DiscoveredDevice, DiscoveredKind};
+use crate::house::{SmartHouse, SmartHouseError, SmartHouseResult};
+use crate::metadata::DeviceMetadata;
+use crate::room::Room;
+use std::collections::HashSet;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+/// Адрес контроллера в `index`-й комнате шаблона
+///
+/// Порт равен `base.port() + index * port_step`, последний октет IPv4
+/// (последний сегмент IPv6) - базовому плюс `index * host_step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressPattern {
+    base: SocketAddr,
+    port_step: u16,
+    host_step: u16,
+}
+
+impl AddressPattern {
+    /// Одинаковый адрес во всех комнатах
+    pub fn fixed(base: SocketAddr) -> Self {
+        Self {
+            base,
+            port_step: 0,
+            host_step: 0,
+        }
+    }
+
+    /// Builder: шаг порта между соседними комнатами
+    pub fn with_port_step(mut self, step: u16) -> Self {
+        self.port_step = step;
+        self
+    }
+
+    /// Builder: шаг последней части IP адреса между соседними комнатами
+    pub fn with_host_step(mut self, step: u16) -> Self {
+        self.host_step = step;
+        self
+    }
+
+    /// Адрес для комнаты с номером `index` (с нуля); `None` при выходе за диапазон
+    pub fn resolve(&self, index: usize) -> Option<SocketAddr> {
+        let offset = |step: u16| u32::try_from(index).ok()?.checked_mul(step.into());
+
+        let port = u32::from(self.base.port()).checked_add(offset(self.port_step)?)?;
+        let port = u16::try_from(port).ok()?;
+
+        let host = offset(self.host_step)?;
+        let ip = match self.base.ip() {
+            IpAddr::V4(ip) => {
+                let mut octets = ip.octets();
+                octets[3] = u8::try_from(u32::from(octets[3]).checked_add(host)?).ok()?;
+                IpAddr::V4(Ipv4Addr::from(octets))
+            }
+            IpAddr::V6(ip) => {
+                let mut segments = ip.segments();
+                segments[7] = u16::try_from(u32::from(segments[7]).checked_add(host)?).ok()?;
+                IpAddr::V6(Ipv6Addr::from(segments))
+            }
+        };
+        Some(SocketAddr::new(ip, port))
+    }
+}
+
+impl From<SocketAddr> for AddressPattern {
+    fn from(base: SocketAddr) -> Self {
+        Self::fixed(base)
+    }
+}
+
+type DeviceFactory = Arc<dyn Fn() -> Device + Send + Sync>;
+
+#[derive(Clone)]
+enum TemplateKind {
+    Device(DeviceFactory),
+    Controller {
+        kind: DiscoveredKind,
+        address: AddressPattern,
+        settings: ControllerConfig,
+    },
+}
+
+#[derive(Clone)]
+struct TemplateItem {
+    key: String,
+    kind: TemplateKind,
+    metadata: DeviceMetadata,
+}
+
+/// Шаблон комнаты: локальные устройства и контроллеры с адресами по образцу
+#[derive(Clone, Default)]
+pub struct RoomTemplate {
+    items: Vec<TemplateItem>,
+}
+
+impl RoomTemplate {
+    /// Пустой шаблон
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Локальная розетка мощностью `power_rating` Вт
+    pub fn socket(self, key: &str, power_rating: f64) -> Self {
+        self.device(key, move || Device::Socket(SmartSocket::new(power_rating)))
+    }
+
+    /// Локальный термометр с начальной температурой
+    pub fn therm(self, key: &str, temperature: f64) -> Self {
+        self.device(key, move || Device::Therm(SmartTherm::new(temperature)))
+    }
+
+    /// Локальное устройство; `factory` вызывается для каждой комнаты
+    pub fn device(self, key: &str, factory: impl Fn() -> Device + Send + Sync + 'static) -> Self {
+        self.item(key, TemplateKind::Device(Arc::new(factory)))
+    }
+
+    /// TCP контроллер розетки по адресу `address`
+    pub fn socket_controller(
+        self,
+        key: &str,
+        address: impl Into<AddressPattern>,
+        power_rating: f64,
+    ) -> Self {
+        let kind = DiscoveredKind::Socket { power_rating };
+        self.controller(key, kind, address.into())
+    }
+
+    /// UDP контроллер термометра, принимающий показания на `address`
+    pub fn therm_controller(
+        self,
+        key: &str,
+        address: impl Into<AddressPattern>,
+        initial_temp: f64,
+    ) -> Self {
+        let kind = DiscoveredKind::Therm { initial_temp };
+        self.controller(key, kind, address.into())
+    }
+
+    /// Метаданные элемента, добавленного ранее
+    pub fn metadata(mut self, key: &str, metadata: DeviceMetadata) -> Self {
+        if let Some(item) = self.items.iter_mut().find(|item| item.key == key) {
+            item.metadata = metadata;
+        }
+        self
+    }
+
+    /// Рабочие параметры контроллера, добавленного ранее
+    pub fn controller_config(mut self, key: &str, config: ControllerConfig) -> Self {
+        let item = self.items.iter_mut().find(|item| item.key == key);
+        if let Some(TemplateKind::Controller { settings, .. }) = item.map(|item| &mut item.kind) {
+            *settings = config;
+        }
+        self
+    }
+
+    /// Ключи элементов шаблона в порядке добавления
+    pub fn keys(&self) -> Vec<String> {
+        self.items.iter().map(|item| item.key.clone()).collect()
+    }
+
+    fn controller(self, key: &str, kind: DiscoveredKind, address: AddressPattern) -> Self {
+        self.item(
+            key,
+            TemplateKind::Controller {
+                kind,
+                address,
+                settings: ControllerConfig::default(),
+            },
+        )
+    }
+
+    /// Повторный ключ заменяет прежний элемент
+    fn item(mut self, key: &str, kind: TemplateKind) -> Self {
+        self.items.retain(|item| item.key != key);
+        self.items.push(TemplateItem {
+            key: key.to_string(),
+            kind,
+            metadata: DeviceMetadata::default(),
+        });
+        self
+    }
+
+    /// Проверяет, что адреса всех контроллеров `index`-й комнаты допустимы
+    fn check_addresses(&self, room_key: &str, index: usize) -> SmartHouseResult<()> {
+        for item in &self.items {
+            if let TemplateKind::Controller { address, .. } = &item.kind
+                && address.resolve(index).is_none()
+            {
+                return Err(SmartHouseError::AddressOutOfRange(
+                    room_key.to_string(),
+                    item.key.clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Собирает `index`-ю комнату; адреса проверены `check_addresses`
+    fn build(&self, index: usize) -> Room {
+        let mut room = Room::new();
+        for item in &self.items {
+            match &item.kind {
+                TemplateKind::Device(factory) => room.add_device(&item.key, factory()),
+                TemplateKind::Controller {
+                    kind,
+                    address,
+                    settings,
+                } => {
+                    let discovered = DiscoveredDevice {
+                        kind: *kind,
+                        address: address.resolve(index).unwrap_or(address.base),
+                        device_id: None,
+                    };
+                    room.add_controller(&item.key, discovered.into_controller_with(settings));
+                }
+            }
+            room.set_metadata(&item.key, item.metadata.clone());
+        }
+        room
+    }
+}
+
+impl fmt::Debug for RoomTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoomTemplate")
+            .field("keys", &self.keys())
+            .finish()
+    }
+}
+
+impl SmartHouse {
+    /// Создает комнаты `names` по шаблону
+    ///
+    /// Комната получает номер по позиции в `names` (с нуля), от него
+    /// считаются адреса контроллеров. Контроллеры создаются и запускаются
+    /// как при `adopt`. Если имя занято или повторяется либо адрес выходит
+    /// за диапазон, дом не меняется.
+    pub fn instantiate<S: AsRef<str>>(
+        &mut self,
+        template: &RoomTemplate,
+        names: impl IntoIterator<Item = S>,
+    ) -> SmartHouseResult<()> {
+        let names: Vec<S> = names.into_iter().collect();
+
+        let mut seen = HashSet::new();
+        for (index, name) in names.iter().enumerate() {
+            let name = name.as_ref();
+            if self.room(name).is_some() || !seen.insert(name) {
+                return Err(SmartHouseError::RoomExists(name.to_string()));
+            }
+            template.check_addresses(name, index)?;
+        }
+
+        for (index, name) in names.iter().enumerate() {
+            self.add_room(name.as_ref(), template.build(index));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::DeviceController;
+    use crate::traits::Reporter;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn address_pattern() {
+        let pattern = AddressPattern::fixed(addr("10.0.0.10:3000")).with_port_step(10);
+        assert_eq!(pattern.resolve(0), Some(addr("10.0.0.10:3000")));
+        assert_eq!(pattern.resolve(3), Some(addr("10.0.0.10:3030")));
+        assert_eq!(pattern.resolve(7000), None);
+
+        let hosts = AddressPattern::fixed(addr("10.0.0.10:3000")).with_host_step(1);
+        assert_eq!(hosts.resolve(5), Some(addr("10.0.0.15:3000")));
+        assert_eq!(hosts.resolve(246), None);
+
+        let v6 = AddressPattern::fixed(addr("[fd00::1]:3000")).with_host_step(2);
+        assert_eq!(v6.resolve(2), Some(addr("[fd00::5]:3000")));
+    }
+
+    #[tokio::test]
+    async fn instantiate_rooms() {
+        let template = RoomTemplate::new()
+            .socket("lamp", 60.0)
+            .socket_controller(
+                "heater",
+                AddressPattern::fixed(addr("127.0.0.1:3100")).with_port_step(1),
+                1500.0,
+            )
+            .therm_controller("therm", addr("127.0.0.1:0"), 21.0)
+            .metadata("heater", DeviceMetadata::new().with_priority(10));
+
+        let mut house = SmartHouse::default();
+        house
+            .instantiate(&template, ["room101", "room102", "room103"])
+            .unwrap();
+
+        assert_eq!(house.rooms_keys(), vec!["room101", "room102", "room103"]);
+        let room = house.room("room103").unwrap();
+        assert_eq!(room.keys(), vec!["lamp", "heater", "therm"]);
+        assert_eq!(room.metadata("heater").priority, 10);
+        match room.controller("heater") {
+            Some(DeviceController::Socket(socket)) => {
+                assert_eq!(*socket.address(), addr("127.0.0.1:3102"))
+            }
+            other => panic!("unexpected controller: {:?}", other.map(|c| c.report())),
+        }
+
+        house.stop_all().await;
+    }
+
+    #[tokio::test]
+    async fn instantiate_is_atomic() {
+        let template = RoomTemplate::new().socket_controller(
+            "heater",
+            AddressPattern::fixed(addr("127.0.0.1:65534")).with_port_step(1),
+            1500.0,
+        );
+        let mut house = SmartHouse::default();
+        house.add_room("room1", Room::new());
+
+        assert!(matches!(
+            house.instantiate(&template, ["room0", "room1"]),
+            Err(SmartHouseError::RoomExists(name)) if name == "room1"
+        ));
+        assert!(matches!(
+            house.instantiate(&template, ["a", "a"]),
+            Err(SmartHouseError::RoomExists(_))
+        ));
+        assert!(matches!(
+            house.instantiate(&template, ["a", "b", "c"]),
+            Err(SmartHouseError::AddressOutOfRange(room, key)) if room == "c" && key == "heater"
+        ));
+        assert_eq!(house.rooms_keys(), vec!["room1"]);
+    }
+}
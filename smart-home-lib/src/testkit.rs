@@ -0,0 +1,475 @@
+//! Сценарии интеграционных тестов поверх парка эмуляторов
+//!
+//! Сценарий читается как описание поведения: какие эмуляторы подняты,
+//! что происходит и чего ожидаем.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), smart_home_lib::testkit::ScenarioError> {
+//! use smart_home_lib::emulators::FleetSpec;
+//! use smart_home_lib::testkit::{Action, Expectation, Given};
+//!
+//! Given::emulators(FleetSpec::new().socket("kitchen", "kettle", 2000.0))
+//!     .when(Action::turn_on("kitchen/kettle"))
+//!     .expect(Expectation::socket_on("kitchen/kettle"))
+//!     .run()
+//!     .await
+//! # }
+//! ```
+//!
+//! Ожидание проверяется повторно, пока не выполнится или не истечет его
+//! таймаут. Парк эмуляторов останавливается в конце сценария и при ошибке.
+
+use crate::audit::CommandOrigin;
+use crate::controllers::{DeviceCommand, DeviceController};
+use crate::emulators::{EmulationScenario, Fleet, FleetSpec};
+use crate::path::DevicePath;
+use std::fmt;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::{Instant, sleep};
+
+/// Таймаут ожидания по умолчанию
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Пауза между проверками ожидания
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Источник команд сценария в журнале дома
+const ORIGIN: &str = "testkit";
+
+/// Ошибки выполнения сценария
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    #[error("не удалось запустить эмуляторы: {0}")]
+    Start(#[from] std::io::Error),
+
+    #[error("шаг {step}: некорректный путь '{path}'")]
+    InvalidPath { step: usize, path: String },
+
+    #[error("шаг {step}: {action} не выполнено: {reason}")]
+    Action {
+        step: usize,
+        action: String,
+        reason: String,
+    },
+
+    #[error("шаг {step}: за {timeout:?} не выполнено ожидание '{expectation}' (последнее: {last})")]
+    Timeout {
+        step: usize,
+        expectation: String,
+        timeout: Duration,
+        last: String,
+    },
+}
+
+type CustomAction = Box<dyn FnOnce(&mut Fleet) -> Result<(), String> + Send>;
+type CustomCheck = Box<dyn Fn(&Fleet) -> bool + Send>;
+
+/// Действие сценария
+pub enum Action {
+    /// Команда устройству через контроллер дома
+    Command {
+        path: String,
+        command: DeviceCommand,
+    },
+    /// Смена сценария эмулятора термометра
+    ThermScenario {
+        path: String,
+        scenario: EmulationScenario,
+    },
+    /// Пауза
+    Wait(Duration),
+    /// Произвольное действие с парком (неисправности, прямое изменение дома)
+    Custom {
+        description: String,
+        action: CustomAction,
+    },
+}
+
+impl Action {
+    /// Команда устройству `room/key`
+    pub fn command(path: &str, command: DeviceCommand) -> Self {
+        Self::Command {
+            path: path.to_string(),
+            command,
+        }
+    }
+
+    /// Включить розетку
+    pub fn turn_on(path: &str) -> Self {
+        Self::command(path, DeviceCommand::TurnOn)
+    }
+
+    /// Выключить розетку
+    pub fn turn_off(path: &str) -> Self {
+        Self::command(path, DeviceCommand::TurnOff)
+    }
+
+    /// Переключить эмулятор термометра на сценарий
+    pub fn therm_scenario(path: &str, scenario: EmulationScenario) -> Self {
+        Self::ThermScenario {
+            path: path.to_string(),
+            scenario,
+        }
+    }
+
+    /// Подождать
+    pub fn wait(duration: Duration) -> Self {
+        Self::Wait(duration)
+    }
+
+    /// Произвольное действие; ошибка завершает сценарий
+    pub fn custom(
+        description: &str,
+        action: impl FnOnce(&mut Fleet) -> Result<(), String> + Send + 'static,
+    ) -> Self {
+        Self::Custom {
+            description: description.to_string(),
+            action: Box::new(action),
+        }
+    }
+
+    async fn run(self, fleet: &mut Fleet, step: usize) -> Result<(), ScenarioError> {
+        let description = self.to_string();
+        let failed = |reason: String| ScenarioError::Action {
+            step,
+            action: description.clone(),
+            reason,
+        };
+
+        match self {
+            Self::Command { path, command } => {
+                let path = parse(&path, step)?;
+                let origin = CommandOrigin::System(ORIGIN.to_string());
+                fleet
+                    .house_mut()
+                    .execute(&path, command, origin)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| failed(e.to_string()))
+            }
+            Self::ThermScenario { path, scenario } => {
+                let path = parse(&path, step)?;
+                let emulator = fleet
+                    .therm_emulator_mut(path.room(), path.key())
+                    .ok_or_else(|| failed("эмулятор термометра не найден".to_string()))?;
+                emulator.set_scenario(scenario);
+                Ok(())
+            }
+            Self::Wait(duration) => {
+                sleep(duration).await;
+                Ok(())
+            }
+            Self::Custom { action, .. } => action(fleet).map_err(failed),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Command { path, command } => write!(f, "{} {}", path, command),
+            Self::ThermScenario { path, scenario } => {
+                write!(f, "{}: сценарий {}", path, scenario.name())
+            }
+            Self::Wait(duration) => write!(f, "пауза {:?}", duration),
+            Self::Custom { description, .. } => write!(f, "{}", description),
+        }
+    }
+}
+
+/// Проверка ожидания
+enum Check {
+    SocketActive(bool),
+    PowerAtLeast(f64),
+    TemperatureBetween(f64, f64),
+    Custom(CustomCheck),
+}
+
+/// Ожидаемое состояние устройства
+pub struct Expectation {
+    description: String,
+    path: String,
+    check: Check,
+    timeout: Option<Duration>,
+}
+
+impl Expectation {
+    fn new(path: &str, description: String, check: Check) -> Self {
+        Self {
+            description,
+            path: path.to_string(),
+            check,
+            timeout: None,
+        }
+    }
+
+    /// Розетка включена
+    pub fn socket_on(path: &str) -> Self {
+        Self::new(path, format!("{} включена", path), Check::SocketActive(true))
+    }
+
+    /// Розетка выключена
+    pub fn socket_off(path: &str) -> Self {
+        Self::new(path, format!("{} выключена", path), Check::SocketActive(false))
+    }
+
+    /// Мощность розетки не меньше `watts`
+    pub fn power_at_least(path: &str, watts: f64) -> Self {
+        Self::new(
+            path,
+            format!("{}: мощность >= {} W", path, watts),
+            Check::PowerAtLeast(watts),
+        )
+    }
+
+    /// Температура термометра в пределах `min..=max`
+    pub fn temperature_between(path: &str, min: f64, max: f64) -> Self {
+        Self::new(
+            path,
+            format!("{}: температура {}..={} °C", path, min, max),
+            Check::TemperatureBetween(min, max),
+        )
+    }
+
+    /// Произвольное условие над парком и домом
+    pub fn that(description: &str, check: impl Fn(&Fleet) -> bool + Send + 'static) -> Self {
+        Self {
+            description: description.to_string(),
+            path: String::new(),
+            check: Check::Custom(Box::new(check)),
+            timeout: None,
+        }
+    }
+
+    /// Builder: таймаут этого ожидания вместо таймаута сценария
+    pub fn within(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Проверяет ожидание до успеха или таймаута
+    async fn verify(
+        &self,
+        fleet: &mut Fleet,
+        step: usize,
+        default_timeout: Duration,
+    ) -> Result<(), ScenarioError> {
+        let timeout = self.timeout.unwrap_or(default_timeout);
+        let deadline = Instant::now() + timeout;
+        loop {
+            let last = match self.observe(fleet, step).await? {
+                Ok(()) => return Ok(()),
+                Err(observed) => observed,
+            };
+            if Instant::now() >= deadline {
+                return Err(ScenarioError::Timeout {
+                    step,
+                    expectation: self.description.clone(),
+                    timeout,
+                    last,
+                });
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Одна проверка: `Ok(Err(..))` - условие пока не выполнено, с описанием состояния
+    async fn observe(
+        &self,
+        fleet: &mut Fleet,
+        step: usize,
+    ) -> Result<Result<(), String>, ScenarioError> {
+        if let Check::Custom(check) = &self.check {
+            return Ok(if check(fleet) {
+                Ok(())
+            } else {
+                Err("условие не выполнено".to_string())
+            });
+        }
+
+        let path = parse(&self.path, step)?;
+        let controller = match fleet.house_mut().controller_at_mut(&path) {
+            Ok(controller) => controller,
+            Err(e) => return Ok(Err(e.to_string())),
+        };
+
+        let observed = match (&self.check, controller) {
+            (Check::SocketActive(expected), DeviceController::Socket(socket)) => {
+                match socket.power().await.and_then(|_| socket.device()) {
+                    Ok(device) if device.is_active() == *expected => Ok(()),
+                    Ok(device) => Err(if device.is_active() { "включена" } else { "выключена" }
+                        .to_string()),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+            (Check::PowerAtLeast(watts), DeviceController::Socket(socket)) => {
+                match socket.power().await {
+                    Ok(power) if power.value() >= *watts => Ok(()),
+                    Ok(power) => Err(power.to_string()),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+            (Check::TemperatureBetween(min, max), DeviceController::Therm(therm)) => {
+                match therm.temperature() {
+                    Ok(t) if (*min..=*max).contains(&t.value.value()) => Ok(()),
+                    Ok(t) => Err(t.value.to_string()),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+            (_, controller) => Err(format!("неподходящее устройство: {}", controller.kind())),
+        };
+        Ok(observed)
+    }
+}
+
+impl fmt::Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+/// Шаг сценария
+enum Step {
+    When(Action),
+    Expect(Expectation),
+}
+
+/// Начало сценария
+pub struct Given;
+
+impl Given {
+    /// Сценарий над парком эмуляторов и подключенным к нему домом
+    pub fn emulators(spec: FleetSpec) -> Scenario {
+        Scenario {
+            spec,
+            steps: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// Сценарий: парк эмуляторов и шаги, выполняемые по порядку
+pub struct Scenario {
+    spec: FleetSpec,
+    steps: Vec<Step>,
+    timeout: Duration,
+}
+
+impl Scenario {
+    /// Builder: действие
+    pub fn when(mut self, action: Action) -> Self {
+        self.steps.push(Step::When(action));
+        self
+    }
+
+    /// Builder: ожидание, проверяемое после предыдущих действий
+    pub fn expect(mut self, expectation: Expectation) -> Self {
+        self.steps.push(Step::Expect(expectation));
+        self
+    }
+
+    /// Builder: таймаут ожиданий по умолчанию
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Количество шагов
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Проверяет, есть ли в сценарии шаги
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Запускает эмуляторы, выполняет шаги и останавливает парк
+    ///
+    /// Шаги нумеруются с единицы; первая ошибка прерывает сценарий.
+    pub async fn run(self) -> Result<(), ScenarioError> {
+        let mut fleet = Fleet::start(self.spec).await?;
+
+        let mut result = Ok(());
+        for (index, step) in self.steps.into_iter().enumerate() {
+            let step_no = index + 1;
+            result = match step {
+                Step::When(action) => action.run(&mut fleet, step_no).await,
+                Step::Expect(expectation) => {
+                    expectation.verify(&mut fleet, step_no, self.timeout).await
+                }
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+
+        fleet.shutdown().await;
+        result
+    }
+}
+
+fn parse(path: &str, step: usize) -> Result<DevicePath, ScenarioError> {
+    path.parse().map_err(|_| ScenarioError::InvalidPath {
+        step,
+        path: path.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kitchen() -> FleetSpec {
+        FleetSpec::new()
+            .socket("kitchen", "kettle", 2000.0)
+            .therm("kitchen", "therm", 22.0, EmulationScenario::Normal)
+            .with_update_interval(Duration::from_millis(10))
+            .with_seed(7)
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with emulators over TCP and UDP"]
+    async fn socket_and_therm_scenario() {
+        Given::emulators(kitchen())
+            .when(Action::turn_on("kitchen/kettle"))
+            .expect(Expectation::socket_on("kitchen/kettle"))
+            .expect(Expectation::power_at_least("kitchen/kettle", 1.0))
+            .expect(Expectation::temperature_between("kitchen/therm", 15.0, 30.0))
+            .when(Action::turn_off("kitchen/kettle"))
+            .expect(Expectation::socket_off("kitchen/kettle"))
+            .expect(Expectation::that("в доме одна комната", |fleet| {
+                fleet.house().rooms_keys().len() == 1
+            }))
+            .run()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with emulators over TCP and UDP"]
+    async fn failures_report_step() {
+        let error = Given::emulators(kitchen())
+            .expect(Expectation::socket_on("kitchen/kettle").within(Duration::from_millis(50)))
+            .run()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ScenarioError::Timeout { step: 1, .. }));
+
+        let error = Given::emulators(kitchen())
+            .when(Action::turn_on("kettle"))
+            .run()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ScenarioError::InvalidPath { step: 1, .. }));
+
+        let error = Given::emulators(kitchen())
+            .when(Action::wait(Duration::from_millis(1)))
+            .when(Action::turn_on("kitchen/missing"))
+            .run()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ScenarioError::Action { step: 2, .. }));
+    }
+}
@@ -1,7 +1,57 @@
 //! Общие трейты, используемые в библиотеке
 
+use crate::controllers::HealthStatus;
+use crate::report::ReportOptions;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
 /// Трейт для типов, которые могут формировать отчет о состоянии
 pub trait Reporter {
     /// Формирует отчет о состоянии объекта
     fn report(&self) -> String;
+
+    /// Формирует отчет с заданными единицами и точностью
+    ///
+    /// По умолчанию настройки не учитываются.
+    fn report_with(&self, _options: &ReportOptions) -> String {
+        self.report()
+    }
+}
+
+/// Отчет с предварительным опросом устройств
+///
+/// В отличие от `Reporter`, который показывает последнее известное
+/// состояние, перед формированием отчета устройства опрашиваются заново.
+pub trait AsyncReporter: Reporter {
+    /// Опрашивает устройства (каждое не дольше `timeout`) и формирует отчет
+    ///
+    /// Устройство, не ответившее вовремя, попадает в отчет с последним
+    /// известным состоянием и пометкой `[stale: ...]`.
+    fn live_report(&mut self, timeout: Duration) -> impl Future<Output = String> + Send;
+}
+
+/// Запуск и остановка фоновой работы контроллера или эмулятора
+///
+/// Позволяет запускать и останавливать разнородные объекты одинаково
+/// (`SmartHouse::start_controllers`). Повторный `start` запущенного объекта ничего
+/// не делает, `stop` остановленного - тоже.
+pub trait Lifecycle {
+    /// Запускает фоновую работу (прием данных, сервер, соединение)
+    fn start(&mut self) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Останавливает фоновую работу
+    ///
+    /// Ошибка означает, что работа завершилась аварийно (например, поток
+    /// приема упал); объект при этом все равно остановлен.
+    fn stop(&mut self) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Состояние объекта: работает ли он и какие ошибки были
+    fn health(&self) -> HealthStatus;
+}
+
+/// Отчет устройства, которое не удалось опросить
+#[cfg_attr(not(feature = "net"), allow(dead_code))]
+pub(crate) fn stale_report(report: String, error: impl fmt::Display) -> String {
+    format!("{} [stale: {}]", report, error)
 }
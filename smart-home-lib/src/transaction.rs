@@ -0,0 +1,408 @@
+//! Транзакционное изменение состава дома
+//!
+//! `SmartHouse::transaction` собирает операции (добавить комнату, перенести
+//! устройство, заменить группу...) и применяет их только если все они
+//! допустимы: операции сначала проверяются по модели ключей дома, которая
+//! меняется так же, как дом. При ошибке дом не меняется. Вместо событий
+//! отдельных операций в шину публикуется одно `HouseEvent::TopologyChanged`,
+//! и подписчики (например, при перезагрузке конфигурации) видят уже
+//! согласованный дом.
+
+use crate::events::{HouseEvent, TopologyChange};
+use crate::group::DeviceGroup;
+use crate::house::{SmartHouse, SmartHouseError, SmartHouseResult};
+use crate::room::Room;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+
+/// Операция транзакции
+enum Operation {
+    AddRoom(String, Room),
+    RemoveRoom(String),
+    RenameRoom(String, String),
+    RenameItem {
+        room: String,
+        old: String,
+        new: String,
+    },
+    Move {
+        from_room: String,
+        key: String,
+        to_room: String,
+        controller: bool,
+    },
+    AddGroup(String, DeviceGroup),
+    RemoveGroup(String),
+}
+
+/// Набор операций, применяемых к дому вместе
+#[derive(Default)]
+pub struct Transaction {
+    operations: Vec<Operation>,
+}
+
+impl Transaction {
+    /// Добавляет комнату; ключ не должен быть занят
+    pub fn add_room(&mut self, key: &str, room: Room) -> &mut Self {
+        self.push(Operation::AddRoom(key.to_string(), room))
+    }
+
+    /// Удаляет комнату
+    pub fn remove_room(&mut self, key: &str) -> &mut Self {
+        self.push(Operation::RemoveRoom(key.to_string()))
+    }
+
+    /// Переименовывает комнату (как `SmartHouse::rename_room`)
+    pub fn rename_room(&mut self, old: &str, new: &str) -> &mut Self {
+        self.push(Operation::RenameRoom(old.to_string(), new.to_string()))
+    }
+
+    /// Переименовывает элемент комнаты (как `SmartHouse::rename_item`)
+    pub fn rename_item(&mut self, room: &str, old: &str, new: &str) -> &mut Self {
+        self.push(Operation::RenameItem {
+            room: room.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        })
+    }
+
+    /// Переносит локальное устройство (как `SmartHouse::move_device`)
+    pub fn move_device(&mut self, from_room: &str, key: &str, to_room: &str) -> &mut Self {
+        self.push_move(from_room, key, to_room, false)
+    }
+
+    /// Переносит контроллер (как `SmartHouse::move_controller`)
+    pub fn move_controller(&mut self, from_room: &str, key: &str, to_room: &str) -> &mut Self {
+        self.push_move(from_room, key, to_room, true)
+    }
+
+    /// Добавляет группу; имя не должно быть занято, участники должны существовать
+    pub fn add_group(&mut self, name: &str, group: DeviceGroup) -> &mut Self {
+        self.push(Operation::AddGroup(name.to_string(), group))
+    }
+
+    /// Удаляет группу
+    pub fn remove_group(&mut self, name: &str) -> &mut Self {
+        self.push(Operation::RemoveGroup(name.to_string()))
+    }
+
+    /// Количество операций
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Транзакция без операций
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    fn push(&mut self, operation: Operation) -> &mut Self {
+        self.operations.push(operation);
+        self
+    }
+
+    fn push_move(
+        &mut self,
+        from_room: &str,
+        key: &str,
+        to_room: &str,
+        controller: bool,
+    ) -> &mut Self {
+        self.push(Operation::Move {
+            from_room: from_room.to_string(),
+            key: key.to_string(),
+            to_room: to_room.to_string(),
+            controller,
+        })
+    }
+}
+
+/// Ключи дома: элементы комнат (`true` - контроллер) и имена групп
+struct Layout {
+    rooms: IndexMap<String, HashMap<String, bool>>,
+    groups: HashSet<String>,
+}
+
+impl Layout {
+    fn of(house: &SmartHouse) -> Self {
+        Self {
+            rooms: house
+                .rooms_keys()
+                .into_iter()
+                .filter_map(|key| {
+                    let room = house.room(&key)?;
+                    Some((key, items(room)))
+                })
+                .collect(),
+            groups: house.groups_keys().into_iter().collect(),
+        }
+    }
+
+    /// Проверяет операцию и применяет ее к модели
+    fn apply(&mut self, operation: &Operation) -> SmartHouseResult<()> {
+        match operation {
+            Operation::AddRoom(key, room) => {
+                if self.rooms.contains_key(key) {
+                    return Err(SmartHouseError::RoomExists(key.clone()));
+                }
+                self.rooms.insert(key.clone(), items(room));
+            }
+            Operation::RemoveRoom(key) => {
+                self.rooms
+                    .shift_remove(key)
+                    .ok_or(SmartHouseError::RoomNotFound(key.clone()))?;
+            }
+            Operation::RenameRoom(old, new) => {
+                self.room(old)?;
+                if old != new {
+                    if self.rooms.contains_key(new) {
+                        return Err(SmartHouseError::RoomExists(new.clone()));
+                    }
+                    let items = self.rooms.shift_remove(old).unwrap_or_default();
+                    self.rooms.insert(new.clone(), items);
+                }
+            }
+            Operation::RenameItem { room, old, new } => {
+                let items = self.room(room)?;
+                let controller = *items
+                    .get(old)
+                    .ok_or(SmartHouseError::DeviceNotFound(room.clone(), old.clone()))?;
+                if old != new {
+                    if items.contains_key(new) {
+                        return Err(SmartHouseError::KeyExists(room.clone(), new.clone()));
+                    }
+                    items.remove(old);
+                    items.insert(new.clone(), controller);
+                }
+            }
+            Operation::Move {
+                from_room,
+                key,
+                to_room,
+                controller,
+            } => {
+                self.room(to_room)?;
+                let source = self.room(from_room)?;
+                if source.get(key) != Some(controller) {
+                    return Err(SmartHouseError::DeviceNotFound(
+                        from_room.clone(),
+                        key.clone(),
+                    ));
+                }
+                if from_room != to_room {
+                    let target = self.room(to_room)?;
+                    if target.contains_key(key) {
+                        return Err(SmartHouseError::KeyExists(to_room.clone(), key.clone()));
+                    }
+                    target.insert(key.clone(), *controller);
+                    self.room(from_room)?.remove(key);
+                }
+            }
+            Operation::AddGroup(name, group) => {
+                if self.groups.contains(name) {
+                    return Err(SmartHouseError::GroupExists(name.clone()));
+                }
+                if let Some((room, key)) = group.members().find(|(room, key)| {
+                    !self
+                        .rooms
+                        .get(*room)
+                        .is_some_and(|items| items.contains_key(*key))
+                }) {
+                    return Err(SmartHouseError::DeviceNotFound(
+                        room.to_string(),
+                        key.to_string(),
+                    ));
+                }
+                self.groups.insert(name.clone());
+            }
+            Operation::RemoveGroup(name) => {
+                if !self.groups.remove(name) {
+                    return Err(SmartHouseError::GroupNotFound(name.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn room(&mut self, key: &str) -> SmartHouseResult<&mut HashMap<String, bool>> {
+        self.rooms
+            .get_mut(key)
+            .ok_or(SmartHouseError::RoomNotFound(key.to_string()))
+    }
+}
+
+fn items(room: &Room) -> HashMap<String, bool> {
+    let devices = room.devices_keys().into_iter().map(|key| (key, false));
+    let controllers = room.controllers_keys().into_iter().map(|key| (key, true));
+    devices.chain(controllers).collect()
+}
+
+impl SmartHouse {
+    /// Применяет операции `build` атомарно
+    ///
+    /// Все операции проверяются до изменения дома; первая недопустимая
+    /// возвращается как `SmartHouseError::Transaction` с номером операции
+    /// (с 0), дом при этом не меняется. После успешного применения в шину
+    /// публикуется одно событие `HouseEvent::TopologyChanged` со всеми
+    /// изменениями (пустая транзакция событий не публикует).
+    pub fn transaction<F>(&mut self, build: F) -> SmartHouseResult<()>
+    where
+        F: FnOnce(&mut Transaction),
+    {
+        let mut transaction = Transaction::default();
+        build(&mut transaction);
+
+        let mut layout = Layout::of(self);
+        for (index, operation) in transaction.operations.iter().enumerate() {
+            layout
+                .apply(operation)
+                .map_err(|e| SmartHouseError::Transaction {
+                    index,
+                    source: Box::new(e),
+                })?;
+        }
+
+        let mut changes = Vec::with_capacity(transaction.len());
+        for operation in transaction.operations {
+            changes.push(self.apply_checked(operation)?);
+        }
+        if !changes.is_empty() {
+            self.events()
+                .publish(HouseEvent::TopologyChanged { changes });
+        }
+        Ok(())
+    }
+
+    /// Применяет проверенную операцию без событий
+    fn apply_checked(&mut self, operation: Operation) -> SmartHouseResult<TopologyChange> {
+        Ok(match operation {
+            Operation::AddRoom(room, value) => {
+                self.add_room_silently(&room, value);
+                TopologyChange::RoomAdded { room }
+            }
+            Operation::RemoveRoom(room) => {
+                self.remove_room_silently(&room);
+                TopologyChange::RoomRemoved { room }
+            }
+            Operation::RenameRoom(from, to) => {
+                self.rename_room_silently(&from, &to)?;
+                TopologyChange::RoomRenamed { from, to }
+            }
+            Operation::RenameItem { room, old, new } => {
+                self.rename_item_silently(&room, &old, &new)?;
+                TopologyChange::ItemRenamed {
+                    room,
+                    from: old,
+                    to: new,
+                }
+            }
+            Operation::Move {
+                from_room,
+                key,
+                to_room,
+                ..
+            } => {
+                self.relocate_item(&from_room, &key, &to_room);
+                TopologyChange::ItemMoved {
+                    key,
+                    from_room,
+                    to_room,
+                }
+            }
+            Operation::AddGroup(group, value) => {
+                self.add_group(&group, value);
+                TopologyChange::GroupAdded { group }
+            }
+            Operation::RemoveGroup(group) => {
+                self.remove_group(&group);
+                TopologyChange::GroupRemoved { group }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{Device, SmartSocket, SmartTherm};
+
+    fn house() -> SmartHouse {
+        let mut house = crate::house![(
+            "kitchen",
+            crate::room![
+                ("kettle", Device::Socket(SmartSocket::new(2000.0))),
+                ("therm", Device::Therm(SmartTherm::new(21.0)))
+            ]
+        )];
+        house.add_group("heat", DeviceGroup::new().with_member("kitchen", "kettle"));
+        house
+    }
+
+    #[test]
+    fn applies_all_with_single_event() {
+        let mut house = house();
+        let mut events = house.events().subscribe();
+
+        house
+            .transaction(|tx| {
+                tx.add_room("hall", Room::new())
+                    .move_device("kitchen", "kettle", "hall")
+                    .rename_item("hall", "kettle", "boiler")
+                    .remove_group("heat")
+                    .add_group("heat", DeviceGroup::new().with_member("hall", "boiler"));
+            })
+            .unwrap();
+
+        assert!(house.device("hall", "boiler").is_ok());
+        assert!(house.device("kitchen", "kettle").is_err());
+        assert!(house.group("heat").unwrap().contains("hall", "boiler"));
+
+        let HouseEvent::TopologyChanged { changes } = events.try_recv().unwrap() else {
+            panic!("expected topology event");
+        };
+        assert_eq!(changes.len(), 5);
+        assert_eq!(
+            changes[1],
+            TopologyChange::ItemMoved {
+                key: "kettle".to_string(),
+                from_room: "kitchen".to_string(),
+                to_room: "hall".to_string(),
+            }
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn invalid_step_leaves_house_unchanged() {
+        let mut house = house();
+        let mut events = house.events().subscribe();
+
+        let error = house
+            .transaction(|tx| {
+                tx.add_room("hall", Room::new())
+                    .move_device("kitchen", "therm", "hall")
+                    // после переноса термометра в кухне его уже нет
+                    .rename_item("kitchen", "therm", "t1");
+            })
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SmartHouseError::Transaction { index: 2, ref source }
+                if matches!(**source, SmartHouseError::DeviceNotFound(_, _))
+        ));
+        assert!(house.room("hall").is_none());
+        assert!(house.device("kitchen", "therm").is_ok());
+        assert!(events.try_recv().is_err());
+
+        let error = house
+            .transaction(|tx| {
+                tx.add_group("bad", DeviceGroup::new().with_member("garage", "lamp"));
+            })
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SmartHouseError::Transaction { index: 0, .. }
+        ));
+        assert!(house.group("bad").is_none());
+    }
+}
@@ -1,7 +1,13 @@
 //! Модуль для физических единиц измерения.
 
 mod celsius;
+mod lux;
+mod pascal;
+mod percent;
 mod watts;
 
 pub use celsius::Celsius;
+pub use lux::Lux;
+pub use pascal::Pascal;
+pub use percent::Percent;
 pub use watts::Watts;
@@ -22,6 +22,11 @@ impl Celsius {
     pub fn value(&self) -> f64 {
         self.0
     }
+
+    /// Температура в градусах Фаренгейта
+    pub fn fahrenheit(&self) -> f64 {
+        self.0 * 9.0 / 5.0 + 32.0
+    }
 }
 
 impl fmt::Display for Celsius {
@@ -0,0 +1,83 @@
+//! Освещенность в люксах
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Lux(f64);
+
+impl Lux {
+    pub fn new(value: f64) -> Self {
+        if value < 0.0 {
+            panic!("Illuminance must be positive");
+        }
+        Lux(value)
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Lux {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0} lx", self.0)
+    }
+}
+
+impl Add for Lux {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Lux(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Lux {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        let result = self.0 - rhs.0;
+        Lux(if result < 0.0 { 0.0 } else { result })
+    }
+}
+
+impl Mul<f64> for Lux {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Lux(self.0 * rhs)
+    }
+}
+
+impl Div<f64> for Lux {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self::Output {
+        if rhs == 0.0 {
+            panic!("Division by zero");
+        }
+
+        Lux(self.0 / rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lux_operations() {
+        let l1 = Lux::new(400.0);
+        let l2 = Lux::new(150.0);
+
+        assert_eq!(l1 + l2, Lux::new(550.0));
+        assert_eq!(l2 - l1, Lux::new(0.0));
+        assert_eq!(l1 * 2.0, Lux::new(800.0));
+        assert_eq!(l1 / 4.0, Lux::new(100.0));
+        assert_eq!(format!("{}", Lux::new(320.4)), "320 lx");
+    }
+
+    #[test]
+    #[should_panic(expected = "Illuminance must be positive")]
+    fn lux_negative_value() {
+        Lux::new(-1.0);
+    }
+}
@@ -0,0 +1,92 @@
+//! Давление в паскалях
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Pascal(f64);
+
+impl Pascal {
+    pub fn new(value: f64) -> Self {
+        if value < 0.0 {
+            panic!("Pressure must be positive");
+        }
+        Pascal(value)
+    }
+
+    /// Давление в гектопаскалях (как в метеосводках)
+    pub fn from_hectopascals(value: f64) -> Self {
+        Self::new(value * 100.0)
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
+    pub fn hectopascals(&self) -> f64 {
+        self.0 / 100.0
+    }
+}
+
+impl fmt::Display for Pascal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0} Pa", self.0)
+    }
+}
+
+impl Add for Pascal {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Pascal(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Pascal {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        let result = self.0 - rhs.0;
+        Pascal(if result < 0.0 { 0.0 } else { result })
+    }
+}
+
+impl Mul<f64> for Pascal {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Pascal(self.0 * rhs)
+    }
+}
+
+impl Div<f64> for Pascal {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self::Output {
+        if rhs == 0.0 {
+            panic!("Division by zero");
+        }
+
+        Pascal(self.0 / rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pascal_operations() {
+        let p = Pascal::from_hectopascals(1013.25);
+        assert_eq!(p.value(), 101325.0);
+        assert_eq!(p.hectopascals(), 1013.25);
+
+        assert_eq!(Pascal::new(100.0) + Pascal::new(50.0), Pascal::new(150.0));
+        assert_eq!(Pascal::new(50.0) - Pascal::new(100.0), Pascal::new(0.0));
+        assert_eq!(Pascal::new(100.0) / 2.0, Pascal::new(50.0));
+        assert_eq!(format!("{}", p), "101325 Pa");
+    }
+
+    #[test]
+    #[should_panic(expected = "Pressure must be positive")]
+    fn pascal_negative_value() {
+        Pascal::new(-1.0);
+    }
+}
@@ -0,0 +1,121 @@
+//! Проценты (0-100), например относительная влажность
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Percent(f64);
+
+impl Percent {
+    pub fn new(value: f64) -> Self {
+        match Self::try_from(value) {
+            Ok(percent) => percent,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Значение, ограниченное диапазоном 0-100
+    pub fn clamped(value: f64) -> Self {
+        Percent(if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 100.0)
+        })
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
+    /// Доля от целого, 0.0-1.0
+    pub fn fraction(&self) -> f64 {
+        self.0 / 100.0
+    }
+}
+
+impl TryFrom<f64> for Percent {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if !(0.0..=100.0).contains(&value) {
+            return Err(format!("Percent must be within 0..=100, got {}", value));
+        }
+        Ok(Percent(value))
+    }
+}
+
+impl From<Percent> for f64 {
+    fn from(percent: Percent) -> Self {
+        percent.0
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}%", self.0)
+    }
+}
+
+impl Add for Percent {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Percent::clamped(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Percent {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Percent::clamped(self.0 - rhs.0)
+    }
+}
+
+impl Mul<f64> for Percent {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Percent::clamped(self.0 * rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_creation() {
+        assert_eq!(Percent::new(45.0).value(), 45.0);
+        assert_eq!(Percent::new(45.0).fraction(), 0.45);
+        assert!(Percent::try_from(100.5).is_err());
+        assert!(Percent::try_from(f64::NAN).is_err());
+        assert_eq!(Percent::clamped(120.0), Percent::new(100.0));
+    }
+
+    #[test]
+    fn percent_operations() {
+        let p1 = Percent::new(70.0);
+        let p2 = Percent::new(40.0);
+
+        assert_eq!(p1 + p2, Percent::new(100.0));
+        assert_eq!(p2 - p1, Percent::new(0.0));
+        assert_eq!(p2 * 0.5, Percent::new(20.0));
+    }
+
+    #[test]
+    fn percent_display_and_serde() {
+        assert_eq!(format!("{}", Percent::new(55.55)), "55.5%");
+        assert_eq!(serde_json::to_string(&Percent::new(42.0)).unwrap(), "42.0");
+        assert_eq!(
+            serde_json::from_str::<Percent>("42").unwrap(),
+            Percent::new(42.0)
+        );
+        assert!(serde_json::from_str::<Percent>("142").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Percent must be within 0..=100")]
+    fn percent_out_of_range() {
+        Percent::new(-1.0);
+    }
+}
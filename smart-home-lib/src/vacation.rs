@@ -0,0 +1,295 @@
+//! Имитация присутствия (режим отпуска)
+//!
+//! Пока жильцов нет дома, `VacationMode` повторяет включения и выключения
+//! розеток и ламп по истории показаний: состояние устройства берется из
+//! мощности в тот же момент `replay_offset` назад (по умолчанию неделю
+//! назад). Момент выборки сдвигается на случайную величину в пределах
+//! `jitter`, поэтому переключения не совпадают с историей до минуты.
+//! Команды выполняет собственная сверка состояния (`Reconciler`).
+
+use crate::clock::{SharedClock, system_clock};
+use crate::events::EventBus;
+use crate::history::{History, ReadingKind};
+use crate::house::SmartHouse;
+use crate::reconciler::{DesiredState, ReconcileReport, Reconciler};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{MissedTickBehavior, interval};
+
+/// Насколько далеко в прошлое смотрит имитация по умолчанию
+pub const DEFAULT_REPLAY_OFFSET: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Случайный сдвиг переключений по умолчанию
+pub const DEFAULT_JITTER: Duration = Duration::from_secs(15 * 60);
+/// Мощность, выше которой устройство считалось включенным, Вт
+const DEFAULT_ON_THRESHOLD: f64 = 1.0;
+
+/// Имитация присутствия по истории показаний
+pub struct VacationMode {
+    history: History,
+    /// Устройства (комната, ключ); пусто - все розетки из истории
+    devices: Vec<(String, String)>,
+    replay_offset: Duration,
+    jitter: Duration,
+    on_threshold: f64,
+    clock: SharedClock,
+    rng: StdRng,
+    /// Текущий сдвиг выборки по устройствам, мс
+    shifts: HashMap<(String, String), i64>,
+    /// Последнее запланированное состояние по устройствам
+    planned: BTreeMap<(String, String), DesiredState>,
+    reconciler: Reconciler,
+}
+
+impl VacationMode {
+    /// Создает имитацию по истории `history`
+    pub fn new(history: History) -> Self {
+        Self {
+            history,
+            devices: Vec::new(),
+            replay_offset: DEFAULT_REPLAY_OFFSET,
+            jitter: DEFAULT_JITTER,
+            on_threshold: DEFAULT_ON_THRESHOLD,
+            clock: system_clock(),
+            rng: StdRng::from_os_rng(),
+            shifts: HashMap::new(),
+            planned: BTreeMap::new(),
+            reconciler: Reconciler::new(),
+        }
+    }
+
+    /// Builder: имитировать только указанное устройство (можно вызывать несколько раз)
+    pub fn with_device(mut self, room: &str, key: &str) -> Self {
+        self.devices.push((room.to_string(), key.to_string()));
+        self
+    }
+
+    /// Builder: насколько далеко в прошлое смотреть (обычно сутки или неделя)
+    pub fn with_replay_offset(mut self, replay_offset: Duration) -> Self {
+        self.replay_offset = replay_offset;
+        self
+    }
+
+    /// Builder: максимальный случайный сдвиг переключений
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Builder: мощность, выше которой устройство считалось включенным
+    pub fn with_on_threshold(mut self, watts: f64) -> Self {
+        self.on_threshold = watts;
+        self
+    }
+
+    /// Builder: источник времени
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Builder: зерно генератора сдвигов (для воспроизводимых тестов)
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Builder: шина, в которую публикуются события переключений
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.reconciler = self.reconciler.with_events(events);
+        self
+    }
+
+    /// Последнее запланированное состояние устройства
+    pub fn planned(&self, room: &str, key: &str) -> Option<DesiredState> {
+        self.planned
+            .get(&(room.to_string(), key.to_string()))
+            .copied()
+    }
+
+    /// Вычисляет состояния устройств на текущий момент
+    ///
+    /// Устройства без истории на момент выборки не попадают в план.
+    /// Сдвиг выборки меняется после каждого переключения устройства.
+    pub fn plan(&mut self) -> BTreeMap<(String, String), DesiredState> {
+        let replay_at = self
+            .clock
+            .now_ms()
+            .saturating_sub(self.replay_offset.as_millis() as u64);
+
+        let mut plan = BTreeMap::new();
+        for device in self.devices() {
+            let shift = match self.shifts.get(&device) {
+                Some(&shift) => shift,
+                None => self.next_shift(&device),
+            };
+            let at = replay_at.saturating_add_signed(shift);
+            let device_id = format!("{}/{}", device.0, device.1);
+            let Some(power) = self.history.value_at(&device_id, ReadingKind::Power, at) else {
+                continue;
+            };
+
+            let state = DesiredState::from_active(power > self.on_threshold);
+            if self.planned.get(&device).is_some_and(|&last| last != state) {
+                self.next_shift(&device);
+            }
+            self.planned.insert(device.clone(), state);
+            plan.insert(device, state);
+        }
+        plan
+    }
+
+    /// Один шаг имитации: план и переключение устройств дома
+    pub async fn tick(&mut self, house: &mut SmartHouse) -> ReconcileReport {
+        for ((room, key), state) in self.plan() {
+            self.reconciler.set_desired(&room, &key, state);
+        }
+        self.reconciler.reconcile(house).await
+    }
+
+    /// Периодическая имитация в общем доме; работает, пока задачу не отменят
+    pub async fn run(&mut self, house: Arc<Mutex<SmartHouse>>, period: Duration) {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            let report = self.tick(&mut *house.lock().await).await;
+            for e in &report.errors {
+                eprintln!("⚠️ Режим отпуска {}", e);
+            }
+        }
+    }
+
+    /// Имитируемые устройства
+    fn devices(&self) -> Vec<(String, String)> {
+        if !self.devices.is_empty() {
+            return self.devices.clone();
+        }
+        self.history
+            .series()
+            .into_iter()
+            .filter(|(_, kind)| *kind == ReadingKind::Power)
+            .filter_map(|(device_id, _)| {
+                let (room, key) = device_id.split_once('/')?;
+                Some((room.to_string(), key.to_string()))
+            })
+            .collect()
+    }
+
+    /// Выбирает новый случайный сдвиг выборки для устройства
+    fn next_shift(&mut self, device: &(String, String)) -> i64 {
+        let jitter = self.jitter.as_millis() as i64;
+        let shift = if jitter > 0 {
+            self.rng.random_range(-jitter..=jitter)
+        } else {
+            0
+        };
+        self.shifts.insert(device.clone(), shift);
+        shift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use crate::controllers::SocketController;
+    use crate::emulators::{Fleet, FleetSpec};
+
+    const HOUR: u64 = 3600 * 1000;
+    const DAY: u64 = 24 * HOUR;
+
+    /// Вчера лампа горела с 19:00 до 23:00, телевизор не включали
+    fn history() -> History {
+        let history = History::default();
+        history.record_at("hall/lamp", ReadingKind::Power, HOUR, 0.0);
+        history.record_at("hall/lamp", ReadingKind::Power, 19 * HOUR, 60.0);
+        history.record_at("hall/lamp", ReadingKind::Power, 23 * HOUR, 0.0);
+        history.record_at("hall/tv", ReadingKind::Power, HOUR, 0.0);
+        history.record_at("hall/therm", ReadingKind::Temperature, 0, 21.0);
+        history
+    }
+
+    fn vacation(clock: &Arc<MockClock>) -> VacationMode {
+        VacationMode::new(history())
+            .with_replay_offset(Duration::from_millis(DAY))
+            .with_clock(clock.clone())
+            .with_seed(7)
+    }
+
+    #[test]
+    fn replays_history() {
+        let clock = Arc::new(MockClock::starting_at(DAY + 20 * HOUR));
+        let mut vacation = vacation(&clock).with_jitter(Duration::ZERO);
+
+        let plan = vacation.plan();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(vacation.planned("hall", "lamp"), Some(DesiredState::On));
+        assert_eq!(vacation.planned("hall", "tv"), Some(DesiredState::Off));
+
+        clock.set(DAY + 23 * HOUR + 1);
+        vacation.plan();
+        assert_eq!(vacation.planned("hall", "lamp"), Some(DesiredState::Off));
+
+        // Раньше начала истории повторять нечего
+        clock.set(DAY / 2);
+        assert!(vacation.plan().is_empty());
+    }
+
+    #[test]
+    fn jitter_shifts_switching() {
+        let jitter = Duration::from_millis(HOUR / 2);
+        let switch_at = |seed: u64| {
+            let clock = Arc::new(MockClock::starting_at(DAY + 18 * HOUR));
+            let mut vacation = vacation(&clock).with_jitter(jitter).with_seed(seed);
+            while vacation.plan()[&("hall".to_string(), "lamp".to_string())] == DesiredState::Off {
+                clock.advance(Duration::from_secs(60));
+            }
+            clock.now_ms() - DAY
+        };
+
+        let switches: Vec<u64> = (0..8).map(switch_at).collect();
+        for &at in &switches {
+            assert!((18 * HOUR + HOUR / 2..=19 * HOUR + HOUR / 2).contains(&at));
+        }
+        assert!(switches.iter().any(|&at| at != switches[0]));
+    }
+
+    #[test]
+    fn selected_devices_only() {
+        let clock = Arc::new(MockClock::starting_at(DAY + 20 * HOUR));
+        let mut vacation = vacation(&clock).with_device("hall", "lamp");
+
+        assert_eq!(vacation.plan().len(), 1);
+        assert_eq!(vacation.planned("hall", "tv"), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "integration test with async TCP networking"]
+    async fn switches_sockets() {
+        let spec = FleetSpec::new()
+            .socket("hall", "lamp", 60.0)
+            .with_controller_timeout(Duration::from_secs(1));
+        let mut fleet = Fleet::start(spec).await.unwrap();
+        let addr = fleet.socket_addr("hall", "lamp").unwrap();
+
+        let clock = Arc::new(MockClock::starting_at(DAY + 20 * HOUR));
+        let mut vacation = vacation(&clock)
+            .with_device("hall", "lamp")
+            .with_jitter(Duration::ZERO);
+
+        let report = vacation.tick(fleet.house_mut()).await;
+        assert_eq!(report.drifts.len(), 1);
+        assert!(report.is_converged());
+
+        let mut observer = SocketController::new(addr, 60.0, Duration::from_secs(1));
+        observer.power().await.unwrap();
+        assert!(observer.device().unwrap().is_active());
+
+        fleet.shutdown().await;
+    }
+}
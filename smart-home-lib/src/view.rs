@@ -0,0 +1,210 @@
+//! Доступ к общему дому только для чтения
+//!
+//! Службы, управляющие домом (`Reconciler::run`, `VacationMode::run`),
+//! работают с общим `Arc<Mutex<SmartHouse>>`. `HouseView` дает панелям и
+//! отчетам доступ к тому же дому только для чтения: каждый запрос
+//! захватывает блокировку ненадолго и возвращает копию данных, поэтому
+//! читатель не держит дом и не может его изменить. Клоны дешевы и
+//! указывают на один и тот же дом.
+
+use crate::group::GroupStatus;
+use crate::house::{SmartHouse, SmartHouseError, SmartHouseResult};
+use crate::report::{HealthReport, HouseReport, ReportOptions};
+use crate::room::RoomSummary;
+use crate::traits::Reporter;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{MissedTickBehavior, interval};
+use tokio_stream::wrappers::IntervalStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Представление общего дома только для чтения
+#[derive(Clone)]
+pub struct HouseView {
+    house: Arc<Mutex<SmartHouse>>,
+}
+
+impl HouseView {
+    /// Представление дома, который уже используется совместно
+    pub fn new(house: Arc<Mutex<SmartHouse>>) -> Self {
+        Self { house }
+    }
+
+    /// Общий дом для задач, которые им управляют
+    pub fn shared(&self) -> Arc<Mutex<SmartHouse>> {
+        Arc::clone(&self.house)
+    }
+
+    /// Выполняет запрос к дому под блокировкой
+    ///
+    /// Замыкание получает только `&SmartHouse`; блокировка держится, пока
+    /// оно выполняется, поэтому запрос должен быть коротким.
+    pub async fn read<R>(&self, query: impl FnOnce(&SmartHouse) -> R) -> R {
+        query(&*self.house.lock().await)
+    }
+
+    /// Текстовый отчет о доме
+    pub async fn report(&self) -> String {
+        self.read(|house| house.report()).await
+    }
+
+    /// Снимок состояния дома
+    pub async fn snapshot(&self) -> HouseReport {
+        self.read(SmartHouse::snapshot).await
+    }
+
+    /// Снимок состояния дома с заданными единицами и точностью
+    pub async fn snapshot_with(&self, options: ReportOptions) -> HouseReport {
+        self.read(|house| house.snapshot_with(&options)).await
+    }
+
+    /// Состояние связи контроллеров
+    pub async fn health_report(&self) -> HealthReport {
+        self.read(SmartHouse::health_report).await
+    }
+
+    /// Ключи комнат
+    pub async fn rooms_keys(&self) -> Vec<String> {
+        self.read(SmartHouse::rooms_keys).await
+    }
+
+    /// Имена групп
+    pub async fn groups_keys(&self) -> Vec<String> {
+        self.read(SmartHouse::groups_keys).await
+    }
+
+    /// Сводка по комнате
+    pub async fn room_summary(&self, room: &str) -> SmartHouseResult<RoomSummary> {
+        self.read(|house| {
+            house
+                .room(room)
+                .map(|room| room.summary())
+                .ok_or(SmartHouseError::RoomNotFound(room.to_string()))
+        })
+        .await
+    }
+
+    /// Отчет об устройстве или контроллере
+    pub async fn item_report(&self, room: &str, key: &str) -> SmartHouseResult<String> {
+        self.read(|house| {
+            house
+                .device(room, key)
+                .map(|device| device.report())
+                .or_else(|_| house.controller(room, key).map(|c| c.report()))
+        })
+        .await
+    }
+
+    /// Сводное состояние группы
+    pub async fn group_status(&self, name: &str) -> SmartHouseResult<GroupStatus> {
+        self.read(|house| {
+            house
+                .group(name)
+                .map(|group| group.status(house))
+                .ok_or(SmartHouseError::GroupNotFound(name.to_string()))
+        })
+        .await
+    }
+
+    /// Поток отчетов, как `SmartHouse::watch_reports`
+    ///
+    /// Дом блокируется только на время снимка, между снимками им могут
+    /// пользоваться другие задачи.
+    pub fn watch_reports(
+        &self,
+        period: Duration,
+    ) -> impl Stream<Item = HouseReport> + Send + use<> {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let view = self.clone();
+        let mut last: Option<HouseReport> = None;
+        IntervalStream::new(ticker)
+            .then(move |_| {
+                let view = view.clone();
+                async move { view.snapshot().await }
+            })
+            .filter_map(move |report| {
+                if last.as_ref().is_some_and(|last| last.same_state(&report)) {
+                    return None;
+                }
+                last = Some(report.clone());
+                Some(report)
+            })
+    }
+}
+
+impl From<SmartHouse> for HouseView {
+    fn from(house: SmartHouse) -> Self {
+        Self::new(Arc::new(Mutex::new(house)))
+    }
+}
+
+impl SmartHouse {
+    /// Переводит дом в совместное использование и возвращает представление для чтения
+    pub fn into_view(self) -> HouseView {
+        HouseView::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::Device;
+    use crate::group::DeviceGroup;
+
+    fn view() -> HouseView {
+        let mut house = crate::house! {
+            "hall" => { "lamp" => socket(60.0), "therm" => therm(21.0) },
+        };
+        house.add_group("lights", DeviceGroup::new().with_member("hall", "lamp"));
+        house.into_view()
+    }
+
+    #[tokio::test]
+    async fn queries() {
+        let view = view();
+
+        assert_eq!(view.rooms_keys().await, vec!["hall"]);
+        assert_eq!(view.groups_keys().await, vec!["lights"]);
+        assert_eq!(view.room_summary("hall").await.unwrap().sockets, 1);
+        assert!(view.item_report("hall", "therm").await.is_ok());
+        assert!(matches!(
+            view.item_report("hall", "missing").await,
+            Err(SmartHouseError::DeviceNotFound(_, _))
+        ));
+        assert!(matches!(
+            view.group_status("heaters").await,
+            Err(SmartHouseError::GroupNotFound(_))
+        ));
+        assert_eq!(view.report().await, view.snapshot().await.to_string());
+    }
+
+    #[tokio::test]
+    async fn reads_while_other_task_mutates() {
+        let view = view();
+        let house = view.shared();
+
+        let control = tokio::spawn(async move {
+            for _ in 0..10 {
+                if let Ok(Device::Socket(s)) = house.lock().await.device_mut("hall", "lamp") {
+                    if s.is_active() {
+                        s.turn_off();
+                    } else {
+                        s.turn_on();
+                    }
+                }
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let dashboard = view.clone();
+        let mut reports = Box::pin(dashboard.watch_reports(Duration::from_millis(1)));
+        let first = reports.next().await.unwrap();
+        control.await.unwrap();
+
+        assert_eq!(first.room("hall").unwrap().items.len(), 2);
+        assert_eq!(view.group_status("lights").await.unwrap().members, 1);
+    }
+}